@@ -1,7 +1,9 @@
 //! Application state and logic.
 
 use crate::cheatsheets::{bundled_cheatsheets, load_user_cheatsheets, CheatSheet};
-use crate::config::Config;
+use crate::config::{Config, SnippetOutput};
+use crate::snippet::{self, extract_commands, Command};
+use crate::tldr::{default_platforms, load_tldr, pages_dir};
 use crossterm::event::{KeyCode, KeyEvent};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
@@ -16,9 +18,45 @@ pub struct App {
     pub searching: bool,
     pub scroll_offset: usize,
     pub show_help: bool,
+    pub picker: Option<CommandPicker>,
+    pub prompt: Option<PlaceholderPrompt>,
+    pub message: Option<String>,
+    /// Command to print on exit.
+    pub output: Option<String>,
+    pub quit: bool,
     matcher: SkimMatcherV2,
 }
 
+/// Choosing a command from the open sheet.
+#[derive(Debug, Clone)]
+pub struct CommandPicker {
+    pub commands: Vec<Command>,
+    pub selected: usize,
+}
+
+/// Filling in a picked command's placeholders, one at a time.
+#[derive(Debug, Clone)]
+pub struct PlaceholderPrompt {
+    pub command: Command,
+    pub names: Vec<String>,
+    /// Values entered so far, paired with `names`.
+    pub values: Vec<(String, String)>,
+    pub input: String,
+    pub output: SnippetOutput,
+}
+
+impl PlaceholderPrompt {
+    /// Placeholder being asked for.
+    pub fn current(&self) -> Option<&str> {
+        self.names.get(self.values.len()).map(String::as_str)
+    }
+
+    /// The command filled in with the values so far.
+    pub fn preview(&self) -> String {
+        self.command.fill(&self.values)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum View {
     List,
@@ -43,6 +81,19 @@ impl App {
             cheatsheets.extend(load_user_cheatsheets(&path));
         }
 
+        // Load tldr pages from the first root that has them
+        if config.sources.tldr {
+            let platforms = config
+                .sources
+                .tldr_platforms
+                .clone()
+                .unwrap_or_else(default_platforms);
+            let roots = config.tldr_roots();
+            if let Some(root) = roots.into_iter().find(|r| pages_dir(r).is_some()) {
+                cheatsheets.extend(load_tldr(&root, &platforms));
+            }
+        }
+
         // Sort by category then topic
         cheatsheets.sort_by(|a, b| (&a.category, &a.topic).cmp(&(&b.category, &b.topic)));
 
@@ -58,6 +109,11 @@ impl App {
             searching: false,
             scroll_offset: 0,
             show_help: false,
+            picker: None,
+            prompt: None,
+            message: None,
+            output: None,
+            quit: false,
             matcher: SkimMatcherV2::default(),
         }
     }
@@ -107,12 +163,29 @@ impl App {
         }
     }
 
+    /// Whether keys are going to a text input.
+    pub fn is_typing(&self) -> bool {
+        self.searching || self.prompt.is_some()
+    }
+
     pub fn handle_key(&mut self, key: KeyEvent) {
+        self.message = None;
+
         if self.show_help {
             self.show_help = false;
             return;
         }
 
+        if self.prompt.is_some() {
+            self.handle_prompt_key(key);
+            return;
+        }
+
+        if self.picker.is_some() {
+            self.handle_picker_key(key);
+            return;
+        }
+
         if self.searching {
             match key.code {
                 KeyCode::Esc => {
@@ -196,6 +269,7 @@ impl App {
             KeyCode::Char('u') => {
                 self.scroll_offset = self.scroll_offset.saturating_sub(10);
             }
+            KeyCode::Enter => self.open_picker(),
             KeyCode::Char('g') => {
                 self.scroll_offset = 0;
             }
@@ -238,4 +312,102 @@ impl App {
             _ => {}
         }
     }
+
+    fn open_picker(&mut self) {
+        let Some(sheet) = self.selected_cheatsheet() else {
+            return;
+        };
+        let commands = extract_commands(&sheet.content);
+        if commands.is_empty() {
+            self.message = Some("No commands in this sheet".to_string());
+            return;
+        }
+        self.picker = Some(CommandPicker {
+            commands,
+            selected: 0,
+        });
+    }
+
+    fn handle_picker_key(&mut self, key: KeyEvent) {
+        let Some(picker) = &mut self.picker else {
+            return;
+        };
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                picker.selected = (picker.selected + 1).min(picker.commands.len() - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                picker.selected = picker.selected.saturating_sub(1);
+            }
+            KeyCode::Enter => self.pick(self.config.snippets.output),
+            KeyCode::Char('y') => self.pick(SnippetOutput::Clipboard),
+            KeyCode::Char('p') => self.pick(SnippetOutput::Stdout),
+            KeyCode::Esc | KeyCode::Char('q') => self.picker = None,
+            _ => {}
+        }
+    }
+
+    /// Take the selected command, prompting for its placeholders if it has
+    /// any.
+    fn pick(&mut self, output: SnippetOutput) {
+        let Some(picker) = self.picker.take() else {
+            return;
+        };
+        let command = picker.commands[picker.selected].clone();
+        let names = command.placeholders();
+        if names.is_empty() {
+            self.deliver(command.command, output);
+        } else {
+            self.prompt = Some(PlaceholderPrompt {
+                command,
+                names,
+                values: Vec::new(),
+                input: String::new(),
+                output,
+            });
+        }
+    }
+
+    fn handle_prompt_key(&mut self, key: KeyEvent) {
+        let Some(prompt) = &mut self.prompt else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Enter => {
+                let Some(name) = prompt.current().map(str::to_string) else {
+                    return;
+                };
+                let value = match std::mem::take(&mut prompt.input) {
+                    input if input.is_empty() => snippet::default_value(&name),
+                    input => input,
+                };
+                prompt.values.push((name, value));
+                if prompt.current().is_none() {
+                    let prompt = self.prompt.take().unwrap();
+                    self.deliver(prompt.preview(), prompt.output);
+                }
+            }
+            KeyCode::Backspace => {
+                prompt.input.pop();
+            }
+            KeyCode::Char(c) => prompt.input.push(c),
+            _ => {}
+        }
+    }
+
+    fn deliver(&mut self, command: String, output: SnippetOutput) {
+        match output {
+            SnippetOutput::Clipboard => {
+                self.message = Some(match snippet::copy_to_clipboard(&command) {
+                    Ok(_) => format!("Copied: {}", command),
+                    Err(e) => format!("Copy failed: {}", e),
+                });
+            }
+            SnippetOutput::Stdout => {
+                self.output = Some(command);
+                self.quit = true;
+            }
+        }
+    }
 }
@@ -14,6 +14,7 @@ pub struct CheatSheet {
 pub enum Source {
     Bundled,
     User { path: PathBuf },
+    Tldr { path: PathBuf, platform: String },
 }
 
 impl Source {
@@ -21,6 +22,7 @@ impl Source {
         match self {
             Source::Bundled => "bundled",
             Source::User { .. } => "user",
+            Source::Tldr { .. } => "tldr",
         }
     }
 }
@@ -9,6 +9,8 @@ pub struct Config {
     pub sources: SourcesConfig,
     #[serde(default)]
     pub display: DisplayConfig,
+    #[serde(default)]
+    pub snippets: SnippetConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +19,32 @@ pub struct SourcesConfig {
     pub bundled: bool,
     #[serde(default)]
     pub user_path: Option<PathBuf>,
+    #[serde(default = "default_true")]
+    pub tldr: bool,
+    /// tldr-pages clone or client cache; common cache locations are tried
+    /// when unset.
+    #[serde(default)]
+    pub tldr_path: Option<PathBuf>,
+    /// Platform folders to read; `common` and the current OS when unset.
+    #[serde(default)]
+    pub tldr_platforms: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnippetConfig {
+    /// What Enter does with a picked command.
+    #[serde(default)]
+    pub output: SnippetOutput,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnippetOutput {
+    /// Copy to the clipboard and keep browsing.
+    #[default]
+    Clipboard,
+    /// Print to stdout on exit.
+    Stdout,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +64,9 @@ impl Default for SourcesConfig {
         Self {
             bundled: true,
             user_path: None,
+            tldr: true,
+            tldr_path: None,
+            tldr_platforms: None,
         }
     }
 }
@@ -54,6 +85,7 @@ impl Default for Config {
         Self {
             sources: SourcesConfig::default(),
             display: DisplayConfig::default(),
+            snippets: SnippetConfig::default(),
         }
     }
 }
@@ -71,8 +103,24 @@ impl Config {
             .map(|d| d.config_dir().join("config.toml"))
     }
 
+    /// tldr roots to try: the configured path, or common client caches.
+    pub fn tldr_roots(&self) -> Vec<PathBuf> {
+        match &self.sources.tldr_path {
+            Some(path) => vec![expand_home(path)],
+            None => crate::tldr::default_roots(),
+        }
+    }
+
     pub fn user_cheatsheets_path() -> Option<PathBuf> {
         directories::ProjectDirs::from("", "", "cheatsheet-browser")
             .map(|d| d.config_dir().join("cheatsheets"))
     }
 }
+
+/// Expand a leading `~` to the home directory.
+fn expand_home(path: &std::path::Path) -> PathBuf {
+    match (path.strip_prefix("~"), directories::BaseDirs::new()) {
+        (Ok(rest), Some(dirs)) => dirs.home_dir().join(rest),
+        _ => path.to_path_buf(),
+    }
+}
@@ -4,10 +4,13 @@
 mod app;
 mod cheatsheets;
 mod config;
+mod snippet;
+mod tldr;
 mod ui;
 
 use anyhow::Result;
 use app::App;
+use config::SnippetOutput;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
@@ -17,13 +20,22 @@ use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
 
 fn main() -> Result<()> {
+    let mut app = App::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--print" | "-p" => app.config.snippets.output = SnippetOutput::Stdout,
+            _ => anyhow::bail!("usage: cheatsheet-browser [--print]"),
+        }
+    }
+
+    // Draw on stderr so a picked command printed to stdout can be captured,
+    // e.g. `cmd=$(cheatsheet-browser --print)`
     enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+    let mut stderr = io::stderr();
+    execute!(stderr, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(stderr);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new();
     let result = run_app(&mut terminal, &mut app);
 
     disable_raw_mode()?;
@@ -33,7 +45,12 @@ fn main() -> Result<()> {
         DisableMouseCapture
     )?;
     terminal.show_cursor()?;
-    result
+    result?;
+
+    if let Some(command) = app.output {
+        println!("{}", command);
+    }
+    Ok(())
 }
 
 fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()> {
@@ -41,7 +58,7 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &mut A
         terminal.draw(|f| ui::draw(f, app))?;
 
         if let Event::Key(key) = event::read()? {
-            if key.code == KeyCode::Char('q') && !app.searching {
+            if key.code == KeyCode::Char('q') && !app.is_typing() && app.picker.is_none() {
                 if app.view == app::View::List {
                     break;
                 } else {
@@ -53,6 +70,9 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &mut A
                 break;
             }
             app.handle_key(key);
+            if app.quit {
+                break;
+            }
         }
     }
     Ok(())
@@ -0,0 +1,213 @@
+//! Executable snippets.
+//!
+//! Commands are the lines of a sheet's code blocks. A command may contain
+//! `{{placeholder}}`s, as in tldr-pages; picking it prompts for each one and
+//! the filled-in command is copied to the clipboard or printed on exit.
+
+use std::io::Write;
+use std::process::{Command as Process, Stdio};
+
+/// A command found in a cheat sheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Command {
+    pub command: String,
+    /// Trailing `# comment`, or the text introducing the code block.
+    pub description: String,
+}
+
+impl Command {
+    /// Placeholder names, in order of first use.
+    pub fn placeholders(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for (_, name) in find_placeholders(&self.command) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    /// The command with each placeholder replaced by its value from `values`
+    /// (paired with [`placeholders`](Self::placeholders)). Placeholders
+    /// without a value are left as they are.
+    pub fn fill(&self, values: &[(String, String)]) -> String {
+        let mut out = String::new();
+        let mut last = 0;
+        for (range, name) in find_placeholders(&self.command) {
+            out.push_str(&self.command[last..range.start]);
+            match values.iter().find(|(n, _)| *n == name) {
+                Some((_, value)) => out.push_str(value),
+                None => out.push_str(&self.command[range.clone()]),
+            }
+            last = range.end;
+        }
+        out.push_str(&self.command[last..]);
+        // tldr escapes literal braces
+        out.replace("\\{\\{", "{{").replace("\\}\\}", "}}")
+    }
+}
+
+/// Value used when a placeholder is left empty: the placeholder text
+/// itself, or the first choice of a tldr `[-v|--verbose]` alternative.
+pub fn default_value(name: &str) -> String {
+    match name.strip_prefix('[').and_then(|n| n.strip_suffix(']')) {
+        Some(choices) => choices.split('|').next().unwrap_or("").to_string(),
+        None => name.to_string(),
+    }
+}
+
+/// `{{name}}` occurrences as (byte range, name), skipping `\{\{`.
+fn find_placeholders(s: &str) -> Vec<(std::ops::Range<usize>, String)> {
+    let mut found = Vec::new();
+    let mut pos = 0;
+    while let Some(start) = s[pos..].find("{{").map(|i| pos + i) {
+        if s[..start].ends_with('\\') {
+            pos = start + 2;
+            continue;
+        }
+        let Some(len) = s[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + len + 2;
+        let name = s[start + 2..end - 2].trim();
+        if !name.is_empty() {
+            found.push((start..end, name.to_string()));
+        }
+        pos = end;
+    }
+    found
+}
+
+/// Commands in the code blocks of `content`.
+///
+/// A trailing comment (`cmd   # what it does`) describes its line; otherwise
+/// the last line of text before the block does, as in tldr pages.
+pub fn extract_commands(content: &str) -> Vec<Command> {
+    let mut commands = Vec::new();
+    let mut in_block = false;
+    let mut context = String::new();
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_block = !in_block;
+            continue;
+        }
+        if !in_block {
+            if !trimmed.is_empty() {
+                context = trimmed
+                    .trim_start_matches(['#', '-', '*', '>'])
+                    .trim()
+                    .trim_end_matches(':')
+                    .to_string();
+            }
+            continue;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("//") {
+            continue;
+        }
+        let (command, comment) = match line.find("  #") {
+            Some(i) => (&line[..i], line[i..].trim().trim_start_matches('#').trim()),
+            None => (line, ""),
+        };
+        commands.push(Command {
+            command: command.trim().to_string(),
+            description: if comment.is_empty() {
+                context.clone()
+            } else {
+                comment.to_string()
+            },
+        });
+    }
+    commands
+}
+
+/// Copy `text` to the clipboard with the first available tool, returning
+/// its name.
+pub fn copy_to_clipboard(text: &str) -> anyhow::Result<&'static str> {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    let tools: [(&'static str, &[&str]); 5] = [
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+        ("pbcopy", &[]),
+        ("clip.exe", &[]),
+    ];
+
+    for (tool, args) in tools {
+        if tool == "wl-copy" && !wayland {
+            continue;
+        }
+        let Ok(mut child) = Process::new(tool)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(tool);
+        }
+    }
+    anyhow::bail!("no clipboard tool found (install wl-clipboard, xclip or xsel)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(s: &str) -> Command {
+        Command {
+            command: s.to_string(),
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let cmd = command("tar cf {{target.tar}} {{file}} {{ file }} \\{\\{literal\\}\\}");
+        assert_eq!(cmd.placeholders(), vec!["target.tar", "file"]);
+
+        let values = vec![("target.tar".to_string(), "out.tar".to_string())];
+        assert_eq!(
+            cmd.fill(&values),
+            "tar cf out.tar {{file}} {{ file }} {{literal}}"
+        );
+
+        let values = vec![
+            ("target.tar".to_string(), "a.tar".to_string()),
+            ("file".to_string(), "notes.txt".to_string()),
+        ];
+        assert_eq!(
+            cmd.fill(&values),
+            "tar cf a.tar notes.txt notes.txt {{literal}}"
+        );
+    }
+
+    #[test]
+    fn test_default_value() {
+        assert_eq!(default_value("path/to/file"), "path/to/file");
+        assert_eq!(default_value("[-r|--recursive]"), "-r");
+    }
+
+    #[test]
+    fn test_extract_commands() {
+        let content = "# Git\n\n## Branching\n```bash\n\
+                       git branch                  # List branches\n\
+                       # a comment\n\
+                       git checkout -b <branch>\n```\n\n\
+                       - Create an archive:\n\n```sh\ntar cf {{target.tar}} {{file}}\n```\n";
+        let commands = extract_commands(content);
+        assert_eq!(commands.len(), 3);
+        assert_eq!(commands[0].command, "git branch");
+        assert_eq!(commands[0].description, "List branches");
+        assert_eq!(commands[1].description, "Branching");
+        assert_eq!(commands[2].description, "Create an archive");
+        assert_eq!(commands[2].placeholders().len(), 2);
+    }
+}
@@ -0,0 +1,230 @@
+//! tldr-pages.
+//!
+//! Reads pages from a local clone of tldr-pages or a tldr client's cache.
+//! Pages live in `pages/<platform>/<command>.md`; a command documented for
+//! the current platform replaces its `common` page, as tldr clients do.
+
+use crate::cheatsheets::{CheatSheet, Source};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A parsed tldr page.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TldrPage {
+    pub name: String,
+    pub description: Vec<String>,
+    pub more_info: Option<String>,
+    pub examples: Vec<Example>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub description: String,
+    pub command: String,
+}
+
+impl TldrPage {
+    /// Parse the tldr Markdown format:
+    ///
+    /// ```text
+    /// # tar
+    /// > Archiving utility.
+    /// > More information: <https://www.gnu.org/software/tar>.
+    /// - Create an archive from files:
+    /// `tar cf {{target.tar}} {{file1}}`
+    /// ```
+    pub fn parse(text: &str) -> Self {
+        let mut page = TldrPage::default();
+        let mut pending: Option<String> = None;
+
+        for line in text.lines().map(str::trim) {
+            if let Some(name) = line.strip_prefix("# ") {
+                page.name = name.trim().to_string();
+            } else if let Some(desc) = line.strip_prefix('>') {
+                let desc = desc.trim();
+                match desc.strip_prefix("More information:") {
+                    Some(url) => {
+                        let url = url.trim().trim_end_matches('.');
+                        let url = url.trim_start_matches('<').trim_end_matches('>');
+                        page.more_info = Some(url.to_string());
+                    }
+                    None => page.description.push(desc.to_string()),
+                }
+            } else if let Some(desc) = line.strip_prefix("- ") {
+                pending = Some(desc.trim().trim_end_matches(':').to_string());
+            } else if line.len() > 1 && line.starts_with('`') && line.ends_with('`') {
+                page.examples.push(Example {
+                    description: pending.take().unwrap_or_default(),
+                    command: line[1..line.len() - 1].to_string(),
+                });
+            }
+        }
+        page
+    }
+
+    /// Render as a cheat sheet, one code block per example.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", self.name);
+        for line in &self.description {
+            out.push_str(line);
+            out.push('\n');
+        }
+        if let Some(url) = &self.more_info {
+            out.push_str(&format!("More information: {}\n", url));
+        }
+        for example in &self.examples {
+            out.push_str(&format!(
+                "\n- {}:\n```sh\n{}\n```\n",
+                example.description, example.command
+            ));
+        }
+        out
+    }
+}
+
+/// Directory holding the platform folders, given a clone or cache root.
+pub fn pages_dir(root: &Path) -> Option<PathBuf> {
+    [
+        root.join("pages"),
+        root.join("pages.en"),
+        root.join("tldr-pages").join("pages.en"),
+        root.join("tldr-master").join("pages"),
+        root.to_path_buf(),
+    ]
+    .into_iter()
+    .find(|dir| dir.join("common").is_dir())
+}
+
+/// Cache directories of common tldr clients, used when no path is set.
+pub fn default_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if let Some(dirs) = directories::BaseDirs::new() {
+        let cache = dirs.cache_dir();
+        roots.push(cache.join("tldr"));
+        roots.push(cache.join("tealdeer"));
+        roots.push(dirs.home_dir().join(".tldr").join("cache"));
+        roots.push(dirs.home_dir().join(".tldrc").join("tldr"));
+    }
+    roots
+}
+
+/// `common` plus the platform this was built for.
+pub fn default_platforms() -> Vec<String> {
+    let current = match std::env::consts::OS {
+        "macos" => "osx",
+        "solaris" | "illumos" => "sunos",
+        os => os,
+    };
+    vec!["common".to_string(), current.to_string()]
+}
+
+/// Load the pages for `platforms` under `root`. Later platforms take
+/// precedence for commands documented in several.
+pub fn load_tldr(root: &Path, platforms: &[String]) -> Vec<CheatSheet> {
+    let Some(pages) = pages_dir(root) else {
+        return Vec::new();
+    };
+
+    let mut sheets: HashMap<String, CheatSheet> = HashMap::new();
+    for platform in platforms {
+        let Ok(entries) = std::fs::read_dir(pages.join(platform)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|e| e != "md") {
+                continue;
+            }
+            let Ok(text) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let mut page = TldrPage::parse(&text);
+            if page.name.is_empty() {
+                page.name = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("unknown")
+                    .to_string();
+            }
+            sheets.insert(
+                page.name.clone(),
+                CheatSheet {
+                    topic: page.name.clone(),
+                    category: format!("tldr/{}", platform),
+                    source: Source::Tldr {
+                        path,
+                        platform: platform.clone(),
+                    },
+                    content: page.to_markdown(),
+                },
+            );
+        }
+    }
+    sheets.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snippet::extract_commands;
+
+    const TAR: &str = "# tar\n\n\
+        > Archiving utility.\n\
+        > Often combined with a compression method.\n\
+        > More information: <https://www.gnu.org/software/tar>.\n\n\
+        - [c]reate an archive and write it to a [f]ile:\n\n\
+        `tar cf {{path/to/target.tar}} {{path/to/file1 path/to/file2 ...}}`\n\n\
+        - E[x]tract a (compressed) archive [f]ile into the current directory [v]erbosely:\n\n\
+        `tar xvf {{path/to/source.tar[.gz|.bz2|.xz]}}`\n";
+
+    #[test]
+    fn test_parse_page() {
+        let page = TldrPage::parse(TAR);
+        assert_eq!(page.name, "tar");
+        assert_eq!(page.description.len(), 2);
+        assert_eq!(
+            page.more_info.as_deref(),
+            Some("https://www.gnu.org/software/tar")
+        );
+        assert_eq!(page.examples.len(), 2);
+        assert_eq!(
+            page.examples[0].command,
+            "tar cf {{path/to/target.tar}} {{path/to/file1 path/to/file2 ...}}"
+        );
+
+        let commands = extract_commands(&page.to_markdown());
+        assert_eq!(commands.len(), 2);
+        assert_eq!(
+            commands[1].description,
+            "E[x]tract a (compressed) archive [f]ile into the current directory [v]erbosely"
+        );
+    }
+
+    #[test]
+    fn test_load_platforms() {
+        let root = std::env::temp_dir().join(format!("tldr-test-{}", std::process::id()));
+        let pages = root.join("pages");
+        for dir in ["common", "linux", "osx"] {
+            std::fs::create_dir_all(pages.join(dir)).unwrap();
+        }
+        std::fs::write(pages.join("common/tar.md"), TAR).unwrap();
+        std::fs::write(pages.join("common/ls.md"), "# ls\n\n- List:\n\n`ls`\n").unwrap();
+        std::fs::write(
+            pages.join("linux/ls.md"),
+            "# ls\n\n- List all:\n\n`ls -a`\n",
+        )
+        .unwrap();
+        std::fs::write(pages.join("osx/open.md"), "# open\n").unwrap();
+
+        let platforms = vec!["common".to_string(), "linux".to_string()];
+        let mut sheets = load_tldr(&root, &platforms);
+        sheets.sort_by(|a, b| a.topic.cmp(&b.topic));
+        let topics: Vec<_> = sheets.iter().map(|s| s.topic.as_str()).collect();
+        assert_eq!(topics, vec!["ls", "tar"]);
+        assert_eq!(sheets[0].category, "tldr/linux");
+        assert!(sheets[0].content.contains("ls -a"));
+
+        assert_eq!(pages_dir(&root), Some(pages));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
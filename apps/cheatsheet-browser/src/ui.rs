@@ -5,7 +5,7 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
 
@@ -15,6 +15,13 @@ pub fn draw(f: &mut Frame, app: &App) {
         View::Detail => draw_detail(f, app),
     }
 
+    if app.picker.is_some() {
+        draw_picker(f, app);
+    }
+    if app.prompt.is_some() {
+        draw_prompt(f, app);
+    }
+
     if app.show_help {
         draw_help(f);
     }
@@ -53,6 +60,7 @@ fn draw_list(f: &mut Frame, app: &App) {
     let filtered = app.filtered_cheatsheets();
     let mut items: Vec<ListItem> = Vec::new();
     let mut current_category = String::new();
+    let mut state = ListState::default();
 
     for (i, sheet) in filtered.iter().enumerate() {
         // Add category header if changed
@@ -69,6 +77,9 @@ fn draw_list(f: &mut Frame, app: &App) {
             current_category = sheet.category.clone();
         }
 
+        if i == app.selected_index {
+            state.select(Some(items.len()));
+        }
         let style = if i == app.selected_index {
             Style::default()
                 .bg(Color::DarkGray)
@@ -80,6 +91,7 @@ fn draw_list(f: &mut Frame, app: &App) {
         let source_indicator = match &sheet.source {
             crate::cheatsheets::Source::Bundled => "",
             crate::cheatsheets::Source::User { .. } => " [user]",
+            crate::cheatsheets::Source::Tldr { .. } => "",
         };
 
         items.push(ListItem::new(Line::from(vec![
@@ -94,7 +106,8 @@ fn draw_list(f: &mut Frame, app: &App) {
             .borders(Borders::ALL)
             .title(format!(" {} cheatsheets ", filtered.len())),
     );
-    f.render_widget(list, chunks[1]);
+    // Scroll so the selection stays visible
+    f.render_stateful_widget(list, chunks[1], &mut state);
 
     // Status bar
     let status = Paragraph::new("j/k: Navigate  Enter: View  /: Search  ?: Help  q: Quit")
@@ -165,15 +178,101 @@ fn draw_detail(f: &mut Frame, app: &App) {
 
     // Status bar
     let total_lines = sheet.content.lines().count();
-    let status = Paragraph::new(format!(
-        "j/k: Scroll  d/u: Page  n/p: Section  g/G: Top/Bottom  q: Back  ({}/{})",
-        app.scroll_offset + 1,
-        total_lines
-    ))
-    .style(Style::default().fg(Color::DarkGray));
+    let status = match &app.message {
+        Some(message) => {
+            Paragraph::new(message.as_str()).style(Style::default().fg(Color::Yellow))
+        }
+        None => Paragraph::new(format!(
+            "j/k: Scroll  d/u: Page  n/p: Section  g/G: Top/Bottom  \
+             Enter: Commands  q: Back  ({}/{})",
+            app.scroll_offset + 1,
+            total_lines
+        ))
+        .style(Style::default().fg(Color::DarkGray)),
+    };
     f.render_widget(status, chunks[1]);
 }
 
+fn draw_picker(f: &mut Frame, app: &App) {
+    let Some(picker) = &app.picker else {
+        return;
+    };
+    let area = centered_rect(80, 70, f.area());
+    f.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = picker
+        .commands
+        .iter()
+        .enumerate()
+        .map(|(i, cmd)| {
+            let style = if i == picker.selected {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(vec![
+                Line::from(Span::styled(
+                    format!("  {}", cmd.description),
+                    Style::default().fg(Color::DarkGray),
+                )),
+                Line::from(Span::styled(format!("  {}", cmd.command), style)),
+            ])
+        })
+        .collect();
+
+    let mut state = ListState::default();
+    state.select(Some(picker.selected));
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" Commands ")
+            .title_bottom(" Enter: Use  y: Copy  p: Print on exit  Esc: Close "),
+    );
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_prompt(f: &mut Frame, app: &App) {
+    let Some(prompt) = &app.prompt else {
+        return;
+    };
+    let Some(name) = prompt.current() else {
+        return;
+    };
+    let area = centered_rect(70, 30, f.area());
+    f.render_widget(Clear, area);
+
+    let text = vec![
+        Line::from(Span::styled(
+            prompt.preview(),
+            Style::default().fg(Color::Magenta),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(format!("{}: ", name), Style::default().fg(Color::Cyan)),
+            Span::styled(
+                format!("{}_", prompt.input),
+                Style::default().fg(Color::Yellow),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(
+                "({} of {})  Enter: Next, empty keeps '{}'  Esc: Cancel",
+                prompt.values.len() + 1,
+                prompt.names.len(),
+                crate::snippet::default_value(name)
+            ),
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let popup = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title(" Fill in "))
+        .wrap(Wrap { trim: false });
+    f.render_widget(popup, area);
+}
+
 fn draw_help(f: &mut Frame) {
     let area = centered_rect(60, 60, f.area());
     f.render_widget(Clear, area);
@@ -197,8 +296,13 @@ fn draw_help(f: &mut Frame) {
         Line::from("  d/u          Page down/up"),
         Line::from("  n/p          Next/previous section"),
         Line::from("  g/G          Go to top/bottom"),
+        Line::from("  Enter        Pick a command"),
         Line::from("  q/Esc        Back to list"),
         Line::from(""),
+        Line::from(Span::styled("Commands:", Style::default().fg(Color::Cyan))),
+        Line::from("  Enter        Fill in and copy (or print)"),
+        Line::from("  y / p        Copy / print on exit"),
+        Line::from(""),
         Line::from(Span::styled(
             "Press any key to close",
             Style::default().fg(Color::DarkGray),
@@ -1,8 +1,11 @@
 //! Application state and logic.
 
 use crate::config::Config;
-use crate::formats::{parse_json, parse_toml, validate_format, ConfigFormat, ConfigNode, ConfigValue};
+use crate::convert::{convert, converted_path};
+use crate::edit::{self, input_text, parse_input, Edit};
+use crate::formats::{validate_format, ConfigFormat, ConfigNode, ConfigValue};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 pub struct App {
@@ -23,6 +26,10 @@ pub struct App {
     pub show_quit_confirm: bool,
     pub input_buffer: String,
     pub input_mode: InputMode,
+    /// The tree path the value, rename or add prompt works on
+    pub edit_path: Vec<String>,
+    /// An entry waiting for `y` to be deleted
+    pub pending_delete: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone)]
@@ -52,6 +59,10 @@ pub enum InputMode {
     None,
     OpenFile,
     SaveAs,
+    Convert,
+    EditValue,
+    RenameKey,
+    AddEntry,
 }
 
 impl App {
@@ -85,6 +96,8 @@ impl App {
             show_quit_confirm: false,
             input_buffer: String::new(),
             input_mode: InputMode::None,
+            edit_path: Vec::new(),
+            pending_delete: None,
         };
 
         app.parse_content();
@@ -104,11 +117,7 @@ impl App {
         }
 
         // Parse tree
-        self.tree = match self.format {
-            ConfigFormat::Toml => parse_toml(&content_str).ok(),
-            ConfigFormat::Json => parse_json(&content_str).ok(),
-            ConfigFormat::Unknown => None,
-        };
+        self.tree = self.format.parse(&content_str).and_then(Result::ok);
 
         self.rebuild_tree_items();
     }
@@ -133,13 +142,8 @@ impl App {
         }
 
         if node.expanded || node.key == "root" {
-            match &node.value {
-                ConfigValue::Table(children) | ConfigValue::Array(children) => {
-                    for child in children {
-                        self.add_tree_items(child);
-                    }
-                }
-                _ => {}
+            for child in node.value.children().unwrap_or(&[]) {
+                self.add_tree_items(child);
             }
         }
     }
@@ -152,6 +156,14 @@ impl App {
             return;
         }
 
+        if let Some(path) = self.pending_delete.take() {
+            match key.code {
+                KeyCode::Char('y') => self.apply_edit(Edit::Remove { path }, None),
+                _ => self.message = Some("Not deleted".to_string()),
+            }
+            return;
+        }
+
         if self.input_mode != InputMode::None {
             self.handle_input_key(key);
             return;
@@ -186,12 +198,25 @@ impl App {
                     self.move_cursor(-1, 0);
                 }
             }
-            KeyCode::Enter => {
-                if self.pane == Pane::Tree {
-                    self.toggle_tree_item();
+            KeyCode::Enter if self.pane == Pane::Tree => {
+                match self.tree_items.get(self.selected_tree_index) {
+                    Some(item) if !item.is_container => self.start_edit_value(),
+                    _ => self.toggle_tree_item(),
                 }
             }
 
+            // Structural edits
+            KeyCode::Char('n') if self.pane == Pane::Tree => self.start_rename(),
+            KeyCode::Char('a') if self.pane == Pane::Tree => self.start_add(),
+            KeyCode::Char('x') if self.pane == Pane::Tree => {
+                if let Some(item) = self.tree_items.get(self.selected_tree_index) {
+                    self.message = Some(format!("Delete {}? (y/n)", item.path.join(".")));
+                    self.pending_delete = Some(item.path.clone());
+                }
+            }
+            KeyCode::Char('J') if self.pane == Pane::Tree => self.move_item(false),
+            KeyCode::Char('K') if self.pane == Pane::Tree => self.move_item(true),
+
             // Actions
             KeyCode::Char('e') => {
                 self.mode = Mode::Editing;
@@ -215,6 +240,10 @@ impl App {
                 self.parse_content();
                 self.message = Some("Refreshed".to_string());
             }
+            KeyCode::Char('c') => {
+                self.input_mode = InputMode::Convert;
+                self.input_buffer.clear();
+            }
 
             // Help
             KeyCode::Char('?') => self.show_help = true,
@@ -320,6 +349,29 @@ impl App {
                 self.format = ConfigFormat::from_path(&path);
                 self.save_file();
             }
+            InputMode::Convert => match ConfigFormat::from_name(&self.input_buffer) {
+                Some(format) => self.convert_to(format),
+                None => self.message = Some(format!("Unknown format: {}", self.input_buffer)),
+            },
+            InputMode::EditValue => {
+                let edit = Edit::Set {
+                    path: self.edit_path.clone(),
+                    value: parse_input(&self.input_buffer),
+                };
+                self.apply_edit(edit, Some(self.edit_path.clone()));
+            }
+            InputMode::RenameKey => {
+                let key = self.input_buffer.trim().to_string();
+                let mut renamed = self.edit_path.clone();
+                renamed.pop();
+                renamed.push(key.clone());
+                let edit = Edit::Rename {
+                    path: self.edit_path.clone(),
+                    key,
+                };
+                self.apply_edit(edit, Some(renamed));
+            }
+            InputMode::AddEntry => self.finish_add(),
             InputMode::None => {}
         }
 
@@ -327,6 +379,203 @@ impl App {
         self.input_buffer.clear();
     }
 
+    /// Replace the buffer with the document in `format`. The file name
+    /// takes the new extension, so saving doesn't overwrite the original.
+    fn convert_to(&mut self, format: ConfigFormat) {
+        let Some(tree) = &self.tree else {
+            self.message = Some(match &self.validation_error {
+                Some(err) => format!("Can't convert: {}", err),
+                None => "Nothing to convert".to_string(),
+            });
+            return;
+        };
+        match convert(tree, format) {
+            Ok(converted) => {
+                self.content = converted.text.lines().map(|s| s.to_string()).collect();
+                self.format = format;
+                if let Some(path) = &mut self.file_path {
+                    *path = converted_path(path, format);
+                }
+                self.modified = true;
+                self.cursor = (0, 0);
+                self.selected_tree_index = 0;
+                self.parse_content();
+                self.message = Some(match converted.warnings.len() {
+                    0 => format!("Converted to {}", format.name()),
+                    n => format!(
+                        "Converted to {} ({} warnings: {})",
+                        format.name(),
+                        n,
+                        converted.warnings.join("; ")
+                    ),
+                });
+            }
+            Err(e) => self.message = Some(format!("Can't convert to {}: {}", format.name(), e)),
+        }
+    }
+
+    /// The node behind the selected tree item.
+    fn selected_node(&self) -> Option<&ConfigNode> {
+        let item = self.tree_items.get(self.selected_tree_index)?;
+        self.tree.as_ref()?.find(&item.path)
+    }
+
+    fn start_edit_value(&mut self) {
+        let selected = self.selected_node();
+        if let Some((text, path)) = selected.map(|n| (input_text(&n.value), n.path.clone())) {
+            self.input_buffer = text;
+            self.edit_path = path;
+            self.input_mode = InputMode::EditValue;
+        }
+    }
+
+    fn start_rename(&mut self) {
+        let Some(path) = self.selected_node().map(|n| n.path.clone()) else {
+            return;
+        };
+        let parent = &path[..path.len() - 1];
+        let in_table = self
+            .tree
+            .as_ref()
+            .and_then(|tree| tree.find(parent))
+            .is_some_and(|p| matches!(p.value, ConfigValue::Table(_)));
+        if !in_table {
+            self.message = Some("Only table entries have keys".to_string());
+            return;
+        }
+        self.input_buffer = path.last().cloned().unwrap_or_default();
+        self.edit_path = path;
+        self.input_mode = InputMode::RenameKey;
+    }
+
+    /// Add to the selected table or array, or next to the selected value.
+    fn start_add(&mut self) {
+        let Some(tree) = &self.tree else {
+            self.message = Some("Fix the file before editing its structure".to_string());
+            return;
+        };
+        let path = match self.selected_node() {
+            Some(node) if matches!(node.value, ConfigValue::Table(_) | ConfigValue::Array(_)) => {
+                node.path.clone()
+            }
+            Some(node) => node.path[..node.path.len() - 1].to_vec(),
+            None => tree.path.clone(),
+        };
+        self.edit_path = path;
+        self.input_buffer.clear();
+        self.input_mode = InputMode::AddEntry;
+    }
+
+    /// Whether the add prompt is for an array, which takes a value only.
+    pub fn adding_to_array(&self) -> bool {
+        self.tree
+            .as_ref()
+            .and_then(|tree| tree.find(&self.edit_path))
+            .is_some_and(|node| matches!(node.value, ConfigValue::Array(_)))
+    }
+
+    fn finish_add(&mut self) {
+        let parent = self.edit_path.clone();
+        let (key, value, select) = if self.adding_to_array() {
+            let len = self
+                .tree
+                .as_ref()
+                .and_then(|tree| tree.find(&parent))
+                .and_then(|node| node.value.children())
+                .map_or(0, |items| items.len());
+            (None, parse_input(&self.input_buffer), len.to_string())
+        } else {
+            let Some((key, value)) = self.input_buffer.split_once('=') else {
+                self.message = Some("Type the entry as key = value".to_string());
+                return;
+            };
+            let key = key.trim().to_string();
+            (Some(key.clone()), parse_input(value), key)
+        };
+        let mut selected = parent.clone();
+        selected.push(select);
+        self.apply_edit(Edit::Insert { parent, key, value }, Some(selected));
+    }
+
+    fn move_item(&mut self, up: bool) {
+        let Some(item) = self.tree_items.get(self.selected_tree_index) else {
+            return;
+        };
+        let path = item.path.clone();
+        let moved = path.last().and_then(|i| i.parse::<usize>().ok()).map(|i| {
+            let mut moved = path[..path.len() - 1].to_vec();
+            moved.push(if up { i.saturating_sub(1) } else { i + 1 }.to_string());
+            moved
+        });
+        self.apply_edit(Edit::Move { path, up }, moved);
+    }
+
+    /// Make `edit` on the text and re-read the tree, keeping which nodes
+    /// are open and selecting `select` if it's there.
+    fn apply_edit(&mut self, edit: Edit, select: Option<Vec<String>>) {
+        let content = self.content.join("\n");
+        let text = match edit::apply(self.format, &content, &edit) {
+            Ok(text) => text,
+            Err(e) => {
+                self.message = Some(format!("Can't edit: {}", e));
+                return;
+            }
+        };
+
+        let mut expanded = HashMap::new();
+        if let Some(tree) = &self.tree {
+            Self::collect_expanded(tree, &mut expanded);
+        }
+        if let Some(path) = &select {
+            for depth in 0..path.len() {
+                expanded.insert(path[..depth].to_vec(), true);
+            }
+        }
+
+        self.content = text.lines().map(|s| s.to_string()).collect();
+        if self.content.is_empty() {
+            self.content.push(String::new());
+        }
+        self.cursor.0 = self.cursor.0.min(self.content.len() - 1);
+        self.modified = true;
+        self.parse_content();
+        if let Some(tree) = &mut self.tree {
+            Self::restore_expanded(tree, &expanded);
+        }
+        self.rebuild_tree_items();
+
+        let selected = select.and_then(|path| self.tree_items.iter().position(|t| t.path == path));
+        if let Some(i) = selected {
+            self.selected_tree_index = i;
+        }
+        self.selected_tree_index = self
+            .selected_tree_index
+            .min(self.tree_items.len().saturating_sub(1));
+        self.message = Some(match edit {
+            Edit::Set { path, .. } => format!("Set {}", path.join(".")),
+            Edit::Rename { key, .. } => format!("Renamed to {}", key),
+            Edit::Insert { .. } => "Added".to_string(),
+            Edit::Remove { path } => format!("Deleted {}", path.join(".")),
+            Edit::Move { .. } => "Moved".to_string(),
+        });
+    }
+
+    fn collect_expanded(node: &ConfigNode, expanded: &mut HashMap<Vec<String>, bool>) {
+        expanded.insert(node.path.clone(), node.expanded);
+        for child in node.value.children().unwrap_or(&[]) {
+            Self::collect_expanded(child, expanded);
+        }
+    }
+
+    fn restore_expanded(node: &mut ConfigNode, expanded: &HashMap<Vec<String>, bool>) {
+        if let Some(open) = expanded.get(&node.path) {
+            node.expanded = *open;
+        }
+        for child in node.value.children_mut().into_iter().flatten() {
+            Self::restore_expanded(child, expanded);
+        }
+    }
+
     fn move_tree_selection(&mut self, delta: i32) {
         let len = self.tree_items.len();
         if len == 0 { return; }
@@ -352,13 +601,8 @@ impl App {
             return;
         }
 
-        match &mut node.value {
-            ConfigValue::Table(children) | ConfigValue::Array(children) => {
-                for child in children {
-                    Self::toggle_node(child, path);
-                }
-            }
-            _ => {}
+        for child in node.value.children_mut().into_iter().flatten() {
+            Self::toggle_node(child, path);
         }
     }
 
@@ -0,0 +1,388 @@
+//! Converting documents between formats.
+//!
+//! Conversion works on the [`ConfigNode`] tree, so any format that parses
+//! can be written as any other. What the target can't hold is flattened
+//! and reported: TOML has no null, INI has one level of sections and no
+//! lists, dotenv only flat strings. Only YAML holds several documents.
+
+use crate::formats::{ConfigFormat, ConfigNode, ConfigValue};
+use crate::{dotenv, ini};
+use std::path::{Path, PathBuf};
+
+/// A converted document and notes on anything that changed shape.
+#[derive(Debug, Default)]
+pub struct Converted {
+    pub text: String,
+    pub warnings: Vec<String>,
+}
+
+pub fn convert(tree: &ConfigNode, to: ConfigFormat) -> Result<Converted, String> {
+    let mut out = Converted::default();
+    let documents = match &tree.value {
+        ConfigValue::Documents(documents) => documents.as_slice(),
+        _ => std::slice::from_ref(tree),
+    };
+    if documents.len() > 1 && to != ConfigFormat::Yaml {
+        return Err(format!(
+            "{} documents; only YAML holds more than one",
+            documents.len()
+        ));
+    }
+
+    match to {
+        ConfigFormat::Yaml => {
+            for (i, document) in documents.iter().enumerate() {
+                if i > 0 {
+                    out.text.push_str("---\n");
+                }
+                let text =
+                    serde_yaml::to_string(&to_yaml(&document.value)).map_err(|e| e.to_string())?;
+                out.text.push_str(&text);
+            }
+        }
+        ConfigFormat::Json => {
+            write_json(&tree.value, 0, &mut out.text);
+            out.text.push('\n');
+        }
+        ConfigFormat::Toml => {
+            let Some(toml::Value::Table(table)) = to_toml(tree, &mut out.warnings) else {
+                return Err("TOML needs a table at the top".to_string());
+            };
+            out.text = toml::to_string_pretty(&table).map_err(|e| e.to_string())?;
+        }
+        ConfigFormat::Ini => write_ini(tree, &mut out)?,
+        ConfigFormat::Env => write_env(tree, &mut out)?,
+        ConfigFormat::Unknown => return Err("not a structured format".to_string()),
+    }
+    Ok(out)
+}
+
+/// A node's path for warnings, `a.b.0`.
+fn dotted(node: &ConfigNode) -> String {
+    node.path.join(".")
+}
+
+fn to_yaml(value: &ConfigValue) -> serde_yaml::Value {
+    use serde_yaml::Value;
+    match value {
+        ConfigValue::String(s) => Value::String(s.clone()),
+        ConfigValue::Integer(i) => Value::Number((*i).into()),
+        ConfigValue::Float(f) => Value::Number((*f).into()),
+        ConfigValue::Boolean(b) => Value::Bool(*b),
+        ConfigValue::Null => Value::Null,
+        ConfigValue::Array(items) | ConfigValue::Documents(items) => {
+            Value::Sequence(items.iter().map(|n| to_yaml(&n.value)).collect())
+        }
+        ConfigValue::Table(entries) => Value::Mapping(
+            entries
+                .iter()
+                .map(|n| (Value::String(n.key.clone()), to_yaml(&n.value)))
+                .collect(),
+        ),
+    }
+}
+
+/// JSON with two-space indentation, keeping the tree's key order.
+fn write_json(value: &ConfigValue, indent: usize, out: &mut String) {
+    let pad = "  ".repeat(indent + 1);
+    match value {
+        ConfigValue::String(s) => out.push_str(&serde_json::to_string(s).unwrap_or_default()),
+        ConfigValue::Integer(i) => out.push_str(&i.to_string()),
+        ConfigValue::Float(f) if f.is_finite() => out.push_str(&format!("{:?}", f)),
+        ConfigValue::Float(_) | ConfigValue::Null => out.push_str("null"),
+        ConfigValue::Boolean(b) => out.push_str(&b.to_string()),
+        ConfigValue::Array(items) | ConfigValue::Documents(items) if items.is_empty() => {
+            out.push_str("[]")
+        }
+        ConfigValue::Table(entries) if entries.is_empty() => out.push_str("{}"),
+        ConfigValue::Array(items) | ConfigValue::Documents(items) => {
+            out.push_str("[\n");
+            for (i, item) in items.iter().enumerate() {
+                out.push_str(&pad);
+                write_json(&item.value, indent + 1, out);
+                out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&"  ".repeat(indent));
+            out.push(']');
+        }
+        ConfigValue::Table(entries) => {
+            out.push_str("{\n");
+            for (i, entry) in entries.iter().enumerate() {
+                out.push_str(&pad);
+                out.push_str(&serde_json::to_string(&entry.key).unwrap_or_default());
+                out.push_str(": ");
+                write_json(&entry.value, indent + 1, out);
+                out.push_str(if i + 1 < entries.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&"  ".repeat(indent));
+            out.push('}');
+        }
+    }
+}
+
+/// `None` for nulls, which TOML can't hold.
+fn to_toml(node: &ConfigNode, warnings: &mut Vec<String>) -> Option<toml::Value> {
+    Some(match &node.value {
+        ConfigValue::String(s) => toml::Value::String(s.clone()),
+        ConfigValue::Integer(i) => toml::Value::Integer(*i),
+        ConfigValue::Float(f) => toml::Value::Float(*f),
+        ConfigValue::Boolean(b) => toml::Value::Boolean(*b),
+        ConfigValue::Null => {
+            warnings.push(format!("{}: null left out", dotted(node)));
+            return None;
+        }
+        ConfigValue::Array(items) | ConfigValue::Documents(items) => {
+            toml::Value::Array(items.iter().filter_map(|n| to_toml(n, warnings)).collect())
+        }
+        ConfigValue::Table(entries) => toml::Value::Table(
+            entries
+                .iter()
+                .filter_map(|n| Some((n.key.clone(), to_toml(n, warnings)?)))
+                .collect(),
+        ),
+    })
+}
+
+fn write_ini(tree: &ConfigNode, out: &mut Converted) -> Result<(), String> {
+    let ConfigValue::Table(entries) = &tree.value else {
+        return Err("INI needs a table at the top".to_string());
+    };
+    write_ini_section(None, entries, out);
+    Ok(())
+}
+
+/// Scalars of a section, then its tables as `[parent.child]` sections.
+fn write_ini_section(name: Option<&str>, entries: &[ConfigNode], out: &mut Converted) {
+    let (tables, values): (Vec<&ConfigNode>, Vec<&ConfigNode>) = entries
+        .iter()
+        .partition(|n| matches!(n.value, ConfigValue::Table(_)));
+
+    if let Some(name) = name {
+        if !out.text.is_empty() {
+            out.text.push('\n');
+        }
+        out.text.push_str(&format!("[{}]\n", name));
+    }
+    for node in values {
+        let value = match &node.value {
+            ConfigValue::Null => {
+                out.text.push_str(&format!("{}\n", node.key));
+                continue;
+            }
+            ConfigValue::Array(items) => {
+                out.warnings.push(format!(
+                    "{}: list written as comma-separated text",
+                    dotted(node)
+                ));
+                let items: Vec<String> = items.iter().map(|n| scalar_text(n, out)).collect();
+                items.join(", ")
+            }
+            _ => scalar_text(node, out),
+        };
+        out.text
+            .push_str(&format!("{} = {}\n", node.key, ini::quote(&value)));
+    }
+    for table in tables {
+        let section = match name {
+            Some(name) => format!("{}.{}", name, table.key),
+            None => table.key.clone(),
+        };
+        write_ini_section(Some(&section), table.value.children().unwrap_or(&[]), out);
+    }
+}
+
+/// Text of a scalar; containers are written as JSON with a warning.
+fn scalar_text(node: &ConfigNode, out: &mut Converted) -> String {
+    match &node.value {
+        ConfigValue::String(s) => s.clone(),
+        ConfigValue::Null => String::new(),
+        ConfigValue::Integer(_) | ConfigValue::Float(_) | ConfigValue::Boolean(_) => {
+            let mut text = String::new();
+            write_json(&node.value, 0, &mut text);
+            text
+        }
+        value => {
+            out.warnings
+                .push(format!("{}: nested value written as JSON", dotted(node)));
+            let mut text = String::new();
+            write_json(value, 0, &mut text);
+            text.split_whitespace().collect::<Vec<_>>().join(" ")
+        }
+    }
+}
+
+fn write_env(tree: &ConfigNode, out: &mut Converted) -> Result<(), String> {
+    let ConfigValue::Table(entries) = &tree.value else {
+        return Err("dotenv needs a table at the top".to_string());
+    };
+    let mut flattened = false;
+    for node in entries {
+        let mut name = env_name(&node.key);
+        if !dotenv::is_valid_key(&name) {
+            name.insert(0, '_');
+        }
+        write_env_entry(&name, node, &mut flattened, out);
+    }
+    if flattened {
+        out.warnings
+            .push("nested keys flattened to PARENT_CHILD names".to_string());
+    }
+    Ok(())
+}
+
+/// Where a file converted to `to` is saved: the same name with the new
+/// extension. Dotenv names lose their leading dot instead (`.env.local`
+/// gives `env.local.yaml`), as `.env.yaml` would still open as dotenv.
+pub fn converted_path(path: &Path, to: ConfigFormat) -> PathBuf {
+    let from = ConfigFormat::from_path(path);
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    match name.strip_prefix('.') {
+        _ if from == to => path.to_path_buf(),
+        Some(rest) if from == ConfigFormat::Env => {
+            path.with_file_name(format!("{}.{}", rest, to.extension()))
+        }
+        _ => path.with_extension(to.extension()),
+    }
+}
+
+fn write_env_entry(name: &str, node: &ConfigNode, flattened: &mut bool, out: &mut Converted) {
+    match node.value.children() {
+        Some(children) => {
+            *flattened = true;
+            for child in children {
+                let name = format!("{}_{}", name, env_name(&child.key));
+                write_env_entry(&name, child, flattened, out);
+            }
+        }
+        None => {
+            let value = scalar_text(node, out);
+            out.text
+                .push_str(&format!("{}={}\n", name, dotenv::quote(&value)));
+        }
+    }
+}
+
+/// A variable name part from a key: `log-level` gives `LOG_LEVEL`.
+fn env_name(key: &str) -> String {
+    key.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::{parse_json, parse_yaml};
+
+    const JSON: &str = r#"{
+  "name": "demo",
+  "debug": false,
+  "ratio": 0.5,
+  "server": {
+    "host": "0.0.0.0",
+    "ports": [8080, 8443],
+    "tls": null
+  },
+  "greeting": "Hello \"world\""
+}
+"#;
+
+    #[test]
+    fn test_round_trips() {
+        let tree = parse_json(JSON).unwrap();
+        for format in ConfigFormat::STRUCTURED {
+            let converted = convert(&tree, format).unwrap();
+            let again = format.parse(&converted.text).unwrap().unwrap();
+            let keys = if format == ConfigFormat::Env { 8 } else { 5 };
+            assert_eq!(
+                again.value.children().unwrap().len(),
+                keys,
+                "{}:\n{}",
+                format.name(),
+                converted.text
+            );
+        }
+
+        let yaml = convert(&tree, ConfigFormat::Yaml).unwrap();
+        let json = convert(&parse_yaml(&yaml.text).unwrap(), ConfigFormat::Json).unwrap();
+        assert!(json.warnings.is_empty());
+        assert_eq!(
+            parse_json(&json.text).unwrap().value.display_value(),
+            "{5 keys}"
+        );
+    }
+
+    #[test]
+    fn test_lossy_targets() {
+        let tree = parse_json(JSON).unwrap();
+
+        let toml = convert(&tree, ConfigFormat::Toml).unwrap();
+        assert_eq!(toml.warnings, vec!["server.tls: null left out"]);
+
+        let ini = convert(&tree, ConfigFormat::Ini).unwrap();
+        assert!(ini
+            .text
+            .contains("\n[server]\nhost = 0.0.0.0\nports = 8080, 8443\ntls\n"));
+        assert_eq!(
+            ini.warnings,
+            vec!["server.ports: list written as comma-separated text"]
+        );
+
+        let env = convert(&tree, ConfigFormat::Env).unwrap();
+        assert!(env.text.contains("SERVER_PORTS_1=8443\n"));
+        assert!(env.text.contains("GREETING=\"Hello \\\"world\\\"\"\n"));
+        let greeting = crate::dotenv::parse_env(&env.text).unwrap();
+        assert!(greeting
+            .value
+            .children()
+            .unwrap()
+            .iter()
+            .any(|n| n.value.display_value() == "\"Hello \"world\"\""));
+    }
+
+    #[test]
+    fn test_multiple_documents() {
+        let tree = parse_yaml("a: 1\n---\nb: [x, y]\n").unwrap();
+        assert_eq!(tree.value.display_value(), "[2 documents]");
+        let yaml = convert(&tree, ConfigFormat::Yaml).unwrap();
+        assert_eq!(yaml.text, "a: 1\n---\nb:\n- x\n- y\n");
+        assert_eq!(
+            convert(&tree, ConfigFormat::Json).unwrap_err(),
+            "2 documents; only YAML holds more than one"
+        );
+    }
+
+    #[test]
+    fn test_converted_path() {
+        let converted = |path: &str, to| converted_path(Path::new(path), to);
+        assert_eq!(
+            converted("app/config.toml", ConfigFormat::Json),
+            Path::new("app/config.json")
+        );
+        assert_eq!(
+            converted("app/.env", ConfigFormat::Yaml),
+            Path::new("app/env.yaml")
+        );
+        assert_eq!(
+            converted(".env.local", ConfigFormat::Toml),
+            Path::new("env.local.toml")
+        );
+        assert_eq!(converted(".env", ConfigFormat::Env), Path::new(".env"));
+        assert_eq!(
+            converted("config.yml", ConfigFormat::Yaml),
+            Path::new("config.yml")
+        );
+        assert_eq!(
+            converted("settings.yaml", ConfigFormat::Env),
+            Path::new("settings.env")
+        );
+        let yaml = converted(".env", ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_path(&yaml), ConfigFormat::Yaml);
+    }
+}
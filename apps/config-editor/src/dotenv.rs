@@ -0,0 +1,356 @@
+//! dotenv files.
+//!
+//! `KEY=value` lines, optionally starting with `export`. Double-quoted
+//! values take `\n`, `\t`, `\"`, `\\` and `\$` escapes and may span lines;
+//! single-quoted values are literal; unquoted values end at ` #`. Values
+//! are strings, as the programs reading them see them.
+
+use crate::edit::{edit_lines, line_end, scalar_text, Edit, Splice};
+use crate::formats::{ConfigNode, ConfigValue};
+use std::ops::Range;
+
+pub fn parse_env(content: &str) -> Result<ConfigNode, String> {
+    let mut entries: Vec<ConfigNode> = Vec::new();
+    let mut lines = content.lines().enumerate();
+
+    while let Some((number, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line
+            .strip_prefix("export ")
+            .map(str::trim_start)
+            .unwrap_or(line);
+        let (key, rest) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected KEY=value", number + 1))?;
+        let key = key.trim();
+        if !is_valid_key(key) {
+            return Err(format!(
+                "line {}: invalid variable name `{}`",
+                number + 1,
+                key
+            ));
+        }
+
+        let rest = rest.trim_start();
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let mut text = rest[1..].to_string();
+                loop {
+                    if let Some(end) = closing_quote(&text, quote) {
+                        text.truncate(end);
+                        break;
+                    }
+                    match lines.next() {
+                        Some((_, next)) => {
+                            text.push('\n');
+                            text.push_str(next);
+                        }
+                        None => return Err(format!("line {}: unterminated quote", number + 1)),
+                    }
+                }
+                if quote == '"' {
+                    unescape(&text)
+                } else {
+                    text
+                }
+            }
+            _ => match rest.find(" #") {
+                Some(comment) => rest[..comment].trim_end().to_string(),
+                None => rest.trim_end().to_string(),
+            },
+        };
+
+        let value = ConfigValue::String(value);
+        match entries.iter_mut().find(|n| n.key == key) {
+            Some(existing) => existing.value = value,
+            None => entries.push(ConfigNode::new(key, value, &[], 1)),
+        }
+    }
+
+    Ok(ConfigNode::new("root", ConfigValue::Table(entries), &[], 0))
+}
+
+pub fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-')
+}
+
+/// A value the parser reads back as it is: plain when it's safe, else in
+/// double quotes with escapes.
+pub fn quote(value: &str) -> String {
+    if value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_./:,+@%".contains(c))
+    {
+        return value.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '"' | '\\' | '$' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A variable as it sits in the text.
+struct Entry {
+    key: String,
+    key_span: Range<usize>,
+    /// The value as written, quotes and all, without a trailing comment
+    value_span: Range<usize>,
+    start: usize,
+    end: usize,
+}
+
+/// The variables in `text`, read the way [`parse_env`] reads them.
+fn layout(text: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let mut end = line_end(text, start);
+        let raw = &text[start..end];
+        let mut offset = start + raw.len() - raw.trim_start().len();
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            start = end;
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("export ") {
+            offset += line.len() - rest.trim_start().len();
+        }
+        let line = &text[offset..start + raw.trim_end().len()];
+        let Some((key, rest)) = line.split_once('=') else {
+            start = end;
+            continue;
+        };
+
+        let value_start = offset + key.len() + 1 + rest.len() - rest.trim_start().len();
+        let rest = rest.trim_start();
+        let value_end = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                match closing_quote(&text[value_start + 1..], quote) {
+                    // Quoted values may run over several lines
+                    Some(close) => {
+                        let value_end = value_start + 1 + close + 1;
+                        end = line_end(text, value_end - 1);
+                        value_end
+                    }
+                    None => value_start + rest.len(),
+                }
+            }
+            _ => {
+                value_start
+                    + rest
+                        .find(" #")
+                        .map_or(rest.len(), |i| rest[..i].trim_end().len())
+            }
+        };
+        entries.push(Entry {
+            key: key.trim().to_string(),
+            key_span: offset..offset + key.trim_end().len(),
+            value_span: value_start..value_end,
+            start,
+            end,
+        });
+        start = end;
+    }
+    entries
+}
+
+pub fn edit(content: &str, edit: &Edit) -> Result<String, String> {
+    edit_lines(content, |text| splices(text, edit))
+}
+
+fn splices(text: &str, edit: &Edit) -> Result<Vec<Splice>, String> {
+    let entries = layout(text);
+    let named = |key: &str| -> Vec<&Entry> { entries.iter().filter(|e| e.key == key).collect() };
+    let variable = |path: &[String]| -> Result<Vec<&Entry>, String> {
+        let [key] = path else {
+            return Err("dotenv files have only top-level variables".to_string());
+        };
+        let found = named(key);
+        if found.is_empty() {
+            return Err(format!("no variable `{}`", key));
+        }
+        Ok(found)
+    };
+    let check_key = |key: &str| match is_valid_key(key) {
+        true if !named(key).is_empty() => Err(format!("`{}` already exists", key)),
+        true => Ok(()),
+        false => Err(format!("invalid variable name `{}`", key)),
+    };
+    let value_text = |value: &ConfigValue| {
+        scalar_text(value)
+            .map(|text| quote(&text))
+            .ok_or_else(|| "dotenv values are text".to_string())
+    };
+
+    Ok(match edit {
+        Edit::Set { path, value } => {
+            let found = variable(path)?;
+            let entry = found.last().expect("found is never empty");
+            vec![(entry.value_span.clone(), value_text(value)?)]
+        }
+        Edit::Rename { path, key } => {
+            let found = variable(path)?;
+            check_key(key)?;
+            found
+                .into_iter()
+                .map(|e| (e.key_span.clone(), key.clone()))
+                .collect()
+        }
+        // Every definition goes, or an earlier one would show through
+        Edit::Remove { path } => variable(path)?
+            .into_iter()
+            .map(|e| (e.start..e.end, String::new()))
+            .collect(),
+        Edit::Insert { parent, key, value } => {
+            if !parent.is_empty() {
+                return Err("dotenv files have only top-level variables".to_string());
+            }
+            let key = key
+                .as_deref()
+                .ok_or_else(|| "a variable needs a name".to_string())?;
+            check_key(key)?;
+            let line = format!("{}={}\n", key, value_text(value)?);
+            vec![(text.len()..text.len(), line)]
+        }
+        Edit::Move { .. } => return Err("dotenv has no lists to reorder".to_string()),
+    })
+}
+
+/// Byte offset of the quote closing a value, skipping escaped ones in
+/// double quotes.
+fn closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && quote == '"' {
+            escaped = true;
+        } else if c == quote {
+            return Some(i);
+        }
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(c @ ('"' | '\\' | '$')) => out.push(c),
+            Some(c) => {
+                out.push('\\');
+                out.push(c);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_env() {
+        let tree = parse_env(
+            "# database\nexport DB_HOST=localhost # local only\nDB_PASS='p@ss#word'\n\
+             GREETING=\"Hello\\n\\\"you\\\"\"\nCERT=\"line one\nline two\"\nEMPTY=\n",
+        )
+        .unwrap();
+        let entries = tree.value.children().unwrap();
+        let pairs: Vec<_> = entries
+            .iter()
+            .map(|n| (n.key.as_str(), n.value.display_value()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("DB_HOST", "\"localhost\"".to_string()),
+                ("DB_PASS", "\"p@ss#word\"".to_string()),
+                ("GREETING", "\"Hello\n\"you\"\"".to_string()),
+                ("CERT", "\"line one\nline two\"".to_string()),
+                ("EMPTY", "\"\"".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            parse_env("A=1\nB=\"open\n").unwrap_err(),
+            "line 2: unterminated quote"
+        );
+        assert_eq!(
+            parse_env("1X=2\n").unwrap_err(),
+            "line 1: invalid variable name `1X`"
+        );
+    }
+
+    #[test]
+    fn test_edit() {
+        let path = |key: &str| vec![key.to_string()];
+        let content =
+            "# database\nexport DB_HOST=localhost # local only\nCERT=\"line one\nline two\"\n\
+                       DB_PASS='secret'\nDB_PASS=override";
+        let edits = [
+            Edit::Set {
+                path: path("DB_HOST"),
+                value: ConfigValue::String("db.internal".to_string()),
+            },
+            Edit::Set {
+                path: path("DB_PASS"),
+                value: ConfigValue::String("p@ss word".to_string()),
+            },
+            Edit::Rename {
+                path: path("DB_HOST"),
+                key: "DATABASE_HOST".to_string(),
+            },
+            Edit::Remove { path: path("CERT") },
+            Edit::Insert {
+                parent: vec![],
+                key: Some("PORT".to_string()),
+                value: ConfigValue::Integer(5432),
+            },
+        ];
+        let text = edits
+            .iter()
+            .fold(content.to_string(), |text, e| edit(&text, e).unwrap());
+        assert_eq!(
+            text,
+            "# database\nexport DATABASE_HOST=db.internal # local only\n\
+             DB_PASS='secret'\nDB_PASS=\"p@ss word\"\nPORT=5432"
+        );
+
+        let err = edit(
+            &text,
+            &Edit::Rename {
+                path: path("PORT"),
+                key: "2X".to_string(),
+            },
+        );
+        assert_eq!(err.unwrap_err(), "invalid variable name `2X`");
+    }
+}
@@ -0,0 +1,227 @@
+//! Structural edits from the tree pane.
+//!
+//! Edits are made on the file's text rather than on the tree, so what the
+//! tree doesn't hold — comments, key order, blank lines, quoting style —
+//! stays as the user wrote it. TOML goes through `toml_edit`'s document
+//! model; the other formats find the entry by its path in the text and
+//! splice the change in.
+
+use crate::formats::{ConfigFormat, ConfigValue};
+use crate::{dotenv, ini, json_doc, toml_doc, yaml_doc};
+use std::ops::Range;
+
+/// Replace the text in a range.
+pub type Splice = (Range<usize>, String);
+
+/// A change to the entry at a tree path ([`ConfigNode::path`]).
+///
+/// [`ConfigNode::path`]: crate::formats::ConfigNode
+#[derive(Debug, Clone)]
+pub enum Edit {
+    /// Replace a value
+    Set {
+        path: Vec<String>,
+        value: ConfigValue,
+    },
+    /// Give a table entry another key, keeping its place
+    Rename {
+        path: Vec<String>,
+        key: String,
+    },
+    /// Add an entry at the end of a table (with a key) or an array
+    Insert {
+        parent: Vec<String>,
+        key: Option<String>,
+        value: ConfigValue,
+    },
+    Remove {
+        path: Vec<String>,
+    },
+    /// Swap an array item with the one above or below it
+    Move {
+        path: Vec<String>,
+        up: bool,
+    },
+}
+
+/// Apply `edit` to `content`, returning the new text.
+pub fn apply(format: ConfigFormat, content: &str, edit: &Edit) -> Result<String, String> {
+    match format {
+        ConfigFormat::Toml => toml_doc::edit(content, edit),
+        ConfigFormat::Json => json_doc::edit(content, edit),
+        ConfigFormat::Yaml => yaml_doc::edit(content, edit),
+        ConfigFormat::Ini => ini::edit(content, edit),
+        ConfigFormat::Env => dotenv::edit(content, edit),
+        ConfigFormat::Unknown => Err("plain text has no structure to edit".to_string()),
+    }
+}
+
+/// A value typed in a prompt: `true`, `42`, `1.5`, `null`, `[]` and `{}`
+/// are what they look like, `"quoted"` text is a string with JSON escapes,
+/// and anything else is a string as typed.
+pub fn parse_input(text: &str) -> ConfigValue {
+    let text = text.trim();
+    match text {
+        "true" => return ConfigValue::Boolean(true),
+        "false" => return ConfigValue::Boolean(false),
+        "null" | "~" => return ConfigValue::Null,
+        "[]" => return ConfigValue::Array(Vec::new()),
+        "{}" => return ConfigValue::Table(Vec::new()),
+        _ => {}
+    }
+    if let Ok(i) = text.parse::<i64>() {
+        return ConfigValue::Integer(i);
+    }
+    // Leave `inf`, `nan` and the like to be strings
+    if text.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') {
+        if let Ok(f) = text.parse::<f64>() {
+            return ConfigValue::Float(f);
+        }
+    }
+    if text.len() > 1 && text.starts_with('"') && text.ends_with('"') {
+        if let Ok(s) = serde_json::from_str::<String>(text) {
+            return ConfigValue::String(s);
+        }
+    }
+    ConfigValue::String(text.to_string())
+}
+
+/// A value as [`parse_input`] reads it back, to start the edit prompt with.
+pub fn input_text(value: &ConfigValue) -> String {
+    match value {
+        ConfigValue::String(s) => serde_json::to_string(s).unwrap_or_default(),
+        ConfigValue::Array(_) => "[]".to_string(),
+        ConfigValue::Table(_) | ConfigValue::Documents(_) => "{}".to_string(),
+        other => other.display_value(),
+    }
+}
+
+/// Text of a scalar, for formats where every value is text.
+pub fn scalar_text(value: &ConfigValue) -> Option<String> {
+    match value {
+        ConfigValue::String(s) => Some(s.clone()),
+        ConfigValue::Integer(i) => Some(i.to_string()),
+        ConfigValue::Float(f) => Some(f.to_string()),
+        ConfigValue::Boolean(b) => Some(b.to_string()),
+        ConfigValue::Null => Some(String::new()),
+        _ => None,
+    }
+}
+
+/// The parent's path and the last key of `path`.
+pub fn split_path(path: &[String]) -> Result<(&[String], &str), String> {
+    match path.split_last() {
+        Some((key, parent)) => Ok((parent, key)),
+        None => Err("the document itself can't be changed".to_string()),
+    }
+}
+
+pub fn index(key: &str) -> Result<usize, String> {
+    key.parse()
+        .map_err(|_| format!("`{}` is not an array index", key))
+}
+
+/// Index of the item `index` moves to, within `len` items.
+pub fn neighbour(index: usize, len: usize, up: bool) -> Result<usize, String> {
+    match up {
+        true if index == 0 => Err("already the first item".to_string()),
+        false if index + 1 >= len => Err("already the last item".to_string()),
+        true => Ok(index - 1),
+        false => Ok(index + 1),
+    }
+}
+
+/// Lines of `text` with the byte offset each starts at, without the `\n`.
+pub fn lines(text: &str) -> Vec<(usize, &str)> {
+    let mut start = 0;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let at = start;
+            start += line.len();
+            (at, line.strip_suffix('\n').unwrap_or(line))
+        })
+        .collect()
+}
+
+/// Offset just past the line starting at `start`, including its `\n`.
+pub fn line_end(text: &str, start: usize) -> usize {
+    text[start..]
+        .find('\n')
+        .map(|i| start + i + 1)
+        .unwrap_or(text.len())
+}
+
+/// Apply the splices `plan` makes for `content`. `plan` sees the text with
+/// a final `\n`, so every line ends in one; the result keeps the original
+/// ending.
+pub fn edit_lines(
+    content: &str,
+    plan: impl FnOnce(&str) -> Result<Vec<Splice>, String>,
+) -> Result<String, String> {
+    let mut text = content.to_string();
+    let newline = text.is_empty() || text.ends_with('\n');
+    if !newline {
+        text.push('\n');
+    }
+    let mut splices = plan(&text)?;
+    splices.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    for (range, replacement) in splices {
+        text.replace_range(range, &replacement);
+    }
+    if !newline && text.ends_with('\n') {
+        text.pop();
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() {
+        let parsed: Vec<String> = [
+            "true",
+            "42",
+            "-1.5",
+            "null",
+            "[]",
+            "{}",
+            "\"8080\"",
+            "\"a\\nb\"",
+            "inf",
+            "plain text",
+        ]
+        .iter()
+        .map(|text| {
+            let value = parse_input(text);
+            format!("{} {}", value.type_name(), input_text(&value))
+        })
+        .collect();
+        assert_eq!(
+            parsed,
+            vec![
+                "boolean true",
+                "integer 42",
+                "float -1.5",
+                "null null",
+                "array []",
+                "table {}",
+                "string \"8080\"",
+                "string \"a\\nb\"",
+                "string \"inf\"",
+                "string \"plain text\"",
+            ]
+        );
+
+        let text = edit_lines("a = 1", |text| {
+            Ok(vec![
+                (0..0, "# top\n".to_string()),
+                (text.len()..text.len(), "b = 2\n".to_string()),
+            ])
+        });
+        assert_eq!(text.unwrap(), "# top\na = 1\nb = 2");
+        assert_eq!(neighbour(0, 3, true).unwrap_err(), "already the first item");
+        assert_eq!(neighbour(1, 3, false), Ok(2));
+    }
+}
@@ -1,31 +1,91 @@
 //! Configuration file format handling.
 
+use crate::dotenv::parse_env;
+use crate::ini::parse_ini;
 use serde_json::Value as JsonValue;
+use serde_yaml::Value as YamlValue;
 use std::path::Path;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConfigFormat {
     Toml,
     Json,
+    Yaml,
+    Ini,
+    Env,
     Unknown,
 }
 
 impl ConfigFormat {
+    /// Formats documents can be parsed from and converted to.
+    pub const STRUCTURED: [ConfigFormat; 5] = [
+        ConfigFormat::Toml,
+        ConfigFormat::Json,
+        ConfigFormat::Yaml,
+        ConfigFormat::Ini,
+        ConfigFormat::Env,
+    ];
+
     pub fn from_path(path: &Path) -> Self {
+        let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if file_name == ".env" || file_name.starts_with(".env.") {
+            return ConfigFormat::Env;
+        }
         match path.extension().and_then(|e| e.to_str()) {
             Some("toml") => ConfigFormat::Toml,
             Some("json") => ConfigFormat::Json,
+            Some("yaml" | "yml") => ConfigFormat::Yaml,
+            Some("ini" | "cfg" | "desktop") => ConfigFormat::Ini,
+            Some("env") => ConfigFormat::Env,
             _ => ConfigFormat::Unknown,
         }
     }
 
+    /// A format by name or extension, as typed in the convert prompt.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().trim_start_matches('.').to_lowercase().as_str() {
+            "toml" => Some(ConfigFormat::Toml),
+            "json" => Some(ConfigFormat::Json),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            "ini" | "cfg" => Some(ConfigFormat::Ini),
+            "env" | "dotenv" => Some(ConfigFormat::Env),
+            _ => None,
+        }
+    }
+
     pub fn name(&self) -> &'static str {
         match self {
             ConfigFormat::Toml => "TOML",
             ConfigFormat::Json => "JSON",
+            ConfigFormat::Yaml => "YAML",
+            ConfigFormat::Ini => "INI",
+            ConfigFormat::Env => "dotenv",
             ConfigFormat::Unknown => "Text",
         }
     }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Json => "json",
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Ini => "ini",
+            ConfigFormat::Env => "env",
+            ConfigFormat::Unknown => "txt",
+        }
+    }
+
+    /// Parse `content` into a tree, or `None` for plain text.
+    pub fn parse(&self, content: &str) -> Option<Result<ConfigNode, String>> {
+        Some(match self {
+            ConfigFormat::Toml => parse_toml(content),
+            ConfigFormat::Json => parse_json(content),
+            ConfigFormat::Yaml => parse_yaml(content),
+            ConfigFormat::Ini => parse_ini(content),
+            ConfigFormat::Env => parse_env(content),
+            ConfigFormat::Unknown => return None,
+        })
+    }
 }
 
 /// A config value node for tree display.
@@ -38,6 +98,35 @@ pub struct ConfigNode {
     pub expanded: bool,
 }
 
+impl ConfigNode {
+    /// A node under `parent`; the root has an empty `key` path.
+    pub fn new(key: &str, value: ConfigValue, parent: &[String], depth: usize) -> Self {
+        let mut path = parent.to_vec();
+        if depth > 0 {
+            path.push(key.to_string());
+        }
+        Self {
+            key: key.to_string(),
+            value,
+            path,
+            depth,
+            expanded: depth < 2,
+        }
+    }
+
+    /// The node at `path`, this one or below.
+    pub fn find(&self, path: &[String]) -> Option<&ConfigNode> {
+        if self.path == path {
+            return Some(self);
+        }
+        self.value
+            .children()?
+            .iter()
+            .find(|child| path.starts_with(&child.path))?
+            .find(path)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum ConfigValue {
     String(String),
@@ -46,6 +135,8 @@ pub enum ConfigValue {
     Boolean(bool),
     Array(Vec<ConfigNode>),
     Table(Vec<ConfigNode>),
+    /// The documents of a multi-document YAML stream
+    Documents(Vec<ConfigNode>),
     Null,
 }
 
@@ -58,6 +149,7 @@ impl ConfigValue {
             ConfigValue::Boolean(_) => "boolean",
             ConfigValue::Array(_) => "array",
             ConfigValue::Table(_) => "table",
+            ConfigValue::Documents(_) => "documents",
             ConfigValue::Null => "null",
         }
     }
@@ -70,12 +162,31 @@ impl ConfigValue {
             ConfigValue::Boolean(b) => b.to_string(),
             ConfigValue::Array(arr) => format!("[{} items]", arr.len()),
             ConfigValue::Table(tbl) => format!("{{{} keys}}", tbl.len()),
+            ConfigValue::Documents(docs) => format!("[{} documents]", docs.len()),
             ConfigValue::Null => "null".to_string(),
         }
     }
 
     pub fn is_container(&self) -> bool {
-        matches!(self, ConfigValue::Array(_) | ConfigValue::Table(_))
+        self.children().is_some()
+    }
+
+    pub fn children(&self) -> Option<&[ConfigNode]> {
+        match self {
+            ConfigValue::Array(children)
+            | ConfigValue::Table(children)
+            | ConfigValue::Documents(children) => Some(children),
+            _ => None,
+        }
+    }
+
+    pub fn children_mut(&mut self) -> Option<&mut Vec<ConfigNode>> {
+        match self {
+            ConfigValue::Array(children)
+            | ConfigValue::Table(children)
+            | ConfigValue::Documents(children) => Some(children),
+            _ => None,
+        }
     }
 }
 
@@ -172,6 +283,77 @@ fn json_to_node(key: &str, value: &JsonValue, path: Vec<String>, depth: usize) -
     }
 }
 
+/// Parse YAML. A stream of several documents gives a `Documents` root.
+pub fn parse_yaml(content: &str) -> Result<ConfigNode, String> {
+    let mut documents = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let value: YamlValue =
+            serde::Deserialize::deserialize(document).map_err(|e| e.to_string())?;
+        documents.push(value);
+    }
+    match documents.len() {
+        0 => Ok(ConfigNode::new("root", ConfigValue::Null, &[], 0)),
+        1 => Ok(yaml_to_node("root", &documents[0], &[], 0)),
+        _ => {
+            let children = documents
+                .iter()
+                .enumerate()
+                .map(|(i, doc)| yaml_to_node(&format!("document {}", i + 1), doc, &[], 1))
+                .collect();
+            Ok(ConfigNode::new("root", ConfigValue::Documents(children), &[], 0))
+        }
+    }
+}
+
+fn yaml_to_node(key: &str, value: &YamlValue, parent: &[String], depth: usize) -> ConfigNode {
+    let mut node = ConfigNode::new(key, ConfigValue::Null, parent, depth);
+    node.value = match value {
+        YamlValue::Null => ConfigValue::Null,
+        YamlValue::Bool(b) => ConfigValue::Boolean(*b),
+        YamlValue::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                ConfigValue::Integer(i)
+            } else if let Some(f) = n.as_f64() {
+                ConfigValue::Float(f)
+            } else {
+                ConfigValue::String(n.to_string())
+            }
+        }
+        YamlValue::String(s) => ConfigValue::String(s.clone()),
+        YamlValue::Sequence(seq) => ConfigValue::Array(
+            seq.iter()
+                .enumerate()
+                .map(|(i, v)| yaml_to_node(&i.to_string(), v, &node.path, depth + 1))
+                .collect(),
+        ),
+        YamlValue::Mapping(map) => ConfigValue::Table(
+            map.iter()
+                .map(|(k, v)| yaml_to_node(&yaml_key(k), v, &node.path, depth + 1))
+                .collect(),
+        ),
+        // Tags such as `!Ref` are shown on the value's key
+        YamlValue::Tagged(tagged) => {
+            let mut inner = yaml_to_node(key, &tagged.value, parent, depth);
+            inner.key = format!("{} {}", key, tagged.tag);
+            return inner;
+        }
+    };
+    node
+}
+
+/// Mapping keys as text; YAML allows any value as a key.
+pub fn yaml_key(key: &YamlValue) -> String {
+    match key {
+        YamlValue::String(s) => s.clone(),
+        YamlValue::Null => "null".to_string(),
+        YamlValue::Bool(b) => b.to_string(),
+        YamlValue::Number(n) => n.to_string(),
+        other => serde_yaml::to_string(other)
+            .map(|s| s.trim_end().to_string())
+            .unwrap_or_default(),
+    }
+}
+
 pub fn validate_format(content: &str, format: ConfigFormat) -> Result<(), String> {
     match format {
         ConfigFormat::Toml => {
@@ -182,6 +364,9 @@ pub fn validate_format(content: &str, format: ConfigFormat) -> Result<(), String
             serde_json::from_str::<JsonValue>(content).map_err(|e| e.to_string())?;
             Ok(())
         }
+        ConfigFormat::Yaml => parse_yaml(content).map(|_| ()),
+        ConfigFormat::Ini => parse_ini(content).map(|_| ()),
+        ConfigFormat::Env => parse_env(content).map(|_| ()),
         ConfigFormat::Unknown => Ok(()),
     }
 }
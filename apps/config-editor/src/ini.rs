@@ -0,0 +1,481 @@
+//! INI files.
+//!
+//! `[section]` headers group `key = value` (or `key: value`) entries;
+//! entries above the first header are top-level. Lines starting with `;`
+//! or `#` are comments, and an indented line without a separator continues
+//! the previous value. INI has no types, so values stay strings; a key
+//! with no value is null.
+
+use crate::edit::{edit_lines, line_end, lines, scalar_text, Edit, Splice};
+use crate::formats::{ConfigNode, ConfigValue};
+use std::ops::Range;
+
+pub fn parse_ini(content: &str) -> Result<ConfigNode, String> {
+    let mut root: Vec<ConfigNode> = Vec::new();
+    let mut section: Option<usize> = None;
+    // Whether the last line was an entry that an indented line can continue
+    let mut continuable = false;
+
+    for (number, raw) in content.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continuable = false;
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let name = header
+                .strip_suffix(']')
+                .ok_or_else(|| format!("line {}: missing `]`", number + 1))?
+                .trim();
+            if name.is_empty() {
+                return Err(format!("line {}: empty section name", number + 1));
+            }
+            // Repeated sections add to the first one
+            section = Some(match root.iter().position(|n| n.key == name) {
+                Some(i) if matches!(root[i].value, ConfigValue::Table(_)) => i,
+                _ => {
+                    root.push(ConfigNode::new(
+                        name,
+                        ConfigValue::Table(Vec::new()),
+                        &[],
+                        1,
+                    ));
+                    root.len() - 1
+                }
+            });
+            continuable = false;
+            continue;
+        }
+
+        let separator = line.find(['=', ':']);
+        let indented = raw.starts_with([' ', '\t']);
+        if indented && separator.is_none() && continuable {
+            let entries = entries(&mut root, section);
+            if let Some(ConfigNode {
+                value: ConfigValue::String(value),
+                ..
+            }) = entries.last_mut()
+            {
+                value.push('\n');
+                value.push_str(line);
+            }
+            continue;
+        }
+
+        let (key, value) = match separator {
+            Some(at) => (
+                line[..at].trim(),
+                ConfigValue::String(unquote(line[at + 1..].trim()).to_string()),
+            ),
+            None => (line, ConfigValue::Null),
+        };
+        if key.is_empty() {
+            return Err(format!("line {}: missing key", number + 1));
+        }
+
+        let parent: Vec<String> = section.map(|i| root[i].path.clone()).unwrap_or_default();
+        let depth = parent.len() + 1;
+        let entries = entries(&mut root, section);
+        match entries.iter_mut().find(|n| n.key == key) {
+            Some(existing) => existing.value = value,
+            None => entries.push(ConfigNode::new(key, value, &parent, depth)),
+        }
+        continuable = separator.is_some();
+    }
+
+    Ok(ConfigNode::new("root", ConfigValue::Table(root), &[], 0))
+}
+
+/// The entries of the current section, or the top level before any.
+fn entries(root: &mut Vec<ConfigNode>, section: Option<usize>) -> &mut Vec<ConfigNode> {
+    match section {
+        Some(i) if root[i].value.is_container() => root[i].value.children_mut().unwrap(),
+        _ => root,
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// Quote values the parser would otherwise read differently.
+pub fn quote(value: &str) -> String {
+    let needs_quotes = value != value.trim()
+        || value.contains('\n')
+        || (value.starts_with('"') && value.ends_with('"') && value.len() > 1);
+    if needs_quotes {
+        format!("\"{}\"", value.replace('\n', "\\n"))
+    } else {
+        value.to_string()
+    }
+}
+
+/// A section as it sits in the text; the entries above the first header
+/// are one with no name. A repeated section has a part for each header.
+struct Part {
+    name: Option<String>,
+    name_span: Range<usize>,
+    start: usize,
+    /// Past its last line that isn't blank
+    end: usize,
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    key: String,
+    key_span: Range<usize>,
+    /// What follows the separator; `None` for a bare key
+    value_span: Option<Range<usize>>,
+    start: usize,
+    end: usize,
+}
+
+/// Where the sections and entries are, read the way [`parse_ini`] reads
+/// them.
+fn layout(text: &str) -> Vec<Part> {
+    let mut parts = vec![Part {
+        name: None,
+        name_span: 0..0,
+        start: 0,
+        end: 0,
+        entries: Vec::new(),
+    }];
+    let mut continuable = false;
+
+    for (start, raw) in lines(text) {
+        let end = line_end(text, start);
+        let line = raw.trim();
+        let offset = start + raw.len() - raw.trim_start().len();
+        let part = parts.last_mut().expect("the top level is always there");
+        if line.is_empty() {
+            continuable = false;
+            continue;
+        }
+        if line.starts_with(';') || line.starts_with('#') {
+            part.end = end;
+            continuable = false;
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.strip_suffix(']').unwrap_or(header);
+            let name = header.trim();
+            let name_start = offset + 1 + header.len() - header.trim_start().len();
+            parts.push(Part {
+                name: Some(name.to_string()),
+                name_span: name_start..name_start + name.len(),
+                start,
+                end,
+                entries: Vec::new(),
+            });
+            continuable = false;
+            continue;
+        }
+
+        let separator = line.find(['=', ':']);
+        if raw.starts_with([' ', '\t']) && separator.is_none() && continuable {
+            if let Some(entry) = part.entries.last_mut() {
+                entry.end = end;
+                if let Some(span) = &mut entry.value_span {
+                    span.end = start + raw.trim_end().len();
+                }
+            }
+            part.end = end;
+            continue;
+        }
+
+        let (key, value_span) = match separator {
+            Some(at) => {
+                let rest = &line[at + 1..];
+                let value_start = offset + at + 1 + rest.len() - rest.trim_start().len();
+                (line[..at].trim(), Some(value_start..offset + line.len()))
+            }
+            None => (line, None),
+        };
+        part.entries.push(Entry {
+            key: key.to_string(),
+            key_span: offset..offset + key.len(),
+            value_span,
+            start,
+            end,
+        });
+        part.end = end;
+        continuable = separator.is_some();
+    }
+    parts
+}
+
+/// A value as entry text; more lines go on indented continuation lines.
+fn render(value: &str) -> String {
+    let mut lines = value.split('\n');
+    let first = quote(lines.next().unwrap_or_default());
+    lines.fold(first, |text, line| format!("{}\n    {}", text, line.trim()))
+}
+
+fn check_key(key: &str) -> Result<(), String> {
+    let valid = !key.is_empty()
+        && key == key.trim()
+        && !key.contains(['=', ':', '[', ']', '\n'])
+        && !key.starts_with([';', '#']);
+    match valid {
+        true => Ok(()),
+        false => Err(format!("`{}` can't be an INI key", key)),
+    }
+}
+
+pub fn edit(content: &str, edit: &Edit) -> Result<String, String> {
+    edit_lines(content, |text| splices(text, edit))
+}
+
+fn splices(text: &str, edit: &Edit) -> Result<Vec<Splice>, String> {
+    let parts = layout(text);
+    let is_section = |name: &str| parts.iter().any(|p| p.name.as_deref() == Some(name));
+    let sections = |name: &str| -> Vec<&Part> {
+        parts
+            .iter()
+            .filter(|p| p.name.as_deref() == Some(name))
+            .collect()
+    };
+    let entries = |section: Option<&str>, key: &str| -> Vec<&Entry> {
+        parts
+            .iter()
+            .filter(|p| p.name.as_deref() == section)
+            .flat_map(|p| &p.entries)
+            .filter(|e| e.key == key)
+            .collect()
+    };
+    // A path to an entry: `[key]` at the top level or `[section, key]`
+    let entry_path = |path: &[String]| -> Result<(Option<String>, String), String> {
+        match path {
+            [key] if !is_section(key) => Ok((None, key.clone())),
+            [section, key] => Ok((Some(section.clone()), key.clone())),
+            _ => Err("sections hold entries, not a value".to_string()),
+        }
+    };
+    // Copy the spacing around `=` from the first entry with a value
+    let separator = parts
+        .iter()
+        .flat_map(|p| &p.entries)
+        .find_map(|e| {
+            let span = e.value_span.as_ref().filter(|s| !s.is_empty())?;
+            Some(&text[e.key_span.end..span.start])
+        })
+        .unwrap_or(" = ");
+    let value_text = |value: &ConfigValue| match value {
+        ConfigValue::Array(_) => Err("INI has no lists".to_string()),
+        ConfigValue::Table(_) | ConfigValue::Documents(_) => {
+            Err("INI sections can't nest".to_string())
+        }
+        _ => Ok(scalar_text(value)
+            .map(|text| render(&text))
+            .unwrap_or_default()),
+    };
+
+    Ok(match edit {
+        Edit::Set { path, value } => {
+            let (section, key) = entry_path(path)?;
+            let found = entries(section.as_deref(), &key);
+            let entry = found.last().ok_or_else(|| format!("no entry `{}`", key))?;
+            let after_key = entry.key_span.end;
+            match (&entry.value_span, value) {
+                // A key on its own is null
+                (span, ConfigValue::Null) => {
+                    let end = span.as_ref().map_or(after_key, |s| s.end);
+                    vec![(after_key..end, String::new())]
+                }
+                (Some(span), _) if span.is_empty() && !text[..span.start].ends_with(' ') => {
+                    vec![(span.clone(), format!(" {}", value_text(value)?))]
+                }
+                (Some(span), _) => vec![(span.clone(), value_text(value)?)],
+                (None, _) => vec![(
+                    after_key..after_key,
+                    format!("{}{}", separator, value_text(value)?),
+                )],
+            }
+        }
+        Edit::Rename { path, key: new_key } => {
+            check_key(new_key)?;
+            match path.as_slice() {
+                [name] if is_section(name) => {
+                    if is_section(new_key) {
+                        return Err(format!("`{}` already exists", new_key));
+                    }
+                    sections(name)
+                        .into_iter()
+                        .map(|p| (p.name_span.clone(), new_key.clone()))
+                        .collect()
+                }
+                _ => {
+                    let (section, key) = entry_path(path)?;
+                    if !entries(section.as_deref(), new_key).is_empty() {
+                        return Err(format!("`{}` already exists", new_key));
+                    }
+                    entries(section.as_deref(), &key)
+                        .into_iter()
+                        .map(|e| (e.key_span.clone(), new_key.clone()))
+                        .collect()
+                }
+            }
+        }
+        // Every definition goes, or an earlier one would show through
+        Edit::Remove { path } => match path.as_slice() {
+            [name] if is_section(name) => sections(name)
+                .into_iter()
+                .map(|p| (p.start..p.end, String::new()))
+                .collect(),
+            _ => {
+                let (section, key) = entry_path(path)?;
+                entries(section.as_deref(), &key)
+                    .into_iter()
+                    .map(|e| (e.start..e.end, String::new()))
+                    .collect()
+            }
+        },
+        Edit::Insert { parent, key, value } => {
+            let key = key
+                .as_deref()
+                .ok_or_else(|| "a section entry needs a key".to_string())?;
+            check_key(key)?;
+            let line = || -> Result<String, String> {
+                Ok(match value {
+                    ConfigValue::Null => format!("{}\n", key),
+                    _ => format!("{}{}{}\n", key, separator, value_text(value)?),
+                })
+            };
+            match parent.as_slice() {
+                [] if matches!(value, ConfigValue::Table(_)) => {
+                    if is_section(key) {
+                        return Err(format!("`{}` already exists", key));
+                    }
+                    let gap = if text.is_empty() { "" } else { "\n" };
+                    vec![(text.len()..text.len(), format!("{}[{}]\n", gap, key))]
+                }
+                [] => {
+                    if !entries(None, key).is_empty() || is_section(key) {
+                        return Err(format!("`{}` already exists", key));
+                    }
+                    // After the last top-level entry, or above the first section
+                    let at = parts[0]
+                        .entries
+                        .last()
+                        .map(|e| e.end)
+                        .or(parts.get(1).map(|p| p.start))
+                        .unwrap_or(text.len());
+                    vec![(at..at, line()?)]
+                }
+                [section] if is_section(section) => {
+                    if !entries(Some(section), key).is_empty() {
+                        return Err(format!("`{}` already exists", key));
+                    }
+                    let parts = sections(section);
+                    let last = parts.last().expect("the section is there");
+                    let at = last
+                        .entries
+                        .last()
+                        .map_or(line_end(text, last.start), |e| e.end);
+                    vec![(at..at, line()?)]
+                }
+                _ => return Err(format!("`{}` is not a section", parent.join("."))),
+            }
+        }
+        Edit::Move { .. } => return Err("INI has no lists to reorder".to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ini() {
+        let tree = parse_ini(
+            "; global\nname = demo\n\n[server]\nhost = \"0.0.0.0\"\nport: 8080\n\
+             motd = Hello\n  world\n\n[flags]\nverbose\n\n[server]\nport = 9090\n",
+        )
+        .unwrap();
+        let ConfigValue::Table(root) = &tree.value else {
+            panic!("expected a table");
+        };
+        let keys: Vec<_> = root.iter().map(|n| n.key.as_str()).collect();
+        assert_eq!(keys, vec!["name", "server", "flags"]);
+
+        let server = root[1].value.children().unwrap();
+        let values: Vec<_> = server.iter().map(|n| n.value.display_value()).collect();
+        assert_eq!(values, vec!["\"0.0.0.0\"", "\"9090\"", "\"Hello\nworld\""]);
+        assert_eq!(server[1].path, vec!["server", "port"]);
+        assert!(matches!(
+            root[2].value.children().unwrap()[0].value,
+            ConfigValue::Null
+        ));
+
+        assert_eq!(parse_ini("[open\n").unwrap_err(), "line 1: missing `]`");
+        assert_eq!(parse_ini("= 1\n").unwrap_err(), "line 1: missing key");
+    }
+
+    #[test]
+    fn test_edit() {
+        let path = |path: &[&str]| path.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let content = "; global\nname=demo\n\n[server]\nhost = 0.0.0.0 \nport: 8080\n\n\
+                       [flags]\nverbose\n\n[server]\nport = 9090\n";
+        let edits = [
+            Edit::Set {
+                path: path(&["server", "port"]),
+                value: ConfigValue::Integer(80),
+            },
+            Edit::Set {
+                path: path(&["flags", "verbose"]),
+                value: ConfigValue::Boolean(true),
+            },
+            Edit::Rename {
+                path: path(&["server", "port"]),
+                key: "listen".to_string(),
+            },
+            Edit::Insert {
+                parent: path(&["server"]),
+                key: Some("motd".to_string()),
+                value: ConfigValue::String("Hello\nworld".to_string()),
+            },
+            Edit::Insert {
+                parent: vec![],
+                key: Some("version".to_string()),
+                value: ConfigValue::String(" 2 ".to_string()),
+            },
+            Edit::Remove {
+                path: path(&["flags"]),
+            },
+            Edit::Insert {
+                parent: vec![],
+                key: Some("extra".to_string()),
+                value: ConfigValue::Table(Vec::new()),
+            },
+        ];
+        let text = edits
+            .iter()
+            .fold(content.to_string(), |text, e| edit(&text, e).unwrap());
+        assert_eq!(
+            text,
+            "; global\nname=demo\nversion=\" 2 \"\n\n[server]\nhost = 0.0.0.0 \nlisten: 8080\n\n\n\
+             [server]\nlisten = 80\nmotd=Hello\n    world\n\n[extra]\n"
+        );
+
+        let tree = parse_ini(&text).unwrap();
+        let server = tree.value.children().unwrap()[2].value.children().unwrap();
+        let values: Vec<_> = server.iter().map(|n| n.value.display_value()).collect();
+        assert_eq!(values, vec!["\"0.0.0.0\"", "\"80\"", "\"Hello\nworld\""]);
+        assert_eq!(
+            edit(
+                &text,
+                &Edit::Move {
+                    path: path(&["server", "host"]),
+                    up: true
+                }
+            )
+            .unwrap_err(),
+            "INI has no lists to reorder"
+        );
+    }
+}
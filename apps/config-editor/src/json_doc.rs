@@ -0,0 +1,406 @@
+//! JSON edits on the text.
+//!
+//! A small parser notes where each value, key and entry sits, and edits
+//! splice new text in there. New entries copy the separators and
+//! indentation of their neighbours, so the file keeps its layout.
+
+use crate::edit::{index, neighbour, split_path, Edit};
+use crate::formats::ConfigValue;
+use std::ops::Range;
+
+/// A value and where it is in the text.
+struct Node {
+    span: Range<usize>,
+    kind: Kind,
+}
+
+enum Kind {
+    Scalar,
+    Array(Vec<Node>),
+    Object(Vec<Member>),
+}
+
+struct Member {
+    key: String,
+    key_span: Range<usize>,
+    value: Node,
+}
+
+impl Node {
+    /// Spans of the entries, from a member's key to the end of its value.
+    fn entry_spans(&self) -> Vec<Range<usize>> {
+        match &self.kind {
+            Kind::Scalar => Vec::new(),
+            Kind::Array(items) => items.iter().map(|n| n.span.clone()).collect(),
+            Kind::Object(members) => members
+                .iter()
+                .map(|m| m.key_span.start..m.value.span.end)
+                .collect(),
+        }
+    }
+
+    /// Index of the entry `key`; the last of repeated keys, which is the
+    /// one that counts.
+    fn position(&self, key: &str) -> Result<usize, String> {
+        let found = match &self.kind {
+            Kind::Scalar => None,
+            Kind::Array(items) => Some(index(key)?).filter(|i| *i < items.len()),
+            Kind::Object(members) => members.iter().rposition(|m| m.key == key),
+        };
+        found.ok_or_else(|| format!("no entry `{}`", key))
+    }
+
+    fn child(&self, i: usize) -> &Node {
+        match &self.kind {
+            Kind::Array(items) => &items[i],
+            Kind::Object(members) => &members[i].value,
+            Kind::Scalar => unreachable!("scalars have no entries"),
+        }
+    }
+
+    fn find(&self, path: &[String]) -> Result<&Node, String> {
+        path.iter()
+            .try_fold(self, |node, key| Ok(node.child(node.position(key)?)))
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t', '\n', '\r']).len();
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn error(&self) -> String {
+        format!("invalid JSON at byte {}", self.pos)
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() != Some(byte) {
+            return Err(self.error());
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Node, String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let kind = match self.peek() {
+            Some(b'[') => Kind::Array(self.entries(b']', Parser::value)?),
+            Some(b'{') => Kind::Object(self.entries(b'}', Parser::member)?),
+            Some(b'"') => {
+                self.string()?;
+                Kind::Scalar
+            }
+            Some(_) => {
+                let rest = &self.text[self.pos..];
+                let len = rest
+                    .find([',', ']', '}', ' ', '\t', '\n', '\r'])
+                    .unwrap_or(rest.len());
+                if len == 0 {
+                    return Err(self.error());
+                }
+                self.pos += len;
+                Kind::Scalar
+            }
+            None => return Err(self.error()),
+        };
+        Ok(Node {
+            span: start..self.pos,
+            kind,
+        })
+    }
+
+    /// The comma-separated entries of an array or object.
+    fn entries<T>(
+        &mut self,
+        close: u8,
+        mut entry: impl FnMut(&mut Self) -> Result<T, String>,
+    ) -> Result<Vec<T>, String> {
+        self.pos += 1;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            return Ok(entries);
+        }
+        loop {
+            entries.push(entry(self)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    return Ok(entries);
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    fn member(&mut self) -> Result<Member, String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let key = self.string()?;
+        let key_span = start..self.pos;
+        self.expect(b':')?;
+        Ok(Member {
+            key,
+            key_span,
+            value: self.value()?,
+        })
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        if self.peek() != Some(b'"') {
+            return Err(self.error());
+        }
+        let mut escaped = false;
+        for (i, c) in self.text[start + 1..].char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    self.pos = start + i + 2;
+                    return serde_json::from_str(&self.text[start..self.pos])
+                        .map_err(|e| e.to_string());
+                }
+                _ => {}
+            }
+        }
+        Err(self.error())
+    }
+}
+
+fn parse(text: &str) -> Result<Node, String> {
+    let mut parser = Parser { text, pos: 0 };
+    let root = parser.value()?;
+    parser.skip_whitespace();
+    match parser.peek() {
+        None => Ok(root),
+        Some(_) => Err(parser.error()),
+    }
+}
+
+fn render(value: &ConfigValue) -> Result<String, String> {
+    Ok(match value {
+        ConfigValue::String(s) => serde_json::to_string(s).map_err(|e| e.to_string())?,
+        ConfigValue::Integer(i) => i.to_string(),
+        ConfigValue::Float(f) if f.is_finite() => {
+            serde_json::to_string(f).map_err(|e| e.to_string())?
+        }
+        ConfigValue::Float(_) => return Err("JSON numbers must be finite".to_string()),
+        ConfigValue::Boolean(b) => b.to_string(),
+        ConfigValue::Null => "null".to_string(),
+        ConfigValue::Array(_) => "[]".to_string(),
+        ConfigValue::Table(_) | ConfigValue::Documents(_) => "{}".to_string(),
+    })
+}
+
+pub fn edit(content: &str, edit: &Edit) -> Result<String, String> {
+    let root = parse(content)?;
+    let mut text = content.to_string();
+    match edit {
+        Edit::Set { path, value } => {
+            let node = root.find(path)?;
+            text.replace_range(node.span.clone(), &render(value)?);
+        }
+        Edit::Rename { path, key: new_key } => {
+            let (parent, key) = split_path(path)?;
+            let Kind::Object(members) = &root.find(parent)?.kind else {
+                return Err("array items have no key".to_string());
+            };
+            if members.iter().any(|m| m.key == *new_key) {
+                return Err(format!("`{}` already exists", new_key));
+            }
+            let member = members
+                .iter()
+                .rfind(|m| m.key == key)
+                .ok_or_else(|| format!("no entry `{}`", key))?;
+            let quoted = serde_json::to_string(new_key).map_err(|e| e.to_string())?;
+            text.replace_range(member.key_span.clone(), &quoted);
+        }
+        Edit::Insert { parent, key, value } => {
+            let container = root.find(parent)?;
+            let entry = match (&container.kind, key) {
+                (Kind::Object(members), Some(key)) => {
+                    if members.iter().any(|m| m.key == *key) {
+                        return Err(format!("`{}` already exists", key));
+                    }
+                    let colon = members
+                        .first()
+                        .map(|m| &content[m.key_span.end..m.value.span.start])
+                        .unwrap_or(": ");
+                    let quoted = serde_json::to_string(key).map_err(|e| e.to_string())?;
+                    format!("{}{}{}", quoted, colon, render(value)?)
+                }
+                (Kind::Array(_), None) => render(value)?,
+                (Kind::Object(_), None) => return Err("a table entry needs a key".to_string()),
+                (Kind::Array(_), Some(_)) => return Err("array items have no key".to_string()),
+                (Kind::Scalar, _) => {
+                    return Err(format!("`{}` is not a table or array", parent.join(".")))
+                }
+            };
+            insert(&mut text, container, &entry);
+        }
+        Edit::Remove { path } => {
+            let (parent, key) = split_path(path)?;
+            let container = root.find(parent)?;
+            let i = container.position(key)?;
+            let spans = container.entry_spans();
+            // Take a separator with the entry: the one after it, or before
+            // it for the last entry
+            let range = if spans.len() == 1 {
+                container.span.start + 1..container.span.end - 1
+            } else if i + 1 < spans.len() {
+                spans[i].start..spans[i + 1].start
+            } else {
+                spans[i - 1].end..spans[i].end
+            };
+            text.replace_range(range, "");
+        }
+        Edit::Move { path, up } => {
+            let (parent, key) = split_path(path)?;
+            let container = root.find(parent)?;
+            if !matches!(container.kind, Kind::Array(_)) {
+                return Err("only array items can be moved".to_string());
+            }
+            let i = container.position(key)?;
+            let spans = container.entry_spans();
+            let j = neighbour(i, spans.len(), *up)?;
+            let (first, second) = (&spans[i.min(j)], &spans[i.max(j)]);
+            text = format!(
+                "{}{}{}{}{}",
+                &content[..first.start],
+                &content[second.clone()],
+                &content[first.end..second.start],
+                &content[first.clone()],
+                &content[second.end..]
+            );
+        }
+    }
+    Ok(text)
+}
+
+/// Add `entry` at the end of `container`, separated like the entries
+/// already there.
+fn insert(text: &mut String, container: &Node, entry: &str) {
+    let spans = container.entry_spans();
+    let open = container.span.start + 1;
+    match spans.as_slice() {
+        [] => {
+            let inner = open..container.span.end - 1;
+            if text.contains('\n') {
+                let line_start = text[..container.span.start]
+                    .rfind('\n')
+                    .map_or(0, |i| i + 1);
+                let line = &text[line_start..container.span.start];
+                let indent = &line[..line.len() - line.trim_start().len()];
+                let inside = format!("\n{}{}{}\n{}", indent, indent_unit(text), entry, indent);
+                text.replace_range(inner, &inside);
+            } else {
+                text.replace_range(inner, entry);
+            }
+        }
+        [only] => {
+            let before = &text[open..only.start];
+            let separator = match before.rfind('\n') {
+                Some(i) => format!(",{}", &before[i..]),
+                None => ", ".to_string(),
+            };
+            text.insert_str(only.end, &format!("{}{}", separator, entry));
+        }
+        [.., previous, last] => {
+            let separator = text[previous.end..last.start].to_string();
+            text.insert_str(last.end, &format!("{}{}", separator, entry));
+        }
+    }
+}
+
+/// The document's indentation step: its shallowest indented line.
+fn indent_unit(text: &str) -> &str {
+    text.lines()
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .filter(|indent| !indent.is_empty())
+        .min_by_key(|indent| indent.len())
+        .unwrap_or("  ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(path: &str) -> Vec<String> {
+        path.split('.').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_edit_keeps_layout() {
+        let content = "{\n    \"name\": \"demo\",\n    \"tags\": [\"a\", \"b\", \"c\"],\n    \
+                       \"server\": {\"port\": 8080},\n    \"extra\": {}\n}";
+        let edits = [
+            Edit::Set {
+                path: path("server.port"),
+                value: ConfigValue::Integer(9090),
+            },
+            Edit::Rename {
+                path: path("name"),
+                key: "title".to_string(),
+            },
+            Edit::Insert {
+                parent: path("server"),
+                key: Some("tls".to_string()),
+                value: ConfigValue::Boolean(true),
+            },
+            Edit::Insert {
+                parent: path("extra"),
+                key: Some("debug".to_string()),
+                value: ConfigValue::Null,
+            },
+            Edit::Move {
+                path: path("tags.0"),
+                up: false,
+            },
+            Edit::Remove {
+                path: path("tags.2"),
+            },
+            Edit::Insert {
+                parent: vec![],
+                key: Some("version".to_string()),
+                value: ConfigValue::String("1.0".to_string()),
+            },
+        ];
+        let text = edits
+            .iter()
+            .fold(content.to_string(), |text, e| edit(&text, e).unwrap());
+        assert_eq!(
+            text,
+            "{\n    \"title\": \"demo\",\n    \"tags\": [\"b\", \"a\"],\n    \
+             \"server\": {\"port\": 9090, \"tls\": true},\n    \"extra\": {\n        \
+             \"debug\": null\n    },\n    \"version\": \"1.0\"\n}"
+        );
+        assert!(serde_json::from_str::<serde_json::Value>(&text).is_ok());
+
+        let removed = edit("[1]", &Edit::Remove { path: path("0") }).unwrap();
+        assert_eq!(removed, "[]");
+        let err = edit(
+            content,
+            &Edit::Move {
+                path: path("name"),
+                up: true,
+            },
+        );
+        assert_eq!(err.unwrap_err(), "only array items can be moved");
+    }
+}
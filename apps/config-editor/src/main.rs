@@ -2,18 +2,27 @@
 //! Config Editor - Configuration file editor TUI.
 //!
 //! Features:
-//! - Support for TOML, JSON, YAML, INI
+//! - Support for TOML, JSON, YAML, INI and .env
+//! - Conversion between formats
 //! - Syntax highlighting
 //! - Schema validation
-//! - Tree view navigation
+//! - Tree view navigation and editing that keeps comments
 
 mod app;
 mod config;
+mod convert;
+mod dotenv;
+mod edit;
 mod formats;
+mod ini;
+mod json_doc;
+mod toml_doc;
 mod ui;
+mod yaml_doc;
 
 use anyhow::Result;
 use app::App;
+use formats::ConfigFormat;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
@@ -24,6 +33,9 @@ use std::{env, io, time::Duration};
 
 fn main() -> Result<()> {
     let args: Vec<String> = env::args().collect();
+    if args.get(1).map(String::as_str) == Some("convert") {
+        return run_convert(&args[2..]);
+    }
     let file_path = args.get(1).cloned();
 
     enable_raw_mode()?;
@@ -73,3 +85,29 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &mut A
         }
     }
 }
+
+/// `config-editor convert FILE FORMAT`
+///
+/// Prints FILE converted to FORMAT (toml, json, yaml, ini or env), with
+/// anything that had to be flattened reported on stderr.
+fn run_convert(args: &[String]) -> Result<()> {
+    let [file, target] = args else {
+        anyhow::bail!("usage: config-editor convert FILE toml|json|yaml|ini|env");
+    };
+    let path = std::path::Path::new(file);
+    let to = ConfigFormat::from_name(target)
+        .ok_or_else(|| anyhow::anyhow!("unknown format {}", target))?;
+    let from = ConfigFormat::from_path(path);
+    let content = std::fs::read_to_string(path)?;
+    let tree = from
+        .parse(&content)
+        .ok_or_else(|| anyhow::anyhow!("can't tell the format of {}", file))?
+        .map_err(anyhow::Error::msg)?;
+
+    let converted = convert::convert(&tree, to).map_err(anyhow::Error::msg)?;
+    print!("{}", converted.text);
+    for warning in &converted.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    Ok(())
+}
@@ -0,0 +1,391 @@
+//! TOML edits through `toml_edit`, whose document keeps each key's and
+//! value's formatting, so only what changes is rewritten.
+
+use crate::edit::{index, neighbour, split_path, Edit};
+use crate::formats::ConfigValue;
+use toml_edit::{Array, ArrayOfTables, DocumentMut, InlineTable, Item, Key, Table, Value};
+
+/// A table or array on the way down a path.
+enum Container<'a> {
+    Table(&'a mut Table),
+    Inline(&'a mut InlineTable),
+    Array(&'a mut Array),
+    /// `[[name]]` tables
+    Tables(&'a mut ArrayOfTables),
+}
+
+pub fn edit(content: &str, edit: &Edit) -> Result<String, String> {
+    let mut doc: DocumentMut = content
+        .parse()
+        .map_err(|e: toml_edit::TomlError| e.to_string())?;
+    match edit {
+        Edit::Set { path, value } => {
+            let (parent, key) = split_path(path)?;
+            set(container(&mut doc, parent)?, key, value)?;
+        }
+        Edit::Rename { path, key: new_key } => {
+            let (parent, key) = split_path(path)?;
+            rename(container(&mut doc, parent)?, key, new_key)?;
+        }
+        Edit::Insert { parent, key, value } => {
+            insert(container(&mut doc, parent)?, key.as_deref(), value)?;
+        }
+        Edit::Remove { path } => {
+            let (parent, key) = split_path(path)?;
+            remove(container(&mut doc, parent)?, key)?;
+        }
+        Edit::Move { path, up } => {
+            let (parent, key) = split_path(path)?;
+            move_item(container(&mut doc, parent)?, key, *up)?;
+        }
+    }
+    Ok(doc.to_string())
+}
+
+fn container<'a>(doc: &'a mut DocumentMut, path: &[String]) -> Result<Container<'a>, String> {
+    let mut current = Container::Table(doc.as_table_mut());
+    for key in path {
+        let not_container = || format!("`{}` is not a table or array", key);
+        current = match current {
+            Container::Table(t) => item_container(t.get_mut(key).ok_or_else(|| missing(key))?),
+            Container::Inline(t) => value_container(t.get_mut(key).ok_or_else(|| missing(key))?),
+            Container::Array(a) => {
+                value_container(a.get_mut(index(key)?).ok_or_else(|| missing(key))?)
+            }
+            Container::Tables(a) => Some(Container::Table(
+                a.get_mut(index(key)?).ok_or_else(|| missing(key))?,
+            )),
+        }
+        .ok_or_else(not_container)?;
+    }
+    Ok(current)
+}
+
+fn item_container(item: &mut Item) -> Option<Container<'_>> {
+    match item {
+        Item::Table(t) => Some(Container::Table(t)),
+        Item::ArrayOfTables(a) => Some(Container::Tables(a)),
+        Item::Value(v) => value_container(v),
+        Item::None => None,
+    }
+}
+
+fn value_container(value: &mut Value) -> Option<Container<'_>> {
+    match value {
+        Value::InlineTable(t) => Some(Container::Inline(t)),
+        Value::Array(a) => Some(Container::Array(a)),
+        _ => None,
+    }
+}
+
+fn missing(key: &str) -> String {
+    format!("no entry `{}`", key)
+}
+
+/// A new value; containers come out empty, as the prompts only make empty
+/// ones.
+fn to_value(value: &ConfigValue) -> Result<Value, String> {
+    Ok(match value {
+        ConfigValue::String(s) => Value::from(s.as_str()),
+        ConfigValue::Integer(i) => Value::from(*i),
+        ConfigValue::Float(f) => Value::from(*f),
+        ConfigValue::Boolean(b) => Value::from(*b),
+        ConfigValue::Array(_) => Value::Array(Array::new()),
+        ConfigValue::Table(_) | ConfigValue::Documents(_) => Value::InlineTable(InlineTable::new()),
+        ConfigValue::Null => return Err("TOML has no null".to_string()),
+    })
+}
+
+fn set(container: Container, key: &str, value: &ConfigValue) -> Result<(), String> {
+    let mut new = to_value(value)?;
+    match container {
+        Container::Table(t) => match t.get_mut(key).ok_or_else(|| missing(key))? {
+            // Keep the spacing and any comment after the old value
+            Item::Value(old) => {
+                *new.decor_mut() = old.decor().clone();
+                *old = new;
+            }
+            other => *other = Item::Value(new),
+        },
+        Container::Inline(t) => {
+            let old = t.get_mut(key).ok_or_else(|| missing(key))?;
+            *new.decor_mut() = old.decor().clone();
+            *old = new;
+        }
+        Container::Array(a) => {
+            let i = index(key)?;
+            if i >= a.len() {
+                return Err(missing(key));
+            }
+            a.replace(i, new);
+        }
+        Container::Tables(_) => return Err("an array of tables holds only tables".to_string()),
+    }
+    Ok(())
+}
+
+/// Rename by taking out the entry and those after it and putting them back,
+/// as the tables keep insertion order.
+fn rename(container: Container, key: &str, new_key: &str) -> Result<(), String> {
+    if key == new_key {
+        return Ok(());
+    }
+    let renamed = |old: &Key| {
+        Key::new(new_key)
+            .with_leaf_decor(old.leaf_decor().clone())
+            .with_dotted_decor(old.dotted_decor().clone())
+    };
+    match container {
+        Container::Table(t) => {
+            if t.contains_key(new_key) {
+                return Err(format!("`{}` already exists", new_key));
+            }
+            let keys: Vec<String> = t.iter().map(|(k, _)| k.to_string()).collect();
+            let at = keys
+                .iter()
+                .position(|k| k == key)
+                .ok_or_else(|| missing(key))?;
+            let entries: Vec<(Key, Item)> = keys[at..]
+                .iter()
+                .filter_map(|k| t.remove_entry(k))
+                .collect();
+            for (i, (k, item)) in entries.into_iter().enumerate() {
+                let k = if i == 0 { renamed(&k) } else { k };
+                t.insert_formatted(&k, item);
+            }
+        }
+        Container::Inline(t) => {
+            if t.contains_key(new_key) {
+                return Err(format!("`{}` already exists", new_key));
+            }
+            let keys: Vec<String> = t.iter().map(|(k, _)| k.to_string()).collect();
+            let at = keys
+                .iter()
+                .position(|k| k == key)
+                .ok_or_else(|| missing(key))?;
+            let entries: Vec<(Key, Value)> = keys[at..]
+                .iter()
+                .filter_map(|k| t.remove_entry(k))
+                .collect();
+            for (i, (k, value)) in entries.into_iter().enumerate() {
+                let k = if i == 0 { renamed(&k) } else { k };
+                t.insert_formatted(&k, value);
+            }
+        }
+        Container::Array(_) | Container::Tables(_) => {
+            return Err("array items have no key".to_string())
+        }
+    }
+    Ok(())
+}
+
+fn insert(container: Container, key: Option<&str>, value: &ConfigValue) -> Result<(), String> {
+    match (container, key) {
+        (Container::Table(t), Some(key)) => {
+            if t.contains_key(key) {
+                return Err(format!("`{}` already exists", key));
+            }
+            // An empty table gets a `[section]` of its own
+            let item = match value {
+                ConfigValue::Table(_) => Item::Table(Table::new()),
+                _ => Item::Value(to_value(value)?),
+            };
+            t.insert(key, item);
+        }
+        (Container::Inline(t), Some(key)) => {
+            if t.contains_key(key) {
+                return Err(format!("`{}` already exists", key));
+            }
+            t.insert(key, to_value(value)?);
+        }
+        (Container::Array(a), None) => {
+            let mut new = to_value(value)?;
+            // Lay the item out like the last one, leaving out its comments
+            match a
+                .iter()
+                .last()
+                .and_then(|last| last.decor().prefix()?.as_str())
+            {
+                Some(prefix) => {
+                    let prefix = match prefix.rsplit_once('\n') {
+                        Some((_, indent)) => format!("\n{}", indent),
+                        // The first item of `[a, b]` has no space before it
+                        None if a.len() == 1 && prefix.is_empty() => " ".to_string(),
+                        None => prefix.to_string(),
+                    };
+                    new.decor_mut().set_prefix(prefix);
+                    a.push_formatted(new);
+                }
+                None => a.push(new),
+            }
+        }
+        (Container::Tables(a), None) => match value {
+            ConfigValue::Table(_) => a.push(Table::new()),
+            _ => return Err("an array of tables holds only tables".to_string()),
+        },
+        (Container::Table(_) | Container::Inline(_), None) => {
+            return Err("a table entry needs a key".to_string())
+        }
+        (Container::Array(_) | Container::Tables(_), Some(_)) => {
+            return Err("array items have no key".to_string())
+        }
+    }
+    Ok(())
+}
+
+fn remove(container: Container, key: &str) -> Result<(), String> {
+    match container {
+        Container::Table(t) => t.remove(key).map(drop).ok_or_else(|| missing(key)),
+        Container::Inline(t) => t.remove(key).map(drop).ok_or_else(|| missing(key)),
+        Container::Array(a) => {
+            let i = index(key)?;
+            if i >= a.len() {
+                return Err(missing(key));
+            }
+            let removed = a.remove(i);
+            // The new first item takes the old one's place right after `[`
+            if i == 0 {
+                let prefix = removed.decor().prefix().cloned().unwrap_or_default();
+                if let Some(first) = a.get_mut(0) {
+                    first.decor_mut().set_prefix(prefix);
+                }
+            }
+            Ok(())
+        }
+        Container::Tables(a) => {
+            let i = index(key)?;
+            if i >= a.len() {
+                return Err(missing(key));
+            }
+            a.remove(i);
+            Ok(())
+        }
+    }
+}
+
+fn move_item(container: Container, key: &str, up: bool) -> Result<(), String> {
+    let i = index(key)?;
+    match container {
+        Container::Array(a) => {
+            let x = a.get(i).cloned().ok_or_else(|| missing(key))?;
+            let j = neighbour(i, a.len(), up)?;
+            let y = a.get(j).cloned().ok_or_else(|| missing(key))?;
+            // Comments above an item go with it; plain spacing stays put
+            let commented = |v: &Value| {
+                v.decor()
+                    .prefix()
+                    .and_then(|p| p.as_str())
+                    .is_some_and(|p| p.contains('#'))
+            };
+            if commented(&x) || commented(&y) {
+                a.replace_formatted(i, y);
+                a.replace_formatted(j, x);
+            } else {
+                a.replace(i, y);
+                a.replace(j, x);
+            }
+        }
+        Container::Tables(a) => {
+            let mut tables: Vec<Table> = a.iter().cloned().collect();
+            if i >= tables.len() {
+                return Err(missing(key));
+            }
+            let j = neighbour(i, tables.len(), up)?;
+            let (at_i, at_j) = (tables[i].position(), tables[j].position());
+            tables.swap(i, j);
+            // Tables are written in document order, so trade places there too
+            if let (Some(at_i), Some(at_j)) = (at_i, at_j) {
+                tables[i].set_position(at_i);
+                tables[j].set_position(at_j);
+            }
+            a.clear();
+            for table in tables {
+                a.push(table);
+            }
+        }
+        Container::Table(_) | Container::Inline(_) => {
+            return Err("only array items can be moved".to_string())
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(path: &str) -> Vec<String> {
+        path.split('.').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_edit_keeps_formatting() {
+        let content = "# Server settings\n[server]\nhost   = \"localhost\"  # dev only\nport = 8080\n\
+                       ports = [80, 443, 8443]\n\n[[users]]\nname = \"ann\"\n\n[[users]]\nname = \"bo\"\n";
+        let edits = [
+            Edit::Set {
+                path: path("server.port"),
+                value: ConfigValue::Integer(9090),
+            },
+            Edit::Set {
+                path: path("server.host"),
+                value: ConfigValue::String("0.0.0.0".to_string()),
+            },
+            Edit::Rename {
+                path: path("server.host"),
+                key: "address".to_string(),
+            },
+            Edit::Insert {
+                parent: path("server"),
+                key: Some("tls".to_string()),
+                value: ConfigValue::Boolean(true),
+            },
+            Edit::Remove {
+                path: path("server.ports.0"),
+            },
+            Edit::Move {
+                path: path("server.ports.1"),
+                up: true,
+            },
+            Edit::Move {
+                path: path("users.0"),
+                up: false,
+            },
+        ];
+        let text = edits
+            .iter()
+            .fold(content.to_string(), |text, e| edit(&text, e).unwrap());
+        assert_eq!(
+            text,
+            "# Server settings\n[server]\naddress   = \"0.0.0.0\"  # dev only\nport = 9090\n\
+             ports = [8443, 443]\ntls = true\n\n[[users]]\nname = \"bo\"\n\n[[users]]\nname = \"ann\"\n"
+        );
+
+        let err = edit(
+            &text,
+            &Edit::Rename {
+                path: path("server.port"),
+                key: "tls".to_string(),
+            },
+        );
+        assert_eq!(err.unwrap_err(), "`tls` already exists");
+        let err = edit(
+            &text,
+            &Edit::Set {
+                path: path("server.port"),
+                value: ConfigValue::Null,
+            },
+        );
+        assert_eq!(err.unwrap_err(), "TOML has no null");
+
+        let text = edit(
+            "a = [1]\n",
+            &Edit::Insert {
+                parent: path("a"),
+                key: None,
+                value: ConfigValue::Integer(2),
+            },
+        );
+        assert_eq!(text.unwrap(), "a = [1, 2]\n");
+    }
+}
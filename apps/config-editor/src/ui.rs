@@ -220,7 +220,7 @@ fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(info_widget, chunks[0]);
 
     let msg = app.message.clone().unwrap_or_else(|| {
-        "? help | e edit | o open | Ctrl+S save".to_string()
+        "? help | e edit | o open | c convert | Ctrl+S save".to_string()
     });
     let msg_widget = Paragraph::new(msg)
         .block(Block::default().borders(Borders::ALL));
@@ -231,10 +231,19 @@ fn draw_input_dialog(f: &mut Frame, app: &App) {
     let area = centered_rect(60, 15, f.area());
     f.render_widget(Clear, area);
 
+    let target = match app.edit_path.join(".") {
+        path if path.is_empty() => "top level".to_string(),
+        path => path,
+    };
     let title = match app.input_mode {
-        InputMode::OpenFile => " Open File ",
-        InputMode::SaveAs => " Save As ",
-        InputMode::None => " Input ",
+        InputMode::OpenFile => " Open File ".to_string(),
+        InputMode::SaveAs => " Save As ".to_string(),
+        InputMode::Convert => " Convert to (toml, json, yaml, ini, env) ".to_string(),
+        InputMode::EditValue => format!(" Value of {} ", target),
+        InputMode::RenameKey => format!(" Rename {} ", target),
+        InputMode::AddEntry if app.adding_to_array() => format!(" Add to {} (value) ", target),
+        InputMode::AddEntry => format!(" Add to {} (key = value) ", target),
+        InputMode::None => " Input ".to_string(),
     };
 
     let input = Paragraph::new(app.input_buffer.as_str())
@@ -262,14 +271,14 @@ fn draw_quit_confirm(f: &mut Frame) {
 }
 
 fn draw_help(f: &mut Frame) {
-    let area = centered_rect(60, 60, f.area());
+    let area = centered_rect(60, 70, f.area());
     f.render_widget(Clear, area);
 
     let help_text = vec![
         Line::from(Span::styled("Navigation", Style::default().add_modifier(Modifier::BOLD))),
         Line::from("  j/k          Move up/down"),
         Line::from("  Tab          Switch pane"),
-        Line::from("  Enter        Expand/collapse (tree)"),
+        Line::from("  Enter        Expand/collapse or edit value (tree)"),
         Line::from(""),
         Line::from(Span::styled("Actions", Style::default().add_modifier(Modifier::BOLD))),
         Line::from("  e            Edit mode"),
@@ -277,8 +286,15 @@ fn draw_help(f: &mut Frame) {
         Line::from("  Ctrl+S       Save"),
         Line::from("  Ctrl+Shift+S Save as"),
         Line::from("  r            Refresh/reparse"),
+        Line::from("  c            Convert to another format"),
         Line::from("  Esc          Exit edit mode"),
         Line::from(""),
+        Line::from(Span::styled("Tree", Style::default().add_modifier(Modifier::BOLD))),
+        Line::from("  n            Rename key"),
+        Line::from("  a            Add entry"),
+        Line::from("  x            Delete entry"),
+        Line::from("  J/K          Move array item down/up"),
+        Line::from(""),
         Line::from(Span::styled("Other", Style::default().add_modifier(Modifier::BOLD))),
         Line::from("  q            Quit"),
     ];
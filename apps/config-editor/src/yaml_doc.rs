@@ -0,0 +1,654 @@
+//! YAML edits on the text.
+//!
+//! The block layout is read line by line: an entry runs from its key or
+//! `-` to the next line indented no deeper. Values are replaced in place,
+//! keeping anchors, tags and comments beside them, and new entries take
+//! the indentation of the block they join. Flow collections (`[a, b]`,
+//! `{a: 1}`) are single values here, except that empty ones can be filled,
+//! which turns them into blocks. An anchor (`&name`) still used by an
+//! alias elsewhere can't be removed or overwritten.
+
+use crate::edit::{edit_lines, index, line_end, lines, neighbour, split_path, Edit, Splice};
+use crate::formats::{parse_yaml, yaml_key, ConfigValue};
+use serde_yaml::Value as YamlValue;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Map,
+    Seq,
+}
+
+struct Block {
+    kind: Kind,
+    indent: usize,
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    key: String,
+    key_span: Range<usize>,
+    /// Start of the entry's first line, or of the comment lines just above
+    start: usize,
+    /// Where the key or `-` is, and its column
+    head: usize,
+    column: usize,
+    /// Whether the entry shares its first line with its parent's `-`
+    inline: bool,
+    /// Just past the `:` or `-` and any anchor or tag
+    header_end: usize,
+    /// Past the `\n` of the entry's last line
+    end: usize,
+    value: Value,
+}
+
+enum Value {
+    /// A scalar or flow collection, without its comment
+    Inline(Range<usize>),
+    Block(Block),
+    /// Nothing, which is null
+    Empty,
+}
+
+struct Document {
+    root: Value,
+    /// Where lines go when the document is empty
+    end: usize,
+}
+
+type Lines<'a> = [(usize, &'a str)];
+
+fn significant(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with('#')
+}
+
+fn indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn is_dash(text: &str) -> bool {
+    text == "-" || text.starts_with("- ") || text.starts_with("-\t")
+}
+
+fn is_marker(line: &str) -> bool {
+    line == "---" || line.starts_with("--- ") || line.starts_with("---\t") || line == "..."
+}
+
+/// `text` without a trailing comment; `#` only starts one after a space,
+/// and not inside a quoted scalar or flow collection.
+fn strip_comment(text: &str) -> &str {
+    let quoting = text.starts_with(['"', '\'', '[', '{']);
+    let mut quote = None;
+    let mut escaped = false;
+    let mut after_space = true;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '#' && after_space => return text[..i].trim_end(),
+            None if quoting && (c == '"' || c == '\'') => quote = Some(c),
+            None => {}
+        }
+        after_space = c == ' ' || c == '\t';
+    }
+    text.trim_end()
+}
+
+/// Offset just past the quote closing the scalar `text` starts with.
+fn closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1).peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if quote == '"' => {
+                chars.next();
+            }
+            // `''` is an escaped quote in single quotes
+            '\'' if quote == '\'' && chars.peek().is_some_and(|(_, c)| *c == '\'') => {
+                chars.next();
+            }
+            c if c == quote => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The key's length and the offset of its `:`, for `key: value` text.
+fn split_key(text: &str) -> Option<(usize, usize)> {
+    let colon_at = |i: usize| {
+        text[i..].starts_with(':')
+            && (text[i + 1..].is_empty() || text[i + 1..].starts_with([' ', '\t']))
+    };
+    match text.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let key_end = closing_quote(text, quote)?;
+            let after = &text[key_end..];
+            let colon = key_end + after.len() - after.trim_start().len();
+            colon_at(colon).then_some((key_end, colon))
+        }
+        '[' | '{' | '#' | '|' | '>' | '?' | '*' | '&' | '!' | '%' | '@' | '`' => None,
+        _ => {
+            let colon = (0..strip_comment(text).len()).find(|i| colon_at(*i))?;
+            let len = text[..colon].trim_end().len();
+            (len > 0).then_some((len, colon))
+        }
+    }
+}
+
+/// A key as the tree shows it.
+fn decode_key(raw: &str) -> String {
+    serde_yaml::from_str::<YamlValue>(raw)
+        .map(|key| yaml_key(&key))
+        .unwrap_or_else(|_| raw.to_string())
+}
+
+/// The block whose first entry is at `column` of line `first`, running at
+/// most to line `end`.
+fn parse_block(lines: &Lines, first: usize, column: usize, end: usize) -> Result<Block, String> {
+    let kind = if is_dash(&lines[first].1[column..]) {
+        Kind::Seq
+    } else {
+        Kind::Map
+    };
+    let starts_entry = |line: &str| (kind == Kind::Seq) == is_dash(&line[column..]);
+    let mut entries = Vec::new();
+    let mut i = first;
+    loop {
+        // The entry runs to the next line at its column or less; a map's
+        // list may sit at the column of its key
+        let mut last = i;
+        let mut next = end;
+        for (j, (_, line)) in lines.iter().enumerate().take(end).skip(i + 1) {
+            if !significant(line) {
+                continue;
+            }
+            let at = indent(line);
+            if at < column || (at == column && (kind == Kind::Seq || !is_dash(&line[column..]))) {
+                next = j;
+                break;
+            }
+            last = j;
+        }
+        entries.push(parse_entry(lines, kind, i, column, last + 1)?);
+        if next == end || indent(lines[next].1) != column || !starts_entry(lines[next].1) {
+            break;
+        }
+        i = next;
+    }
+    Ok(Block {
+        kind,
+        indent: column,
+        entries,
+    })
+}
+
+fn parse_entry(
+    lines: &Lines,
+    kind: Kind,
+    i: usize,
+    column: usize,
+    end: usize,
+) -> Result<Entry, String> {
+    let (line_start, line) = lines[i];
+    let head = line_start + column;
+    let (key, key_span, mut at) = match kind {
+        Kind::Seq => (String::new(), head..head, column + 1),
+        Kind::Map => {
+            let (len, colon) = split_key(&line[column..])
+                .ok_or_else(|| format!("line {}: expected `key: value`", i + 1))?;
+            let key = decode_key(&line[column..column + len]);
+            (key, head..head + len, column + colon + 1)
+        }
+    };
+    // Anchors and tags stay with the entry
+    loop {
+        let rest = &line[at..];
+        let trimmed = rest.trim_start();
+        if !trimmed.starts_with(['&', '!']) {
+            break;
+        }
+        at += rest.len() - trimmed.len() + trimmed.find([' ', '\t']).unwrap_or(trimmed.len());
+    }
+    let header_end = line_start + at;
+    at += line[at..].len() - line[at..].trim_start().len();
+    let text = strip_comment(&line[at..]);
+
+    let inline = column > indent(line);
+    let mut first_line = i;
+    while !inline
+        && first_line > 0
+        && lines[first_line - 1].1.trim_start().starts_with('#')
+        && indent(lines[first_line - 1].1) == column
+    {
+        first_line -= 1;
+    }
+    let (last_start, last_line) = lines[end - 1];
+
+    let value = if text.is_empty() {
+        match (i + 1..end).find(|j| significant(lines[*j].1)) {
+            Some(k) => Value::Block(parse_block(lines, k, indent(lines[k].1), end)?),
+            None => Value::Empty,
+        }
+    } else if is_dash(text) || (kind == Kind::Seq && split_key(text).is_some()) {
+        // `- - item` or `- key: value`: a block starting on this line
+        Value::Block(parse_block(lines, i, at, end)?)
+    } else if end > i + 1 {
+        // Block scalars and plain scalars carried over more lines
+        Value::Inline(line_start + at..last_start + last_line.trim_end().len())
+    } else {
+        Value::Inline(line_start + at..line_start + at + text.len())
+    };
+
+    Ok(Entry {
+        key,
+        key_span,
+        start: lines[first_line].0,
+        head,
+        column,
+        inline,
+        header_end,
+        end: last_start + last_line.len() + 1,
+        value,
+    })
+}
+
+fn documents(text: &str, lines: &Lines) -> Result<Vec<Document>, String> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for (i, (_, line)) in lines.iter().enumerate() {
+        if is_marker(line) {
+            ranges.push(start..i);
+            start = i + 1;
+        }
+    }
+    ranges.push(start..lines.len());
+
+    let content = |range: &Range<usize>| {
+        range
+            .clone()
+            .find(|i| significant(lines[*i].1) && !lines[*i].1.starts_with('%'))
+    };
+    let mut documents = Vec::new();
+    for range in ranges.iter().filter(|r| content(r).is_some()) {
+        let first = content(range).unwrap_or(range.start);
+        let (first_start, line) = lines[first];
+        let column = indent(line);
+        let root = if split_key(&line[column..]).is_some() || is_dash(&line[column..]) {
+            Value::Block(parse_block(lines, first, column, range.end)?)
+        } else {
+            let (last_start, last) = lines[range.end - 1];
+            Value::Inline(first_start + column..last_start + strip_comment(last).len())
+        };
+        documents.push(Document {
+            root,
+            end: lines.get(range.end).map_or(text.len(), |(start, _)| *start),
+        });
+    }
+    if documents.is_empty() {
+        documents.push(Document {
+            root: Value::Empty,
+            end: text.len(),
+        });
+    }
+    Ok(documents)
+}
+
+/// The document a path is in, and the path within it; paths into a stream
+/// of several start with `document N`.
+fn document<'d, 'p>(
+    documents: &'d [Document],
+    path: &'p [String],
+) -> Result<(&'d Document, &'p [String]), String> {
+    if documents.len() == 1 {
+        return Ok((&documents[0], path));
+    }
+    let n = path
+        .first()
+        .and_then(|first| first.strip_prefix("document "))
+        .and_then(|n| n.parse::<usize>().ok())
+        .filter(|n| (1..=documents.len()).contains(n))
+        .ok_or_else(|| "no such document".to_string())?;
+    Ok((&documents[n - 1], &path[1..]))
+}
+
+fn block<'v>(value: &'v Value, name: &str) -> Result<&'v Block, String> {
+    match value {
+        Value::Block(block) => Ok(block),
+        Value::Inline(_) => Err(format!("`{}` is written inline; edit it in the text", name)),
+        Value::Empty => Err(format!("`{}` is empty", name)),
+    }
+}
+
+fn position(block: &Block, key: &str) -> Result<usize, String> {
+    let found = match block.kind {
+        Kind::Map => block.entries.iter().rposition(|e| e.key == key),
+        Kind::Seq => Some(index(key)?).filter(|i| *i < block.entries.len()),
+    };
+    found.ok_or_else(|| format!("no entry `{}`", key))
+}
+
+/// The value at `path` and the entry holding it, `None` for the root.
+fn find<'v>(root: &'v Value, path: &[String]) -> Result<(&'v Value, Option<&'v Entry>), String> {
+    let mut found = (root, None);
+    for key in path {
+        let block = block(found.0, key)?;
+        let entry = &block.entries[position(block, key)?];
+        found = (&entry.value, Some(entry));
+    }
+    Ok(found)
+}
+
+fn render(value: &ConfigValue) -> String {
+    match value {
+        // Double quotes are the same in JSON, and keep it on one line
+        ConfigValue::String(s) if s.contains(|c: char| c.is_control()) => {
+            serde_json::to_string(s).unwrap_or_default()
+        }
+        ConfigValue::String(s) => render_yaml(&YamlValue::String(s.clone())),
+        ConfigValue::Integer(i) => i.to_string(),
+        ConfigValue::Float(f) => render_yaml(&YamlValue::from(*f)),
+        ConfigValue::Boolean(b) => b.to_string(),
+        ConfigValue::Null => "null".to_string(),
+        ConfigValue::Array(_) => "[]".to_string(),
+        ConfigValue::Table(_) | ConfigValue::Documents(_) => "{}".to_string(),
+    }
+}
+
+/// A scalar as `serde_yaml` writes it, quoted when it would read as
+/// something else.
+fn render_yaml(value: &YamlValue) -> String {
+    serde_yaml::to_string(value)
+        .map(|s| s.trim_end().to_string())
+        .unwrap_or_default()
+}
+
+pub fn edit(content: &str, edit: &Edit) -> Result<String, String> {
+    let documents_expected = match parse_yaml(content)?.value {
+        ConfigValue::Documents(documents) => documents.len(),
+        _ => 1,
+    };
+    edit_lines(content, |text| {
+        let lines = lines(text);
+        let documents = documents(text, &lines)?;
+        if documents.len() != documents_expected {
+            return Err("can't tell where each document starts".to_string());
+        }
+        splices(text, &documents, edit)
+    })
+}
+
+/// The text replacements making `edit`.
+fn splices(text: &str, documents: &[Document], edit: &Edit) -> Result<Vec<Splice>, String> {
+    if let Edit::Insert { parent, key, value } = edit {
+        let (document, parent) = document(documents, parent)?;
+        return insert(text, document, parent, key.as_deref(), &render(value));
+    }
+    let path = match edit {
+        Edit::Set { path, .. }
+        | Edit::Rename { path, .. }
+        | Edit::Remove { path }
+        | Edit::Move { path, .. } => path,
+        Edit::Insert { .. } => unreachable!(),
+    };
+    let (document, path) = document(documents, path)?;
+    let (parent, key) = split_path(path)?;
+    let (parent_value, owner) = find(&document.root, parent)?;
+    let block = block(
+        parent_value,
+        parent.last().map_or("document", String::as_str),
+    )?;
+    let i = position(block, key)?;
+    let entry = &block.entries[i];
+
+    let splices = match edit {
+        Edit::Set { value, .. } => {
+            let value = render(value);
+            match &entry.value {
+                Value::Inline(range) => vec![(range.clone(), value)],
+                Value::Empty => vec![(entry.header_end..entry.header_end, format!(" {}", value))],
+                Value::Block(_) => vec![(entry.header_end..entry.end - 1, format!(" {}", value))],
+            }
+        }
+        Edit::Rename { key: new_key, .. } => {
+            if block.kind == Kind::Seq {
+                return Err("array items have no key".to_string());
+            }
+            if block.entries.iter().any(|e| e.key == *new_key) {
+                return Err(format!("`{}` already exists", new_key));
+            }
+            vec![(
+                entry.key_span.clone(),
+                render_yaml(&YamlValue::String(new_key.clone())),
+            )]
+        }
+        // The last entry leaves an empty `{}` or `[]` behind; one on its
+        // parent's `-` line hands the line to the next
+        Edit::Remove { .. } => match owner {
+            Some(owner) if block.entries.len() == 1 => {
+                let empty = if block.kind == Kind::Map { "{}" } else { "[]" };
+                vec![(owner.header_end..owner.end - 1, format!(" {}", empty))]
+            }
+            _ if entry.inline && i + 1 < block.entries.len() => {
+                vec![(entry.head..block.entries[i + 1].head, String::new())]
+            }
+            _ => vec![(entry.start..entry.end, String::new())],
+        },
+        Edit::Move { up, .. } => {
+            if block.kind == Kind::Map {
+                return Err("only array items can be moved".to_string());
+            }
+            let j = neighbour(i, block.entries.len(), *up)?;
+            let (first, second) = (&block.entries[i.min(j)], &block.entries[i.max(j)]);
+            if first.inline {
+                return Err(
+                    "the item shares a line with its parent; move it in the text".to_string(),
+                );
+            }
+            vec![
+                (
+                    first.start..first.end,
+                    text[second.start..second.end].to_string(),
+                ),
+                (
+                    second.start..second.end,
+                    text[first.start..first.end].to_string(),
+                ),
+            ]
+        }
+        Edit::Insert { .. } => unreachable!(),
+    };
+    if matches!(edit, Edit::Set { .. } | Edit::Remove { .. }) {
+        for (range, _) in &splices {
+            check_aliases(text, range)?;
+        }
+    }
+    Ok(splices)
+}
+
+/// Refuse to drop an anchor that an alias outside `removed` still uses,
+/// which would leave the file unreadable.
+fn check_aliases(text: &str, removed: &Range<usize>) -> Result<(), String> {
+    let rest = [&text[..removed.start], &text[removed.end..]];
+    for name in anchor_names(&text[removed.clone()], '&') {
+        if rest.iter().any(|t| anchor_names(t, '*').contains(&name)) {
+            return Err(format!("`&{}` is still used by `*{}`", name, name));
+        }
+    }
+    Ok(())
+}
+
+/// Names of the anchors (`&name`) or aliases (`*name`) in `text`.
+fn anchor_names(text: &str, sigil: char) -> Vec<&str> {
+    text.lines()
+        .flat_map(|line| {
+            strip_comment(line).split(|c: char| c.is_whitespace() || "[]{},".contains(c))
+        })
+        .filter_map(|word| word.strip_prefix(sigil))
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+fn insert(
+    text: &str,
+    document: &Document,
+    parent: &[String],
+    key: Option<&str>,
+    value: &str,
+) -> Result<Vec<Splice>, String> {
+    let (container, owner) = find(&document.root, parent)?;
+    let name = parent.last().map_or("document", String::as_str);
+    let line = |column: usize| {
+        let indent = " ".repeat(column);
+        match key {
+            Some(key) => format!(
+                "{}{}: {}\n",
+                indent,
+                render_yaml(&YamlValue::String(key.to_string())),
+                value
+            ),
+            None => format!("{}- {}\n", indent, value),
+        }
+    };
+    let check_kind = |kind: Kind| match (kind, key) {
+        (Kind::Map, None) => Err("a table entry needs a key".to_string()),
+        (Kind::Seq, Some(_)) => Err("array items have no key".to_string()),
+        _ => Ok(()),
+    };
+
+    // An empty value or `{}`/`[]` becomes a block below its key
+    let (emptied, kind) = match container {
+        Value::Block(block) => {
+            check_kind(block.kind)?;
+            if let Some(key) = key.filter(|k| block.entries.iter().any(|e| e.key == *k)) {
+                return Err(format!("`{}` already exists", key));
+            }
+            let at = block.entries.last().map_or(document.end, |e| e.end);
+            return Ok(vec![(at..at, line(block.indent))]);
+        }
+        Value::Empty => (None, if key.is_some() { Kind::Map } else { Kind::Seq }),
+        Value::Inline(range) => match text[range.clone()].replace(' ', "").as_str() {
+            "{}" => (Some(range.clone()), Kind::Map),
+            "[]" => (Some(range.clone()), Kind::Seq),
+            _ => return Err(format!("`{}` is written inline; edit it in the text", name)),
+        },
+    };
+    check_kind(kind)?;
+
+    let mut splices = Vec::new();
+    let (at, column) = match owner {
+        Some(owner) => {
+            if let Some(range) = &emptied {
+                splices.push((owner.header_end..range.end, String::new()));
+            }
+            (line_end(text, owner.head), owner.column + 2)
+        }
+        None => {
+            if let Some(range) = &emptied {
+                splices.push((range.clone(), String::new()));
+            }
+            (emptied.map_or(document.end, |r| line_end(text, r.start)), 0)
+        }
+    };
+    splices.push((at..at, line(column)));
+    Ok(splices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(path: &str) -> Vec<String> {
+        path.split('.').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_edit_keeps_comments() {
+        let content = "# Deployment\nname: web  # shown in the dashboard\nreplicas: 2\n\
+                       base: &base\n  image: nginx\n\n# Ports, in order\nports:\n- 80\n- 443\n\
+                       env:\n  - name: MODE\n    value: prod\nlabels: {}\n";
+        let edits = [
+            Edit::Set {
+                path: path("name"),
+                value: ConfigValue::String("api".to_string()),
+            },
+            Edit::Set {
+                path: path("replicas"),
+                value: ConfigValue::String("3".to_string()),
+            },
+            Edit::Rename {
+                path: path("base.image"),
+                key: "image name".to_string(),
+            },
+            Edit::Move {
+                path: path("ports.1"),
+                up: true,
+            },
+            Edit::Insert {
+                parent: path("ports"),
+                key: None,
+                value: ConfigValue::Integer(8080),
+            },
+            Edit::Remove {
+                path: path("env.0.name"),
+            },
+            Edit::Insert {
+                parent: path("labels"),
+                key: Some("tier".to_string()),
+                value: ConfigValue::String("front end".to_string()),
+            },
+        ];
+        let text = edits
+            .iter()
+            .fold(content.to_string(), |text, e| edit(&text, e).unwrap());
+        assert_eq!(
+            text,
+            "# Deployment\nname: api  # shown in the dashboard\nreplicas: '3'\n\
+             base: &base\n  image name: nginx\n\n# Ports, in order\nports:\n- 443\n- 80\n- 8080\n\
+             env:\n  - value: prod\nlabels:\n  tier: front end\n"
+        );
+        assert!(parse_yaml(&text).is_ok());
+
+        let emptied = edit(
+            &text,
+            &Edit::Remove {
+                path: path("base.image name"),
+            },
+        )
+        .unwrap();
+        assert!(emptied.contains("base: &base {}\n"));
+
+        let aliased = "defaults:\n  base: &base\n    image: nginx\nweb:\n  <<: *base\n  port: 80\n";
+        for change in [
+            Edit::Remove {
+                path: path("defaults.base"),
+            },
+            Edit::Set {
+                path: path("defaults"),
+                value: ConfigValue::Null,
+            },
+        ] {
+            assert_eq!(
+                edit(aliased, &change).unwrap_err(),
+                "`&base` is still used by `*base`"
+            );
+        }
+        let unused = edit(aliased, &Edit::Remove { path: path("web") });
+        assert_eq!(
+            unused.unwrap(),
+            "defaults:\n  base: &base\n    image: nginx\n"
+        );
+
+        let stream = "a: 1\n---\nb: 2";
+        let changed = edit(
+            stream,
+            &Edit::Set {
+                path: path("document 2.b"),
+                value: ConfigValue::Boolean(true),
+            },
+        );
+        assert_eq!(changed.unwrap(), "a: 1\n---\nb: true");
+    }
+}
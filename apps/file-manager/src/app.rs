@@ -2,6 +2,7 @@ use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent};
 use std::fs;
 use std::path::PathBuf;
+use tui_plugins::{PluginHost, PluginPrompt, PromptStatus};
 
 use crate::config::Config;
 use crate::entry::format_bytes;
@@ -43,24 +44,33 @@ pub struct App {
     pub mode: Mode,
     pub message: Option<String>,
     pub show_preview: bool,
+    pub plugins: PluginHost,
+    pub prompt: Option<PluginPrompt>,
 }
 
 impl App {
     pub fn new(initial_path: Option<&str>) -> Result<Self> {
-        let config = Config::load()?;
+        let mut app = Self::with_config(Config::load()?, initial_path);
+        app.plugins.load();
+        Ok(app)
+    }
 
-        let left_pane = Pane::new(initial_path);
-        let right_pane = Pane::new(initial_path);
+    /// Create an app with both panes at `initial_path`, without loading plugins.
+    pub(crate) fn with_config(config: Config, initial_path: Option<&str>) -> Self {
+        let mut plugins = PluginHost::new("file-manager", env!("CARGO_PKG_VERSION"));
+        plugins.register_commands(crate::plugins::commands());
 
-        Ok(Self {
+        Self {
             config,
-            left_pane,
-            right_pane,
+            left_pane: Pane::new(initial_path),
+            right_pane: Pane::new(initial_path),
             active: ActivePane::Left,
             mode: Mode::Normal,
             message: None,
             show_preview: true,
-        })
+            plugins,
+            prompt: None,
+        }
     }
 
     pub fn active_pane(&self) -> &Pane {
@@ -85,6 +95,24 @@ impl App {
     }
 
     pub fn handle_key(&mut self, key: KeyEvent) {
+        if let Some(prompt) = self.prompt.as_mut() {
+            match prompt.handle_key(&key) {
+                PromptStatus::Submitted => {
+                    if let Some(prompt) = self.prompt.take() {
+                        self.plugins.respond(&prompt);
+                    }
+                }
+                PromptStatus::Cancelled => self.prompt = None,
+                PromptStatus::Pending => {}
+            }
+        } else if self.mode != Mode::Normal || !self.plugins.key(key) {
+            self.handle_mode_key(key);
+        }
+
+        self.sync_plugins();
+    }
+
+    fn handle_mode_key(&mut self, key: KeyEvent) {
         match &self.mode {
             Mode::Normal => self.handle_normal_key(key),
             Mode::Search(_) => self.handle_search_key(key),
@@ -119,7 +147,9 @@ impl App {
             // Directory navigation
             KeyCode::Left | KeyCode::Char('h') => self.active_pane_mut().go_parent(),
             KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => {
-                self.active_pane_mut().enter();
+                if let Some(path) = self.active_pane_mut().enter() {
+                    self.plugins.file_opened(&path);
+                }
             }
             KeyCode::Backspace => self.active_pane_mut().go_parent(),
 
@@ -355,7 +385,7 @@ impl App {
         }
     }
 
-    fn copy_to_other_pane(&mut self) {
+    pub(crate) fn copy_to_other_pane(&mut self) {
         let files = self.active_pane().get_selected_files();
         if files.is_empty() {
             return;
@@ -384,7 +414,7 @@ impl App {
         self.message = Some(format!("Copied {} item(s)", files.len()));
     }
 
-    fn move_to_other_pane(&mut self) {
+    pub(crate) fn move_to_other_pane(&mut self) {
         let files = self.active_pane().get_selected_files();
         if files.is_empty() {
             return;
@@ -408,7 +438,7 @@ impl App {
         self.message = Some(format!("Moved {} item(s)", files.len()));
     }
 
-    fn delete_selected(&mut self) {
+    pub(crate) fn delete_selected(&mut self) {
         let files = self.active_pane().get_selected_files();
         if files.is_empty() {
             return;
@@ -441,12 +471,12 @@ impl App {
         self.message = Some(format!("Deleted {} item(s)", files.len()));
     }
 
-    fn do_rename(&mut self, new_name: &str) {
+    pub(crate) fn do_rename(&mut self, new_name: &str) {
         if new_name.is_empty() {
             return;
         }
 
-        if let Some(entry) = self.active_pane().current_entry() {
+        if let Some(entry) = self.active_pane().current_entry().filter(|e| e.name != "..") {
             let old_path = entry.path.clone();
             let new_path = old_path.parent().unwrap().join(new_name);
 
@@ -460,7 +490,7 @@ impl App {
         }
     }
 
-    fn create_file(&mut self, name: &str) {
+    pub(crate) fn create_file(&mut self, name: &str) {
         if name.is_empty() {
             return;
         }
@@ -475,7 +505,7 @@ impl App {
         }
     }
 
-    fn create_dir(&mut self, name: &str) {
+    pub(crate) fn create_dir(&mut self, name: &str) {
         if name.is_empty() {
             return;
         }
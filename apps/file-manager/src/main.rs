@@ -4,6 +4,7 @@ mod app;
 mod config;
 mod entry;
 mod pane;
+mod plugins;
 mod ui;
 
 use anyhow::Result;
@@ -14,6 +15,7 @@ use crossterm::{
 };
 use ratatui::prelude::*;
 use std::io;
+use std::time::Duration;
 
 use app::App;
 
@@ -46,14 +48,19 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
     loop {
         terminal.draw(|frame| ui::render(frame, app))?;
 
-        if let Event::Key(key) = event::read()? {
-            match (key.code, key.modifiers) {
-                (KeyCode::Char('q'), KeyModifiers::NONE) |
-                (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
-                    return Ok(());
+        // Poll with a timeout so plugin timers keep firing while idle
+        if event::poll(Duration::from_millis(100))? {
+            if let Event::Key(key) = event::read()? {
+                match (key.code, key.modifiers) {
+                    (KeyCode::Char('c'), KeyModifiers::CONTROL) => return Ok(()),
+                    (KeyCode::Char('q'), KeyModifiers::NONE) if app.prompt.is_none() => {
+                        return Ok(());
+                    }
+                    _ => app.handle_key(key),
                 }
-                _ => app.handle_key(key),
             }
         }
+
+        app.tick();
     }
 }
@@ -0,0 +1,223 @@
+//! Plugin integration: commands exposed to plugins and handling of their actions.
+
+use crate::app::{ActivePane, App, Mode};
+use std::collections::HashMap;
+use std::path::Path;
+use tui_plugins::{AppCommand, HostAction, SelectionType};
+
+/// Commands plugins can run with `tui.run_command`.
+pub fn commands() -> Vec<AppCommand> {
+    vec![
+        AppCommand::new("cd", "Change directory").with_description("args: path"),
+        AppCommand::new("select", "Select entry").with_description("Move the cursor; args: name"),
+        AppCommand::new("copy", "Copy").with_description("Copy selection to the other pane"),
+        AppCommand::new("move", "Move").with_description("Move selection to the other pane"),
+        AppCommand::new("delete", "Delete").with_description("Delete selection (asks first if configured)"),
+        AppCommand::new("rename", "Rename").with_description("Rename the current entry; args: name"),
+        AppCommand::new("touch", "New file").with_description("args: name"),
+        AppCommand::new("mkdir", "New directory").with_description("args: name"),
+        AppCommand::new("switch_pane", "Switch pane"),
+        AppCommand::new("toggle_hidden", "Toggle hidden files"),
+        AppCommand::new("refresh", "Refresh both panes"),
+    ]
+}
+
+impl App {
+    /// Fire plugin timers and apply anything they asked for.
+    pub fn tick(&mut self) {
+        self.plugins.tick();
+        self.sync_plugins();
+    }
+
+    /// Report the current selection and apply queued plugin actions.
+    pub fn sync_plugins(&mut self) {
+        let current = self
+            .active_pane()
+            .current_entry()
+            .map(|e| e.path.display().to_string());
+        if let Some(path) = current {
+            self.plugins.selection_changed(path, SelectionType::Item);
+        }
+
+        for action in self.plugins.take_actions() {
+            self.apply_plugin_action(action);
+        }
+    }
+
+    fn apply_plugin_action(&mut self, action: HostAction) {
+        match action {
+            HostAction::Notify { message, .. } => self.message = Some(message),
+            HostAction::Prompt(prompt) => self.prompt = Some(prompt),
+            HostAction::RunCommand { name, args } => self.run_command(&name, &args),
+            HostAction::OpenFile { path, .. } => self.reveal(Path::new(&path)),
+            HostAction::InsertText(text) => match &mut self.mode {
+                Mode::Search(input)
+                | Mode::Rename(input)
+                | Mode::NewFile(input)
+                | Mode::NewDir(input) => input.push_str(&text),
+                _ => self.mode = Mode::Search(text),
+            },
+            HostAction::RequestData {
+                plugin,
+                data_type,
+                callback,
+            } => {
+                let pane = self.active_pane();
+                let data = match data_type.as_str() {
+                    "selection" => serde_json::json!(pane.get_selected_files()),
+                    "cwd" => serde_json::json!(pane.path),
+                    "entries" => pane
+                        .entries
+                        .iter()
+                        .map(|e| serde_json::json!({ "name": e.name, "path": e.path, "size": e.size }))
+                        .collect(),
+                    _ => serde_json::Value::Null,
+                };
+                self.plugins.reply(&plugin, &callback, data);
+            }
+            _ => {}
+        }
+    }
+
+    /// Run an app command on behalf of a plugin.
+    pub fn run_command(&mut self, name: &str, args: &HashMap<String, serde_json::Value>) {
+        let arg = |key: &str| args.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+
+        match name {
+            "cd" => {
+                let path = self.active_pane().path.join(arg("path"));
+                if path.is_dir() {
+                    self.active_pane_mut().navigate(&path);
+                } else {
+                    self.message = Some(format!("Not a directory: {}", path.display()));
+                }
+            }
+            "select" => {
+                let name = arg("name");
+                let pane = self.active_pane_mut();
+                match pane.entries.iter().position(|e| e.name == name) {
+                    Some(index) => pane.selected = index,
+                    None => self.message = Some(format!("No entry named {}", name)),
+                }
+            }
+            "copy" => self.copy_to_other_pane(),
+            "move" => self.move_to_other_pane(),
+            "delete" => self.delete_selected(),
+            "rename" | "touch" | "mkdir" => {
+                let entry_name = arg("name");
+                if entry_name.is_empty() || entry_name.contains(std::path::is_separator) {
+                    self.message = Some(format!("{}: invalid name '{}'", name, entry_name));
+                } else if name == "rename" {
+                    self.do_rename(&entry_name);
+                } else if name == "touch" {
+                    self.create_file(&entry_name);
+                } else {
+                    self.create_dir(&entry_name);
+                }
+            }
+            "switch_pane" => {
+                self.active = match self.active {
+                    ActivePane::Left => ActivePane::Right,
+                    ActivePane::Right => ActivePane::Left,
+                };
+            }
+            "toggle_hidden" => {
+                self.left_pane.toggle_hidden();
+                self.right_pane.toggle_hidden();
+            }
+            "refresh" => {
+                self.left_pane.refresh();
+                self.right_pane.refresh();
+            }
+            other => self.message = Some(format!("Unknown command: {}", other)),
+        }
+    }
+
+    /// Show a path in the active pane: directories are entered, files selected.
+    fn reveal(&mut self, path: &Path) {
+        if path.is_dir() {
+            self.active_pane_mut().navigate(path);
+            return;
+        }
+
+        if let Some(parent) = path.parent() {
+            let pane = self.active_pane_mut();
+            pane.navigate(parent);
+            if let Some(index) = pane.entries.iter().position(|e| e.path == path) {
+                pane.selected = index;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    /// An app with both panes in a fresh scratch directory.
+    fn app(test: &str) -> (App, PathBuf) {
+        let name = format!("file-manager-{}-{}", test, std::process::id());
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let app = App::with_config(Config::default(), dir.to_str());
+        (app, dir)
+    }
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, serde_json::Value> {
+        pairs.iter().map(|(k, v)| (k.to_string(), json!(v))).collect()
+    }
+
+    fn names(app: &App) -> Vec<&str> {
+        app.active_pane().entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_commands_dispatch() {
+        let (mut app, dir) = app("dispatch");
+        for command in commands() {
+            app.message = None;
+            app.run_command(&command.name, &HashMap::new());
+            let message = app.message.take().unwrap_or_default();
+            assert!(!message.starts_with("Unknown command"), "{}", command.name);
+        }
+
+        app.run_command("chmod", &HashMap::new());
+        assert_eq!(app.message.as_deref(), Some("Unknown command: chmod"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_commands() {
+        let (mut app, dir) = app("files");
+        app.run_command("mkdir", &args(&[("name", "docs")]));
+        app.run_command("cd", &args(&[("path", "docs")]));
+        assert_eq!(app.active_pane().path, dir.join("docs"));
+        app.run_command("cd", &args(&[("path", "missing")]));
+        assert!(app.message.as_deref().is_some_and(|m| m.starts_with("Not a directory")));
+        assert_eq!(app.active_pane().path, dir.join("docs"));
+
+        app.run_command("touch", &args(&[("name", "notes.txt")]));
+        app.run_command("select", &args(&[("name", "notes.txt")]));
+        app.run_command("rename", &args(&[("name", "todo.txt")]));
+        assert!(dir.join("docs/todo.txt").is_file());
+        assert_eq!(names(&app), vec!["..", "todo.txt"]);
+
+        app.run_command("select", &args(&[("name", "notes.txt")]));
+        assert_eq!(app.message.as_deref(), Some("No entry named notes.txt"));
+        app.run_command("rename", &args(&[("name", "../escape.txt")]));
+        assert_eq!(app.message.as_deref(), Some("rename: invalid name '../escape.txt'"));
+        app.run_command("touch", &args(&[]));
+        assert_eq!(app.message.as_deref(), Some("touch: invalid name ''"));
+        assert!(!dir.join("escape.txt").exists());
+
+        // The parent entry is never renamed
+        app.run_command("select", &args(&[("name", "..")]));
+        app.run_command("rename", &args(&[("name", "moved")]));
+        assert!(dir.join("docs").is_dir());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
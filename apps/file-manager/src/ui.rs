@@ -59,6 +59,14 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         Mode::Sort => render_sort_menu(frame),
         Mode::Normal => {}
     }
+
+    if let Some(prompt) = &app.prompt {
+        let title = match &prompt.message {
+            Some(message) => format!("{} - {}", prompt.title, message),
+            None => prompt.title.clone(),
+        };
+        render_input_dialog(frame, &title, &prompt.display_input());
+    }
 }
 
 fn render_pane(frame: &mut Frame, pane: &Pane, area: Rect, is_active: bool) {
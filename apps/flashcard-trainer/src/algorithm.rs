@@ -1,6 +1,8 @@
 //! Spaced repetition algorithms.
 
-use crate::models::{CardSchedule, CardState, Response, ReviewSchedule};
+use crate::config::AlgorithmConfig;
+use crate::fsrs::Fsrs;
+use crate::models::{CardSchedule, CardState, Deck, Response, ReviewSchedule};
 use chrono::{Duration, Utc};
 
 /// Trait for spaced repetition algorithms.
@@ -82,6 +84,8 @@ impl SrsAlgorithm for Sm2 {
             interval: Duration::days(interval),
             ease_factor: ease,
             state,
+            stability: None,
+            difficulty: None,
         }
     }
 
@@ -91,6 +95,8 @@ impl SrsAlgorithm for Sm2 {
             interval: Duration::days(0),
             ease_factor: self.initial_ease,
             state: CardState::New,
+            stability: None,
+            difficulty: None,
         }
     }
 }
@@ -135,6 +141,8 @@ impl SrsAlgorithm for Leitner {
             interval: Duration::days(new_box as i64), // Store box index
             ease_factor: 2.5, // Leitner doesn't use ease
             state,
+            stability: None,
+            difficulty: None,
         }
     }
 
@@ -144,6 +152,8 @@ impl SrsAlgorithm for Leitner {
             interval: Duration::days(0), // Box 0
             ease_factor: 2.5,
             state: CardState::New,
+            stability: None,
+            difficulty: None,
         }
     }
 }
@@ -152,10 +162,33 @@ impl SrsAlgorithm for Leitner {
 pub fn get_algorithm(name: &str) -> Box<dyn SrsAlgorithm> {
     match name.to_lowercase().as_str() {
         "leitner" => Box::new(Leitner::default()),
+        "fsrs" => Box::new(Fsrs::default()),
         _ => Box::new(Sm2::default()), // Default to SM-2
     }
 }
 
+/// Get the algorithm a deck is scheduled with, using the configured
+/// parameters and any weights the deck was tuned with. Decks without an
+/// algorithm of their own use the configured default.
+pub fn for_deck(config: &AlgorithmConfig, deck: &Deck) -> Box<dyn SrsAlgorithm> {
+    let name = match deck.algorithm.as_str() {
+        "" => &config.default_algorithm,
+        name => name,
+    };
+    match name.to_lowercase().as_str() {
+        "leitner" => Box::new(Leitner::default()),
+        "fsrs" => Box::new(
+            Fsrs::from_config(&config.fsrs).with_deck_config(&deck.algorithm_config),
+        ),
+        _ => Box::new(Sm2 {
+            initial_ease: config.sm2.initial_ease,
+            easy_bonus: config.sm2.easy_bonus,
+            hard_multiplier: config.sm2.hard_multiplier,
+            ..Sm2::default()
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,6 +204,8 @@ mod tests {
             review_count: 1,
             lapses: 0,
             state: CardState::Review,
+            stability: None,
+            difficulty: None,
         };
 
         let result = algo.calculate_next_review(&schedule, Response::Good);
@@ -189,6 +224,8 @@ mod tests {
             review_count: 5,
             lapses: 0,
             state: CardState::Review,
+            stability: None,
+            difficulty: None,
         };
 
         let result = algo.calculate_next_review(&schedule, Response::Again);
@@ -207,9 +244,27 @@ mod tests {
             review_count: 0,
             lapses: 0,
             state: CardState::New,
+            stability: None,
+            difficulty: None,
         };
 
         let result = algo.calculate_next_review(&schedule, Response::Good);
         assert_eq!(result.interval.num_days(), 1); // Box 1
     }
+
+    #[test]
+    fn test_for_deck() {
+        let config = AlgorithmConfig::default();
+        let mut deck = Deck::new("Test");
+        assert_eq!(for_deck(&config, &deck).name(), "SM-2");
+        deck.algorithm = "fsrs".to_string();
+        assert_eq!(for_deck(&config, &deck).name(), "FSRS");
+
+        let config = AlgorithmConfig {
+            default_algorithm: "leitner".to_string(),
+            ..AlgorithmConfig::default()
+        };
+        deck.algorithm.clear();
+        assert_eq!(for_deck(&config, &deck).name(), "Leitner");
+    }
 }
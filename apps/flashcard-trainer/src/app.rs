@@ -1,8 +1,10 @@
 //! Application state and logic.
 
-use crate::algorithm::{get_algorithm, SrsAlgorithm};
+use crate::algorithm::{self, get_algorithm, SrsAlgorithm};
 use crate::config::Config;
 use crate::db::{Database, DbResult};
+use crate::fsrs::{self, Fsrs};
+use crate::import;
 use crate::models::{Card, CardSchedule, Deck, DeckId, DeckStats, Response, Review, Session};
 use crossterm::event::{KeyCode, KeyEvent};
 use std::collections::HashMap;
@@ -39,6 +41,7 @@ pub enum InputField {
     DeckName,
     CardFront,
     CardBack,
+    ImportPath,
 }
 
 impl App {
@@ -147,6 +150,12 @@ impl App {
                 self.input_field = InputField::DeckName;
                 self.input_buffer.clear();
             }
+            KeyCode::Char('i') => {
+                self.editing = true;
+                self.input_field = InputField::ImportPath;
+                self.input_buffer.clear();
+            }
+            KeyCode::Char('O') => self.optimize_selected(),
             KeyCode::Char('s') => self.view = View::Stats,
             KeyCode::Char('b') => self.view = View::CardBrowser,
             KeyCode::Char('?') => self.show_help = true,
@@ -208,7 +217,8 @@ impl App {
         match self.input_field {
             InputField::DeckName => {
                 if !self.input_buffer.is_empty() {
-                    let deck = Deck::new(&self.input_buffer);
+                    let deck = Deck::new(&self.input_buffer)
+                        .with_algorithm(&self.config.algorithm.default_algorithm);
                     if self.db.insert_deck(&deck).is_ok() {
                         self.message = Some("Deck created".to_string());
                         let _ = self.refresh_decks();
@@ -230,6 +240,12 @@ impl App {
                     }
                 }
             }
+            InputField::ImportPath => {
+                let path = self.input_buffer.trim().to_string();
+                if !path.is_empty() {
+                    self.import(&path);
+                }
+            }
             InputField::None => {}
         }
         self.editing = false;
@@ -253,6 +269,7 @@ impl App {
         }
 
         let session = Session::new(deck.id, cards_due, cards_new);
+        self.algorithm = algorithm::for_deck(&self.config.algorithm, &deck);
         self.session = Some(session);
         self.view = View::Study;
         self.load_current_card();
@@ -278,6 +295,8 @@ impl App {
         updated.interval = new_schedule.interval.num_days();
         updated.ease_factor = new_schedule.ease_factor;
         updated.state = new_schedule.state;
+        updated.stability = new_schedule.stability;
+        updated.difficulty = new_schedule.difficulty;
         updated.review_count += 1;
         if matches!(response, Response::Again) {
             updated.lapses += 1;
@@ -303,6 +322,30 @@ impl App {
         self.load_current_card();
     }
 
+    /// Import an Anki package or CSV/TSV file.
+    pub fn import(&mut self, path: &str) {
+        let path = expand_home(path);
+        let algorithm = self.config.algorithm.default_algorithm.clone();
+        self.message = Some(match import::import_file(&self.db, &path, None, &algorithm) {
+            Ok(summary) => summary.describe(),
+            Err(e) => format!("Import failed: {}", e),
+        });
+        let _ = self.refresh_decks();
+    }
+
+    /// Fit FSRS weights to the selected deck's review history and switch the
+    /// deck to FSRS.
+    fn optimize_selected(&mut self) {
+        let Some(deck) = self.selected_deck().cloned() else {
+            return;
+        };
+        self.message = Some(match optimize_deck(&self.db, &self.config, deck) {
+            Ok(message) => message,
+            Err(e) => format!("Optimize failed: {}", e),
+        });
+        let _ = self.refresh_decks();
+    }
+
     fn end_session(&mut self) {
         self.session = None;
         self.current_card = None;
@@ -311,3 +354,37 @@ impl App {
         let _ = self.refresh_decks();
     }
 }
+
+/// Fit FSRS weights to a deck's reviews, store them in the deck and schedule
+/// it with FSRS from now on. Returns a summary for the user.
+pub fn optimize_deck(db: &Database, config: &Config, mut deck: Deck) -> DbResult<String> {
+    let histories = db.get_review_histories(deck.id)?;
+    let current =
+        Fsrs::from_config(&config.algorithm.fsrs).with_deck_config(&deck.algorithm_config);
+    let Some(fitted) = fsrs::optimize(&current.weights, &histories) else {
+        return Ok(format!(
+            "Not enough review history in {} to optimize (need {} reviews on separate days)",
+            deck.name,
+            fsrs::MIN_REVIEWS
+        ));
+    };
+
+    if !deck.algorithm_config.is_object() {
+        deck.algorithm_config = serde_json::json!({});
+    }
+    deck.algorithm_config["fsrs"] = serde_json::json!({ "weights": fitted.weights });
+    deck.algorithm = "fsrs".to_string();
+    db.update_deck(&deck)?;
+    Ok(format!(
+        "Optimized {} from {} reviews: log loss {:.4} -> {:.4}",
+        deck.name, fitted.reviews, fitted.loss_before, fitted.loss_after
+    ))
+}
+
+/// Expand a leading `~` to the home directory.
+pub fn expand_home(path: &str) -> std::path::PathBuf {
+    match (path.strip_prefix("~/"), directories::BaseDirs::new()) {
+        (Some(rest), Some(dirs)) => dirs.home_dir().join(rest),
+        _ => path.into(),
+    }
+}
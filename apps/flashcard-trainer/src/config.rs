@@ -26,10 +26,13 @@ impl Default for Config {
 
 impl Config {
     pub fn load() -> Self {
-        Self::config_path()
+        let mut config: Self = Self::config_path()
             .and_then(|p| std::fs::read_to_string(p).ok())
             .and_then(|s| toml::from_str(&s).ok())
-            .unwrap_or_default()
+            .unwrap_or_default();
+        let fsrs = &mut config.algorithm.fsrs;
+        fsrs.maximum_interval = fsrs.maximum_interval.clamp(1, FsrsConfig::MAX_INTERVAL);
+        config
     }
 
     pub fn save(&self) -> anyhow::Result<()> {
@@ -95,6 +98,8 @@ pub struct AlgorithmConfig {
     pub default_algorithm: String,
     #[serde(default)]
     pub sm2: Sm2Config,
+    #[serde(default)]
+    pub fsrs: FsrsConfig,
 }
 
 fn default_algorithm() -> String { "sm2".to_string() }
@@ -104,6 +109,7 @@ impl Default for AlgorithmConfig {
         Self {
             default_algorithm: "sm2".to_string(),
             sm2: Sm2Config::default(),
+            fsrs: FsrsConfig::default(),
         }
     }
 }
@@ -132,6 +138,38 @@ impl Default for Sm2Config {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FsrsConfig {
+    /// Probability of recall to schedule reviews at.
+    #[serde(default = "default_desired_retention")]
+    pub desired_retention: f64,
+    /// Longest interval in days.
+    #[serde(default = "default_maximum_interval")]
+    pub maximum_interval: i64,
+    /// Model weights; the FSRS defaults when unset. Decks tuned with
+    /// `optimize` keep their own weights.
+    #[serde(default)]
+    pub weights: Option<Vec<f64>>,
+}
+
+impl FsrsConfig {
+    /// Longest `maximum_interval` allowed: 100 years.
+    pub const MAX_INTERVAL: i64 = 36500;
+}
+
+fn default_desired_retention() -> f64 { 0.9 }
+fn default_maximum_interval() -> i64 { 36500 }
+
+impl Default for FsrsConfig {
+    fn default() -> Self {
+        Self {
+            desired_retention: 0.9,
+            maximum_interval: 36500,
+            weights: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DisplayConfig {
     #[serde(default = "default_true")]
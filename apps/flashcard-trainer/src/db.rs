@@ -1,6 +1,8 @@
 //! Database operations for flashcard trainer.
 
-use crate::models::{Card, CardId, CardSchedule, CardState, CardType, Deck, DeckId, DeckStats, Review};
+use crate::models::{
+    Card, CardId, CardSchedule, CardState, CardType, Deck, DeckId, DeckStats, Response, Review,
+};
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection, Result as SqlResult};
 use std::path::Path;
@@ -67,7 +69,9 @@ impl Database {
                 ease_factor REAL NOT NULL,
                 review_count INTEGER DEFAULT 0,
                 lapses INTEGER DEFAULT 0,
-                state TEXT NOT NULL
+                state TEXT NOT NULL,
+                stability REAL,
+                difficulty REAL
             );
 
             CREATE TABLE IF NOT EXISTS reviews (
@@ -86,11 +90,35 @@ impl Database {
 
             CREATE INDEX IF NOT EXISTS idx_schedules_due ON card_schedules(due);
             CREATE INDEX IF NOT EXISTS idx_cards_deck ON cards(deck_id);
+            CREATE INDEX IF NOT EXISTS idx_reviews_card ON reviews(card_id);
             "#,
         )?;
+
+        // Databases created before FSRS lack the memory state columns. Their
+        // decks all say "sm2" but were scheduled with the configured default,
+        // so they are left to follow it.
+        if self.conn.prepare("SELECT stability FROM card_schedules LIMIT 0").is_err() {
+            self.conn.execute_batch(
+                "ALTER TABLE card_schedules ADD COLUMN stability REAL;
+                 ALTER TABLE card_schedules ADD COLUMN difficulty REAL;
+                 UPDATE decks SET algorithm = '';",
+            )?;
+        }
         Ok(())
     }
 
+    /// Run `f` in a transaction, rolling back if it fails.
+    pub fn in_transaction<T, E: From<DbError>>(
+        &self,
+        f: impl FnOnce(&Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        self.conn.execute_batch("BEGIN").map_err(DbError::from)?;
+        let result = f(self);
+        let end = if result.is_ok() { "COMMIT" } else { "ROLLBACK" };
+        self.conn.execute_batch(end).map_err(DbError::from)?;
+        result
+    }
+
     // Deck operations
 
     pub fn insert_deck(&self, deck: &Deck) -> DbResult<()> {
@@ -143,6 +171,15 @@ impl Database {
         }
     }
 
+    pub fn find_deck(&self, name: &str) -> DbResult<Option<Deck>> {
+        let mut stmt = self.conn.prepare("SELECT * FROM decks WHERE name = ?1")?;
+        match stmt.query_row(params![name], parse_deck_row) {
+            Ok(d) => Ok(Some(d)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     pub fn list_decks(&self) -> DbResult<Vec<Deck>> {
         let mut stmt = self.conn.prepare("SELECT * FROM decks ORDER BY name")?;
         let decks = stmt
@@ -223,15 +260,29 @@ impl Database {
         Ok(cards)
     }
 
+    /// Whether the deck already has a card with this front and back.
+    pub fn card_exists(&self, deck_id: DeckId, front: &str, back: &str) -> DbResult<bool> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM cards WHERE deck_id = ?1 AND front = ?2 AND back = ?3",
+            params![deck_id.to_string(), front, back],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
     // Schedule operations
 
     pub fn upsert_schedule(&self, schedule: &CardSchedule) -> DbResult<()> {
         self.conn.execute(
-            "INSERT INTO card_schedules (card_id, due, interval, ease_factor, review_count, lapses, state)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "INSERT INTO card_schedules
+                (card_id, due, interval, ease_factor, review_count, lapses, state,
+                 stability, difficulty)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
              ON CONFLICT(card_id) DO UPDATE SET
                 due = excluded.due, interval = excluded.interval, ease_factor = excluded.ease_factor,
-                review_count = excluded.review_count, lapses = excluded.lapses, state = excluded.state",
+                review_count = excluded.review_count, lapses = excluded.lapses,
+                state = excluded.state, stability = excluded.stability,
+                difficulty = excluded.difficulty",
             params![
                 schedule.card_id.to_string(),
                 schedule.due.to_rfc3339(),
@@ -240,6 +291,8 @@ impl Database {
                 schedule.review_count,
                 schedule.lapses,
                 format!("{:?}", schedule.state).to_lowercase(),
+                schedule.stability,
+                schedule.difficulty,
             ],
         )?;
         Ok(())
@@ -310,6 +363,28 @@ impl Database {
         Ok(())
     }
 
+    /// Reviews of each card in the deck, oldest first.
+    pub fn get_review_histories(&self, deck_id: DeckId) -> DbResult<Vec<Vec<Review>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT r.* FROM reviews r
+             JOIN cards c ON c.id = r.card_id
+             WHERE c.deck_id = ?1
+             ORDER BY r.card_id, r.reviewed_at",
+        )?;
+        let reviews = stmt
+            .query_map(params![deck_id.to_string()], parse_review_row)?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        let mut histories: Vec<Vec<Review>> = Vec::new();
+        for review in reviews {
+            match histories.last_mut() {
+                Some(h) if h[0].card_id == review.card_id => h.push(review),
+                _ => histories.push(vec![review]),
+            }
+        }
+        Ok(histories)
+    }
+
     // Statistics
 
     pub fn get_deck_stats(&self, deck_id: DeckId) -> DbResult<DeckStats> {
@@ -414,6 +489,32 @@ fn parse_schedule_row(row: &rusqlite::Row) -> SqlResult<CardSchedule> {
         review_count: row.get("review_count")?,
         lapses: row.get("lapses")?,
         state,
+        stability: row.get("stability")?,
+        difficulty: row.get("difficulty")?,
+    })
+}
+
+fn parse_review_row(row: &rusqlite::Row) -> SqlResult<Review> {
+    let id_str: String = row.get("id")?;
+    let card_id_str: String = row.get("card_id")?;
+    let response_str: String = row.get("response")?;
+    let reviewed_str: String = row.get("reviewed_at")?;
+
+    let response = match response_str.as_str() {
+        "again" => Response::Again,
+        "hard" => Response::Hard,
+        "easy" => Response::Easy,
+        _ => Response::Good,
+    };
+
+    Ok(Review {
+        id: Uuid::parse_str(&id_str).unwrap(),
+        card_id: Uuid::parse_str(&card_id_str).unwrap(),
+        response,
+        time_taken_ms: row.get::<_, Option<i64>>("time_taken_ms")?.unwrap_or(0),
+        reviewed_at: DateTime::parse_from_rfc3339(&reviewed_str)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
     })
 }
 
@@ -445,5 +546,49 @@ mod tests {
 
         let schedule = db.get_schedule(card.id).unwrap().unwrap();
         assert_eq!(schedule.state, CardState::New);
+        assert_eq!(schedule.stability, None);
+        assert!(db.card_exists(deck.id, "Q?", "A").unwrap());
+    }
+
+    #[test]
+    fn test_review_histories() {
+        let db = Database::in_memory().unwrap();
+        let deck = Deck::new("Test");
+        db.insert_deck(&deck).unwrap();
+        let a = Card::new_basic(deck.id, "A", "1");
+        let b = Card::new_basic(deck.id, "B", "2");
+        db.insert_card(&a).unwrap();
+        db.insert_card(&b).unwrap();
+
+        let reviews = [(&a, Response::Good), (&b, Response::Again), (&a, Response::Hard)];
+        for (card, response) in reviews {
+            db.insert_review(&Review::new(card.id, response, 1500)).unwrap();
+        }
+
+        let histories = db.get_review_histories(deck.id).unwrap();
+        assert_eq!(histories.len(), 2);
+        let a_history = histories.iter().find(|h| h[0].card_id == a.id).unwrap();
+        let responses: Vec<_> = a_history.iter().map(|r| r.response).collect();
+        assert_eq!(responses, vec![Response::Good, Response::Hard]);
+    }
+
+    #[test]
+    fn test_migrate_pre_fsrs() {
+        let db = Database::in_memory().unwrap();
+        let deck = Deck::new("Old");
+        db.insert_deck(&deck).unwrap();
+        db.conn
+            .execute_batch(
+                "ALTER TABLE card_schedules DROP COLUMN stability;
+                 ALTER TABLE card_schedules DROP COLUMN difficulty;",
+            )
+            .unwrap();
+
+        db.init().unwrap();
+        assert_eq!(db.get_deck(deck.id).unwrap().unwrap().algorithm, "");
+        db.init().unwrap();
+        let new = Deck::new("New");
+        db.insert_deck(&new).unwrap();
+        assert_eq!(db.get_deck(new.id).unwrap().unwrap().algorithm, "sm2");
     }
 }
@@ -0,0 +1,499 @@
+//! FSRS (Free Spaced Repetition Scheduler).
+//!
+//! Models each card's memory with a stability S (days until recall
+//! probability falls to 90%) and a difficulty D (1-10), updated after every
+//! review. Reviews are scheduled for when the predicted recall probability
+//! reaches the desired retention. This is FSRS-4.5; [`optimize`] fits its 17
+//! weights to a deck's review history.
+
+use crate::algorithm::SrsAlgorithm;
+use crate::config::FsrsConfig;
+use crate::models::{CardSchedule, CardState, Response, Review, ReviewSchedule};
+use chrono::{Duration, Utc};
+
+/// FSRS-4.5 default weights.
+pub const DEFAULT_WEIGHTS: [f64; 17] = [
+    0.4872, 1.4003, 3.7145, 13.8206, 5.1618, 1.2298, 0.8975, 0.031, 1.6474, 0.1367, 1.0461, 2.1072,
+    0.0793, 0.3246, 1.587, 0.2272, 2.8755,
+];
+
+/// Allowed range of each weight while optimizing.
+const BOUNDS: [(f64, f64); 17] = [
+    (0.1, 100.0),
+    (0.1, 100.0),
+    (0.1, 100.0),
+    (0.1, 100.0),
+    (1.0, 10.0),
+    (0.001, 4.0),
+    (0.001, 4.0),
+    (0.001, 0.75),
+    (0.0, 4.5),
+    (0.0, 0.8),
+    (0.001, 3.5),
+    (0.001, 5.0),
+    (0.001, 0.25),
+    (0.001, 0.9),
+    (0.0, 4.0),
+    (0.0, 1.0),
+    (1.0, 6.0),
+];
+
+const DECAY: f64 = -0.5;
+const FACTOR: f64 = 19.0 / 81.0;
+
+/// Fewest predictable reviews (reviews after a card's first, on a later
+/// day) needed before weights are fitted.
+pub const MIN_REVIEWS: usize = 50;
+
+const ITERATIONS: usize = 200;
+const LEARNING_RATE: f64 = 0.02;
+
+/// Memory state of a card.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryState {
+    pub stability: f64,
+    pub difficulty: f64,
+}
+
+/// FSRS scheduler.
+#[derive(Debug, Clone)]
+pub struct Fsrs {
+    pub weights: [f64; 17],
+    /// Recall probability to schedule reviews at.
+    pub desired_retention: f64,
+    /// Longest interval in days.
+    pub maximum_interval: i64,
+}
+
+impl Default for Fsrs {
+    fn default() -> Self {
+        Self {
+            weights: DEFAULT_WEIGHTS,
+            desired_retention: 0.9,
+            maximum_interval: 36500,
+        }
+    }
+}
+
+impl Fsrs {
+    pub fn from_config(config: &FsrsConfig) -> Self {
+        let mut fsrs = Self {
+            desired_retention: config.desired_retention.clamp(0.7, 0.99),
+            maximum_interval: config.maximum_interval.clamp(1, FsrsConfig::MAX_INTERVAL),
+            ..Self::default()
+        };
+        if let Some(weights) = config.weights.as_deref().and_then(parse_weights) {
+            fsrs.weights = weights;
+        }
+        fsrs
+    }
+
+    /// Use the weights stored in a deck's `algorithm_config` by `optimize`,
+    /// if any.
+    pub fn with_deck_config(mut self, deck_config: &serde_json::Value) -> Self {
+        let weights = deck_config["fsrs"]["weights"]
+            .as_array()
+            .map(|a| a.iter().filter_map(|v| v.as_f64()).collect::<Vec<_>>());
+        if let Some(weights) = weights.as_deref().and_then(parse_weights) {
+            self.weights = weights;
+        }
+        self
+    }
+
+    /// Probability of recall `elapsed_days` after a review.
+    pub fn retrievability(elapsed_days: f64, stability: f64) -> f64 {
+        (1.0 + FACTOR * elapsed_days / stability).powf(DECAY)
+    }
+
+    /// Memory state after a card's first review.
+    pub fn init_state(&self, response: Response) -> MemoryState {
+        let w = &self.weights;
+        let g = grade(response);
+        MemoryState {
+            stability: w[g as usize - 1].max(0.1),
+            difficulty: (w[4] - (g - 3.0) * w[5]).clamp(1.0, 10.0),
+        }
+    }
+
+    /// Memory state after reviewing a card `elapsed_days` after the last review.
+    pub fn next_state(
+        &self,
+        state: MemoryState,
+        elapsed_days: f64,
+        response: Response,
+    ) -> MemoryState {
+        let w = &self.weights;
+        let g = grade(response);
+        let (s, d) = (state.stability, state.difficulty);
+        let r = Self::retrievability(elapsed_days.max(0.0), s);
+
+        // Difficulty moves with the grade, reverting towards the default
+        let difficulty = (w[7] * w[4] + (1.0 - w[7]) * (d - w[6] * (g - 3.0))).clamp(1.0, 10.0);
+
+        let stability = if response == Response::Again {
+            let forget =
+                w[11] * d.powf(-w[12]) * ((s + 1.0).powf(w[13]) - 1.0) * (w[14] * (1.0 - r)).exp();
+            forget.min(s)
+        } else {
+            let bonus = match response {
+                Response::Hard => w[15],
+                Response::Easy => w[16],
+                _ => 1.0,
+            };
+            let growth =
+                w[8].exp() * (11.0 - d) * s.powf(-w[9]) * ((w[10] * (1.0 - r)).exp() - 1.0);
+            s * (1.0 + growth * bonus)
+        };
+
+        MemoryState {
+            stability: stability.clamp(0.01, 36500.0),
+            difficulty,
+        }
+    }
+
+    /// Days until recall probability falls to the desired retention.
+    pub fn interval(&self, stability: f64) -> i64 {
+        let days = stability / FACTOR * (self.desired_retention.powf(1.0 / DECAY) - 1.0);
+        (days.round() as i64).clamp(1, self.maximum_interval)
+    }
+
+    /// Current memory state of a scheduled card. Cards scheduled by another
+    /// algorithm are converted from their interval and ease.
+    pub fn memory_state(schedule: &CardSchedule) -> Option<MemoryState> {
+        if schedule.state == CardState::New {
+            return None;
+        }
+        match (schedule.stability, schedule.difficulty) {
+            (Some(stability), Some(difficulty)) => Some(MemoryState {
+                stability,
+                difficulty,
+            }),
+            _ => Some(MemoryState {
+                stability: schedule.interval.max(1) as f64,
+                difficulty: (5.0 + (2.5 - schedule.ease_factor) * 5.0).clamp(1.0, 10.0),
+            }),
+        }
+    }
+}
+
+impl SrsAlgorithm for Fsrs {
+    fn name(&self) -> &str {
+        "FSRS"
+    }
+
+    fn calculate_next_review(&self, schedule: &CardSchedule, response: Response) -> ReviewSchedule {
+        let now = Utc::now();
+        let memory = match Self::memory_state(schedule) {
+            Some(memory) => {
+                let last_review = schedule.due - Duration::days(schedule.interval);
+                let elapsed = (now - last_review).num_seconds() as f64 / 86400.0;
+                self.next_state(memory, elapsed, response)
+            }
+            None => self.init_state(response),
+        };
+
+        let state = match (response, schedule.state) {
+            (Response::Again, CardState::New | CardState::Learning) => CardState::Learning,
+            (Response::Again, _) => CardState::Relearning,
+            _ => CardState::Review,
+        };
+        let interval = self.interval(memory.stability);
+
+        ReviewSchedule {
+            next_review: now + Duration::days(interval),
+            interval: Duration::days(interval),
+            ease_factor: schedule.ease_factor,
+            state,
+            stability: Some(memory.stability),
+            difficulty: Some(memory.difficulty),
+        }
+    }
+
+    fn initial_schedule(&self) -> ReviewSchedule {
+        ReviewSchedule {
+            next_review: Utc::now(),
+            interval: Duration::days(0),
+            ease_factor: 2.5,
+            state: CardState::New,
+            stability: None,
+            difficulty: None,
+        }
+    }
+}
+
+fn grade(response: Response) -> f64 {
+    match response {
+        Response::Again => 1.0,
+        Response::Hard => 2.0,
+        Response::Good => 3.0,
+        Response::Easy => 4.0,
+    }
+}
+
+fn parse_weights(weights: &[f64]) -> Option<[f64; 17]> {
+    let weights: [f64; 17] = weights.try_into().ok()?;
+    weights.iter().all(|w| w.is_finite()).then_some(weights)
+}
+
+/// Weights fitted by [`optimize`].
+#[derive(Debug, Clone)]
+pub struct Optimized {
+    pub weights: [f64; 17],
+    /// Number of reviews the weights were fitted to.
+    pub reviews: usize,
+    /// Log loss of the starting weights.
+    pub loss_before: f64,
+    /// Log loss of the fitted weights.
+    pub loss_after: f64,
+}
+
+/// One card's reviews as (days since the previous review, response).
+type Sequence = Vec<(f64, Response)>;
+
+/// Fit weights to per-card review histories, starting from `start`.
+///
+/// Minimizes the log loss of predicted recall against whether each review
+/// was passed. Same-day repeats are skipped, as the model only predicts
+/// recall across days. `None` if there are fewer than [`MIN_REVIEWS`]
+/// reviews to learn from.
+pub fn optimize(start: &[f64; 17], histories: &[Vec<Review>]) -> Option<Optimized> {
+    let sequences: Vec<Sequence> = histories.iter().filter_map(|h| to_sequence(h)).collect();
+    let reviews: usize = sequences.iter().map(|s| s.len() - 1).sum();
+    if reviews < MIN_REVIEWS {
+        return None;
+    }
+
+    let loss = |w: &[f64; 17]| {
+        let penalty: f64 = w
+            .iter()
+            .zip(start)
+            .zip(BOUNDS)
+            .map(|((w, s), (lo, hi))| ((w - s) / (hi - lo)).powi(2))
+            .sum();
+        log_loss(w, &sequences) + 0.01 * penalty
+    };
+
+    // Adam with central-difference gradients, steps scaled to each bound
+    let mut weights = *start;
+    for (w, (lo, hi)) in weights.iter_mut().zip(BOUNDS) {
+        *w = w.clamp(lo, hi);
+    }
+    let loss_before = log_loss(&weights, &sequences);
+    let mut best = (loss(&weights), weights);
+    let (mut m, mut v) = ([0.0; 17], [0.0; 17]);
+    let (beta1, beta2) = (0.9_f64, 0.999_f64);
+
+    for t in 1..=ITERATIONS {
+        let rate = LEARNING_RATE * (1.0 - 0.9 * t as f64 / ITERATIONS as f64);
+        for i in 0..17 {
+            let (lo, hi) = BOUNDS[i];
+            let h = (hi - lo) * 1e-4;
+            let (mut up, mut down) = (weights, weights);
+            up[i] = (weights[i] + h).min(hi);
+            down[i] = (weights[i] - h).max(lo);
+            if up[i] <= down[i] {
+                continue;
+            }
+            let grad = (loss(&up) - loss(&down)) / (up[i] - down[i]);
+
+            m[i] = beta1 * m[i] + (1.0 - beta1) * grad;
+            v[i] = beta2 * v[i] + (1.0 - beta2) * grad * grad;
+            let m_hat = m[i] / (1.0 - beta1.powi(t as i32));
+            let v_hat = v[i] / (1.0 - beta2.powi(t as i32));
+            let step = rate * (hi - lo) * m_hat / (v_hat.sqrt() + 1e-8);
+            weights[i] = (weights[i] - step).clamp(lo, hi);
+        }
+        let current = loss(&weights);
+        if current < best.0 {
+            best = (current, weights);
+        }
+    }
+
+    let weights = best.1;
+    Some(Optimized {
+        weights,
+        reviews,
+        loss_before,
+        loss_after: log_loss(&weights, &sequences),
+    })
+}
+
+/// Turn a card's reviews into a sequence, dropping same-day repeats.
+/// `None` if there is nothing to predict.
+fn to_sequence(history: &[Review]) -> Option<Sequence> {
+    let mut reviews: Vec<&Review> = history.iter().collect();
+    reviews.sort_by_key(|r| r.reviewed_at);
+    let first = reviews.first()?;
+
+    let mut sequence = vec![(0.0, first.response)];
+    let mut last = first.reviewed_at;
+    for review in &reviews[1..] {
+        let elapsed = (review.reviewed_at - last).num_seconds() as f64 / 86400.0;
+        if elapsed < 1.0 {
+            continue;
+        }
+        sequence.push((elapsed, review.response));
+        last = review.reviewed_at;
+    }
+    (sequence.len() > 1).then_some(sequence)
+}
+
+/// Mean log loss of recall predictions over all reviews after the first.
+fn log_loss(weights: &[f64; 17], sequences: &[Sequence]) -> f64 {
+    let fsrs = Fsrs {
+        weights: *weights,
+        ..Fsrs::default()
+    };
+    let mut total = 0.0;
+    let mut count = 0;
+    for sequence in sequences {
+        let mut state = fsrs.init_state(sequence[0].1);
+        for &(elapsed, response) in &sequence[1..] {
+            let r = Fsrs::retrievability(elapsed, state.stability).clamp(1e-4, 1.0 - 1e-4);
+            total -= if response == Response::Again {
+                (1.0 - r).ln()
+            } else {
+                r.ln()
+            };
+            count += 1;
+            state = fsrs.next_state(state, elapsed, response);
+        }
+    }
+    total / count.max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn review_schedule(interval: i64, stability: f64, difficulty: f64) -> CardSchedule {
+        CardSchedule {
+            card_id: Uuid::new_v4(),
+            due: Utc::now(),
+            interval,
+            ease_factor: 2.5,
+            review_count: 3,
+            lapses: 0,
+            state: CardState::Review,
+            stability: Some(stability),
+            difficulty: Some(difficulty),
+        }
+    }
+
+    #[test]
+    fn test_first_review() {
+        let fsrs = Fsrs::default();
+        let new = CardSchedule::new(Uuid::new_v4());
+
+        let good = fsrs.calculate_next_review(&new, Response::Good);
+        assert_eq!(good.interval.num_days(), 4);
+        assert_eq!(good.state, CardState::Review);
+        assert_eq!(good.stability, Some(DEFAULT_WEIGHTS[2]));
+
+        let again = fsrs.calculate_next_review(&new, Response::Again);
+        assert_eq!(again.interval.num_days(), 1);
+        assert_eq!(again.state, CardState::Learning);
+
+        let easy = fsrs.calculate_next_review(&new, Response::Easy);
+        assert_eq!(easy.interval.num_days(), 14);
+        assert!(easy.difficulty < good.difficulty);
+    }
+
+    #[test]
+    fn test_review_on_time() {
+        let fsrs = Fsrs::default();
+        let schedule = review_schedule(10, 10.0, 5.0);
+
+        let good = fsrs.calculate_next_review(&schedule, Response::Good);
+        assert!(good.stability.unwrap() > 20.0);
+        let hard = fsrs.calculate_next_review(&schedule, Response::Hard);
+        assert!(hard.interval < good.interval);
+        assert!(hard.difficulty > good.difficulty);
+
+        let again = fsrs.calculate_next_review(&schedule, Response::Again);
+        assert!(again.stability.unwrap() < 10.0);
+        assert_eq!(again.state, CardState::Relearning);
+
+        // Higher retention means shorter intervals
+        let strict = Fsrs {
+            desired_retention: 0.95,
+            ..Fsrs::default()
+        };
+        assert!(strict.interval(10.0) < fsrs.interval(10.0));
+    }
+
+    #[test]
+    fn test_deck_weights() {
+        let mut weights = DEFAULT_WEIGHTS;
+        weights[2] = 7.0;
+        let config = serde_json::json!({ "fsrs": { "weights": weights } });
+        let fsrs = Fsrs::default().with_deck_config(&config);
+        assert_eq!(fsrs.init_state(Response::Good).stability, 7.0);
+
+        // Wrong length is ignored
+        let config = serde_json::json!({ "fsrs": { "weights": [1.0, 2.0] } });
+        assert_eq!(
+            Fsrs::default().with_deck_config(&config).weights,
+            DEFAULT_WEIGHTS
+        );
+    }
+
+    /// Simulate reviews of cards remembered according to `truth`, scheduled
+    /// with the default weights.
+    fn simulate(truth: &Fsrs, cards: usize) -> Vec<Vec<Review>> {
+        let scheduler = Fsrs::default();
+        let mut seed: u64 = 42;
+        let mut random = move || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64
+        };
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap();
+
+        (0..cards)
+            .map(|_| {
+                let card_id = Uuid::new_v4();
+                let mut at = start;
+                let mut history = vec![];
+                let mut actual = truth.init_state(Response::Good);
+                let mut believed = scheduler.init_state(Response::Good);
+                history.push(Review::new(card_id, Response::Good, 0));
+                history[0].reviewed_at = at;
+                for _ in 0..8 {
+                    let days = scheduler.interval(believed.stability);
+                    at += Duration::days(days);
+                    let recalled = random() < Fsrs::retrievability(days as f64, actual.stability);
+                    let response = if recalled {
+                        Response::Good
+                    } else {
+                        Response::Again
+                    };
+                    actual = truth.next_state(actual, days as f64, response);
+                    believed = scheduler.next_state(believed, days as f64, response);
+                    let mut review = Review::new(card_id, response, 0);
+                    review.reviewed_at = at;
+                    history.push(review);
+                }
+                history
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_optimize() {
+        let mut truth = Fsrs::default();
+        truth.weights[2] = 1.0;
+        truth.weights[8] = 1.0;
+        let histories = simulate(&truth, 60);
+
+        let fitted = optimize(&DEFAULT_WEIGHTS, &histories).unwrap();
+        assert_eq!(fitted.reviews, 60 * 8);
+        assert!(fitted.loss_after < fitted.loss_before);
+        assert!(fitted.weights[8] < DEFAULT_WEIGHTS[8]);
+
+        assert!(optimize(&DEFAULT_WEIGHTS, &histories[..2]).is_none());
+    }
+}
@@ -0,0 +1,1131 @@
+//! Deck import from Anki packages and CSV/TSV files.
+//!
+//! Anki `.apkg`/`.colpkg` files are zip archives holding a SQLite
+//! collection. Each Anki card becomes one card here: standard note types are
+//! rendered through their card templates, cloze notes keep only their own
+//! deletion. Tags, scheduling (including FSRS memory state, when Anki has
+//! one) and the review log are carried over.
+//!
+//! Text files hold one note per line: front, back and optional tags. Anki's
+//! `#separator:`, `#html:`, `#tags column:` and `#deck column:` headers are
+//! understood, as is a `front,back,...` header row.
+
+use crate::db::{Database, DbError};
+use crate::models::{
+    cloze_numbers, isolate_cloze, Card, CardSchedule, CardState, Deck, DeckId, Response, Review,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid package: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("Collection error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    #[error(transparent)]
+    Db(#[from] DbError),
+    #[error("{0}")]
+    Format(String),
+}
+
+pub type ImportResult<T> = Result<T, ImportError>;
+
+/// What an import added.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Decks cards were added to.
+    pub decks: Vec<String>,
+    pub cards: usize,
+    /// Cards already in their deck with the same front and back.
+    pub duplicates: usize,
+    pub reviews: usize,
+}
+
+impl ImportSummary {
+    pub fn describe(&self) -> String {
+        let mut s = format!("Imported {} cards", self.cards);
+        if self.reviews > 0 {
+            s.push_str(&format!(" and {} reviews", self.reviews));
+        }
+        if !self.decks.is_empty() {
+            s.push_str(&format!(" into {}", self.decks.join(", ")));
+        }
+        if self.duplicates > 0 {
+            s.push_str(&format!(" ({} duplicates skipped)", self.duplicates));
+        }
+        s
+    }
+}
+
+/// Import a file, choosing the format from its extension.
+///
+/// Cards go into `deck` if given, otherwise into the Anki decks they came
+/// from, or a deck named after a text file. New decks use `algorithm`.
+pub fn import_file(
+    db: &Database,
+    path: &Path,
+    deck: Option<&str>,
+    algorithm: &str,
+) -> ImportResult<ImportSummary> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let mut importer = Importer {
+        db,
+        deck_override: deck,
+        algorithm,
+        decks: HashMap::new(),
+        summary: ImportSummary::default(),
+    };
+    db.in_transaction(|_| {
+        match ext.as_str() {
+            "apkg" | "colpkg" => importer.anki(path)?,
+            "csv" | "tsv" | "txt" => importer.text(path, &ext)?,
+            _ => {
+                return Err(ImportError::Format(format!(
+                    "unsupported file type '.{}' (expected .apkg, .colpkg, .csv, .tsv or .txt)",
+                    ext
+                )))
+            }
+        }
+        Ok(())
+    })?;
+    Ok(importer.summary)
+}
+
+struct Importer<'a> {
+    db: &'a Database,
+    deck_override: Option<&'a str>,
+    algorithm: &'a str,
+    decks: HashMap<String, DeckId>,
+    summary: ImportSummary,
+}
+
+impl Importer<'_> {
+    /// Deck id for `name`, creating the deck if needed.
+    fn deck(&mut self, name: &str) -> ImportResult<DeckId> {
+        let name = self.deck_override.unwrap_or(name).trim();
+        let name = if name.is_empty() { "Imported" } else { name };
+        if let Some(id) = self.decks.get(name) {
+            return Ok(*id);
+        }
+        let id = match self.db.find_deck(name)? {
+            Some(deck) => deck.id,
+            None => {
+                let deck = Deck::new(name).with_algorithm(self.algorithm);
+                self.db.insert_deck(&deck)?;
+                deck.id
+            }
+        };
+        self.decks.insert(name.to_string(), id);
+        self.summary.decks.push(name.to_string());
+        Ok(id)
+    }
+
+    /// Add a card unless its deck already has it. Returns whether it was added.
+    fn add(&mut self, card: &Card) -> ImportResult<bool> {
+        if card.front.trim().is_empty()
+            || self.db.card_exists(card.deck_id, &card.front, &card.back)?
+        {
+            self.summary.duplicates += usize::from(!card.front.trim().is_empty());
+            return Ok(false);
+        }
+        self.db.insert_card(card)?;
+        self.summary.cards += 1;
+        Ok(true)
+    }
+
+    // Anki packages
+
+    fn anki(&mut self, path: &Path) -> ImportResult<()> {
+        let file = std::fs::File::open(path)?;
+        let mut archive = zip::ZipArchive::new(file)?;
+
+        // Newer packages also carry a legacy collection that only holds an
+        // "update Anki" notice, so take the newest format present
+        let names = [
+            "collection.anki21b",
+            "collection.anki21",
+            "collection.anki2",
+        ];
+        let Some(name) = names
+            .into_iter()
+            .find(|n| archive.index_for_name(n).is_some())
+        else {
+            return Err(ImportError::Format(
+                "no Anki collection in package".to_string(),
+            ));
+        };
+        let mut bytes = Vec::new();
+        archive.by_name(name)?.read_to_end(&mut bytes)?;
+        if name.ends_with('b') {
+            bytes = zstd::decode_all(bytes.as_slice())?;
+        }
+
+        let temp = TempFile::new(bytes)?;
+        let conn = Connection::open(&temp.0)?;
+        let collection = Collection::load(&conn)?;
+        self.anki_cards(&conn, &collection)
+    }
+
+    fn anki_cards(&mut self, conn: &Connection, col: &Collection) -> ImportResult<()> {
+        let mut stmt = conn.prepare(
+            "SELECT c.id, n.id, CASE WHEN c.odid != 0 THEN c.odid ELSE c.did END, c.ord,
+                    c.type, c.queue, CASE WHEN c.odid != 0 THEN c.odue ELSE c.due END,
+                    c.ivl, c.factor, c.reps, c.lapses, c.data, n.mid, n.tags, n.flds
+             FROM cards c JOIN notes n ON n.id = c.nid
+             ORDER BY n.id, c.ord",
+        )?;
+        let mut rows = stmt.query([])?;
+        let mut ids: HashMap<i64, Uuid> = HashMap::new();
+
+        while let Some(row) = rows.next()? {
+            let anki = AnkiCard {
+                ord: row.get(3)?,
+                card_type: row.get(4)?,
+                queue: row.get(5)?,
+                due: row.get(6)?,
+                ivl: row.get(7)?,
+                factor: row.get(8)?,
+                reps: row.get(9)?,
+                lapses: row.get(10)?,
+                data: row.get::<_, Option<String>>(11)?.unwrap_or_default(),
+            };
+            let (card_id, note_id, deck_id): (i64, i64, i64) =
+                (row.get(0)?, row.get(1)?, row.get(2)?);
+            let note_type = col.note_types.get(&row.get::<_, i64>(12)?);
+            let tags: String = row.get(13)?;
+            let fields: String = row.get(14)?;
+            let fields: Vec<&str> = fields.split('\x1f').collect();
+
+            let deck_name = col
+                .decks
+                .get(&deck_id)
+                .map(String::as_str)
+                .unwrap_or("Default");
+            let deck = self.deck(deck_name)?;
+            let Some(mut card) = anki_card(deck, note_type, &fields, anki.ord) else {
+                continue;
+            };
+            card.tags = tags.split_whitespace().map(str::to_string).collect();
+            if let Some(created) = Utc.timestamp_millis_opt(note_id).single() {
+                card.created_at = created;
+                card.updated_at = created;
+            }
+
+            if self.add(&card)? {
+                self.db.upsert_schedule(&anki.schedule(card.id, col.crt))?;
+                ids.insert(card_id, card.id);
+            }
+        }
+
+        let mut stmt = conn.prepare("SELECT id, cid, ease, time FROM revlog ORDER BY id")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let Some(card_id) = ids.get(&row.get::<_, i64>(1)?) else {
+                continue;
+            };
+            // Ease 0 marks manual reschedules rather than answers
+            let response = match row.get::<_, i64>(2)? {
+                1 => Response::Again,
+                2 => Response::Hard,
+                3 => Response::Good,
+                4 => Response::Easy,
+                _ => continue,
+            };
+            let mut review = Review::new(*card_id, response, row.get(3)?);
+            if let Some(at) = Utc.timestamp_millis_opt(row.get(0)?).single() {
+                review.reviewed_at = at;
+            }
+            self.db.insert_review(&review)?;
+            self.summary.reviews += 1;
+        }
+        Ok(())
+    }
+
+    // Text files
+
+    fn text(&mut self, path: &Path, ext: &str) -> ImportResult<()> {
+        let content = std::fs::read_to_string(path)?;
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Imported");
+
+        let mut separator = None;
+        let mut html = false;
+        let mut tags_column = None;
+        let mut deck_column = None;
+        let mut body_start = 0;
+        for line in content.lines() {
+            let Some(header) = line.strip_prefix('#') else {
+                break;
+            };
+            body_start += line.len() + 1;
+            let Some((key, value)) = header.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "separator" => separator = parse_separator(value),
+                "html" => html = value == "true",
+                "tags column" => {
+                    tags_column = value.parse::<usize>().ok().and_then(|c| c.checked_sub(1))
+                }
+                "deck column" => {
+                    deck_column = value.parse::<usize>().ok().and_then(|c| c.checked_sub(1))
+                }
+                _ => {}
+            }
+        }
+        let body = content.get(body_start..).unwrap_or("");
+
+        let separator = separator.unwrap_or_else(|| {
+            let first = body.lines().next().unwrap_or("");
+            if ext == "tsv" || first.contains('\t') {
+                b'\t'
+            } else if ext == "txt" && first.contains(';') {
+                b';'
+            } else {
+                b','
+            }
+        });
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(separator)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(body.as_bytes());
+
+        let mut columns = Columns {
+            front: 0,
+            back: 1,
+            tags: tags_column,
+            deck: deck_column,
+        };
+        for (i, record) in reader.records().enumerate() {
+            let record = record?;
+            if i == 0 && columns.read_header(&record) {
+                continue;
+            }
+            let field = |col: Option<usize>| {
+                let value = col.and_then(|c| record.get(c)).unwrap_or("").trim();
+                if html {
+                    html_to_text(value)
+                } else {
+                    value.to_string()
+                }
+            };
+            let front = field(Some(columns.front));
+            let back = field(Some(columns.back));
+            // A third column holds tags, as in Anki's plain text export
+            let tags_col = columns
+                .tags
+                .or(if record.len() == 3 { Some(2) } else { None });
+            let tags: Vec<String> = field(tags_col)
+                .split_whitespace()
+                .map(str::to_string)
+                .collect();
+            let deck_name = field(columns.deck);
+            let deck = self.deck(if deck_name.is_empty() {
+                stem
+            } else {
+                &deck_name
+            })?;
+
+            for mut card in text_cards(deck, &front, &back) {
+                card.tags = tags.clone();
+                self.add(&card)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Column positions in a text file.
+struct Columns {
+    front: usize,
+    back: usize,
+    tags: Option<usize>,
+    deck: Option<usize>,
+}
+
+impl Columns {
+    /// Take positions from a `front,back,...` header row; false if `record`
+    /// is not one.
+    fn read_header(&mut self, record: &csv::StringRecord) -> bool {
+        let names: Vec<String> = record.iter().map(|f| f.trim().to_lowercase()).collect();
+        let position = |name: &str| names.iter().position(|n| n == name);
+        let (Some(front), Some(back)) = (position("front"), position("back")) else {
+            return false;
+        };
+        self.front = front;
+        self.back = back;
+        self.tags = position("tags").or(self.tags);
+        self.deck = position("deck").or(self.deck);
+        true
+    }
+}
+
+fn parse_separator(value: &str) -> Option<u8> {
+    match value.to_lowercase().as_str() {
+        "tab" => Some(b'\t'),
+        "comma" => Some(b','),
+        "semicolon" => Some(b';'),
+        "pipe" => Some(b'|'),
+        "space" => Some(b' '),
+        "colon" => Some(b':'),
+        s if s.len() == 1 => s.bytes().next(),
+        _ => None,
+    }
+}
+
+/// Cards for one line of a text file: one per cloze number if the front has
+/// clozes, otherwise a basic card.
+fn text_cards(deck: DeckId, front: &str, back: &str) -> Vec<Card> {
+    let numbers = cloze_numbers(front);
+    if numbers.is_empty() {
+        return vec![Card::new_basic(deck, front, back)];
+    }
+    numbers
+        .into_iter()
+        .map(|n| cloze_card(deck, isolate_cloze(front, n), back))
+        .collect()
+}
+
+/// A cloze card, with `extra` shown below the text on the back.
+fn cloze_card(deck: DeckId, text: String, extra: &str) -> Card {
+    let mut card = Card::new_cloze(deck, text);
+    if !extra.is_empty() {
+        card.back = format!("{}\n\n{}", card.back, extra);
+    }
+    card
+}
+
+// Anki collections
+
+/// Note type: its fields and card templates.
+#[derive(Debug, Clone, Default)]
+struct NoteType {
+    cloze: bool,
+    fields: Vec<String>,
+    /// Question and answer templates, by card ordinal.
+    templates: Vec<(String, String)>,
+}
+
+struct Collection {
+    /// Collection creation time, which review due days count from.
+    crt: i64,
+    note_types: HashMap<i64, NoteType>,
+    decks: HashMap<i64, String>,
+}
+
+impl Collection {
+    fn load(conn: &Connection) -> ImportResult<Self> {
+        let (crt, models, decks): (i64, String, String) =
+            conn.query_row("SELECT crt, models, decks FROM col", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?;
+
+        // Older collections keep note types and decks as JSON in `col`;
+        // newer ones have tables with protobuf configs
+        let mut note_types = legacy_note_types(&models);
+        if note_types.is_empty() {
+            note_types = table_note_types(conn)?;
+        }
+        let mut deck_names: HashMap<i64, String> =
+            serde_json::from_str::<serde_json::Value>(&decks)
+                .ok()
+                .and_then(|v| v.as_object().cloned())
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|(id, deck)| {
+                    Some((id.parse().ok()?, deck["name"].as_str()?.to_string()))
+                })
+                .collect();
+        if deck_names.is_empty() {
+            let mut stmt = conn.prepare("SELECT id, name FROM decks")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?.replace('\x1f', "::"),
+                ))
+            })?;
+            deck_names = rows.collect::<rusqlite::Result<_>>()?;
+        }
+
+        Ok(Self {
+            crt,
+            note_types,
+            decks: deck_names,
+        })
+    }
+}
+
+fn legacy_note_types(models: &str) -> HashMap<i64, NoteType> {
+    let Ok(serde_json::Value::Object(models)) = serde_json::from_str(models) else {
+        return HashMap::new();
+    };
+    let strings = |v: &serde_json::Value, key: &str| -> Vec<serde_json::Value> {
+        v[key].as_array().cloned().unwrap_or_default()
+    };
+    models
+        .iter()
+        .filter_map(|(id, model)| {
+            let note_type = NoteType {
+                cloze: model["type"].as_i64() == Some(1),
+                fields: strings(model, "flds")
+                    .iter()
+                    .map(|f| f["name"].as_str().unwrap_or("").to_string())
+                    .collect(),
+                templates: strings(model, "tmpls")
+                    .iter()
+                    .map(|t| {
+                        let q = t["qfmt"].as_str().unwrap_or("").to_string();
+                        let a = t["afmt"].as_str().unwrap_or("").to_string();
+                        (q, a)
+                    })
+                    .collect(),
+            };
+            Some((id.parse().ok()?, note_type))
+        })
+        .collect()
+}
+
+fn table_note_types(conn: &Connection) -> ImportResult<HashMap<i64, NoteType>> {
+    let mut note_types: HashMap<i64, NoteType> = HashMap::new();
+    let mut stmt = conn.prepare("SELECT id, config FROM notetypes")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let config: Vec<u8> = row.get(1)?;
+        // Field 1 of the config is the kind: 0 normal, 1 cloze
+        let cloze = proto_fields(&config)
+            .iter()
+            .any(|f| matches!(f, (1, ProtoValue::Varint(1))));
+        note_types.insert(
+            row.get(0)?,
+            NoteType {
+                cloze,
+                ..NoteType::default()
+            },
+        );
+    }
+
+    let mut stmt = conn.prepare("SELECT ntid, name FROM fields ORDER BY ntid, ord")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        if let Some(nt) = note_types.get_mut(&row.get(0)?) {
+            nt.fields.push(row.get(1)?);
+        }
+    }
+
+    // Template config fields 1 and 2 are the question and answer formats
+    let mut stmt = conn.prepare("SELECT ntid, config FROM templates ORDER BY ntid, ord")?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let config: Vec<u8> = row.get(1)?;
+        let text = |n: u64| {
+            proto_fields(&config)
+                .into_iter()
+                .find_map(|f| match f {
+                    (field, ProtoValue::Bytes(b)) if field == n => {
+                        Some(String::from_utf8_lossy(b).into_owned())
+                    }
+                    _ => None,
+                })
+                .unwrap_or_default()
+        };
+        if let Some(nt) = note_types.get_mut(&row.get(0)?) {
+            nt.templates.push((text(1), text(2)));
+        }
+    }
+    Ok(note_types)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProtoValue<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+/// Top-level fields of a protobuf message. Fixed-width values are skipped;
+/// reading stops at anything malformed.
+fn proto_fields(mut buf: &[u8]) -> Vec<(u64, ProtoValue<'_>)> {
+    fn varint(buf: &mut &[u8]) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = buf.split_first()?;
+            *buf = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    let mut fields = Vec::new();
+    while let Some(key) = varint(&mut buf) {
+        let value = match key & 7 {
+            0 => match varint(&mut buf) {
+                Some(v) => ProtoValue::Varint(v),
+                None => break,
+            },
+            2 => {
+                let Some(len) = varint(&mut buf)
+                    .map(|l| l as usize)
+                    .filter(|l| *l <= buf.len())
+                else {
+                    break;
+                };
+                let (bytes, rest) = buf.split_at(len);
+                buf = rest;
+                ProtoValue::Bytes(bytes)
+            }
+            1 if buf.len() >= 8 => {
+                buf = &buf[8..];
+                continue;
+            }
+            5 if buf.len() >= 4 => {
+                buf = &buf[4..];
+                continue;
+            }
+            _ => break,
+        };
+        fields.push((key >> 3, value));
+    }
+    fields
+}
+
+/// Build the card for template `ord` of a note.
+fn anki_card(
+    deck: DeckId,
+    note_type: Option<&NoteType>,
+    fields: &[&str],
+    ord: usize,
+) -> Option<Card> {
+    let field = |i: usize| fields.get(i).copied().unwrap_or("");
+    let Some(nt) = note_type else {
+        // Unknown note type: guess from the content
+        if !cloze_numbers(field(0)).is_empty() {
+            let text = isolate_cloze(&html_to_text(field(0)), ord as u32 + 1);
+            return Some(cloze_card(deck, text, &html_to_text(field(1))));
+        }
+        let (front, back) = if ord == 1 {
+            (field(1), field(0))
+        } else {
+            (field(0), field(1))
+        };
+        return Some(Card::new_basic(
+            deck,
+            html_to_text(front),
+            html_to_text(back),
+        ));
+    };
+
+    let values: HashMap<&str, &str> = nt
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), field(i)))
+        .collect();
+    let default = (
+        format!(
+            "{{{{{}}}}}",
+            nt.fields.first().map(String::as_str).unwrap_or("")
+        ),
+        format!(
+            "{{{{{}}}}}",
+            nt.fields.get(1).map(String::as_str).unwrap_or("")
+        ),
+    );
+    let index = if nt.cloze { 0 } else { ord };
+    let (qfmt, afmt) = nt.templates.get(index).unwrap_or(&default);
+    // The answer is shown on its own, without the question above it
+    let afmt = afmt
+        .split_once("<hr id=answer>")
+        .map_or(afmt.as_str(), |(_, a)| a);
+    let afmt = afmt.replace("{{FrontSide}}", "");
+
+    if nt.cloze {
+        let name = qfmt
+            .split("{{cloze:")
+            .nth(1)
+            .and_then(|s| s.split("}}").next())
+            .unwrap_or_else(|| nt.fields.first().map(String::as_str).unwrap_or(""));
+        let text = html_to_text(values.get(name).copied().unwrap_or(field(0)));
+        let text = isolate_cloze(&text, ord as u32 + 1);
+        let extra = afmt.replace(&format!("{{{{cloze:{}}}}}", name), "");
+        return Some(cloze_card(
+            deck,
+            text,
+            &html_to_text(&render_template(&extra, &values)),
+        ));
+    }
+
+    let front = html_to_text(&render_template(qfmt, &values));
+    let back = html_to_text(&render_template(&afmt, &values));
+    Some(Card::new_basic(deck, front, back))
+}
+
+/// Fill in an Anki card template: `{{Field}}`, filtered `{{filter:Field}}`,
+/// and `{{#Field}}...{{/Field}}` / `{{^Field}}...{{/Field}}` sections.
+fn render_template(template: &str, fields: &HashMap<&str, &str>) -> String {
+    let value = |name: &str| fields.get(name.trim()).copied().unwrap_or("");
+    let mut out = String::new();
+    let mut rest = template;
+
+    while let Some(open) = rest.find("{{") {
+        out.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find("}}").map(|i| open + i) else {
+            rest = &rest[open..];
+            break;
+        };
+        let tag = rest[open + 2..close].trim();
+        rest = &rest[close + 2..];
+
+        if let Some(section) = tag.strip_prefix('#').or_else(|| tag.strip_prefix('^')) {
+            let end_tag = format!("{{{{/{}}}}}", section);
+            let (inner, after) = rest.split_once(end_tag.as_str()).unwrap_or((rest, ""));
+            let filled = !value(section).trim().is_empty();
+            if filled == tag.starts_with('#') {
+                out.push_str(&render_template(inner, fields));
+            }
+            rest = after;
+        } else if tag.starts_with('/') {
+            // Stray section end
+        } else {
+            let (filters, name) = tag.rsplit_once(':').unwrap_or(("", tag));
+            // Typed answers need an input box
+            if !filters.split(':').any(|f| f == "type") {
+                out.push_str(value(name));
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Convert field HTML to the plain text/Markdown cards are stored as.
+pub fn html_to_text(html: &str) -> String {
+    let mut out = String::new();
+    let mut rest = html;
+    let newline = |out: &mut String| {
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.push('\n');
+        }
+    };
+
+    while let Some(lt) = rest.find('<') {
+        out.push_str(&rest[..lt]);
+        let after = &rest[lt + 1..];
+        let is_tag = after.starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!');
+        let Some(gt) = after.find('>').filter(|_| is_tag) else {
+            out.push('<');
+            rest = after;
+            continue;
+        };
+        let tag = &after[..gt];
+        rest = &after[gt + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        match name.as_str() {
+            "br" => out.push('\n'),
+            "div" | "p" | "tr" | "ul" | "ol" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                newline(&mut out)
+            }
+            "li" if !closing => {
+                newline(&mut out);
+                out.push_str("- ");
+            }
+            "img" => {
+                if let Some(src) = attribute(tag, "src") {
+                    out.push_str(&format!("![]({})", src));
+                }
+            }
+            "style" | "script" if !closing => {
+                let end = format!("</{}>", name);
+                rest = rest.find(&end).map_or("", |i| &rest[i + end.len()..]);
+            }
+            _ => {}
+        }
+    }
+    out.push_str(rest);
+
+    let text = decode_entities(&out);
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        // At most one blank line in a row
+        if line.is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n").trim().to_string()
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!("{}=", name))? + name.len() + 1;
+    let value = &tag[start..];
+    match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next(),
+        _ => value.split(|c: char| c.is_whitespace() || c == '/').next(),
+    }
+}
+
+fn decode_entities(s: &str) -> String {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&i| i <= 8)
+            .map(|i| &rest[1..=i]);
+        let decoded = entity.and_then(|e| match e {
+            "nbsp" => Some(' '),
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                let code = e.strip_prefix('#')?;
+                let code = match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => code.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (entity, decoded) {
+            (Some(e), Some(c)) => {
+                out.push(c);
+                rest = &rest[e.len() + 2..];
+            }
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Scheduling columns of an Anki card.
+struct AnkiCard {
+    ord: usize,
+    card_type: i64,
+    queue: i64,
+    due: i64,
+    ivl: i64,
+    factor: i64,
+    reps: i32,
+    lapses: i32,
+    data: String,
+}
+
+impl AnkiCard {
+    fn schedule(&self, card_id: Uuid, crt: i64) -> CardSchedule {
+        let day = |d: i64| Utc.timestamp_opt(crt + d * 86400, 0).single();
+        let state = match (self.queue, self.card_type) {
+            (-1, _) => CardState::Suspended,
+            (_, 1) => CardState::Learning,
+            (_, 2) => CardState::Review,
+            (_, 3) => CardState::Relearning,
+            _ => CardState::New,
+        };
+        // Learning cards due today hold a timestamp, others a day number
+        let due: Option<DateTime<Utc>> = match (self.card_type, self.queue) {
+            (0, _) => None,
+            (1 | 3, 1) => Utc.timestamp_opt(self.due, 0).single(),
+            _ => day(self.due),
+        };
+        let memory: serde_json::Value = serde_json::from_str(&self.data).unwrap_or_default();
+
+        CardSchedule {
+            card_id,
+            due: due.unwrap_or_else(Utc::now),
+            interval: self.ivl.max(0),
+            ease_factor: if self.factor > 0 {
+                self.factor as f64 / 1000.0
+            } else {
+                2.5
+            },
+            review_count: self.reps,
+            lapses: self.lapses,
+            state,
+            stability: memory["s"].as_f64(),
+            difficulty: memory["d"].as_f64(),
+        }
+    }
+}
+
+/// A file deleted when dropped.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(contents: Vec<u8>) -> std::io::Result<Self> {
+        let path = std::env::temp_dir().join(format!("flashcard-import-{}.db", Uuid::new_v4()));
+        std::fs::write(&path, contents)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("flashcard-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    fn write_package(path: &Path, entry: &str, collection: &[u8]) {
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file(entry, options).unwrap();
+        zip.write_all(collection).unwrap();
+        zip.start_file("media", options).unwrap();
+        zip.write_all(b"{}").unwrap();
+        zip.finish().unwrap();
+    }
+
+    /// A collection with the tables import reads, filled by `sql`.
+    fn collection(sql: &str) -> Vec<u8> {
+        let path = temp_path("collection.anki2");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE col (crt INTEGER, models TEXT, decks TEXT);
+             CREATE TABLE notes (id INTEGER, mid INTEGER, tags TEXT, flds TEXT);
+             CREATE TABLE cards (id INTEGER, nid INTEGER, did INTEGER, ord INTEGER, type INTEGER,
+                 queue INTEGER, due INTEGER, ivl INTEGER, factor INTEGER, reps INTEGER,
+                 lapses INTEGER, odid INTEGER DEFAULT 0, odue INTEGER DEFAULT 0, data TEXT);
+             CREATE TABLE revlog (id INTEGER, cid INTEGER, ease INTEGER, time INTEGER);",
+        )
+        .unwrap();
+        conn.execute_batch(sql).unwrap();
+        drop(conn);
+        std::fs::read(&path).unwrap()
+    }
+
+    fn card(db: &Database, deck: &str, front: &str) -> Card {
+        let deck = db.find_deck(deck).unwrap().unwrap();
+        db.get_cards_for_deck(deck.id)
+            .unwrap()
+            .into_iter()
+            .find(|c| c.front == front)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_import_apkg() {
+        let models = serde_json::json!({
+            "100": {
+                "type": 0,
+                "flds": [{"name": "Front"}, {"name": "Back"}, {"name": "Note"}],
+                "tmpls": [
+                    {"qfmt": "{{Front}}", "afmt": "{{FrontSide}}<hr id=answer>{{Back}}{{#Note}}<br>({{Note}}){{/Note}}"},
+                    {"qfmt": "{{Back}}", "afmt": "{{FrontSide}}<hr id=answer>{{Front}}"}
+                ]
+            },
+            "200": {
+                "type": 1,
+                "flds": [{"name": "Text"}, {"name": "Back Extra"}],
+                "tmpls": [{"qfmt": "{{cloze:Text}}", "afmt": "{{cloze:Text}}<br>{{Back Extra}}"}]
+            }
+        });
+        let decks = serde_json::json!({"1": {"name": "Default"}, "5": {"name": "Lang::French"}});
+        let bytes = collection(&format!(
+            "INSERT INTO col VALUES (1690000000, '{}', '{}');
+             INSERT INTO notes VALUES (1700000000000, 100, ' vocab french ', 'le chien\x1fthe dog\x1f');
+             INSERT INTO notes VALUES (1700000000001, 200, '', '<b>{{{{c1::Paris}}}}</b> is in {{{{c2::France}}}}\x1fGeography');
+             INSERT INTO cards VALUES (11, 1700000000000, 5, 0, 2, 2, 100, 15, 2300, 5, 1, 0, 0, '{{\"s\":12.5,\"d\":6.1}}');
+             INSERT INTO cards VALUES (12, 1700000000000, 5, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0, '');
+             INSERT INTO cards VALUES (13, 1700000000001, 5, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, '');
+             INSERT INTO cards VALUES (14, 1700000000001, 5, 1, 2, -1, 90, 4, 2500, 2, 0, 0, 0, '');
+             INSERT INTO revlog VALUES (1700000100000, 11, 3, 4000);
+             INSERT INTO revlog VALUES (1700000200000, 11, 1, 6000);
+             INSERT INTO revlog VALUES (1700000300000, 11, 0, 0);",
+            models, decks
+        ));
+        let path = temp_path("french.apkg");
+        write_package(&path, "collection.anki2", &bytes);
+
+        let db = Database::in_memory().unwrap();
+        let summary = import_file(&db, &path, None, "fsrs").unwrap();
+        assert_eq!(summary.cards, 4);
+        assert_eq!(summary.reviews, 2);
+        assert_eq!(summary.decks, vec!["Lang::French"]);
+        assert_eq!(
+            db.find_deck("Lang::French").unwrap().unwrap().algorithm,
+            "fsrs"
+        );
+
+        let forward = card(&db, "Lang::French", "le chien");
+        assert_eq!(forward.back, "the dog");
+        assert_eq!(forward.tags, Vec::<String>::new()); // tags aren't loaded with cards
+        let reverse = card(&db, "Lang::French", "the dog");
+        assert_eq!(reverse.back, "le chien");
+
+        let schedule = db.get_schedule(forward.id).unwrap().unwrap();
+        assert_eq!(schedule.state, CardState::Review);
+        assert_eq!(
+            (schedule.interval, schedule.ease_factor, schedule.lapses),
+            (15, 2.3, 1)
+        );
+        assert_eq!(schedule.due.timestamp(), 1690000000 + 100 * 86400);
+        assert_eq!(
+            (schedule.stability, schedule.difficulty),
+            (Some(12.5), Some(6.1))
+        );
+        let history = db.get_review_histories(forward.deck_id).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0][1].response, Response::Again);
+
+        let cloze = card(&db, "Lang::French", "{{c1::Paris}} is in France");
+        assert_eq!(cloze.question(), "[...] is in France");
+        assert_eq!(cloze.answer(), "Paris is in France\n\nGeography");
+        let cloze = card(&db, "Lang::French", "Paris is in {{c2::France}}");
+        let schedule = db.get_schedule(cloze.id).unwrap().unwrap();
+        assert_eq!(schedule.state, CardState::Suspended);
+
+        // Importing again only finds duplicates
+        let summary = import_file(&db, &path, None, "sm2").unwrap();
+        assert_eq!(
+            (summary.cards, summary.duplicates, summary.reviews),
+            (0, 4, 0)
+        );
+    }
+
+    #[test]
+    fn test_import_apkg_newer_schema() {
+        // Template config: field 1 question, field 2 answer
+        let mut template = vec![0x0a, 9];
+        template.extend_from_slice(b"{{Front}}");
+        let answer = b"{{FrontSide}}<hr id=answer>{{Back}}";
+        template.extend_from_slice(&[0x12, answer.len() as u8]);
+        template.extend_from_slice(answer);
+
+        let hex: String = template.iter().map(|b| format!("{:02x}", b)).collect();
+
+        let bytes = collection(&format!(
+            "INSERT INTO col VALUES (1690000000, '', '');
+             CREATE TABLE notetypes (id INTEGER, name TEXT, config BLOB);
+             CREATE TABLE fields (ntid INTEGER, ord INTEGER, name TEXT);
+             CREATE TABLE templates (ntid INTEGER, ord INTEGER, config BLOB);
+             CREATE TABLE decks (id INTEGER, name TEXT);
+             INSERT INTO notetypes VALUES (300, 'Basic', x'');
+             INSERT INTO fields VALUES (300, 1, 'Back'), (300, 0, 'Front');
+             INSERT INTO templates VALUES (300, 0, x'{}');
+             INSERT INTO decks VALUES (7, 'Lang\x1fGerman');
+             INSERT INTO notes VALUES (1700000000000, 300, '', 'der Hund\x1fthe dog');
+             INSERT INTO cards VALUES (21, 1700000000000, 7, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, '');",
+            hex
+        ));
+        let bytes = zstd::encode_all(bytes.as_slice(), 0).unwrap();
+        let path = temp_path("collection.colpkg");
+        write_package(&path, "collection.anki21b", &bytes);
+
+        let db = Database::in_memory().unwrap();
+        let summary = import_file(&db, &path, None, "sm2").unwrap();
+        assert_eq!(summary.decks, vec!["Lang::German"]);
+        assert_eq!(card(&db, "Lang::German", "der Hund").back, "the dog");
+
+        // Cloze kind in the note type config
+        assert!(matches!(
+            proto_fields(&[0x08, 0x01, 0x1a, 0x01, b'x'])[..],
+            [(1, ProtoValue::Varint(1)), (3, ProtoValue::Bytes(b"x"))]
+        ));
+    }
+
+    #[test]
+    fn test_import_text() {
+        let path = temp_path("Spanish.txt");
+        std::fs::write(
+            &path,
+            "#separator:tab\n#html:true\n#tags column:3\n\
+             hola\thello<br>hi\tgreeting spanish\n\
+             {{c1::Madrid}} is in {{c2::Spain}}\t\tgeo\n\
+             hola\thello<br>hi\t\n",
+        )
+        .unwrap();
+
+        let db = Database::in_memory().unwrap();
+        let summary = import_file(&db, &path, None, "sm2").unwrap();
+        assert_eq!((summary.cards, summary.duplicates), (3, 1));
+        assert_eq!(summary.decks, vec!["Spanish"]);
+        assert_eq!(card(&db, "Spanish", "hola").back, "hello\nhi");
+        let cloze = card(&db, "Spanish", "Madrid is in {{c2::Spain}}");
+        assert_eq!(cloze.question(), "Madrid is in [...]");
+
+        let path = temp_path("words.csv");
+        std::fs::write(&path, "Tags,Front,Back\nnoun,\"apple, red\",fruit\n").unwrap();
+        let summary = import_file(&db, &path, Some("Spanish"), "sm2").unwrap();
+        assert_eq!(summary.cards, 1);
+        assert_eq!(card(&db, "Spanish", "apple, red").back, "fruit");
+
+        let path = temp_path("deck.json");
+        std::fs::write(&path, "{}").unwrap();
+        assert!(matches!(
+            import_file(&db, &path, None, "sm2"),
+            Err(ImportError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        assert_eq!(
+            html_to_text("<div>one&nbsp;two</div><div>3 &lt; 4 &amp;&#33;</div><br><br><br>end"),
+            "one two\n3 < 4 &!\n\nend"
+        );
+        assert_eq!(
+            html_to_text("a < b <img src=\"cat.jpg\"><ul><li>x</li><li>y</li></ul>"),
+            "a < b ![](cat.jpg)\n- x\n- y"
+        );
+        assert_eq!(html_to_text("<style>.card {}</style>Q"), "Q");
+    }
+
+    #[test]
+    fn test_render_template() {
+        let fields = HashMap::from([("Front", "Q"), ("Back", "A"), ("Hint", "")]);
+        assert_eq!(
+            render_template(
+                "{{text:Front}}{{#Hint}} ({{Hint}}){{/Hint}}{{^Hint}}!{{/Hint}}",
+                &fields
+            ),
+            "Q!"
+        );
+        assert_eq!(
+            render_template("{{Back}}{{type:Back}}{{Missing}}", &fields),
+            "A"
+        );
+    }
+}
@@ -5,19 +5,30 @@ mod algorithm;
 mod app;
 mod config;
 mod db;
+mod fsrs;
+mod import;
 mod models;
 mod ui;
 
 use app::App;
+use config::Config;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use db::Database;
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
 
 fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("import") => return run_import(&args[2..]),
+        Some("optimize") => return run_optimize(&args[2..]),
+        _ => {}
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -66,3 +77,56 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> anyhow::
 
     Ok(())
 }
+
+fn open_db() -> anyhow::Result<Database> {
+    let db_path = Config::db_path().unwrap_or_else(|| "flashcards.db".into());
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    Ok(Database::open(&db_path)?)
+}
+
+/// `flashcard-trainer import <file.apkg|file.csv|file.tsv> [--deck NAME]`
+fn run_import(args: &[String]) -> anyhow::Result<()> {
+    let usage = "usage: flashcard-trainer import <file.apkg|file.csv|file.tsv> [--deck NAME]";
+    let mut path = None;
+    let mut deck = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--deck" | "-d" => deck = Some(args.next().ok_or_else(|| anyhow::anyhow!(usage))?),
+            _ => path = Some(app::expand_home(arg)),
+        }
+    }
+    let path = path.ok_or_else(|| anyhow::anyhow!(usage))?;
+
+    let config = Config::load();
+    let db = open_db()?;
+    let summary = import::import_file(
+        &db,
+        &path,
+        deck.map(String::as_str),
+        &config.algorithm.default_algorithm,
+    )?;
+    println!("{}: {}", path.display(), summary.describe());
+    Ok(())
+}
+
+/// `flashcard-trainer optimize [DECK]`
+///
+/// Fits FSRS weights to the review history of the named deck, or of every
+/// deck.
+fn run_optimize(args: &[String]) -> anyhow::Result<()> {
+    let config = Config::load();
+    let db = open_db()?;
+    let decks = match args.first() {
+        Some(name) => vec![db
+            .find_deck(name)?
+            .ok_or_else(|| anyhow::anyhow!("no deck named '{}'", name))?],
+        None => db.list_decks()?,
+    };
+    for deck in decks {
+        println!("{}", app::optimize_deck(&db, &config, deck)?);
+    }
+    Ok(())
+}
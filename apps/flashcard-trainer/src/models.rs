@@ -18,7 +18,7 @@ pub struct Deck {
     pub name: String,
     /// Optional description.
     pub description: Option<String>,
-    /// Algorithm name.
+    /// Algorithm name; empty for the configured default.
     pub algorithm: String,
     /// Algorithm-specific configuration.
     pub algorithm_config: serde_json::Value,
@@ -131,6 +131,105 @@ impl Card {
         self.tags.push(tag.into());
         self
     }
+
+    /// Text to show before flipping. Cloze deletions are hidden as `[...]`,
+    /// or `[hint]` when the cloze has one.
+    pub fn question(&self) -> String {
+        match self.card_type {
+            CardType::Cloze => render_cloze(&self.front, false),
+            _ => self.front.clone(),
+        }
+    }
+
+    /// Text to show after flipping, with cloze deletions filled in.
+    pub fn answer(&self) -> String {
+        match self.card_type {
+            CardType::Cloze => render_cloze(&self.back, true),
+            _ => self.back.clone(),
+        }
+    }
+}
+
+/// A `{{cN::answer::hint}}` deletion found in cloze text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cloze {
+    /// Byte range of the whole `{{...}}` in the text.
+    pub range: std::ops::Range<usize>,
+    pub number: u32,
+    pub answer: String,
+    pub hint: Option<String>,
+}
+
+/// Find cloze deletions in `text`, in order.
+pub fn find_clozes(text: &str) -> Vec<Cloze> {
+    let mut clozes = Vec::new();
+    let mut pos = 0;
+    while let Some(start) = text[pos..].find("{{c").map(|i| pos + i) {
+        let rest = &text[start + 3..];
+        let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+        let number = rest[..digits].parse::<u32>().ok();
+        let body = rest[digits..].strip_prefix("::");
+        let (Some(number), Some(body)) = (number, body) else {
+            pos = start + 3;
+            continue;
+        };
+        let Some(end) = body.find("}}") else {
+            break;
+        };
+        let content = &body[..end];
+        let (answer, hint) = match content.split_once("::") {
+            Some((answer, hint)) => (answer, Some(hint.to_string())),
+            None => (content, None),
+        };
+        let close = start + 3 + digits + 2 + content.len() + 2;
+        clozes.push(Cloze {
+            range: start..close,
+            number,
+            answer: answer.to_string(),
+            hint,
+        });
+        pos = close;
+    }
+    clozes
+}
+
+/// Cloze numbers used in `text`, sorted and deduplicated.
+pub fn cloze_numbers(text: &str) -> Vec<u32> {
+    let mut numbers: Vec<u32> = find_clozes(text).iter().map(|c| c.number).collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+    numbers
+}
+
+/// Keep only cloze `number` as a deletion; the others are filled in.
+pub fn isolate_cloze(text: &str, number: u32) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for cloze in find_clozes(text) {
+        if cloze.number != number {
+            out.push_str(&text[last..cloze.range.start]);
+            out.push_str(&cloze.answer);
+            last = cloze.range.end;
+        }
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+fn render_cloze(text: &str, reveal: bool) -> String {
+    let mut out = String::new();
+    let mut last = 0;
+    for cloze in find_clozes(text) {
+        out.push_str(&text[last..cloze.range.start]);
+        if reveal {
+            out.push_str(&cloze.answer);
+        } else {
+            out.push_str(&format!("[{}]", cloze.hint.as_deref().unwrap_or("...")));
+        }
+        last = cloze.range.end;
+    }
+    out.push_str(&text[last..]);
+    out
 }
 
 /// Card state in the learning process.
@@ -185,6 +284,10 @@ pub struct CardSchedule {
     pub lapses: i32,
     /// Current state.
     pub state: CardState,
+    /// FSRS memory stability in days, if scheduled by FSRS.
+    pub stability: Option<f64>,
+    /// FSRS difficulty (1-10), if scheduled by FSRS.
+    pub difficulty: Option<f64>,
 }
 
 impl CardSchedule {
@@ -198,6 +301,8 @@ impl CardSchedule {
             review_count: 0,
             lapses: 0,
             state: CardState::New,
+            stability: None,
+            difficulty: None,
         }
     }
 
@@ -282,6 +387,10 @@ pub struct ReviewSchedule {
     pub ease_factor: f64,
     /// New state.
     pub state: CardState,
+    /// FSRS stability; `None` for other algorithms.
+    pub stability: Option<f64>,
+    /// FSRS difficulty; `None` for other algorithms.
+    pub difficulty: Option<f64>,
 }
 
 /// Study session.
@@ -460,4 +569,20 @@ mod tests {
         session.next_card();
         assert!(session.is_complete());
     }
+
+    #[test]
+    fn test_cloze_rendering() {
+        let text = "{{c1::Paris}} is the capital of {{c2::France::country}}.";
+        assert_eq!(cloze_numbers(text), vec![1, 2]);
+
+        let card = Card::new_cloze(Uuid::new_v4(), isolate_cloze(text, 2));
+        assert_eq!(card.question(), "Paris is the capital of [country].");
+        assert_eq!(card.answer(), "Paris is the capital of France.");
+
+        let card = Card::new_cloze(Uuid::new_v4(), isolate_cloze(text, 1));
+        assert_eq!(card.question(), "[...] is the capital of France.");
+
+        // Not a cloze
+        assert!(find_clozes("{{c::x}} and {{c1:x}}").is_empty());
+    }
 }
@@ -88,7 +88,10 @@ fn draw_deck_list(f: &mut Frame, app: &App) {
     }
 
     // Footer
-    let footer = Paragraph::new("j/k:Navigate  Enter:Study  a:Add deck  s:Stats  b:Browse  ?:Help  q:Quit")
+    let footer = Paragraph::new(
+        "j/k:Navigate  Enter:Study  a:Add deck  i:Import  O:Optimize  s:Stats  b:Browse  ?:Help  \
+         q:Quit",
+    )
         .style(Style::default().fg(Color::DarkGray))
         .block(Block::default().borders(Borders::ALL));
     f.render_widget(footer, chunks[2]);
@@ -134,13 +137,13 @@ fn draw_study(f: &mut Frame, app: &App) {
                     .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
                     .split(card_area);
 
-                let front = Paragraph::new(card.front.as_str())
+                let front = Paragraph::new(card.question())
                     .alignment(Alignment::Center)
                     .block(Block::default().borders(Borders::ALL).title(" Question "))
                     .wrap(Wrap { trim: true });
                 f.render_widget(front, inner[0]);
 
-                let back = Paragraph::new(card.back.as_str())
+                let back = Paragraph::new(card.answer())
                     .alignment(Alignment::Center)
                     .style(Style::default().fg(Color::Green))
                     .block(Block::default().borders(Borders::ALL).title(" Answer "))
@@ -148,7 +151,7 @@ fn draw_study(f: &mut Frame, app: &App) {
                 f.render_widget(back, inner[1]);
             } else {
                 // Show only front
-                let front = Paragraph::new(card.front.as_str())
+                let front = Paragraph::new(card.question())
                     .alignment(Alignment::Center)
                     .block(Block::default().borders(Borders::ALL).title(" Question "))
                     .wrap(Wrap { trim: true });
@@ -275,6 +278,8 @@ Deck List:
   j/k, Up/Down    Navigate decks
   Enter, Space    Start study session
   a               Add new deck
+  i               Import Anki package or CSV/TSV file
+  O               Optimize FSRS weights for deck
   s               View statistics
   b               Browse cards
   q               Quit
@@ -307,6 +312,7 @@ fn draw_input(f: &mut Frame, app: &App) {
         InputField::DeckName => "Enter deck name",
         InputField::CardFront => "Enter card front",
         InputField::CardBack => "Enter card back",
+        InputField::ImportPath => "Import file (.apkg, .csv, .tsv)",
         InputField::None => "",
     };
 
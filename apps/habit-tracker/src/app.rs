@@ -2,8 +2,12 @@
 
 use crate::config::Config;
 use crate::db::{Database, DbResult};
-use crate::models::{Habit, HabitEntry, HabitId, HabitStats};
-use chrono::{Duration, NaiveDate, Utc};
+use crate::export::{self, DateRange};
+use crate::models::{
+    Habit, HabitEntry, HabitId, HabitStats, Metric, PeriodProgress, Schedule, Skip,
+};
+use crate::reminder::Reminder;
+use chrono::{Duration, Local, NaiveDate};
 use crossterm::event::{KeyCode, KeyEvent};
 use std::collections::HashMap;
 
@@ -31,14 +35,26 @@ pub struct App {
     pub editing_field: EditField,
     /// Statistics cache.
     pub stats_cache: HashMap<HabitId, HabitStats>,
-    /// Calendar heatmap data.
-    pub heatmap: Vec<(NaiveDate, f32)>,
+    /// Progress toward weekly/monthly targets for the selected date.
+    pub progress: HashMap<HabitId, PeriodProgress>,
+    /// Skips and vacations covering the selected date.
+    pub skips: Vec<Skip>,
+    /// Calendar heatmap data for the last 52 weeks.
+    pub heatmap: HashMap<NaiveDate, HeatCell>,
+    /// Habit shown in the heatmap, or `None` for all habits.
+    pub heatmap_habit: Option<HabitId>,
+    /// Completed entries in the heatmap.
+    pub heatmap_completions: usize,
     /// Message to display.
     pub message: Option<(String, MessageType)>,
     /// Show help popup.
     pub show_help: bool,
     /// Confirmation dialog.
     pub confirm_dialog: Option<ConfirmDialog>,
+    /// Daily reminder schedule.
+    pub reminder: Option<Reminder>,
+    /// Incomplete habits listed by a fired reminder.
+    pub reminder_popup: Option<Vec<String>>,
 }
 
 /// Current view.
@@ -62,6 +78,19 @@ pub enum EditField {
     HabitDescription,
     Value,
     Notes,
+    ImportPath,
+    Schedule,
+    Metric,
+    Vacation,
+}
+
+/// A day in the calendar heatmap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeatCell {
+    /// Share of due habits completed (0.0 to 1.0).
+    Rate(f32),
+    /// Every due habit was skipped.
+    Skipped,
 }
 
 /// Message type.
@@ -85,6 +114,7 @@ pub struct ConfirmDialog {
 #[derive(Debug, Clone)]
 pub enum ConfirmAction {
     DeleteHabit(HabitId),
+    EndVacation(uuid::Uuid),
 }
 
 impl App {
@@ -98,7 +128,7 @@ impl App {
         }
         let db = Database::open(&db_path)?;
 
-        let today = Utc::now().date_naive();
+        let today = Local::now().date_naive();
         let mut app = Self {
             db,
             config,
@@ -111,11 +141,18 @@ impl App {
             input_buffer: String::new(),
             editing_field: EditField::None,
             stats_cache: HashMap::new(),
-            heatmap: Vec::new(),
+            progress: HashMap::new(),
+            skips: Vec::new(),
+            heatmap: HashMap::new(),
+            heatmap_habit: None,
+            heatmap_completions: 0,
             message: None,
             show_help: false,
             confirm_dialog: None,
+            reminder: None,
+            reminder_popup: None,
         };
+        app.reminder = Reminder::from_config(&app.config.notifications);
 
         app.refresh()?;
         Ok(app)
@@ -135,12 +172,18 @@ impl App {
             self.entries.insert(entry.habit_id, entry);
         }
 
+        self.skips = self.db.get_skips_on(self.selected_date)?;
+
         // Refresh stats cache
         self.stats_cache.clear();
+        self.progress.clear();
         for habit in &self.habits {
             if let Ok(stats) = self.db.calculate_stats(habit) {
                 self.stats_cache.insert(habit.id, stats);
             }
+            if let Ok(Some(progress)) = self.db.period_progress(habit, self.selected_date) {
+                self.progress.insert(habit.id, progress);
+            }
         }
 
         // Refresh heatmap if in calendar view
@@ -156,30 +199,108 @@ impl App {
         Ok(())
     }
 
-    /// Refresh heatmap data.
+    /// Refresh heatmap data from each habit's entries over the last 52 weeks.
     fn refresh_heatmap(&mut self) -> DbResult<()> {
         self.heatmap.clear();
+        self.heatmap_completions = 0;
+
+        let today = Local::now().date_naive();
+        let start = today - Duration::weeks(53);
+
+        let habits: Vec<Habit> = self
+            .db
+            .list_habits(false)?
+            .into_iter()
+            .filter(|h| self.heatmap_habit.is_none_or(|id| id == h.id))
+            .collect();
+        let skips = self.db.list_skips()?;
+
+        // Per day: (habits due, completion summed over them, any skipped)
+        let mut days: HashMap<NaiveDate, (u32, f32, bool)> = HashMap::new();
+        for habit in &habits {
+            let entries: HashMap<NaiveDate, HabitEntry> = self
+                .db
+                .get_entries_range(habit.id, start, today)?
+                .into_iter()
+                .map(|e| (e.date, e))
+                .collect();
+            self.heatmap_completions += entries.values().filter(|e| e.completed).count();
+
+            for date in start.iter_days().take_while(|d| *d <= today) {
+                let day = days.entry(date).or_default();
+                if skips.iter().any(|s| s.covers(habit.id, date)) {
+                    day.2 = true;
+                    continue;
+                }
+                let entry = entries.get(&date);
+                // Weekly/monthly targets can be met on any day, so only
+                // days with progress count for them
+                let due = if habit.goal_period().is_some() {
+                    entry.is_some_and(|e| e.completed)
+                } else {
+                    habit.is_due_on(date)
+                };
+                if due {
+                    day.0 += 1;
+                    day.1 +=
+                        entry.map_or(0.0, |e| e.completion_percent(habit.goal()) as f32 / 100.0);
+                }
+            }
+        }
 
-        let today = Utc::now().date_naive();
-        let start = today - Duration::days(365);
+        for (date, (due, done, skipped)) in days {
+            if due > 0 {
+                self.heatmap.insert(date, HeatCell::Rate(done / due as f32));
+            } else if skipped {
+                self.heatmap.insert(date, HeatCell::Skipped);
+            }
+        }
 
-        let all_habits = self.db.list_habits(false)?;
+        Ok(())
+    }
 
-        let mut date = start;
-        while date <= today {
-            let habits_due: Vec<_> = all_habits.iter().filter(|h| h.is_due_on(date)).collect();
+    /// Cycle the heatmap between all habits and each single habit.
+    fn cycle_heatmap_habit(&mut self, delta: i32) {
+        let ids: Vec<Option<HabitId>> = std::iter::once(None)
+            .chain(self.habits.iter().map(|h| Some(h.id)))
+            .collect();
+        let current = ids.iter().position(|id| *id == self.heatmap_habit).unwrap_or(0) as i32;
+        let next = (current + delta).rem_euclid(ids.len() as i32) as usize;
+        self.heatmap_habit = ids[next];
+        let _ = self.refresh_heatmap();
+    }
 
-            if !habits_due.is_empty() {
-                let entries = self.db.get_entries_for_date(date)?;
-                let completed = entries.iter().filter(|e| e.completed).count();
-                let rate = completed as f32 / habits_due.len() as f32;
-                self.heatmap.push((date, rate));
-            }
+    /// Name of the habit shown in the heatmap.
+    pub fn heatmap_title(&self) -> String {
+        self.heatmap_habit
+            .and_then(|id| self.habits.iter().find(|h| h.id == id))
+            .map_or_else(|| "All habits".to_string(), |h| h.name.clone())
+    }
 
-            date = date.succ_opt().unwrap();
+    /// Periodic update; fires the daily reminder when due.
+    pub fn tick(&mut self) {
+        let now = Local::now().naive_local();
+        let Some(reminder) = self.reminder.as_mut() else {
+            return;
+        };
+        if !reminder.check(now) {
+            return;
         }
 
-        Ok(())
+        if let Ok(habits) = self.db.get_incomplete_habits_on(now.date()) {
+            if !habits.is_empty() {
+                self.reminder_popup = Some(habits.into_iter().map(|h| h.name).collect());
+            }
+        }
+    }
+
+    /// Whether incomplete habits should be flagged: the selected date is
+    /// today and the reminder time has passed.
+    pub fn warn_incomplete(&self) -> bool {
+        let now = Local::now().naive_local();
+        self.config.notifications.incomplete_warning
+            && self.selected_date == now.date()
+            && self.reminder.as_ref().is_some_and(|r| r.has_passed(now))
     }
 
     /// Check if in editing mode.
@@ -192,6 +313,13 @@ impl App {
         self.habits.get(self.selected_index)
     }
 
+    /// Check if a habit is skipped on the selected date.
+    pub fn is_skipped(&self, habit_id: HabitId) -> bool {
+        self.skips
+            .iter()
+            .any(|s| s.covers(habit_id, self.selected_date))
+    }
+
     /// Get entry for selected habit.
     pub fn selected_entry(&self) -> Option<&HabitEntry> {
         self.selected_habit()
@@ -215,6 +343,11 @@ impl App {
             return;
         }
 
+        // Any key dismisses the reminder
+        if self.reminder_popup.take().is_some() {
+            return;
+        }
+
         // Handle help popup
         if self.show_help {
             self.show_help = false;
@@ -231,6 +364,14 @@ impl App {
         }
 
         match key.code {
+            // Heatmap habit
+            KeyCode::Char('j') | KeyCode::Down if self.view == View::Calendar => {
+                self.cycle_heatmap_habit(1)
+            }
+            KeyCode::Char('k') | KeyCode::Up if self.view == View::Calendar => {
+                self.cycle_heatmap_habit(-1)
+            }
+
             // Navigation
             KeyCode::Char('j') | KeyCode::Down => self.move_selection(1),
             KeyCode::Char('k') | KeyCode::Up => self.move_selection(-1),
@@ -245,7 +386,7 @@ impl App {
             KeyCode::Char('h') | KeyCode::Left => self.change_date(-1),
             KeyCode::Char('l') | KeyCode::Right => self.change_date(1),
             KeyCode::Char('t') => {
-                self.selected_date = Utc::now().date_naive();
+                self.selected_date = Local::now().date_naive();
                 let _ = self.refresh();
             }
 
@@ -275,6 +416,12 @@ impl App {
             KeyCode::Char('e') => self.start_edit_habit(),
             KeyCode::Char('d') => self.confirm_delete_habit(),
             KeyCode::Char('n') => self.start_add_note(),
+            KeyCode::Char('S') => self.start_edit_schedule(),
+            KeyCode::Char('m') => self.start_edit_metric(),
+            KeyCode::Char('z') => self.toggle_skip(),
+            KeyCode::Char('V') => self.start_vacation(),
+            KeyCode::Char('x') => self.export(),
+            KeyCode::Char('i') => self.start_import(),
 
             // Help
             KeyCode::Char('?') => self.show_help = true,
@@ -386,6 +533,12 @@ impl App {
                     let _ = self.refresh();
                 }
             }
+            ConfirmAction::EndVacation(id) => {
+                if self.db.delete_skip(id).is_ok() {
+                    self.message = Some(("Vacation removed".to_string(), MessageType::Success));
+                    let _ = self.refresh();
+                }
+            }
         }
     }
 
@@ -401,6 +554,100 @@ impl App {
         }
     }
 
+    /// Start editing the selected habit's schedule.
+    fn start_edit_schedule(&mut self) {
+        if let Some(spec) = self.selected_habit().map(|h| h.schedule.spec()) {
+            self.editing = true;
+            self.editing_field = EditField::Schedule;
+            self.input_buffer = spec;
+        }
+    }
+
+    /// Start editing the selected habit's goal.
+    fn start_edit_metric(&mut self) {
+        if let Some(spec) = self.selected_habit().map(|h| h.metric.spec()) {
+            self.editing = true;
+            self.editing_field = EditField::Metric;
+            self.input_buffer = spec;
+        }
+    }
+
+    /// Skip the selected habit on the selected date, or undo the skip.
+    fn toggle_skip(&mut self) {
+        let Some(habit) = self.selected_habit().cloned() else {
+            return;
+        };
+        let date = self.selected_date;
+
+        let own: Vec<Skip> = self
+            .skips
+            .iter()
+            .filter(|s| s.habit_id == Some(habit.id) && s.covers(habit.id, date))
+            .cloned()
+            .collect();
+        let result = if !own.is_empty() {
+            own.iter().try_for_each(|s| self.db.delete_skip(s.id))
+        } else if self.skips.iter().any(|s| s.is_vacation()) {
+            self.message = Some((
+                "On vacation; press V to end it".to_string(),
+                MessageType::Info,
+            ));
+            return;
+        } else {
+            self.db.insert_skip(&Skip::day(habit.id, date))
+        };
+
+        if result.is_ok() {
+            let action = if own.is_empty() { "skipped" } else { "unskipped" };
+            self.message = Some((format!("{} {}", habit.name, action), MessageType::Success));
+            let _ = self.refresh();
+        }
+    }
+
+    /// Start entering a vacation, or offer to end the one on the selected date.
+    fn start_vacation(&mut self) {
+        if let Some(vacation) = self.skips.iter().find(|s| s.is_vacation()) {
+            self.confirm_dialog = Some(ConfirmDialog {
+                title: "End Vacation".to_string(),
+                message: format!(
+                    "Remove vacation {} to {}? (y/n)",
+                    vacation.start, vacation.end
+                ),
+                action: ConfirmAction::EndVacation(vacation.id),
+            });
+            return;
+        }
+        self.editing = true;
+        self.editing_field = EditField::Vacation;
+        self.input_buffer = format!("{}..{}", self.selected_date, self.selected_date);
+    }
+
+    /// Export everything to the configured directory and format.
+    fn export(&mut self) {
+        let dir = self.config.export_dir();
+        self.message = Some(
+            match export::export_to(
+                &self.db,
+                &dir,
+                self.config.export.format,
+                DateRange::default(),
+            ) {
+                Ok(files) => (
+                    format!("Exported {} file(s) to {}", files.len(), dir.display()),
+                    MessageType::Success,
+                ),
+                Err(e) => (format!("Export failed: {:#}", e), MessageType::Error),
+            },
+        );
+    }
+
+    /// Start entering a path to import from.
+    fn start_import(&mut self) {
+        self.editing = true;
+        self.editing_field = EditField::ImportPath;
+        self.input_buffer = self.config.export_dir().display().to_string();
+    }
+
     /// Finish editing and save.
     fn finish_editing(&mut self) {
         match self.editing_field {
@@ -426,11 +673,10 @@ impl App {
             EditField::Value => {
                 if let Some(habit) = self.selected_habit().cloned() {
                     if let Ok(value) = self.input_buffer.parse::<f64>() {
-                        let goal = habit.goal().unwrap_or(1.0);
-                        let entry =
-                            HabitEntry::new_quantity(habit.id, self.selected_date, value, goal);
+                        let entry = habit.entry_with_value(self.selected_date, value);
                         if self.db.upsert_entry(&entry).is_ok() {
                             self.entries.insert(habit.id, entry);
+                            let _ = self.refresh();
                         }
                     }
                 }
@@ -455,6 +701,61 @@ impl App {
                     }
                 }
             }
+            EditField::Schedule | EditField::Metric => {
+                let today = Local::now().date_naive();
+                let input = self.input_buffer.clone();
+                if let Some(habit) = self.habits.get_mut(self.selected_index) {
+                    let parsed = if self.editing_field == EditField::Schedule {
+                        Schedule::parse(&input, today).map(|s| habit.schedule = s)
+                    } else {
+                        Metric::parse(&input).map(|m| habit.metric = m)
+                    };
+                    self.message = Some(match parsed {
+                        Some(()) if self.db.update_habit(habit).is_ok() => (
+                            format!(
+                                "{}: {}, {}",
+                                habit.name,
+                                habit.schedule.display(),
+                                habit.metric.display()
+                            ),
+                            MessageType::Success,
+                        ),
+                        Some(()) => ("Could not save habit".to_string(), MessageType::Error),
+                        None => (format!("Not understood: '{}'", input), MessageType::Error),
+                    });
+                    let _ = self.refresh();
+                }
+            }
+            EditField::Vacation => {
+                match parse_date_range(&self.input_buffer, self.selected_date) {
+                    Some((start, end)) => {
+                        if self.db.insert_skip(&Skip::vacation(start, end)).is_ok() {
+                            self.message = Some((
+                                format!("Vacation {} to {}", start, end),
+                                MessageType::Success,
+                            ));
+                            let _ = self.refresh();
+                        }
+                    }
+                    None => {
+                        self.message = Some((
+                            "Expected YYYY-MM-DD..YYYY-MM-DD or a number of days".to_string(),
+                            MessageType::Error,
+                        ))
+                    }
+                }
+            }
+            EditField::ImportPath => {
+                let path = std::path::PathBuf::from(self.input_buffer.trim());
+                self.message = Some(match export::import_from(&self.db, &path) {
+                    Ok(summary) => (
+                        format!("Imported: {}", summary.label()),
+                        MessageType::Success,
+                    ),
+                    Err(e) => (format!("Import failed: {:#}", e), MessageType::Error),
+                });
+                let _ = self.refresh();
+            }
             _ => {}
         }
 
@@ -463,19 +764,19 @@ impl App {
         self.editing_field = EditField::None;
     }
 
-    /// Get completion rate for today.
+    /// Get completion rate for today, leaving out skipped habits.
     pub fn today_completion_rate(&self) -> f32 {
-        if self.habits.is_empty() {
+        let active: Vec<&Habit> = self.habits.iter().filter(|h| !self.is_skipped(h.id)).collect();
+        if active.is_empty() {
             return 0.0;
         }
 
-        let completed = self
-            .habits
+        let completed = active
             .iter()
             .filter(|h| self.entries.get(&h.id).map_or(false, |e| e.completed))
             .count();
 
-        completed as f32 / self.habits.len() as f32
+        completed as f32 / active.len() as f32
     }
 
     /// Get view title.
@@ -488,3 +789,36 @@ impl App {
         }
     }
 }
+
+/// Parse a vacation as `start..end`, a single date, or a number of days
+/// starting at `from`.
+fn parse_date_range(input: &str, from: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let date = |s: &str| NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok();
+    let input = input.trim();
+    if let Some((start, end)) = input.split_once("..") {
+        return Some((date(start)?, date(end)?));
+    }
+    if let Some(start) = date(input) {
+        return Some((start, start));
+    }
+    let days: i64 = input.trim_end_matches('d').trim().parse().ok().filter(|&d| d > 0)?;
+    Some((from, from + Duration::days(days - 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_date_range() {
+        let from = NaiveDate::from_ymd_opt(2024, 7, 1).unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2024, 7, d).unwrap();
+        assert_eq!(
+            parse_date_range("2024-07-03..2024-07-10", from),
+            Some((date(3), date(10)))
+        );
+        assert_eq!(parse_date_range("2024-07-04", from), Some((date(4), date(4))));
+        assert_eq!(parse_date_range("7d", from), Some((date(1), date(7))));
+        assert_eq!(parse_date_range("soon", from), None);
+    }
+}
@@ -54,6 +54,21 @@ impl Config {
             .map(|d| d.config_dir().join("config.toml"))
     }
 
+    /// Get export directory, expanding a leading `~`.
+    ///
+    /// Defaults to `exports` under the data directory.
+    pub fn export_dir(&self) -> PathBuf {
+        match &self.export.path {
+            Some(path) => match (path.strip_prefix("~"), directories::BaseDirs::new()) {
+                (Ok(rest), Some(dirs)) => dirs.home_dir().join(rest),
+                _ => path.clone(),
+            },
+            None => directories::ProjectDirs::from("", "", "habit-tracker")
+                .map(|d| d.data_dir().join("exports"))
+                .unwrap_or_else(|| "habits-export".into()),
+        }
+    }
+
     /// Get database path.
     pub fn db_path() -> Option<PathBuf> {
         directories::ProjectDirs::from("", "", "habit-tracker")
@@ -105,7 +120,7 @@ pub enum WeekStart {
 /// Notification settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotificationConfig {
-    /// Time for daily reminder (HH:MM format).
+    /// Time for daily reminder (HH:MM format, local time).
     #[serde(default)]
     pub remind_time: Option<String>,
     /// Warn about incomplete habits.
@@ -1,7 +1,11 @@
 //! Database operations for habit tracking.
 
-use crate::models::{Habit, HabitEntry, HabitId, HabitStats, Metric, Schedule, StreakInfo};
-use chrono::{NaiveDate, Utc};
+use crate::models::{
+    Habit, HabitEntry, HabitId, HabitStats, Metric, Period, PeriodProgress, Schedule, Skip,
+    StreakInfo,
+};
+use crate::streak;
+use chrono::{Local, NaiveDate, Utc};
 use rusqlite::{params, Connection, Result as SqlResult};
 use std::path::Path;
 use thiserror::Error;
@@ -55,6 +59,7 @@ impl Database {
                 metric_type TEXT NOT NULL,
                 metric_goal REAL,
                 metric_unit TEXT,
+                metric_period TEXT,
                 color TEXT,
                 created_at TEXT NOT NULL,
                 archived INTEGER DEFAULT 0
@@ -71,23 +76,39 @@ impl Database {
                 UNIQUE(habit_id, date)
             );
 
+            CREATE TABLE IF NOT EXISTS skips (
+                id TEXT PRIMARY KEY,
+                habit_id TEXT REFERENCES habits(id),
+                start_date TEXT NOT NULL,
+                end_date TEXT NOT NULL,
+                reason TEXT
+            );
+
             CREATE INDEX IF NOT EXISTS idx_entries_date ON habit_entries(date);
             CREATE INDEX IF NOT EXISTS idx_entries_habit ON habit_entries(habit_id);
             "#,
         )?;
+
+        // Columns added after the first release
+        if self.conn.prepare("SELECT metric_period FROM habits LIMIT 0").is_err() {
+            self.conn
+                .execute_batch("ALTER TABLE habits ADD COLUMN metric_period TEXT")?;
+        }
         Ok(())
     }
 
     /// Insert a new habit.
     pub fn insert_habit(&self, habit: &Habit) -> DbResult<()> {
         let (schedule_type, schedule_data) = serialize_schedule(&habit.schedule)?;
-        let (metric_type, metric_goal, metric_unit) = serialize_metric(&habit.metric);
+        let (metric_type, metric_goal, metric_unit, metric_period) =
+            serialize_metric(&habit.metric);
 
         self.conn.execute(
             r#"
             INSERT INTO habits (id, name, description, schedule_type, schedule_data,
-                               metric_type, metric_goal, metric_unit, color, created_at, archived)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                               metric_type, metric_goal, metric_unit, color, created_at, archived,
+                               metric_period)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
             "#,
             params![
                 habit.id.to_string(),
@@ -101,6 +122,7 @@ impl Database {
                 habit.color,
                 habit.created_at.to_rfc3339(),
                 habit.archived as i32,
+                metric_period,
             ],
         )?;
         Ok(())
@@ -109,13 +131,15 @@ impl Database {
     /// Update an existing habit.
     pub fn update_habit(&self, habit: &Habit) -> DbResult<()> {
         let (schedule_type, schedule_data) = serialize_schedule(&habit.schedule)?;
-        let (metric_type, metric_goal, metric_unit) = serialize_metric(&habit.metric);
+        let (metric_type, metric_goal, metric_unit, metric_period) =
+            serialize_metric(&habit.metric);
 
         self.conn.execute(
             r#"
             UPDATE habits SET
                 name = ?2, description = ?3, schedule_type = ?4, schedule_data = ?5,
-                metric_type = ?6, metric_goal = ?7, metric_unit = ?8, color = ?9, archived = ?10
+                metric_type = ?6, metric_goal = ?7, metric_unit = ?8, color = ?9, archived = ?10,
+                metric_period = ?11
             WHERE id = ?1
             "#,
             params![
@@ -129,6 +153,7 @@ impl Database {
                 metric_unit,
                 habit.color,
                 habit.archived as i32,
+                metric_period,
             ],
         )?;
         Ok(())
@@ -140,6 +165,10 @@ impl Database {
             "DELETE FROM habit_entries WHERE habit_id = ?1",
             params![id.to_string()],
         )?;
+        self.conn.execute(
+            "DELETE FROM skips WHERE habit_id = ?1",
+            params![id.to_string()],
+        )?;
         self.conn.execute(
             "DELETE FROM habits WHERE id = ?1",
             params![id.to_string()],
@@ -264,84 +293,132 @@ impl Database {
         Ok(entries)
     }
 
-    /// Calculate streak for a habit.
-    pub fn calculate_streak(&self, habit: &Habit) -> DbResult<StreakInfo> {
+    /// Get all entries, optionally limited to an inclusive date range.
+    pub fn list_entries(
+        &self,
+        start: Option<NaiveDate>,
+        end: Option<NaiveDate>,
+    ) -> DbResult<Vec<HabitEntry>> {
         let mut stmt = self.conn.prepare(
-            "SELECT date, completed FROM habit_entries WHERE habit_id = ?1 ORDER BY date DESC",
+            "SELECT * FROM habit_entries WHERE (?1 IS NULL OR date >= ?1) AND (?2 IS NULL OR date <= ?2) ORDER BY date, habit_id",
         )?;
 
-        let entries: Vec<(NaiveDate, bool)> = stmt
-            .query_map(params![habit.id.to_string()], |row| {
-                let date_str: String = row.get(0)?;
-                let completed: i32 = row.get(1)?;
-                Ok((
-                    NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").unwrap(),
-                    completed != 0,
-                ))
-            })?
+        let entries = stmt
+            .query_map(
+                params![start.map(|d| d.to_string()), end.map(|d| d.to_string())],
+                |row| Ok(parse_entry_row(row)?),
+            )?
             .collect::<SqlResult<Vec<_>>>()?;
 
-        let today = Utc::now().date_naive();
-        let mut current_streak = 0u32;
-        let mut best_streak = 0u32;
-        let mut temp_streak = 0u32;
-        let mut last_date = None;
+        Ok(entries)
+    }
 
-        // Calculate current streak (from today backwards)
-        let mut check_date = today;
-        for (date, completed) in &entries {
-            if !habit.is_due_on(*date) {
+    /// Get habits due on a date that have not been completed yet.
+    ///
+    /// Skipped habits and those whose weekly or monthly target is already
+    /// met are left out.
+    pub fn get_incomplete_habits_on(&self, date: NaiveDate) -> DbResult<Vec<Habit>> {
+        let completed: Vec<HabitId> = self
+            .get_entries_for_date(date)?
+            .into_iter()
+            .filter(|e| e.completed)
+            .map(|e| e.habit_id)
+            .collect();
+        let skips = self.get_skips_on(date)?;
+
+        let mut incomplete = Vec::new();
+        for habit in self.get_habits_due_on(date)? {
+            if completed.contains(&habit.id) || skips.iter().any(|s| s.covers(habit.id, date)) {
                 continue;
             }
-
-            // Skip future dates
-            if *date > today {
+            if self.period_progress(&habit, date)?.is_some_and(|p| p.is_met()) {
                 continue;
             }
+            incomplete.push(habit);
+        }
+        Ok(incomplete)
+    }
 
-            // Check for gaps in scheduled days
-            while check_date > *date {
-                if habit.is_due_on(check_date) {
-                    // Missed a scheduled day
-                    break;
-                }
-                check_date = check_date.pred_opt().unwrap();
-            }
+    /// Insert a skip or vacation.
+    pub fn insert_skip(&self, skip: &Skip) -> DbResult<()> {
+        self.conn.execute(
+            r#"
+            INSERT OR REPLACE INTO skips (id, habit_id, start_date, end_date, reason)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+            params![
+                skip.id.to_string(),
+                skip.habit_id.map(|id| id.to_string()),
+                skip.start.to_string(),
+                skip.end.to_string(),
+                skip.reason,
+            ],
+        )?;
+        Ok(())
+    }
 
-            if check_date != *date {
-                break; // Gap found
-            }
+    /// Delete a skip or vacation.
+    pub fn delete_skip(&self, id: Uuid) -> DbResult<()> {
+        self.conn
+            .execute("DELETE FROM skips WHERE id = ?1", params![id.to_string()])?;
+        Ok(())
+    }
 
-            if *completed {
-                current_streak += 1;
-                last_date = Some(*date);
-                check_date = date.pred_opt().unwrap();
-            } else {
-                break;
-            }
-        }
+    /// List all skips and vacations.
+    pub fn list_skips(&self) -> DbResult<Vec<Skip>> {
+        let mut stmt = self.conn.prepare("SELECT * FROM skips ORDER BY start_date")?;
+        let skips = stmt
+            .query_map([], |row| Ok(parse_skip_row(row)?))?
+            .collect::<SqlResult<Vec<_>>>()?;
+        Ok(skips)
+    }
 
-        // Calculate best streak
-        for (date, completed) in &entries {
-            if !habit.is_due_on(*date) {
-                continue;
-            }
+    /// Get skips and vacations that apply to a habit.
+    pub fn get_skips_for_habit(&self, habit_id: HabitId) -> DbResult<Vec<Skip>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM skips WHERE habit_id = ?1 OR habit_id IS NULL ORDER BY start_date",
+        )?;
+        let skips = stmt
+            .query_map(params![habit_id.to_string()], |row| {
+                Ok(parse_skip_row(row)?)
+            })?
+            .collect::<SqlResult<Vec<_>>>()?;
+        Ok(skips)
+    }
 
-            if *completed {
-                temp_streak += 1;
-                best_streak = best_streak.max(temp_streak);
-            } else {
-                temp_streak = 0;
-            }
-        }
+    /// Get skips and vacations covering a date.
+    pub fn get_skips_on(&self, date: NaiveDate) -> DbResult<Vec<Skip>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM skips WHERE start_date <= ?1 AND end_date >= ?1",
+        )?;
+        let skips = stmt
+            .query_map(params![date.to_string()], |row| Ok(parse_skip_row(row)?))?
+            .collect::<SqlResult<Vec<_>>>()?;
+        Ok(skips)
+    }
 
-        best_streak = best_streak.max(current_streak);
+    /// Get progress toward a habit's weekly or monthly target for the period
+    /// containing `date`.
+    pub fn period_progress(
+        &self,
+        habit: &Habit,
+        date: NaiveDate,
+    ) -> DbResult<Option<PeriodProgress>> {
+        let Some(per) = habit.goal_period() else {
+            return Ok(None);
+        };
+        let (start, end) = per.bounds(date);
+        let entries = self.get_entries_range(habit.id, start, end)?;
+        let skips = self.get_skips_for_habit(habit.id)?;
+        Ok(streak::period_progress(habit, &entries, &skips, date))
+    }
 
-        Ok(StreakInfo {
-            current: current_streak,
-            best: best_streak,
-            last_date,
-        })
+    /// Calculate streak for a habit.
+    pub fn calculate_streak(&self, habit: &Habit) -> DbResult<StreakInfo> {
+        let today = Local::now().date_naive();
+        let entries = self.get_entries_range(habit.id, NaiveDate::MIN, today)?;
+        let skips = self.get_skips_for_habit(habit.id)?;
+        Ok(streak::calculate(habit, &entries, &skips, today))
     }
 
     /// Calculate statistics for a habit.
@@ -349,7 +426,7 @@ impl Database {
         let streak = self.calculate_streak(habit)?;
 
         let mut stmt = self.conn.prepare(
-            "SELECT COUNT(*), SUM(completed), AVG(value), SUM(value) FROM habit_entries WHERE habit_id = ?1",
+            "SELECT COUNT(*), COALESCE(SUM(completed), 0), AVG(value), SUM(value) FROM habit_entries WHERE habit_id = ?1",
         )?;
 
         let (total, completed, avg_value, total_value): (u32, u32, Option<f64>, Option<f64>) =
@@ -392,6 +469,7 @@ fn serialize_schedule(schedule: &Schedule) -> DbResult<(String, Option<String>)>
                 Schedule::Weekly { .. } => "weekly",
                 Schedule::Monthly { .. } => "monthly",
                 Schedule::Interval { .. } => "interval",
+                Schedule::Times { .. } => "times",
             };
             Ok((type_name.to_string(), Some(data)))
         }
@@ -407,19 +485,37 @@ fn deserialize_schedule(schedule_type: &str, data: Option<String>) -> Schedule {
         .unwrap_or(Schedule::Daily)
 }
 
-fn serialize_metric(metric: &Metric) -> (String, Option<f64>, Option<String>) {
+fn serialize_metric(metric: &Metric) -> (String, Option<f64>, Option<String>, Option<String>) {
     match metric {
-        Metric::Binary => ("binary".to_string(), None, None),
-        Metric::Quantity { goal, unit } => ("quantity".to_string(), Some(*goal), Some(unit.clone())),
+        Metric::Binary => ("binary".to_string(), None, None, None),
+        Metric::Quantity { goal, unit } => {
+            ("quantity".to_string(), Some(*goal), Some(unit.clone()), None)
+        }
+        Metric::Cumulative { goal, unit, per } => (
+            "cumulative".to_string(),
+            Some(*goal),
+            Some(unit.clone()),
+            Some(per.name().to_string()),
+        ),
     }
 }
 
-fn deserialize_metric(metric_type: &str, goal: Option<f64>, unit: Option<String>) -> Metric {
+fn deserialize_metric(
+    metric_type: &str,
+    goal: Option<f64>,
+    unit: Option<String>,
+    period: Option<String>,
+) -> Metric {
     match metric_type {
         "quantity" => Metric::Quantity {
             goal: goal.unwrap_or(1.0),
             unit: unit.unwrap_or_default(),
         },
+        "cumulative" => Metric::Cumulative {
+            goal: goal.unwrap_or(1.0),
+            unit: unit.unwrap_or_default(),
+            per: period.as_deref().and_then(Period::parse).unwrap_or(Period::Week),
+        },
         _ => Metric::Binary,
     }
 }
@@ -431,6 +527,7 @@ fn parse_habit_row(row: &rusqlite::Row) -> SqlResult<Habit> {
     let metric_type: String = row.get("metric_type")?;
     let metric_goal: Option<f64> = row.get("metric_goal")?;
     let metric_unit: Option<String> = row.get("metric_unit")?;
+    let metric_period: Option<String> = row.get("metric_period")?;
     let created_str: String = row.get("created_at")?;
     let archived: i32 = row.get("archived")?;
 
@@ -439,7 +536,7 @@ fn parse_habit_row(row: &rusqlite::Row) -> SqlResult<Habit> {
         name: row.get("name")?,
         description: row.get("description")?,
         schedule: deserialize_schedule(&schedule_type, schedule_data),
-        metric: deserialize_metric(&metric_type, metric_goal, metric_unit),
+        metric: deserialize_metric(&metric_type, metric_goal, metric_unit, metric_period),
         color: row.get("color")?,
         created_at: chrono::DateTime::parse_from_rfc3339(&created_str)
             .map(|dt| dt.with_timezone(&Utc))
@@ -468,6 +565,21 @@ fn parse_entry_row(row: &rusqlite::Row) -> SqlResult<HabitEntry> {
     })
 }
 
+fn parse_skip_row(row: &rusqlite::Row) -> SqlResult<Skip> {
+    let id_str: String = row.get("id")?;
+    let habit_id_str: Option<String> = row.get("habit_id")?;
+    let start_str: String = row.get("start_date")?;
+    let end_str: String = row.get("end_date")?;
+
+    Ok(Skip {
+        id: Uuid::parse_str(&id_str).unwrap(),
+        habit_id: habit_id_str.map(|s| Uuid::parse_str(&s).unwrap()),
+        start: NaiveDate::parse_from_str(&start_str, "%Y-%m-%d").unwrap(),
+        end: NaiveDate::parse_from_str(&end_str, "%Y-%m-%d").unwrap(),
+        reason: row.get("reason")?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -491,11 +603,72 @@ mod tests {
         let habit = Habit::new_binary("Exercise");
         db.insert_habit(&habit).unwrap();
 
-        let today = Utc::now().date_naive();
+        let today = Local::now().date_naive();
         let entry = HabitEntry::new_binary(habit.id, today, true);
         db.upsert_entry(&entry).unwrap();
 
         let loaded = db.get_entry(habit.id, today).unwrap().unwrap();
         assert!(loaded.completed);
     }
+
+    #[test]
+    fn test_incomplete_habits() {
+        let db = Database::in_memory().unwrap();
+
+        let exercise = Habit::new_binary("Exercise");
+        let water = Habit::new_quantitative("Water", 8.0, "glasses");
+        db.insert_habit(&exercise).unwrap();
+        db.insert_habit(&water).unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        db.upsert_entry(&HabitEntry::new_binary(exercise.id, date, true))
+            .unwrap();
+        db.upsert_entry(&HabitEntry::new_quantity(water.id, date, 3.0, 8.0))
+            .unwrap();
+
+        let incomplete = db.get_incomplete_habits_on(date).unwrap();
+        assert_eq!(incomplete.len(), 1);
+        assert_eq!(incomplete[0].id, water.id);
+        assert_eq!(db.list_entries(None, Some(date)).unwrap().len(), 2);
+        assert!(db.list_entries(date.succ_opt(), None).unwrap().is_empty());
+
+        db.insert_skip(&Skip::vacation(date, date)).unwrap();
+        assert!(db.get_incomplete_habits_on(date).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cumulative_habit_and_skips() {
+        let db = Database::in_memory().unwrap();
+
+        let mut habit = Habit::new_binary("Cycling");
+        habit.metric = Metric::Cumulative {
+            goal: 100.0,
+            unit: "km".to_string(),
+            per: Period::Month,
+        };
+        habit.schedule = Schedule::Times {
+            times: 2,
+            per: Period::Week,
+        };
+        db.insert_habit(&habit).unwrap();
+        let loaded = db.get_habit(habit.id).unwrap().unwrap();
+        assert_eq!(loaded.metric, habit.metric);
+        assert_eq!(loaded.schedule, habit.schedule);
+        assert_eq!(db.calculate_stats(&habit).unwrap().completed_entries, 0);
+
+        let date = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        db.upsert_entry(&habit.entry_with_value(date, 60.0)).unwrap();
+        let progress = db.period_progress(&habit, date).unwrap().unwrap();
+        assert_eq!(progress.per, Period::Month);
+        assert_eq!(progress.achieved, 60.0);
+
+        let skip = Skip::day(habit.id, date);
+        db.insert_skip(&skip).unwrap();
+        db.insert_skip(&Skip::vacation(date, date.succ_opt().unwrap()))
+            .unwrap();
+        assert_eq!(db.get_skips_for_habit(habit.id).unwrap().len(), 2);
+        assert_eq!(db.get_skips_on(date.succ_opt().unwrap()).unwrap().len(), 1);
+        db.delete_skip(skip.id).unwrap();
+        assert_eq!(db.list_skips().unwrap().len(), 1);
+    }
 }
@@ -0,0 +1,607 @@
+//! CSV and JSON export of habits, entries and statistics, and the matching import.
+//!
+//! A CSV export is a directory holding `habits.csv`, `entries.csv` and
+//! `stats.csv`; a JSON export is a single `habits.json` with all three plus
+//! planned skips and vacations. Statistics are derived data and are ignored
+//! on import. Habits and entries keep their ids, so importing the same
+//! export twice updates in place.
+
+use crate::config::ExportFormat;
+use crate::db::Database;
+use crate::models::{Habit, HabitEntry, HabitId, HabitStats, Metric, Period, Schedule, Skip};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// File names inside an export directory.
+pub const HABITS_CSV: &str = "habits.csv";
+pub const ENTRIES_CSV: &str = "entries.csv";
+pub const STATS_CSV: &str = "stats.csv";
+pub const JSON_FILE: &str = "habits.json";
+
+const HABIT_COLUMNS: &[&str] = &[
+    "id",
+    "name",
+    "description",
+    "schedule",
+    "metric",
+    "goal",
+    "unit",
+    "period",
+    "color",
+    "created_at",
+    "archived",
+];
+const ENTRY_COLUMNS: &[&str] = &[
+    "id",
+    "habit_id",
+    "habit",
+    "date",
+    "completed",
+    "value",
+    "notes",
+    "created_at",
+];
+const STATS_COLUMNS: &[&str] = &[
+    "habit_id",
+    "habit",
+    "total_entries",
+    "completed_entries",
+    "completion_rate",
+    "current_streak",
+    "best_streak",
+    "average_value",
+    "total_value",
+];
+
+/// Inclusive range of entry dates to export. Open ends are unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+/// Streak and completion summary for one habit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HabitSummary {
+    pub habit_id: HabitId,
+    pub habit: String,
+    #[serde(flatten)]
+    pub stats: HabitStats,
+}
+
+/// The contents of an export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportData {
+    pub exported_at: DateTime<Utc>,
+    pub habits: Vec<Habit>,
+    pub entries: Vec<HabitEntry>,
+    #[serde(default)]
+    pub stats: Vec<HabitSummary>,
+    #[serde(default)]
+    pub skips: Vec<Skip>,
+}
+
+/// Outcome of an import.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub habits_created: usize,
+    pub habits_updated: usize,
+    pub entries: usize,
+    pub skips: usize,
+    /// Entries and skips whose habit is neither in the file nor the database
+    pub skipped: usize,
+}
+
+impl ImportSummary {
+    pub fn label(&self) -> String {
+        let mut label = format!(
+            "{} habits created, {} updated, {} entries",
+            self.habits_created, self.habits_updated, self.entries
+        );
+        if self.skips > 0 {
+            label.push_str(&format!(", {} skips", self.skips));
+        }
+        if self.skipped > 0 {
+            label.push_str(&format!(", {} skipped", self.skipped));
+        }
+        label
+    }
+}
+
+impl ExportData {
+    /// Collect every habit, archived ones included, with its entries in `range`
+    /// and its all-time statistics.
+    pub fn collect(db: &Database, range: DateRange) -> Result<Self> {
+        let habits = db.list_habits(true)?;
+        let entries = db.list_entries(range.from, range.to)?;
+        let mut stats = Vec::with_capacity(habits.len());
+        for habit in &habits {
+            stats.push(HabitSummary {
+                habit_id: habit.id,
+                habit: habit.name.clone(),
+                stats: db.calculate_stats(habit)?,
+            });
+        }
+        Ok(Self {
+            exported_at: Utc::now(),
+            habits,
+            entries,
+            stats,
+            skips: db.list_skips()?,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(text: &str) -> Result<Self> {
+        serde_json::from_str(text).context("invalid habit export")
+    }
+
+    pub fn habits_csv(&self) -> Result<String> {
+        let mut rows = Vec::with_capacity(self.habits.len());
+        for habit in &self.habits {
+            let (metric, goal, unit, period) = match &habit.metric {
+                Metric::Binary => ("binary", String::new(), String::new(), ""),
+                Metric::Quantity { goal, unit } => ("quantity", goal.to_string(), unit.clone(), ""),
+                Metric::Cumulative { goal, unit, per } => {
+                    ("cumulative", goal.to_string(), unit.clone(), per.name())
+                }
+            };
+            rows.push(vec![
+                habit.id.to_string(),
+                habit.name.clone(),
+                habit.description.clone().unwrap_or_default(),
+                serde_json::to_string(&habit.schedule)?,
+                metric.to_string(),
+                goal,
+                unit,
+                period.to_string(),
+                habit.color.clone().unwrap_or_default(),
+                habit.created_at.to_rfc3339(),
+                habit.archived.to_string(),
+            ]);
+        }
+        write_csv(HABIT_COLUMNS, rows)
+    }
+
+    pub fn entries_csv(&self) -> Result<String> {
+        let names: HashMap<HabitId, &str> = self
+            .habits
+            .iter()
+            .map(|h| (h.id, h.name.as_str()))
+            .collect();
+        let rows = self.entries.iter().map(|entry| {
+            vec![
+                entry.id.to_string(),
+                entry.habit_id.to_string(),
+                names.get(&entry.habit_id).unwrap_or(&"").to_string(),
+                entry.date.to_string(),
+                entry.completed.to_string(),
+                entry.value.map(|v| v.to_string()).unwrap_or_default(),
+                entry.notes.clone().unwrap_or_default(),
+                entry.created_at.to_rfc3339(),
+            ]
+        });
+        write_csv(ENTRY_COLUMNS, rows)
+    }
+
+    pub fn stats_csv(&self) -> Result<String> {
+        let optional = |v: Option<f64>| v.map(|v| format!("{:.2}", v)).unwrap_or_default();
+        let rows = self.stats.iter().map(|summary| {
+            let stats = &summary.stats;
+            vec![
+                summary.habit_id.to_string(),
+                summary.habit.clone(),
+                stats.total_entries.to_string(),
+                stats.completed_entries.to_string(),
+                format!("{:.3}", stats.completion_rate),
+                stats.current_streak.to_string(),
+                stats.best_streak.to_string(),
+                optional(stats.average_value),
+                optional(stats.total_value),
+            ]
+        });
+        write_csv(STATS_COLUMNS, rows)
+    }
+
+    /// Parse the contents of `habits.csv` and `entries.csv`.
+    ///
+    /// Columns are matched by header name. Rows without an id get a fresh
+    /// one, entries without a `habit_id` are matched to a habit by name, and
+    /// a blank `completed` on a quantity entry is derived from the goal.
+    /// Schedules may be JSON or a description such as "3/week".
+    pub fn from_csv(habits: &str, entries: &str) -> Result<Self> {
+        let mut data = Self {
+            exported_at: Utc::now(),
+            habits: Vec::new(),
+            entries: Vec::new(),
+            stats: Vec::new(),
+            skips: Vec::new(),
+        };
+
+        for (number, row) in csv_records(habits)? {
+            let habit =
+                parse_habit(&row).with_context(|| format!("{} row {}", HABITS_CSV, number))?;
+            data.habits.push(habit);
+        }
+
+        for (number, row) in csv_records(entries)? {
+            let entry = parse_entry(&row, &data.habits)
+                .with_context(|| format!("{} row {}", ENTRIES_CSV, number))?;
+            data.entries.push(entry);
+        }
+        Ok(data)
+    }
+
+    /// Write habits and entries to the database, updating those with known ids.
+    pub fn import(&self, db: &Database) -> Result<ImportSummary> {
+        let mut summary = ImportSummary::default();
+        let mut known: HashSet<HabitId> = db.list_habits(true)?.into_iter().map(|h| h.id).collect();
+
+        for habit in &self.habits {
+            if known.contains(&habit.id) {
+                db.update_habit(habit)?;
+                summary.habits_updated += 1;
+            } else {
+                db.insert_habit(habit)?;
+                known.insert(habit.id);
+                summary.habits_created += 1;
+            }
+        }
+
+        for entry in &self.entries {
+            if known.contains(&entry.habit_id) {
+                db.upsert_entry(entry)?;
+                summary.entries += 1;
+            } else {
+                summary.skipped += 1;
+            }
+        }
+
+        for skip in &self.skips {
+            if skip.habit_id.is_none_or(|id| known.contains(&id)) {
+                db.insert_skip(skip)?;
+                summary.skips += 1;
+            } else {
+                summary.skipped += 1;
+            }
+        }
+        Ok(summary)
+    }
+}
+
+/// Export everything to `dir`, returning the files written.
+pub fn export_to(
+    db: &Database,
+    dir: &Path,
+    format: ExportFormat,
+    range: DateRange,
+) -> Result<Vec<PathBuf>> {
+    let data = ExportData::collect(db, range)?;
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+
+    let files = match format {
+        ExportFormat::Json => vec![(JSON_FILE, data.to_json()?)],
+        ExportFormat::Csv => vec![
+            (HABITS_CSV, data.habits_csv()?),
+            (ENTRIES_CSV, data.entries_csv()?),
+            (STATS_CSV, data.stats_csv()?),
+        ],
+    };
+
+    let mut written = Vec::with_capacity(files.len());
+    for (name, content) in files {
+        let path = dir.join(name);
+        std::fs::write(&path, content).with_context(|| format!("writing {}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Import a JSON export file, or a CSV export directory (or any file in one).
+pub fn import_from(db: &Database, path: &Path) -> Result<ImportSummary> {
+    let data = if path.extension().is_some_and(|e| e == "json") {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        ExportData::from_json(&text)?
+    } else {
+        let dir = if path.is_dir() {
+            path
+        } else {
+            path.parent().unwrap_or(Path::new("."))
+        };
+        let read = |name: &str| {
+            let file = dir.join(name);
+            std::fs::read_to_string(&file).with_context(|| format!("reading {}", file.display()))
+        };
+        if !dir.join(HABITS_CSV).exists() && dir.join(JSON_FILE).exists() {
+            return import_from(db, &dir.join(JSON_FILE));
+        }
+        let entries = if dir.join(ENTRIES_CSV).exists() {
+            read(ENTRIES_CSV)?
+        } else {
+            String::new()
+        };
+        ExportData::from_csv(&read(HABITS_CSV)?, &entries)?
+    };
+    data.import(db)
+}
+
+fn parse_habit(row: &HashMap<String, String>) -> Result<Habit> {
+    let field = |name: &str| row.get(name).map(|v| v.trim()).unwrap_or_default();
+    let optional = |name: &str| Some(field(name).to_string()).filter(|v| !v.is_empty());
+
+    let name = field("name");
+    if name.is_empty() {
+        bail!("habit without a name");
+    }
+    let mut habit = Habit::new_binary(name);
+    if let Some(id) = optional("id") {
+        habit.id = Uuid::parse_str(&id).with_context(|| format!("invalid id '{}'", id))?;
+    }
+    habit.description = optional("description");
+    if let Some(schedule) = optional("schedule") {
+        habit.schedule = serde_json::from_str::<Schedule>(&schedule)
+            .ok()
+            .or_else(|| Schedule::parse(&schedule, Local::now().date_naive()))
+            .ok_or_else(|| anyhow!("invalid schedule '{}'", schedule))?;
+    }
+    let goal = || -> Result<f64> {
+        let goal = field("goal");
+        goal.parse()
+            .with_context(|| format!("invalid goal '{}'", goal))
+    };
+    match field("metric") {
+        "quantity" => {
+            habit.metric = Metric::Quantity {
+                goal: goal()?,
+                unit: field("unit").to_string(),
+            }
+        }
+        "cumulative" => {
+            habit.metric = Metric::Cumulative {
+                goal: goal()?,
+                unit: field("unit").to_string(),
+                per: Period::parse(field("period"))
+                    .ok_or_else(|| anyhow!("invalid period '{}'", field("period")))?,
+            }
+        }
+        _ => {}
+    }
+    habit.color = optional("color");
+    if let Some(created) = optional("created_at") {
+        habit.created_at = parse_timestamp(&created)?;
+    }
+    habit.archived = parse_bool(field("archived"))?;
+    Ok(habit)
+}
+
+fn parse_entry(row: &HashMap<String, String>, habits: &[Habit]) -> Result<HabitEntry> {
+    let field = |name: &str| row.get(name).map(|v| v.trim()).unwrap_or_default();
+
+    let habit_id = match field("habit_id") {
+        "" => habits
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case(field("habit")))
+            .map(|h| h.id)
+            .ok_or_else(|| anyhow!("unknown habit '{}'", field("habit")))?,
+        id => Uuid::parse_str(id).with_context(|| format!("invalid habit_id '{}'", id))?,
+    };
+    let date = NaiveDate::parse_from_str(field("date"), "%Y-%m-%d")
+        .with_context(|| format!("invalid date '{}'", field("date")))?;
+
+    let mut entry = HabitEntry::new_binary(habit_id, date, parse_bool(field("completed"))?);
+    if !field("id").is_empty() {
+        entry.id = Uuid::parse_str(field("id"))
+            .with_context(|| format!("invalid id '{}'", field("id")))?;
+    }
+    if !field("value").is_empty() {
+        entry.value = Some(
+            field("value")
+                .parse()
+                .with_context(|| format!("invalid value '{}'", field("value")))?,
+        );
+    }
+    if field("completed").is_empty() {
+        let goal = habits
+            .iter()
+            .find(|h| h.id == habit_id)
+            .and_then(Habit::goal);
+        if let (Some(value), Some(goal)) = (entry.value, goal) {
+            entry.completed = value >= goal;
+        }
+    }
+    entry.notes = Some(field("notes").to_string()).filter(|n| !n.is_empty());
+    if !field("created_at").is_empty() {
+        entry.created_at = parse_timestamp(field("created_at"))?;
+    }
+    Ok(entry)
+}
+
+fn parse_bool(value: &str) -> Result<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "1" | "x" => Ok(true),
+        "false" | "no" | "0" | "" => Ok(false),
+        other => bail!("invalid boolean '{}'", other),
+    }
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .with_context(|| format!("invalid timestamp '{}'", value))
+}
+
+// CSV
+
+fn write_csv(columns: &[&str], rows: impl IntoIterator<Item = Vec<String>>) -> Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(columns)?;
+    for row in rows {
+        writer.write_record(&row)?;
+    }
+    let bytes = writer.into_inner().map_err(|e| anyhow!("writing CSV: {}", e))?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+/// Rows keyed by their header, numbered from 2 (after the header) for error messages.
+///
+/// Rows may be shorter than the header; missing fields are left out.
+fn csv_records(text: &str) -> Result<Vec<(usize, HashMap<String, String>)>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let header: Vec<String> = reader
+        .headers()?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    reader
+        .records()
+        .enumerate()
+        .map(|(i, record)| {
+            let record = record.with_context(|| format!("row {}", i + 2))?;
+            let fields = header.iter().cloned().zip(record.iter().map(str::to_string));
+            Ok((i + 2, fields.collect()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Weekday;
+
+    fn sample_db() -> (Database, Habit, Habit) {
+        let db = Database::in_memory().unwrap();
+        let mut read = Habit::new_binary("Read, \"deeply\"");
+        read.schedule = Schedule::Weekly {
+            days: vec![Weekday::Mon, Weekday::Thu],
+        };
+        read.description = Some("Two lines\nof description".to_string());
+        let water = Habit::new_quantitative("Water", 8.0, "glasses");
+        db.insert_habit(&read).unwrap();
+        db.insert_habit(&water).unwrap();
+
+        let day = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let mut entry = HabitEntry::new_binary(read.id, day, true);
+        entry.notes = Some("chapter 3, done".to_string());
+        db.upsert_entry(&entry).unwrap();
+        db.upsert_entry(&HabitEntry::new_quantity(water.id, day, 5.0, 8.0))
+            .unwrap();
+        db.upsert_entry(&HabitEntry::new_quantity(
+            water.id,
+            day.succ_opt().unwrap(),
+            9.0,
+            8.0,
+        ))
+        .unwrap();
+        (db, read, water)
+    }
+
+    #[test]
+    fn test_csv_quoting_round_trip() {
+        let fields = vec![
+            "plain".to_string(),
+            "a,b".to_string(),
+            "say \"hi\"".to_string(),
+            "two\nlines".to_string(),
+            String::new(),
+        ];
+        let text = write_csv(&["a", "b", "c", "d", "e"], [fields]).unwrap() + "x\n";
+        let records = csv_records(&text).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].1["b"], "a,b");
+        assert_eq!(records[0].1["c"], "say \"hi\"");
+        assert_eq!(records[0].1["d"], "two\nlines");
+        assert_eq!(records[1], (3, HashMap::from([("a".to_string(), "x".to_string())])));
+    }
+
+    #[test]
+    fn test_csv_export_import() {
+        let (db, read, water) = sample_db();
+        let data = ExportData::collect(&db, DateRange::default()).unwrap();
+
+        let stats = data.stats_csv().unwrap();
+        assert!(stats.contains(&format!("{},Water,2,1,0.500", water.id)));
+
+        let copy = Database::in_memory().unwrap();
+        let parsed =
+            ExportData::from_csv(&data.habits_csv().unwrap(), &data.entries_csv().unwrap()).unwrap();
+        let summary = parsed.import(&copy).unwrap();
+        assert_eq!(summary.habits_created, 2);
+        assert_eq!(summary.entries, 3);
+
+        let imported = copy.get_habit(read.id).unwrap().unwrap();
+        assert_eq!(imported.name, read.name);
+        assert_eq!(imported.schedule, read.schedule);
+        assert_eq!(imported.description, read.description);
+        assert_eq!(
+            copy.get_habit(water.id).unwrap().unwrap().metric,
+            water.metric
+        );
+        let day = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let entry = copy.get_entry(read.id, day).unwrap().unwrap();
+        assert_eq!(entry.notes.as_deref(), Some("chapter 3, done"));
+
+        // Importing again updates instead of duplicating
+        let again = parsed.import(&copy).unwrap();
+        assert_eq!(again.habits_updated, 2);
+        assert_eq!(copy.list_habits(true).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_json_export_import_with_range() {
+        let (db, _, water) = sample_db();
+        let day = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        db.insert_skip(&Skip::vacation(day, day)).unwrap();
+        let range = DateRange {
+            from: Some(day),
+            to: None,
+        };
+        let json = ExportData::collect(&db, range).unwrap().to_json().unwrap();
+
+        let data = ExportData::from_json(&json).unwrap();
+        assert_eq!(data.entries.len(), 1);
+        assert_eq!(data.stats.len(), 2);
+
+        let copy = Database::in_memory().unwrap();
+        assert_eq!(data.import(&copy).unwrap().skips, 1);
+        assert_eq!(copy.get_skips_on(day).unwrap().len(), 1);
+        let entry = copy.get_entry(water.id, day).unwrap().unwrap();
+        assert!(entry.completed);
+        assert_eq!(entry.value, Some(9.0));
+    }
+
+    #[test]
+    fn test_hand_written_csv() {
+        let habits = "Name,Schedule,Metric,Goal,Unit,Period\n\
+                      Stretch,\"mon,thu\",,,,\n\
+                      Steps,,quantity,10000,steps,\n\
+                      Swim,2/week,cumulative,3,km,week\n";
+        let entries =
+            "habit,date,completed,value\nstretch,2024-01-02,yes,\nSteps,2024-01-02,,12000\n";
+        let data = ExportData::from_csv(habits, entries).unwrap();
+        assert_eq!(data.habits.len(), 3);
+        assert_eq!(
+            data.habits[0].schedule,
+            Schedule::Weekly {
+                days: vec![Weekday::Mon, Weekday::Thu]
+            }
+        );
+        assert_eq!(data.habits[2].goal_period(), Some(Period::Week));
+        assert_eq!(data.entries[0].habit_id, data.habits[0].id);
+        assert!(data.entries[0].completed);
+        assert_eq!(data.entries[1].value, Some(12000.0));
+        assert!(data.entries[1].completed);
+
+        let err = ExportData::from_csv(habits, "habit,date\nYoga,2024-01-02\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("row 2"));
+    }
+}
@@ -4,19 +4,35 @@
 mod app;
 mod config;
 mod db;
+mod export;
 mod models;
+mod reminder;
+mod streak;
 mod ui;
 
 use app::App;
+use chrono::NaiveDate;
+use config::{Config, ExportFormat};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use db::Database;
+use export::DateRange;
 use ratatui::{backend::CrosstermBackend, Terminal};
 use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
 
 fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("export") => return run_export(&args[2..]),
+        Some("import") => return run_import(&args[2..]),
+        _ => {}
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -50,18 +66,78 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> anyhow::
     loop {
         terminal.draw(|f| ui::draw(f, &mut app))?;
 
-        if let Event::Key(key) = event::read()? {
-            if key.code == KeyCode::Char('q') && key.modifiers.is_empty() {
-                if !app.is_editing() {
+        if event::poll(Duration::from_millis(500))? {
+            if let Event::Key(key) = event::read()? {
+                if key.code == KeyCode::Char('q') && key.modifiers.is_empty() && !app.is_editing() {
                     break;
                 }
+                if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    break;
+                }
+                app.handle_key(key);
             }
-            if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
-                break;
+        }
+        app.tick();
+    }
+
+    Ok(())
+}
+
+fn open_db() -> anyhow::Result<Database> {
+    let db_path = Config::db_path().unwrap_or_else(|| "habits.db".into());
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    Ok(Database::open(&db_path)?)
+}
+
+/// `habit-tracker export [<dir>] [--format csv|json] [--from DATE] [--to DATE]`
+///
+/// Defaults to the `[export]` config section.
+fn run_export(args: &[String]) -> anyhow::Result<()> {
+    let config = Config::load();
+    let mut dir: Option<PathBuf> = None;
+    let mut format = config.export.format;
+    let mut range = DateRange::default();
+
+    let date = |value: Option<&String>| -> anyhow::Result<NaiveDate> {
+        let value = value.map(String::as_str).unwrap_or_default();
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| anyhow::anyhow!("invalid date '{}', expected YYYY-MM-DD", value))
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" | "-f" => {
+                format = match args.next().map(String::as_str) {
+                    Some("csv") => ExportFormat::Csv,
+                    Some("json") => ExportFormat::Json,
+                    other => anyhow::bail!("unknown format: {}", other.unwrap_or_default()),
+                }
             }
-            app.handle_key(key);
+            "--from" => range.from = Some(date(args.next())?),
+            "--to" => range.to = Some(date(args.next())?),
+            _ => dir = Some(PathBuf::from(arg)),
         }
     }
+    let dir = dir.unwrap_or_else(|| config.export_dir());
+
+    let db = open_db()?;
+    for file in export::export_to(&db, &dir, format, range)? {
+        println!("Wrote {}", file.display());
+    }
+    Ok(())
+}
+
+/// `habit-tracker import <file.json|dir>`
+fn run_import(args: &[String]) -> anyhow::Result<()> {
+    let path = args
+        .first()
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow::anyhow!("usage: habit-tracker import <habits.json|export-dir>"))?;
 
+    let db = open_db()?;
+    let summary = export::import_from(&db, &path)?;
+    println!("Imported {}: {}", path.display(), summary.label());
     Ok(())
 }
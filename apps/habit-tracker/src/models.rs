@@ -1,6 +1,6 @@
 //! Data models for habit tracking.
 
-use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -74,7 +74,7 @@ impl Habit {
     pub fn goal(&self) -> Option<f64> {
         match &self.metric {
             Metric::Binary => None,
-            Metric::Quantity { goal, .. } => Some(*goal),
+            Metric::Quantity { goal, .. } | Metric::Cumulative { goal, .. } => Some(*goal),
         }
     }
 
@@ -82,7 +82,99 @@ impl Habit {
     pub fn unit(&self) -> Option<&str> {
         match &self.metric {
             Metric::Binary => None,
-            Metric::Quantity { unit, .. } => Some(unit),
+            Metric::Quantity { unit, .. } | Metric::Cumulative { unit, .. } => Some(unit),
+        }
+    }
+
+    /// Period whose total decides success, for cumulative goals and
+    /// "N times per week/month" schedules. `None` for day-by-day habits.
+    pub fn goal_period(&self) -> Option<Period> {
+        match (&self.metric, &self.schedule) {
+            (Metric::Cumulative { per, .. }, _) => Some(*per),
+            (_, Schedule::Times { per, .. }) => Some(*per),
+            _ => None,
+        }
+    }
+
+    /// Target for a whole period: the cumulative goal, or the number of
+    /// completions for a frequency schedule.
+    pub fn period_target(&self) -> Option<f64> {
+        match (&self.metric, &self.schedule) {
+            (Metric::Cumulative { goal, .. }, _) => Some(*goal),
+            (_, Schedule::Times { times, .. }) => Some(*times as f64),
+            _ => None,
+        }
+    }
+
+    /// Create an entry recording `value` on `date`.
+    ///
+    /// A day counts as completed once the daily goal is reached; for
+    /// cumulative goals any progress does.
+    pub fn entry_with_value(&self, date: NaiveDate, value: f64) -> HabitEntry {
+        match &self.metric {
+            Metric::Cumulative { .. } => {
+                let mut entry = HabitEntry::new_quantity(self.id, date, value, 0.0);
+                entry.completed = value > 0.0;
+                entry
+            }
+            _ => HabitEntry::new_quantity(self.id, date, value, self.goal().unwrap_or(1.0)),
+        }
+    }
+
+    /// Format a streak length in the habit's unit, e.g. "3 days" or "1 week".
+    pub fn streak_label(&self, count: u32) -> String {
+        let unit = match self.goal_period() {
+            None => "day",
+            Some(per) => per.name(),
+        };
+        format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+    }
+}
+
+/// Calendar period for frequency targets and cumulative goals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    /// Monday to Sunday.
+    Week,
+    /// Calendar month.
+    Month,
+}
+
+impl Period {
+    /// Parse a period name such as "week", "wk", "month" or "mo".
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "w" | "wk" | "week" | "weekly" => Some(Self::Week),
+            "m" | "mo" | "month" | "monthly" => Some(Self::Month),
+            _ => None,
+        }
+    }
+
+    /// Get display name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+
+    /// First and last day of the period containing `date`.
+    pub fn bounds(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            Self::Week => {
+                let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                (start, start + Duration::days(6))
+            }
+            Self::Month => {
+                let start = date.with_day(1).unwrap();
+                let next = if date.month() == 12 {
+                    NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+                };
+                (start, next.unwrap().pred_opt().unwrap())
+            }
         }
     }
 }
@@ -99,6 +191,8 @@ pub enum Schedule {
     Monthly { days: Vec<u8> },
     /// Every N days.
     Interval { every_n_days: u32, start_date: NaiveDate },
+    /// Any N days per week or month.
+    Times { times: u32, per: Period },
 }
 
 impl Schedule {
@@ -112,7 +206,88 @@ impl Schedule {
                 let diff = date.signed_duration_since(*start_date).num_days();
                 diff >= 0 && diff as u32 % every_n_days == 0
             }
+            // Can be done any day until the period's target is met
+            Self::Times { .. } => true,
+        }
+    }
+
+    /// Parse a schedule description.
+    ///
+    /// Accepts "daily", weekday lists ("mon,wed,fri", "weekdays",
+    /// "weekends"), days of the month ("1,15"), "every 3 days" and
+    /// frequency targets ("3/week", "2x month", "4 per month").
+    /// Interval schedules start on `today`.
+    pub fn parse(s: &str, today: NaiveDate) -> Option<Self> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "daily" | "every day" => return Some(Self::Daily),
+            "weekdays" => {
+                return Some(Self::Weekly {
+                    days: vec![
+                        Weekday::Mon,
+                        Weekday::Tue,
+                        Weekday::Wed,
+                        Weekday::Thu,
+                        Weekday::Fri,
+                    ],
+                })
+            }
+            "weekends" => {
+                return Some(Self::Weekly {
+                    days: vec![Weekday::Sat, Weekday::Sun],
+                })
+            }
+            _ => {}
         }
+
+        if let Some(rest) = s.strip_prefix("every ") {
+            let n = rest
+                .trim_end_matches("days")
+                .trim_end_matches("day")
+                .trim_end_matches('d')
+                .trim();
+            let every_n_days = n.parse().ok().filter(|&n| n > 0)?;
+            return Some(Self::Interval {
+                every_n_days,
+                start_date: today,
+            });
+        }
+
+        // "3/week", "3x week", "3 per week", "3 times a week"
+        let normalized = s
+            .replace(" times a ", "/")
+            .replace(" times per ", "/")
+            .replace(" per ", "/")
+            .replace("x ", "/")
+            .replace('x', "/");
+        if let Some((times, per)) = normalized.split_once('/') {
+            let times = times.trim().parse().ok().filter(|&n| n > 0)?;
+            return Some(Self::Times {
+                times,
+                per: Period::parse(per)?,
+            });
+        }
+
+        let parts: Vec<&str> = s.split([',', ' ']).filter(|p| !p.is_empty()).collect();
+        if parts.is_empty() {
+            return None;
+        }
+        if parts.iter().all(|p| p.chars().all(|c| c.is_ascii_digit())) {
+            let mut days: Vec<u8> = parts
+                .iter()
+                .map(|p| p.parse().ok().filter(|d| (1..=31).contains(d)))
+                .collect::<Option<_>>()?;
+            days.sort_unstable();
+            days.dedup();
+            return Some(Self::Monthly { days });
+        }
+        let mut days: Vec<Weekday> = parts
+            .iter()
+            .map(|p| p.parse::<Weekday>().ok())
+            .collect::<Option<_>>()?;
+        days.sort_by_key(|d| d.num_days_from_monday());
+        days.dedup();
+        Some(Self::Weekly { days })
     }
 
     /// Get display name.
@@ -128,6 +303,26 @@ impl Schedule {
                 format!("Monthly: {}", day_strs.join(", "))
             }
             Self::Interval { every_n_days, .. } => format!("Every {} days", every_n_days),
+            Self::Times { times, per } => format!("{}x per {}", times, per.name()),
+        }
+    }
+
+    /// Get a description that [`Schedule::parse`] reads back.
+    pub fn spec(&self) -> String {
+        match self {
+            Self::Daily => "daily".to_string(),
+            Self::Weekly { days } => days
+                .iter()
+                .map(|d| d.to_string().to_lowercase())
+                .collect::<Vec<_>>()
+                .join(","),
+            Self::Monthly { days } => days
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+            Self::Interval { every_n_days, .. } => format!("every {} days", every_n_days),
+            Self::Times { times, per } => format!("{}/{}", times, per.name()),
         }
     }
 }
@@ -146,14 +341,54 @@ pub enum Metric {
     Binary,
     /// Numeric goal.
     Quantity { goal: f64, unit: String },
+    /// Numeric goal summed over a week or month.
+    Cumulative { goal: f64, unit: String, per: Period },
 }
 
 impl Metric {
+    /// Parse a metric description: "yes/no", "8 glasses" or "50 km/week".
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if matches!(s.to_lowercase().as_str(), "yes/no" | "binary" | "") {
+            return Some(Self::Binary);
+        }
+
+        let (amount, rest) = s.split_once(' ').unwrap_or((s, ""));
+        let goal: f64 = amount.parse().ok().filter(|g: &f64| *g > 0.0)?;
+        let rest = rest.trim();
+        let split = rest
+            .rsplit_once('/')
+            .or_else(|| rest.rsplit_once(" per "))
+            .or_else(|| rest.rsplit_once(" a "));
+        if let Some((unit, per)) = split {
+            if let Some(per) = Period::parse(per) {
+                return Some(Self::Cumulative {
+                    goal,
+                    unit: unit.trim().to_string(),
+                    per,
+                });
+            }
+        }
+        Some(Self::Quantity {
+            goal,
+            unit: rest.to_string(),
+        })
+    }
+
     /// Get display string.
     pub fn display(&self) -> String {
         match self {
             Self::Binary => "Yes/No".to_string(),
             Self::Quantity { goal, unit } => format!("{} {}", goal, unit),
+            Self::Cumulative { goal, unit, per } => format!("{} {}/{}", goal, unit, per.name()),
+        }
+    }
+
+    /// Get a description that [`Metric::parse`] reads back.
+    pub fn spec(&self) -> String {
+        match self {
+            Self::Binary => "yes/no".to_string(),
+            _ => self.display(),
         }
     }
 }
@@ -223,10 +458,77 @@ impl HabitEntry {
     }
 }
 
+/// A planned break: days that neither count toward nor break a streak.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Skip {
+    /// Unique identifier.
+    pub id: Uuid,
+    /// Skipped habit, or `None` for a vacation from all habits.
+    pub habit_id: Option<HabitId>,
+    /// First skipped day.
+    pub start: NaiveDate,
+    /// Last skipped day.
+    pub end: NaiveDate,
+    /// Optional reason.
+    pub reason: Option<String>,
+}
+
+impl Skip {
+    /// Skip a single day of one habit.
+    pub fn day(habit_id: HabitId, date: NaiveDate) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            habit_id: Some(habit_id),
+            start: date,
+            end: date,
+            reason: None,
+        }
+    }
+
+    /// A vacation from all habits.
+    pub fn vacation(start: NaiveDate, end: NaiveDate) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            habit_id: None,
+            start: start.min(end),
+            end: start.max(end),
+            reason: None,
+        }
+    }
+
+    /// Check if this skips `habit` on `date`.
+    pub fn covers(&self, habit: HabitId, date: NaiveDate) -> bool {
+        self.habit_id.is_none_or(|id| id == habit) && self.start <= date && date <= self.end
+    }
+
+    /// Check if this is a vacation from all habits.
+    pub fn is_vacation(&self) -> bool {
+        self.habit_id.is_none()
+    }
+}
+
+/// Progress toward a habit's target for the current week or month.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PeriodProgress {
+    /// Period the target applies to.
+    pub per: Period,
+    /// Completions or summed value so far.
+    pub achieved: f64,
+    /// Target, reduced for skipped days.
+    pub target: f64,
+}
+
+impl PeriodProgress {
+    /// Check if the target has been met.
+    pub fn is_met(&self) -> bool {
+        self.achieved >= self.target
+    }
+}
+
 /// Streak information for a habit.
 #[derive(Debug, Clone, Default)]
 pub struct StreakInfo {
-    /// Current streak count.
+    /// Current streak count (days, or weeks/months for period targets).
     pub current: u32,
     /// Best streak ever.
     pub best: u32,
@@ -267,7 +569,7 @@ impl Correlation {
 }
 
 /// Statistics for a habit.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct HabitStats {
     /// Total entries.
     pub total_entries: u32,
@@ -330,6 +632,108 @@ mod tests {
         assert!(schedule.is_due_on(NaiveDate::from_ymd_opt(2024, 1, 4).unwrap()));
     }
 
+    #[test]
+    fn test_parse_schedule() {
+        let today = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let parse = |s: &str| Schedule::parse(s, today);
+
+        assert_eq!(parse("Daily"), Some(Schedule::Daily));
+        assert_eq!(
+            parse("fri, mon wed"),
+            Some(Schedule::Weekly {
+                days: vec![Weekday::Mon, Weekday::Wed, Weekday::Fri]
+            })
+        );
+        assert_eq!(parse("15,1"), Some(Schedule::Monthly { days: vec![1, 15] }));
+        assert_eq!(
+            parse("every 3 days"),
+            Some(Schedule::Interval {
+                every_n_days: 3,
+                start_date: today
+            })
+        );
+        for s in ["3/week", "3x week", "3 times a week", "3 per wk"] {
+            assert_eq!(
+                parse(s),
+                Some(Schedule::Times {
+                    times: 3,
+                    per: Period::Week
+                }),
+                "{}",
+                s
+            );
+        }
+        assert_eq!(
+            parse("2x month"),
+            Some(Schedule::Times {
+                times: 2,
+                per: Period::Month
+            })
+        );
+        for schedule in [
+            Schedule::Daily,
+            Schedule::Weekly {
+                days: vec![Weekday::Tue, Weekday::Sat],
+            },
+            Schedule::Monthly { days: vec![1, 15] },
+            Schedule::Times {
+                times: 2,
+                per: Period::Month,
+            },
+        ] {
+            assert_eq!(parse(&schedule.spec()), Some(schedule));
+        }
+        assert_eq!(parse("32"), None);
+        assert_eq!(parse("sometimes"), None);
+        assert_eq!(parse("0/week"), None);
+    }
+
+    #[test]
+    fn test_parse_metric() {
+        assert_eq!(Metric::parse("yes/no"), Some(Metric::Binary));
+        assert_eq!(
+            Metric::parse("8 glasses"),
+            Some(Metric::Quantity {
+                goal: 8.0,
+                unit: "glasses".to_string()
+            })
+        );
+        assert_eq!(
+            Metric::parse("50 km/week"),
+            Some(Metric::Cumulative {
+                goal: 50.0,
+                unit: "km".to_string(),
+                per: Period::Week
+            })
+        );
+        assert_eq!(
+            Metric::parse("20 pages per month"),
+            Some(Metric::Cumulative {
+                goal: 20.0,
+                unit: "pages".to_string(),
+                per: Period::Month
+            })
+        );
+        assert_eq!(Metric::parse("lots"), None);
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        // Jan 3, 2024 is a Wednesday
+        let date = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+        assert_eq!(
+            Period::Week.bounds(date),
+            (
+                NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2024, 1, 7).unwrap()
+            )
+        );
+        let date = NaiveDate::from_ymd_opt(2024, 12, 25).unwrap();
+        assert_eq!(Period::Month.bounds(date).1, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+        let date = NaiveDate::from_ymd_opt(2024, 2, 10).unwrap();
+        assert_eq!(Period::Month.bounds(date).1, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+    }
+
     #[test]
     fn test_habit_creation() {
         let habit = Habit::new_binary("Exercise");
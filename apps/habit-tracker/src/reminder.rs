@@ -0,0 +1,86 @@
+//! Daily in-app reminder.
+//!
+//! Fires once per day at `notifications.remind_time` (local time). If the app
+//! is started after that time, the reminder fires on the first tick.
+
+use crate::config::NotificationConfig;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+/// Tracks when the daily reminder is due.
+#[derive(Debug, Clone)]
+pub struct Reminder {
+    /// Time of day to remind at.
+    pub time: NaiveTime,
+    /// Date the reminder last fired.
+    pub last_fired: Option<NaiveDate>,
+}
+
+impl Reminder {
+    /// Create from config; `None` if no valid `remind_time` is set.
+    pub fn from_config(config: &NotificationConfig) -> Option<Self> {
+        let time = parse_time(config.remind_time.as_deref()?)?;
+        Some(Self {
+            time,
+            last_fired: None,
+        })
+    }
+
+    /// Check whether the reminder should fire at `now`, marking it fired for
+    /// the day if so.
+    pub fn check(&mut self, now: NaiveDateTime) -> bool {
+        if now.time() < self.time || self.last_fired == Some(now.date()) {
+            return false;
+        }
+        self.last_fired = Some(now.date());
+        true
+    }
+
+    /// Whether the reminder time has passed on `now`'s day.
+    pub fn has_passed(&self, now: NaiveDateTime) -> bool {
+        now.time() >= self.time
+    }
+}
+
+/// Parse an `HH:MM` time of day.
+pub fn parse_time(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, time: &str) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, day)
+            .unwrap()
+            .and_time(parse_time(time).unwrap())
+    }
+
+    #[test]
+    fn test_fires_once_per_day() {
+        let config = NotificationConfig {
+            remind_time: Some("20:00".to_string()),
+            incomplete_warning: true,
+        };
+        let mut reminder = Reminder::from_config(&config).unwrap();
+
+        assert!(!reminder.check(at(1, "19:59")));
+        assert!(reminder.check(at(1, "20:00")));
+        assert!(!reminder.check(at(1, "21:30")));
+        assert!(!reminder.check(at(2, "08:00")));
+        // Started late on the next day: fires straight away
+        assert!(reminder.check(at(2, "23:10")));
+    }
+
+    #[test]
+    fn test_invalid_time() {
+        let mut config = NotificationConfig {
+            remind_time: Some("8pm".to_string()),
+            ..Default::default()
+        };
+        assert!(Reminder::from_config(&config).is_none());
+        config.remind_time = None;
+        assert!(Reminder::from_config(&config).is_none());
+        assert_eq!(parse_time(" 07:05"), NaiveTime::from_hms_opt(7, 5, 0));
+    }
+}
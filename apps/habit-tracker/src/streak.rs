@@ -0,0 +1,248 @@
+//! Streak and period-target calculations.
+//!
+//! Day-by-day habits count consecutive scheduled days. Habits with a
+//! frequency schedule ("3x per week") or a cumulative goal count consecutive
+//! weeks or months in which the target was met. Skipped days are treated as
+//! unscheduled: they neither extend nor break a streak, and they shrink a
+//! period's target in proportion. Today, and the current week or month, are
+//! still in progress and only ever extend a streak.
+
+use crate::models::{Habit, HabitEntry, Metric, PeriodProgress, Skip, StreakInfo};
+use chrono::NaiveDate;
+use std::collections::HashSet;
+
+/// Calculate the streak for `habit` from all its entries and skips.
+pub fn calculate(
+    habit: &Habit,
+    entries: &[HabitEntry],
+    skips: &[Skip],
+    today: NaiveDate,
+) -> StreakInfo {
+    let Some(first) = entries
+        .iter()
+        .filter(|e| e.habit_id == habit.id)
+        .map(|e| e.date)
+        .filter(|d| *d <= today)
+        .min()
+    else {
+        return StreakInfo::default();
+    };
+    let skipped = |date: NaiveDate| skips.iter().any(|s| s.covers(habit.id, date));
+
+    let mut run = 0;
+    let mut best = 0;
+    let mut last_date = None;
+
+    if let Some(per) = habit.goal_period() {
+        let mut start = per.bounds(first).0;
+        while start <= today {
+            let end = per.bounds(start).1;
+            if let Some(progress) = period_progress(habit, entries, skips, start) {
+                if progress.is_met() {
+                    run += 1;
+                    best = best.max(run);
+                    last_date = entries
+                        .iter()
+                        .filter(|e| e.habit_id == habit.id && e.completed)
+                        .map(|e| e.date)
+                        .filter(|d| *d >= start && *d <= end.min(today))
+                        .max()
+                        .or(last_date);
+                } else if end < today {
+                    run = 0;
+                    last_date = None;
+                }
+            }
+            let Some(next) = end.succ_opt() else { break };
+            start = next;
+        }
+    } else {
+        let done: HashSet<NaiveDate> = entries
+            .iter()
+            .filter(|e| e.habit_id == habit.id && e.completed)
+            .map(|e| e.date)
+            .collect();
+        for date in first.iter_days().take_while(|d| *d <= today) {
+            if !habit.is_due_on(date) || skipped(date) {
+                continue;
+            }
+            if done.contains(&date) {
+                run += 1;
+                best = best.max(run);
+                last_date = Some(date);
+            } else if date < today {
+                run = 0;
+                last_date = None;
+            }
+        }
+    }
+
+    StreakInfo {
+        current: run,
+        best,
+        last_date,
+    }
+}
+
+/// Progress toward the target of the week or month containing `date`.
+///
+/// `None` for day-by-day habits, and for periods skipped entirely.
+pub fn period_progress(
+    habit: &Habit,
+    entries: &[HabitEntry],
+    skips: &[Skip],
+    date: NaiveDate,
+) -> Option<PeriodProgress> {
+    let per = habit.goal_period()?;
+    let target = habit.period_target()?;
+    let (start, end) = per.bounds(date);
+
+    let days = start.iter_days().take_while(|d| *d <= end);
+    let total = (end - start).num_days() + 1;
+    let available = days
+        .filter(|d| !skips.iter().any(|s| s.covers(habit.id, *d)))
+        .count() as i64;
+    if available == 0 {
+        return None;
+    }
+
+    let in_period = entries
+        .iter()
+        .filter(|e| e.habit_id == habit.id && e.date >= start && e.date <= end);
+    let (achieved, target) = if matches!(habit.metric, Metric::Cumulative { .. }) {
+        let sum = in_period.filter_map(|e| e.value).sum();
+        (sum, target * available as f64 / total as f64)
+    } else {
+        // Frequency: count completions, rounding the target up
+        let count = in_period.filter(|e| e.completed).count() as f64;
+        (count, (target * available as f64 / total as f64).ceil())
+    };
+
+    Some(PeriodProgress {
+        per,
+        achieved,
+        target,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Period, Schedule};
+    use chrono::{Duration, Weekday};
+
+    fn date(day: u32) -> NaiveDate {
+        // January 2024 starts on a Monday
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn done(habit: &Habit, days: &[u32]) -> Vec<HabitEntry> {
+        days.iter()
+            .map(|&d| HabitEntry::new_binary(habit.id, date(d), true))
+            .collect()
+    }
+
+    #[test]
+    fn test_daily_streak() {
+        let habit = Habit::new_binary("Read");
+        let entries = done(&habit, &[1, 2, 3, 5, 6]);
+
+        let streak = calculate(&habit, &entries, &[], date(6));
+        assert_eq!((streak.current, streak.best), (2, 3));
+        assert_eq!(streak.last_date, Some(date(6)));
+
+        // Today not done yet does not break the streak
+        let streak = calculate(&habit, &entries, &[], date(7));
+        assert_eq!(streak.current, 2);
+        // ...but yesterday does
+        let streak = calculate(&habit, &entries, &[], date(8));
+        assert_eq!(streak.current, 0);
+    }
+
+    #[test]
+    fn test_other_habit_entries_ignored() {
+        let habit = Habit::new_binary("Read");
+        let other = Habit::new_binary("Walk");
+        // Another habit's older entries must not start this habit's history
+        let mut entries = done(&other, &[1]);
+        entries.extend(done(&habit, &[3, 4]));
+
+        let streak = calculate(&habit, &entries, &[], date(4));
+        assert_eq!((streak.current, streak.best), (2, 2));
+        let streak = calculate(&habit, &done(&other, &[1, 2]), &[], date(4));
+        assert_eq!((streak.current, streak.best, streak.last_date), (0, 0, None));
+    }
+
+    #[test]
+    fn test_weekday_schedule_streak() {
+        let mut habit = Habit::new_binary("Gym");
+        habit.schedule = Schedule::Weekly {
+            days: vec![Weekday::Mon, Weekday::Wed, Weekday::Fri],
+        };
+        // Mon, Wed, Fri, Mon: unscheduled days in between don't matter
+        let entries = done(&habit, &[1, 3, 5, 8]);
+        assert_eq!(calculate(&habit, &entries, &[], date(9)).current, 4);
+        // Missing Wednesday the 10th breaks it
+        assert_eq!(calculate(&habit, &entries, &[], date(11)).current, 0);
+    }
+
+    #[test]
+    fn test_skips_keep_streak() {
+        let habit = Habit::new_binary("Meditate");
+        let entries = done(&habit, &[1, 2, 6, 7]);
+        let vacation = Skip::vacation(date(3), date(5));
+        let streak = calculate(&habit, &entries, &[vacation], date(7));
+        assert_eq!(streak.current, 4);
+
+        let other = Skip::day(uuid::Uuid::new_v4(), date(3));
+        assert_eq!(calculate(&habit, &entries, &[other], date(7)).current, 2);
+    }
+
+    #[test]
+    fn test_times_per_week() {
+        let mut habit = Habit::new_binary("Run");
+        habit.schedule = Schedule::Times {
+            times: 3,
+            per: Period::Week,
+        };
+        // Week 1: 3 runs, week 2: 3 runs, week 3: 1 run, week 4 in progress
+        let entries = done(&habit, &[1, 3, 6, 8, 9, 10, 16, 22]);
+
+        let streak = calculate(&habit, &entries, &[], date(22));
+        assert_eq!((streak.current, streak.best), (0, 2));
+
+        // Week 3 was mostly vacation: one run is enough
+        let vacation = Skip::vacation(date(17), date(21));
+        let streak = calculate(&habit, &entries, std::slice::from_ref(&vacation), date(22));
+        assert_eq!(streak.current, 3);
+
+        let progress = period_progress(&habit, &entries, &[vacation], date(18)).unwrap();
+        assert_eq!((progress.achieved, progress.target), (1.0, 1.0));
+        let progress = period_progress(&habit, &entries, &[], date(24)).unwrap();
+        assert!(!progress.is_met());
+    }
+
+    #[test]
+    fn test_cumulative_goal() {
+        let mut habit = Habit::new_binary("Cycling");
+        habit.metric = Metric::Cumulative {
+            goal: 100.0,
+            unit: "km".to_string(),
+            per: Period::Week,
+        };
+        let ride = |day, km| habit.entry_with_value(date(day), km);
+        let entries = vec![ride(1, 60.0), ride(4, 45.0), ride(9, 30.0), ride(14, 20.0)];
+
+        let streak = calculate(&habit, &entries, &[], date(15));
+        assert_eq!((streak.current, streak.best), (0, 1));
+        assert!(entries[3].completed);
+
+        let progress = period_progress(&habit, &entries, &[], date(10)).unwrap();
+        assert_eq!(progress.achieved, 50.0);
+        assert_eq!(progress.target, 100.0);
+
+        // Whole period skipped
+        let skip = Skip::vacation(date(8), date(8) + Duration::days(6));
+        assert!(period_progress(&habit, &entries, &[skip], date(10)).is_none());
+    }
+}
@@ -1,7 +1,9 @@
 //! UI rendering for habit tracker.
 
-use crate::app::{App, EditField, MessageType, View};
-use chrono::{Duration, Utc};
+use crate::app::{App, EditField, HeatCell, MessageType, View};
+use crate::config::WeekStart;
+use crate::models::Metric;
+use chrono::{Datelike, Duration, Local, Weekday};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -37,6 +39,10 @@ pub fn draw(f: &mut Frame, app: &mut App) {
     if app.editing {
         draw_edit_dialog(f, app);
     }
+
+    if let Some(habits) = &app.reminder_popup {
+        draw_reminder_popup(f, habits);
+    }
 }
 
 fn draw_header(f: &mut Frame, app: &App, area: Rect) {
@@ -92,6 +98,7 @@ fn draw_daily_view(f: &mut Frame, app: &App, area: Rect) {
         return;
     }
 
+    let warn_incomplete = app.warn_incomplete();
     let items: Vec<ListItem> = app
         .habits
         .iter()
@@ -100,22 +107,26 @@ fn draw_daily_view(f: &mut Frame, app: &App, area: Rect) {
             let entry = app.entries.get(&habit.id);
             let completed = entry.map_or(false, |e| e.completed);
 
-            let checkbox = if completed { "[x]" } else { "[ ]" };
+            let skipped = app.is_skipped(habit.id);
+            let (checkbox, checkbox_color) = if completed {
+                ("[x]", Color::Green)
+            } else if skipped {
+                ("[-]", Color::Blue)
+            } else if warn_incomplete {
+                ("[!]", Color::Yellow)
+            } else {
+                ("[ ]", Color::Gray)
+            };
 
             let mut spans = vec![
-                Span::styled(
-                    checkbox,
-                    Style::default().fg(if completed {
-                        Color::Green
-                    } else {
-                        Color::Gray
-                    }),
-                ),
+                Span::styled(checkbox, Style::default().fg(checkbox_color)),
                 Span::raw(" "),
             ];
 
             // Habit name
-            let name_style = if completed {
+            let name_style = if skipped && i != app.selected_index {
+                Style::default().fg(Color::DarkGray)
+            } else if completed {
                 Style::default()
                     .fg(Color::DarkGray)
                     .add_modifier(Modifier::CROSSED_OUT)
@@ -129,14 +140,47 @@ fn draw_daily_view(f: &mut Frame, app: &App, area: Rect) {
             spans.push(Span::styled(&habit.name, name_style));
 
             // Quantitative progress
-            if let Some(goal) = habit.goal() {
-                let value = entry.and_then(|e| e.value).unwrap_or(0.0);
-                let unit = habit.unit().unwrap_or("");
-                spans.push(Span::raw(" "));
-                spans.push(Span::styled(
-                    format!("[{:.0}/{:.0} {}]", value, goal, unit),
-                    Style::default().fg(Color::Cyan),
-                ));
+            let value = entry.and_then(|e| e.value).unwrap_or(0.0);
+            let unit = habit.unit().unwrap_or("");
+            match (&habit.metric, app.progress.get(&habit.id)) {
+                (Metric::Quantity { goal, .. }, _) => {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(
+                        format!("[{:.0}/{:.0} {}]", value, goal, unit),
+                        Style::default().fg(Color::Cyan),
+                    ));
+                }
+                (Metric::Cumulative { .. }, Some(progress)) => {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(
+                        format!(
+                            "[{:.0} {} today, {:.0}/{:.0} this {}]",
+                            value,
+                            unit,
+                            progress.achieved,
+                            progress.target,
+                            progress.per.name()
+                        ),
+                        Style::default().fg(Color::Cyan),
+                    ));
+                }
+                (_, Some(progress)) => {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(
+                        format!(
+                            "[{:.0}/{:.0} this {}]",
+                            progress.achieved,
+                            progress.target,
+                            progress.per.name()
+                        ),
+                        Style::default().fg(if progress.is_met() {
+                            Color::Green
+                        } else {
+                            Color::Cyan
+                        }),
+                    ));
+                }
+                _ => {}
             }
 
             // Streak info
@@ -144,7 +188,7 @@ fn draw_daily_view(f: &mut Frame, app: &App, area: Rect) {
                 if stats.current_streak > 0 {
                     spans.push(Span::raw(" "));
                     spans.push(Span::styled(
-                        format!("{}d streak", stats.current_streak),
+                        format!("{} streak", habit.streak_label(stats.current_streak)),
                         Style::default().fg(Color::Magenta),
                     ));
                 }
@@ -180,61 +224,91 @@ fn draw_calendar_view(f: &mut Frame, app: &App, area: Rect) {
         .constraints([Constraint::Min(0), Constraint::Length(5)])
         .split(area);
 
-    // Heatmap
-    let today = Utc::now().date_naive();
-    let weeks = 52;
-    let week_width = 3;
-    let _total_width = weeks * week_width;
+    // 52 full weeks plus the current one, one column per week
+    let today = Local::now().date_naive();
+    let week_start = match app.config.display.week_start {
+        WeekStart::Monday => Weekday::Mon,
+        WeekStart::Sunday => Weekday::Sun,
+    };
+    let offset = today.weekday().days_since(week_start) as i64;
+    let first = today - Duration::days(offset) - Duration::weeks(52);
+    let weeks = 53;
 
     let mut lines: Vec<Line> = Vec::new();
 
-    // Day labels
-    let day_labels = vec!["Mon", "   ", "Wed", "   ", "Fri", "   ", "Sun"];
-
-    for (day_idx, label) in day_labels.iter().enumerate() {
+    // Month labels above the first week of each month
+    let mut months = String::new();
+    for week in 0..weeks {
+        let date = first + Duration::weeks(week);
+        let column = 4 + week as usize * 2;
+        if months.len() > column {
+            continue;
+        }
+        if week == 0 || date.month() != (date - Duration::weeks(1)).month() {
+            months.push_str(&" ".repeat(column - months.len()));
+            months.push_str(&date.format("%b").to_string());
+        }
+    }
+    lines.push(Line::from(Span::styled(
+        months,
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    for day_idx in 0..7 {
+        let weekday = first + Duration::days(day_idx);
+        let label = if day_idx % 2 == 0 {
+            weekday.format("%a").to_string()
+        } else {
+            "   ".to_string()
+        };
         let mut spans = vec![Span::raw(format!("{} ", label))];
 
         for week in 0..weeks {
-            let date = today - Duration::days((weeks - 1 - week) as i64 * 7 + (6 - day_idx as i64));
-
-            if date <= today {
-                let rate = app
-                    .heatmap
-                    .iter()
-                    .find(|(d, _)| *d == date)
-                    .map(|(_, r)| *r)
-                    .unwrap_or(0.0);
-
-                let color = rate_to_color(rate);
-                spans.push(Span::styled("  ", Style::default().bg(color)));
-                spans.push(Span::raw(" "));
-            } else {
-                spans.push(Span::raw("   "));
+            let date = first + Duration::weeks(week) + Duration::days(day_idx);
+            if date > today {
+                break;
             }
+            let color = match app.heatmap.get(&date) {
+                Some(HeatCell::Rate(rate)) => rate_to_color(*rate),
+                Some(HeatCell::Skipped) => Color::Rgb(48, 54, 93),
+                None => rate_to_color(0.0),
+            };
+            let symbol = if date == app.selected_date { "◆" } else { "■" };
+            spans.push(Span::styled(symbol, Style::default().fg(color)));
+            spans.push(Span::raw(" "));
         }
 
         lines.push(Line::from(spans));
     }
 
+    let title = format!(" Activity: {} ", app.heatmap_title());
     let heatmap = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title(" Activity "));
+        .block(Block::default().borders(Borders::ALL).title(title));
 
     f.render_widget(heatmap, chunks[0]);
 
     // Legend
-    let legend = Paragraph::new(Line::from(vec![
-        Span::raw("Less "),
-        Span::styled("  ", Style::default().bg(Color::Rgb(22, 27, 34))),
-        Span::raw(" "),
-        Span::styled("  ", Style::default().bg(Color::Rgb(14, 68, 41))),
-        Span::raw(" "),
-        Span::styled("  ", Style::default().bg(Color::Rgb(0, 109, 50))),
-        Span::raw(" "),
-        Span::styled("  ", Style::default().bg(Color::Rgb(38, 166, 65))),
-        Span::raw(" "),
-        Span::styled("  ", Style::default().bg(Color::Rgb(57, 211, 83))),
-        Span::raw(" More"),
-    ]))
+    let swatch = |color| Span::styled("■ ", Style::default().fg(color));
+    let legend = Paragraph::new(vec![
+        Line::from(vec![
+            Span::raw("Less "),
+            swatch(rate_to_color(0.0)),
+            swatch(rate_to_color(0.2)),
+            swatch(rate_to_color(0.4)),
+            swatch(rate_to_color(0.6)),
+            swatch(rate_to_color(1.0)),
+            Span::raw("More   "),
+            swatch(Color::Rgb(48, 54, 93)),
+            Span::raw("Skipped"),
+        ]),
+        Line::from(Span::styled(
+            format!(
+                "{} completions in the last year   j/k: switch habit",
+                app.heatmap_completions
+            ),
+            Style::default().fg(Color::DarkGray),
+        )),
+    ])
     .block(Block::default().borders(Borders::ALL))
     .alignment(Alignment::Center);
 
@@ -280,8 +354,8 @@ fn draw_streaks_view(f: &mut Frame, app: &App, area: Rect) {
 
             Row::new(vec![
                 habit.name.clone(),
-                format!("{} days", stats.current_streak),
-                format!("{} days", stats.best_streak),
+                habit.streak_label(stats.current_streak),
+                habit.streak_label(stats.best_streak),
                 format!("{:.0}%", stats.completion_percent()),
             ])
         })
@@ -404,8 +478,8 @@ fn draw_footer(f: &mut Frame, app: &App, area: Rect) {
         (message.clone(), Style::default().fg(color))
     } else {
         let help = match app.view {
-            View::Daily => "j/k:Navigate  Space:Toggle  a:Add  e:Edit  d:Delete  n:Note  h/l:Date  ?:Help  q:Quit",
-            View::Calendar => "h/l:Navigate weeks  t:Today  1:Daily view  ?:Help  q:Quit",
+            View::Daily => "j/k:Navigate  Space:Toggle  a:Add  e:Edit  d:Delete  n:Note  h/l:Date  x:Export  ?:Help  q:Quit",
+            View::Calendar => "j/k:Switch habit  h/l:Date  t:Today  1:Daily view  ?:Help  q:Quit",
             View::Streaks | View::Stats => "j/k:Navigate  1:Daily view  ?:Help  q:Quit",
         };
         (help.to_string(), Style::default().fg(Color::DarkGray))
@@ -443,6 +517,12 @@ Actions:
   e               Edit habit
   d               Delete habit
   n               Add note
+  S               Set schedule (daily, mon,wed, 3/week...)
+  m               Set goal (yes/no, 8 glasses, 50 km/week)
+  z               Skip habit on selected day
+  V               Add/end vacation for all habits
+  x               Export (format from config)
+  i               Import from file or directory
 
 General:
   ?               Show this help
@@ -483,6 +563,10 @@ fn draw_edit_dialog(f: &mut Frame, app: &App) {
         EditField::HabitDescription => "Enter description",
         EditField::Value => "Enter value",
         EditField::Notes => "Enter note",
+        EditField::ImportPath => "Import from (JSON file or CSV directory)",
+        EditField::Schedule => "Schedule (daily, mon,wed,fri, 1,15, every 3 days, 3/week)",
+        EditField::Metric => "Goal (yes/no, 8 glasses, 50 km/week)",
+        EditField::Vacation => "Vacation (YYYY-MM-DD..YYYY-MM-DD or days)",
         EditField::None => "",
     };
 
@@ -503,6 +587,30 @@ fn draw_edit_dialog(f: &mut Frame, app: &App) {
     ));
 }
 
+fn draw_reminder_popup(f: &mut Frame, habits: &[String]) {
+    let area = centered_rect(50, 40, f.area());
+    f.render_widget(Clear, area);
+
+    let mut lines = vec![Line::from("Still to do today:"), Line::from("")];
+    lines.extend(habits.iter().map(|name| {
+        Line::from(Span::styled(
+            format!("  [ ] {}", name),
+            Style::default().fg(Color::Yellow),
+        ))
+    }));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Press any key to close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let popup = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" Reminder "))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(popup, area);
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -5,6 +5,7 @@ use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::PathBuf;
+use tui_plugins::{PluginHost, PluginPrompt, PromptStatus};
 
 use crate::config::Config;
 use crate::log_entry::{LogEntry, LogLevel};
@@ -53,14 +54,34 @@ pub struct App {
     // UI state
     pub viewport_height: u16,
     pub message: Option<String>,
+
+    // Plugins
+    pub plugins: PluginHost,
+    pub prompt: Option<PluginPrompt>,
 }
 
 impl App {
     pub fn new(file_path: Option<&str>) -> Result<Self> {
-        let config = Config::load()?;
-        let parser = LogParser::new();
+        let mut app = Self::with_config(Config::load()?);
+        app.plugins.load();
+
+        if let Some(path) = file_path {
+            app.load_file(path)?;
+            app.watcher = FileWatcher::new(path).ok();
+        } else {
+            // Demo mode with sample entries
+            app.load_demo_entries();
+        }
+
+        Ok(app)
+    }
 
-        let mut app = Self {
+    /// Create an empty app, without loading plugins.
+    pub(crate) fn with_config(config: Config) -> Self {
+        let mut plugins = PluginHost::new("log-viewer", env!("CARGO_PKG_VERSION"));
+        plugins.register_commands(crate::plugins::commands());
+
+        Self {
             config,
             mode: Mode::Normal,
             entries: Vec::new(),
@@ -76,26 +97,18 @@ impl App {
             level_filter: None,
             filter_input: String::new(),
             bookmarks: HashSet::new(),
-            file_path: file_path.map(PathBuf::from),
+            file_path: None,
             file_position: 0,
             watcher: None,
-            parser,
+            parser: LogParser::new(),
             viewport_height: 24,
             message: None,
-        };
-
-        if let Some(path) = file_path {
-            app.load_file(path)?;
-            app.watcher = FileWatcher::new(path).ok();
-        } else {
-            // Demo mode with sample entries
-            app.load_demo_entries();
+            plugins,
+            prompt: None,
         }
-
-        Ok(app)
     }
 
-    fn load_file(&mut self, path: &str) -> Result<()> {
+    pub(crate) fn load_file(&mut self, path: &str) -> Result<()> {
         let file = File::open(path)?;
         let reader = BufReader::new(file);
 
@@ -118,6 +131,7 @@ impl App {
         }
 
         self.message = Some(format!("Loaded {} lines from {}", self.entries.len(), path));
+        self.plugins.file_opened(path);
         Ok(())
     }
 
@@ -155,6 +169,10 @@ impl App {
         self.message = Some("Demo mode: showing sample log entries. Pass a file path to view real logs.".to_string());
     }
 
+    pub(crate) fn set_watcher(&mut self, watcher: Option<FileWatcher>) {
+        self.watcher = watcher;
+    }
+
     pub fn check_updates(&mut self) -> Result<()> {
         if !self.follow_mode {
             return Ok(());
@@ -201,6 +219,24 @@ impl App {
     }
 
     pub fn handle_key(&mut self, key: KeyEvent) {
+        if let Some(prompt) = self.prompt.as_mut() {
+            match prompt.handle_key(&key) {
+                PromptStatus::Submitted => {
+                    if let Some(prompt) = self.prompt.take() {
+                        self.plugins.respond(&prompt);
+                    }
+                }
+                PromptStatus::Cancelled => self.prompt = None,
+                PromptStatus::Pending => {}
+            }
+        } else if self.mode != Mode::Normal || !self.plugins.key(key) {
+            self.handle_mode_key(key);
+        }
+
+        self.sync_plugins();
+    }
+
+    fn handle_mode_key(&mut self, key: KeyEvent) {
         match self.mode {
             Mode::Normal => self.handle_normal_key(key),
             Mode::Search => self.handle_search_key(key),
@@ -364,19 +400,19 @@ impl App {
         self.ensure_visible();
     }
 
-    fn scroll_to_top(&mut self) {
+    pub(crate) fn scroll_to_top(&mut self) {
         self.follow_mode = false;
         self.selected = 0;
         self.scroll_offset = 0;
     }
 
-    fn scroll_to_bottom(&mut self) {
+    pub(crate) fn scroll_to_bottom(&mut self) {
         let visible = self.visible_entries();
         self.selected = visible.len().saturating_sub(1);
         self.ensure_visible();
     }
 
-    fn ensure_visible(&mut self) {
+    pub(crate) fn ensure_visible(&mut self) {
         let viewport = self.viewport_height.saturating_sub(4) as usize;
         if self.selected < self.scroll_offset {
             self.scroll_offset = self.selected;
@@ -385,7 +421,7 @@ impl App {
         }
     }
 
-    fn execute_search(&mut self) {
+    pub(crate) fn execute_search(&mut self) {
         if self.search_query.is_empty() {
             self.search_regex = None;
             self.search_matches.clear();
@@ -455,7 +491,7 @@ impl App {
         ));
     }
 
-    fn set_level_filter(&mut self, level: Option<LogLevel>) {
+    pub(crate) fn set_level_filter(&mut self, level: Option<LogLevel>) {
         self.level_filter = level;
         self.selected = 0;
         self.scroll_offset = 0;
@@ -478,7 +514,7 @@ impl App {
         self.filter_input.clear();
     }
 
-    fn toggle_bookmark(&mut self) {
+    pub(crate) fn toggle_bookmark(&mut self) {
         let visible = self.visible_entries();
         if let Some(entry) = visible.get(self.selected) {
             let line_num = entry.line_number;
@@ -4,6 +4,7 @@ mod app;
 mod config;
 mod log_entry;
 mod parser;
+mod plugins;
 mod ui;
 mod watcher;
 
@@ -52,8 +53,8 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
         if event::poll(Duration::from_millis(100))? {
             if let Event::Key(key) = event::read()? {
                 match (key.code, key.modifiers) {
-                    (KeyCode::Char('q'), KeyModifiers::NONE) |
-                    (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                    (KeyCode::Char('c'), KeyModifiers::CONTROL) => return Ok(()),
+                    (KeyCode::Char('q'), KeyModifiers::NONE) if app.prompt.is_none() => {
                         return Ok(());
                     }
                     _ => app.handle_key(key),
@@ -63,5 +64,6 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
 
         // Check for file updates
         app.check_updates()?;
+        app.tick();
     }
 }
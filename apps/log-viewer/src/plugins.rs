@@ -0,0 +1,230 @@
+//! Plugin integration: commands exposed to plugins and handling of their actions.
+
+use crate::app::{App, Mode};
+use crate::log_entry::{LogEntry, LogLevel};
+use crate::watcher::FileWatcher;
+use std::collections::HashMap;
+use tui_plugins::{AppCommand, HostAction, SelectionType};
+
+/// Commands plugins can run with `tui.run_command`.
+pub fn commands() -> Vec<AppCommand> {
+    vec![
+        AppCommand::new("open", "Open file").with_description("Open a log file; args: path"),
+        AppCommand::new("search", "Search").with_description("Regex search; args: query"),
+        AppCommand::new("filter_level", "Filter by level")
+            .with_description("args: level (error, warn, info, debug, trace, all)"),
+        AppCommand::new("clear_filters", "Clear filters"),
+        AppCommand::new("goto_line", "Go to line").with_description("args: line"),
+        AppCommand::new("toggle_bookmark", "Toggle bookmark")
+            .with_description("Bookmark the selected line"),
+        AppCommand::new("follow", "Toggle follow mode"),
+        AppCommand::new("top", "Go to top"),
+        AppCommand::new("bottom", "Go to bottom"),
+    ]
+}
+
+impl App {
+    /// Fire plugin timers and apply anything they asked for.
+    pub fn tick(&mut self) {
+        self.plugins.tick();
+        self.sync_plugins();
+    }
+
+    /// Report the current selection and apply queued plugin actions.
+    pub fn sync_plugins(&mut self) {
+        let selected = self.visible_entries().get(self.selected).map(|e| e.raw.clone());
+        if let Some(line) = selected {
+            self.plugins.selection_changed(line, SelectionType::Line);
+        }
+
+        for action in self.plugins.take_actions() {
+            self.apply_plugin_action(action);
+        }
+    }
+
+    fn apply_plugin_action(&mut self, action: HostAction) {
+        match action {
+            HostAction::Notify { message, .. } => self.message = Some(message),
+            HostAction::Prompt(prompt) => self.prompt = Some(prompt),
+            HostAction::RunCommand { name, args } => self.run_command(&name, &args),
+            HostAction::OpenFile { path, .. } => self.open(&path),
+            HostAction::InsertText(text) => {
+                match self.mode {
+                    Mode::Filter => self.filter_input.push_str(&text),
+                    Mode::Search => self.search_query.push_str(&text),
+                    _ => {
+                        self.mode = Mode::Search;
+                        self.search_query = text;
+                    }
+                }
+            }
+            HostAction::RequestData {
+                plugin,
+                data_type,
+                callback,
+            } => {
+                let data = match data_type.as_str() {
+                    "selection" => self
+                        .visible_entries()
+                        .get(self.selected)
+                        .map(|e| entry_json(e))
+                        .unwrap_or_default(),
+                    "entries" => self
+                        .visible_entries()
+                        .into_iter()
+                        .map(entry_json)
+                        .collect(),
+                    "file" => serde_json::json!(self.file_path),
+                    _ => serde_json::Value::Null,
+                };
+                self.plugins.reply(&plugin, &callback, data);
+            }
+            _ => {}
+        }
+    }
+
+    /// Run an app command on behalf of a plugin.
+    pub fn run_command(&mut self, name: &str, args: &HashMap<String, serde_json::Value>) {
+        let arg = |key: &str| args.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+
+        match name {
+            "open" => self.open(arg("path")),
+            "search" => {
+                self.search_query = arg("query").to_string();
+                self.execute_search();
+            }
+            "filter_level" => match arg("level") {
+                "all" | "" => self.set_level_filter(None),
+                level => match LogLevel::from_str(level) {
+                    Some(level) => self.set_level_filter(Some(level)),
+                    None => self.message = Some(format!("Unknown level: {}", level)),
+                },
+            },
+            "clear_filters" => {
+                self.level_filter = None;
+                self.search_query.clear();
+                self.search_regex = None;
+                self.search_matches.clear();
+            }
+            "goto_line" => {
+                let Some(line) = args.get("line").and_then(|v| v.as_u64()).filter(|n| *n > 0)
+                else {
+                    self.message = Some("goto_line needs a line number".to_string());
+                    return;
+                };
+                let line = line as usize;
+                let index = self
+                    .visible_entries()
+                    .iter()
+                    .position(|e| e.line_number >= line);
+                if let Some(index) = index {
+                    self.follow_mode = false;
+                    self.selected = index;
+                    self.ensure_visible();
+                }
+            }
+            "toggle_bookmark" => self.toggle_bookmark(),
+            "follow" => {
+                self.follow_mode = !self.follow_mode;
+                if self.follow_mode {
+                    self.scroll_to_bottom();
+                }
+            }
+            "top" => self.scroll_to_top(),
+            "bottom" => self.scroll_to_bottom(),
+            other => self.message = Some(format!("Unknown command: {}", other)),
+        }
+    }
+
+    fn open(&mut self, path: &str) {
+        match self.load_file(path) {
+            Ok(()) => self.set_watcher(FileWatcher::new(path).ok()),
+            Err(e) => self.message = Some(format!("Failed to open {}: {}", path, e)),
+        }
+    }
+}
+
+fn entry_json(entry: &LogEntry) -> serde_json::Value {
+    serde_json::json!({
+        "line": entry.line_number,
+        "raw": entry.raw,
+        "level": entry.level.label(),
+        "message": entry.message,
+        "timestamp": entry.timestamp.map(|t| t.to_rfc3339()),
+        "fields": entry.fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use serde_json::json;
+
+    const LOG: &str = "2024-01-15 10:30:00 INFO  Started\n\
+                       2024-01-15 10:30:01 DEBUG Loading config\n\
+                       2024-01-15 10:30:02 ERROR Connection refused\n\
+                       2024-01-15 10:30:03 WARN  Retrying\n\
+                       2024-01-15 10:30:04 INFO  Connected\n";
+
+    fn app(test: &str) -> (App, std::path::PathBuf) {
+        let name = format!("log-viewer-{}-{}.log", test, std::process::id());
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, LOG).unwrap();
+        let mut app = App::with_config(Config::default());
+        app.run_command("open", &args(&[("path", json!(path.to_str().unwrap()))]));
+        (app, path)
+    }
+
+    fn args(pairs: &[(&str, serde_json::Value)]) -> HashMap<String, serde_json::Value> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+    }
+
+    #[test]
+    fn test_commands_dispatch() {
+        let (mut app, path) = app("dispatch");
+        for command in commands() {
+            app.message = None;
+            app.run_command(&command.name, &HashMap::new());
+            let message = app.message.take().unwrap_or_default();
+            assert!(!message.starts_with("Unknown command"), "{}", command.name);
+        }
+
+        app.run_command("tail", &HashMap::new());
+        assert_eq!(app.message.as_deref(), Some("Unknown command: tail"));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_open_filter_and_goto() {
+        let (mut app, path) = app("goto");
+        assert_eq!(app.entries.len(), 5);
+
+        app.run_command("filter_level", &args(&[("level", json!("warn"))]));
+        assert_eq!(app.level_filter, Some(LogLevel::Warn));
+        assert_eq!(app.visible_entries().len(), 2);
+        app.run_command("filter_level", &args(&[("level", json!("loud"))]));
+        assert_eq!(app.message.as_deref(), Some("Unknown level: loud"));
+        assert_eq!(app.level_filter, Some(LogLevel::Warn));
+
+        app.run_command("goto_line", &args(&[("line", json!(4))]));
+        assert_eq!(app.visible_entries()[app.selected].line_number, 4);
+        assert!(!app.follow_mode);
+        app.run_command("goto_line", &args(&[("line", json!("first"))]));
+        assert_eq!(app.message.as_deref(), Some("goto_line needs a line number"));
+        assert_eq!(app.visible_entries()[app.selected].line_number, 4);
+
+        app.run_command("toggle_bookmark", &HashMap::new());
+        assert!(app.bookmarks.contains(&4));
+
+        app.run_command("search", &args(&[("query", json!("Conn\\w+"))]));
+        assert_eq!(app.search_matches.len(), 1);
+        app.run_command("clear_filters", &HashMap::new());
+        assert_eq!(app.level_filter, None);
+        assert!(app.search_matches.is_empty());
+
+        app.run_command("open", &args(&[("path", json!("/nonexistent/app.log"))]));
+        assert!(app.message.as_deref().is_some_and(|m| m.starts_with("Failed to open")));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -194,6 +194,16 @@ fn render_log_content(frame: &mut Frame, app: &mut App, area: Rect) {
 }
 
 fn render_input_bar(frame: &mut Frame, app: &App, area: Rect) {
+    if let Some(prompt) = &app.prompt {
+        let content = match &prompt.message {
+            Some(message) => format!("{} ({}): {}█", prompt.title, message, prompt.display_input()),
+            None => format!("{}: {}█", prompt.title, prompt.display_input()),
+        };
+        let bar = Paragraph::new(content).style(Style::default().fg(Color::Yellow));
+        frame.render_widget(bar, area);
+        return;
+    }
+
     let content = match app.mode {
         Mode::Search => {
             format!("/{}█", app.search_query)
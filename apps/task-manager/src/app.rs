@@ -2,9 +2,16 @@
 
 use crate::config::Config;
 use crate::db::{Database, DbResult};
-use crate::models::{Context, Project, Status, Task, TaskStats};
-use chrono::Utc;
+use crate::deps::{CriticalPath, DependencyGraph};
+use crate::models::{
+    Context, Project, ProjectId, Recurrence, RecurrenceEnd, Status, Task, TaskId, TaskStats,
+};
+use crate::query::Query;
+use crate::recurrence;
+use chrono::{NaiveDate, Utc};
 use crossterm::event::{KeyCode, KeyEvent};
+use std::collections::HashSet;
+use tui_plugins::{PluginHost, PluginPrompt, PromptStatus};
 
 pub struct App {
     pub db: Database,
@@ -21,7 +28,20 @@ pub struct App {
     pub message: Option<String>,
     pub show_help: bool,
     pub show_completed: bool,
+    /// Show tasks waiting on open blockers in the list and today views
+    pub show_blocked: bool,
     pub stats: TaskStats,
+    pub dependencies: DependencyGraph,
+    /// Open tasks with at least one open blocker
+    pub blocked: HashSet<TaskId>,
+    /// Every open task, for resolving dependency links
+    pub open_tasks: Vec<Task>,
+    /// Task marked as the blocker for the next link
+    pub link_from: Option<TaskId>,
+    pub plugins: PluginHost,
+    pub prompt: Option<PluginPrompt>,
+    /// Day recurring tasks were last checked for rollover
+    pub last_rollover: Option<NaiveDate>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -30,6 +50,10 @@ pub enum View {
     Board,
     Projects,
     Today,
+    /// Blocked-by links and each project's critical path
+    Dependencies,
+    /// A saved view from the config, by index
+    Saved(usize),
 }
 
 #[derive(Debug, Clone, Default)]
@@ -37,6 +61,8 @@ pub struct Filter {
     pub project_id: Option<i64>,
     pub status: Option<Status>,
     pub search: String,
+    /// Filter expression, see [`crate::query`]
+    pub query: Option<Query>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -46,6 +72,7 @@ pub enum InputField {
     TaskDescription,
     ProjectName,
     Search,
+    Query,
 }
 
 impl App {
@@ -57,6 +84,16 @@ impl App {
         }
         let db = Database::open(&db_path)?;
 
+        let mut app = Self::with_db(db, config)?;
+        app.plugins.load();
+        Ok(app)
+    }
+
+    /// Create an app over an open database, without loading plugins.
+    pub(crate) fn with_db(db: Database, config: Config) -> anyhow::Result<Self> {
+        let mut plugins = PluginHost::new("task-manager", env!("CARGO_PKG_VERSION"));
+        plugins.register_commands(crate::plugins::commands());
+
         let mut app = Self {
             db,
             config,
@@ -72,9 +109,18 @@ impl App {
             message: None,
             show_help: false,
             show_completed: false,
+            show_blocked: false,
             stats: TaskStats::default(),
+            dependencies: DependencyGraph::default(),
+            blocked: HashSet::new(),
+            open_tasks: Vec::new(),
+            link_from: None,
+            plugins,
+            prompt: None,
+            last_rollover: None,
         };
 
+        app.roll_over_recurring()?;
         app.refresh()?;
         Ok(app)
     }
@@ -82,6 +128,9 @@ impl App {
     pub fn refresh(&mut self) -> DbResult<()> {
         self.projects = self.db.list_projects(false)?;
         self.contexts = self.db.list_contexts()?;
+        self.dependencies = DependencyGraph::new(&self.db.list_dependencies()?);
+        self.open_tasks = self.db.list_tasks(false)?;
+        self.blocked = self.dependencies.blocked_ids(&self.open_tasks);
         self.reload_tasks()?;
         self.update_stats();
 
@@ -92,8 +141,10 @@ impl App {
         Ok(())
     }
 
-    fn reload_tasks(&mut self) -> DbResult<()> {
-        self.tasks = if let Some(project_id) = self.filter.project_id {
+    pub(crate) fn reload_tasks(&mut self) -> DbResult<()> {
+        self.tasks = if let Some(query) = &self.filter.query {
+            self.db.list_tasks_matching(query, self.show_completed)?
+        } else if let Some(project_id) = self.filter.project_id {
             self.db.list_tasks_by_project(project_id)?
         } else if let Some(status) = self.filter.status {
             self.db.list_tasks_by_status(status)?
@@ -109,10 +160,36 @@ impl App {
                     || t.tags.iter().any(|tag| tag.to_lowercase().contains(&search))
             });
         }
+        self.hide_blocked();
 
         Ok(())
     }
 
+    /// Drop tasks waiting on open blockers from next-action lists.
+    ///
+    /// Filter expressions decide for themselves with `+BLOCKED`.
+    fn hide_blocked(&mut self) {
+        let next_actions = matches!(self.view, View::List | View::Today);
+        if next_actions && !self.show_blocked && self.filter.query.is_none() {
+            let blocked = &self.blocked;
+            self.tasks.retain(|t| !blocked.contains(&t.id));
+        }
+    }
+
+    pub(crate) fn show_today(&mut self) {
+        self.view = View::Today;
+        if let Ok(tasks) = self.db.list_tasks_due_today() {
+            self.tasks = tasks;
+            self.hide_blocked();
+            self.selected_index = 0;
+        }
+    }
+
+    pub(crate) fn show_view(&mut self, view: View) {
+        self.view = view;
+        let _ = self.refresh();
+    }
+
     fn update_stats(&mut self) {
         self.stats = TaskStats::default();
         for task in &self.tasks {
@@ -133,10 +210,28 @@ impl App {
     }
 
     pub fn can_quit(&self) -> bool {
-        !self.editing
+        !self.editing && self.prompt.is_none()
     }
 
     pub fn handle_key(&mut self, key: KeyEvent) {
+        if let Some(prompt) = self.prompt.as_mut() {
+            match prompt.handle_key(&key) {
+                PromptStatus::Submitted => {
+                    if let Some(prompt) = self.prompt.take() {
+                        self.plugins.respond(&prompt);
+                    }
+                }
+                PromptStatus::Cancelled => self.prompt = None,
+                PromptStatus::Pending => {}
+            }
+        } else if self.show_help || self.editing || !self.plugins.key(key) {
+            self.handle_app_key(key);
+        }
+
+        self.sync_plugins();
+    }
+
+    fn handle_app_key(&mut self, key: KeyEvent) {
         self.message = None;
 
         if self.show_help {
@@ -163,15 +258,12 @@ impl App {
                 self.filter = Filter::default();
                 let _ = self.refresh();
             }
-            KeyCode::Char('2') => self.view = View::Board,
+            KeyCode::Char('2') => self.show_view(View::Board),
             KeyCode::Char('3') => self.view = View::Projects,
-            KeyCode::Char('4') => {
-                self.view = View::Today;
-                if let Ok(tasks) = self.db.list_tasks_due_today() {
-                    self.tasks = tasks;
-                    self.selected_index = 0;
-                }
-            }
+            KeyCode::Char('4') => self.show_today(),
+            KeyCode::Char('5') => self.show_view(View::Dependencies),
+            KeyCode::Char(c @ '6'..='9') => self.open_saved_view(c as usize - '6' as usize),
+            KeyCode::Char('v') => self.next_saved_view(),
             KeyCode::Char('a') => self.start_add_task(),
             KeyCode::Char('A') => self.start_add_project(),
             KeyCode::Enter | KeyCode::Char(' ') => self.toggle_task_status(),
@@ -184,9 +276,25 @@ impl App {
                 self.input_field = InputField::Search;
                 self.input_buffer.clear();
             }
+            KeyCode::Char('f') => {
+                self.editing = true;
+                self.input_field = InputField::Query;
+                self.input_buffer = self
+                    .filter
+                    .query
+                    .as_ref()
+                    .map(|q| q.source().to_string())
+                    .unwrap_or_default();
+            }
+            KeyCode::Char('s') => self.skip_occurrence(),
             KeyCode::Char('c') => self.toggle_show_completed(),
+            KeyCode::Char('b') => self.toggle_show_blocked(),
+            KeyCode::Char('L') => self.link_selected(),
             KeyCode::Char('?') => self.show_help = true,
             KeyCode::Esc => {
+                if let View::Saved(_) = self.view {
+                    self.view = View::List;
+                }
                 self.filter = Filter::default();
                 let _ = self.refresh();
             }
@@ -216,7 +324,7 @@ impl App {
         }
     }
 
-    fn finish_editing(&mut self) {
+    pub(crate) fn finish_editing(&mut self) {
         match self.input_field {
             InputField::TaskTitle => {
                 if !self.input_buffer.is_empty() {
@@ -243,6 +351,10 @@ impl App {
                     }
                 }
             }
+            InputField::Query => {
+                let source = self.input_buffer.clone();
+                self.apply_query(&source);
+            }
             InputField::Search => {}
             InputField::None => {}
         }
@@ -252,7 +364,49 @@ impl App {
         self.input_field = InputField::None;
     }
 
-    fn move_selection(&mut self, delta: i32) {
+    /// Filter tasks by an expression; an empty one clears the filter.
+    pub(crate) fn apply_query(&mut self, source: &str) {
+        match Query::parse(source) {
+            Ok(query) => {
+                self.filter.query = (!query.is_empty()).then_some(query);
+                self.selected_index = 0;
+                let _ = self.refresh();
+            }
+            Err(e) => self.message = Some(format!("Invalid filter: {}", e)),
+        }
+    }
+
+    /// Switch to a saved view from the config.
+    pub(crate) fn open_saved_view(&mut self, index: usize) {
+        let Some(view) = self.config.views.get(index) else {
+            return;
+        };
+        match Query::parse(&view.filter) {
+            Ok(query) => {
+                self.view = View::Saved(index);
+                self.filter = Filter { query: Some(query), ..Filter::default() };
+                self.selected_index = 0;
+                let _ = self.refresh();
+            }
+            Err(e) => self.message = Some(format!("Invalid filter in '{}': {}", view.name, e)),
+        }
+    }
+
+    /// Switch to the saved view after the current one, wrapping around.
+    pub(crate) fn next_saved_view(&mut self) {
+        let count = self.config.views.len();
+        if count == 0 {
+            self.message = Some("No saved views".to_string());
+            return;
+        }
+        let next = match self.view {
+            View::Saved(i) => (i + 1) % count,
+            _ => 0,
+        };
+        self.open_saved_view(next);
+    }
+
+    pub(crate) fn move_selection(&mut self, delta: i32) {
         let len = self.tasks.len();
         if len == 0 {
             return;
@@ -261,7 +415,7 @@ impl App {
         self.selected_index = new_idx.clamp(0, len as i32 - 1) as usize;
     }
 
-    fn start_add_task(&mut self) {
+    pub(crate) fn start_add_task(&mut self) {
         self.editing = true;
         self.input_field = InputField::TaskTitle;
         self.input_buffer.clear();
@@ -281,7 +435,7 @@ impl App {
         }
     }
 
-    fn toggle_task_status(&mut self) {
+    pub(crate) fn toggle_task_status(&mut self) {
         if let Some(task) = self.tasks.get_mut(self.selected_index) {
             let new_status = task.status.cycle();
             task.status = new_status;
@@ -291,12 +445,190 @@ impl App {
                 None
             };
             let _ = self.db.update_task(task);
-            let _ = self.refresh();
+            let completed = task.clone();
+
             self.message = Some(format!("Status: {}", new_status.label()));
+            if new_status == Status::Done && completed.is_recurring() {
+                if let Ok(Some(next)) = self.spawn_next_instance(&completed) {
+                    self.message = Some(format!("Done. Next: {}", next_date_label(&next)));
+                }
+            }
+            let _ = self.refresh();
+        }
+    }
+
+    /// Skip the selected recurring task's current occurrence.
+    pub(crate) fn skip_occurrence(&mut self) {
+        let today = Utc::now().date_naive();
+        let Some(task) = self.tasks.get_mut(self.selected_index) else {
+            return;
+        };
+        if !task.is_recurring() {
+            self.message = Some("Task does not recur".to_string());
+            return;
+        }
+
+        if recurrence::skip_occurrence(task, today) {
+            self.message = Some(format!("Skipped. Next: {}", next_date_label(task)));
+        } else {
+            // No occurrences left: the series is over
+            task.status = Status::Cancelled;
+            task.completed_at = Some(Utc::now());
+            self.message = Some("Series ended".to_string());
+        }
+        let _ = self.db.update_task(task);
+        let _ = self.refresh();
+    }
+
+    /// Set or clear the selected task's recurrence rule and end condition.
+    pub(crate) fn set_recurrence(&mut self, rule: &str, end: &str) {
+        let Some(task) = self.tasks.get_mut(self.selected_index) else {
+            return;
+        };
+        if rule.is_empty() || rule == "none" {
+            task.recurrence = None;
+            task.recurrence_end = None;
+        } else {
+            let Some(recurrence) = Recurrence::parse(rule) else {
+                self.message = Some(format!("Invalid recurrence: {}", rule));
+                return;
+            };
+            let recurrence_end = match end {
+                "" => None,
+                end => match RecurrenceEnd::parse(end) {
+                    Some(end) => Some(end),
+                    None => {
+                        self.message = Some(format!("Invalid end: {}", end));
+                        return;
+                    }
+                },
+            };
+            task.recurrence = Some(recurrence);
+            task.recurrence_end = recurrence_end;
+        }
+
+        self.message = Some(match (&task.recurrence, &task.recurrence_end) {
+            (Some(rule), Some(end)) => format!("Repeats: {}, {}", rule.label(), end.label()),
+            (Some(rule), None) => format!("Repeats: {}", rule.label()),
+            (None, _) => "Recurrence cleared".to_string(),
+        });
+        let _ = self.db.update_task(task);
+        let _ = self.refresh();
+    }
+
+    /// Create the next instance of a recurring task unless it already exists.
+    pub(crate) fn spawn_next_instance(&mut self, task: &Task) -> DbResult<Option<Task>> {
+        if self.db.find_successor(&task.uuid)?.is_some() {
+            return Ok(None);
+        }
+        let Some(mut next) = recurrence::next_instance(task, Utc::now().date_naive()) else {
+            return Ok(None);
+        };
+        next.id = self.db.insert_task(&next)?;
+        Ok(Some(next))
+    }
+
+    /// Create successors for open recurring tasks whose period has passed.
+    ///
+    /// Runs at most once per day; the overdue instance stays open.
+    pub(crate) fn roll_over_recurring(&mut self) -> DbResult<usize> {
+        let today = Utc::now().date_naive();
+        if self.last_rollover == Some(today) {
+            return Ok(0);
+        }
+        self.last_rollover = Some(today);
+
+        let mut created = 0;
+        for task in self.db.list_tasks(false)? {
+            if recurrence::is_rolled_over(&task, today) && self.spawn_next_instance(&task)?.is_some() {
+                created += 1;
+            }
         }
+        Ok(created)
     }
 
-    fn delete_selected(&mut self) {
+    /// Mark the selected task as a blocker, or link the marked blocker to it.
+    ///
+    /// Linking an existing pair removes the link instead.
+    pub(crate) fn link_selected(&mut self) {
+        let Some(task) = self.tasks.get(self.selected_index) else {
+            return;
+        };
+        let (id, title) = (task.id, task.title.clone());
+        match self.link_from.take() {
+            None => {
+                self.link_from = Some(id);
+                self.message = Some(format!(
+                    "Blocker: {}. Select the task it blocks and press L",
+                    title
+                ));
+            }
+            Some(blocker) if blocker == id => self.message = Some("Link cancelled".to_string()),
+            Some(blocker) => self.toggle_dependency(id, blocker),
+        }
+    }
+
+    /// Add or remove "`task` is blocked by `blocker`", refusing cycles.
+    pub(crate) fn toggle_dependency(&mut self, task: TaskId, blocker: TaskId) {
+        let result = if self.dependencies.contains(task, blocker) {
+            self.db.remove_dependency(task, blocker).map(|_| {
+                format!("{} no longer blocked by {}", self.task_title(task), self.task_title(blocker))
+            })
+        } else if let Some(cycle) = self.dependencies.cycle_with(task, blocker) {
+            let chain: Vec<&str> = cycle.iter().map(|&id| self.task_title(id)).collect();
+            Ok(format!("Would create a cycle: {}", chain.join(" \u{2190} ")))
+        } else {
+            self.db.add_dependency(task, blocker).map(|_| {
+                format!("{} blocked by {}", self.task_title(task), self.task_title(blocker))
+            })
+        };
+        let _ = self.refresh();
+        self.message = Some(result.unwrap_or_else(|e| format!("Error: {}", e)));
+    }
+
+    /// Title of an open or listed task.
+    pub fn task_title(&self, id: TaskId) -> &str {
+        self.tasks
+            .iter()
+            .chain(&self.open_tasks)
+            .find(|t| t.id == id)
+            .map(|t| t.title.as_str())
+            .unwrap_or("?")
+    }
+
+    /// Open blockers of a task.
+    pub fn open_blockers(&self, id: TaskId) -> Vec<&Task> {
+        self.dependencies
+            .blockers(id)
+            .iter()
+            .filter_map(|b| self.open_tasks.iter().find(|t| t.id == *b))
+            .collect()
+    }
+
+    /// Remaining critical path of each project with open tasks, longest first.
+    pub fn critical_paths(&self) -> Vec<(Option<ProjectId>, CriticalPath)> {
+        let mut projects: Vec<Option<ProjectId>> =
+            self.open_tasks.iter().map(|t| t.project_id).collect();
+        projects.sort_unstable();
+        projects.dedup();
+
+        let mut paths: Vec<_> = projects
+            .into_iter()
+            .map(|project| {
+                let tasks: Vec<Task> = self
+                    .open_tasks
+                    .iter()
+                    .filter(|t| t.project_id == project)
+                    .cloned()
+                    .collect();
+                (project, self.dependencies.critical_path(&tasks))
+            })
+            .collect();
+        paths.sort_by_key(|(_, path)| std::cmp::Reverse(path.total_mins));
+        paths
+    }
+
+    pub(crate) fn delete_selected(&mut self) {
         if let Some(task) = self.tasks.get(self.selected_index) {
             if self.db.delete_task(task.id).is_ok() {
                 self.message = Some("Task deleted".to_string());
@@ -305,7 +637,7 @@ impl App {
         }
     }
 
-    fn cycle_priority(&mut self) {
+    pub(crate) fn cycle_priority(&mut self) {
         if let Some(task) = self.tasks.get_mut(self.selected_index) {
             task.priority = task.priority.next();
             let _ = self.db.update_task(task);
@@ -339,7 +671,7 @@ impl App {
         }
     }
 
-    fn toggle_show_completed(&mut self) {
+    pub(crate) fn toggle_show_completed(&mut self) {
         self.show_completed = !self.show_completed;
         let _ = self.refresh();
         self.message = Some(if self.show_completed {
@@ -349,6 +681,16 @@ impl App {
         });
     }
 
+    pub(crate) fn toggle_show_blocked(&mut self) {
+        self.show_blocked = !self.show_blocked;
+        let _ = self.refresh();
+        self.message = Some(if self.show_blocked {
+            "Showing blocked tasks".to_string()
+        } else {
+            "Hiding blocked tasks".to_string()
+        });
+    }
+
     pub fn get_project_name(&self, project_id: Option<i64>) -> Option<&str> {
         project_id.and_then(|id| {
             self.projects.iter().find(|p| p.id == id).map(|p| p.name.as_str())
@@ -359,3 +701,46 @@ impl App {
         self.tasks.iter().filter(|t| t.status == status).collect()
     }
 }
+
+/// Date shown for the next occurrence of a recurring task.
+fn next_date_label(task: &Task) -> String {
+    task.due_date
+        .or(task.scheduled_date)
+        .map(|d| d.to_string())
+        .unwrap_or_else(|| "unscheduled".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SavedView;
+    use crossterm::event::KeyModifiers;
+
+    fn press(app: &mut App, c: char) {
+        app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_every_saved_view_keyed() {
+        let mut config = Config::default();
+        config.views = (1..=6)
+            .map(|i| SavedView {
+                name: format!("View {}", i),
+                filter: format!("+list{}", i),
+            })
+            .collect();
+        let mut app = App::with_db(Database::in_memory().unwrap(), config).unwrap();
+
+        press(&mut app, '9');
+        assert_eq!(app.view, View::Saved(3));
+        press(&mut app, 'v');
+        assert_eq!(app.view, View::Saved(4));
+        assert_eq!(app.filter.query.as_ref().unwrap().source(), "+list5");
+
+        press(&mut app, 'v');
+        press(&mut app, 'v');
+        assert_eq!(app.view, View::Saved(0));
+        press(&mut app, '5');
+        assert_eq!(app.view, View::Dependencies);
+    }
+}
@@ -11,6 +11,9 @@ pub struct Config {
     pub defaults: DefaultsConfig,
     #[serde(default)]
     pub filters: FilterConfig,
+    /// Saved filter views, shown after the built-in views
+    #[serde(default)]
+    pub views: Vec<SavedView>,
 }
 
 impl Default for Config {
@@ -19,6 +22,7 @@ impl Default for Config {
             display: DisplayConfig::default(),
             defaults: DefaultsConfig::default(),
             filters: FilterConfig::default(),
+            views: Vec::new(),
         }
     }
 }
@@ -42,6 +46,11 @@ impl Config {
         Ok(())
     }
 
+    /// Index of a saved view by name, ignoring case.
+    pub fn view_index(&self, name: &str) -> Option<usize> {
+        self.views.iter().position(|v| v.name.eq_ignore_ascii_case(name))
+    }
+
     pub fn config_path() -> Option<PathBuf> {
         directories::ProjectDirs::from("", "", "task-manager")
             .map(|d| d.config_dir().join("config.toml"))
@@ -122,3 +131,16 @@ impl Default for FilterConfig {
         }
     }
 }
+
+/// A named filter expression.
+///
+/// ```toml
+/// [[views]]
+/// name = "This week"
+/// filter = "project:work due.before:eow"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedView {
+    pub name: String,
+    pub filter: String,
+}
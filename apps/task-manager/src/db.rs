@@ -1,8 +1,10 @@
 //! Database operations for task manager.
 
 use crate::models::{Context, ContextId, Priority, Project, ProjectId, Status, Task, TaskId};
+use crate::query::Query;
 use chrono::{DateTime, NaiveDate, Utc};
-use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Result as SqlResult};
 use std::path::Path;
 
 pub type DbResult<T> = SqlResult<T>;
@@ -19,6 +21,13 @@ impl Database {
         Ok(db)
     }
 
+    /// Create in-memory database (for testing).
+    pub fn in_memory() -> DbResult<Self> {
+        let db = Self { conn: Connection::open_in_memory()? };
+        db.init_schema()?;
+        Ok(db)
+    }
+
     fn init_schema(&self) -> DbResult<()> {
         self.conn.execute_batch(
             r#"
@@ -55,17 +64,57 @@ impl Database {
                 parent_id INTEGER REFERENCES tasks(id) ON DELETE CASCADE,
                 created_at TEXT NOT NULL,
                 updated_at TEXT NOT NULL,
-                completed_at TEXT
+                completed_at TEXT,
+                recurrence_end TEXT,
+                series_uuid TEXT,
+                previous_uuid TEXT,
+                occurrence INTEGER NOT NULL DEFAULT 1,
+                annotations TEXT NOT NULL DEFAULT '[]'
+            );
+
+            CREATE TABLE IF NOT EXISTS dependencies (
+                task_id INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                blocker_id INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                PRIMARY KEY (task_id, blocker_id)
             );
 
             CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status);
             CREATE INDEX IF NOT EXISTS idx_tasks_due_date ON tasks(due_date);
             CREATE INDEX IF NOT EXISTS idx_tasks_project_id ON tasks(project_id);
             CREATE INDEX IF NOT EXISTS idx_tasks_parent_id ON tasks(parent_id);
+            CREATE INDEX IF NOT EXISTS idx_dependencies_blocker_id ON dependencies(blocker_id);
             "#,
+        )?;
+        self.migrate()?;
+        self.conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_tasks_previous_uuid ON tasks(previous_uuid);",
         )
     }
 
+    /// Add columns introduced after the initial schema.
+    fn migrate(&self) -> DbResult<()> {
+        let columns = self
+            .conn
+            .prepare("PRAGMA table_info(tasks)")?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let added = [
+            ("recurrence_end", "TEXT"),
+            ("series_uuid", "TEXT"),
+            ("previous_uuid", "TEXT"),
+            ("occurrence", "INTEGER NOT NULL DEFAULT 1"),
+            ("annotations", "TEXT NOT NULL DEFAULT '[]'"),
+        ];
+        for (name, definition) in added {
+            if !columns.iter().any(|c| c == name) {
+                let sql = format!("ALTER TABLE tasks ADD COLUMN {} {}", name, definition);
+                self.conn.execute(&sql, [])?;
+            }
+        }
+        Ok(())
+    }
+
     // Projects
 
     pub fn insert_project(&self, project: &Project) -> DbResult<ProjectId> {
@@ -161,12 +210,16 @@ impl Database {
     pub fn insert_task(&self, task: &Task) -> DbResult<TaskId> {
         let tags_str = task.tags.join(",");
         let recurrence_json = task.recurrence.as_ref().map(|r| serde_json::to_string(r).unwrap_or_default());
+        let end_json = task.recurrence_end.as_ref().map(|e| serde_json::to_string(e).unwrap_or_default());
+        let annotations_json = serde_json::to_string(&task.annotations).unwrap_or_default();
 
         self.conn.execute(
             "INSERT INTO tasks (uuid, title, description, status, priority, project_id, context_id,
              tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
-             created_at, updated_at, completed_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+             ?18, ?19, ?20, ?21, ?22)",
             params![
                 task.uuid,
                 task.title,
@@ -185,6 +238,11 @@ impl Database {
                 task.created_at.to_rfc3339(),
                 task.updated_at.to_rfc3339(),
                 task.completed_at.map(|d| d.to_rfc3339()),
+                end_json,
+                task.series_uuid,
+                task.previous_uuid,
+                task.occurrence,
+                annotations_json,
             ],
         )?;
         Ok(self.conn.last_insert_rowid())
@@ -193,12 +251,15 @@ impl Database {
     pub fn update_task(&self, task: &Task) -> DbResult<()> {
         let tags_str = task.tags.join(",");
         let recurrence_json = task.recurrence.as_ref().map(|r| serde_json::to_string(r).unwrap_or_default());
+        let end_json = task.recurrence_end.as_ref().map(|e| serde_json::to_string(e).unwrap_or_default());
+        let annotations_json = serde_json::to_string(&task.annotations).unwrap_or_default();
 
         self.conn.execute(
             "UPDATE tasks SET title = ?1, description = ?2, status = ?3, priority = ?4,
              project_id = ?5, context_id = ?6, tags = ?7, due_date = ?8, scheduled_date = ?9,
              recurrence = ?10, estimated_mins = ?11, actual_mins = ?12, parent_id = ?13,
-             updated_at = ?14, completed_at = ?15 WHERE id = ?16",
+             updated_at = ?14, completed_at = ?15, recurrence_end = ?16, series_uuid = ?17,
+             previous_uuid = ?18, occurrence = ?19, annotations = ?20 WHERE id = ?21",
             params![
                 task.title,
                 task.description,
@@ -215,22 +276,71 @@ impl Database {
                 task.parent_id,
                 Utc::now().to_rfc3339(),
                 task.completed_at.map(|d| d.to_rfc3339()),
+                end_json,
+                task.series_uuid,
+                task.previous_uuid,
+                task.occurrence,
+                annotations_json,
                 task.id,
             ],
         )?;
         Ok(())
     }
 
+    /// Set a task's parent without touching its modification time.
+    pub fn set_parent(&self, id: TaskId, parent_id: Option<TaskId>) -> DbResult<()> {
+        self.conn.execute("UPDATE tasks SET parent_id = ?1 WHERE id = ?2", params![parent_id, id])?;
+        Ok(())
+    }
+
     pub fn delete_task(&self, id: TaskId) -> DbResult<()> {
+        self.conn.execute("DELETE FROM dependencies WHERE task_id = ?1 OR blocker_id = ?1", [id])?;
         self.conn.execute("DELETE FROM tasks WHERE id = ?1", [id])?;
         Ok(())
     }
 
+    // Dependencies
+
+    /// Record that `task_id` is blocked by `blocker_id`. Callers check for
+    /// cycles with [`crate::deps::DependencyGraph::cycle_with`] first.
+    pub fn add_dependency(&self, task_id: TaskId, blocker_id: TaskId) -> DbResult<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO dependencies (task_id, blocker_id) VALUES (?1, ?2)",
+            params![task_id, blocker_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_dependency(&self, task_id: TaskId, blocker_id: TaskId) -> DbResult<()> {
+        self.conn.execute(
+            "DELETE FROM dependencies WHERE task_id = ?1 AND blocker_id = ?2",
+            params![task_id, blocker_id],
+        )?;
+        Ok(())
+    }
+
+    /// Remove every blocker of a task.
+    pub fn clear_dependencies(&self, task_id: TaskId) -> DbResult<()> {
+        self.conn.execute("DELETE FROM dependencies WHERE task_id = ?1", [task_id])?;
+        Ok(())
+    }
+
+    /// All `(task_id, blocker_id)` links.
+    pub fn list_dependencies(&self) -> DbResult<Vec<(TaskId, TaskId)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT task_id, blocker_id FROM dependencies ORDER BY task_id, blocker_id"
+        )?;
+        let links = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(links)
+    }
+
     pub fn get_task(&self, id: TaskId) -> DbResult<Option<Task>> {
         self.conn.query_row(
             "SELECT id, uuid, title, description, status, priority, project_id, context_id,
              tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
-             created_at, updated_at, completed_at FROM tasks WHERE id = ?1",
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations FROM tasks WHERE id = ?1",
             [id],
             |row| self.row_to_task(row),
         ).optional()
@@ -240,13 +350,15 @@ impl Database {
         let sql = if include_completed {
             "SELECT id, uuid, title, description, status, priority, project_id, context_id,
              tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
-             created_at, updated_at, completed_at FROM tasks ORDER BY
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations FROM tasks ORDER BY
              CASE priority WHEN 'Urgent' THEN 0 WHEN 'High' THEN 1 WHEN 'Medium' THEN 2 WHEN 'Low' THEN 3 ELSE 4 END,
              due_date NULLS LAST, created_at"
         } else {
             "SELECT id, uuid, title, description, status, priority, project_id, context_id,
              tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
-             created_at, updated_at, completed_at FROM tasks WHERE status NOT IN ('Done', 'Cancelled')
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations FROM tasks WHERE status NOT IN ('Done', 'Cancelled')
              ORDER BY
              CASE priority WHEN 'Urgent' THEN 0 WHEN 'High' THEN 1 WHEN 'Medium' THEN 2 WHEN 'Low' THEN 3 ELSE 4 END,
              due_date NULLS LAST, created_at"
@@ -258,11 +370,40 @@ impl Database {
         Ok(tasks)
     }
 
+    /// List tasks matching a filter expression. Completed tasks are hidden
+    /// unless requested or the query selects by status.
+    pub fn list_tasks_matching(&self, query: &Query, include_completed: bool) -> DbResult<Vec<Task>> {
+        let (mut condition, params) = query.to_sql(Utc::now().date_naive());
+        if !include_completed && !query.mentions_status() {
+            condition = format!("({}) AND status NOT IN ('Done', 'Cancelled')", condition);
+        }
+        self.list_tasks_where(&condition, &params)
+    }
+
+    /// List tasks matching a SQL condition, in the same order as [`Database::list_tasks`].
+    pub fn list_tasks_where(&self, condition: &str, params: &[Value]) -> DbResult<Vec<Task>> {
+        let sql = format!(
+            "SELECT id, uuid, title, description, status, priority, project_id, context_id,
+             tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations FROM tasks WHERE {}
+             ORDER BY
+             CASE priority WHEN 'Urgent' THEN 0 WHEN 'High' THEN 1 WHEN 'Medium' THEN 2 WHEN 'Low' THEN 3 ELSE 4 END,
+             due_date NULLS LAST, created_at",
+            condition
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let tasks = stmt.query_map(params_from_iter(params), |row| self.row_to_task(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tasks)
+    }
+
     pub fn list_tasks_by_project(&self, project_id: ProjectId) -> DbResult<Vec<Task>> {
         let mut stmt = self.conn.prepare(
             "SELECT id, uuid, title, description, status, priority, project_id, context_id,
              tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
-             created_at, updated_at, completed_at FROM tasks WHERE project_id = ?1
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations FROM tasks WHERE project_id = ?1
              ORDER BY status, priority DESC, due_date NULLS LAST"
         )?;
         let tasks = stmt.query_map([project_id], |row| self.row_to_task(row))?
@@ -274,7 +415,8 @@ impl Database {
         let mut stmt = self.conn.prepare(
             "SELECT id, uuid, title, description, status, priority, project_id, context_id,
              tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
-             created_at, updated_at, completed_at FROM tasks WHERE status = ?1
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations FROM tasks WHERE status = ?1
              ORDER BY priority DESC, due_date NULLS LAST"
         )?;
         let tasks = stmt.query_map([format!("{:?}", status)], |row| self.row_to_task(row))?
@@ -287,7 +429,8 @@ impl Database {
         let mut stmt = self.conn.prepare(
             "SELECT id, uuid, title, description, status, priority, project_id, context_id,
              tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
-             created_at, updated_at, completed_at FROM tasks
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations FROM tasks
              WHERE due_date = ?1 AND status NOT IN ('Done', 'Cancelled')
              ORDER BY priority DESC"
         )?;
@@ -301,7 +444,8 @@ impl Database {
         let mut stmt = self.conn.prepare(
             "SELECT id, uuid, title, description, status, priority, project_id, context_id,
              tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
-             created_at, updated_at, completed_at FROM tasks
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations FROM tasks
              WHERE due_date < ?1 AND status NOT IN ('Done', 'Cancelled')
              ORDER BY due_date, priority DESC"
         )?;
@@ -314,7 +458,8 @@ impl Database {
         let mut stmt = self.conn.prepare(
             "SELECT id, uuid, title, description, status, priority, project_id, context_id,
              tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
-             created_at, updated_at, completed_at FROM tasks WHERE parent_id = ?1
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations FROM tasks WHERE parent_id = ?1
              ORDER BY created_at"
         )?;
         let tasks = stmt.query_map([parent_id], |row| self.row_to_task(row))?
@@ -322,6 +467,43 @@ impl Database {
         Ok(tasks)
     }
 
+    pub fn get_task_by_uuid(&self, uuid: &str) -> DbResult<Option<Task>> {
+        self.conn.query_row(
+            "SELECT id, uuid, title, description, status, priority, project_id, context_id,
+             tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations FROM tasks WHERE uuid = ?1",
+            [uuid],
+            |row| self.row_to_task(row),
+        ).optional()
+    }
+
+    /// Get the instance generated from a recurring task, if any.
+    pub fn find_successor(&self, uuid: &str) -> DbResult<Option<Task>> {
+        self.conn.query_row(
+            "SELECT id, uuid, title, description, status, priority, project_id, context_id,
+             tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations FROM tasks WHERE previous_uuid = ?1 LIMIT 1",
+            [uuid],
+            |row| self.row_to_task(row),
+        ).optional()
+    }
+
+    /// List every instance of a recurring series, oldest first.
+    pub fn list_series(&self, series_uuid: &str) -> DbResult<Vec<Task>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, uuid, title, description, status, priority, project_id, context_id,
+             tags, due_date, scheduled_date, recurrence, estimated_mins, actual_mins, parent_id,
+             created_at, updated_at, completed_at, recurrence_end, series_uuid, previous_uuid,
+             occurrence, annotations FROM tasks WHERE uuid = ?1 OR series_uuid = ?1
+             ORDER BY occurrence"
+        )?;
+        let tasks = stmt.query_map([series_uuid], |row| self.row_to_task(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tasks)
+    }
+
     fn row_to_task(&self, row: &rusqlite::Row) -> rusqlite::Result<Task> {
         let status_str: String = row.get(4)?;
         let priority_str: String = row.get(5)?;
@@ -332,6 +514,8 @@ impl Database {
         let created_str: String = row.get(15)?;
         let updated_str: String = row.get(16)?;
         let completed_str: Option<String> = row.get(17)?;
+        let end_str: Option<String> = row.get(18)?;
+        let annotations_str: String = row.get(22)?;
 
         Ok(Task {
             id: row.get(0)?,
@@ -347,9 +531,14 @@ impl Database {
             } else {
                 tags_str.split(',').map(|s| s.to_string()).collect()
             },
+            annotations: serde_json::from_str(&annotations_str).unwrap_or_default(),
             due_date: due_str.and_then(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok()),
             scheduled_date: sched_str.and_then(|s| NaiveDate::parse_from_str(&s, "%Y-%m-%d").ok()),
             recurrence: recur_str.and_then(|s| serde_json::from_str(&s).ok()),
+            recurrence_end: end_str.and_then(|s| serde_json::from_str(&s).ok()),
+            series_uuid: row.get(19)?,
+            previous_uuid: row.get(20)?,
+            occurrence: row.get(21)?,
             estimated_mins: row.get(12)?,
             actual_mins: row.get(13)?,
             parent_id: row.get(14)?,
@@ -387,3 +576,52 @@ fn parse_priority(s: &str) -> Priority {
         _ => Priority::Low,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Recurrence;
+
+    #[test]
+    fn test_recurring_series_roundtrip() {
+        let db = Database::in_memory().unwrap();
+        let mut first = Task::new("Water plants");
+        first.recurrence = Some(Recurrence::Every { days: 3 });
+        first.recurrence_end = Some(crate::models::RecurrenceEnd::After { count: 4 });
+        first.due_date = NaiveDate::from_ymd_opt(2024, 5, 1);
+        db.insert_task(&first).unwrap();
+
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let second = crate::recurrence::next_instance(&first, today).unwrap();
+        db.insert_task(&second).unwrap();
+
+        let found = db.find_successor(&first.uuid).unwrap().unwrap();
+        assert_eq!(found.uuid, second.uuid);
+        assert_eq!(found.recurrence_end, first.recurrence_end);
+        assert_eq!(found.occurrence, 2);
+        assert!(db.find_successor(&second.uuid).unwrap().is_none());
+
+        let series = db.list_series(&first.uuid).unwrap();
+        assert_eq!(series.iter().map(|t| t.occurrence).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let db = Database::in_memory().unwrap();
+        let design = db.insert_task(&Task::new("Design")).unwrap();
+        let build = db.insert_task(&Task::new("Build")).unwrap();
+        let ship = db.insert_task(&Task::new("Ship")).unwrap();
+
+        db.add_dependency(build, design).unwrap();
+        db.add_dependency(build, design).unwrap();
+        db.add_dependency(ship, build).unwrap();
+        assert_eq!(db.list_dependencies().unwrap(), vec![(build, design), (ship, build)]);
+
+        db.remove_dependency(ship, build).unwrap();
+        assert_eq!(db.list_dependencies().unwrap(), vec![(build, design)]);
+
+        // Deleting either end drops the link
+        db.delete_task(design).unwrap();
+        assert!(db.list_dependencies().unwrap().is_empty());
+    }
+}
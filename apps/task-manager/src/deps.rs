@@ -0,0 +1,254 @@
+//! Task dependencies: "blocked-by" links, cycle detection and critical paths.
+//!
+//! A link `(task, blocker)` means `task` cannot start until `blocker` is
+//! complete. Links form a directed acyclic graph; [`DependencyGraph::cycle_with`]
+//! is checked before every insert.
+
+use crate::models::{Task, TaskId};
+use std::collections::{HashMap, HashSet};
+
+/// Blocked-by links between tasks.
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    blockers: HashMap<TaskId, Vec<TaskId>>,
+}
+
+impl DependencyGraph {
+    /// Build from `(task, blocker)` pairs.
+    pub fn new(links: &[(TaskId, TaskId)]) -> Self {
+        let mut graph = Self::default();
+        for &(task, blocker) in links {
+            graph.add(task, blocker);
+        }
+        graph
+    }
+
+    pub fn add(&mut self, task: TaskId, blocker: TaskId) {
+        let blockers = self.blockers.entry(task).or_default();
+        if !blockers.contains(&blocker) {
+            blockers.push(blocker);
+        }
+    }
+
+    pub fn remove(&mut self, task: TaskId, blocker: TaskId) {
+        if let Some(blockers) = self.blockers.get_mut(&task) {
+            blockers.retain(|&b| b != blocker);
+        }
+    }
+
+    /// Tasks `task` is directly blocked by.
+    pub fn blockers(&self, task: TaskId) -> &[TaskId] {
+        self.blockers.get(&task).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn contains(&self, task: TaskId, blocker: TaskId) -> bool {
+        self.blockers(task).contains(&blocker)
+    }
+
+    /// Tasks directly blocked by `blocker`.
+    pub fn blocking(&self, blocker: TaskId) -> Vec<TaskId> {
+        let mut tasks: Vec<TaskId> = self
+            .blockers
+            .iter()
+            .filter(|(_, blockers)| blockers.contains(&blocker))
+            .map(|(&task, _)| task)
+            .collect();
+        tasks.sort_unstable();
+        tasks
+    }
+
+    /// The cycle that making `task` blocked by `blocker` would close, as the
+    /// chain `task, blocker, ..., task`; `None` if the link is safe.
+    pub fn cycle_with(&self, task: TaskId, blocker: TaskId) -> Option<Vec<TaskId>> {
+        if task == blocker {
+            return Some(vec![task, task]);
+        }
+        // Depth-first search for `task` among the blockers of `blocker`
+        let mut visited = HashSet::new();
+        let mut stack = vec![vec![task, blocker]];
+        while let Some(path) = stack.pop() {
+            let last = *path.last()?;
+            if !visited.insert(last) {
+                continue;
+            }
+            for &next in self.blockers(last) {
+                let mut path = path.clone();
+                path.push(next);
+                if next == task {
+                    return Some(path);
+                }
+                stack.push(path);
+            }
+        }
+        None
+    }
+
+    /// Ids of open tasks with at least one open blocker.
+    pub fn blocked_ids(&self, tasks: &[Task]) -> HashSet<TaskId> {
+        let open: HashSet<TaskId> = tasks
+            .iter()
+            .filter(|t| !t.status.is_complete())
+            .map(|t| t.id)
+            .collect();
+        open.iter()
+            .copied()
+            .filter(|&id| self.blockers(id).iter().any(|b| open.contains(b)))
+            .collect()
+    }
+
+    /// The chain of open tasks with the most estimated work left.
+    ///
+    /// Only links between the given tasks count, so passing one project's
+    /// tasks gives that project's critical path. Unestimated tasks count as
+    /// zero minutes.
+    pub fn critical_path(&self, tasks: &[Task]) -> CriticalPath {
+        let open: HashMap<TaskId, &Task> = tasks
+            .iter()
+            .filter(|t| !t.status.is_complete())
+            .map(|t| (t.id, t))
+            .collect();
+
+        // Longest path ending at each task: (total minutes, previous task)
+        let mut memo: HashMap<TaskId, (u32, Option<TaskId>)> = HashMap::new();
+        let mut ids: Vec<TaskId> = open.keys().copied().collect();
+        ids.sort_unstable();
+        for &id in &ids {
+            self.longest(id, &open, &mut memo, &mut HashSet::new());
+        }
+
+        // Ties go to the longer chain, then the lowest id
+        let end = ids.iter().copied().max_by_key(|id| {
+            let chain_len = self.chain(*id, &memo).len();
+            (memo[id].0, chain_len, std::cmp::Reverse(*id))
+        });
+        let Some(end) = end else {
+            return CriticalPath::default();
+        };
+
+        let mut chain = self.chain(end, &memo);
+        chain.reverse();
+        CriticalPath {
+            unestimated: chain
+                .iter()
+                .filter(|id| open[id].estimated_mins.is_none())
+                .count(),
+            total_mins: memo[&end].0,
+            tasks: chain,
+        }
+    }
+
+    fn longest(
+        &self,
+        id: TaskId,
+        open: &HashMap<TaskId, &Task>,
+        memo: &mut HashMap<TaskId, (u32, Option<TaskId>)>,
+        visiting: &mut HashSet<TaskId>,
+    ) -> u32 {
+        if let Some(&(mins, _)) = memo.get(&id) {
+            return mins;
+        }
+        // Guard against cycles in data written by older versions
+        if !visiting.insert(id) {
+            return 0;
+        }
+        let mut best = (0, None);
+        for &blocker in self.blockers(id) {
+            if open.contains_key(&blocker) {
+                let mins = self.longest(blocker, open, memo, visiting);
+                if best.1.is_none() || mins > best.0 {
+                    best = (mins, Some(blocker));
+                }
+            }
+        }
+        visiting.remove(&id);
+        let own = open[&id].estimated_mins.unwrap_or(0);
+        memo.insert(id, (best.0 + own, best.1));
+        best.0 + own
+    }
+
+    /// Walk back from `end` through the memoized predecessors.
+    fn chain(&self, end: TaskId, memo: &HashMap<TaskId, (u32, Option<TaskId>)>) -> Vec<TaskId> {
+        let mut chain = vec![end];
+        let mut current = end;
+        while let Some(&(_, Some(previous))) = memo.get(&current) {
+            if chain.contains(&previous) {
+                break;
+            }
+            chain.push(previous);
+            current = previous;
+        }
+        chain
+    }
+}
+
+/// The longest chain of remaining work.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CriticalPath {
+    /// Task ids in the order they must be done
+    pub tasks: Vec<TaskId>,
+    pub total_mins: u32,
+    /// Tasks on the path without an estimate
+    pub unestimated: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Status;
+
+    fn task(id: TaskId, mins: Option<u32>) -> Task {
+        let mut task = Task::new(&format!("Task {}", id));
+        task.id = id;
+        task.estimated_mins = mins;
+        task
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        // 3 is blocked by 2, which is blocked by 1
+        let graph = DependencyGraph::new(&[(3, 2), (2, 1)]);
+        assert_eq!(graph.cycle_with(1, 3), Some(vec![1, 3, 2, 1]));
+        assert_eq!(graph.cycle_with(2, 2), Some(vec![2, 2]));
+        assert_eq!(graph.cycle_with(3, 1), None);
+        assert_eq!(graph.cycle_with(4, 3), None);
+        assert_eq!(graph.blocking(1), vec![2]);
+    }
+
+    #[test]
+    fn test_blocked_until_blockers_done() {
+        let graph = DependencyGraph::new(&[(2, 1), (3, 2)]);
+        let mut tasks = vec![task(1, None), task(2, None), task(3, None)];
+        assert_eq!(graph.blocked_ids(&tasks), HashSet::from([2, 3]));
+
+        tasks[0].status = Status::Done;
+        assert_eq!(graph.blocked_ids(&tasks), HashSet::from([3]));
+    }
+
+    #[test]
+    fn test_critical_path() {
+        // 1 (60) -> 2 (30) -> 4 (10)
+        // 3 (120) ---------> 4
+        // 5 (20) on its own
+        let graph = DependencyGraph::new(&[(2, 1), (4, 2), (4, 3)]);
+        let mut tasks = vec![
+            task(1, Some(60)),
+            task(2, Some(30)),
+            task(3, Some(120)),
+            task(4, Some(10)),
+            task(5, Some(20)),
+        ];
+        let path = graph.critical_path(&tasks);
+        assert_eq!(path.tasks, vec![3, 4]);
+        assert_eq!(path.total_mins, 130);
+
+        // Finished work drops out of what is left
+        tasks[2].status = Status::Done;
+        let path = graph.critical_path(&tasks);
+        assert_eq!(path.tasks, vec![1, 2, 4]);
+        assert_eq!(path.total_mins, 100);
+
+        tasks[0].estimated_mins = None;
+        assert_eq!(graph.critical_path(&tasks).unestimated, 1);
+        assert_eq!(graph.critical_path(&[]), CriticalPath::default());
+    }
+}
@@ -0,0 +1,479 @@
+//! Import and export of Taskwarrior JSON and todo.txt.
+//!
+//! Both formats are parsed into [`ImportedTask`]s, which carry project,
+//! context and parent by name/uuid so they can be resolved against the
+//! database. Re-importing a task with a known `uuid` updates it in place,
+//! replacing its blockers with the ones in the file.
+
+pub mod taskwarrior;
+pub mod todotxt;
+
+use crate::db::Database;
+use crate::deps::DependencyGraph;
+use crate::models::{Context, Project, Recurrence, Task};
+use anyhow::{bail, Context as _, Result};
+use chrono::{Datelike, NaiveDate};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Interchange format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JSON array as produced by `task export`
+    Taskwarrior,
+    /// One task per line, see <https://github.com/todotxt/todo.txt>
+    TodoTxt,
+}
+
+impl Format {
+    /// Guess the format from a file extension (`.json` or `.txt`).
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Some(Format::Taskwarrior),
+            Some("txt") => Some(Format::TodoTxt),
+            _ => None,
+        }
+    }
+
+    /// Parse a format name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "taskwarrior" | "tw" | "json" => Some(Format::Taskwarrior),
+            "todotxt" | "todo.txt" | "txt" => Some(Format::TodoTxt),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Taskwarrior => "Taskwarrior",
+            Format::TodoTxt => "todo.txt",
+        }
+    }
+}
+
+/// A parsed task with its references still unresolved.
+#[derive(Debug, Clone)]
+pub struct ImportedTask {
+    pub task: Task,
+    pub project: Option<String>,
+    pub context: Option<String>,
+    /// UUID of the parent task
+    pub parent: Option<String>,
+    /// UUIDs of the tasks this one is blocked by
+    pub depends: Vec<String>,
+}
+
+/// Outcome of an import.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub projects_created: usize,
+    pub contexts_created: usize,
+}
+
+impl ImportSummary {
+    pub fn label(&self) -> String {
+        format!(
+            "{} created, {} updated, {} skipped",
+            self.created, self.updated, self.skipped
+        )
+    }
+}
+
+/// Import a file, detecting the format from its extension.
+pub fn import_file(db: &Database, path: &Path, format: Option<Format>) -> Result<ImportSummary> {
+    let format = match format.or_else(|| Format::from_path(path)) {
+        Some(format) => format,
+        None => bail!("cannot tell the format of {}; use --format", path.display()),
+    };
+    let input = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    import_str(db, format, &input)
+}
+
+/// Import tasks from a string.
+pub fn import_str(db: &Database, format: Format, input: &str) -> Result<ImportSummary> {
+    let (tasks, skipped) = match format {
+        Format::Taskwarrior => taskwarrior::parse(input)?,
+        Format::TodoTxt => todotxt::parse(input),
+    };
+    let mut summary = apply(db, tasks)?;
+    summary.skipped += skipped;
+    Ok(summary)
+}
+
+/// Export all tasks to a file, detecting the format from its extension.
+pub fn export_file(db: &Database, path: &Path, format: Option<Format>) -> Result<usize> {
+    let format = match format.or_else(|| Format::from_path(path)) {
+        Some(format) => format,
+        None => bail!("cannot tell the format of {}; use --format", path.display()),
+    };
+    let tasks = db.list_tasks(true)?;
+    std::fs::write(path, format_tasks(db, format, &tasks)?)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(tasks.len())
+}
+
+/// Export all tasks as a string.
+pub fn export_str(db: &Database, format: Format) -> Result<String> {
+    format_tasks(db, format, &db.list_tasks(true)?)
+}
+
+fn format_tasks(db: &Database, format: Format, tasks: &[Task]) -> Result<String> {
+    let names = Names::load(db)?;
+    Ok(match format {
+        Format::Taskwarrior => taskwarrior::format(tasks, &names)?,
+        Format::TodoTxt => todotxt::format(tasks, &names),
+    })
+}
+
+/// Project, context and task-uuid lookups used when exporting.
+pub struct Names {
+    projects: HashMap<i64, String>,
+    contexts: HashMap<i64, String>,
+    uuids: HashMap<i64, String>,
+    blockers: DependencyGraph,
+}
+
+impl Names {
+    fn load(db: &Database) -> Result<Self> {
+        Ok(Self {
+            projects: db.list_projects(true)?.into_iter().map(|p| (p.id, p.name)).collect(),
+            contexts: db.list_contexts()?.into_iter().map(|c| (c.id, c.name)).collect(),
+            uuids: db.list_tasks(true)?.into_iter().map(|t| (t.id, t.uuid)).collect(),
+            blockers: DependencyGraph::new(&db.list_dependencies()?),
+        })
+    }
+
+    pub fn project(&self, task: &Task) -> Option<&str> {
+        task.project_id.and_then(|id| self.projects.get(&id)).map(String::as_str)
+    }
+
+    pub fn context(&self, task: &Task) -> Option<&str> {
+        task.context_id.and_then(|id| self.contexts.get(&id)).map(String::as_str)
+    }
+
+    pub fn parent(&self, task: &Task) -> Option<&str> {
+        task.parent_id.and_then(|id| self.uuids.get(&id)).map(String::as_str)
+    }
+
+    /// UUIDs of the tasks `task` is blocked by.
+    pub fn depends(&self, task: &Task) -> Vec<&str> {
+        self.blockers
+            .blockers(task.id)
+            .iter()
+            .filter_map(|id| self.uuids.get(id))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Write imported tasks to the database, deduplicating by uuid.
+fn apply(db: &Database, imported: Vec<ImportedTask>) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+    let mut projects: HashMap<String, i64> =
+        db.list_projects(true)?.into_iter().map(|p| (p.name, p.id)).collect();
+    let mut contexts: HashMap<String, i64> =
+        db.list_contexts()?.into_iter().map(|c| (c.name, c.id)).collect();
+
+    let mut parents = Vec::new();
+    let mut depends = Vec::new();
+    for ImportedTask { mut task, project, context, parent, depends: blockers } in imported {
+        task.project_id = match project {
+            Some(name) => Some(match projects.get(&name) {
+                Some(&id) => id,
+                None => {
+                    let id = db.insert_project(&Project::new(&name))?;
+                    summary.projects_created += 1;
+                    *projects.entry(name).or_insert(id)
+                }
+            }),
+            None => None,
+        };
+        task.context_id = match context {
+            Some(name) => Some(match contexts.get(&name) {
+                Some(&id) => id,
+                None => {
+                    let id = db.insert_context(&Context::new(&name))?;
+                    summary.contexts_created += 1;
+                    *contexts.entry(name).or_insert(id)
+                }
+            }),
+            None => None,
+        };
+
+        // Parents may appear later in the input; link them afterwards
+        task.parent_id = None;
+        match db.get_task_by_uuid(&task.uuid)? {
+            Some(existing) => {
+                task.id = existing.id;
+                db.update_task(&task)?;
+                summary.updated += 1;
+            }
+            None => {
+                task.id = db.insert_task(&task)?;
+                summary.created += 1;
+            }
+        }
+        if let Some(parent) = parent {
+            parents.push((task.id, parent));
+        }
+        depends.push((task.id, blockers));
+    }
+
+    for (id, parent) in parents {
+        if let Some(parent) = db.get_task_by_uuid(&parent)? {
+            db.set_parent(id, Some(parent.id))?;
+        }
+    }
+
+    // Blockers may also appear later; links that would close a cycle are dropped
+    for (id, _) in &depends {
+        db.clear_dependencies(*id)?;
+    }
+    let mut graph = DependencyGraph::new(&db.list_dependencies()?);
+    for (id, blockers) in depends {
+        for uuid in blockers {
+            let Some(blocker) = db.get_task_by_uuid(&uuid)? else {
+                continue;
+            };
+            if graph.cycle_with(id, blocker.id).is_none() {
+                db.add_dependency(id, blocker.id)?;
+                graph.add(id, blocker.id);
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// A recurrence interval both formats can express natively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    Days(u32),
+    Weekly,
+    Weekdays,
+    Monthly,
+    /// Every `n` months, for `n` of two or more.
+    Months(u32),
+    Yearly,
+}
+
+impl Interval {
+    /// Parse `daily`, `weekly`, `weekdays`, `biweekly`, `monthly`,
+    /// `quarterly`, `semiannual`, `yearly`, or a count and unit such as
+    /// `2d`, `3w`, `1m`, `+2y`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().trim_start_matches('+').to_lowercase();
+        match s.as_str() {
+            "daily" | "day" => return Some(Interval::Days(1)),
+            "weekly" | "week" => return Some(Interval::Weekly),
+            "weekdays" => return Some(Interval::Weekdays),
+            "biweekly" | "fortnight" => return Some(Interval::Days(14)),
+            "monthly" | "month" => return Some(Interval::Monthly),
+            "bimonthly" => return Some(Interval::Months(2)),
+            "quarterly" | "quarter" => return Some(Interval::Months(3)),
+            "semiannual" => return Some(Interval::Months(6)),
+            "yearly" | "annual" | "year" => return Some(Interval::Yearly),
+            "biannual" | "biyearly" => return Some(Interval::Months(24)),
+            _ => {}
+        }
+
+        let split = s.find(|c: char| !c.is_ascii_digit())?;
+        let count: u32 = s[..split].parse().ok().filter(|n| *n > 0)?;
+        match (&s[split..], count) {
+            ("d" | "day" | "days", n) => Some(Interval::Days(n)),
+            ("w" | "wk" | "wks" | "week" | "weeks", 1) => Some(Interval::Weekly),
+            ("w" | "wk" | "wks" | "week" | "weeks", n) => n.checked_mul(7).map(Interval::Days),
+            ("m" | "mo" | "mos" | "month" | "months", 1) => Some(Interval::Monthly),
+            ("m" | "mo" | "mos" | "month" | "months", n) => Some(Interval::Months(n)),
+            ("q" | "qtr" | "qtrs" | "quarter" | "quarters", n) => {
+                n.checked_mul(3).map(Interval::Months)
+            }
+            ("y" | "yr" | "yrs" | "year" | "years", 1) => Some(Interval::Yearly),
+            ("y" | "yr" | "yrs" | "year" | "years", n) => n.checked_mul(12).map(Interval::Months),
+            _ => None,
+        }
+    }
+
+    /// The recurrence rule for this interval, anchored at a task's date.
+    pub fn to_recurrence(self, anchor: NaiveDate) -> Recurrence {
+        match self {
+            Interval::Days(1) => Recurrence::Daily,
+            Interval::Days(days) => Recurrence::Every { days },
+            Interval::Weekly => Recurrence::Weekly {
+                days: vec![anchor.weekday().num_days_from_sunday() as u8],
+            },
+            Interval::Weekdays => Recurrence::Weekly { days: vec![1, 2, 3, 4, 5] },
+            Interval::Monthly => Recurrence::Monthly { day: anchor.day() as u8 },
+            Interval::Months(months) => Recurrence::EveryMonths {
+                months,
+                day: anchor.day() as u8,
+            },
+            Interval::Yearly => Recurrence::Yearly {
+                month: anchor.month() as u8,
+                day: anchor.day() as u8,
+            },
+        }
+    }
+
+    /// The interval that reproduces a rule exactly from `anchor`, if any.
+    pub fn from_recurrence(rule: &Recurrence, anchor: Option<NaiveDate>) -> Option<Self> {
+        let interval = Self::closest(rule);
+        let exact = match (interval, anchor) {
+            (Interval::Days(_) | Interval::Weekdays, _) => true,
+            (_, Some(anchor)) => interval.to_recurrence(anchor) == *rule,
+            (_, None) => false,
+        };
+        exact.then_some(interval)
+    }
+
+    /// The interval with the same period as a rule.
+    pub fn closest(rule: &Recurrence) -> Self {
+        match rule {
+            Recurrence::Daily => Interval::Days(1),
+            Recurrence::Every { days } => Interval::Days(*days),
+            Recurrence::Weekly { days } if days == &[1, 2, 3, 4, 5] => Interval::Weekdays,
+            Recurrence::Weekly { .. } => Interval::Weekly,
+            Recurrence::Monthly { .. } => Interval::Monthly,
+            Recurrence::EveryMonths { months, .. } => Interval::Months(*months),
+            Recurrence::Yearly { .. } => Interval::Yearly,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Annotation, Priority, RecurrenceEnd, Status};
+    use chrono::{TimeZone, Utc};
+
+    /// A database exercising every field the formats carry.
+    fn sample_db() -> Database {
+        let db = Database::in_memory().unwrap();
+        let project = db.insert_project(&Project::new("Home")).unwrap();
+        let context = db.insert_context(&Context::new("errands")).unwrap();
+        let created = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+
+        let mut parent = Task::new("Move house");
+        parent.priority = Priority::Urgent;
+        parent.project_id = Some(project);
+        parent.created_at = created;
+        parent.updated_at = created;
+        parent.id = db.insert_task(&parent).unwrap();
+
+        let mut child = Task::new("Book van");
+        child.parent_id = Some(parent.id);
+        child.context_id = Some(context);
+        child.status = Status::Blocked;
+        child.tags = vec!["phone".into()];
+        child.due_date = NaiveDate::from_ymd_opt(2024, 3, 15);
+        child.estimated_mins = Some(20);
+        child.created_at = created;
+        child.updated_at = created;
+        db.insert_task(&child).unwrap();
+
+        let mut bins = Task::new("Put out bins");
+        bins.recurrence = Some(Recurrence::Weekly { days: vec![1, 4] });
+        bins.recurrence_end = Some(RecurrenceEnd::After { count: 10 });
+        bins.series_uuid = Some(bins.uuid.clone());
+        bins.due_date = NaiveDate::from_ymd_opt(2024, 3, 4);
+        bins.created_at = created;
+        bins.updated_at = created;
+        bins.annotations.push(Annotation { entry: created, description: "Recycling too".into() });
+        db.insert_task(&bins).unwrap();
+
+        let mut done = Task::new("Pack books");
+        done.status = Status::Done;
+        done.priority = Priority::Low;
+        done.completed_at = Some(Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap());
+        done.created_at = created;
+        done.updated_at = created;
+        let done = db.insert_task(&done).unwrap();
+        db.add_dependency(parent.id, done).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for format in [Format::Taskwarrior, Format::TodoTxt] {
+            let source = sample_db();
+            let exported = export_str(&source, format).unwrap();
+
+            let target = Database::in_memory().unwrap();
+            let summary = import_str(&target, format, &exported).unwrap();
+            assert_eq!(summary.created, 4, "{}", format.name());
+            assert_eq!(summary.projects_created, 1);
+            assert_eq!(summary.contexts_created, 1);
+            assert_eq!(export_str(&target, format).unwrap(), exported, "{}", format.name());
+
+            let van = target.list_tasks(true).unwrap().into_iter().find(|t| t.title == "Book van");
+            assert!(van.and_then(|t| t.parent_id).is_some());
+            assert_eq!(target.list_dependencies().unwrap().len(), 1, "{}", format.name());
+        }
+    }
+
+    #[test]
+    fn test_reimport_updates_in_place() {
+        let db = sample_db();
+        let exported = export_str(&db, Format::Taskwarrior).unwrap();
+        let summary = import_str(&db, Format::Taskwarrior, &exported).unwrap();
+        assert_eq!(summary.created, 0);
+        assert_eq!(summary.updated, 4);
+        assert_eq!(summary.projects_created, 0);
+        assert_eq!(db.list_tasks(true).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_todotxt_reimport_and_escapes() {
+        let db = Database::in_memory().unwrap();
+        let plain = "(A) 2024-01-02 Call dentist +Health\n2024-01-02 Call dentist\nWater plants\n";
+        assert_eq!(import_str(&db, Format::TodoTxt, plain).unwrap().created, 3);
+        let summary = import_str(&db, Format::TodoTxt, plain).unwrap();
+        assert_eq!((summary.created, summary.updated), (0, 3));
+        assert_eq!(db.list_tasks(true).unwrap().len(), 3);
+
+        let source = Database::in_memory().unwrap();
+        let title = "Fix bug #123 for +foo @bob due:2024-01-01 after:3 uuid:x status:done \\#7";
+        source.insert_task(&Task::new(title)).unwrap();
+        let exported = export_str(&source, Format::TodoTxt).unwrap();
+        let target = Database::in_memory().unwrap();
+        import_str(&target, Format::TodoTxt, &exported).unwrap();
+        let task = target.list_tasks(true).unwrap().remove(0);
+        assert_eq!(task.title, title);
+        assert!(task.tags.is_empty() && task.due_date.is_none());
+    }
+
+    #[test]
+    fn test_interval_parse() {
+        assert_eq!(Interval::parse("daily"), Some(Interval::Days(1)));
+        assert_eq!(Interval::parse("3d"), Some(Interval::Days(3)));
+        assert_eq!(Interval::parse("2w"), Some(Interval::Days(14)));
+        assert_eq!(Interval::parse("+1m"), Some(Interval::Monthly));
+        assert_eq!(Interval::parse("1y"), Some(Interval::Yearly));
+        assert_eq!(Interval::parse("3m"), Some(Interval::Months(3)));
+        assert_eq!(Interval::parse("quarterly"), Some(Interval::Months(3)));
+        assert_eq!(Interval::parse("semiannual"), Some(Interval::Months(6)));
+        assert_eq!(Interval::parse("2y"), Some(Interval::Months(24)));
+        assert_eq!(Interval::parse("4000000000w"), None);
+
+        let anchor = NaiveDate::from_ymd_opt(2024, 2, 15).unwrap();
+        assert_eq!(
+            Interval::Months(3).to_recurrence(anchor),
+            Recurrence::EveryMonths { months: 3, day: 15 }
+        );
+    }
+
+    #[test]
+    fn test_interval_exactness() {
+        let friday = NaiveDate::from_ymd_opt(2024, 3, 8);
+        let weekly = Recurrence::Weekly { days: vec![5] };
+        assert_eq!(Interval::from_recurrence(&weekly, friday), Some(Interval::Weekly));
+        let twice_weekly = Recurrence::Weekly { days: vec![1, 5] };
+        assert_eq!(Interval::from_recurrence(&twice_weekly, friday), None);
+        assert_eq!(Interval::from_recurrence(&Recurrence::Monthly { day: 8 }, None), None);
+    }
+}
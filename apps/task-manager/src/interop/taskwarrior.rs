@@ -0,0 +1,393 @@
+//! Taskwarrior JSON (`task export` / `task import`).
+//!
+//! Attributes Taskwarrior has no equivalent for are written as UDAs
+//! (`context`, `estimate`, `actual`, and `tm*` fields for exact status,
+//! priority and recurrence), so an export imports back unchanged.
+
+use super::{ImportedTask, Interval, Names};
+use crate::models::{Annotation, Priority, Recurrence, RecurrenceEnd, Status, Task};
+use anyhow::{Context as _, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Taskwarrior's timestamp format.
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TwTask {
+    uuid: String,
+    description: String,
+    status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scheduled: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    until: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recur: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<TwAnnotation>,
+    /// UUIDs of blocking tasks
+    #[serde(default, deserialize_with = "uuid_list", skip_serializing_if = "Vec::is_empty")]
+    depends: Vec<String>,
+    /// Recurrence template this instance belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parent: Option<String>,
+    /// UDAs and attributes we do not model
+    #[serde(flatten)]
+    extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TwAnnotation {
+    entry: String,
+    description: String,
+}
+
+/// Parse `task export` output. Returns the tasks and the number skipped.
+///
+/// Recurrence templates (status `recurring`) are skipped: their pending
+/// instances carry the rule and link to them as their series.
+pub fn parse(input: &str) -> Result<(Vec<ImportedTask>, usize)> {
+    let input = input.trim();
+    // `task export` prints an array; older versions print one object per line
+    let tw_tasks: Vec<TwTask> = if input.starts_with('[') || input.is_empty() {
+        serde_json::from_str(if input.is_empty() { "[]" } else { input })
+            .context("invalid Taskwarrior JSON")?
+    } else {
+        input
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| serde_json::from_str(l.trim().trim_end_matches(',')))
+            .collect::<Result<_, _>>()
+            .context("invalid Taskwarrior JSON")?
+    };
+
+    let mut skipped = 0;
+    let mut tasks = Vec::new();
+    for tw in tw_tasks {
+        if tw.status == "recurring" || tw.uuid.is_empty() {
+            skipped += 1;
+            continue;
+        }
+        tasks.push(to_task(tw));
+    }
+    Ok((tasks, skipped))
+}
+
+fn to_task(tw: TwTask) -> ImportedTask {
+    let uda = |key: &str| tw.extra.get(key).and_then(Value::as_str).map(str::to_string);
+    let uda_num = |key: &str| {
+        tw.extra.get(key).and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))
+    };
+
+    let mut task = Task::new(&tw.description);
+    task.uuid = tw.uuid.clone();
+    task.status = match uda("tmstatus").as_deref() {
+        Some("InProgress") => Status::InProgress,
+        Some("Blocked") => Status::Blocked,
+        _ => match tw.status.as_str() {
+            "completed" => Status::Done,
+            "deleted" => Status::Cancelled,
+            "waiting" => Status::Blocked,
+            _ if tw.start.is_some() => Status::InProgress,
+            _ => Status::Todo,
+        },
+    };
+    task.priority = match uda("tmpriority").as_deref() {
+        Some("Urgent") => Priority::Urgent,
+        _ => match tw.priority.as_deref() {
+            Some("H") => Priority::High,
+            Some("M") => Priority::Medium,
+            Some("L") => Priority::Low,
+            _ => Priority::None,
+        },
+    };
+    task.description = uda("tmdescription").unwrap_or_default();
+    task.tags = tw.tags.clone();
+    task.annotations = tw
+        .annotations
+        .iter()
+        .map(|a| Annotation {
+            entry: parse_datetime(&a.entry).unwrap_or_else(Utc::now),
+            description: a.description.clone(),
+        })
+        .collect();
+    task.due_date = tw.due.as_deref().and_then(parse_date);
+    task.scheduled_date = tw.scheduled.as_deref().and_then(parse_date);
+    task.estimated_mins = uda_num("estimate").map(|n| n as u32);
+    task.actual_mins = uda_num("actual").map(|n| n as u32);
+    if let Some(entry) = tw.entry.as_deref().and_then(parse_datetime) {
+        task.created_at = entry;
+    }
+    if let Some(modified) = tw.modified.as_deref().and_then(parse_datetime) {
+        task.updated_at = modified;
+    }
+    task.completed_at = tw.end.as_deref().and_then(parse_datetime);
+
+    let anchor = task.due_date.or(task.scheduled_date).unwrap_or_else(|| task.created_at.date_naive());
+    task.recurrence = uda("tmrecur")
+        .and_then(|r| Recurrence::parse(&r))
+        .or_else(|| tw.recur.as_deref().and_then(Interval::parse).map(|i| i.to_recurrence(anchor)));
+    task.recurrence_end = uda("tmrecurend")
+        .and_then(|e| RecurrenceEnd::parse(&e))
+        .or_else(|| tw.until.as_deref().and_then(parse_date).map(|date| RecurrenceEnd::On { date }));
+    task.series_uuid = uda("tmseries").or(tw.parent.clone());
+    task.previous_uuid = uda("tmprevious");
+    task.occurrence = uda_num("tmoccurrence")
+        .or_else(|| uda_num("imask").map(|i| i + 1))
+        .unwrap_or(1) as u32;
+
+    ImportedTask {
+        task,
+        project: tw.project.clone(),
+        context: uda("context"),
+        parent: uda("tmparent"),
+        depends: tw.depends.clone(),
+    }
+}
+
+/// Format tasks as a `task import` compatible JSON array.
+pub fn format(tasks: &[Task], names: &Names) -> Result<String> {
+    let tw_tasks: Vec<TwTask> = tasks.iter().map(|t| from_task(t, names)).collect();
+    Ok(serde_json::to_string_pretty(&tw_tasks)?)
+}
+
+fn from_task(task: &Task, names: &Names) -> TwTask {
+    let mut extra = Map::new();
+    let mut set = |key: &str, value: Value| {
+        extra.insert(key.to_string(), value);
+    };
+
+    let status = match task.status {
+        Status::Todo | Status::InProgress | Status::Blocked => "pending",
+        Status::Done => "completed",
+        Status::Cancelled => "deleted",
+    };
+    if matches!(task.status, Status::InProgress | Status::Blocked) {
+        set("tmstatus", format!("{:?}", task.status).into());
+    }
+
+    let priority = match task.priority {
+        Priority::Urgent | Priority::High => Some("H"),
+        Priority::Medium => Some("M"),
+        Priority::Low => Some("L"),
+        Priority::None => None,
+    };
+    if task.priority == Priority::Urgent {
+        set("tmpriority", "Urgent".into());
+    }
+
+    if !task.description.is_empty() {
+        set("tmdescription", task.description.clone().into());
+    }
+    if let Some(context) = names.context(task) {
+        set("context", context.into());
+    }
+    if let Some(mins) = task.estimated_mins {
+        set("estimate", mins.into());
+    }
+    if let Some(mins) = task.actual_mins {
+        set("actual", mins.into());
+    }
+    if let Some(parent) = names.parent(task) {
+        set("tmparent", parent.into());
+    }
+
+    let anchor = task.due_date.or(task.scheduled_date);
+    let recur = task.recurrence.as_ref().map(|rule| {
+        match Interval::from_recurrence(rule, anchor) {
+            Some(interval) => interval_name(interval),
+            None => {
+                // Closest native interval; the exact rule is kept in a UDA
+                set("tmrecur", rule.rule().into());
+                interval_name(Interval::closest(rule))
+            }
+        }
+    });
+    let until = match task.recurrence_end {
+        Some(RecurrenceEnd::On { date }) => Some(format_date(date)),
+        Some(RecurrenceEnd::After { count }) => {
+            set("tmrecurend", format!("after:{}", count).into());
+            None
+        }
+        None => None,
+    };
+    if task.recurrence.is_some() {
+        if let Some(series) = &task.series_uuid {
+            set("tmseries", series.clone().into());
+        }
+        if let Some(previous) = &task.previous_uuid {
+            set("tmprevious", previous.clone().into());
+        }
+        set("tmoccurrence", task.occurrence.into());
+    }
+
+    TwTask {
+        uuid: task.uuid.clone(),
+        description: task.title.clone(),
+        status: status.to_string(),
+        entry: Some(format_datetime(task.created_at)),
+        modified: Some(format_datetime(task.updated_at)),
+        start: (task.status == Status::InProgress).then(|| format_datetime(task.updated_at)),
+        end: task.completed_at.map(format_datetime),
+        due: task.due_date.map(format_date),
+        scheduled: task.scheduled_date.map(format_date),
+        until,
+        recur,
+        project: names.project(task).map(str::to_string),
+        priority: priority.map(str::to_string),
+        tags: task.tags.clone(),
+        annotations: task
+            .annotations
+            .iter()
+            .map(|a| TwAnnotation {
+                entry: format_datetime(a.entry),
+                description: a.description.clone(),
+            })
+            .collect(),
+        depends: names.depends(task).into_iter().map(str::to_string).collect(),
+        parent: None,
+        extra,
+    }
+}
+
+/// `depends` is an array since Taskwarrior 2.6 and a comma-separated string before.
+fn uuid_list<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum List {
+        Array(Vec<String>),
+        Joined(String),
+    }
+    Ok(match List::deserialize(deserializer)? {
+        List::Array(uuids) => uuids,
+        List::Joined(joined) => joined
+            .split(',')
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+fn interval_name(interval: Interval) -> String {
+    match interval {
+        Interval::Days(1) => "daily".to_string(),
+        Interval::Days(days) => format!("{}d", days),
+        Interval::Weekly => "weekly".to_string(),
+        Interval::Weekdays => "weekdays".to_string(),
+        Interval::Monthly => "monthly".to_string(),
+        Interval::Months(3) => "quarterly".to_string(),
+        Interval::Months(6) => "semiannual".to_string(),
+        Interval::Months(months) if months % 12 == 0 => format!("{}y", months / 12),
+        Interval::Months(months) => format!("{}mo", months),
+        Interval::Yearly => "yearly".to_string(),
+    }
+}
+
+fn parse_datetime(s: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(s, DATE_FORMAT)
+        .ok()
+        .map(|dt| dt.and_utc())
+        .or_else(|| DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&Utc)))
+}
+
+fn format_datetime(dt: DateTime<Utc>) -> String {
+    dt.format(DATE_FORMAT).to_string()
+}
+
+/// Taskwarrior dates are UTC instants; tasks use the local calendar day.
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    parse_datetime(s).map(|dt| dt.with_timezone(&Local).date_naive())
+}
+
+/// Local midnight of a date, as a Taskwarrior timestamp.
+fn format_date(date: NaiveDate) -> String {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let utc = Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| midnight.and_utc());
+    format_datetime(utc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"[
+      {"id":1,"description":"Renew passport","entry":"20240301T090000Z","modified":"20240302T090000Z",
+       "due":"20240410T120000Z","priority":"H","project":"Home.Admin","status":"pending",
+       "tags":["errand"],"uuid":"6e0f7a2c-1a34-4c58-9a7a-2b7a0b6ac001","urgency":12.3,
+       "depends":"6e0f7a2c-1a34-4c58-9a7a-2b7a0b6ac004",
+       "annotations":[{"entry":"20240302T090000Z","description":"Photos taken"}]},
+      {"description":"Water plants","entry":"20240301T090000Z","mask":"-","recur":"weekly",
+       "status":"recurring","uuid":"6e0f7a2c-1a34-4c58-9a7a-2b7a0b6ac002","due":"20240301T120000Z"},
+      {"description":"Water plants","entry":"20240301T090000Z","recur":"weekly","imask":1,
+       "parent":"6e0f7a2c-1a34-4c58-9a7a-2b7a0b6ac002","status":"pending",
+       "uuid":"6e0f7a2c-1a34-4c58-9a7a-2b7a0b6ac003","due":"20240308T120000Z","until":"20240601T120000Z"},
+      {"description":"File taxes","end":"20240415T100000Z","entry":"20240101T090000Z",
+       "status":"completed","uuid":"6e0f7a2c-1a34-4c58-9a7a-2b7a0b6ac004"}
+    ]"#;
+
+    #[test]
+    fn test_parse_export() {
+        let (tasks, skipped) = parse(EXPORT).unwrap();
+        assert_eq!(skipped, 1);
+        assert_eq!(tasks.len(), 3);
+
+        let passport = &tasks[0];
+        assert_eq!(passport.task.title, "Renew passport");
+        assert_eq!(passport.task.priority, Priority::High);
+        assert_eq!(passport.project.as_deref(), Some("Home.Admin"));
+        assert_eq!(passport.task.tags, vec!["errand"]);
+        assert_eq!(passport.task.annotations[0].description, "Photos taken");
+        assert_eq!(passport.task.due_date, NaiveDate::from_ymd_opt(2024, 4, 10));
+        assert_eq!(passport.depends, vec!["6e0f7a2c-1a34-4c58-9a7a-2b7a0b6ac004"]);
+
+        let plants = &tasks[1].task;
+        assert_eq!(plants.recurrence, Some(Recurrence::Weekly { days: vec![5] }));
+        assert_eq!(
+            plants.recurrence_end,
+            Some(RecurrenceEnd::On { date: NaiveDate::from_ymd_opt(2024, 6, 1).unwrap() })
+        );
+        assert_eq!(plants.series_uuid.as_deref(), Some("6e0f7a2c-1a34-4c58-9a7a-2b7a0b6ac002"));
+        assert_eq!(plants.occurrence, 2);
+
+        assert_eq!(tasks[2].task.status, Status::Done);
+        assert!(tasks[2].task.completed_at.is_some());
+    }
+
+    #[test]
+    fn test_unknown_attributes_kept_out() {
+        let (tasks, _) = parse(EXPORT).unwrap();
+        // `urgency` and `id` are computed by Taskwarrior and not imported as UDAs
+        let names = Names {
+            projects: Default::default(),
+            contexts: Default::default(),
+            uuids: Default::default(),
+            blockers: Default::default(),
+        };
+        let json = format(&[tasks[0].task.clone()], &names).unwrap();
+        assert!(!json.contains("urgency"));
+        assert!(json.contains("\"priority\": \"H\""));
+    }
+}
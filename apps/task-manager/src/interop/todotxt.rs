@@ -0,0 +1,324 @@
+//! todo.txt, one task per line.
+//!
+//! ```text
+//! x 2024-04-15 2024-01-02 (A) Title +Project @context #tag due:2024-04-20 rec:1w
+//! ```
+//!
+//! The first `+project` and `@context` map to the task's project and
+//! context; further ones, and `#tag`s, become tags. Priorities `A`-`D` map
+//! to Urgent-Low. Dates, recurrence and identity are carried in `key:value`
+//! pairs (`dep:<uuid>` for each blocker); descriptions and annotations have
+//! no todo.txt form and are dropped.
+//!
+//! Title words that would read as metadata (`#123`, `due:soon`) are written
+//! with a leading `\`, which import takes off again. Lines without a
+//! `uuid:` get one made from their title and creation date, so importing
+//! the same file twice updates the tasks instead of adding them again.
+
+use super::{ImportedTask, Interval, Names};
+use crate::models::{Priority, Recurrence, RecurrenceEnd, Status, Task};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Keys read from `key:value` words.
+const KEYS: &[&str] = &[
+    "due", "t", "pri", "rec", "uuid", "parent", "dep", "series", "prev", "occ", "est", "status",
+];
+
+/// Parse a todo.txt file. Returns the tasks and the number of lines skipped.
+pub fn parse(input: &str) -> (Vec<ImportedTask>, usize) {
+    let mut skipped = 0;
+    let mut tasks = Vec::new();
+    let mut seen = HashMap::new();
+    for line in input.lines().filter(|l| !l.trim().is_empty()) {
+        match parse_line(line, &mut seen) {
+            Some(task) => tasks.push(task),
+            None => skipped += 1,
+        }
+    }
+    (tasks, skipped)
+}
+
+/// Parse one line, or `None` if it has no title. `seen` counts the lines
+/// with each title and creation date, to tell repeated ones apart.
+fn parse_line(line: &str, seen: &mut HashMap<String, usize>) -> Option<ImportedTask> {
+    let mut words = line.split_whitespace().peekable();
+    let mut task = Task::new("");
+    task.priority = Priority::None;
+
+    let done = words.next_if_eq(&"x").is_some();
+    if done {
+        task.status = Status::Done;
+        task.completed_at = words.next_if(|w| parse_date(w).is_some()).and_then(parse_datetime);
+    } else if let Some(priority) = words.next_if(|w| parse_priority(w).is_some()) {
+        task.priority = parse_priority(priority).unwrap_or_default();
+    }
+    let created = words.next_if(|w| parse_date(w).is_some());
+    if let Some(created) = created.and_then(parse_datetime) {
+        task.created_at = created;
+        task.updated_at = created;
+    }
+
+    let mut title = Vec::new();
+    let mut project = None;
+    let mut context = None;
+    let mut parent = None;
+    let mut depends = Vec::new();
+    let mut rec = None;
+    let mut uuid = None;
+    for word in words {
+        if let Some(text) = word.strip_prefix('\\').filter(|w| escaped(w)) {
+            title.push(text);
+            continue;
+        }
+        if let Some(name) = word.strip_prefix('+').filter(|n| !n.is_empty()) {
+            match project {
+                None => project = Some(name.to_string()),
+                Some(_) => task.tags.push(name.to_string()),
+            }
+            continue;
+        }
+        if let Some(name) = word.strip_prefix('@').filter(|n| !n.is_empty()) {
+            match context {
+                None => context = Some(name.to_string()),
+                Some(_) => task.tags.push(name.to_string()),
+            }
+            continue;
+        }
+        if let Some(tag) = word.strip_prefix('#').filter(|t| !t.is_empty()) {
+            task.tags.push(tag.to_string());
+            continue;
+        }
+        if let Some(end) = RecurrenceEnd::parse(word) {
+            task.recurrence_end = Some(end);
+            continue;
+        }
+
+        let Some((key, value)) = word.split_once(':').filter(|(_, v)| !v.is_empty()) else {
+            title.push(word);
+            continue;
+        };
+        match key {
+            "due" if parse_date(value).is_some() => task.due_date = parse_date(value),
+            "t" if parse_date(value).is_some() => task.scheduled_date = parse_date(value),
+            "pri" if done && parse_letter(value).is_some() => {
+                task.priority = parse_letter(value).unwrap_or_default();
+            }
+            "rec" => rec = Some(value.to_string()),
+            "uuid" => uuid = Some(value.to_string()),
+            "parent" => parent = Some(value.to_string()),
+            "dep" => depends.push(value.to_string()),
+            "series" => task.series_uuid = Some(value.to_string()),
+            "prev" => task.previous_uuid = Some(value.to_string()),
+            "occ" if value.parse::<u32>().is_ok() => task.occurrence = value.parse().unwrap_or(1),
+            "est" if value.parse::<u32>().is_ok() => task.estimated_mins = value.parse().ok(),
+            "status" => match value {
+                "started" if !done => task.status = Status::InProgress,
+                "blocked" if !done => task.status = Status::Blocked,
+                "cancelled" => task.status = Status::Cancelled,
+                _ => title.push(word),
+            },
+            _ => title.push(word),
+        }
+    }
+
+    if title.is_empty() {
+        return None;
+    }
+    task.title = title.join(" ");
+    task.uuid = uuid.unwrap_or_else(|| {
+        let name = format!("{} {}", created.unwrap_or_default(), task.title);
+        let count = seen.entry(name.clone()).or_insert(0);
+        *count += 1;
+        let name = format!("todo.txt:{}:{}", count, name);
+        Uuid::new_v5(&Uuid::NAMESPACE_OID, name.as_bytes()).to_string()
+    });
+
+    let anchor = task.due_date.or(task.scheduled_date).unwrap_or_else(|| task.created_at.date_naive());
+    task.recurrence = rec.and_then(|rec| {
+        Interval::parse(&rec)
+            .map(|interval| interval.to_recurrence(anchor))
+            .or_else(|| Recurrence::parse(&rec))
+    });
+
+    Some(ImportedTask { task, project, context, parent, depends })
+}
+
+/// Format tasks as todo.txt lines.
+pub fn format(tasks: &[Task], names: &Names) -> String {
+    tasks.iter().map(|task| format_line(task, names) + "\n").collect()
+}
+
+fn format_line(task: &Task, names: &Names) -> String {
+    let mut parts = Vec::new();
+    let done = matches!(task.status, Status::Done | Status::Cancelled);
+    let letter = match task.priority {
+        Priority::Urgent => Some('A'),
+        Priority::High => Some('B'),
+        Priority::Medium => Some('C'),
+        Priority::Low => Some('D'),
+        Priority::None => None,
+    };
+
+    if done {
+        parts.push("x".to_string());
+        parts.push(format_date(task.completed_at.unwrap_or(task.updated_at)));
+    } else if let Some(letter) = letter {
+        parts.push(format!("({})", letter));
+    }
+    parts.push(format_date(task.created_at));
+    parts.extend(task.title.split_whitespace().map(|word| match escaped(word) {
+        true => format!("\\{}", word),
+        false => word.to_string(),
+    }));
+
+    if let Some(project) = names.project(task) {
+        parts.push(format!("+{}", word(project)));
+    }
+    if let Some(context) = names.context(task) {
+        parts.push(format!("@{}", word(context)));
+    }
+    parts.extend(task.tags.iter().map(|tag| format!("#{}", word(tag))));
+
+    if let Some(due) = task.due_date {
+        parts.push(format!("due:{}", due.format(DATE_FORMAT)));
+    }
+    if let Some(scheduled) = task.scheduled_date {
+        parts.push(format!("t:{}", scheduled.format(DATE_FORMAT)));
+    }
+    if done {
+        if let Some(letter) = letter {
+            parts.push(format!("pri:{}", letter));
+        }
+    }
+    match task.status {
+        Status::InProgress => parts.push("status:started".to_string()),
+        Status::Blocked => parts.push("status:blocked".to_string()),
+        Status::Cancelled => parts.push("status:cancelled".to_string()),
+        _ => {}
+    }
+    if let Some(mins) = task.estimated_mins {
+        parts.push(format!("est:{}", mins));
+    }
+
+    if let Some(rule) = &task.recurrence {
+        let anchor = task.due_date.or(task.scheduled_date);
+        let rec = match Interval::from_recurrence(rule, anchor) {
+            Some(Interval::Days(days)) => format!("{}d", days),
+            Some(Interval::Weekly) => "1w".to_string(),
+            Some(Interval::Monthly) => "1m".to_string(),
+            Some(Interval::Months(months)) if months % 12 == 0 => format!("{}y", months / 12),
+            Some(Interval::Months(months)) => format!("{}m", months),
+            Some(Interval::Yearly) => "1y".to_string(),
+            Some(Interval::Weekdays) | None => rule.rule(),
+        };
+        parts.push(format!("rec:{}", rec));
+        match task.recurrence_end {
+            Some(RecurrenceEnd::After { count }) => parts.push(format!("after:{}", count)),
+            Some(RecurrenceEnd::On { date }) => {
+                parts.push(format!("until:{}", date.format(DATE_FORMAT)))
+            }
+            None => {}
+        }
+        if let Some(series) = &task.series_uuid {
+            parts.push(format!("series:{}", series));
+        }
+        if let Some(previous) = &task.previous_uuid {
+            parts.push(format!("prev:{}", previous));
+        }
+        parts.push(format!("occ:{}", task.occurrence));
+    }
+
+    if let Some(parent) = names.parent(task) {
+        parts.push(format!("parent:{}", parent));
+    }
+    parts.extend(names.depends(task).iter().map(|uuid| format!("dep:{}", uuid)));
+    parts.push(format!("uuid:{}", task.uuid));
+    parts.join(" ")
+}
+
+/// Whether a title word needs a `\` to be read back as text: it reads as
+/// metadata, or is such a word already escaped.
+fn escaped(word: &str) -> bool {
+    let word = word.trim_start_matches('\\');
+    let tagged = word.len() > 1 && word.starts_with(['+', '@', '#']);
+    let pair = word
+        .split_once(':')
+        .is_some_and(|(key, value)| !value.is_empty() && KEYS.contains(&key));
+    tagged || pair || RecurrenceEnd::parse(word).is_some()
+}
+
+/// `(A)` style priority.
+fn parse_priority(word: &str) -> Option<Priority> {
+    parse_letter(word.strip_prefix('(')?.strip_suffix(')')?)
+}
+
+fn parse_letter(letter: &str) -> Option<Priority> {
+    match letter {
+        "A" => Some(Priority::Urgent),
+        "B" => Some(Priority::High),
+        "C" => Some(Priority::Medium),
+        // todo.txt allows A-Z; everything below C is low
+        l if l.len() == 1 && l.chars().all(|c| c.is_ascii_uppercase()) => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, DATE_FORMAT).ok()
+}
+
+fn parse_datetime(s: &str) -> Option<DateTime<Utc>> {
+    parse_date(s).and_then(|d| d.and_hms_opt(0, 0, 0)).map(|dt| dt.and_utc())
+}
+
+fn format_date(dt: DateTime<Utc>) -> String {
+    dt.format(DATE_FORMAT).to_string()
+}
+
+/// Names may not contain spaces in todo.txt.
+fn word(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines() {
+        let input = "\
+(A) 2024-01-02 Call dentist +Health @phone due:2024-01-10 #errand http://example.com
+x 2024-01-05 2024-01-01 Buy milk +Home +Shopping pri:C
+2024-01-03 Water plants rec:1w due:2024-01-05 until:2024-06-01
+
+(B)
+";
+        let (tasks, skipped) = parse(input);
+        assert_eq!(skipped, 1);
+        assert_eq!(tasks.len(), 3);
+
+        let dentist = &tasks[0];
+        assert_eq!(dentist.task.title, "Call dentist http://example.com");
+        assert_eq!(dentist.task.priority, Priority::Urgent);
+        assert_eq!(dentist.project.as_deref(), Some("Health"));
+        assert_eq!(dentist.context.as_deref(), Some("phone"));
+        assert_eq!(dentist.task.tags, vec!["errand"]);
+        assert_eq!(dentist.task.due_date, NaiveDate::from_ymd_opt(2024, 1, 10));
+
+        let milk = &tasks[1];
+        assert_eq!(milk.task.status, Status::Done);
+        assert_eq!(milk.task.priority, Priority::Medium);
+        assert_eq!(milk.task.tags, vec!["Shopping"]);
+        assert_eq!(milk.task.completed_at.map(|d| d.date_naive()), NaiveDate::from_ymd_opt(2024, 1, 5));
+        assert_eq!(milk.task.created_at.date_naive(), NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+
+        let plants = &tasks[2].task;
+        // 2024-01-05 is a Friday
+        assert_eq!(plants.recurrence, Some(Recurrence::Weekly { days: vec![5] }));
+        assert!(matches!(plants.recurrence_end, Some(RecurrenceEnd::On { .. })));
+    }
+}
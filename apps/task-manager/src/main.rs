@@ -3,28 +3,46 @@
 //!
 //! Features:
 //! - Tasks with priority, due dates, tags, and recurrence
+//! - Blocked-by dependencies with critical paths per project
 //! - Projects and contexts for organization
 //! - Multiple views: list, board (kanban), calendar
-//! - Filtering and sorting
+//! - Filtering and sorting, with a filter expression language and saved views
 //! - SQLite persistence
+//! - Taskwarrior JSON and todo.txt import/export
 
 mod app;
 mod config;
 mod db;
+mod deps;
+mod interop;
 mod models;
+mod plugins;
+mod query;
+mod recurrence;
 mod ui;
 
 use anyhow::Result;
 use app::App;
+use config::Config;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::{io, time::Duration};
+use db::Database;
+use interop::Format;
+use query::Query;
+use std::{env, io, path::Path, time::Duration};
 
 fn main() -> Result<()> {
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some(command @ ("import" | "export")) => return run_interop(command, &args[2..]),
+        Some("report") => return run_report(&args[2..]),
+        _ => {}
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -60,5 +78,106 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &mut A
                 app.handle_key(key);
             }
         }
+
+        app.tick();
+    }
+}
+
+/// `task-manager import|export <file> [--format taskwarrior|todotxt]`
+fn run_interop(command: &str, args: &[String]) -> Result<()> {
+    let mut path = None;
+    let mut format = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" | "-f" => {
+                let name = args.next().map(String::as_str).unwrap_or_default();
+                format = Some(
+                    Format::from_name(name)
+                        .ok_or_else(|| anyhow::anyhow!("unknown format: {}", name))?,
+                );
+            }
+            _ => path = Some(Path::new(arg)),
+        }
+    }
+    let path = path.ok_or_else(|| {
+        anyhow::anyhow!("usage: task-manager {} <file> [--format taskwarrior|todotxt]", command)
+    })?;
+
+    let db = open_db()?;
+    if command == "import" {
+        let summary = interop::import_file(&db, path, format)?;
+        println!("Imported {}: {}", path.display(), summary.label());
+    } else {
+        let count = interop::export_file(&db, path, format)?;
+        println!("Exported {} tasks to {}", count, path.display());
+    }
+    Ok(())
+}
+
+/// `task-manager report [<view>|<filter>...] [--all] [--json]`
+///
+/// Prints tasks matching a saved view or a filter expression, one per line
+/// as tab-separated id, status, priority, due date, project and title.
+/// Without arguments, lists the saved views.
+fn run_report(args: &[String]) -> Result<()> {
+    let config = Config::load();
+    let mut words = Vec::new();
+    let mut include_completed = false;
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--all" | "-a" => include_completed = true,
+            "--json" => json = true,
+            _ => words.push(arg.as_str()),
+        }
+    }
+
+    if words.is_empty() {
+        for view in &config.views {
+            println!("{}\t{}", view.name, view.filter);
+        }
+        return Ok(());
+    }
+
+    let expression = words.join(" ");
+    let source = match config.view_index(&expression) {
+        Some(index) => config.views[index].filter.as_str(),
+        None => expression.as_str(),
+    };
+    let query = Query::parse(source)?;
+
+    let db = open_db()?;
+    let tasks = db.list_tasks_matching(&query, include_completed)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&tasks)?);
+        return Ok(());
+    }
+
+    let projects = db.list_projects(true)?;
+    for task in tasks {
+        let project = task
+            .project_id
+            .and_then(|id| projects.iter().find(|p| p.id == id))
+            .map(|p| p.name.as_str())
+            .unwrap_or_default();
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            task.id,
+            task.status.label(),
+            task.priority.label(),
+            task.due_date.map(|d| d.to_string()).unwrap_or_default(),
+            project,
+            task.title
+        );
+    }
+    Ok(())
+}
+
+fn open_db() -> Result<Database> {
+    let db_path = Config::db_path().unwrap_or_else(|| "tasks.db".into());
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
     }
+    Ok(Database::open(&db_path)?)
 }
@@ -102,13 +102,14 @@ impl Status {
 }
 
 /// Recurrence pattern.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Recurrence {
     Daily,
     Weekly { days: Vec<u8> }, // 0=Sun, 1=Mon, etc.
     Monthly { day: u8 },
     Yearly { month: u8, day: u8 },
     Every { days: u32 },
+    EveryMonths { months: u32, day: u8 },
 }
 
 impl Recurrence {
@@ -131,10 +132,38 @@ impl Recurrence {
             Recurrence::Monthly { day } => format!("Monthly (day {})", day),
             Recurrence::Yearly { month, day } => format!("Yearly ({}/{})", month, day),
             Recurrence::Every { days } => format!("Every {} days", days),
+            Recurrence::EveryMonths { months, day } => {
+                format!("Every {} months (day {})", months, day)
+            }
         }
     }
 }
 
+/// When a recurring series stops generating instances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecurrenceEnd {
+    /// Stop after this many occurrences.
+    After { count: u32 },
+    /// No occurrences after this date.
+    On { date: NaiveDate },
+}
+
+impl RecurrenceEnd {
+    pub fn label(&self) -> String {
+        match self {
+            RecurrenceEnd::After { count } => format!("{} times", count),
+            RecurrenceEnd::On { date } => format!("until {}", date),
+        }
+    }
+}
+
+/// A timestamped note attached to a task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub entry: DateTime<Utc>,
+    pub description: String,
+}
+
 /// A task.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
@@ -147,9 +176,17 @@ pub struct Task {
     pub project_id: Option<ProjectId>,
     pub context_id: Option<ContextId>,
     pub tags: Vec<String>,
+    pub annotations: Vec<Annotation>,
     pub due_date: Option<NaiveDate>,
     pub scheduled_date: Option<NaiveDate>,
     pub recurrence: Option<Recurrence>,
+    pub recurrence_end: Option<RecurrenceEnd>,
+    /// UUID of the first task in the recurring series.
+    pub series_uuid: Option<String>,
+    /// UUID of the instance this one was generated from.
+    pub previous_uuid: Option<String>,
+    /// 1-based position in the recurring series.
+    pub occurrence: u32,
     pub estimated_mins: Option<u32>,
     pub actual_mins: Option<u32>,
     pub parent_id: Option<TaskId>,
@@ -171,9 +208,14 @@ impl Task {
             project_id: None,
             context_id: None,
             tags: Vec::new(),
+            annotations: Vec::new(),
             due_date: None,
             scheduled_date: None,
             recurrence: None,
+            recurrence_end: None,
+            series_uuid: None,
+            previous_uuid: None,
+            occurrence: 1,
             estimated_mins: None,
             actual_mins: None,
             parent_id: None,
@@ -207,6 +249,10 @@ impl Task {
         }
     }
 
+    pub fn is_recurring(&self) -> bool {
+        self.recurrence.is_some()
+    }
+
     pub fn days_until_due(&self) -> Option<i64> {
         self.due_date.map(|d| {
             let today = Utc::now().date_naive();
@@ -0,0 +1,235 @@
+//! Plugin integration: commands exposed to plugins and handling of their actions.
+
+use crate::app::{App, InputField, View};
+use std::collections::HashMap;
+use tui_plugins::{AppCommand, HostAction, SelectionType};
+
+/// Commands plugins can run with `tui.run_command`.
+pub fn commands() -> Vec<AppCommand> {
+    vec![
+        AppCommand::new("add_task", "Add task").with_description("Create a task; args: title"),
+        AppCommand::new("toggle_status", "Toggle status")
+            .with_description("Cycle the selected task's status"),
+        AppCommand::new("cycle_priority", "Cycle priority")
+            .with_description("Cycle the selected task's priority"),
+        AppCommand::new("skip_occurrence", "Skip occurrence")
+            .with_description("Move the selected recurring task to its next occurrence"),
+        AppCommand::new("set_recurrence", "Set recurrence").with_description(
+            "args: rule (daily, weekly:mon,fri, monthly:31, yearly:2-29, every:10, none), \
+             end (after:5, until:2025-12-31)",
+        ),
+        AppCommand::new("delete_task", "Delete task").with_description("Delete the selected task"),
+        AppCommand::new("link_blocker", "Link blocker").with_description(
+            "Mark the selected task as a blocker, or make it blocked by the marked task",
+        ),
+        AppCommand::new("show_blocked", "Toggle blocked"),
+        AppCommand::new("search", "Search").with_description("Filter tasks; args: query"),
+        AppCommand::new("filter", "Filter")
+            .with_description("Filter tasks by expression; args: query (e.g. project:work +urgent)"),
+        AppCommand::new("view", "Switch view")
+            .with_description(
+                "args: name (list, board, projects, today, dependencies, or a saved view)",
+            ),
+        AppCommand::new("show_completed", "Toggle completed"),
+        AppCommand::new("refresh", "Refresh").with_description("Reload tasks from the database"),
+    ]
+}
+
+impl App {
+    /// Roll over recurring tasks, fire plugin timers and apply anything they asked for.
+    pub fn tick(&mut self) {
+        if let Ok(created) = self.roll_over_recurring() {
+            if created > 0 {
+                let _ = self.refresh();
+            }
+        }
+        self.plugins.tick();
+        self.sync_plugins();
+    }
+
+    /// Report the current selection and apply queued plugin actions.
+    pub fn sync_plugins(&mut self) {
+        if let Some(task) = self.tasks.get(self.selected_index) {
+            let title = task.title.clone();
+            self.plugins.selection_changed(title, SelectionType::Item);
+        }
+
+        for action in self.plugins.take_actions() {
+            self.apply_plugin_action(action);
+        }
+    }
+
+    fn apply_plugin_action(&mut self, action: HostAction) {
+        match action {
+            HostAction::Notify { message, .. } => self.message = Some(message),
+            HostAction::Prompt(prompt) => self.prompt = Some(prompt),
+            HostAction::RunCommand { name, args } => self.run_command(&name, &args),
+            HostAction::InsertText(text) => {
+                if !self.editing {
+                    self.start_add_task();
+                }
+                self.input_buffer.push_str(&text);
+            }
+            HostAction::ReplaceSelection(text) => {
+                if let Some(task) = self.tasks.get_mut(self.selected_index) {
+                    task.title = text;
+                    let _ = self.db.update_task(task);
+                    let _ = self.refresh();
+                }
+            }
+            HostAction::RequestData {
+                plugin,
+                data_type,
+                callback,
+            } => {
+                let data = match data_type.as_str() {
+                    "selection" => serde_json::to_value(self.tasks.get(self.selected_index)),
+                    "tasks" => serde_json::to_value(&self.tasks),
+                    "stats" => Ok(serde_json::json!({
+                        "total": self.stats.total,
+                        "todo": self.stats.todo,
+                        "in_progress": self.stats.in_progress,
+                        "done": self.stats.done,
+                        "overdue": self.stats.overdue,
+                        "due_today": self.stats.due_today,
+                    })),
+                    _ => Ok(serde_json::Value::Null),
+                };
+                self.plugins
+                    .reply(&plugin, &callback, data.unwrap_or_default());
+            }
+            _ => {}
+        }
+    }
+
+    /// Run an app command on behalf of a plugin.
+    pub fn run_command(&mut self, name: &str, args: &HashMap<String, serde_json::Value>) {
+        let arg = |key: &str| args.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+
+        match name {
+            "add_task" => {
+                let title = arg("title");
+                if title.is_empty() {
+                    self.start_add_task();
+                } else {
+                    self.editing = true;
+                    self.input_field = InputField::TaskTitle;
+                    self.input_buffer = title.to_string();
+                    self.finish_editing();
+                }
+            }
+            "toggle_status" => self.toggle_task_status(),
+            "cycle_priority" => self.cycle_priority(),
+            "skip_occurrence" => self.skip_occurrence(),
+            "set_recurrence" => self.set_recurrence(arg("rule"), arg("end")),
+            "delete_task" => self.delete_selected(),
+            "link_blocker" => self.link_selected(),
+            "show_blocked" => self.toggle_show_blocked(),
+            "search" => {
+                self.filter.search = arg("query").to_string();
+                let _ = self.reload_tasks();
+                self.selected_index = 0;
+            }
+            "filter" => self.apply_query(arg("query")),
+            "view" => match arg("name") {
+                "board" => self.show_view(View::Board),
+                "projects" => self.view = View::Projects,
+                "today" => self.show_today(),
+                "dependencies" | "deps" => self.show_view(View::Dependencies),
+                name => match self.config.view_index(name) {
+                    Some(index) => self.open_saved_view(index),
+                    None => {
+                        self.view = View::List;
+                        self.filter = Default::default();
+                        let _ = self.refresh();
+                    }
+                },
+            },
+            "show_completed" => self.toggle_show_completed(),
+            "refresh" => {
+                let _ = self.refresh();
+            }
+            other => self.message = Some(format!("Unknown command: {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, SavedView};
+    use crate::db::Database;
+    use crate::models::{Recurrence, RecurrenceEnd};
+    use serde_json::json;
+
+    fn app() -> App {
+        let mut config = Config::default();
+        config.views.push(SavedView {
+            name: "Urgent".to_string(),
+            filter: "+urgent".to_string(),
+        });
+        App::with_db(Database::in_memory().unwrap(), config).unwrap()
+    }
+
+    fn args(pairs: &[(&str, &str)]) -> HashMap<String, serde_json::Value> {
+        pairs.iter().map(|(k, v)| (k.to_string(), json!(v))).collect()
+    }
+
+    #[test]
+    fn test_commands_dispatch() {
+        let mut app = app();
+        for command in commands() {
+            app.message = None;
+            app.run_command(&command.name, &HashMap::new());
+            let message = app.message.take().unwrap_or_default();
+            assert!(!message.starts_with("Unknown command"), "{}", command.name);
+            app.editing = false;
+        }
+
+        app.run_command("frobnicate", &HashMap::new());
+        assert_eq!(app.message.as_deref(), Some("Unknown command: frobnicate"));
+    }
+
+    #[test]
+    fn test_add_task_and_set_recurrence() {
+        let mut app = app();
+        app.run_command("add_task", &args(&[("title", "Water plants")]));
+        assert!(!app.editing);
+        assert_eq!(app.tasks.len(), 1);
+        assert_eq!(app.tasks[0].title, "Water plants");
+
+        let weekly = args(&[("rule", "weekly:mon,fri"), ("end", "after:5")]);
+        app.run_command("set_recurrence", &weekly);
+        assert_eq!(app.tasks[0].recurrence, Some(Recurrence::Weekly { days: vec![1, 5] }));
+        assert_eq!(app.tasks[0].recurrence_end, Some(RecurrenceEnd::After { count: 5 }));
+
+        app.run_command("set_recurrence", &args(&[("rule", "fortnightly")]));
+        assert_eq!(app.message.as_deref(), Some("Invalid recurrence: fortnightly"));
+        app.run_command("set_recurrence", &args(&[("rule", "daily"), ("end", "soon")]));
+        assert_eq!(app.message.as_deref(), Some("Invalid end: soon"));
+        assert_eq!(app.tasks[0].recurrence, Some(Recurrence::Weekly { days: vec![1, 5] }));
+
+        app.run_command("set_recurrence", &args(&[("rule", "none")]));
+        assert_eq!(app.tasks[0].recurrence, None);
+        assert_eq!(app.tasks[0].recurrence_end, None);
+    }
+
+    #[test]
+    fn test_view_and_filter() {
+        let mut app = app();
+        app.run_command("view", &args(&[("name", "deps")]));
+        assert_eq!(app.view, View::Dependencies);
+        app.run_command("view", &args(&[("name", "Urgent")]));
+        assert_eq!(app.view, View::Saved(0));
+        assert_eq!(app.filter.query.as_ref().map(|q| q.source()), Some("+urgent"));
+        app.run_command("view", &args(&[("name", "nowhere")]));
+        assert_eq!(app.view, View::List);
+        assert!(app.filter.query.is_none());
+
+        app.run_command("filter", &args(&[("query", "due.before:")]));
+        assert!(app.message.as_deref().is_some_and(|m| m.starts_with("Invalid filter")));
+        assert!(app.filter.query.is_none());
+        app.run_command("filter", &args(&[("query", "priority:high")]));
+        assert_eq!(app.filter.query.as_ref().map(|q| q.source()), Some("priority:high"));
+    }
+}
@@ -0,0 +1,789 @@
+//! Filter expression language.
+//!
+//! ```text
+//! project:work +urgent due.before:eow -waiting priority>=high
+//! ```
+//!
+//! Terms are ANDed together; `or`, `not` and parentheses combine them
+//! further. A query compiles to a SQL condition on the `tasks` table.
+//!
+//! - `+tag` / `-tag` require or exclude a tag; upper-case names such as
+//!   `+OVERDUE`, `+TODAY`, `+WEEK`, `+ACTIVE` or `+RECURRING` are virtual tags;
+//!   `+BLOCKED` also covers tasks waiting on an open blocker, `+BLOCKING`
+//!   tasks others wait on
+//! - `@name` is short for `context:name`
+//! - `field:value`, `field.modifier:value` or `field<op>value` with the
+//!   operators `= != < <= > >=` and the modifiers `is`, `not`, `before`,
+//!   `after`, `by`, `has`, `none` and `any`
+//! - fields: `project`, `context`, `status`, `priority`, `tag`, `due`,
+//!   `scheduled`, `created`, `completed` and `description`
+//! - anything else, or a `"quoted phrase"`, searches title, description and tags
+//!
+//! Dates are `YYYY-MM-DD`, `today`, `tomorrow`, `yesterday`, a weekday name,
+//! an offset such as `+3d`, `2w` or `-1m`, or a period boundary: `sow`/`eow`,
+//! `som`/`eom`, `soy`/`eoy`. Period ends cover the whole last day, so
+//! `due.before:eow` includes tasks due on Sunday.
+
+use crate::models::{Priority, Status};
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+use rusqlite::types::Value;
+use std::fmt;
+
+/// A parsed filter expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    source: String,
+    expr: Option<Expr>,
+}
+
+/// Error from parsing a filter expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError(pub String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+type ParseResult<T> = Result<T, ParseError>;
+
+fn error<T>(message: impl Into<String>) -> ParseResult<T> {
+    Err(ParseError(message.into()))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    /// Project by name, including sub-projects (`Home` matches `Home.Admin`)
+    Project(Option<String>),
+    Context(Option<String>),
+    /// `None` matches untagged tasks
+    Tag(Option<String>),
+    Virtual(VirtualTag),
+    Status(Vec<Status>),
+    Priority(Cmp, Priority),
+    /// A date comparison; a value of `None` tests for a missing date
+    Date(DateField, Cmp, Option<String>),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateField {
+    Due,
+    Scheduled,
+    Created,
+    Completed,
+}
+
+impl DateField {
+    /// SQL for the field as a `YYYY-MM-DD` string.
+    fn column(self) -> &'static str {
+        match self {
+            DateField::Due => "due_date",
+            DateField::Scheduled => "scheduled_date",
+            DateField::Created => "substr(created_at, 1, 10)",
+            DateField::Completed => "substr(completed_at, 1, 10)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VirtualTag {
+    Overdue,
+    Today,
+    Tomorrow,
+    Week,
+    Active,
+    Blocked,
+    Unblocked,
+    Blocking,
+    Pending,
+    Completed,
+    Cancelled,
+    Recurring,
+    Scheduled,
+    Tagged,
+}
+
+impl VirtualTag {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "OVERDUE" => VirtualTag::Overdue,
+            "TODAY" => VirtualTag::Today,
+            "TOMORROW" => VirtualTag::Tomorrow,
+            "WEEK" => VirtualTag::Week,
+            "ACTIVE" => VirtualTag::Active,
+            "BLOCKED" | "WAITING" => VirtualTag::Blocked,
+            "UNBLOCKED" => VirtualTag::Unblocked,
+            "BLOCKING" => VirtualTag::Blocking,
+            "PENDING" => VirtualTag::Pending,
+            "COMPLETED" => VirtualTag::Completed,
+            "CANCELLED" | "DELETED" => VirtualTag::Cancelled,
+            "RECURRING" => VirtualTag::Recurring,
+            "SCHEDULED" => VirtualTag::Scheduled,
+            "TAGGED" => VirtualTag::Tagged,
+            _ => return None,
+        })
+    }
+}
+
+const OPEN: &str = "status NOT IN ('Done', 'Cancelled')";
+
+/// Blocked by status or by an open blocker.
+const BLOCKED: &str = "(status = 'Blocked' OR EXISTS (SELECT 1 FROM dependencies d \
+     JOIN tasks b ON b.id = d.blocker_id \
+     WHERE d.task_id = tasks.id AND b.status NOT IN ('Done', 'Cancelled')))";
+
+impl Query {
+    /// Parse an expression. An empty expression matches every task.
+    pub fn parse(source: &str) -> ParseResult<Self> {
+        let tokens = tokenize(source);
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = if parser.tokens.is_empty() { None } else { Some(parser.or()?) };
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return error(format!("unexpected {}", token.describe()));
+        }
+        Ok(Self { source: source.trim().to_string(), expr })
+    }
+
+    /// The expression as written.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expr.is_none()
+    }
+
+    /// Whether the query selects by status, so completed tasks should not
+    /// be hidden implicitly.
+    pub fn mentions_status(&self) -> bool {
+        fn walk(expr: &Expr) -> bool {
+            match expr {
+                Expr::And(exprs) | Expr::Or(exprs) => exprs.iter().any(walk),
+                Expr::Not(expr) => walk(expr),
+                Expr::Term(Term::Status(_)) => true,
+                Expr::Term(Term::Virtual(tag)) => matches!(
+                    tag,
+                    VirtualTag::Completed | VirtualTag::Cancelled | VirtualTag::Pending
+                ),
+                Expr::Term(Term::Date(DateField::Completed, _, _)) => true,
+                Expr::Term(_) => false,
+            }
+        }
+        self.expr.as_ref().is_some_and(walk)
+    }
+
+    /// Compile to a SQL condition and its parameters, resolving relative
+    /// dates against `today`.
+    pub fn to_sql(&self, today: NaiveDate) -> (String, Vec<Value>) {
+        let mut params = Vec::new();
+        let sql = match &self.expr {
+            Some(expr) => compile(expr, today, &mut params),
+            None => "1".to_string(),
+        };
+        (sql, params)
+    }
+}
+
+fn compile(expr: &Expr, today: NaiveDate, params: &mut Vec<Value>) -> String {
+    match expr {
+        Expr::And(exprs) => join(exprs, " AND ", today, params),
+        Expr::Or(exprs) => join(exprs, " OR ", today, params),
+        Expr::Not(expr) => format!("NOT ({})", compile(expr, today, params)),
+        Expr::Term(term) => compile_term(term, today, params),
+    }
+}
+
+fn join(exprs: &[Expr], sep: &str, today: NaiveDate, params: &mut Vec<Value>) -> String {
+    let parts: Vec<String> = exprs
+        .iter()
+        .map(|e| format!("({})", compile(e, today, params)))
+        .collect();
+    parts.join(sep)
+}
+
+fn compile_term(term: &Term, today: NaiveDate, params: &mut Vec<Value>) -> String {
+    let mut param = |value: Value| {
+        params.push(value);
+        format!("?{}", params.len())
+    };
+
+    match term {
+        Term::Project(None) => "project_id IS NULL".to_string(),
+        Term::Project(Some(name)) => {
+            let exact = param(Value::Text(name.clone()));
+            let child = param(Value::Text(format!("{}.%", escape_like(name))));
+            format!(
+                "project_id IN (SELECT id FROM projects WHERE name = {} COLLATE NOCASE \
+                 OR name LIKE {} ESCAPE '\\')",
+                exact, child
+            )
+        }
+        Term::Context(None) => "context_id IS NULL".to_string(),
+        Term::Context(Some(name)) => format!(
+            "context_id IN (SELECT id FROM contexts WHERE name = {} COLLATE NOCASE)",
+            param(Value::Text(name.clone()))
+        ),
+        Term::Tag(None) => "tags = ''".to_string(),
+        Term::Tag(Some(tag)) => format!(
+            "(',' || tags || ',') LIKE {} ESCAPE '\\'",
+            param(Value::Text(format!("%,{},%", escape_like(tag))))
+        ),
+        Term::Virtual(tag) => {
+            let day = |date: NaiveDate| Value::Text(date.to_string());
+            match tag {
+                VirtualTag::Overdue => format!("due_date < {} AND {}", param(day(today)), OPEN),
+                VirtualTag::Today => format!("due_date = {}", param(day(today))),
+                VirtualTag::Tomorrow => {
+                    format!("due_date = {}", param(day(today + Duration::days(1))))
+                }
+                VirtualTag::Week => {
+                    let (start, end) = week(today);
+                    let start = param(day(start));
+                    format!("due_date BETWEEN {} AND {}", start, param(day(end)))
+                }
+                VirtualTag::Active => "status = 'InProgress'".to_string(),
+                VirtualTag::Blocked => BLOCKED.to_string(),
+                VirtualTag::Unblocked => format!("NOT {}", BLOCKED),
+                VirtualTag::Blocking => format!(
+                    "{} AND EXISTS (SELECT 1 FROM dependencies d JOIN tasks b ON b.id = d.task_id \
+                     WHERE d.blocker_id = tasks.id AND b.status NOT IN ('Done', 'Cancelled'))",
+                    OPEN
+                ),
+                VirtualTag::Pending => OPEN.to_string(),
+                VirtualTag::Completed => "status = 'Done'".to_string(),
+                VirtualTag::Cancelled => "status = 'Cancelled'".to_string(),
+                VirtualTag::Recurring => "recurrence IS NOT NULL".to_string(),
+                VirtualTag::Scheduled => "scheduled_date IS NOT NULL".to_string(),
+                VirtualTag::Tagged => "tags != ''".to_string(),
+            }
+        }
+        Term::Status(statuses) => {
+            let names: Vec<String> = statuses
+                .iter()
+                .map(|s| param(Value::Text(format!("{:?}", s))))
+                .collect();
+            format!("status IN ({})", names.join(", "))
+        }
+        Term::Priority(cmp, priority) => format!(
+            "CASE priority WHEN 'Low' THEN 1 WHEN 'Medium' THEN 2 WHEN 'High' THEN 3 \
+             WHEN 'Urgent' THEN 4 ELSE 0 END {} {}",
+            cmp_sql(*cmp),
+            param(Value::Integer(*priority as i64))
+        ),
+        Term::Date(field, Cmp::Eq, None) => format!("{} IS NULL", field.column()),
+        Term::Date(field, _, None) => format!("{} IS NOT NULL", field.column()),
+        Term::Date(field, cmp, Some(value)) => {
+            // Validated while parsing
+            let (date, end_of_day) = resolve_date(value, today).unwrap_or((today, false));
+            // A period end is the last instant of its day
+            let cmp = match (cmp, end_of_day) {
+                (Cmp::Lt, true) => Cmp::Le,
+                (Cmp::Ge, true) => Cmp::Gt,
+                (cmp, _) => *cmp,
+            };
+            format!(
+                "{} {} {}",
+                field.column(),
+                cmp_sql(cmp),
+                param(Value::Text(date.to_string()))
+            )
+        }
+        Term::Text(text) => {
+            let pattern = format!("%{}%", escape_like(text));
+            let title = param(Value::Text(pattern));
+            format!(
+                "(title LIKE {0} ESCAPE '\\' OR description LIKE {0} ESCAPE '\\' \
+                 OR tags LIKE {0} ESCAPE '\\')",
+                title
+            )
+        }
+    }
+}
+
+fn cmp_sql(cmp: Cmp) -> &'static str {
+    match cmp {
+        Cmp::Eq => "=",
+        Cmp::Ne => "!=",
+        Cmp::Lt => "<",
+        Cmp::Le => "<=",
+        Cmp::Gt => ">",
+        Cmp::Ge => ">=",
+    }
+}
+
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+// Tokens
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Or,
+    And,
+    Not,
+    Word(String),
+    Quoted(String),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Open => "'('".to_string(),
+            Token::Close => "')'".to_string(),
+            Token::Or => "'or'".to_string(),
+            Token::And => "'and'".to_string(),
+            Token::Not => "'not'".to_string(),
+            Token::Word(w) | Token::Quoted(w) => format!("'{}'", w),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::Open);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::Close);
+            }
+            '"' => {
+                chars.next();
+                let text: String = chars.by_ref().take_while(|&c| c != '"').collect();
+                tokens.push(Token::Quoted(text));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    // Allow quoted values: description:"two words"
+                    if c == '"' {
+                        chars.next();
+                        word.extend(chars.by_ref().take_while(|&c| c != '"'));
+                        continue;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(match word.to_lowercase().as_str() {
+                    "or" | "||" => Token::Or,
+                    "and" | "&&" => Token::And,
+                    "not" | "!" => Token::Not,
+                    _ => Token::Word(word),
+                });
+            }
+        }
+    }
+    tokens
+}
+
+// Grammar:
+//   or    := and ("or" and)*
+//   and   := unary ("and"? unary)*
+//   unary := "not" unary | "(" or ")" | term
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn or(&mut self) -> ParseResult<Expr> {
+        let mut exprs = vec![self.and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            exprs.push(self.and()?);
+        }
+        Ok(if exprs.len() == 1 { exprs.remove(0) } else { Expr::Or(exprs) })
+    }
+
+    fn and(&mut self) -> ParseResult<Expr> {
+        let mut exprs = vec![self.unary()?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => self.pos += 1,
+                Some(Token::Or | Token::Close) | None => break,
+                Some(_) => {}
+            }
+            exprs.push(self.unary()?);
+        }
+        Ok(if exprs.len() == 1 { exprs.remove(0) } else { Expr::And(exprs) })
+    }
+
+    fn unary(&mut self) -> ParseResult<Expr> {
+        let Some(token) = self.tokens.get(self.pos).cloned() else {
+            return error("expression ends early");
+        };
+        self.pos += 1;
+        match token {
+            Token::Not => Ok(Expr::Not(Box::new(self.unary()?))),
+            Token::Open => {
+                let expr = self.or()?;
+                if self.peek() != Some(&Token::Close) {
+                    return error("missing ')'");
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Token::Quoted(text) => Ok(Expr::Term(Term::Text(text))),
+            Token::Word(word) => parse_word(&word),
+            token => error(format!("unexpected {}", token.describe())),
+        }
+    }
+}
+
+fn parse_word(word: &str) -> ParseResult<Expr> {
+    if let Some(name) = word.strip_prefix('+').filter(|n| !n.is_empty()) {
+        return Ok(Expr::Term(tag_term(name)));
+    }
+    if let Some(name) = word.strip_prefix('-').filter(|n| !n.is_empty()) {
+        // `-3d` style words are text, not tags
+        if !name.starts_with(|c: char| c.is_ascii_digit()) {
+            return Ok(Expr::Not(Box::new(Expr::Term(tag_term(name)))));
+        }
+    }
+    if let Some(name) = word.strip_prefix('@').filter(|n| !n.is_empty()) {
+        return Ok(Expr::Term(Term::Context(Some(name.to_string()))));
+    }
+
+    let Some(split) = word.find([':', '=', '<', '>', '!']) else {
+        return Ok(Expr::Term(Term::Text(word.to_string())));
+    };
+    let (attribute, rest) = word.split_at(split);
+    let op_len = if rest[1..].starts_with('=') { 2 } else { 1 };
+    let (op, value) = rest.split_at(op_len);
+    if attribute.is_empty() {
+        return Ok(Expr::Term(Term::Text(word.to_string())));
+    }
+
+    let (field, modifier) = match attribute.split_once('.') {
+        Some((field, modifier)) => (field, Some(modifier)),
+        None => (attribute, None),
+    };
+    let cmp = match (op, modifier) {
+        (":" | "=", None) => Cmp::Eq,
+        (":" | "=", Some("is" | "equals" | "has" | "contains")) => Cmp::Eq,
+        (":" | "=", Some("not" | "isnt" | "hasnt")) => Cmp::Ne,
+        (":" | "=", Some("before" | "below" | "under")) => Cmp::Lt,
+        (":" | "=", Some("by")) => Cmp::Le,
+        (":" | "=", Some("after" | "above" | "over")) => Cmp::Gt,
+        (":" | "=", Some("none")) => return field_term(field, Cmp::Eq, ""),
+        (":" | "=", Some("any")) => return field_term(field, Cmp::Ne, ""),
+        ("!=" | "!:", None) => Cmp::Ne,
+        ("<", None) => Cmp::Lt,
+        ("<=", None) => Cmp::Le,
+        (">", None) => Cmp::Gt,
+        (">=", None) => Cmp::Ge,
+        (_, Some(modifier)) => return error(format!("unknown modifier '{}'", modifier)),
+        (op, None) => return error(format!("unknown operator '{}' in '{}'", op, word)),
+    };
+    field_term(field, cmp, value)
+}
+
+fn tag_term(name: &str) -> Term {
+    match VirtualTag::parse(name) {
+        Some(tag) => Term::Virtual(tag),
+        None => Term::Tag(Some(name.to_string())),
+    }
+}
+
+/// Build the term for `field <cmp> value`; an empty value means "no value".
+fn field_term(field: &str, cmp: Cmp, value: &str) -> ParseResult<Expr> {
+    let name = (!value.is_empty()).then(|| value.to_string());
+    let negate = |term: Term| match cmp {
+        Cmp::Eq => Ok(Expr::Term(term)),
+        Cmp::Ne => Ok(Expr::Not(Box::new(Expr::Term(term)))),
+        _ => error(format!("'{}' cannot be compared by order", field)),
+    };
+
+    match field.to_lowercase().as_str() {
+        "project" | "proj" | "pro" => negate(Term::Project(name)),
+        "context" | "ctx" => negate(Term::Context(name)),
+        "tag" | "tags" => negate(Term::Tag(name)),
+        "description" | "desc" | "title" => match name {
+            Some(text) => negate(Term::Text(text)),
+            None => error("description needs a value"),
+        },
+        "status" => match parse_status(value) {
+            Some(statuses) => negate(Term::Status(statuses)),
+            None => error(format!("unknown status '{}'", value)),
+        },
+        "priority" | "pri" => match parse_priority(value) {
+            Some(priority) => Ok(Expr::Term(Term::Priority(cmp, priority))),
+            None => error(format!("unknown priority '{}'", value)),
+        },
+        other => {
+            let field = match other {
+                "due" => DateField::Due,
+                "scheduled" | "sched" => DateField::Scheduled,
+                "created" | "entry" => DateField::Created,
+                "completed" | "end" => DateField::Completed,
+                _ => return error(format!("unknown field '{}'", field)),
+            };
+            let any_day = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap_or_default();
+            if name.is_some() && resolve_date(value, any_day).is_none() {
+                return error(format!("invalid date '{}'", value));
+            }
+            match (cmp, name.is_some()) {
+                (Cmp::Eq | Cmp::Ne, false) | (_, true) => {
+                    Ok(Expr::Term(Term::Date(field, cmp, name)))
+                }
+                (_, false) => error(format!("{} needs a date", other)),
+            }
+        }
+    }
+}
+
+fn parse_status(value: &str) -> Option<Vec<Status>> {
+    Some(match value.to_lowercase().as_str() {
+        "pending" | "open" => vec![Status::Todo, Status::InProgress, Status::Blocked],
+        "todo" => vec![Status::Todo],
+        "active" | "started" | "inprogress" | "in-progress" => vec![Status::InProgress],
+        "blocked" | "waiting" => vec![Status::Blocked],
+        "done" | "completed" => vec![Status::Done],
+        "cancelled" | "canceled" | "deleted" => vec![Status::Cancelled],
+        _ => return None,
+    })
+}
+
+fn parse_priority(value: &str) -> Option<Priority> {
+    Some(match value.to_lowercase().as_str() {
+        "" | "none" => Priority::None,
+        "l" | "low" => Priority::Low,
+        "m" | "medium" | "med" => Priority::Medium,
+        "h" | "high" => Priority::High,
+        "u" | "urgent" => Priority::Urgent,
+        _ => return None,
+    })
+}
+
+// Dates
+
+/// Monday and Sunday of the week containing `date`.
+fn week(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+    (start, start + Duration::days(6))
+}
+
+/// Resolve a date value; the flag is set for period ends, which stand for
+/// the end of their day.
+fn resolve_date(value: &str, today: NaiveDate) -> Option<(NaiveDate, bool)> {
+    let value = value.to_lowercase();
+    let start = |date: NaiveDate| Some((date, false));
+    let end = |date: NaiveDate| Some((date, true));
+    let month_start = today.with_day(1)?;
+
+    match value.as_str() {
+        "today" | "now" => return start(today),
+        "eod" => return end(today),
+        "tomorrow" => return start(today.succ_opt()?),
+        "yesterday" => return start(today.pred_opt()?),
+        "sow" => return start(week(today).0),
+        "eow" => return end(week(today).1),
+        "som" => return start(month_start),
+        "eom" => return end(month_start.checked_add_months(Months::new(1))?.pred_opt()?),
+        "soy" => return start(today.with_ordinal(1)?),
+        "eoy" => return end(NaiveDate::from_ymd_opt(today.year(), 12, 31)?),
+        _ => {}
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+        return start(date);
+    }
+    if let Ok(weekday) = value.parse::<Weekday>() {
+        // The next such day, never today
+        let ahead = (weekday.num_days_from_monday() + 7
+            - today.weekday().num_days_from_monday())
+            % 7;
+        return start(today + Duration::days(if ahead == 0 { 7 } else { ahead as i64 }));
+    }
+
+    // Offsets: 3d, +2w, -1m, 1y
+    let (sign, offset) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.trim_start_matches('+')),
+    };
+    let split = offset.find(|c: char| !c.is_ascii_digit())?;
+    let count: i64 = offset[..split].parse().ok()?;
+    match &offset[split..] {
+        // Offsets past chrono's range are invalid dates, not panics
+        "d" | "day" | "days" => {
+            start(today.checked_add_signed(Duration::try_days(sign * count)?)?)
+        }
+        "w" | "wk" | "week" | "weeks" => {
+            start(today.checked_add_signed(Duration::try_weeks(sign * count)?)?)
+        }
+        unit @ ("m" | "mo" | "month" | "months" | "y" | "yr" | "year" | "years") => {
+            let per = if unit.starts_with('y') { 12 } else { 1 };
+            let months = Months::new(u32::try_from(count).ok()?.checked_mul(per)?);
+            start(if sign < 0 {
+                today.checked_sub_months(months)?
+            } else {
+                today.checked_add_months(months)?
+            })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::models::{Context, Project, Task};
+
+    /// Wednesday
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 13).unwrap()
+    }
+
+    fn titles(db: &Database, query: &str) -> Vec<String> {
+        let (sql, params) = Query::parse(query).unwrap().to_sql(today());
+        let mut titles: Vec<String> = db
+            .list_tasks_where(&sql, &params)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    fn sample_db() -> Database {
+        let db = Database::in_memory().unwrap();
+        let work = db.insert_project(&Project::new("Work")).unwrap();
+        let admin = db.insert_project(&Project::new("Work.Admin")).unwrap();
+        let phone = db.insert_context(&Context::new("phone")).unwrap();
+
+        let mut report = Task::new("Quarterly report");
+        report.project_id = Some(work);
+        report.priority = Priority::High;
+        report.tags = vec!["urgent".into()];
+        report.due_date = NaiveDate::from_ymd_opt(2024, 3, 17);
+        let report = db.insert_task(&report).unwrap();
+
+        let mut expenses = Task::new("File expenses");
+        expenses.project_id = Some(admin);
+        expenses.priority = Priority::Urgent;
+        expenses.tags = vec!["urgent".into(), "waiting".into()];
+        expenses.due_date = NaiveDate::from_ymd_opt(2024, 3, 10);
+        db.insert_task(&expenses).unwrap();
+
+        let mut call = Task::new("Call plumber");
+        call.context_id = Some(phone);
+        call.priority = Priority::Low;
+        call.due_date = NaiveDate::from_ymd_opt(2024, 3, 20);
+        let call = db.insert_task(&call).unwrap();
+        db.add_dependency(call, report).unwrap();
+
+        let mut done = Task::new("Old report");
+        done.project_id = Some(work);
+        done.status = Status::Done;
+        db.insert_task(&done).unwrap();
+        db
+    }
+
+    #[test]
+    fn test_example_query() {
+        let db = sample_db();
+        assert_eq!(
+            titles(&db, "project:work +urgent due.before:eow -waiting priority>=high"),
+            vec!["Quarterly report"]
+        );
+    }
+
+    #[test]
+    fn test_terms() {
+        let db = sample_db();
+        assert_eq!(titles(&db, "project:Work.Admin"), vec!["File expenses"]);
+        assert_eq!(titles(&db, "project:"), vec!["Call plumber"]);
+        assert_eq!(titles(&db, "@phone"), vec!["Call plumber"]);
+        assert_eq!(titles(&db, "priority:low"), vec!["Call plumber", "Old report"]);
+        assert_eq!(titles(&db, "priority<high status:pending"), vec!["Call plumber"]);
+        assert_eq!(titles(&db, "+OVERDUE"), vec!["File expenses"]);
+        assert_eq!(titles(&db, "+WEEK"), vec!["Quarterly report"]);
+        assert_eq!(titles(&db, "due.none:"), vec!["Old report"]);
+        assert_eq!(titles(&db, "+BLOCKED"), vec!["Call plumber"]);
+        assert_eq!(titles(&db, "+BLOCKING"), vec!["Quarterly report"]);
+        assert_eq!(titles(&db, "+UNBLOCKED +urgent"), vec!["File expenses", "Quarterly report"]);
+        assert_eq!(titles(&db, "report status:done"), vec!["Old report"]);
+        assert_eq!(titles(&db, "\"call plumber\""), vec!["Call plumber"]);
+        assert_eq!(
+            titles(&db, "(@phone or +waiting) and not priority:urgent"),
+            vec!["Call plumber"]
+        );
+    }
+
+    #[test]
+    fn test_dates() {
+        let wed = today();
+        let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        assert_eq!(resolve_date("eow", wed), Some((day(17), true)));
+        assert_eq!(resolve_date("sow", wed), Some((day(11), false)));
+        assert_eq!(resolve_date("eom", wed), Some((day(31), true)));
+        assert_eq!(resolve_date("wednesday", wed), Some((day(20), false)));
+        assert_eq!(resolve_date("fri", wed), Some((day(15), false)));
+        assert_eq!(resolve_date("+3d", wed), Some((day(16), false)));
+        assert_eq!(resolve_date("-1w", wed), Some((day(6), false)));
+        assert_eq!(resolve_date("1m", wed), NaiveDate::from_ymd_opt(2024, 4, 13).map(|d| (d, false)));
+        assert_eq!(resolve_date("soon", wed), None);
+        assert_eq!(resolve_date("+999999999d", wed), None);
+        assert_eq!(resolve_date("+400000000y", wed), None);
+        assert_eq!(resolve_date("+99999999999999w", wed), None);
+        assert_eq!(resolve_date("-9223372036854775807d", wed), None);
+        for query in ["due:+999999999d", "due.before:+400000000y", "due:+99999999999999w"] {
+            let err = Query::parse(query).unwrap_err();
+            assert!(err.0.starts_with("invalid date"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Query::parse("").unwrap().is_empty());
+        assert!(Query::parse("status:done").unwrap().mentions_status());
+        assert!(!Query::parse("+urgent").unwrap().mentions_status());
+        assert!(Query::parse("due.before:someday").is_err());
+        assert!(Query::parse("project>work").is_err());
+        assert!(Query::parse("colour:red").is_err());
+        assert!(Query::parse("(+a or +b").is_err());
+        assert!(Query::parse("+a )").is_err());
+        assert!(Query::parse("due.soon:today").is_err());
+    }
+}
@@ -0,0 +1,354 @@
+//! Recurrence engine for repeating tasks.
+//!
+//! A recurring task is one instance of a series. Completing it (or letting
+//! its date pass) generates the next instance, linked to the previous one by
+//! `previous_uuid` and to the first one by `series_uuid`.
+
+use crate::models::{Recurrence, RecurrenceEnd, Status, Task};
+use chrono::{Datelike, Duration, Months, NaiveDate, Utc};
+use uuid::Uuid;
+
+impl Recurrence {
+    /// First occurrence strictly after `date`.
+    pub fn next_after(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Recurrence::Daily => date.succ_opt(),
+            Recurrence::Weekly { days } if days.is_empty() => {
+                date.checked_add_signed(Duration::days(7))
+            }
+            Recurrence::Weekly { days } => (1..=7)
+                .filter_map(|offset| date.checked_add_signed(Duration::days(offset)))
+                .find(|d| days.contains(&(d.weekday().num_days_from_sunday() as u8))),
+            Recurrence::Monthly { day } => month_day_after(date, 1, *day),
+            Recurrence::Yearly { month, day } => {
+                let month = (*month as u32).clamp(1, 12);
+                let this_year = clamped_date(date.year(), month, *day as u32)?;
+                if this_year > date {
+                    return Some(this_year);
+                }
+                clamped_date(date.year() + 1, month, *day as u32)
+            }
+            Recurrence::Every { days } => {
+                date.checked_add_signed(Duration::days((*days).max(1) as i64))
+            }
+            Recurrence::EveryMonths { months, day } => month_day_after(date, *months, *day),
+        }
+    }
+
+    /// Format as a rule accepted by [`Recurrence::parse`].
+    pub fn rule(&self) -> String {
+        match self {
+            Recurrence::Daily => "daily".to_string(),
+            Recurrence::Weekly { days } => {
+                let names: Vec<&str> =
+                    days.iter().filter_map(|d| WEEKDAYS.get(*d as usize).copied()).collect();
+                format!("weekly:{}", names.join(","))
+            }
+            Recurrence::Monthly { day } => format!("monthly:{}", day),
+            Recurrence::Yearly { month, day } => format!("yearly:{}-{}", month, day),
+            Recurrence::Every { days } => format!("every:{}", days),
+            Recurrence::EveryMonths { months, day } => format!("monthly:{}/{}", day, months),
+        }
+    }
+
+    /// Parse a rule such as `daily`, `weekly:mon,fri`, `monthly:31`,
+    /// `monthly:15/3` (every third month), `yearly:2-29` or `every:10`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (kind, arg) = s.trim().split_once(':').unwrap_or((s.trim(), ""));
+        match kind.to_lowercase().as_str() {
+            "daily" => Some(Recurrence::Daily),
+            "weekly" => {
+                let days = arg
+                    .split(',')
+                    .filter(|d| !d.trim().is_empty())
+                    .map(parse_weekday)
+                    .collect::<Option<Vec<u8>>>()?;
+                Some(Recurrence::Weekly { days })
+            }
+            "monthly" => {
+                let (day, months) = arg.split_once('/').unwrap_or((arg, "1"));
+                let day = day.parse().ok().filter(|d| (1..=31).contains(d))?;
+                match months.parse().ok().filter(|m| *m > 0)? {
+                    1 => Some(Recurrence::Monthly { day }),
+                    months => Some(Recurrence::EveryMonths { months, day }),
+                }
+            }
+            "yearly" => {
+                let (month, day) = arg.split_once('-')?;
+                let month = month.parse().ok().filter(|m| (1..=12).contains(m))?;
+                let day = day.parse().ok().filter(|d| (1..=31).contains(d))?;
+                Some(Recurrence::Yearly { month, day })
+            }
+            "every" => {
+                let days = arg.trim_end_matches('d').parse().ok().filter(|d| *d > 0)?;
+                Some(Recurrence::Every { days })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl RecurrenceEnd {
+    /// Parse `after:N` or `until:YYYY-MM-DD`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().split_once(':')? {
+            ("after", count) => count.parse().ok().map(|count| RecurrenceEnd::After { count }),
+            ("until", date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .map(|date| RecurrenceEnd::On { date }),
+            _ => None,
+        }
+    }
+}
+
+/// Short weekday names, indexed by weekday number (0 = Sunday).
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// Weekday number (0 = Sunday) from a number or a name.
+fn parse_weekday(s: &str) -> Option<u8> {
+    let s = s.trim().to_lowercase();
+    if let Ok(n) = s.parse::<u8>() {
+        return (n < 7).then_some(n);
+    }
+    WEEKDAYS
+        .iter()
+        .position(|name| s.starts_with(name))
+        .map(|i| i as u8)
+}
+
+/// First date `day` strictly after `date`, in its month or `months` later.
+fn month_day_after(date: NaiveDate, months: u32, day: u8) -> Option<NaiveDate> {
+    let this_month = clamped_date(date.year(), date.month(), day as u32)?;
+    if this_month > date {
+        return Some(this_month);
+    }
+    let next = NaiveDate::from_ymd_opt(date.year(), date.month(), 1)?
+        .checked_add_months(Months::new(months.max(1)))?;
+    clamped_date(next.year(), next.month(), day as u32)
+}
+
+/// The date `day` of a month, clamped to the month's last day (Feb 30 -> Feb 28/29).
+fn clamped_date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let last = first.checked_add_months(Months::new(1))?.pred_opt()?.day();
+    NaiveDate::from_ymd_opt(year, month, day.clamp(1, last))
+}
+
+/// Date a task's occurrences are counted from: its due date, else its scheduled date.
+fn anchor(task: &Task) -> Option<NaiveDate> {
+    task.due_date.or(task.scheduled_date)
+}
+
+/// Move a task's dates to its next occurrence.
+///
+/// The scheduled date keeps its lead time before the due date. Undated
+/// tasks get a due date counted from `today`. Returns false, leaving the
+/// task untouched, when the series has ended.
+fn advance(task: &mut Task, today: NaiveDate) -> bool {
+    let Some(rule) = &task.recurrence else {
+        return false;
+    };
+    let from = anchor(task).unwrap_or(today);
+    let Some(next) = rule.next_after(from) else {
+        return false;
+    };
+
+    match task.recurrence_end {
+        Some(RecurrenceEnd::After { count }) if task.occurrence >= count => return false,
+        Some(RecurrenceEnd::On { date }) if next > date => return false,
+        _ => {}
+    }
+
+    if task.due_date.is_some() || task.scheduled_date.is_none() {
+        let shift = next - from;
+        task.due_date = Some(next);
+        task.scheduled_date = task.scheduled_date.map(|d| d + shift);
+    } else {
+        task.scheduled_date = Some(next);
+    }
+    task.occurrence += 1;
+    true
+}
+
+/// Advance until the task's date is today or later, counting missed periods.
+fn catch_up(task: &mut Task, today: NaiveDate) -> bool {
+    if !advance(task, today) {
+        return false;
+    }
+    while anchor(task).is_some_and(|d| d < today) {
+        let mut next = task.clone();
+        if !advance(&mut next, today) {
+            // The series ends before today; the last occurrence is still owed
+            break;
+        }
+        *task = next;
+    }
+    true
+}
+
+/// Build the instance that follows `task` in its series.
+///
+/// Returns `None` for non-recurring tasks and when the series has ended.
+pub fn next_instance(task: &Task, today: NaiveDate) -> Option<Task> {
+    let mut next = task.clone();
+    if !catch_up(&mut next, today) {
+        return None;
+    }
+
+    let now = Utc::now();
+    next.id = 0;
+    next.uuid = Uuid::new_v4().to_string();
+    next.status = Status::Todo;
+    next.actual_mins = None;
+    next.completed_at = None;
+    next.created_at = now;
+    next.updated_at = now;
+    next.series_uuid = Some(task.series_uuid.clone().unwrap_or_else(|| task.uuid.clone()));
+    next.previous_uuid = Some(task.uuid.clone());
+    Some(next)
+}
+
+/// Skip the current occurrence, moving the task to the next one in place.
+///
+/// Returns false, leaving the task untouched, when there is no next occurrence.
+pub fn skip_occurrence(task: &mut Task, today: NaiveDate) -> bool {
+    let mut skipped = task.clone();
+    if !catch_up(&mut skipped, today) {
+        return false;
+    }
+    *task = skipped;
+    true
+}
+
+/// Whether an open recurring task's period has passed, so its successor is due.
+pub fn is_rolled_over(task: &Task, today: NaiveDate) -> bool {
+    task.is_recurring() && !task.status.is_complete() && anchor(task).is_some_and(|d| d < today)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn recurring(rule: Recurrence, due: NaiveDate) -> Task {
+        let mut task = Task::new("Pay rent");
+        task.recurrence = Some(rule);
+        task.due_date = Some(due);
+        task
+    }
+
+    #[test]
+    fn test_next_after() {
+        assert_eq!(Recurrence::Daily.next_after(date(2024, 12, 31)), Some(date(2025, 1, 1)));
+        let every = Recurrence::Every { days: 10 };
+        assert_eq!(every.next_after(date(2024, 2, 25)), Some(date(2024, 3, 6)));
+
+        // Mon/Fri from a Friday
+        let weekly = Recurrence::Weekly { days: vec![1, 5] };
+        assert_eq!(weekly.next_after(date(2024, 3, 8)), Some(date(2024, 3, 11)));
+        assert_eq!(weekly.next_after(date(2024, 3, 11)), Some(date(2024, 3, 15)));
+    }
+
+    #[test]
+    fn test_month_end_and_leap_years() {
+        let monthly = Recurrence::Monthly { day: 31 };
+        assert_eq!(monthly.next_after(date(2024, 1, 31)), Some(date(2024, 2, 29)));
+        assert_eq!(monthly.next_after(date(2023, 1, 31)), Some(date(2023, 2, 28)));
+        // Clamping does not drift: the series returns to the 31st
+        assert_eq!(monthly.next_after(date(2024, 2, 29)), Some(date(2024, 3, 31)));
+        assert_eq!(monthly.next_after(date(2024, 3, 31)), Some(date(2024, 4, 30)));
+
+        let quarterly = Recurrence::EveryMonths { months: 3, day: 31 };
+        assert_eq!(quarterly.next_after(date(2024, 1, 31)), Some(date(2024, 4, 30)));
+        assert_eq!(quarterly.next_after(date(2024, 4, 30)), Some(date(2024, 7, 31)));
+
+        let leap_day = Recurrence::Yearly { month: 2, day: 29 };
+        assert_eq!(leap_day.next_after(date(2024, 2, 29)), Some(date(2025, 2, 28)));
+        assert_eq!(leap_day.next_after(date(2027, 3, 1)), Some(date(2028, 2, 29)));
+    }
+
+    #[test]
+    fn test_next_instance_links_chain() {
+        let mut task = recurring(Recurrence::Monthly { day: 31 }, date(2024, 1, 31));
+        task.scheduled_date = Some(date(2024, 1, 28));
+        task.status = Status::Done;
+
+        let next = next_instance(&task, date(2024, 1, 31)).unwrap();
+        assert_eq!(next.due_date, Some(date(2024, 2, 29)));
+        assert_eq!(next.scheduled_date, Some(date(2024, 2, 26)));
+        assert_eq!(next.status, Status::Todo);
+        assert_eq!(next.occurrence, 2);
+        assert_eq!(next.previous_uuid.as_deref(), Some(task.uuid.as_str()));
+        assert_eq!(next.series_uuid.as_deref(), Some(task.uuid.as_str()));
+        assert_ne!(next.uuid, task.uuid);
+
+        let third = next_instance(&next, date(2024, 2, 29)).unwrap();
+        assert_eq!(third.series_uuid.as_deref(), Some(task.uuid.as_str()));
+        assert_eq!(third.previous_uuid.as_deref(), Some(next.uuid.as_str()));
+    }
+
+    #[test]
+    fn test_catch_up_after_rollover() {
+        let task = recurring(Recurrence::Daily, date(2024, 3, 1));
+        assert!(is_rolled_over(&task, date(2024, 3, 5)));
+
+        let next = next_instance(&task, date(2024, 3, 5)).unwrap();
+        assert_eq!(next.due_date, Some(date(2024, 3, 5)));
+        assert_eq!(next.occurrence, 5);
+        assert!(!is_rolled_over(&next, date(2024, 3, 5)));
+    }
+
+    #[test]
+    fn test_end_conditions() {
+        let mut task = recurring(Recurrence::Daily, date(2024, 3, 1));
+        task.recurrence_end = Some(RecurrenceEnd::After { count: 2 });
+        let second = next_instance(&task, date(2024, 3, 1)).unwrap();
+        assert!(next_instance(&second, date(2024, 3, 2)).is_none());
+
+        task.recurrence_end = Some(RecurrenceEnd::On { date: date(2024, 3, 2) });
+        let second = next_instance(&task, date(2024, 3, 1)).unwrap();
+        assert_eq!(second.due_date, Some(date(2024, 3, 2)));
+        assert!(next_instance(&second, date(2024, 3, 2)).is_none());
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!(Recurrence::parse("daily"), Some(Recurrence::Daily));
+        assert_eq!(
+            Recurrence::parse("weekly:mon,fri"),
+            Some(Recurrence::Weekly { days: vec![1, 5] })
+        );
+        assert_eq!(
+            Recurrence::parse("yearly:2-29"),
+            Some(Recurrence::Yearly { month: 2, day: 29 })
+        );
+        assert_eq!(Recurrence::parse("every:10d"), Some(Recurrence::Every { days: 10 }));
+        assert_eq!(Recurrence::parse("monthly:32"), None);
+        assert_eq!(Recurrence::parse("monthly:5/1"), Some(Recurrence::Monthly { day: 5 }));
+        assert_eq!(Recurrence::parse("monthly:5/0"), None);
+        for rule in ["daily", "weekly:mon,fri", "monthly:31", "monthly:15/3", "yearly:2-29", "every:10"] {
+            assert_eq!(Recurrence::parse(rule).unwrap().rule(), rule);
+        }
+        assert_eq!(RecurrenceEnd::parse("after:3"), Some(RecurrenceEnd::After { count: 3 }));
+        assert_eq!(
+            RecurrenceEnd::parse("until:2025-06-30"),
+            Some(RecurrenceEnd::On { date: date(2025, 6, 30) })
+        );
+    }
+
+    #[test]
+    fn test_skip_occurrence() {
+        let mut task = recurring(Recurrence::Weekly { days: vec![1] }, date(2024, 3, 4));
+        let uuid = task.uuid.clone();
+        assert!(skip_occurrence(&mut task, date(2024, 3, 1)));
+        assert_eq!(task.due_date, Some(date(2024, 3, 11)));
+        assert_eq!(task.uuid, uuid);
+
+        task.recurrence_end = Some(RecurrenceEnd::On { date: date(2024, 3, 15) });
+        assert!(!skip_occurrence(&mut task, date(2024, 3, 1)));
+        assert_eq!(task.due_date, Some(date(2024, 3, 11)));
+    }
+}
@@ -23,9 +23,10 @@ pub fn draw(f: &mut Frame, app: &App) {
     draw_tabs(f, app, chunks[0]);
 
     match app.view {
-        View::List | View::Today => draw_list_view(f, app, chunks[1]),
+        View::List | View::Today | View::Saved(_) => draw_list_view(f, app, chunks[1]),
         View::Board => draw_board_view(f, app, chunks[1]),
         View::Projects => draw_projects_view(f, app, chunks[1]),
+        View::Dependencies => draw_dependencies_view(f, app, chunks[1]),
     }
 
     draw_status_bar(f, app, chunks[2]);
@@ -37,15 +38,28 @@ pub fn draw(f: &mut Frame, app: &App) {
     if app.show_help {
         draw_help(f);
     }
+
+    if let Some(prompt) = &app.prompt {
+        draw_plugin_prompt(f, prompt);
+    }
 }
 
 fn draw_tabs(f: &mut Frame, app: &App, area: Rect) {
-    let titles = vec!["[1] List", "[2] Board", "[3] Projects", "[4] Today"];
+    let mut titles: Vec<String> = ["[1] List", "[2] Board", "[3] Projects", "[4] Today", "[5] Deps"]
+        .iter()
+        .map(|t| t.to_string())
+        .collect();
+    titles.extend(app.config.views.iter().enumerate().map(|(i, view)| match i {
+        0..=3 => format!("[{}] {}", i + 6, view.name),
+        _ => format!("[v] {}", view.name),
+    }));
     let selected = match app.view {
         View::List => 0,
         View::Board => 1,
         View::Projects => 2,
         View::Today => 3,
+        View::Dependencies => 4,
+        View::Saved(i) => 5 + i,
     };
 
     let tabs = Tabs::new(titles)
@@ -87,14 +101,28 @@ fn draw_list_view(f: &mut Frame, app: &App, area: Rect) {
                 .map(|n| format!(" @{}", n))
                 .unwrap_or_default();
 
-            let content = format!("{} {} {}{}{}", status_icon, priority_icon, task.title, due, project);
+            let repeat = if task.is_recurring() { " \u{21bb}" } else { "" };
+            let waiting = if app.blocked.contains(&task.id) { " \u{29d6}" } else { "" };
+            let marked = if app.link_from == Some(task.id) { " [blocker]" } else { "" };
+
+            let content = format!(
+                "{} {} {}{}{}{}{}{}",
+                status_icon, priority_icon, task.title, repeat, waiting, due, project, marked
+            );
             ListItem::new(content).style(style)
         })
         .collect();
 
-    let title = match app.view {
-        View::Today => " Today's Tasks ",
-        _ => " Tasks ",
+    let title = match (app.view, &app.filter.query) {
+        (View::Today, _) => " Today's Tasks ".to_string(),
+        (View::Saved(i), _) => app
+            .config
+            .views
+            .get(i)
+            .map(|v| format!(" {} ", v.name))
+            .unwrap_or_else(|| " Tasks ".to_string()),
+        (_, Some(query)) => format!(" Tasks: {} ", query.source()),
+        _ => " Tasks ".to_string(),
     };
 
     let list = List::new(items)
@@ -162,6 +190,74 @@ fn draw_projects_view(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(list, area);
 }
 
+fn draw_dependencies_view(f: &mut Frame, app: &App, area: Rect) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+
+    draw_list_view(f, app, columns[0]);
+
+    let heading = Style::default().add_modifier(Modifier::BOLD);
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines = Vec::new();
+
+    if let Some(task) = app.tasks.get(app.selected_index) {
+        lines.push(Line::from(Span::styled(task.title.as_str(), heading)));
+        let blockers = app.open_blockers(task.id);
+        if blockers.is_empty() {
+            lines.push(Line::from(Span::styled("  Not blocked", dim)));
+        }
+        for blocker in blockers {
+            lines.push(Line::from(format!("  blocked by {} {}", blocker.status.symbol(), blocker.title)));
+        }
+        for id in app.dependencies.blocking(task.id) {
+            if app.open_tasks.iter().any(|t| t.id == id) {
+                lines.push(Line::from(format!("  blocks {}", app.task_title(id))));
+            }
+        }
+        lines.push(Line::from(""));
+    }
+
+    lines.push(Line::from(Span::styled("Critical paths", heading)));
+    for (project, path) in app.critical_paths() {
+        let name = app.get_project_name(project).unwrap_or("No project");
+        let mut summary = format!(
+            "{}: {} over {} tasks",
+            name,
+            format_mins(path.total_mins),
+            path.tasks.len()
+        );
+        if path.unestimated > 0 {
+            summary.push_str(&format!(" ({} unestimated)", path.unestimated));
+        }
+        lines.push(Line::from(Span::styled(summary, Style::default().fg(Color::Cyan))));
+        for (i, id) in path.tasks.iter().enumerate() {
+            let estimate = app
+                .open_tasks
+                .iter()
+                .find(|t| t.id == *id)
+                .and_then(|t| t.estimated_mins)
+                .map(format_mins)
+                .unwrap_or_else(|| "?".to_string());
+            lines.push(Line::from(format!("  {}. {} ({})", i + 1, app.task_title(*id), estimate)));
+        }
+    }
+
+    let details = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" Dependencies "));
+    f.render_widget(details, columns[1]);
+}
+
+/// Minutes as `1h 30m`.
+fn format_mins(mins: u32) -> String {
+    match (mins / 60, mins % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
 fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -197,6 +293,7 @@ fn draw_input_dialog(f: &mut Frame, app: &App) {
         InputField::TaskDescription => " Edit Description ",
         InputField::ProjectName => " New Project ",
         InputField::Search => " Search ",
+        InputField::Query => " Filter (e.g. project:work +urgent due.before:eow) ",
         InputField::None => " Input ",
     };
 
@@ -207,8 +304,25 @@ fn draw_input_dialog(f: &mut Frame, app: &App) {
     f.render_widget(input, area);
 }
 
+fn draw_plugin_prompt(f: &mut Frame, prompt: &tui_plugins::PluginPrompt) {
+    let area = centered_rect(60, 20, f.area());
+    f.render_widget(Clear, area);
+
+    let mut lines = Vec::new();
+    if let Some(message) = &prompt.message {
+        lines.push(Line::from(Span::styled(message.as_str(), Style::default().fg(Color::Gray))));
+    }
+    lines.push(Line::from(prompt.display_input()));
+
+    let input = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!(" {} ", prompt.title)))
+        .style(Style::default().fg(Color::Yellow));
+
+    f.render_widget(input, area);
+}
+
 fn draw_help(f: &mut Frame) {
-    let area = centered_rect(60, 70, f.area());
+    let area = centered_rect(60, 80, f.area());
     f.render_widget(Clear, area);
 
     let help_text = vec![
@@ -216,6 +330,9 @@ fn draw_help(f: &mut Frame) {
         Line::from("  j/k or arrows  Move selection"),
         Line::from("  g/G            Go to first/last"),
         Line::from("  1-4            Switch views"),
+        Line::from("  5              Dependencies and critical paths"),
+        Line::from("  6-9            Saved views"),
+        Line::from("  v              Next saved view"),
         Line::from(""),
         Line::from(Span::styled("Actions", Style::default().add_modifier(Modifier::BOLD))),
         Line::from("  a              Add task"),
@@ -225,10 +342,14 @@ fn draw_help(f: &mut Frame) {
         Line::from("  d              Delete task"),
         Line::from("  p              Cycle priority"),
         Line::from("  P              Assign project"),
+        Line::from("  s              Skip recurring occurrence"),
+        Line::from("  L              Mark blocker / link (twice)"),
         Line::from(""),
         Line::from(Span::styled("Filters", Style::default().add_modifier(Modifier::BOLD))),
         Line::from("  /              Search"),
+        Line::from("  f              Filter expression"),
         Line::from("  c              Toggle completed"),
+        Line::from("  b              Toggle blocked"),
         Line::from("  Esc            Clear filters"),
         Line::from(""),
         Line::from("  q              Quit"),
@@ -1,10 +1,12 @@
 //! Application state and logic.
 
 use crate::config::Config;
+use crate::cron::CronExpr;
 use crate::db::Database;
-use crate::models::{RunStatus, Schedule, ScheduledTask, TaskRun};
-use chrono::Utc;
+use crate::executor::{self, Executor};
+use crate::models::{Notification, Schedule, ScheduledTask, TaskRun, Trigger};
 use crossterm::event::{KeyCode, KeyEvent};
+use std::fs::File;
 
 pub struct App {
     pub db: Database,
@@ -13,6 +15,10 @@ pub struct App {
     pub selected_index: usize,
     pub current_task: Option<ScheduledTask>,
     pub task_runs: Vec<TaskRun>,
+    pub run_index: usize,
+    pub executor: Executor,
+    /// Held while this process is the one starting scheduled tasks
+    pub lock: Option<File>,
     pub mode: Mode,
     pub pane: Pane,
     pub input_buffer: String,
@@ -21,6 +27,8 @@ pub struct App {
     pub new_task: NewTaskBuilder,
     pub message: Option<String>,
     pub show_help: bool,
+    /// The notification log, while it is shown
+    pub notifications: Option<Vec<Notification>>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -31,6 +39,7 @@ pub struct NewTaskBuilder {
     pub interval_mins: u32,
     pub daily_hour: u32,
     pub daily_minute: u32,
+    pub cron: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -62,6 +71,13 @@ impl App {
             std::fs::create_dir_all(parent)?;
         }
         let db = Database::open(&db_path)?;
+        let lock = Config::lock_path().and_then(|p| executor::lock(&p).ok().flatten());
+        let executor = Executor::new(&config);
+        let mut message = None;
+        if lock.is_some() {
+            db.prepare_scheduler()?;
+            message = executor.catch_up(&db).pop();
+        }
         let tasks = db.list_tasks()?;
 
         Ok(Self {
@@ -71,14 +87,18 @@ impl App {
             selected_index: 0,
             current_task: None,
             task_runs: Vec::new(),
+            run_index: 0,
+            executor,
+            lock,
             mode: Mode::Normal,
             pane: Pane::Tasks,
             input_buffer: String::new(),
             input_mode: InputMode::None,
             input_step: 0,
             new_task: NewTaskBuilder::default(),
-            message: None,
+            message,
             show_help: false,
+            notifications: None,
         })
     }
 
@@ -93,41 +113,32 @@ impl App {
         }
     }
 
+    /// Start due tasks and pick up finished runs. Without the scheduler
+    /// lock only runs started from here are tracked.
     pub fn check_scheduled_tasks(&mut self) {
-        if let Ok(due_tasks) = self.db.get_due_tasks() {
-            for mut task in due_tasks {
-                self.run_task(&mut task);
-            }
+        let events = if self.lock.is_some() {
+            self.executor.tick(&self.db)
+        } else {
+            self.executor.poll(&self.db)
+        };
+        if let Some(event) = events.last() {
+            self.message = Some(event.clone());
+            self.refresh();
+            self.load_runs();
         }
     }
 
-    fn run_task(&mut self, task: &mut ScheduledTask) {
-        let run = TaskRun {
-            id: 0,
-            task_id: task.id,
-            started_at: Utc::now(),
-            finished_at: Some(Utc::now()),
-            status: RunStatus::Success,
-            exit_code: Some(0),
-            output: format!("Simulated run of: {}", task.command),
-        };
-
-        let _ = self.db.insert_run(&run);
-
-        task.last_run = Some(Utc::now());
-        task.run_count += 1;
-        task.update_next_run();
-        let _ = self.db.update_task(task);
-
-        self.message = Some(format!("Ran: {}", task.name));
-        self.refresh();
+    /// Stop running tasks before exiting.
+    pub fn shutdown(&mut self) {
+        self.executor.shutdown(&self.db);
     }
 
     pub fn handle_key(&mut self, key: KeyEvent) {
         self.message = None;
 
-        if self.show_help {
+        if self.show_help || self.notifications.is_some() {
             self.show_help = false;
+            self.notifications = None;
             return;
         }
 
@@ -137,6 +148,12 @@ impl App {
         }
 
         match key.code {
+            KeyCode::Char('j') | KeyCode::Down if self.pane == Pane::History => {
+                self.move_run_selection(1)
+            }
+            KeyCode::Char('k') | KeyCode::Up if self.pane == Pane::History => {
+                self.move_run_selection(-1)
+            }
             KeyCode::Char('j') | KeyCode::Down => self.move_selection(1),
             KeyCode::Char('k') | KeyCode::Up => self.move_selection(-1),
             KeyCode::Tab => {
@@ -150,6 +167,10 @@ impl App {
             KeyCode::Char('d') => self.delete_selected(),
             KeyCode::Char('e') => self.toggle_enabled(),
             KeyCode::Char('r') => self.run_selected_now(),
+            KeyCode::Char('s') => self.stop_selected(),
+            KeyCode::Char('L') => {
+                self.notifications = Some(self.db.list_notifications(100).unwrap_or_default())
+            }
             KeyCode::Char('?') => self.show_help = true,
             _ => {}
         }
@@ -198,7 +219,15 @@ impl App {
                 self.input_mode = InputMode::ScheduleValue;
             }
             InputMode::ScheduleValue => {
-                self.new_task.interval_mins = self.input_buffer.parse().unwrap_or(60);
+                if self.new_task.schedule_type == 3 {
+                    if let Err(e) = CronExpr::parse(&self.input_buffer) {
+                        self.message = Some(e.to_string());
+                        return;
+                    }
+                    self.new_task.cron = self.input_buffer.trim().to_string();
+                } else {
+                    self.new_task.interval_mins = self.input_buffer.parse().unwrap_or(60);
+                }
                 self.create_task();
                 self.input_mode = InputMode::None;
                 self.mode = Mode::Normal;
@@ -211,6 +240,7 @@ impl App {
     fn create_task(&mut self) {
         let schedule = match self.new_task.schedule_type {
             2 => Schedule::Daily { hour: 9, minute: 0 },
+            3 => Schedule::Cron(self.new_task.cron.clone()),
             _ => Schedule::Interval { minutes: self.new_task.interval_mins },
         };
 
@@ -228,10 +258,25 @@ impl App {
         self.selected_index = new_idx.clamp(0, len as i32 - 1) as usize;
     }
 
+    fn move_run_selection(&mut self, delta: i32) {
+        let len = self.task_runs.len();
+        if len == 0 { return; }
+        let new_idx = self.run_index as i32 + delta;
+        self.run_index = new_idx.clamp(0, len as i32 - 1) as usize;
+    }
+
     fn select_task(&mut self) {
         if let Some(task) = self.tasks.get(self.selected_index) {
             self.current_task = Some(task.clone());
+            self.run_index = 0;
+            self.load_runs();
+        }
+    }
+
+    fn load_runs(&mut self) {
+        if let Some(task) = &self.current_task {
             self.task_runs = self.db.get_task_runs(task.id, 20).unwrap_or_default();
+            self.run_index = self.run_index.min(self.task_runs.len().saturating_sub(1));
         }
     }
 
@@ -254,9 +299,37 @@ impl App {
         }
     }
 
+    /// Run the selected task now without moving its schedule.
     fn run_selected_now(&mut self) {
-        if let Some(mut task) = self.tasks.get(self.selected_index).cloned() {
-            self.run_task(&mut task);
+        let Some(mut task) = self.tasks.get(self.selected_index).cloned() else {
+            return;
+        };
+        if self.executor.is_running(task.id) {
+            self.message = Some(format!("Already running: {}", task.name));
+            return;
+        }
+        if !self.executor.has_capacity() {
+            self.message = Some(format!(
+                "All {} run slots busy",
+                self.config.scheduler.max_concurrent
+            ));
+            return;
+        }
+        self.message = Some(match self.executor.start(&self.db, &mut task, Trigger::Manual) {
+            Ok(()) => format!("Started: {}", task.name),
+            Err(e) => format!("Failed to start {}: {}", task.name, e),
+        });
+        self.refresh();
+        self.load_runs();
+    }
+
+    fn stop_selected(&mut self) {
+        if let Some(task) = self.tasks.get(self.selected_index) {
+            self.message = Some(if self.executor.cancel(task.id) {
+                format!("Stopping: {}", task.name)
+            } else {
+                format!("Not running here: {}", task.name)
+            });
         }
     }
 }
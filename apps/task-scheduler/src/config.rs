@@ -7,6 +7,8 @@ use std::path::PathBuf;
 pub struct Config {
     #[serde(default)]
     pub scheduler: SchedulerConfig,
+    #[serde(default)]
+    pub notifications: NotificationConfig,
 }
 
 impl Config {
@@ -26,24 +28,66 @@ impl Config {
         directories::ProjectDirs::from("", "", "task-scheduler")
             .map(|d| d.data_dir().join("scheduler.db"))
     }
+
+    pub fn lock_path() -> Option<PathBuf> {
+        directories::ProjectDirs::from("", "", "task-scheduler")
+            .map(|d| d.data_dir().join("scheduler.lock"))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SchedulerConfig {
+    /// Runs still going after this long are stopped; 0 disables
     #[serde(default = "default_timeout")]
     pub default_timeout_secs: u64,
     #[serde(default = "default_history")]
     pub keep_history_days: u32,
+    /// Tasks run at the same time; further due tasks wait for a free slot
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: usize,
+    /// Shell that runs each command as `<shell> -c <command>`
+    #[serde(default = "default_shell")]
+    pub shell: String,
+    /// Output kept per run, from stdout and stderr together
+    #[serde(default = "default_max_output")]
+    pub max_output_bytes: usize,
 }
 
 fn default_timeout() -> u64 { 300 }
 fn default_history() -> u32 { 30 }
+fn default_max_concurrent() -> usize { 4 }
+fn default_shell() -> String { "/bin/sh".to_string() }
+fn default_max_output() -> usize { 64 * 1024 }
 
 impl Default for SchedulerConfig {
     fn default() -> Self {
         Self {
             default_timeout_secs: 300,
             keep_history_days: 30,
+            max_concurrent: default_max_concurrent(),
+            shell: default_shell(),
+            max_output_bytes: default_max_output(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    /// Record failures, retries and missed runs in the notification log
+    #[serde(default = "default_log")]
+    pub log: bool,
+    /// Run for every task that fails for good, before the task's own hook
+    #[serde(default)]
+    pub on_failure: Option<String>,
+}
+
+fn default_log() -> bool { true }
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            log: default_log(),
+            on_failure: None,
         }
     }
 }
@@ -0,0 +1,698 @@
+//! Cron expressions.
+//!
+//! ```text
+//! CRON_TZ=Europe/Berlin 30 2 * * MON-FRI
+//! ```
+//!
+//! Five fields (minute, hour, day of month, month, day of week) or six with
+//! a leading seconds field. Each field is a comma-separated list of `*`,
+//! values, ranges `a-b` and steps `*/n`, `a/n` or `a-b/n`; months and
+//! weekdays also take names (`JAN`, `MON`), and weekday 7 is Sunday.
+//!
+//! - day of month: `L` (last day), `L-n` (n days before it), `nW` (weekday
+//!   nearest day n, within the month) and `LW` (last weekday)
+//! - day of week: `nL` (last such weekday of the month) and `n#k` (k-th)
+//! - `@yearly`, `@annually`, `@monthly`, `@weekly`, `@daily`, `@midnight`
+//!   and `@hourly` stand for the usual five-field expressions
+//!
+//! As in Vixie cron, when both day fields are restricted a day matches if
+//! either does; a field starting with `*` or `?` counts as unrestricted.
+//!
+//! Times are local unless the expression starts with `CRON_TZ=<zone>` or
+//! `TZ=<zone>`. Times skipped by a DST change run when the gap ends; times
+//! repeated by one run once.
+
+use chrono::offset::LocalResult;
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike,
+    Utc,
+};
+use chrono_tz::Tz;
+use std::fmt;
+
+/// Error from parsing a cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError(pub String);
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+type ParseResult<T> = Result<T, ParseError>;
+
+fn error<T>(message: impl Into<String>) -> ParseResult<T> {
+    Err(ParseError(message.into()))
+}
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// How far ahead to look before giving up on an expression that never
+/// matches, such as `0 0 30 2 *`.
+const SEARCH_YEARS: i32 = 10;
+
+/// A parsed cron expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpr {
+    source: String,
+    zone: Option<Tz>,
+    has_seconds: bool,
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    day_specials: Vec<DaySpecial>,
+    weekday_specials: Vec<WeekdaySpecial>,
+    days_star: bool,
+    weekdays_star: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DaySpecial {
+    /// `L` or `L-n`
+    Last(u32),
+    /// `nW`
+    NearestWeekday(u32),
+    /// `LW`
+    LastWeekday,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WeekdaySpecial {
+    /// `nL`, weekday counted from Sunday = 0
+    Last(u32),
+    /// `n#k`
+    Nth(u32, u32),
+}
+
+impl CronExpr {
+    pub fn parse(expr: &str) -> ParseResult<Self> {
+        let source = expr.trim().to_string();
+        let mut rest = source.as_str();
+
+        let mut zone = None;
+        if let Some(spec) = rest
+            .strip_prefix("CRON_TZ=")
+            .or_else(|| rest.strip_prefix("TZ="))
+        {
+            let (name, tail) = spec.split_once(char::is_whitespace).unwrap_or((spec, ""));
+            zone = Some(
+                name.parse::<Tz>()
+                    .map_err(|_| ParseError(format!("unknown time zone: {}", name)))?,
+            );
+            rest = tail.trim_start();
+        }
+
+        let expanded = match rest.to_ascii_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *".to_string(),
+            "@monthly" => "0 0 1 * *".to_string(),
+            "@weekly" => "0 0 * * 0".to_string(),
+            "@daily" | "@midnight" => "0 0 * * *".to_string(),
+            "@hourly" => "0 * * * *".to_string(),
+            "@reboot" => return error("@reboot is not supported"),
+            other if other.starts_with('@') => return error(format!("unknown macro: {}", rest)),
+            _ => rest.to_string(),
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let (has_seconds, fields) = match fields.len() {
+            5 => (false, [&["0"], &fields[..]].concat()),
+            6 => (true, fields),
+            0 => return error("empty cron expression"),
+            n => return error(format!("expected 5 or 6 fields, found {}", n)),
+        };
+
+        let seconds = parse_field(fields[0], 0, 59, &[]).map_err(|e| in_field("second", e))?;
+        let minutes = parse_field(fields[1], 0, 59, &[]).map_err(|e| in_field("minute", e))?;
+        let hours = parse_field(fields[2], 0, 23, &[]).map_err(|e| in_field("hour", e))?;
+        let (days, day_specials) = parse_days(fields[3]).map_err(|e| in_field("day", e))?;
+        let months = parse_field(fields[4], 1, 12, &MONTHS).map_err(|e| in_field("month", e))?;
+        let (weekdays, weekday_specials) =
+            parse_weekdays(fields[5]).map_err(|e| in_field("weekday", e))?;
+
+        Ok(Self {
+            zone,
+            has_seconds,
+            seconds,
+            minutes,
+            hours,
+            days,
+            months,
+            weekdays,
+            day_specials,
+            weekday_specials,
+            days_star: fields[3].starts_with(['*', '?']),
+            weekdays_star: fields[5].starts_with(['*', '?']),
+            source,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The expression without its `CRON_TZ=` prefix.
+    pub fn fields(&self) -> &str {
+        match self.zone {
+            Some(_) => self
+                .source
+                .split_once(char::is_whitespace)
+                .map_or("", |(_, rest)| rest.trim_start()),
+            None => &self.source,
+        }
+    }
+
+    pub fn zone(&self) -> Option<Tz> {
+        self.zone
+    }
+
+    /// Equivalent systemd `OnCalendar=` values. Cron matches either day
+    /// field when both are restricted, systemd both, so that case takes
+    /// two values.
+    pub fn to_calendar(&self) -> ParseResult<Vec<String>> {
+        if !self.weekday_specials.is_empty()
+            || self
+                .day_specials
+                .iter()
+                .any(|s| !matches!(s, DaySpecial::Last(_)))
+        {
+            return error("W, LW, nL and # have no OnCalendar equivalent");
+        }
+
+        let mut days = format!("-{}", calendar_values(self.days, 1, 31));
+        if let Some(special) = self.day_specials.first() {
+            let DaySpecial::Last(offset) = special else {
+                unreachable!()
+            };
+            if self.days != 0 || self.day_specials.len() > 1 {
+                return error("L can only be used alone in OnCalendar");
+            }
+            days = format!("~{:02}", offset + 1);
+        }
+        let months = calendar_values(self.months, 1, 12);
+        let time = format!(
+            "{}:{}:{}",
+            calendar_values(self.hours, 0, 23),
+            calendar_values(self.minutes, 0, 59),
+            calendar_values(self.seconds, 0, 59),
+        );
+        let zone = self
+            .zone
+            .map(|z| format!(" {}", z.name()))
+            .unwrap_or_default();
+        let weekdays = calendar_weekdays(self.weekdays);
+
+        let line = |weekdays: &str, days: &str| {
+            let prefix = if weekdays == "*" {
+                String::new()
+            } else {
+                format!("{} ", weekdays)
+            };
+            format!("{}*-{}{} {}{}", prefix, months, days, time, zone)
+        };
+        Ok(if self.days_star || self.weekdays_star || weekdays == "*" {
+            vec![line(&weekdays, &days)]
+        } else {
+            vec![line("*", &days), line(&weekdays, "-*")]
+        })
+    }
+
+    /// The first matching time strictly after `from`.
+    pub fn next_after(&self, from: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match &self.zone {
+            Some(tz) => self.next_in(from, tz),
+            None => self.next_in(from, &Local),
+        }
+    }
+
+    /// The next `count` matching times after `from`.
+    pub fn upcoming(&self, from: &DateTime<Utc>, count: usize) -> Vec<DateTime<Utc>> {
+        let mut times = Vec::with_capacity(count);
+        let mut from = *from;
+        while times.len() < count {
+            let Some(next) = self.next_after(&from) else {
+                break;
+            };
+            times.push(next);
+            from = next;
+        }
+        times
+    }
+
+    fn next_in<Z: TimeZone>(&self, from: &DateTime<Utc>, zone: &Z) -> Option<DateTime<Utc>> {
+        let local = from.with_timezone(zone).naive_local();
+        let mut t = local.with_nanosecond(0)?;
+        if !self.has_seconds {
+            t = t.with_second(0)?;
+        }
+        t += self.step();
+        let limit = t.year() + SEARCH_YEARS;
+
+        loop {
+            if t.year() > limit {
+                return None;
+            }
+            if !bit(self.months, t.month()) {
+                let (year, month) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_time(NaiveTime::MIN);
+                continue;
+            }
+            if !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_time(NaiveTime::MIN);
+                continue;
+            }
+            if !bit(self.hours, t.hour()) {
+                t = t.with_minute(0)?.with_second(0)? + Duration::hours(1);
+                continue;
+            }
+            if !bit(self.minutes, t.minute()) {
+                t = t.with_second(0)? + Duration::minutes(1);
+                continue;
+            }
+            if !bit(self.seconds, t.second()) {
+                t += Duration::seconds(1);
+                continue;
+            }
+
+            match zone.from_local_datetime(&t) {
+                LocalResult::Single(dt) => return Some(dt.with_timezone(&Utc)),
+                // A repeated hour: run on the first pass only.
+                LocalResult::Ambiguous(first, _) => {
+                    let first = first.with_timezone(&Utc);
+                    if first > *from {
+                        return Some(first);
+                    }
+                }
+                LocalResult::None => return end_of_gap(zone, t),
+            }
+            t += self.step();
+        }
+    }
+
+    fn step(&self) -> Duration {
+        if self.has_seconds {
+            Duration::seconds(1)
+        } else {
+            Duration::minutes(1)
+        }
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let days = self.days_match(date);
+        let weekdays = self.weekdays_match(date);
+        if self.days_star || self.weekdays_star {
+            days && weekdays
+        } else {
+            days || weekdays
+        }
+    }
+
+    fn days_match(&self, date: NaiveDate) -> bool {
+        let day = date.day();
+        if bit(self.days, day) {
+            return true;
+        }
+        let last = last_day(date);
+        self.day_specials.iter().any(|special| match *special {
+            DaySpecial::Last(offset) => offset < last && day == last - offset,
+            DaySpecial::NearestWeekday(target) => {
+                target <= last && day == nearest_weekday(date.with_day(target), last)
+            }
+            DaySpecial::LastWeekday => day == nearest_weekday(date.with_day(last), last),
+        })
+    }
+
+    fn weekdays_match(&self, date: NaiveDate) -> bool {
+        let weekday = date.weekday().num_days_from_sunday();
+        if bit(self.weekdays, weekday) {
+            return true;
+        }
+        self.weekday_specials.iter().any(|special| match *special {
+            WeekdaySpecial::Last(wd) => wd == weekday && date.day() + 7 > last_day(date),
+            WeekdaySpecial::Nth(wd, n) => wd == weekday && (date.day() - 1) / 7 + 1 == n,
+        })
+    }
+}
+
+impl fmt::Display for CronExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// A bit set in OnCalendar syntax: `*`, `a/n`, or a list of values and
+/// `a..b` ranges.
+fn calendar_values(set: u64, min: u32, max: u32) -> String {
+    let values: Vec<u32> = (min..=max).filter(|&n| bit(set, n)).collect();
+    if values.len() as u32 == max - min + 1 {
+        return "*".to_string();
+    }
+    if values.len() >= 3 {
+        let step = values[1] - values[0];
+        let repeats = (values[0]..=max).step_by(step as usize);
+        if step > 1 && repeats.clone().count() == values.len() && repeats.eq(values.iter().copied())
+        {
+            return format!("{:02}/{}", values[0], step);
+        }
+    }
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < values.len() {
+        let mut j = i;
+        while j + 1 < values.len() && values[j + 1] == values[j] + 1 {
+            j += 1;
+        }
+        if j >= i + 2 {
+            parts.push(format!("{:02}..{:02}", values[i], values[j]));
+        } else {
+            parts.extend(values[i..=j].iter().map(|v| format!("{:02}", v)));
+        }
+        i = j + 1;
+    }
+    parts.join(",")
+}
+
+/// Weekdays in OnCalendar syntax, Monday first.
+fn calendar_weekdays(set: u64) -> String {
+    const NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    // Bit 0 is Sunday; move it to the end
+    let week = (set >> 1 & 0x3f) | (set & 1) << 6;
+    if week & 0x7f == 0x7f {
+        return "*".to_string();
+    }
+    let values: Vec<usize> = (0..7).filter(|&n| week & 1 << n != 0).collect();
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < values.len() {
+        let mut j = i;
+        while j + 1 < values.len() && values[j + 1] == values[j] + 1 {
+            j += 1;
+        }
+        if j >= i + 2 {
+            parts.push(format!("{}..{}", NAMES[values[i]], NAMES[values[j]]));
+        } else {
+            parts.extend(values[i..=j].iter().map(|&v| NAMES[v].to_string()));
+        }
+        i = j + 1;
+    }
+    parts.join(",")
+}
+
+fn in_field(name: &str, e: ParseError) -> ParseError {
+    ParseError(format!("{} field: {}", name, e.0))
+}
+
+fn bit(set: u64, n: u32) -> bool {
+    set & (1 << n) != 0
+}
+
+fn last_day(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|d| d.pred_opt())
+        .map_or(28, |d| d.day())
+}
+
+/// Day of the weekday nearest `date` without leaving its month.
+fn nearest_weekday(date: Option<NaiveDate>, last: u32) -> u32 {
+    let Some(date) = date else {
+        return 0;
+    };
+    let day = date.day();
+    match date.weekday().num_days_from_sunday() {
+        6 if day == 1 => 3,
+        6 => day - 1,
+        0 if day == last => day - 2,
+        0 => day + 1,
+        _ => day,
+    }
+}
+
+/// The first valid local time after a DST gap containing `t`.
+fn end_of_gap<Z: TimeZone>(zone: &Z, t: NaiveDateTime) -> Option<DateTime<Utc>> {
+    let mut probe = t.with_second(0)?;
+    for _ in 0..24 * 60 {
+        probe += Duration::minutes(1);
+        if let Some(dt) = zone.from_local_datetime(&probe).earliest() {
+            return Some(dt.with_timezone(&Utc));
+        }
+    }
+    None
+}
+
+/// A field of plain values, ranges and steps as a bit set.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> ParseResult<u64> {
+    let mut set = 0;
+    for item in field.split(',') {
+        set |= parse_item(item, min, max, names)?;
+    }
+    Ok(set)
+}
+
+fn parse_item(item: &str, min: u32, max: u32, names: &[&str]) -> ParseResult<u64> {
+    let (range, step) = match item.split_once('/') {
+        Some((range, step)) => match step.parse::<u32>() {
+            Ok(step) if step > 0 => (range, Some(step)),
+            _ => return error(format!("invalid step: {}", step)),
+        },
+        None => (item, None),
+    };
+
+    let (start, end) = if range == "*" || range == "?" {
+        (min, max)
+    } else if let Some((a, b)) = range.split_once('-') {
+        (value(a, min, max, names)?, value(b, min, max, names)?)
+    } else {
+        let start = value(range, min, max, names)?;
+        // `a/n` runs from a to the end of the range
+        (start, if step.is_some() { max } else { start })
+    };
+    if start > end {
+        return error(format!("range {} goes backwards", range));
+    }
+
+    let step = step.unwrap_or(1) as usize;
+    Ok((start..=end).step_by(step).fold(0, |set, n| set | 1 << n))
+}
+
+fn value(s: &str, min: u32, max: u32, names: &[&str]) -> ParseResult<u32> {
+    let upper = s.to_ascii_uppercase();
+    let n = match names.iter().position(|name| *name == upper) {
+        Some(i) => i as u32 + min,
+        None => match s.parse::<u32>() {
+            Ok(n) => n,
+            Err(_) => return error(format!("invalid value: {}", s)),
+        },
+    };
+    if n < min || n > max {
+        return error(format!("{} is out of range {}-{}", n, min, max));
+    }
+    Ok(n)
+}
+
+fn parse_days(field: &str) -> ParseResult<(u64, Vec<DaySpecial>)> {
+    let mut set = 0;
+    let mut specials = Vec::new();
+    for item in field.split(',') {
+        let upper = item.to_ascii_uppercase();
+        if upper == "L" {
+            specials.push(DaySpecial::Last(0));
+        } else if upper == "LW" {
+            specials.push(DaySpecial::LastWeekday);
+        } else if let Some(offset) = upper.strip_prefix("L-") {
+            let offset = value(offset, 0, 30, &[])?;
+            specials.push(DaySpecial::Last(offset));
+        } else if let Some(day) = upper.strip_suffix('W') {
+            specials.push(DaySpecial::NearestWeekday(value(day, 1, 31, &[])?));
+        } else {
+            set |= parse_item(item, 1, 31, &[])?;
+        }
+    }
+    Ok((set, specials))
+}
+
+fn parse_weekdays(field: &str) -> ParseResult<(u64, Vec<WeekdaySpecial>)> {
+    let weekday = |s: &str| value(s, 0, 7, &WEEKDAYS).map(|n| n % 7);
+    let mut set = 0;
+    let mut specials = Vec::new();
+    for item in field.split(',') {
+        let upper = item.to_ascii_uppercase();
+        if let Some((day, n)) = upper.split_once('#') {
+            let n = value(n, 1, 5, &[])?;
+            specials.push(WeekdaySpecial::Nth(weekday(day)?, n));
+        } else if let Some(day) = upper.strip_suffix('L').filter(|d| !d.is_empty()) {
+            specials.push(WeekdaySpecial::Last(weekday(day)?));
+        } else {
+            // 7 is Sunday too
+            let bits = parse_item(item, 0, 7, &WEEKDAYS)?;
+            set |= (bits | bits >> 7) & 0x7f;
+        }
+    }
+    Ok((set, specials))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expr: &str, from: &str) -> String {
+        let expr = CronExpr::parse(&format!("TZ=UTC {}", expr)).unwrap();
+        expr.next_after(&utc(from)).unwrap().to_rfc3339()
+    }
+
+    #[test]
+    fn test_fields() {
+        assert_eq!(
+            next("*/15 * * * *", "2024-03-01T10:07:30Z"),
+            "2024-03-01T10:15:00+00:00"
+        );
+        assert_eq!(
+            next("0 9-17/4 * * *", "2024-03-01T13:00:00Z"),
+            "2024-03-01T17:00:00+00:00"
+        );
+        assert_eq!(
+            next("30 2 * * MON-FRI", "2024-03-01T03:00:00Z"),
+            "2024-03-04T02:30:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 1,15 * *", "2024-03-02T00:00:00Z"),
+            "2024-03-15T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 * JUN 7", "2024-03-02T00:00:00Z"),
+            "2024-06-02T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("@yearly", "2024-03-02T00:00:00Z"),
+            "2025-01-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("*/10 * * * * *", "2024-03-01T10:07:30Z"),
+            "2024-03-01T10:07:40+00:00"
+        );
+        // Both day fields restricted: either matches
+        assert_eq!(
+            next("0 0 13 * FRI", "2024-09-01T00:00:00Z"),
+            "2024-09-06T00:00:00+00:00"
+        );
+        // Leap day
+        assert_eq!(
+            next("0 0 29 2 *", "2024-03-01T00:00:00Z"),
+            "2028-02-29T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_special_days() {
+        assert_eq!(
+            next("0 0 L * *", "2024-02-10T00:00:00Z"),
+            "2024-02-29T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 L-2 * *", "2024-04-01T00:00:00Z"),
+            "2024-04-28T00:00:00+00:00"
+        );
+        // 2024-06-15 is a Saturday
+        assert_eq!(
+            next("0 0 15W * *", "2024-06-01T00:00:00Z"),
+            "2024-06-14T00:00:00+00:00"
+        );
+        // 2024-06-01 is a Saturday; 1W stays in June
+        assert_eq!(
+            next("0 0 1W * *", "2024-05-31T00:00:00Z"),
+            "2024-06-03T00:00:00+00:00"
+        );
+        // 2024-08-31 is a Saturday
+        assert_eq!(
+            next("0 0 LW * *", "2024-08-01T00:00:00Z"),
+            "2024-08-30T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 * * 5L", "2024-05-01T00:00:00Z"),
+            "2024-05-31T00:00:00+00:00"
+        );
+        assert_eq!(
+            next("0 0 * * MON#2", "2024-05-01T00:00:00Z"),
+            "2024-05-13T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_time_zones() {
+        let expr = CronExpr::parse("CRON_TZ=America/New_York 30 9 * * *").unwrap();
+        let next = expr.next_after(&utc("2024-01-10T00:00:00Z")).unwrap();
+        assert_eq!(next, utc("2024-01-10T14:30:00Z"));
+
+        // 02:30 does not exist on 2024-03-31 in Berlin: runs at 03:00 CEST
+        let expr = CronExpr::parse("CRON_TZ=Europe/Berlin 30 2 * * *").unwrap();
+        let next = expr.next_after(&utc("2024-03-30T12:00:00Z")).unwrap();
+        assert_eq!(next, utc("2024-03-31T01:00:00Z"));
+
+        // 02:30 happens twice on 2024-10-27: runs once
+        let times = expr.upcoming(&utc("2024-10-26T12:00:00Z"), 2);
+        assert_eq!(
+            times,
+            vec![utc("2024-10-27T00:30:00Z"), utc("2024-10-28T01:30:00Z")]
+        );
+    }
+
+    #[test]
+    fn test_to_calendar() {
+        let calendar = |expr: &str| CronExpr::parse(expr).unwrap().to_calendar().unwrap();
+        assert_eq!(
+            calendar("30 2 * * MON-FRI"),
+            vec!["Mon..Fri *-*-* 02:30:00"]
+        );
+        assert_eq!(calendar("*/15 * * * *"), vec!["*-*-* *:00/15:00"]);
+        assert_eq!(calendar("0 0 L * *"), vec!["*-*~01 00:00:00"]);
+        assert_eq!(calendar("0 0 * * 0,6"), vec!["Sat,Sun *-*-* 00:00:00"]);
+        assert_eq!(
+            calendar("CRON_TZ=Europe/Berlin 0 9 1,15 1-3,6 *"),
+            vec!["*-01..03,06-01,15 09:00:00 Europe/Berlin"]
+        );
+        assert_eq!(
+            calendar("0 0 13 * FRI"),
+            vec!["*-*-13 00:00:00", "Fri *-*-* 00:00:00"]
+        );
+        assert!(CronExpr::parse("0 0 15W * *")
+            .unwrap()
+            .to_calendar()
+            .is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(CronExpr::parse("* * * *").is_err());
+        assert!(CronExpr::parse("60 * * * *").is_err());
+        assert!(CronExpr::parse("5-1 * * * *").is_err());
+        assert!(CronExpr::parse("*/0 * * * *").is_err());
+        assert!(CronExpr::parse("0 0 * FOO *").is_err());
+        assert!(CronExpr::parse("TZ=Mars/Olympus 0 0 * * *").is_err());
+        assert!(CronExpr::parse("@reboot").is_err());
+        let err = CronExpr::parse("0 25 * * *").unwrap_err();
+        assert_eq!(err.to_string(), "hour field: 25 is out of range 0-23");
+        // Never matches
+        let expr = CronExpr::parse("0 0 30 2 *").unwrap();
+        assert_eq!(expr.next_after(&Utc::now()), None);
+    }
+}
@@ -0,0 +1,360 @@
+//! Crontab files.
+//!
+//! Import reads a user crontab (`crontab -l` format): each entry becomes a
+//! cron task named after the comment line directly above it, or after its
+//! command. `CRON_TZ=` lines apply to the entries below them, `@reboot`
+//! becomes a boot task, and `%` stdin is turned into a `printf` pipe.
+//!
+//! Export writes the tasks back in the same form. Daily, weekly, monthly
+//! tasks are in UTC and go under `CRON_TZ=UTC`, which needs cronie; one-off
+//! tasks and intervals cron cannot express are skipped.
+
+use crate::cron::CronExpr;
+use crate::models::{Imported, Schedule, ScheduledTask};
+use chrono::Weekday;
+
+/// Prefix of a commented-out entry for a disabled task.
+const DISABLED: &str = "# disabled: ";
+
+pub fn parse(text: &str) -> Imported {
+    let mut imported = Imported::default();
+    let mut zone: Option<String> = None;
+    let mut comment: Option<String> = None;
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let (enabled, entry) = match line.strip_prefix(DISABLED) {
+            Some(entry) => (false, entry.trim()),
+            None => (true, line),
+        };
+
+        if entry.is_empty() {
+            comment = None;
+            continue;
+        }
+        if enabled && entry.starts_with('#') {
+            comment = Some(entry.trim_start_matches('#').trim().to_string());
+            continue;
+        }
+        if let Some((name, value)) = variable(entry) {
+            if name == "CRON_TZ" {
+                zone = Some(value).filter(|v| !v.is_empty());
+            } else {
+                imported.warnings.push(format!(
+                    "line {}: {} is not carried over",
+                    number + 1,
+                    name
+                ));
+            }
+            continue;
+        }
+
+        match parse_entry(entry, zone.as_deref()) {
+            Ok((schedule, command, program)) => {
+                let name = comment.take().unwrap_or(program);
+                let mut task = ScheduledTask::new(&name, &command, schedule);
+                task.enabled = enabled;
+                imported.tasks.push(task);
+            }
+            Err(e) => imported
+                .warnings
+                .push(format!("line {}: {}", number + 1, e)),
+        }
+        comment = None;
+    }
+    imported
+}
+
+/// `NAME=value` environment lines.
+fn variable(line: &str) -> Option<(&str, String)> {
+    let (name, value) = line.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+    Some((name, value.to_string()))
+}
+
+/// The schedule, command and program name of an entry.
+fn parse_entry(line: &str, zone: Option<&str>) -> Result<(Schedule, String, String), String> {
+    let (expr, command) = if line.starts_with('@') {
+        line.split_once(char::is_whitespace)
+            .ok_or_else(|| "missing command".to_string())?
+    } else {
+        let mut end = 0;
+        for _ in 0..5 {
+            let start = end + line[end..].len() - line[end..].trim_start().len();
+            end = start
+                + line[start..]
+                    .find(char::is_whitespace)
+                    .ok_or_else(|| "missing command".to_string())?;
+        }
+        (&line[..end], &line[end..])
+    };
+    let program = name_for(command.trim());
+    let command = cron_command(command.trim());
+
+    if expr.eq_ignore_ascii_case("@reboot") {
+        return Ok((Schedule::AfterBoot { seconds: 0 }, command, program));
+    }
+    let expr = match zone {
+        Some(zone) => format!("CRON_TZ={} {}", zone, expr),
+        None => expr.to_string(),
+    };
+    CronExpr::parse(&expr).map_err(|e| e.to_string())?;
+    Ok((Schedule::Cron(expr), command, program))
+}
+
+/// Undo cron's `%` handling: `\%` is a literal `%`; text after the first
+/// plain `%` is the command's stdin, with further `%`s as newlines.
+fn cron_command(command: &str) -> String {
+    let mut parts = vec![String::new()];
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'%') => {
+                parts.last_mut().unwrap().push('%');
+                chars.next();
+            }
+            '%' => parts.push(String::new()),
+            c => parts.last_mut().unwrap().push(c),
+        }
+    }
+    let command = parts.remove(0);
+    if parts.is_empty() {
+        return command;
+    }
+    let lines: Vec<String> = parts.iter().map(|line| shell_quote(line)).collect();
+    format!("printf '%s\\n' {} | {}", lines.join(" "), command.trim())
+}
+
+/// A task name from the command's program: `/usr/bin/backup.sh -q` gives
+/// `backup.sh`.
+fn name_for(command: &str) -> String {
+    let program = command
+        .split_whitespace()
+        .find(|word| !word.contains('='))
+        .unwrap_or(command);
+    program.rsplit('/').next().unwrap_or(program).to_string()
+}
+
+pub fn shell_quote(s: &str) -> String {
+    if !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@".contains(c))
+    {
+        return s.to_string();
+    }
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Render `tasks` as a crontab, with a warning for each task left out.
+pub fn render(tasks: &[ScheduledTask]) -> (String, Vec<String>) {
+    let mut entries: Vec<(Option<String>, &ScheduledTask, String)> = Vec::new();
+    let mut warnings = Vec::new();
+
+    for task in tasks {
+        if task.command.contains('\n') {
+            warnings.push(format!(
+                "{}: multi-line commands don't fit in a crontab",
+                task.name
+            ));
+            continue;
+        }
+        let command = task.command.replace('%', "\\%");
+        let utc = Some("UTC".to_string());
+        let (zone, line) = match &task.schedule {
+            Schedule::Cron(expr) => match CronExpr::parse(expr) {
+                Ok(expr) => (
+                    expr.zone().map(|z| z.name().to_string()),
+                    format!("{} {}", expr.fields(), command),
+                ),
+                Err(e) => {
+                    warnings.push(format!("{}: {}", task.name, e));
+                    continue;
+                }
+            },
+            Schedule::Interval { minutes } => match interval_fields(*minutes) {
+                Some(fields) => (None, format!("{} {}", fields, command)),
+                None => {
+                    warnings.push(format!(
+                        "{}: every {} minutes has no cron equivalent",
+                        task.name, minutes
+                    ));
+                    continue;
+                }
+            },
+            Schedule::Daily { hour, minute } => {
+                (utc, format!("{} {} * * * {}", minute, hour, command))
+            }
+            Schedule::Weekly { day, hour, minute } => (
+                utc,
+                format!("{} {} * * {} {}", minute, hour, weekday_name(*day), command),
+            ),
+            Schedule::Monthly { day, hour, minute } => {
+                (utc, format!("{} {} {} * * {}", minute, hour, day, command))
+            }
+            Schedule::AfterBoot { seconds: 0 } => (None, format!("@reboot {}", command)),
+            Schedule::AfterBoot { seconds } => {
+                (None, format!("@reboot sleep {} && {}", seconds, command))
+            }
+            Schedule::Once(_) => {
+                warnings.push(format!(
+                    "{}: one-off tasks have no cron equivalent",
+                    task.name
+                ));
+                continue;
+            }
+            Schedule::AfterDependencies => {
+                warnings.push(format!(
+                    "{}: tasks run after dependencies have no cron equivalent",
+                    task.name
+                ));
+                continue;
+            }
+        };
+        entries.push((zone, task, line));
+    }
+
+    // Local-time entries first, since CRON_TZ can't be unset
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut out = format!(
+        "# Exported by task-scheduler on {}\n",
+        chrono::Local::now().format("%Y-%m-%d %H:%M")
+    );
+    let mut zone = None;
+    for (entry_zone, task, line) in entries {
+        if entry_zone != zone {
+            out.push_str(&format!(
+                "\nCRON_TZ={}\n",
+                entry_zone.as_deref().unwrap_or("")
+            ));
+            zone = entry_zone;
+        }
+        out.push_str(&format!("\n# {}\n", task.name));
+        if !task.enabled {
+            out.push_str(DISABLED);
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    (out, warnings)
+}
+
+/// Fields for intervals that divide an hour or a day evenly.
+fn interval_fields(minutes: u32) -> Option<String> {
+    match minutes {
+        1 => Some("* * * * *".to_string()),
+        m if m < 60 && 60 % m == 0 => Some(format!("*/{} * * * *", m)),
+        60 => Some("0 * * * *".to_string()),
+        m if m % 60 == 0 && 24 % (m / 60) == 0 => Some(format!("0 */{} * * *", m / 60)),
+        1440 => Some("0 0 * * *".to_string()),
+        _ => None,
+    }
+}
+
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MON",
+        Weekday::Tue => "TUE",
+        Weekday::Wed => "WED",
+        Weekday::Thu => "THU",
+        Weekday::Fri => "FRI",
+        Weekday::Sat => "SAT",
+        Weekday::Sun => "SUN",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRONTAB: &str = "\
+SHELL=/bin/bash
+MAILTO=me@example.com
+
+# Nightly backup
+30 2 * * * /usr/local/bin/backup.sh --quiet
+*/5 * * * * curl -fsS https://example.com/ping > /dev/null
+@reboot ~/bin/start-agent
+0 9 * * 1 mail -s \"Weekly\" me%Time for the review%Bye
+# disabled: 0 * * * * echo 100\\% off
+
+CRON_TZ=America/New_York
+0 8 * * MON-FRI notify-send 'Good morning'
+61 * * * * broken
+";
+
+    #[test]
+    fn test_parse() {
+        let imported = parse(CRONTAB);
+        assert_eq!(imported.tasks.len(), 6);
+        assert_eq!(imported.warnings.len(), 3);
+        assert!(imported.warnings[2].starts_with("line 13: minute field"));
+
+        let names: Vec<_> = imported.tasks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Nightly backup",
+                "curl",
+                "start-agent",
+                "mail",
+                "echo",
+                "notify-send"
+            ]
+        );
+        let backup = &imported.tasks[0];
+        assert_eq!(backup.command, "/usr/local/bin/backup.sh --quiet");
+        assert!(matches!(&backup.schedule, Schedule::Cron(e) if e == "30 2 * * *"));
+        assert!(matches!(
+            imported.tasks[2].schedule,
+            Schedule::AfterBoot { seconds: 0 }
+        ));
+        assert_eq!(
+            imported.tasks[3].command,
+            "printf '%s\\n' 'Time for the review' Bye | mail -s \"Weekly\" me"
+        );
+        assert!(!imported.tasks[4].enabled);
+        assert_eq!(imported.tasks[4].command, "echo 100% off");
+        assert!(matches!(
+            &imported.tasks[5].schedule,
+            Schedule::Cron(e) if e == "CRON_TZ=America/New_York 0 8 * * MON-FRI"
+        ));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut tasks = parse(CRONTAB).tasks;
+        tasks.push(ScheduledTask::new(
+            "report",
+            "make report",
+            Schedule::Weekly {
+                day: Weekday::Fri,
+                hour: 16,
+                minute: 0,
+            },
+        ));
+        tasks.push(ScheduledTask::new(
+            "odd",
+            "true",
+            Schedule::Interval { minutes: 7 },
+        ));
+
+        let (text, warnings) = render(&tasks);
+        assert_eq!(warnings.len(), 1);
+        assert!(text.contains("\nCRON_TZ=UTC\n\n# report\n0 16 * * FRI make report\n"));
+
+        let again = parse(&text);
+        assert!(again.warnings.is_empty());
+        assert_eq!(again.tasks.len(), 7);
+        for task in &tasks[..6] {
+            let copy = again.tasks.iter().find(|t| t.name == task.name).unwrap();
+            assert_eq!(copy.command, task.command);
+            assert_eq!(copy.enabled, task.enabled);
+            assert_eq!(copy.schedule.label(), task.schedule.label());
+        }
+    }
+}
@@ -1,10 +1,18 @@
 //! Database operations for task scheduler.
 
-use crate::models::{RunStatus, Schedule, ScheduledTask, TaskId, TaskRun};
+use crate::models::{
+    CatchUp, Notification, NotificationKind, RunId, RunStatus, Schedule, ScheduledTask, TaskId,
+    TaskRun, Trigger,
+};
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection, Result as SqlResult};
 use std::path::Path;
 
+const SELECT_TASKS: &str =
+    "SELECT id, name, command, schedule, enabled, last_run, next_run, run_count, created_at,
+            retry, on_failure, catch_up, attempt, pending
+     FROM tasks";
+
 pub struct Database {
     conn: Connection,
 }
@@ -29,7 +37,12 @@ impl Database {
                 last_run TEXT,
                 next_run TEXT,
                 run_count INTEGER NOT NULL DEFAULT 0,
-                created_at TEXT NOT NULL
+                created_at TEXT NOT NULL,
+                retry TEXT,
+                on_failure TEXT,
+                catch_up TEXT NOT NULL DEFAULT 'Once',
+                attempt INTEGER NOT NULL DEFAULT 0,
+                pending TEXT
             );
 
             CREATE TABLE IF NOT EXISTS runs (
@@ -39,19 +52,52 @@ impl Database {
                 finished_at TEXT,
                 status TEXT NOT NULL,
                 exit_code INTEGER,
-                output TEXT NOT NULL DEFAULT ''
+                output TEXT NOT NULL DEFAULT '',
+                trigger TEXT NOT NULL DEFAULT 'Schedule',
+                attempt INTEGER NOT NULL DEFAULT 1
+            );
+
+            CREATE TABLE IF NOT EXISTS task_deps (
+                task_id INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                depends_on INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                PRIMARY KEY (task_id, depends_on)
+            );
+
+            CREATE TABLE IF NOT EXISTS notifications (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id INTEGER NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                run_id INTEGER REFERENCES runs(id),
+                created_at TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                message TEXT NOT NULL
             );
 
             CREATE INDEX IF NOT EXISTS idx_runs_task ON runs(task_id);
+            CREATE INDEX IF NOT EXISTS idx_notifications_created ON notifications(created_at);
             "#,
-        )
+        )?;
+
+        // Databases created before retries and dependencies lack their columns
+        if self.conn.prepare("SELECT attempt FROM tasks LIMIT 0").is_err() {
+            self.conn.execute_batch(
+                "ALTER TABLE tasks ADD COLUMN retry TEXT;
+                 ALTER TABLE tasks ADD COLUMN on_failure TEXT;
+                 ALTER TABLE tasks ADD COLUMN catch_up TEXT NOT NULL DEFAULT 'Once';
+                 ALTER TABLE tasks ADD COLUMN attempt INTEGER NOT NULL DEFAULT 0;
+                 ALTER TABLE tasks ADD COLUMN pending TEXT;
+                 ALTER TABLE runs ADD COLUMN trigger TEXT NOT NULL DEFAULT 'Schedule';
+                 ALTER TABLE runs ADD COLUMN attempt INTEGER NOT NULL DEFAULT 1;",
+            )?;
+        }
+        Ok(())
     }
 
     pub fn insert_task(&self, task: &ScheduledTask) -> SqlResult<TaskId> {
         let schedule_json = serde_json::to_string(&task.schedule).unwrap_or_default();
         self.conn.execute(
-            "INSERT INTO tasks (name, command, schedule, enabled, last_run, next_run, run_count, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO tasks (name, command, schedule, enabled, last_run, next_run, run_count, created_at,
+                                retry, on_failure, catch_up, attempt, pending)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
             params![
                 task.name,
                 task.command,
@@ -61,16 +107,24 @@ impl Database {
                 task.next_run.map(|dt| dt.to_rfc3339()),
                 task.run_count,
                 task.created_at.to_rfc3339(),
+                serde_json::to_string(&task.retry).unwrap_or_default(),
+                task.on_failure,
+                format!("{:?}", task.catch_up),
+                task.attempt,
+                task.pending.map(|t| format!("{:?}", t)),
             ],
         )?;
-        Ok(self.conn.last_insert_rowid())
+        let id = self.conn.last_insert_rowid();
+        self.set_dependencies(id, &task.depends_on)?;
+        Ok(id)
     }
 
     pub fn update_task(&self, task: &ScheduledTask) -> SqlResult<()> {
         let schedule_json = serde_json::to_string(&task.schedule).unwrap_or_default();
         self.conn.execute(
             "UPDATE tasks SET name = ?1, command = ?2, schedule = ?3, enabled = ?4,
-             last_run = ?5, next_run = ?6, run_count = ?7 WHERE id = ?8",
+             last_run = ?5, next_run = ?6, run_count = ?7, retry = ?8, on_failure = ?9,
+             catch_up = ?10, attempt = ?11, pending = ?12 WHERE id = ?13",
             params![
                 task.name,
                 task.command,
@@ -79,6 +133,11 @@ impl Database {
                 task.last_run.map(|dt| dt.to_rfc3339()),
                 task.next_run.map(|dt| dt.to_rfc3339()),
                 task.run_count,
+                serde_json::to_string(&task.retry).unwrap_or_default(),
+                task.on_failure,
+                format!("{:?}", task.catch_up),
+                task.attempt,
+                task.pending.map(|t| format!("{:?}", t)),
                 task.id,
             ],
         )?;
@@ -87,34 +146,76 @@ impl Database {
 
     pub fn delete_task(&self, id: TaskId) -> SqlResult<()> {
         self.conn.execute("DELETE FROM tasks WHERE id = ?1", [id])?;
+        self.conn.execute(
+            "DELETE FROM task_deps WHERE task_id = ?1 OR depends_on = ?1",
+            [id],
+        )?;
         Ok(())
     }
 
+    /// Replace the tasks `task_id` waits for. Callers check for cycles
+    /// with [`deps::check`](crate::deps::check) first.
+    pub fn set_dependencies(&self, task_id: TaskId, depends_on: &[TaskId]) -> SqlResult<()> {
+        self.conn.execute("DELETE FROM task_deps WHERE task_id = ?1", [task_id])?;
+        for dep in depends_on {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO task_deps (task_id, depends_on) VALUES (?1, ?2)",
+                [task_id, *dep],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Fill in `depends_on` from the dependency table.
+    fn load_dependencies(&self, tasks: &mut [ScheduledTask]) -> SqlResult<()> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT task_id, depends_on FROM task_deps ORDER BY rowid")?;
+        let edges = stmt
+            .query_map([], |row| Ok((row.get::<_, TaskId>(0)?, row.get::<_, TaskId>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for task in tasks {
+            task.depends_on = edges
+                .iter()
+                .filter(|(id, _)| *id == task.id)
+                .map(|(_, dep)| *dep)
+                .collect();
+        }
+        Ok(())
+    }
+
+    pub fn get_task(&self, id: TaskId) -> SqlResult<Option<ScheduledTask>> {
+        let mut stmt = self.conn.prepare(&format!("{} WHERE id = ?1", SELECT_TASKS))?;
+        let mut tasks = stmt.query_map([id], |row| self.row_to_task(row))?
+            .collect::<Result<Vec<_>, _>>()?;
+        self.load_dependencies(&mut tasks)?;
+        Ok(tasks.pop())
+    }
+
     pub fn list_tasks(&self) -> SqlResult<Vec<ScheduledTask>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, command, schedule, enabled, last_run, next_run, run_count, created_at
-             FROM tasks ORDER BY next_run NULLS LAST"
-        )?;
-        let tasks = stmt.query_map([], |row| self.row_to_task(row))?
+        let mut stmt = self.conn.prepare(&format!("{} ORDER BY next_run NULLS LAST", SELECT_TASKS))?;
+        let mut tasks = stmt.query_map([], |row| self.row_to_task(row))?
             .collect::<Result<Vec<_>, _>>()?;
+        self.load_dependencies(&mut tasks)?;
         Ok(tasks)
     }
 
     pub fn get_due_tasks(&self) -> SqlResult<Vec<ScheduledTask>> {
         let now = Utc::now().to_rfc3339();
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, command, schedule, enabled, last_run, next_run, run_count, created_at
-             FROM tasks WHERE enabled = 1 AND next_run <= ?1"
-        )?;
-        let tasks = stmt.query_map([now], |row| self.row_to_task(row))?
+        let mut stmt = self.conn.prepare(&format!(
+            "{} WHERE enabled = 1 AND next_run <= ?1 ORDER BY next_run",
+            SELECT_TASKS
+        ))?;
+        let mut tasks = stmt.query_map([now], |row| self.row_to_task(row))?
             .collect::<Result<Vec<_>, _>>()?;
+        self.load_dependencies(&mut tasks)?;
         Ok(tasks)
     }
 
     pub fn insert_run(&self, run: &TaskRun) -> SqlResult<i64> {
         self.conn.execute(
-            "INSERT INTO runs (task_id, started_at, finished_at, status, exit_code, output)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO runs (task_id, started_at, finished_at, status, exit_code, output, trigger, attempt)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
             params![
                 run.task_id,
                 run.started_at.to_rfc3339(),
@@ -122,14 +223,102 @@ impl Database {
                 format!("{:?}", run.status),
                 run.exit_code,
                 run.output,
+                format!("{:?}", run.trigger),
+                run.attempt,
             ],
         )?;
         Ok(self.conn.last_insert_rowid())
     }
 
+    pub fn update_run(&self, run: &TaskRun) -> SqlResult<()> {
+        self.conn.execute(
+            "UPDATE runs SET finished_at = ?1, status = ?2, exit_code = ?3, output = ?4
+             WHERE id = ?5",
+            params![
+                run.finished_at.map(|dt| dt.to_rfc3339()),
+                format!("{:?}", run.status),
+                run.exit_code,
+                run.output,
+                run.id,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Mark runs left `Running` by a scheduler that exited as cancelled,
+    /// and arm boot tasks that have not run since the last boot.
+    pub fn prepare_scheduler(&self) -> SqlResult<()> {
+        self.cancel_stale_runs()?;
+        for mut task in self.list_tasks()? {
+            if matches!(task.schedule, Schedule::AfterBoot { .. }) {
+                task.update_next_run();
+                self.update_task(&task)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Mark runs left `Running` by a scheduler that exited as cancelled.
+    pub fn cancel_stale_runs(&self) -> SqlResult<usize> {
+        self.conn.execute(
+            "UPDATE runs SET status = 'Cancelled', finished_at = ?1 WHERE status = 'Running'",
+            [Utc::now().to_rfc3339()],
+        )
+    }
+
+    /// When `task_id` last finished successfully.
+    pub fn last_success(&self, task_id: TaskId) -> SqlResult<Option<DateTime<Utc>>> {
+        let finished: Option<String> = self.conn.query_row(
+            "SELECT MAX(finished_at) FROM runs WHERE task_id = ?1 AND status = 'Success'",
+            [task_id],
+            |row| row.get(0),
+        )?;
+        Ok(finished
+            .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+            .map(|dt| dt.with_timezone(&Utc)))
+    }
+
+    pub fn insert_notification(
+        &self,
+        task_id: TaskId,
+        run_id: Option<RunId>,
+        kind: NotificationKind,
+        message: &str,
+    ) -> SqlResult<i64> {
+        self.conn.execute(
+            "INSERT INTO notifications (task_id, run_id, created_at, kind, message)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![task_id, run_id, Utc::now().to_rfc3339(), format!("{:?}", kind), message],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// The latest notifications, newest first.
+    pub fn list_notifications(&self, limit: usize) -> SqlResult<Vec<Notification>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, task_id, run_id, created_at, kind, message
+             FROM notifications ORDER BY id DESC LIMIT ?1",
+        )?;
+        let notifications = stmt
+            .query_map([limit as i64], |row| {
+                Ok(Notification {
+                    id: row.get(0)?,
+                    task_id: row.get(1)?,
+                    run_id: row.get(2)?,
+                    created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(3)?)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    kind: NotificationKind::parse(&row.get::<_, String>(4)?),
+                    message: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(notifications)
+    }
+
     pub fn get_task_runs(&self, task_id: TaskId, limit: usize) -> SqlResult<Vec<TaskRun>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, task_id, started_at, finished_at, status, exit_code, output
+            "SELECT id, task_id, started_at, finished_at, status, exit_code, output, trigger, attempt
              FROM runs WHERE task_id = ?1 ORDER BY started_at DESC LIMIT ?2"
         )?;
         let runs = stmt.query_map([task_id, limit as i64], |row| self.row_to_run(row))?
@@ -158,6 +347,15 @@ impl Database {
             created_at: DateTime::parse_from_rfc3339(&row.get::<_, String>(8)?)
                 .map(|dt| dt.with_timezone(&Utc))
                 .unwrap_or_else(|_| Utc::now()),
+            retry: row.get::<_, Option<String>>(9)?
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+            depends_on: Vec::new(),
+            on_failure: row.get(10)?,
+            catch_up: CatchUp::parse(&row.get::<_, String>(11)?).unwrap_or_default(),
+            attempt: row.get(12)?,
+            pending: row.get::<_, Option<String>>(13)?
+                .and_then(|s| Trigger::parse(&s)),
         })
     }
 
@@ -181,6 +379,8 @@ impl Database {
             },
             exit_code: row.get(5)?,
             output: row.get(6)?,
+            trigger: Trigger::parse(&row.get::<_, String>(7)?).unwrap_or_default(),
+            attempt: row.get(8)?,
         })
     }
 }
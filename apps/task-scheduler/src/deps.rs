@@ -0,0 +1,122 @@
+//! Dependencies between tasks.
+//!
+//! A task can depend on others: once all of them have succeeded since it
+//! last ran, it is queued. The dependencies must form a DAG, so changes
+//! that would close a loop are refused.
+
+use crate::models::{ScheduledTask, TaskId};
+use std::collections::HashMap;
+
+/// Check that `task` can depend on `depends_on`: the tasks exist and no
+/// cycle forms. The error names the tasks of the cycle.
+pub fn check(tasks: &[ScheduledTask], task: TaskId, depends_on: &[TaskId]) -> Result<(), String> {
+    let names: HashMap<TaskId, &str> = tasks.iter().map(|t| (t.id, t.name.as_str())).collect();
+    if let Some(missing) = depends_on.iter().find(|id| !names.contains_key(id)) {
+        return Err(format!("no task with id {}", missing));
+    }
+
+    let mut edges: HashMap<TaskId, Vec<TaskId>> = tasks
+        .iter()
+        .map(|t| (t.id, t.depends_on.clone()))
+        .collect();
+    edges.insert(task, depends_on.to_vec());
+
+    match find_cycle(&edges) {
+        Some(cycle) => {
+            let path: Vec<&str> = cycle
+                .iter()
+                .map(|id| names.get(id).copied().unwrap_or("?"))
+                .collect();
+            Err(format!("dependency cycle: {}", path.join(" → ")))
+        }
+        None => Ok(()),
+    }
+}
+
+/// A cycle in `edges`, from each task to the tasks it depends on, as the
+/// tasks along it with the first repeated at the end.
+pub fn find_cycle(edges: &HashMap<TaskId, Vec<TaskId>>) -> Option<Vec<TaskId>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        Visiting,
+        Done,
+    }
+
+    fn visit(
+        id: TaskId,
+        edges: &HashMap<TaskId, Vec<TaskId>>,
+        state: &mut HashMap<TaskId, State>,
+        path: &mut Vec<TaskId>,
+    ) -> Option<Vec<TaskId>> {
+        match state.get(&id) {
+            Some(State::Done) => return None,
+            Some(State::Visiting) => {
+                let start = path.iter().position(|p| *p == id)?;
+                let mut cycle = path[start..].to_vec();
+                cycle.push(id);
+                return Some(cycle);
+            }
+            None => {}
+        }
+        state.insert(id, State::Visiting);
+        path.push(id);
+        for dep in edges.get(&id).into_iter().flatten() {
+            if let Some(cycle) = visit(*dep, edges, state, path) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        state.insert(id, State::Done);
+        None
+    }
+
+    let mut ids: Vec<TaskId> = edges.keys().copied().collect();
+    ids.sort_unstable();
+    let mut state = HashMap::new();
+    ids.into_iter()
+        .find_map(|id| visit(id, edges, &mut state, &mut Vec::new()))
+}
+
+/// Tasks that depend on `task_id`.
+pub fn dependents(tasks: &[ScheduledTask], task_id: TaskId) -> Vec<&ScheduledTask> {
+    tasks
+        .iter()
+        .filter(|t| t.depends_on.contains(&task_id))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Schedule;
+
+    fn task(id: TaskId, name: &str, depends_on: &[TaskId]) -> ScheduledTask {
+        let mut task = ScheduledTask::new(name, "true", Schedule::AfterDependencies);
+        task.id = id;
+        task.depends_on = depends_on.to_vec();
+        task
+    }
+
+    #[test]
+    fn test_check() {
+        let tasks = vec![
+            task(1, "fetch", &[]),
+            task(2, "build", &[1]),
+            task(3, "test", &[2]),
+            task(4, "deploy", &[2, 3]),
+        ];
+        assert!(check(&tasks, 4, &[3]).is_ok());
+        assert_eq!(
+            check(&tasks, 1, &[4]).unwrap_err(),
+            "dependency cycle: fetch → deploy → build → fetch"
+        );
+        assert_eq!(
+            check(&tasks, 2, &[2]).unwrap_err(),
+            "dependency cycle: build → build"
+        );
+        assert_eq!(check(&tasks, 2, &[9]).unwrap_err(), "no task with id 9");
+
+        let names: Vec<_> = dependents(&tasks, 2).iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["test", "deploy"]);
+    }
+}
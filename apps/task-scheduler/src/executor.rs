@@ -0,0 +1,734 @@
+//! Running task commands.
+//!
+//! Each run is `<shell> -c <command>` in its own process group, started in
+//! the home directory with stdout and stderr captured together. Runs are
+//! polled from the UI or daemon loop: a run past the timeout gets SIGTERM,
+//! then SIGKILL if it is still there [`KILL_GRACE`] later.
+//!
+//! When a run finishes, a failure is retried while the task's retry policy
+//! allows; after the last attempt the failure goes to the notification log
+//! and the failure hooks run. A success queues the tasks depending on it.
+//!
+//! Only one process schedules at a time; it holds an exclusive lock on
+//! [`Config::lock_path`](crate::config::Config::lock_path).
+
+use crate::config::{Config, NotificationConfig, SchedulerConfig};
+use crate::db::Database;
+use crate::deps;
+use crate::models::{
+    CatchUp, NotificationKind, RunId, RunStatus, Schedule, ScheduledTask, TaskId, TaskRun,
+    Trigger,
+};
+use chrono::{Local, Utc};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Time between SIGTERM and SIGKILL.
+pub const KILL_GRACE: Duration = Duration::from_secs(5);
+
+/// How long to wait for output after the command exits, in case a
+/// background process it left behind still holds the pipes.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How late a task can be at startup before it counts as missed.
+const MISSED_GRACE: chrono::Duration = chrono::Duration::minutes(1);
+
+pub struct Executor {
+    config: SchedulerConfig,
+    notifications: NotificationConfig,
+    jobs: Vec<Job>,
+}
+
+struct Job {
+    run: TaskRun,
+    task_name: String,
+    child: Child,
+    output: Arc<Mutex<Output>>,
+    readers: Vec<JoinHandle<()>>,
+    started: Instant,
+    /// Status to record and when the run was told to stop
+    stopping: Option<(RunStatus, Instant)>,
+    exited: Option<(ExitStatus, Instant)>,
+}
+
+#[derive(Default)]
+struct Output {
+    bytes: Vec<u8>,
+    truncated: bool,
+}
+
+impl Output {
+    fn push(&mut self, data: &[u8], limit: usize) {
+        let room = limit.saturating_sub(self.bytes.len());
+        if data.len() > room {
+            self.truncated = true;
+        }
+        self.bytes.extend_from_slice(&data[..data.len().min(room)]);
+    }
+}
+
+impl Executor {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.scheduler.clone(),
+            notifications: config.notifications.clone(),
+            jobs: Vec::new(),
+        }
+    }
+
+    pub fn running(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_running(&self, task_id: TaskId) -> bool {
+        self.jobs.iter().any(|job| job.run.task_id == task_id)
+    }
+
+    pub fn has_capacity(&self) -> bool {
+        self.jobs.len() < self.config.max_concurrent.max(1)
+    }
+
+    /// Start due tasks while there is capacity and record finished runs.
+    /// Returns a line per event.
+    ///
+    /// A task whose previous run is still going waits for it, so overlapping
+    /// occurrences collapse into one run.
+    pub fn tick(&mut self, db: &Database) -> Vec<String> {
+        let mut events = self.poll(db);
+        for mut task in db.get_due_tasks().unwrap_or_default() {
+            if self.is_running(task.id) {
+                continue;
+            }
+            if !self.has_capacity() {
+                break;
+            }
+            let trigger = task.pending.take().unwrap_or_default();
+            events.push(match self.start(db, &mut task, trigger) {
+                Ok(()) => format!("Started: {}", task.name),
+                Err(e) => format!("Failed to start {}: {}", task.name, e),
+            });
+        }
+        events
+    }
+
+    /// Run `task` now, recording the run and updating its counters. Runs
+    /// started by hand leave `next_run` alone.
+    pub fn start(
+        &mut self,
+        db: &Database,
+        task: &mut ScheduledTask,
+        trigger: Trigger,
+    ) -> anyhow::Result<()> {
+        let mut run = TaskRun {
+            id: 0,
+            task_id: task.id,
+            started_at: Utc::now(),
+            finished_at: None,
+            status: RunStatus::Running,
+            exit_code: None,
+            output: String::new(),
+            trigger,
+            attempt: task.attempt + 1,
+        };
+        run.id = db.insert_run(&run)?;
+
+        task.last_run = Some(run.started_at);
+        task.run_count += 1;
+        if trigger != Trigger::Manual {
+            task.update_next_run();
+        }
+        db.update_task(task)?;
+
+        match self.spawn(&task.command) {
+            Ok(mut child) => {
+                let output = Arc::new(Mutex::new(Output::default()));
+                let limit = self.config.max_output_bytes;
+                let mut readers = Vec::new();
+                if let Some(stdout) = child.stdout.take() {
+                    readers.push(capture(stdout, output.clone(), limit));
+                }
+                if let Some(stderr) = child.stderr.take() {
+                    readers.push(capture(stderr, output.clone(), limit));
+                }
+                self.jobs.push(Job {
+                    run,
+                    task_name: task.name.clone(),
+                    child,
+                    output,
+                    readers,
+                    started: Instant::now(),
+                    stopping: None,
+                    exited: None,
+                });
+                Ok(())
+            }
+            Err(e) => {
+                run.finished_at = Some(Utc::now());
+                run.status = RunStatus::Failed;
+                run.output = format!("failed to start {}: {}", self.config.shell, e);
+                db.update_run(&run)?;
+                Err(e.into())
+            }
+        }
+    }
+
+    fn spawn(&self, command: &str) -> std::io::Result<Child> {
+        let mut process = Command::new(&self.config.shell);
+        process
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(dirs) = directories::BaseDirs::new() {
+            process.current_dir(dirs.home_dir());
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            process.process_group(0);
+        }
+        process.spawn()
+    }
+
+    /// Stop the running run of `task_id`, recording it as cancelled.
+    pub fn cancel(&mut self, task_id: TaskId) -> bool {
+        let Some(job) = self.jobs.iter_mut().find(|job| job.run.task_id == task_id) else {
+            return false;
+        };
+        if job.stopping.is_none() && job.exited.is_none() {
+            job.stopping = Some((RunStatus::Cancelled, Instant::now()));
+            signal(&mut job.child, Signal::Term);
+        }
+        true
+    }
+
+    /// Enforce timeouts and record runs that have finished.
+    pub fn poll(&mut self, db: &Database) -> Vec<String> {
+        let timeout = match self.config.default_timeout_secs {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        };
+        let now = Instant::now();
+
+        for job in &mut self.jobs {
+            if job.exited.is_none() {
+                if let Ok(Some(status)) = job.child.try_wait() {
+                    job.exited = Some((status, now));
+                }
+            }
+            if job.exited.is_some() {
+                continue;
+            }
+            match job.stopping {
+                Some((_, since)) if now.duration_since(since) >= KILL_GRACE => {
+                    signal(&mut job.child, Signal::Kill);
+                }
+                Some(_) => {}
+                None if timeout.is_some_and(|t| now.duration_since(job.started) >= t) => {
+                    job.stopping = Some((RunStatus::Timeout, now));
+                    signal(&mut job.child, Signal::Term);
+                }
+                None => {}
+            }
+        }
+
+        let mut events = Vec::new();
+        let mut i = 0;
+        while i < self.jobs.len() {
+            let done = match self.jobs[i].exited {
+                Some((_, at)) => {
+                    self.jobs[i].readers.iter().all(|r| r.is_finished())
+                        || now.duration_since(at) >= DRAIN_TIMEOUT
+                }
+                None => false,
+            };
+            if !done {
+                i += 1;
+                continue;
+            }
+            let job = self.jobs.remove(i);
+            let name = job.task_name.clone();
+            let run = job.finish();
+            events.push(match run.exit_code {
+                Some(code) => format!(
+                    "{} {}: {} (exit {})",
+                    run.status.symbol(),
+                    name,
+                    run.status.label(),
+                    code
+                ),
+                None => format!("{} {}: {}", run.status.symbol(), name, run.status.label()),
+            });
+            let _ = db.update_run(&run);
+            events.extend(self.after_run(db, &run));
+        }
+        events
+    }
+
+    /// Retry a failed run, or notify about it; queue dependents of a
+    /// successful one.
+    fn after_run(&self, db: &Database, run: &TaskRun) -> Vec<String> {
+        let Ok(Some(mut task)) = db.get_task(run.task_id) else {
+            return Vec::new();
+        };
+        match run.status {
+            RunStatus::Success => {
+                if task.attempt > 0 {
+                    task.attempt = 0;
+                    let _ = db.update_task(&task);
+                }
+                self.queue_dependents(db, &task)
+            }
+            RunStatus::Failed | RunStatus::Timeout => self.failed(db, &mut task, run),
+            RunStatus::Running | RunStatus::Cancelled => Vec::new(),
+        }
+    }
+
+    fn failed(&self, db: &Database, task: &mut ScheduledTask, run: &TaskRun) -> Vec<String> {
+        // Runs started by hand are not retried
+        if run.trigger != Trigger::Manual && task.attempt < task.retry.max_retries {
+            task.attempt += 1;
+            let now = Utc::now();
+            let at = now.checked_add_signed(task.retry.delay(task.attempt)).unwrap_or(now);
+            task.next_run = Some(task.next_run.map_or(at, |next| next.min(at)));
+            task.pending = Some(Trigger::Retry);
+            let _ = db.update_task(task);
+            let message = format!(
+                "{}: retry {}/{} at {}",
+                task.name,
+                task.attempt,
+                task.retry.max_retries,
+                at.with_timezone(&Local).format("%H:%M:%S")
+            );
+            self.notify(db, task.id, Some(run.id), NotificationKind::Retry, &message);
+            return vec![format!("↻ {}", message)];
+        }
+
+        let what = match run.status {
+            RunStatus::Timeout => "timed out",
+            _ => "failed",
+        };
+        let message = match run.attempt {
+            1 => format!("{} {}", task.name, what),
+            n => format!("{} {} after {} attempts", task.name, what, n),
+        };
+        let message = match run.exit_code {
+            Some(code) => format!("{} (exit {})", message, code),
+            None => message,
+        };
+        if task.attempt > 0 {
+            task.attempt = 0;
+            let _ = db.update_task(task);
+        }
+        self.notify(db, task.id, Some(run.id), NotificationKind::Failure, &message);
+        let mut events = Vec::new();
+        let hooks = [self.notifications.on_failure.as_ref(), task.on_failure.as_ref()];
+        for hook in hooks.into_iter().flatten() {
+            if let Err(e) = self.run_hook(hook, task, run) {
+                let message = format!("{}: failure hook did not start: {}", task.name, e);
+                self.notify(db, task.id, Some(run.id), NotificationKind::HookError, &message);
+                events.push(format!("! {}", message));
+            }
+        }
+        events
+    }
+
+    /// Queue the enabled tasks depending on `task` whose dependencies have
+    /// all succeeded since they last ran.
+    fn queue_dependents(&self, db: &Database, task: &ScheduledTask) -> Vec<String> {
+        let tasks = db.list_tasks().unwrap_or_default();
+        let mut events = Vec::new();
+        for dependent in deps::dependents(&tasks, task.id) {
+            if !dependent.enabled {
+                continue;
+            }
+            let ready = dependent.depends_on.iter().all(|dep| {
+                db.last_success(*dep)
+                    .ok()
+                    .flatten()
+                    .is_some_and(|at| dependent.last_run.is_none_or(|run| at > run))
+            });
+            if !ready {
+                continue;
+            }
+            let mut dependent = dependent.clone();
+            dependent.next_run = Some(Utc::now());
+            dependent.pending = Some(Trigger::Dependency);
+            let _ = db.update_task(&dependent);
+            events.push(format!("→ {}: queued after {}", dependent.name, task.name));
+        }
+        events
+    }
+
+    /// Start a failure hook in the background. It gets the run's output on
+    /// stdin and the details in `TASK_*` and `RUN_*` variables.
+    fn run_hook(&self, hook: &str, task: &ScheduledTask, run: &TaskRun) -> std::io::Result<()> {
+        let mut process = Command::new(&self.config.shell);
+        process
+            .arg("-c")
+            .arg(hook)
+            .env("TASK_ID", task.id.to_string())
+            .env("TASK_NAME", &task.name)
+            .env("TASK_COMMAND", &task.command)
+            .env("RUN_ID", run.id.to_string())
+            .env("RUN_STATUS", run.status.label())
+            .env("RUN_ATTEMPT", run.attempt.to_string())
+            .env(
+                "RUN_EXIT_CODE",
+                run.exit_code.map(|c| c.to_string()).unwrap_or_default(),
+            )
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        if let Some(dirs) = directories::BaseDirs::new() {
+            process.current_dir(dirs.home_dir());
+        }
+        let mut child = process.spawn()?;
+        let stdin = child.stdin.take();
+        let output = run.output.clone();
+        thread::spawn(move || {
+            if let Some(mut stdin) = stdin {
+                let _ = stdin.write_all(output.as_bytes());
+            }
+            let _ = child.wait();
+        });
+        Ok(())
+    }
+
+    fn notify(
+        &self,
+        db: &Database,
+        task_id: TaskId,
+        run_id: Option<RunId>,
+        kind: NotificationKind,
+        message: &str,
+    ) {
+        if self.notifications.log {
+            let _ = db.insert_notification(task_id, run_id, kind, message);
+        }
+    }
+
+    /// Deal with occurrences missed while no scheduler was running: tasks
+    /// that catch up run once, with the run marked as such; the others
+    /// move on to their next occurrence.
+    pub fn catch_up(&self, db: &Database) -> Vec<String> {
+        let now = Utc::now();
+        let mut events = Vec::new();
+        for mut task in db.list_tasks().unwrap_or_default() {
+            let late = task.next_run.is_some_and(|next| next < now - MISSED_GRACE);
+            if !task.enabled
+                || !late
+                || task.pending.is_some()
+                || matches!(task.schedule, Schedule::AfterBoot { .. })
+            {
+                continue;
+            }
+            let missed = task.missed_runs(&now);
+            let message = match task.catch_up {
+                CatchUp::Once => {
+                    task.pending = Some(Trigger::CatchUp);
+                    format!("{}: missed {} runs, running once", task.name, missed)
+                }
+                CatchUp::Skip => {
+                    task.update_next_run();
+                    format!("{}: missed {} runs, skipped", task.name, missed)
+                }
+            };
+            let _ = db.update_task(&task);
+            self.notify(db, task.id, None, NotificationKind::Missed, &message);
+            events.push(format!("⏭ {}", message));
+        }
+        events
+    }
+
+    /// Kill everything still running and record it as cancelled.
+    pub fn shutdown(&mut self, db: &Database) {
+        for mut job in std::mem::take(&mut self.jobs) {
+            if job.exited.is_none() {
+                signal(&mut job.child, Signal::Kill);
+                let status = job.child.wait().ok();
+                job.exited = status.map(|s| (s, Instant::now()));
+                job.stopping = Some((RunStatus::Cancelled, Instant::now()));
+            }
+            let _ = db.update_run(&job.finish());
+        }
+    }
+}
+
+impl Job {
+    fn finish(self) -> TaskRun {
+        let mut run = self.run;
+        run.finished_at = Some(Utc::now());
+
+        let status = self.exited.map(|(status, _)| status);
+        run.exit_code = status.and_then(exit_code);
+        run.status = match (self.stopping, status) {
+            (Some((stopped, _)), _) => stopped,
+            (None, Some(status)) if status.success() => RunStatus::Success,
+            _ => RunStatus::Failed,
+        };
+
+        let output = self.output.lock().map(|o| {
+            let mut text = String::from_utf8_lossy(&o.bytes).into_owned();
+            if o.truncated {
+                text.push_str("\n[output truncated]");
+            }
+            text
+        });
+        run.output = output.unwrap_or_default();
+        run
+    }
+}
+
+/// The exit code, or 128 + the signal number for a killed process, as
+/// shells report it.
+fn exit_code(status: ExitStatus) -> Option<i32> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return Some(128 + signal);
+        }
+    }
+    status.code()
+}
+
+fn capture<R: Read + Send + 'static>(
+    mut pipe: R,
+    output: Arc<Mutex<Output>>,
+    limit: usize,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        while let Ok(n) = pipe.read(&mut buf) {
+            if n == 0 {
+                break;
+            }
+            if let Ok(mut output) = output.lock() {
+                output.push(&buf[..n], limit);
+            }
+        }
+    })
+}
+
+enum Signal {
+    Term,
+    Kill,
+}
+
+/// Signal the run's whole process group, so commands started by the shell
+/// stop too.
+fn signal(child: &mut Child, signal: Signal) {
+    #[cfg(unix)]
+    {
+        let sig = match signal {
+            Signal::Term => libc::SIGTERM,
+            Signal::Kill => libc::SIGKILL,
+        };
+        // SAFETY: kill(2) has no memory-safety preconditions.
+        unsafe {
+            libc::kill(-(child.id() as libc::pid_t), sig);
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signal;
+        let _ = child.kill();
+    }
+}
+
+/// Take the scheduler lock, or `None` if another process holds it. The
+/// lock is released when the file is closed.
+pub fn lock(path: &Path) -> std::io::Result<Option<File>> {
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::io::AsRawFd;
+        // SAFETY: the descriptor is valid for the lifetime of `file`.
+        let locked = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+        if locked != 0 {
+            return Ok(None);
+        }
+    }
+    Ok(Some(file))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn setup(timeout_secs: u64) -> (Database, Executor) {
+        let db = Database::open(Path::new(":memory:")).unwrap();
+        let config = Config {
+            scheduler: SchedulerConfig {
+                default_timeout_secs: timeout_secs,
+                max_output_bytes: 32,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        (db, Executor::new(&config))
+    }
+
+    /// Poll until nothing is running, returning the events.
+    fn drain(db: &Database, executor: &mut Executor) -> Vec<String> {
+        let mut events = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while executor.running() > 0 && Instant::now() < deadline {
+            events.extend(executor.poll(db));
+            thread::sleep(Duration::from_millis(20));
+        }
+        events
+    }
+
+    fn run_to_end(db: &Database, executor: &mut Executor, task: &mut ScheduledTask) -> TaskRun {
+        task.id = db.insert_task(task).unwrap();
+        executor.start(db, task, Trigger::Manual).unwrap();
+        drain(db, executor);
+        db.get_task_runs(task.id, 1).unwrap().remove(0)
+    }
+
+    #[test]
+    fn test_captures_output_and_exit_code() {
+        let (db, mut executor) = setup(0);
+        let schedule = crate::models::Schedule::Interval { minutes: 5 };
+
+        let mut task = ScheduledTask::new("ok", "echo out; echo err >&2", schedule.clone());
+        let run = run_to_end(&db, &mut executor, &mut task);
+        assert_eq!(run.status, RunStatus::Success);
+        assert_eq!(run.exit_code, Some(0));
+        assert!(run.output.contains("out") && run.output.contains("err"));
+
+        let mut task = ScheduledTask::new("fail", "seq 1 100; exit 3", schedule);
+        let run = run_to_end(&db, &mut executor, &mut task);
+        assert_eq!(run.status, RunStatus::Failed);
+        assert_eq!(run.exit_code, Some(3));
+        assert!(run.output.ends_with("[output truncated]"));
+        let tasks = db.list_tasks().unwrap();
+        assert!(tasks
+            .iter()
+            .all(|t| t.run_count == 1 && t.last_run.is_some()));
+    }
+
+    #[test]
+    fn test_timeout_and_cancel() {
+        let (db, mut executor) = setup(1);
+        let schedule = crate::models::Schedule::Interval { minutes: 5 };
+
+        let mut task = ScheduledTask::new("slow", "sleep 30", schedule.clone());
+        let run = run_to_end(&db, &mut executor, &mut task);
+        assert_eq!(run.status, RunStatus::Timeout);
+        assert_eq!(run.exit_code, Some(128 + libc::SIGTERM));
+
+        let mut task = ScheduledTask::new("cancelled", "sleep 30", schedule);
+        task.id = db.insert_task(&task).unwrap();
+        executor.start(&db, &mut task, Trigger::Manual).unwrap();
+        assert!(executor.is_running(task.id));
+        assert!(executor.cancel(task.id));
+        executor.shutdown(&db);
+        let run = db.get_task_runs(task.id, 1).unwrap().remove(0);
+        assert_eq!(run.status, RunStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_retry_then_notify() {
+        let (db, mut executor) = setup(0);
+        let mut task = ScheduledTask::new("flaky", "exit 1", Schedule::Interval { minutes: 60 });
+        let huge = crate::models::RetryPolicy {
+            max_retries: 1,
+            delay_secs: 10_000_000_000_000,
+            backoff: 1e300,
+            max_delay_secs: u64::MAX,
+        };
+        let max = crate::models::RetryPolicy::MAX_DELAY_SECS as i64;
+        assert_eq!(huge.delay(9).num_seconds(), max);
+
+        task.retry = crate::models::RetryPolicy {
+            max_retries: 1,
+            delay_secs: 0,
+            ..Default::default()
+        };
+        task.on_failure = Some("cat > /dev/null".to_string());
+        task.next_run = Some(Utc::now() - chrono::Duration::seconds(1));
+        task.id = db.insert_task(&task).unwrap();
+
+        executor.tick(&db);
+        let events = drain(&db, &mut executor);
+        assert!(events.iter().any(|e| e.starts_with("↻ flaky: retry 1/1")));
+        let task = db.get_task(task.id).unwrap().unwrap();
+        assert_eq!(task.attempt, 1);
+        assert_eq!(task.pending, Some(Trigger::Retry));
+
+        executor.tick(&db);
+        drain(&db, &mut executor);
+        let runs = db.get_task_runs(task.id, 5).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!((runs[0].trigger, runs[0].attempt), (Trigger::Retry, 2));
+        assert_eq!(db.get_task(task.id).unwrap().unwrap().attempt, 0);
+
+        let log = db.list_notifications(10).unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].kind, NotificationKind::Failure);
+        assert_eq!(log[0].message, "flaky failed after 2 attempts (exit 1)");
+        assert_eq!(log[1].kind, NotificationKind::Retry);
+    }
+
+    #[test]
+    fn test_dependencies_and_catch_up() {
+        let (db, mut executor) = setup(0);
+        let mut fetch = ScheduledTask::new("fetch", "true", Schedule::Interval { minutes: 60 });
+        fetch.id = db.insert_task(&fetch).unwrap();
+        let mut other = ScheduledTask::new("other", "true", Schedule::Interval { minutes: 60 });
+        other.id = db.insert_task(&other).unwrap();
+        let mut build = ScheduledTask::new("build", "true", Schedule::AfterDependencies);
+        build.depends_on = vec![fetch.id, other.id];
+        build.id = db.insert_task(&build).unwrap();
+        assert_eq!(build.next_run, None);
+
+        // Waits for both dependencies
+        executor.start(&db, &mut fetch, Trigger::Manual).unwrap();
+        assert!(drain(&db, &mut executor).iter().all(|e| !e.contains("queued")));
+        executor.start(&db, &mut other, Trigger::Manual).unwrap();
+        let events = drain(&db, &mut executor);
+        assert!(events.contains(&"→ build: queued after other".to_string()));
+
+        executor.tick(&db);
+        drain(&db, &mut executor);
+        let runs = db.get_task_runs(build.id, 5).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].trigger, Trigger::Dependency);
+        assert_eq!(db.get_task(build.id).unwrap().unwrap().next_run, None);
+
+        // Three hourly runs missed
+        let mut late = db.get_task(fetch.id).unwrap().unwrap();
+        late.next_run = Some(Utc::now() - chrono::Duration::minutes(150));
+        db.update_task(&late).unwrap();
+        late.next_run = late.next_run.map(|t| t + chrono::Duration::minutes(1));
+        late.id = other.id;
+        late.name = other.name.clone();
+        late.catch_up = CatchUp::Skip;
+        db.update_task(&late).unwrap();
+
+        let events = executor.catch_up(&db);
+        assert_eq!(
+            events,
+            vec![
+                "⏭ fetch: missed 3 runs, running once",
+                "⏭ other: missed 3 runs, skipped",
+            ]
+        );
+        let fetch = db.get_task(fetch.id).unwrap().unwrap();
+        assert_eq!(fetch.pending, Some(Trigger::CatchUp));
+        assert!(db.get_task(other.id).unwrap().unwrap().next_run > Some(Utc::now()));
+    }
+}
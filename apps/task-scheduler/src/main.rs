@@ -3,27 +3,53 @@
 //!
 //! Features:
 //! - Cron-style scheduling
+//! - Runs commands with captured output, timeouts and a concurrency limit
+//! - Imports and exports crontabs and systemd timers
 //! - One-time and recurring tasks
+//! - Retries with backoff, dependencies between tasks and failure hooks
 //! - Task history and logs
 //! - Enable/disable tasks
 
 mod app;
 mod config;
+mod cron;
+mod crontab;
 mod db;
+mod deps;
+mod executor;
 mod models;
+mod systemd;
 mod ui;
 
 use anyhow::Result;
 use app::App;
+use chrono::Local;
+use config::Config;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
+use db::Database;
+use executor::Executor;
+use models::{CatchUp, Imported, RetryPolicy, ScheduledTask};
+use std::path::PathBuf;
 use std::{io, time::Duration};
 
 fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("daemon") => return run_daemon(),
+        Some("next") => return run_next(&args[2..]),
+        Some("import") => return run_import(&args[2..]),
+        Some("export") => return run_export(&args[2..]),
+        Some("list") => return run_list(),
+        Some("set") => return run_set(&args[2..]),
+        Some("notifications") => return run_notifications(&args[2..]),
+        _ => {}
+    }
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -32,6 +58,7 @@ fn main() -> Result<()> {
 
     let mut app = App::new()?;
     let result = run_app(&mut terminal, &mut app);
+    app.shutdown();
 
     disable_raw_mode()?;
     execute!(
@@ -61,3 +88,347 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, app: &mut A
         app.check_scheduled_tasks();
     }
 }
+
+fn open_db() -> Result<Database> {
+    let db_path = Config::db_path().unwrap_or_else(|| "scheduler.db".into());
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    Ok(Database::open(&db_path)?)
+}
+
+/// `task-scheduler daemon`
+///
+/// Runs due tasks without the TUI, logging to stdout. Meant to run as a
+/// service in place of crond.
+fn run_daemon() -> Result<()> {
+    let config = Config::load();
+    let db = open_db()?;
+    let lock_path = Config::lock_path().unwrap_or_else(|| "scheduler.lock".into());
+    let Some(_lock) = executor::lock(&lock_path)? else {
+        anyhow::bail!("another scheduler is running ({} is locked)", lock_path.display());
+    };
+    db.prepare_scheduler()?;
+
+    let mut executor = Executor::new(&config);
+    println!("{} scheduler started", Local::now().format("%Y-%m-%d %H:%M:%S"));
+    let mut events = executor.catch_up(&db);
+    loop {
+        events.extend(executor.tick(&db));
+        for event in events.drain(..) {
+            println!("{} {}", Local::now().format("%Y-%m-%d %H:%M:%S"), event);
+        }
+        std::thread::sleep(Duration::from_secs(1));
+    }
+}
+
+/// `task-scheduler next <cron expression> [-n COUNT]`
+///
+/// Prints the next times a cron expression fires, in local time.
+fn run_next(args: &[String]) -> Result<()> {
+    let mut count = 5;
+    let mut fields = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-n" => {
+                count = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(|| anyhow::anyhow!("-n expects a number"))?
+            }
+            _ => fields.push(arg.as_str()),
+        }
+    }
+    if fields.is_empty() {
+        anyhow::bail!("usage: task-scheduler next <cron expression> [-n COUNT]");
+    }
+
+    let expr = cron::CronExpr::parse(&fields.join(" "))?;
+    let times = expr.upcoming(&chrono::Utc::now(), count);
+    if times.is_empty() {
+        println!("{} never fires", expr);
+    }
+    for time in times {
+        println!("{}", time.with_timezone(&Local).format("%a %Y-%m-%d %H:%M:%S %Z"));
+    }
+    Ok(())
+}
+
+/// `task-scheduler import crontab [FILE|-]` or
+/// `task-scheduler import systemd [DIR|FILE.timer]`
+///
+/// The crontab defaults to `crontab -l`, timers to the user unit directory.
+/// Tasks already present with the same command and schedule are skipped.
+fn run_import(args: &[String]) -> Result<()> {
+    let usage = "usage: task-scheduler import crontab [FILE|-] | systemd [DIR|FILE.timer]";
+    let path = args.get(1).map(PathBuf::from);
+    let imported: Imported = match args.first().map(String::as_str) {
+        Some("crontab") => {
+            let text = match &path {
+                Some(p) if p.as_os_str() == "-" => io::read_to_string(io::stdin())?,
+                Some(p) => std::fs::read_to_string(p)?,
+                None => {
+                    let output = std::process::Command::new("crontab").arg("-l").output()?;
+                    if !output.status.success() {
+                        anyhow::bail!(
+                            "crontab -l failed: {}",
+                            String::from_utf8_lossy(&output.stderr).trim()
+                        );
+                    }
+                    String::from_utf8_lossy(&output.stdout).into_owned()
+                }
+            };
+            crontab::parse(&text)
+        }
+        Some("systemd") => {
+            let path = path
+                .or_else(systemd::user_unit_dir)
+                .ok_or_else(|| anyhow::anyhow!(usage))?;
+            systemd::import(&path)?
+        }
+        _ => anyhow::bail!(usage),
+    };
+
+    let db = open_db()?;
+    let existing = db.list_tasks()?;
+    let (mut added, mut skipped) = (0, 0);
+    for task in imported.tasks {
+        let duplicate = existing.iter().any(|t| {
+            t.command == task.command && t.schedule.label() == task.schedule.label()
+        });
+        if duplicate {
+            skipped += 1;
+            continue;
+        }
+        db.insert_task(&task)?;
+        println!("  {} ({})", task.name, task.schedule.label());
+        added += 1;
+    }
+    for warning in &imported.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    println!("Imported {} tasks, {} already present", added, skipped);
+    Ok(())
+}
+
+/// `task-scheduler export crontab [FILE]` or
+/// `task-scheduler export systemd [DIR] [--force]`
+///
+/// The crontab goes to stdout unless a file is given; install it with
+/// `crontab FILE`. Units go to the user unit directory by default.
+fn run_export(args: &[String]) -> Result<()> {
+    let usage = "usage: task-scheduler export crontab [FILE] | systemd [DIR] [--force]";
+    let force = args.iter().any(|a| a == "--force");
+    let path = args[1.min(args.len())..]
+        .iter()
+        .find(|a| !a.starts_with("--"))
+        .map(PathBuf::from);
+    let config = Config::load();
+    let tasks = open_db()?.list_tasks()?;
+
+    let warnings = match args.first().map(String::as_str) {
+        Some("crontab") => {
+            let (text, warnings) = crontab::render(&tasks);
+            match &path {
+                Some(path) => {
+                    std::fs::write(path, text)?;
+                    eprintln!("Wrote {}", path.display());
+                }
+                None => print!("{}", text),
+            }
+            warnings
+        }
+        Some("systemd") => {
+            let dir = path
+                .or_else(systemd::user_unit_dir)
+                .ok_or_else(|| anyhow::anyhow!(usage))?;
+            let (written, warnings) = systemd::export(&tasks, &dir, &config.scheduler, force)?;
+            let mut enable = Vec::new();
+            for unit in &written {
+                eprintln!("Wrote {}", unit.timer.display());
+                if unit.enabled {
+                    enable.extend(unit.timer.file_name().map(|n| n.to_string_lossy().into_owned()));
+                }
+            }
+            if !enable.is_empty() {
+                eprintln!(
+                    "Enable with: systemctl --user daemon-reload && \
+                     systemctl --user enable --now {}",
+                    enable.join(" ")
+                );
+            }
+            warnings
+        }
+        _ => anyhow::bail!(usage),
+    };
+    for warning in &warnings {
+        eprintln!("warning: {}", warning);
+    }
+    Ok(())
+}
+
+/// `task-scheduler list`
+fn run_list() -> Result<()> {
+    let tasks = open_db()?.list_tasks()?;
+    for task in &tasks {
+        let next = task
+            .next_run
+            .map(|dt| dt.with_timezone(&Local).format("%m/%d %H:%M").to_string())
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:>4} {} {:<24} {:<28} next {}",
+            task.id,
+            if task.enabled { "●" } else { "○" },
+            task.name,
+            task.schedule.label(),
+            next
+        );
+        for detail in task_details(task, &tasks) {
+            println!("{:>7}{}", "", detail);
+        }
+    }
+    Ok(())
+}
+
+/// Retry, dependency, hook and catch-up settings that differ from the
+/// defaults.
+fn task_details(task: &ScheduledTask, tasks: &[ScheduledTask]) -> Vec<String> {
+    let mut details = Vec::new();
+    if !task.depends_on.is_empty() {
+        let names: Vec<&str> = task
+            .depends_on
+            .iter()
+            .filter_map(|id| tasks.iter().find(|t| t.id == *id))
+            .map(|t| t.name.as_str())
+            .collect();
+        details.push(format!("After: {}", names.join(", ")));
+    }
+    if task.retry.max_retries > 0 {
+        details.push(format!("Retry: {}", task.retry.label()));
+    }
+    if let Some(hook) = &task.on_failure {
+        details.push(format!("On failure: {}", hook));
+    }
+    if task.catch_up == CatchUp::Skip {
+        details.push("Missed runs: skipped".to_string());
+    }
+    details
+}
+
+/// A task by id or name.
+fn find_task<'a>(tasks: &'a [ScheduledTask], key: &str) -> Result<&'a ScheduledTask> {
+    if let Some(task) = tasks.iter().find(|t| key.parse() == Ok(t.id)) {
+        return Ok(task);
+    }
+    match tasks.iter().filter(|t| t.name == key).collect::<Vec<_>>()[..] {
+        [task] => Ok(task),
+        [] => anyhow::bail!("no task {}", key),
+        ref named => anyhow::bail!("{} tasks are named {}; give the id", named.len(), key),
+    }
+}
+
+/// `task-scheduler set TASK [OPTIONS]`
+///
+/// Changes a task's retry policy, dependencies, failure hook and catch-up
+/// behaviour. Tasks are given by id or name; `--after ''` and
+/// `--on-failure ''` clear.
+fn run_set(args: &[String]) -> Result<()> {
+    let usage = "usage: task-scheduler set TASK [--retries N] [--retry-delay SECS] \
+                 [--backoff FACTOR] [--max-delay SECS] [--after TASK,...] \
+                 [--on-failure COMMAND] [--catch-up once|skip]";
+    let db = open_db()?;
+    let tasks = db.list_tasks()?;
+    let (key, options) = args.split_first().ok_or_else(|| anyhow::anyhow!(usage))?;
+    let mut task = find_task(&tasks, key)?.clone();
+    let mut depends_on = None;
+
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let value = options
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("{} expects a value", option))?;
+        let number = || -> Result<u64> {
+            value
+                .parse()
+                .map_err(|_| anyhow::anyhow!("{} expects a number", option))
+        };
+        let seconds = || -> Result<u64> {
+            let max = RetryPolicy::MAX_DELAY_SECS;
+            match number()? {
+                secs if secs > max => anyhow::bail!("{} is at most {} (30 days)", option, max),
+                secs => Ok(secs),
+            }
+        };
+        match option.as_str() {
+            "--retries" => {
+                task.retry.max_retries = u32::try_from(number()?)
+                    .map_err(|_| anyhow::anyhow!("--retries is at most {}", u32::MAX))?
+            }
+            "--retry-delay" => task.retry.delay_secs = seconds()?,
+            "--max-delay" => task.retry.max_delay_secs = seconds()?,
+            "--backoff" => {
+                task.retry.backoff = value
+                    .parse()
+                    .ok()
+                    .filter(|f: &f64| *f >= 1.0)
+                    .ok_or_else(|| anyhow::anyhow!("--backoff expects a factor of 1 or more"))?
+            }
+            "--after" => {
+                let ids = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|k| !k.is_empty())
+                    .map(|k| find_task(&tasks, k).map(|t| t.id))
+                    .collect::<Result<Vec<_>>>()?;
+                deps::check(&tasks, task.id, &ids).map_err(anyhow::Error::msg)?;
+                depends_on = Some(ids);
+            }
+            "--on-failure" => task.on_failure = Some(value.clone()).filter(|v| !v.is_empty()),
+            "--catch-up" => {
+                task.catch_up = CatchUp::parse(value)
+                    .ok_or_else(|| anyhow::anyhow!("--catch-up expects once or skip"))?
+            }
+            _ => anyhow::bail!(usage),
+        }
+    }
+
+    if task.retry.max_retries == 0 {
+        task.attempt = 0;
+    }
+    db.update_task(&task)?;
+    if let Some(ids) = depends_on {
+        db.set_dependencies(task.id, &ids)?;
+        task.depends_on = ids;
+    }
+    println!("{} ({})", task.name, task.schedule.label());
+    for detail in task_details(&task, &tasks) {
+        println!("  {}", detail);
+    }
+    Ok(())
+}
+
+/// `task-scheduler notifications [-n COUNT]`
+///
+/// Prints the notification log, oldest first.
+fn run_notifications(args: &[String]) -> Result<()> {
+    let count = match args {
+        [flag, n] if flag == "-n" => n
+            .parse()
+            .map_err(|_| anyhow::anyhow!("-n expects a number"))?,
+        [] => 20,
+        _ => anyhow::bail!("usage: task-scheduler notifications [-n COUNT]"),
+    };
+    for notification in open_db()?.list_notifications(count)?.iter().rev() {
+        println!(
+            "{} {} {}",
+            notification
+                .created_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S"),
+            notification.kind.symbol(),
+            notification.message
+        );
+    }
+    Ok(())
+}
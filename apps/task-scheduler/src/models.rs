@@ -1,11 +1,15 @@
 //! Data models for task scheduler.
 
+use crate::cron::CronExpr;
 use chrono::{DateTime, Datelike, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 
 pub type TaskId = i64;
 pub type RunId = i64;
 
+/// Where [`ScheduledTask::missed_runs`] stops counting.
+const MAX_MISSED: u32 = 10_000;
+
 /// A scheduled task.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScheduledTask {
@@ -18,6 +22,16 @@ pub struct ScheduledTask {
     pub next_run: Option<DateTime<Utc>>,
     pub run_count: u32,
     pub created_at: DateTime<Utc>,
+    pub retry: RetryPolicy,
+    /// Tasks whose success queues this one
+    pub depends_on: Vec<TaskId>,
+    /// Command run when the task fails for good
+    pub on_failure: Option<String>,
+    pub catch_up: CatchUp,
+    /// Retries already made for the current occurrence
+    pub attempt: u32,
+    /// Why the task is due, when it is not its schedule
+    pub pending: Option<Trigger>,
 }
 
 impl ScheduledTask {
@@ -34,12 +48,39 @@ impl ScheduledTask {
             next_run,
             run_count: 0,
             created_at: now,
+            retry: RetryPolicy::default(),
+            depends_on: Vec::new(),
+            on_failure: None,
+            catch_up: CatchUp::default(),
+            attempt: 0,
+            pending: None,
         }
     }
 
     pub fn update_next_run(&mut self) {
         let now = Utc::now();
-        self.next_run = self.schedule.next_occurrence(&now);
+        self.next_run = match self.schedule {
+            // Once per boot, even if the scheduler started after the delay
+            Schedule::AfterBoot { seconds } => boot_time()
+                .filter(|boot| self.last_run.is_none_or(|run| run < *boot))
+                .map(|boot| boot + chrono::Duration::seconds(seconds as i64)),
+            _ => self.schedule.next_occurrence(&now),
+        };
+    }
+
+    /// Occurrences between `next_run` and `now`, counting the one at
+    /// `next_run`.
+    pub fn missed_runs(&self, now: &DateTime<Utc>) -> u32 {
+        let mut count = 0;
+        let mut at = self.next_run;
+        while let Some(time) = at.filter(|t| t <= now) {
+            count += 1;
+            if count >= MAX_MISSED {
+                break;
+            }
+            at = self.schedule.next_occurrence(&time);
+        }
+        count
     }
 
     pub fn is_due(&self) -> bool {
@@ -63,6 +104,10 @@ pub enum Schedule {
     Weekly { day: Weekday, hour: u32, minute: u32 },
     Monthly { day: u32, hour: u32, minute: u32 },
     Cron(String), // Cron expression
+    /// Once per boot, this long after it
+    AfterBoot { seconds: u64 },
+    /// Only when queued by its dependencies, or run by hand
+    AfterDependencies,
 }
 
 impl Schedule {
@@ -78,6 +123,9 @@ impl Schedule {
                 format!("Monthly on day {} at {:02}:{:02}", day, hour, minute)
             }
             Schedule::Cron(expr) => format!("Cron: {}", expr),
+            Schedule::AfterBoot { seconds: 0 } => "At boot".to_string(),
+            Schedule::AfterBoot { seconds } => format!("{}s after boot", seconds),
+            Schedule::AfterDependencies => "After dependencies".to_string(),
         }
     }
 
@@ -133,14 +181,130 @@ impl Schedule {
                 }
                 None
             }
-            Schedule::Cron(_) => {
-                // Simplified: just return 1 hour from now for demo
-                Some(*from + chrono::Duration::hours(1))
+            Schedule::Cron(expr) => CronExpr::parse(expr).ok()?.next_after(from),
+            Schedule::AfterBoot { seconds } => {
+                let at = boot_time()? + chrono::Duration::seconds(*seconds as i64);
+                if at > *from { Some(at) } else { None }
             }
+            Schedule::AfterDependencies => None,
+        }
+    }
+}
+
+/// When the system booted, from `btime` in `/proc/stat`.
+pub fn boot_time() -> Option<DateTime<Utc>> {
+    let stat = std::fs::read_to_string("/proc/stat").ok()?;
+    let secs = stat.lines().find_map(|line| line.strip_prefix("btime "))?;
+    DateTime::from_timestamp(secs.trim().parse().ok()?, 0)
+}
+
+/// Retries after a failed or timed-out run. The wait before each retry
+/// grows by `backoff` times, up to `max_delay_secs`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub delay_secs: u64,
+    pub backoff: f64,
+    pub max_delay_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            delay_secs: 60,
+            backoff: 2.0,
+            max_delay_secs: 3600,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Longest wait before a retry: 30 days.
+    pub const MAX_DELAY_SECS: u64 = 30 * 24 * 3600;
+
+    /// Wait before retry number `retry`, counting from 1.
+    pub fn delay(&self, retry: u32) -> chrono::Duration {
+        let factor = self.backoff.max(1.0).powi(retry.saturating_sub(1).min(64) as i32);
+        let secs = (self.delay_secs as f64 * factor)
+            .min(self.max_delay_secs.min(Self::MAX_DELAY_SECS) as f64);
+        chrono::Duration::seconds(secs as i64)
+    }
+
+    pub fn label(&self) -> String {
+        match self.max_retries {
+            0 => "No retries".to_string(),
+            n if self.backoff > 1.0 => {
+                format!("{} retries, {}s ×{}", n, self.delay_secs, self.backoff)
+            }
+            n => format!("{} retries, {}s apart", n, self.delay_secs),
+        }
+    }
+}
+
+/// What to do about occurrences missed while no scheduler was running.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CatchUp {
+    /// Run once for all of them
+    #[default]
+    Once,
+    /// Wait for the next occurrence
+    Skip,
+}
+
+impl CatchUp {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "once" => Some(CatchUp::Once),
+            "skip" => Some(CatchUp::Skip),
+            _ => None,
         }
     }
 }
 
+/// Why a run was started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Trigger {
+    #[default]
+    Schedule,
+    Manual,
+    Retry,
+    Dependency,
+    CatchUp,
+}
+
+impl Trigger {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Trigger::Schedule => "schedule",
+            Trigger::Manual => "manual",
+            Trigger::Retry => "retry",
+            Trigger::Dependency => "dependency",
+            Trigger::CatchUp => "catch-up",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "Schedule" => Trigger::Schedule,
+            "Manual" => Trigger::Manual,
+            "Retry" => Trigger::Retry,
+            "Dependency" => Trigger::Dependency,
+            "CatchUp" => Trigger::CatchUp,
+            _ => return None,
+        })
+    }
+}
+
+/// Tasks read from another scheduler, with notes on anything that could
+/// not be carried over.
+#[derive(Debug, Default)]
+pub struct Imported {
+    pub tasks: Vec<ScheduledTask>,
+    pub warnings: Vec<String>,
+}
+
 /// A task execution record.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskRun {
@@ -151,6 +315,9 @@ pub struct TaskRun {
     pub status: RunStatus,
     pub exit_code: Option<i32>,
     pub output: String,
+    pub trigger: Trigger,
+    /// 1 for the first try, 2 for the first retry, ...
+    pub attempt: u32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -183,3 +350,45 @@ impl RunStatus {
         }
     }
 }
+
+/// An entry in the notification log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: i64,
+    pub task_id: TaskId,
+    pub run_id: Option<RunId>,
+    pub created_at: DateTime<Utc>,
+    pub kind: NotificationKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NotificationKind {
+    /// Failed with no retries left
+    Failure,
+    Retry,
+    /// Occurrences missed while no scheduler was running
+    Missed,
+    /// A failure hook could not be started
+    HookError,
+}
+
+impl NotificationKind {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            NotificationKind::Failure => "✗",
+            NotificationKind::Retry => "↻",
+            NotificationKind::Missed => "⏭",
+            NotificationKind::HookError => "!",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "Retry" => NotificationKind::Retry,
+            "Missed" => NotificationKind::Missed,
+            "HookError" => NotificationKind::HookError,
+            _ => NotificationKind::Failure,
+        }
+    }
+}
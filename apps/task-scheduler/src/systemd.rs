@@ -0,0 +1,770 @@
+//! systemd timer units.
+//!
+//! Import reads `.timer` units with their `.service`: `OnCalendar=` becomes
+//! a cron task (one per value), `OnUnitActiveSec=`, `OnUnitInactiveSec=`
+//! or `OnActiveSec=` an interval, and `OnBootSec=` or `OnStartupSec=` a
+//! boot task. A timer with several triggers becomes one task per trigger.
+//! The command is the service's `ExecStart=` lines, chained with `&&`,
+//! after its `Environment=` and `WorkingDirectory=`.
+//!
+//! Export writes a `<name>.timer` and a oneshot `<name>.service` per task,
+//! running the command through the configured shell from the home
+//! directory, as the scheduler does.
+
+use crate::config::SchedulerConfig;
+use crate::cron::CronExpr;
+use crate::crontab::shell_quote;
+use crate::models::{Imported, Schedule, ScheduledTask};
+use chrono::Weekday;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Default place for user units.
+pub fn user_unit_dir() -> Option<PathBuf> {
+    directories::BaseDirs::new().map(|d| d.config_dir().join("systemd").join("user"))
+}
+
+/// A parsed unit file: values per `(section, key)`, in order.
+#[derive(Debug, Default)]
+struct Unit {
+    values: HashMap<(String, String), Vec<String>>,
+}
+
+impl Unit {
+    fn parse(text: &str) -> Self {
+        let mut unit = Unit::default();
+        let mut section = String::new();
+        let mut pending = String::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if pending.is_empty() && (line.starts_with('#') || line.starts_with(';')) {
+                continue;
+            }
+            if let Some(continued) = line.strip_suffix('\\') {
+                pending.push_str(continued);
+                pending.push(' ');
+                continue;
+            }
+            let line = std::mem::take(&mut pending) + line;
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.to_string();
+            } else if let Some((key, value)) = line.split_once('=') {
+                let values = unit
+                    .values
+                    .entry((section.clone(), key.trim().to_string()))
+                    .or_default();
+                // An empty assignment resets the list
+                match value.trim() {
+                    "" => values.clear(),
+                    value => values.push(value.to_string()),
+                }
+            }
+        }
+        unit
+    }
+
+    fn all(&self, section: &str, key: &str) -> &[String] {
+        self.values
+            .get(&(section.to_string(), key.to_string()))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.all(section, key).last().map(String::as_str)
+    }
+}
+
+/// Import `path`: a `.timer` file, or every timer in a directory.
+pub fn import(path: &Path) -> std::io::Result<Imported> {
+    let mut timers = Vec::new();
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "timer") {
+                timers.push(path);
+            }
+        }
+        timers.sort();
+    } else {
+        timers.push(path.to_path_buf());
+    }
+
+    let mut imported = Imported::default();
+    for timer in timers {
+        let name = timer
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("timer")
+            .to_string();
+        if name.ends_with('@') {
+            imported
+                .warnings
+                .push(format!("{}: template units are not supported", name));
+            continue;
+        }
+        match import_timer(&timer, &name) {
+            Ok(mut tasks) => imported.tasks.append(&mut tasks),
+            Err(e) => imported.warnings.push(format!("{}: {}", name, e)),
+        }
+    }
+    Ok(imported)
+}
+
+fn import_timer(path: &Path, name: &str) -> Result<Vec<ScheduledTask>, String> {
+    let timer = Unit::parse(&std::fs::read_to_string(path).map_err(|e| e.to_string())?);
+    let service_name = timer
+        .get("Timer", "Unit")
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}.service", name));
+    let service_path = path.with_file_name(&service_name);
+    let service = Unit::parse(
+        &std::fs::read_to_string(&service_path).map_err(|e| format!("{}: {}", service_name, e))?,
+    );
+    let command = service_command(&service, name)?;
+
+    let mut schedules = Vec::new();
+    for calendar in timer.all("Timer", "OnCalendar") {
+        schedules.push(Schedule::Cron(calendar_to_cron(calendar)?));
+    }
+    let every = ["OnUnitActiveSec", "OnUnitInactiveSec", "OnActiveSec"]
+        .iter()
+        .find_map(|key| timer.get("Timer", key));
+    if let Some(span) = every {
+        let seconds = parse_timespan(span).ok_or(format!("invalid time span: {}", span))?;
+        let minutes = seconds.div_ceil(60).max(1) as u32;
+        schedules.push(Schedule::Interval { minutes });
+    }
+    // A boot delay before a repeating trigger still runs the service once
+    // after boot, so it gets a task of its own
+    let boot = ["OnBootSec", "OnStartupSec"]
+        .iter()
+        .find_map(|key| timer.get("Timer", key));
+    if let Some(span) = boot {
+        let seconds = parse_timespan(span).ok_or(format!("invalid time span: {}", span))?;
+        schedules.push(Schedule::AfterBoot { seconds });
+    }
+    if schedules.is_empty() {
+        return Err("no OnCalendar, OnUnitActiveSec or OnBootSec trigger".to_string());
+    }
+
+    Ok(schedules
+        .into_iter()
+        .enumerate()
+        .map(|(i, schedule)| {
+            let name = if i == 0 {
+                name.to_string()
+            } else {
+                format!("{} #{}", name, i + 1)
+            };
+            ScheduledTask::new(&name, &command, schedule)
+        })
+        .collect())
+}
+
+/// The service's commands as one shell command line.
+fn service_command(service: &Unit, name: &str) -> Result<String, String> {
+    let mut commands = Vec::new();
+    for exec in service.all("Service", "ExecStart") {
+        let exec = expand_specifiers(exec.trim_start_matches(['-', '@', ':', '+', '!']), name);
+        let words = split_words(&exec);
+        // Our own export wraps the command in `<shell> -c`
+        commands.push(match words.as_slice() {
+            [shell, flag, command] if flag.raw == "-c" && shell.raw.ends_with("sh") => {
+                command.raw.clone()
+            }
+            _ => words.iter().map(Word::shell).collect::<Vec<_>>().join(" "),
+        });
+    }
+    if commands.is_empty() {
+        return Err("service has no ExecStart".to_string());
+    }
+
+    let mut prefix = String::new();
+    for environment in service.all("Service", "Environment") {
+        for assignment in split_words(environment) {
+            if let Some((key, value)) = assignment.raw.split_once('=') {
+                prefix.push_str(&format!("export {}={}; ", key, shell_quote(value)));
+            }
+        }
+    }
+    if let Some(dir) = service.get("Service", "WorkingDirectory") {
+        let dir = expand_specifiers(dir.trim_start_matches('-'), name);
+        if dir != "~" {
+            prefix.push_str(&format!("cd {} && ", shell_quote(&dir)));
+        }
+    }
+    Ok(prefix + &commands.join(" && "))
+}
+
+fn expand_specifiers(s: &str, name: &str) -> String {
+    let home = directories::BaseDirs::new()
+        .map(|d| d.home_dir().to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('h') => out.push_str(&home),
+            Some('n') => out.push_str(&format!("{}.service", name)),
+            Some('N') | Some('p') => out.push_str(name),
+            Some('u') => out.push_str(&std::env::var("USER").unwrap_or_default()),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// A word of an `ExecStart=` line.
+#[derive(Debug, Default)]
+struct Word {
+    /// The text systemd passes on, with `$$` as `$`
+    raw: String,
+    /// The same for a shell, where `$VAR` still expands but `$$` does not
+    quoted: String,
+}
+
+impl Word {
+    fn push(&mut self, c: char, literal_dollar: bool) {
+        self.raw.push(c);
+        if matches!(c, '"' | '\\' | '`') || literal_dollar {
+            self.quoted.push('\\');
+        }
+        self.quoted.push(c);
+    }
+
+    fn shell(&self) -> String {
+        if self.raw.contains('$') || shell_quote(&self.raw) != self.raw {
+            format!("\"{}\"", self.quoted)
+        } else {
+            self.raw.clone()
+        }
+    }
+}
+
+/// Split a command line the way systemd does: whitespace-separated words,
+/// with single or double quotes and C-style escapes.
+fn split_words(s: &str) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut word: Option<Word> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let escaped = match chars.next() {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some(other) => other,
+                    None => break,
+                };
+                word.get_or_insert_with(Word::default).push(escaped, false);
+            }
+            '$' if chars.clone().next() == Some('$') => {
+                chars.next();
+                word.get_or_insert_with(Word::default).push('$', true);
+            }
+            c if Some(c) == quote => quote = None,
+            '"' | '\'' if quote.is_none() => {
+                quote = Some(c);
+                word.get_or_insert_with(Word::default);
+            }
+            c if c.is_whitespace() && quote.is_none() => words.extend(word.take()),
+            c => word.get_or_insert_with(Word::default).push(c, false),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+/// Seconds in a systemd time span such as `90`, `5min` or `1h 30min`.
+pub fn parse_timespan(span: &str) -> Option<u64> {
+    let mut total = 0.0;
+    let mut rest = span.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = rest[number_len..].trim_start();
+        let unit_len = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let scale = match &rest[..unit_len] {
+            "" | "s" | "sec" | "second" | "seconds" => 1.0,
+            "ms" | "msec" => 0.001,
+            "us" | "usec" | "µs" => 0.000_001,
+            "m" | "min" | "minute" | "minutes" => 60.0,
+            "h" | "hr" | "hour" | "hours" => 3600.0,
+            "d" | "day" | "days" => 86400.0,
+            "w" | "week" | "weeks" => 7.0 * 86400.0,
+            "M" | "month" | "months" => 30.44 * 86400.0,
+            "y" | "year" | "years" => 365.25 * 86400.0,
+            _ => return None,
+        };
+        total += number * scale;
+        rest = rest[unit_len..].trim_start();
+    }
+    Some(total.round() as u64)
+}
+
+fn format_timespan(seconds: u64) -> String {
+    match seconds {
+        s if s > 0 && s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s > 0 && s % 60 == 0 => format!("{}min", s / 60),
+        s => format!("{}s", s),
+    }
+}
+
+/// Convert an `OnCalendar=` value to a cron expression.
+///
+/// Supports the shorthands (`daily`, `weekly`, ...) and
+/// `[weekdays] [*-]month-day [hour:minute[:second]] [zone]` with lists,
+/// `a..b` ranges, `a/n` repetition and `~` for days from the month's end.
+/// Specific years, and weekdays combined with days of the month, have no
+/// cron equivalent.
+pub fn calendar_to_cron(spec: &str) -> Result<String, String> {
+    let expanded = match spec.trim().to_ascii_lowercase().as_str() {
+        "minutely" => "*-*-* *:*:00",
+        "hourly" => "*-*-* *:00:00",
+        "daily" => "*-*-* 00:00:00",
+        "weekly" => "Mon *-*-* 00:00:00",
+        "monthly" => "*-*-01 00:00:00",
+        "quarterly" => "*-01,04,07,10-01 00:00:00",
+        "semiannually" | "semi-annually" => "*-01,07-01 00:00:00",
+        "yearly" | "annually" => "*-01-01 00:00:00",
+        _ => spec.trim(),
+    };
+
+    let mut words: Vec<&str> = expanded.split_whitespace().collect();
+    let mut zone = None;
+    if let Some(last) = words.last() {
+        if !last.contains(':') && last.parse::<chrono_tz::Tz>().is_ok() {
+            zone = words.pop();
+        }
+    }
+    let mut weekdays = "*".to_string();
+    if let Some(first) = words.first() {
+        if first.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            weekdays = convert_weekdays(first)?;
+            words.remove(0);
+        }
+    }
+    let (mut date, mut time) = ("*-*-*", "00:00:00");
+    for word in words {
+        if word.contains(':') {
+            time = word;
+        } else {
+            date = word;
+        }
+    }
+
+    // [year-]month-day or [year-]month~day
+    let (ym, day, from_end) = match date.rsplit_once('~') {
+        Some((ym, day)) => (ym, day, true),
+        None => date
+            .rsplit_once('-')
+            .map(|(ym, d)| (ym, d, false))
+            .ok_or("invalid date")?,
+    };
+    let month = match ym.split_once('-') {
+        Some(("*", month)) => month,
+        Some(_) => return Err("specific years are not supported".to_string()),
+        None => ym,
+    };
+    let day = if from_end {
+        match day.parse::<u32>() {
+            Ok(1) => "L".to_string(),
+            Ok(n) if n > 1 => format!("L-{}", n - 1),
+            _ => return Err(format!("unsupported day: ~{}", day)),
+        }
+    } else {
+        convert_values(day)
+    };
+    if day != "*" && weekdays != "*" {
+        return Err("weekdays combined with days of the month".to_string());
+    }
+
+    let mut parts = time.split(':');
+    let hour = convert_values(parts.next().unwrap_or("0"));
+    let minute = convert_values(parts.next().unwrap_or("0"));
+    let second = parts.next().unwrap_or("0");
+    if second.contains('.') {
+        return Err("fractional seconds are not supported".to_string());
+    }
+    let second = convert_values(second);
+
+    let mut expr = format!(
+        "{} {} {} {} {}",
+        minute,
+        hour,
+        day,
+        convert_values(month),
+        weekdays
+    );
+    if second != "0" && second != "00" {
+        expr = format!("{} {}", second, expr);
+    }
+    if let Some(zone) = zone {
+        expr = format!("CRON_TZ={} {}", zone, expr);
+    }
+    CronExpr::parse(&expr).map_err(|e| format!("{}: {}", spec, e))?;
+    Ok(expr)
+}
+
+fn convert_values(component: &str) -> String {
+    component.replace("..", "-")
+}
+
+/// `Mon..Fri,Sun` to `1-5,7`. systemd weeks start on Monday, so a range
+/// ending on Sunday is 1-7 in cron terms.
+fn convert_weekdays(spec: &str) -> Result<String, String> {
+    let number = |name: &str| -> Result<u32, String> {
+        let name = name.to_ascii_lowercase();
+        ["mon", "tue", "wed", "thu", "fri", "sat", "sun"]
+            .iter()
+            .position(|day| name.starts_with(day))
+            .map(|i| i as u32 + 1)
+            .ok_or_else(|| format!("unknown weekday: {}", name))
+    };
+    let mut items = Vec::new();
+    for item in spec.split(',') {
+        items.push(match item.split_once("..") {
+            Some((a, b)) => format!("{}-{}", number(a)?, number(b)?),
+            None => number(item)?.to_string(),
+        });
+    }
+    Ok(items.join(","))
+}
+
+/// A timer written by [`export`].
+pub struct Written {
+    pub timer: PathBuf,
+    /// Whether the task was enabled, so the timer should be too
+    pub enabled: bool,
+}
+
+/// Write a timer and service unit per task into `dir`. Existing files are
+/// only replaced with `force`. Returns the timers written and a warning for
+/// each task left out.
+pub fn export(
+    tasks: &[ScheduledTask],
+    dir: &Path,
+    config: &SchedulerConfig,
+    force: bool,
+) -> std::io::Result<(Vec<Written>, Vec<String>)> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    let mut warnings = Vec::new();
+    let mut used: Vec<String> = Vec::new();
+
+    for task in tasks {
+        let triggers = match timer_triggers(&task.schedule) {
+            Ok(triggers) => triggers,
+            Err(e) => {
+                warnings.push(format!("{}: {}", task.name, e));
+                continue;
+            }
+        };
+
+        let base = unit_name(&task.name);
+        let mut name = base.clone();
+        let mut n = 2;
+        while used.contains(&name) {
+            name = format!("{}-{}", base, n);
+            n += 1;
+        }
+        used.push(name.clone());
+
+        let timer_path = dir.join(format!("{}.timer", name));
+        let service_path = dir.join(format!("{}.service", name));
+        if !force && (timer_path.exists() || service_path.exists()) {
+            warnings.push(format!(
+                "{}: {} exists (use --force to replace)",
+                task.name,
+                timer_path.display()
+            ));
+            continue;
+        }
+
+        let mut timer = format!("[Unit]\nDescription={} (task-scheduler)\n", task.name);
+        if !task.enabled {
+            timer.push_str("# Disabled in task-scheduler\n");
+        }
+        timer.push_str("\n[Timer]\n");
+        for trigger in &triggers {
+            timer.push_str(trigger);
+            timer.push('\n');
+        }
+        if triggers.iter().any(|t| t.starts_with("OnCalendar=")) {
+            timer.push_str("Persistent=true\n");
+        }
+        timer.push_str("\n[Install]\nWantedBy=timers.target\n");
+
+        let mut service = format!(
+            "[Unit]\nDescription={}\n\n[Service]\nType=oneshot\nWorkingDirectory=~\n\
+             ExecStart={} -c {}\n",
+            task.name,
+            config.shell,
+            exec_quote(&task.command)
+        );
+        if config.default_timeout_secs > 0 {
+            service.push_str(&format!(
+                "TimeoutStartSec={}\n",
+                config.default_timeout_secs
+            ));
+        }
+
+        std::fs::write(&service_path, service)?;
+        std::fs::write(&timer_path, timer)?;
+        written.push(Written {
+            timer: timer_path,
+            enabled: task.enabled,
+        });
+    }
+    Ok((written, warnings))
+}
+
+fn timer_triggers(schedule: &Schedule) -> Result<Vec<String>, String> {
+    let calendar = |values: Vec<String>| {
+        values
+            .into_iter()
+            .map(|v| format!("OnCalendar={}", v))
+            .collect()
+    };
+    Ok(match schedule {
+        Schedule::Cron(expr) => calendar(
+            CronExpr::parse(expr)
+                .and_then(|e| e.to_calendar())
+                .map_err(|e| e.to_string())?,
+        ),
+        Schedule::Interval { minutes } => vec![
+            format!("OnActiveSec={}", format_timespan(*minutes as u64 * 60)),
+            format!("OnUnitActiveSec={}", format_timespan(*minutes as u64 * 60)),
+        ],
+        Schedule::Daily { hour, minute } => {
+            calendar(vec![format!("*-*-* {:02}:{:02}:00 UTC", hour, minute)])
+        }
+        Schedule::Weekly { day, hour, minute } => calendar(vec![format!(
+            "{} *-*-* {:02}:{:02}:00 UTC",
+            weekday_name(*day),
+            hour,
+            minute
+        )]),
+        Schedule::Monthly { day, hour, minute } => calendar(vec![format!(
+            "*-*-{:02} {:02}:{:02}:00 UTC",
+            day, hour, minute
+        )]),
+        Schedule::Once(at) => calendar(vec![at.format("%Y-%m-%d %H:%M:%S UTC").to_string()]),
+        Schedule::AfterBoot { seconds } => {
+            vec![format!("OnBootSec={}", format_timespan(*seconds))]
+        }
+        Schedule::AfterDependencies => {
+            return Err("tasks run after dependencies have no timer equivalent".to_string())
+        }
+    })
+}
+
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Mon",
+        Weekday::Tue => "Tue",
+        Weekday::Wed => "Wed",
+        Weekday::Thu => "Thu",
+        Weekday::Fri => "Fri",
+        Weekday::Sat => "Sat",
+        Weekday::Sun => "Sun",
+    }
+}
+
+/// A unit file name for a task: lower case, with runs of anything else
+/// than letters, digits, `_` and `.` as `-`.
+fn unit_name(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    let out = out.trim_end_matches('-').to_string();
+    if out.is_empty() {
+        "task".to_string()
+    } else {
+        out
+    }
+}
+
+/// Double-quote an `ExecStart=` argument, escaping what systemd would
+/// otherwise interpret.
+fn exec_quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '%' => out.push_str("%%"),
+            '$' => out.push_str("$$"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calendar_to_cron() {
+        assert_eq!(calendar_to_cron("daily").unwrap(), "00 00 * * *");
+        assert_eq!(calendar_to_cron("weekly").unwrap(), "00 00 * * 1");
+        assert_eq!(
+            calendar_to_cron("Mon..Fri *-*-* 09:30").unwrap(),
+            "30 09 * * 1-5"
+        );
+        assert_eq!(
+            calendar_to_cron("Sat..Sun 10:00 Europe/Berlin").unwrap(),
+            "CRON_TZ=Europe/Berlin 00 10 * * 6-7"
+        );
+        assert_eq!(
+            calendar_to_cron("*-*-1,15 *:0/15:30").unwrap(),
+            "30 0/15 * 1,15 * *"
+        );
+        assert_eq!(calendar_to_cron("*-02~03 12:00").unwrap(), "00 12 L-2 02 *");
+        assert!(calendar_to_cron("2025-06-01 12:00").is_err());
+        assert!(calendar_to_cron("Fri *-*-13").is_err());
+    }
+
+    #[test]
+    fn test_timespan() {
+        assert_eq!(parse_timespan("90"), Some(90));
+        assert_eq!(parse_timespan("5min"), Some(300));
+        assert_eq!(parse_timespan("1h 30min"), Some(5400));
+        assert_eq!(parse_timespan("1d"), Some(86400));
+        assert_eq!(parse_timespan("soon"), None);
+    }
+
+    #[test]
+    fn test_boot_and_interval_triggers() {
+        let dir = std::env::temp_dir().join(format!("systemd-boot-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("poll.timer"),
+            "[Timer]\nOnBootSec=2min\nOnUnitActiveSec=15min\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("poll.service"), "[Service]\nExecStart=fetch\n").unwrap();
+
+        let imported = import(&dir).unwrap();
+        assert!(imported.warnings.is_empty(), "{:?}", imported.warnings);
+        let schedules: Vec<_> = imported
+            .tasks
+            .iter()
+            .map(|t| (t.name.as_str(), &t.schedule))
+            .collect();
+        assert!(matches!(
+            schedules.as_slice(),
+            [
+                ("poll", Schedule::Interval { minutes: 15 }),
+                ("poll #2", Schedule::AfterBoot { seconds: 120 }),
+            ]
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = std::env::temp_dir().join(format!("systemd-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("backup.timer"),
+            "[Unit]\nDescription=Backup\n\n[Timer]\nOnCalendar=Mon..Fri 02:30\n\
+             OnCalendar=Sat 12:00\nPersistent=true\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("backup.service"),
+            "[Service]\nType=oneshot\nEnvironment=\"DEST=/mnt/backup dir\" LEVEL=3\n\
+             ExecStart=-/usr/bin/rsync -a %h/docs \"$${DEST}\"\nExecStart=/usr/bin/sync\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("sync.timer"),
+            "[Timer]\nOnBootSec=5min\nOnUnitActiveSec=1h\nUnit=mail-sync.service\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("mail-sync.service"),
+            "[Service]\nExecStart=mbsync \\\n  --all\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("warmup.timer"), "[Timer]\nOnBootSec=30s\n").unwrap();
+        std::fs::write(dir.join("warmup.service"), "[Service]\nExecStart=true\n").unwrap();
+
+        let imported = import(&dir).unwrap();
+        assert!(imported.warnings.is_empty(), "{:?}", imported.warnings);
+        let tasks = imported.tasks;
+        assert_eq!(tasks.len(), 5);
+        assert_eq!(tasks[0].name, "backup");
+        assert_eq!(tasks[1].name, "backup #2");
+        let home = directories::BaseDirs::new().unwrap();
+        assert_eq!(
+            tasks[0].command,
+            format!(
+                "export DEST='/mnt/backup dir'; export LEVEL=3; \
+                 /usr/bin/rsync -a {}/docs \"\\${{DEST}}\" && /usr/bin/sync",
+                home.home_dir().display()
+            )
+        );
+        assert!(matches!(&tasks[1].schedule, Schedule::Cron(e) if e == "00 12 * * 6"));
+        assert!(matches!(
+            tasks[2].schedule,
+            Schedule::Interval { minutes: 60 }
+        ));
+        assert_eq!(tasks[2].command, "mbsync --all");
+        assert!(matches!(
+            tasks[3].schedule,
+            Schedule::AfterBoot { seconds: 300 }
+        ));
+        assert_eq!(tasks[3].name, "sync #2");
+        assert!(matches!(
+            tasks[4].schedule,
+            Schedule::AfterBoot { seconds: 30 }
+        ));
+
+        let out = dir.join("out");
+        let config = SchedulerConfig::default();
+        let (written, warnings) = export(&tasks, &out, &config, false).unwrap();
+        assert_eq!(written.len(), 5);
+        assert!(warnings.is_empty());
+        let timer = std::fs::read_to_string(out.join("backup-2.timer")).unwrap();
+        assert!(timer.contains("OnCalendar=Sat *-*-* 12:00:00\nPersistent=true\n"));
+
+        let again = import(&out).unwrap();
+        assert!(again.warnings.is_empty(), "{:?}", again.warnings);
+        for task in &tasks {
+            let name = unit_name(&task.name);
+            let copy = again.tasks.iter().find(|t| t.name == name).unwrap();
+            assert_eq!(copy.command, task.command);
+        }
+
+        let (written, warnings) = export(&tasks, &out, &config, false).unwrap();
+        assert!(written.is_empty());
+        assert_eq!(warnings.len(), 5);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
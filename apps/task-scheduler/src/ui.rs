@@ -1,6 +1,7 @@
 //! UI rendering for task scheduler.
 
 use crate::app::{App, InputMode, Pane};
+use crate::models::{Notification, NotificationKind, Trigger};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -26,11 +27,22 @@ pub fn draw(f: &mut Frame, app: &App) {
     if app.show_help {
         draw_help(f);
     }
+
+    if let Some(notifications) = &app.notifications {
+        draw_notifications(f, notifications);
+    }
 }
 
 fn draw_header(f: &mut Frame, app: &App, area: Rect) {
     let enabled = app.tasks.iter().filter(|t| t.enabled).count();
-    let header = Paragraph::new(format!(" Task Scheduler | {} tasks ({} enabled) ", app.tasks.len(), enabled))
+    let mut text = format!(" Task Scheduler | {} tasks ({} enabled) ", app.tasks.len(), enabled);
+    if app.executor.running() > 0 {
+        text.push_str(&format!("| {} running ", app.executor.running()));
+    }
+    if app.lock.is_none() {
+        text.push_str("| scheduled by another process ");
+    }
+    let header = Paragraph::new(text)
         .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD));
     f.render_widget(header, area);
 }
@@ -53,10 +65,22 @@ fn draw_tasks(f: &mut Frame, app: &App, area: Rect) {
     };
 
     let items: Vec<ListItem> = app.tasks.iter().enumerate().map(|(i, task)| {
-        let status = if task.enabled { "●" } else { "○" };
+        let running = app.executor.is_running(task.id);
+        let status = if running { "◔" } else if task.enabled { "●" } else { "○" };
         let next = task.next_run
             .map(|dt| dt.format("%m/%d %H:%M").to_string())
             .unwrap_or_else(|| "-".to_string());
+        let mut detail = format!("  {} | Next: {}", task.schedule.label(), next);
+        if !task.depends_on.is_empty() {
+            let names: Vec<&str> = task.depends_on.iter()
+                .filter_map(|id| app.tasks.iter().find(|t| t.id == *id))
+                .map(|t| t.name.as_str())
+                .collect();
+            detail.push_str(&format!(" | After: {}", names.join(", ")));
+        }
+        if task.retry.max_retries > 0 {
+            detail.push_str(&format!(" | ↻ {}", task.retry.max_retries));
+        }
 
         let mut style = Style::default();
         if i == app.selected_index {
@@ -68,14 +92,17 @@ fn draw_tasks(f: &mut Frame, app: &App, area: Rect) {
 
         ListItem::new(vec![
             Line::from(vec![
-                Span::styled(status, if task.enabled { Style::default().fg(Color::Green) } else { Style::default().fg(Color::DarkGray) }),
+                Span::styled(status, if running {
+                    Style::default().fg(Color::Yellow)
+                } else if task.enabled {
+                    Style::default().fg(Color::Green)
+                } else {
+                    Style::default().fg(Color::DarkGray)
+                }),
                 Span::raw(" "),
                 Span::styled(&task.name, style.add_modifier(Modifier::BOLD)),
             ]),
-            Line::from(Span::styled(
-                format!("  {} | Next: {}", task.schedule.label(), next),
-                Style::default().fg(Color::DarkGray),
-            )),
+            Line::from(Span::styled(detail, Style::default().fg(Color::DarkGray))),
         ])
     }).collect();
 
@@ -95,29 +122,62 @@ fn draw_history(f: &mut Frame, app: &App, area: Rect) {
         .map(|t| format!(" History: {} ", t.name))
         .unwrap_or_else(|| " History ".to_string());
 
-    let items: Vec<ListItem> = app.task_runs.iter().map(|run| {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(area);
+
+    let items: Vec<ListItem> = app.task_runs.iter().enumerate().map(|(i, run)| {
         let status_color = match run.status {
             crate::models::RunStatus::Success => Color::Green,
             crate::models::RunStatus::Failed => Color::Red,
             crate::models::RunStatus::Running => Color::Yellow,
+            crate::models::RunStatus::Timeout => Color::Magenta,
             _ => Color::DarkGray,
         };
+        let duration = run.finished_at
+            .map(|end| format!("{}s", (end - run.started_at).num_seconds()))
+            .unwrap_or_default();
+        let mut exit = run.exit_code.map(|c| format!("exit {}", c)).unwrap_or_default();
+        match run.trigger {
+            Trigger::Schedule => {}
+            Trigger::Retry => exit.push_str(&format!("  retry {}", run.attempt - 1)),
+            trigger => exit.push_str(&format!("  {}", trigger.label())),
+        }
+        let style = if i == app.run_index && app.pane == Pane::History {
+            Style::default().bg(Color::DarkGray)
+        } else {
+            Style::default()
+        };
 
         ListItem::new(Line::from(vec![
             Span::styled(run.status.symbol(), Style::default().fg(status_color)),
             Span::raw(" "),
-            Span::raw(run.started_at.format("%m/%d %H:%M").to_string()),
-        ]))
+            Span::raw(run.started_at.format("%m/%d %H:%M:%S").to_string()),
+            Span::styled(
+                format!("  {:>5}  {}", duration, exit),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])).style(style)
     }).collect();
 
     let list = List::new(items)
         .block(Block::default().borders(Borders::ALL).title(title).border_style(border_style));
-    f.render_widget(list, area);
+    f.render_widget(list, chunks[0]);
+
+    // Output of the selected run, scrolled to the end
+    let output = app.task_runs.get(app.run_index).map(|r| r.output.as_str()).unwrap_or("");
+    let height = chunks[1].height.saturating_sub(2) as usize;
+    let lines: Vec<&str> = output.lines().collect();
+    let tail = lines[lines.len().saturating_sub(height)..].join("\n");
+    let output = Paragraph::new(tail)
+        .block(Block::default().borders(Borders::ALL).title(" Output ").border_style(border_style));
+    f.render_widget(output, chunks[1]);
 }
 
 fn draw_status_bar(f: &mut Frame, app: &App, area: Rect) {
     let msg = app.message.clone().unwrap_or_else(|| {
-        "? help | n new | e enable/disable | r run now | d delete".to_string()
+        "? help | n new | e enable/disable | r run now | s stop | d delete | L log".to_string()
     });
     let status = Paragraph::new(msg).block(Block::default().borders(Borders::ALL));
     f.render_widget(status, area);
@@ -130,7 +190,10 @@ fn draw_input_dialog(f: &mut Frame, app: &App) {
     let title = match app.input_mode {
         InputMode::TaskName => " Task Name ",
         InputMode::TaskCommand => " Command ",
-        InputMode::ScheduleType => " Schedule Type (1=interval, 2=daily) ",
+        InputMode::ScheduleType => " Schedule Type (1=interval, 2=daily, 3=cron) ",
+        InputMode::ScheduleValue if app.new_task.schedule_type == 3 => {
+            " Cron Expression (e.g. 0 9 * * MON-FRI) "
+        }
         InputMode::ScheduleValue => " Interval (minutes) ",
         InputMode::None => " Input ",
     };
@@ -150,18 +213,47 @@ fn draw_help(f: &mut Frame) {
         Line::from("  j/k   Move selection"),
         Line::from("  Tab   Switch pane"),
         Line::from("  Enter View history"),
+        Line::from("  j/k   Select run (history pane)"),
         Line::from(""),
         Line::from(Span::styled("Actions", Style::default().add_modifier(Modifier::BOLD))),
         Line::from("  n     New task"),
         Line::from("  e     Enable/disable"),
         Line::from("  r     Run now"),
+        Line::from("  s     Stop running task"),
         Line::from("  d     Delete"),
+        Line::from("  L     Notification log"),
         Line::from("  q     Quit"),
     ])
     .block(Block::default().borders(Borders::ALL).title(" Help "));
     f.render_widget(help, area);
 }
 
+fn draw_notifications(f: &mut Frame, notifications: &[Notification]) {
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = notifications.iter().map(|n| {
+        let color = match n.kind {
+            NotificationKind::Failure | NotificationKind::HookError => Color::Red,
+            NotificationKind::Retry => Color::Yellow,
+            NotificationKind::Missed => Color::DarkGray,
+        };
+        ListItem::new(Line::from(vec![
+            Span::styled(n.kind.symbol(), Style::default().fg(color)),
+            Span::raw(" "),
+            Span::styled(
+                n.created_at.format("%m/%d %H:%M:%S").to_string(),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::raw(format!("  {}", n.message)),
+        ]))
+    }).collect();
+
+    let title = if notifications.is_empty() { " Notifications (none) " } else { " Notifications " };
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(list, area);
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
     let v = Layout::default().direction(Direction::Vertical)
         .constraints([Constraint::Percentage((100 - percent_y) / 2), Constraint::Percentage(percent_y), Constraint::Percentage((100 - percent_y) / 2)])
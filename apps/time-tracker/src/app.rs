@@ -1,10 +1,12 @@
 //! Application state and logic.
 
-use crate::config::Config;
+use crate::config::{Config, GroupBy, IdleAction, ReportFormat, WeekStart};
 use crate::db::{Database, DbResult};
+use crate::idle::{IdleDetector, IdleEvent, IdleSpan, SystemIdle};
 use crate::models::{Project, ProjectId, TimeEntry};
-use crate::pomodoro::PomodoroTimer;
-use chrono::{Duration, NaiveDate, Utc};
+use crate::pomodoro::{PomodoroTimer, SessionType};
+use crate::report::{self, Rounding, Timesheet};
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
 use crossterm::event::{KeyCode, KeyEvent};
 use std::collections::HashMap;
 use std::time::Instant;
@@ -26,7 +28,23 @@ pub struct App {
     pub message: Option<String>,
     pub show_help: bool,
     pub last_input: Instant,
+    pub idle: Option<IdleDetector>,
+    pub system_idle: SystemIdle,
+    pub idle_prompt: Option<IdlePrompt>,
     pub project_hours: HashMap<ProjectId, f64>,
+    pub report_from: NaiveDate,
+    pub report_to: NaiveDate,
+    pub report_group: GroupBy,
+    pub timesheet: Option<Timesheet>,
+}
+
+/// Asks what to do with an idle span when the user comes back.
+#[derive(Debug, Clone)]
+pub struct IdlePrompt {
+    pub span: IdleSpan,
+    /// Picking a project to move the idle time to.
+    pub reassigning: bool,
+    pub selected: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -42,6 +60,7 @@ pub enum InputField {
     None,
     Description,
     ProjectName,
+    ReportRange,
 }
 
 impl App {
@@ -55,6 +74,12 @@ impl App {
 
         let pomodoro = PomodoroTimer::new(config.pomodoro.clone());
         let today = Utc::now().date_naive();
+        let idle = IdleDetector::from_config(&config.idle, Utc::now());
+        let system_idle = if idle.is_some() {
+            SystemIdle::detect()
+        } else {
+            SystemIdle::unavailable()
+        };
 
         let mut app = Self {
             db,
@@ -73,9 +98,20 @@ impl App {
             message: None,
             show_help: false,
             last_input: Instant::now(),
+            idle,
+            system_idle,
+            idle_prompt: None,
             project_hours: HashMap::new(),
+            report_from: today,
+            report_to: today,
+            report_group: GroupBy::Project,
+            timesheet: None,
         };
+        app.report_group = app.config.report.group_by;
+        (app.report_from, app.report_to) = app.week_of(today);
 
+        // Pick up sessions from earlier runs that never got an entry
+        let _ = app.db.sync_pomodoro_sessions();
         app.refresh()?;
         Ok(app)
     }
@@ -96,20 +132,49 @@ impl App {
             self.selected_index = self.entries.len() - 1;
         }
 
+        self.refresh_report()?;
+        Ok(())
+    }
+
+    pub fn refresh_report(&mut self) -> DbResult<()> {
+        self.timesheet = Some(Timesheet::collect(
+            &self.db,
+            self.report_from,
+            self.report_to,
+            self.report_group,
+            Rounding::from_config(&self.config.report),
+        )?);
         Ok(())
     }
 
     pub fn can_quit(&self) -> bool {
-        !self.editing
+        !self.editing && self.idle_prompt.is_none()
     }
 
     pub fn tick(&mut self) {
         // Update pomodoro timer
-        if self.pomodoro_mode && self.pomodoro.tick() {
+        if let Some(session) = self.pomodoro_mode.then(|| self.pomodoro.tick()).flatten() {
             // Session completed - notify
-            self.message = Some(format!("{} complete!", self.pomodoro.session_type().name()));
+            self.message = Some(format!("{} complete!", session.session_type.name()));
             // Terminal bell
             print!("\x07");
+
+            // Work sessions become time entries unless a timer covered them
+            let _ = self.db.insert_pomodoro_session(&session);
+            if session.session_type == SessionType::Work {
+                let _ = self.db.sync_pomodoro_sessions();
+                let _ = self.refresh();
+            }
+        }
+
+        let system_idle = self.system_idle.poll();
+        let event = self.idle.as_mut().and_then(|d| d.check(Utc::now(), system_idle));
+        match event {
+            Some(IdleEvent::Started(since)) => self.idle_started(since),
+            Some(IdleEvent::Returned(span)) => {
+                self.idle_returned(span);
+            }
+            None => {}
         }
     }
 
@@ -117,6 +182,19 @@ impl App {
         self.last_input = Instant::now();
         self.message = None;
 
+        let returned = self.idle.as_mut().and_then(|d| d.activity(Utc::now()));
+        if let Some(span) = returned {
+            // Don't let the key that brought up the prompt answer it
+            if self.idle_returned(span) {
+                return;
+            }
+        }
+
+        if self.idle_prompt.is_some() {
+            self.handle_idle_prompt_key(key);
+            return;
+        }
+
         if self.show_help {
             self.show_help = false;
             return;
@@ -134,6 +212,35 @@ impl App {
             KeyCode::Char('d') if self.view == View::Entries => self.delete_selected(),
             KeyCode::Char('j') | KeyCode::Down => self.move_selection(1),
             KeyCode::Char('k') | KeyCode::Up => self.move_selection(-1),
+            KeyCode::Char('h') | KeyCode::Left if self.view == View::Reports => {
+                self.shift_report(-1)
+            }
+            KeyCode::Char('l') | KeyCode::Right if self.view == View::Reports => {
+                self.shift_report(1)
+            }
+            KeyCode::Char('w') if self.view == View::Reports => {
+                (self.report_from, self.report_to) = self.week_of(Utc::now().date_naive());
+                let _ = self.refresh_report();
+            }
+            KeyCode::Char('m') if self.view == View::Reports => {
+                (self.report_from, self.report_to) = month_of(Utc::now().date_naive());
+                let _ = self.refresh_report();
+            }
+            KeyCode::Char('g') if self.view == View::Reports => {
+                self.report_group = self.report_group.next();
+                let _ = self.refresh_report();
+            }
+            KeyCode::Char('R') if self.view == View::Reports => {
+                self.editing = true;
+                self.input_field = InputField::ReportRange;
+                self.input_buffer = format!("{}..{}", self.report_from, self.report_to);
+            }
+            KeyCode::Char('x') if self.view == View::Reports => {
+                self.export_report(&[ReportFormat::Csv, ReportFormat::Markdown]);
+            }
+            KeyCode::Char('i') if self.view == View::Reports => {
+                self.export_report(&[ReportFormat::Invoice]);
+            }
             KeyCode::Char('h') | KeyCode::Left => self.change_date(-1),
             KeyCode::Char('l') | KeyCode::Right => self.change_date(1),
             KeyCode::Char('t') => {
@@ -192,6 +299,14 @@ impl App {
                     }
                 }
             }
+            InputField::ReportRange => match parse_range(&self.input_buffer) {
+                Some((from, to)) => {
+                    self.report_from = from;
+                    self.report_to = to;
+                    let _ = self.refresh_report();
+                }
+                None => self.message = Some("Expected YYYY-MM-DD..YYYY-MM-DD".to_string()),
+            },
             InputField::None => {}
         }
 
@@ -224,6 +339,125 @@ impl App {
         }
     }
 
+    fn idle_started(&mut self, since: DateTime<Utc>) {
+        if self.config.idle.action != IdleAction::Pause {
+            return;
+        }
+        if let Some(mut entry) = self.running_entry.take() {
+            entry.stop_at(since);
+            let _ = self.db.update_entry(&entry);
+            self.message = Some(format!("Idle: timer paused at {}", since.format("%H:%M")));
+            let _ = self.refresh();
+        }
+    }
+
+    /// Apply the idle action once activity resumes. Returns true if a prompt
+    /// was opened.
+    fn idle_returned(&mut self, span: IdleSpan) -> bool {
+        // Only the part of the span the running timer covers matters
+        let Some(entry) = &self.running_entry else {
+            return false;
+        };
+        if entry.start_time >= span.end {
+            return false;
+        }
+        let span = IdleSpan {
+            start: span.start.max(entry.start_time),
+            end: span.end,
+        };
+
+        match self.config.idle.action {
+            IdleAction::Pause => false,
+            IdleAction::DiscardIdle => {
+                self.split_idle(span, None);
+                self.message = Some(format!("Discarded idle time {}", span.describe()));
+                false
+            }
+            IdleAction::Prompt => {
+                self.editing = false;
+                self.show_help = false;
+                self.idle_prompt = Some(IdlePrompt {
+                    span,
+                    reassigning: false,
+                    selected: 0,
+                });
+                true
+            }
+        }
+    }
+
+    fn handle_idle_prompt_key(&mut self, key: KeyEvent) {
+        let Some(prompt) = &mut self.idle_prompt else {
+            return;
+        };
+        let span = prompt.span;
+
+        if prompt.reassigning {
+            match key.code {
+                KeyCode::Char('j') | KeyCode::Down if !self.projects.is_empty() => {
+                    prompt.selected = (prompt.selected + 1).min(self.projects.len() - 1);
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    prompt.selected = prompt.selected.saturating_sub(1);
+                }
+                KeyCode::Enter => {
+                    if let Some(project) = self.projects.get(prompt.selected).cloned() {
+                        self.idle_prompt = None;
+                        self.split_idle(span, Some(project.id));
+                        self.message =
+                            Some(format!("Moved {} to {}", span.describe(), project.name));
+                    }
+                }
+                KeyCode::Esc => prompt.reassigning = false,
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('k') | KeyCode::Esc => {
+                self.idle_prompt = None;
+                self.message = Some("Kept idle time".to_string());
+            }
+            KeyCode::Char('d') => {
+                self.idle_prompt = None;
+                self.split_idle(span, None);
+                self.message = Some(format!("Discarded idle time {}", span.describe()));
+            }
+            KeyCode::Char('r') if !self.projects.is_empty() => {
+                prompt.reassigning = true;
+                prompt.selected = 0;
+            }
+            KeyCode::Char('r') => {
+                self.message = Some("No projects to reassign to".to_string());
+            }
+            _ => {}
+        }
+    }
+
+    /// Cut `span` out of the running entry: it stops at the start of the span
+    /// and a copy resumes at its end. With `project`, the span is recorded
+    /// as its own entry for that project instead of being dropped.
+    fn split_idle(&mut self, span: IdleSpan, project: Option<ProjectId>) {
+        let Some(mut entry) = self.running_entry.take() else {
+            return;
+        };
+        let resumed = entry.resume_at(span.end);
+        entry.stop_at(span.start);
+        let _ = self.db.update_entry(&entry);
+
+        if let Some(project_id) = project {
+            let mut moved = TimeEntry::create("", span.start, span.end);
+            moved.project_id = Some(project_id);
+            let _ = self.db.insert_entry(&moved);
+        }
+
+        if self.db.insert_entry(&resumed).is_ok() {
+            self.running_entry = Some(resumed);
+        }
+        let _ = self.refresh();
+    }
+
     fn toggle_pomodoro(&mut self) {
         self.pomodoro_mode = !self.pomodoro_mode;
         if self.pomodoro_mode {
@@ -235,6 +469,53 @@ impl App {
         }
     }
 
+    /// The week containing `date`, starting on the configured day.
+    fn week_of(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let offset = match self.config.display.week_start {
+            WeekStart::Monday => date.weekday().num_days_from_monday(),
+            WeekStart::Sunday => date.weekday().num_days_from_sunday(),
+        };
+        let start = date - Duration::days(offset as i64);
+        (start, start + Duration::days(6))
+    }
+
+    /// Move the report range back or forward by its own length; whole
+    /// months move by month.
+    fn shift_report(&mut self, delta: i32) {
+        let (from, to) = (self.report_from, self.report_to);
+        if (from, to) == month_of(from) {
+            let months = Months::new(delta.unsigned_abs());
+            let start = if delta < 0 { from - months } else { from + months };
+            (self.report_from, self.report_to) = month_of(start);
+        } else {
+            let len = Duration::days((to - from).num_days() + 1) * delta;
+            self.report_from = from + len;
+            self.report_to = to + len;
+        }
+        let _ = self.refresh_report();
+    }
+
+    fn export_report(&mut self, formats: &[ReportFormat]) {
+        let Some(sheet) = &self.timesheet else {
+            return;
+        };
+        let dir = self.config.export_dir();
+        let mut written = Vec::new();
+        for &format in formats {
+            match report::export_to(&self.db, sheet, &dir, format, &self.config.invoice) {
+                Ok(files) => written.extend(files),
+                Err(e) => {
+                    self.message = Some(format!("Export failed: {}", e));
+                    return;
+                }
+            }
+        }
+        self.message = Some(match written.len() {
+            0 => "Nothing to invoice: no billable time for clients with a rate".to_string(),
+            n => format!("Wrote {} file(s) to {}", n, dir.display()),
+        });
+    }
+
     fn move_selection(&mut self, delta: i32) {
         let len = match self.view {
             View::Entries => self.entries.len(),
@@ -284,3 +565,35 @@ impl App {
         format!("{:02}:{:02}:{:02}", hours, mins, secs)
     }
 }
+
+/// First and last day of the month containing `date`.
+pub fn month_of(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start = date.with_day(1).unwrap();
+    let end = start + Months::new(1) - Duration::days(1);
+    (start, end)
+}
+
+/// Parse `YYYY-MM-DD..YYYY-MM-DD`, or a single date.
+pub fn parse_range(s: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let date = |s: &str| NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok();
+    let (from, to) = match s.split_once("..") {
+        Some((from, to)) => (date(from)?, date(to)?),
+        None => (date(s)?, date(s)?),
+    };
+    (from <= to).then_some((from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_ranges() {
+        let d = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        assert_eq!(month_of(d(2, 14)), (d(2, 1), d(2, 29)));
+        assert_eq!(parse_range("2024-01-01..2024-01-31"), Some((d(1, 1), d(1, 31))));
+        assert_eq!(parse_range(" 2024-03-05 "), Some((d(3, 5), d(3, 5))));
+        assert_eq!(parse_range("2024-02-01..2024-01-01"), None);
+        assert_eq!(parse_range("last week"), None);
+    }
+}
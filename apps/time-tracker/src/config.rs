@@ -13,6 +13,12 @@ pub struct Config {
     pub idle: IdleConfig,
     #[serde(default)]
     pub display: DisplayConfig,
+    #[serde(default)]
+    pub report: ReportConfig,
+    #[serde(default)]
+    pub invoice: InvoiceConfig,
+    #[serde(default)]
+    pub export: ExportConfig,
 }
 
 impl Default for Config {
@@ -22,6 +28,9 @@ impl Default for Config {
             pomodoro: PomodoroConfig::default(),
             idle: IdleConfig::default(),
             display: DisplayConfig::default(),
+            report: ReportConfig::default(),
+            invoice: InvoiceConfig::default(),
+            export: ExportConfig::default(),
         }
     }
 }
@@ -54,6 +63,19 @@ impl Config {
         directories::ProjectDirs::from("", "", "time-tracker")
             .map(|d| d.data_dir().join("time.db"))
     }
+
+    /// Directory for report exports, with `~` expanded.
+    pub fn export_dir(&self) -> PathBuf {
+        match &self.export.path {
+            Some(path) => match (path.strip_prefix("~"), directories::BaseDirs::new()) {
+                (Ok(rest), Some(dirs)) => dirs.home_dir().join(rest),
+                _ => path.clone(),
+            },
+            None => directories::ProjectDirs::from("", "", "time-tracker")
+                .map(|d| d.data_dir().join("exports"))
+                .unwrap_or_else(|| "time-exports".into()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -175,3 +197,123 @@ pub enum WeekStart {
     Monday,
     Sunday,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportConfig {
+    /// Round each entry to this many minutes; 0 disables rounding.
+    #[serde(default)]
+    pub rounding_mins: u32,
+    #[serde(default)]
+    pub rounding: RoundingMode,
+    #[serde(default)]
+    pub group_by: GroupBy,
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            rounding_mins: 0,
+            rounding: RoundingMode::Nearest,
+            group_by: GroupBy::Project,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingMode {
+    Up,
+    Down,
+    #[default]
+    Nearest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    Client,
+    #[default]
+    Project,
+    Tag,
+}
+
+impl GroupBy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Client => "Client",
+            Self::Project => "Project",
+            Self::Tag => "Tag",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Client => Self::Project,
+            Self::Project => Self::Tag,
+            Self::Tag => Self::Client,
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "client" => Some(Self::Client),
+            "project" => Some(Self::Project),
+            "tag" => Some(Self::Tag),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceConfig {
+    /// Currency for clients without one.
+    #[serde(default = "default_currency")]
+    pub default_currency: String,
+    /// Sender block printed at the top of invoices, one line per entry.
+    #[serde(default)]
+    pub from: Vec<String>,
+    /// Note printed under the totals, e.g. payment terms.
+    #[serde(default)]
+    pub notes: Option<String>,
+}
+
+fn default_currency() -> String { "USD".to_string() }
+
+impl Default for InvoiceConfig {
+    fn default() -> Self {
+        Self {
+            default_currency: default_currency(),
+            from: Vec::new(),
+            notes: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ExportConfig {
+    #[serde(default)]
+    pub default_format: ReportFormat,
+    /// Directory for exports; defaults to the data directory.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Csv,
+    Markdown,
+    Invoice,
+}
+
+impl ReportFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "md" | "markdown" => Some(Self::Markdown),
+            "invoice" | "html" => Some(Self::Invoice),
+            _ => None,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Database operations for time tracking.
 
-use crate::models::{Client, DailySummary, Project, ProjectId, TimeEntry, EntryId};
+use crate::models::{Client, ClientId, DailySummary, Project, ProjectId, TimeEntry, EntryId};
+use crate::pomodoro::{PomodoroSession, SessionType};
 use chrono::{DateTime, NaiveDate, Utc};
 use rusqlite::{params, Connection, Result as SqlResult};
 use std::path::Path;
@@ -75,6 +76,15 @@ impl Database {
                 PRIMARY KEY (entry_id, tag)
             );
 
+            CREATE TABLE IF NOT EXISTS pomodoro_sessions (
+                id TEXT PRIMARY KEY,
+                entry_id TEXT REFERENCES time_entries(id),
+                session_type TEXT NOT NULL,
+                started_at TEXT NOT NULL,
+                ended_at TEXT,
+                completed INTEGER DEFAULT 0
+            );
+
             CREATE INDEX IF NOT EXISTS idx_entries_start ON time_entries(start_time);
             CREATE INDEX IF NOT EXISTS idx_entries_project ON time_entries(project_id);
             "#,
@@ -112,6 +122,16 @@ impl Database {
         Ok(clients)
     }
 
+    pub fn get_client(&self, id: ClientId) -> DbResult<Option<Client>> {
+        let mut stmt = self.conn.prepare("SELECT * FROM clients WHERE id = ?1")?;
+        let client = stmt.query_row(params![id.to_string()], parse_client_row);
+        match client {
+            Ok(c) => Ok(Some(c)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     // Project operations
 
     pub fn insert_project(&self, project: &Project) -> DbResult<()> {
@@ -231,12 +251,13 @@ impl Database {
             "SELECT * FROM time_entries WHERE start_time >= ?1 AND start_time <= ?2 ORDER BY start_time DESC"
         )?;
 
-        let entries = stmt
+        let mut entries = stmt
             .query_map(params![start.to_rfc3339(), end.to_rfc3339()], |row| {
                 Ok(parse_entry_row(row)?)
             })?
             .collect::<SqlResult<Vec<_>>>()?;
 
+        self.load_tags(&mut entries)?;
         Ok(entries)
     }
 
@@ -248,15 +269,28 @@ impl Database {
             "SELECT * FROM time_entries WHERE start_time >= ?1 AND start_time <= ?2 ORDER BY start_time"
         )?;
 
-        let entries = stmt
+        let mut entries = stmt
             .query_map(params![start_dt.to_rfc3339(), end_dt.to_rfc3339()], |row| {
                 Ok(parse_entry_row(row)?)
             })?
             .collect::<SqlResult<Vec<_>>>()?;
 
+        self.load_tags(&mut entries)?;
         Ok(entries)
     }
 
+    fn load_tags(&self, entries: &mut [TimeEntry]) -> DbResult<()> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT tag FROM entry_tags WHERE entry_id = ?1 ORDER BY tag")?;
+        for entry in entries {
+            entry.tags = stmt
+                .query_map(params![entry.id.to_string()], |row| row.get(0))?
+                .collect::<SqlResult<Vec<String>>>()?;
+        }
+        Ok(())
+    }
+
     pub fn get_recent_entries(&self, limit: usize) -> DbResult<Vec<TimeEntry>> {
         let mut stmt = self.conn.prepare(
             "SELECT * FROM time_entries WHERE end_time IS NOT NULL ORDER BY start_time DESC LIMIT ?1"
@@ -285,6 +319,88 @@ impl Database {
         let secs: i64 = stmt.query_row(params![project_id.to_string()], |row| row.get(0))?;
         Ok(secs as f64 / 3600.0)
     }
+
+    // Pomodoro operations
+
+    pub fn insert_pomodoro_session(&self, session: &PomodoroSession) -> DbResult<()> {
+        self.conn.execute(
+            "INSERT INTO pomodoro_sessions
+                (id, entry_id, session_type, started_at, ended_at, completed)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                session.id.to_string(),
+                session.entry_id.map(|id| id.to_string()),
+                session.session_type.key(),
+                session.started_at.to_rfc3339(),
+                session.ended_at.to_rfc3339(),
+                session.completed as i32,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn list_pomodoro_sessions(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> DbResult<Vec<PomodoroSession>> {
+        let start_dt = start.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let end_dt = end.and_hms_opt(23, 59, 59).unwrap().and_utc();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM pomodoro_sessions WHERE started_at >= ?1 AND started_at <= ?2
+             ORDER BY started_at"
+        )?;
+        let sessions = stmt
+            .query_map(params![start_dt.to_rfc3339(), end_dt.to_rfc3339()], parse_session_row)?
+            .collect::<SqlResult<Vec<_>>>()?;
+        Ok(sessions)
+    }
+
+    /// Link completed work sessions to the time entries covering them. A
+    /// session with no overlapping entry gets a new "Pomodoro" entry of its
+    /// own. Returns the number of entries created.
+    pub fn sync_pomodoro_sessions(&self) -> DbResult<usize> {
+        let mut stmt = self.conn.prepare(
+            "SELECT * FROM pomodoro_sessions
+             WHERE entry_id IS NULL AND completed = 1 AND session_type = 'work'
+               AND ended_at IS NOT NULL"
+        )?;
+        let sessions = stmt
+            .query_map([], parse_session_row)?
+            .collect::<SqlResult<Vec<_>>>()?;
+
+        let mut created = 0;
+        for session in sessions {
+            let overlapping: Option<String> = self.conn.query_row(
+                "SELECT id FROM time_entries
+                 WHERE start_time < ?2 AND (end_time IS NULL OR end_time > ?1)
+                 ORDER BY start_time LIMIT 1",
+                params![session.started_at.to_rfc3339(), session.ended_at.to_rfc3339()],
+                |row| row.get(0),
+            ).map(Some).or_else(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => Ok(None),
+                e => Err(e),
+            })?;
+
+            let entry_id = match overlapping {
+                Some(id) => id,
+                None => {
+                    let mut entry =
+                        TimeEntry::create("Pomodoro", session.started_at, session.ended_at);
+                    entry.tags.push("pomodoro".to_string());
+                    self.insert_entry(&entry)?;
+                    created += 1;
+                    entry.id.to_string()
+                }
+            };
+            self.conn.execute(
+                "UPDATE pomodoro_sessions SET entry_id = ?2 WHERE id = ?1",
+                params![session.id.to_string(), entry_id],
+            )?;
+        }
+        Ok(created)
+    }
 }
 
 fn parse_client_row(row: &rusqlite::Row) -> SqlResult<Client> {
@@ -348,6 +464,32 @@ fn parse_entry_row(row: &rusqlite::Row) -> SqlResult<TimeEntry> {
     })
 }
 
+fn parse_session_row(row: &rusqlite::Row) -> SqlResult<PomodoroSession> {
+    let id_str: String = row.get("id")?;
+    let entry_id_str: Option<String> = row.get("entry_id")?;
+    let type_str: String = row.get("session_type")?;
+    let started_str: String = row.get("started_at")?;
+    let ended_str: Option<String> = row.get("ended_at")?;
+    let completed: i32 = row.get("completed")?;
+
+    let parse_time = |s: &str| {
+        DateTime::parse_from_rfc3339(s)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now())
+    };
+    let session_type = SessionType::from_key(&type_str).unwrap_or(SessionType::Work);
+    let started_at = parse_time(&started_str);
+
+    Ok(PomodoroSession {
+        id: Uuid::parse_str(&id_str).unwrap(),
+        entry_id: entry_id_str.and_then(|s| Uuid::parse_str(&s).ok()),
+        session_type,
+        started_at,
+        ended_at: ended_str.map(|s| parse_time(&s)).unwrap_or(started_at),
+        completed: completed != 0,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -372,4 +514,43 @@ mod tests {
         let loaded = db.get_entry(entry.id).unwrap().unwrap();
         assert_eq!(loaded.description, "Working");
     }
+
+    #[test]
+    fn test_sync_pomodoro_sessions() {
+        let db = Database::in_memory().unwrap();
+        let now = Utc::now();
+        let session = |mins_ago: i64, session_type| PomodoroSession {
+            id: Uuid::new_v4(),
+            entry_id: None,
+            session_type,
+            started_at: now - chrono::Duration::minutes(mins_ago),
+            ended_at: now - chrono::Duration::minutes(mins_ago - 25),
+            completed: true,
+        };
+
+        // Covered by a tracked entry
+        let hours_ago = |h| now - chrono::Duration::hours(h);
+        let tracked = TimeEntry::create("Coding", hours_ago(3), hours_ago(2));
+        db.insert_entry(&tracked).unwrap();
+        let covered = session(170, SessionType::Work);
+        let untracked = session(60, SessionType::Work);
+        db.insert_pomodoro_session(&covered).unwrap();
+        db.insert_pomodoro_session(&untracked).unwrap();
+        db.insert_pomodoro_session(&session(30, SessionType::ShortBreak)).unwrap();
+
+        assert_eq!(db.sync_pomodoro_sessions().unwrap(), 1);
+        assert_eq!(db.sync_pomodoro_sessions().unwrap(), 0);
+
+        let today = now.date_naive();
+        let sessions = db.list_pomodoro_sessions(today - chrono::Duration::days(1), today).unwrap();
+        let linked = |id| sessions.iter().find(|s| s.id == id).unwrap().entry_id;
+        assert_eq!(linked(covered.id), Some(tracked.id));
+
+        let entry = db.get_entry(linked(untracked.id).unwrap()).unwrap().unwrap();
+        assert_eq!(entry.description, "Pomodoro");
+        assert_eq!(entry.duration_secs, Some(25 * 60));
+        let entries = db.get_entries_range(entry.start_time.date_naive(), today).unwrap();
+        let loaded = entries.iter().find(|e| e.id == entry.id).unwrap();
+        assert_eq!(loaded.tags, vec!["pomodoro".to_string()]);
+    }
 }
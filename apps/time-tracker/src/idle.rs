@@ -0,0 +1,337 @@
+//! Idle detection.
+//!
+//! Activity is the last key pressed in the app. When the desktop session's
+//! idle time can be read (X11 via `xprintidle`, GNOME Wayland via the Mutter
+//! idle monitor), input to other windows counts as activity too, so working
+//! in an editor next to the tracker does not look like being away.
+
+use crate::config::IdleConfig;
+use chrono::{DateTime, Duration, Utc};
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration as StdDuration, Instant};
+
+/// How often to ask the desktop session for its idle time.
+const SYSTEM_POLL_SECS: u64 = 5;
+
+/// How long the idle helper gets to answer.
+const PROBE_TIMEOUT_SECS: u64 = 2;
+
+/// A stretch of time with no activity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleSpan {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl IdleSpan {
+    pub fn duration(&self) -> Duration {
+        self.end.signed_duration_since(self.start)
+    }
+
+    /// Format as e.g. `14:02-14:19 (17m)`.
+    pub fn describe(&self) -> String {
+        format!(
+            "{}-{} ({}m)",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M"),
+            self.duration().num_minutes()
+        )
+    }
+}
+
+/// Change in idle state reported by [`IdleDetector::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleEvent {
+    /// The threshold was crossed; idle since the given time.
+    Started(DateTime<Utc>),
+    /// Activity resumed after an idle span.
+    Returned(IdleSpan),
+}
+
+/// Tracks the last activity and whether the user is currently idle.
+#[derive(Debug, Clone)]
+pub struct IdleDetector {
+    threshold: Duration,
+    last_active: DateTime<Utc>,
+    idle_since: Option<DateTime<Utc>>,
+}
+
+impl IdleDetector {
+    /// Create from config; `None` if idle detection is disabled.
+    pub fn from_config(config: &IdleConfig, now: DateTime<Utc>) -> Option<Self> {
+        if !config.enabled || config.threshold_mins == 0 {
+            return None;
+        }
+        Some(Self {
+            threshold: Duration::minutes(config.threshold_mins as i64),
+            last_active: now,
+            idle_since: None,
+        })
+    }
+
+    pub fn idle_since(&self) -> Option<DateTime<Utc>> {
+        self.idle_since
+    }
+
+    /// Record activity at `at`. Returns the idle span this ends, if any.
+    pub fn activity(&mut self, at: DateTime<Utc>) -> Option<IdleSpan> {
+        if at > self.last_active {
+            self.last_active = at;
+        }
+        let start = self.idle_since.take()?;
+        Some(IdleSpan { start, end: at })
+    }
+
+    /// Check for a change in idle state at `now`, using the session's idle
+    /// time as well when it is known.
+    pub fn check(
+        &mut self,
+        now: DateTime<Utc>,
+        system_idle: Option<Duration>,
+    ) -> Option<IdleEvent> {
+        if let Some(seen) = system_idle.and_then(|idle| now.checked_sub_signed(idle)) {
+            // Ignore sub-second drift between polls
+            if seen - self.last_active > Duration::seconds(1) {
+                if let Some(span) = self.activity(seen) {
+                    return Some(IdleEvent::Returned(span));
+                }
+            }
+        }
+
+        if self.idle_since.is_none() && now - self.last_active >= self.threshold {
+            self.idle_since = Some(self.last_active);
+            return Some(IdleEvent::Started(self.last_active));
+        }
+        None
+    }
+}
+
+/// Reads the desktop session's idle time, if the session exposes it. The
+/// helper command runs on its own thread, so a slow or hung one never holds
+/// up the UI.
+#[derive(Debug)]
+pub struct SystemIdle {
+    readings: Option<Receiver<Option<Reading>>>,
+}
+
+/// Session idle time and when it was read.
+type Reading = (Duration, Instant);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IdleSource {
+    X11,
+    Mutter,
+}
+
+impl SystemIdle {
+    /// Pick a source from the environment: `DISPLAY` for X11 (including
+    /// XWayland sessions), otherwise `WAYLAND_DISPLAY` for GNOME's Mutter.
+    pub fn detect() -> Self {
+        let set = |var| std::env::var_os(var).is_some_and(|v| !v.is_empty());
+        let source = if set("DISPLAY") {
+            Some(IdleSource::X11)
+        } else if set("WAYLAND_DISPLAY") {
+            Some(IdleSource::Mutter)
+        } else {
+            None
+        };
+        Self {
+            readings: source.map(spawn_poller),
+        }
+    }
+
+    pub fn unavailable() -> Self {
+        Self { readings: None }
+    }
+
+    /// The newest idle time read since the last call, aged to now. `None`
+    /// if nothing new was read, or if it can't be read; a source that fails
+    /// is not retried.
+    pub fn poll(&mut self) -> Option<Duration> {
+        let readings = self.readings.as_ref()?;
+        let mut latest = None;
+        loop {
+            match readings.try_recv() {
+                Ok(Some(reading)) => latest = Some(reading),
+                Err(TryRecvError::Empty) => break,
+                Ok(None) | Err(TryRecvError::Disconnected) => {
+                    self.readings = None;
+                    break;
+                }
+            }
+        }
+        latest.map(|(idle, at)| idle + Duration::from_std(at.elapsed()).unwrap_or_default())
+    }
+}
+
+/// Read the idle time every few seconds on a thread of its own, until it
+/// can't be read or the receiver is gone.
+fn spawn_poller(source: IdleSource) -> Receiver<Option<Reading>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || loop {
+        let reading = probe(source).map(|idle| (idle, Instant::now()));
+        let failed = reading.is_none();
+        if sender.send(reading).is_err() || failed {
+            break;
+        }
+        thread::sleep(StdDuration::from_secs(SYSTEM_POLL_SECS));
+    });
+    receiver
+}
+
+/// Run the source's helper command, giving up on it after a few seconds.
+fn probe(source: IdleSource) -> Option<Duration> {
+    let mut command = match source {
+        IdleSource::X11 => Command::new("xprintidle"),
+        IdleSource::Mutter => {
+            let mut command = Command::new("gdbus");
+            command.args([
+                "call",
+                "--session",
+                "--dest",
+                "org.gnome.Mutter.IdleMonitor",
+                "--object-path",
+                "/org/gnome/Mutter/IdleMonitor/Core",
+                "--method",
+                "org.gnome.Mutter.IdleMonitor.GetIdletime",
+            ]);
+            command
+        }
+    };
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let deadline = Instant::now() + StdDuration::from_secs(PROBE_TIMEOUT_SECS);
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(StdDuration::from_millis(50)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+    let mut stdout = String::new();
+    child.stdout.take()?.read_to_string(&mut stdout).ok()?;
+    let ms = parse_idle_ms(&stdout).filter(|_| status.success())?;
+    Duration::try_milliseconds(i64::try_from(ms).ok()?)
+}
+
+/// Parse milliseconds from `xprintidle` (`12345`) or `gdbus` (`(uint64 12345,)`).
+fn parse_idle_ms(s: &str) -> Option<u64> {
+    s.trim()
+        .trim_start_matches('(')
+        .trim_start_matches("uint64")
+        .trim_end_matches(')')
+        .trim_end_matches(',')
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 15, h, m, 0).unwrap()
+    }
+
+    fn detector(start: DateTime<Utc>) -> IdleDetector {
+        IdleDetector::from_config(&IdleConfig::default(), start).unwrap()
+    }
+
+    #[test]
+    fn test_idle_from_app_input() {
+        let mut idle = detector(at(9, 0));
+        assert_eq!(idle.check(at(9, 4), None), None);
+        assert_eq!(idle.activity(at(9, 4)), None);
+
+        assert_eq!(idle.check(at(9, 8), None), None);
+        assert_eq!(
+            idle.check(at(9, 9), None),
+            Some(IdleEvent::Started(at(9, 4)))
+        );
+        // Reported once
+        assert_eq!(idle.check(at(9, 30), None), None);
+
+        let span = idle.activity(at(9, 31)).unwrap();
+        assert_eq!(
+            span,
+            IdleSpan {
+                start: at(9, 4),
+                end: at(9, 31)
+            }
+        );
+        assert_eq!(span.duration(), Duration::minutes(27));
+        assert_eq!(idle.idle_since(), None);
+    }
+
+    #[test]
+    fn test_idle_from_system() {
+        let mut idle = detector(at(9, 0));
+        // Busy in another window: the session saw input 30s ago
+        assert_eq!(idle.check(at(9, 10), Some(Duration::seconds(30))), None);
+
+        assert!(matches!(
+            idle.check(at(9, 20), None),
+            Some(IdleEvent::Started(_))
+        ));
+        let event = idle.check(at(9, 40), Some(Duration::minutes(2)));
+        let span = IdleSpan {
+            start: at(9, 9) + Duration::seconds(30),
+            end: at(9, 38),
+        };
+        assert_eq!(event, Some(IdleEvent::Returned(span)));
+    }
+
+    #[test]
+    fn test_disabled() {
+        let config = IdleConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert!(IdleDetector::from_config(&config, at(9, 0)).is_none());
+    }
+
+    #[test]
+    fn test_system_poll() {
+        let (sender, receiver) = mpsc::channel();
+        let mut system = SystemIdle {
+            readings: Some(receiver),
+        };
+        assert_eq!(system.poll(), None);
+
+        sender
+            .send(Some((Duration::seconds(3), Instant::now())))
+            .unwrap();
+        sender
+            .send(Some((Duration::seconds(1), Instant::now())))
+            .unwrap();
+        let idle = system.poll().unwrap();
+        assert!(idle >= Duration::seconds(1) && idle < Duration::seconds(2));
+        assert_eq!(system.poll(), None);
+
+        // A failed read drops the source
+        sender.send(None).unwrap();
+        assert_eq!(system.poll(), None);
+        assert!(system.readings.is_none());
+    }
+
+    #[test]
+    fn test_parse_idle_ms() {
+        assert_eq!(parse_idle_ms("12345\n"), Some(12345));
+        assert_eq!(parse_idle_ms("(uint64 678,)\n"), Some(678));
+        assert_eq!(parse_idle_ms("Error: no such interface"), None);
+    }
+}
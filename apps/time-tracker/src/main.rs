@@ -4,21 +4,33 @@
 mod app;
 mod config;
 mod db;
+mod idle;
 mod models;
 mod pomodoro;
+mod report;
 mod ui;
 
 use app::App;
+use chrono::{Duration as ChronoDuration, Utc};
+use config::{Config, GroupBy, ReportFormat};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use db::Database;
 use ratatui::{backend::CrosstermBackend, Terminal};
+use report::{Rounding, Timesheet};
 use std::io;
+use std::path::PathBuf;
 use std::time::Duration;
 
 fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("report") {
+        return run_report(&args[2..]);
+    }
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -71,3 +83,66 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> anyhow::
 
     Ok(())
 }
+
+/// `time-tracker report [RANGE] [--group client|project|tag]
+/// [--format csv|md|invoice] [-o DIR]`
+///
+/// RANGE is `YYYY-MM-DD..YYYY-MM-DD` or a single date, defaulting to the
+/// last 7 days. CSV and Markdown go to stdout unless `-o` is given;
+/// invoices are always written as files, to the export directory by default.
+fn run_report(args: &[String]) -> anyhow::Result<()> {
+    let config = Config::load();
+    let today = Utc::now().date_naive();
+    let mut range = (today - ChronoDuration::days(6), today);
+    let mut group_by = config.report.group_by;
+    let mut format = config.export.default_format;
+    let mut dir: Option<PathBuf> = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().map(String::as_str).unwrap_or_default();
+        match arg.as_str() {
+            "--group" | "-g" => {
+                let value = value();
+                group_by = GroupBy::parse(value)
+                    .ok_or_else(|| anyhow::anyhow!("unknown grouping: {}", value))?;
+            }
+            "--format" | "-f" => {
+                let value = value();
+                format = ReportFormat::parse(value)
+                    .ok_or_else(|| anyhow::anyhow!("unknown format: {}", value))?;
+            }
+            "--output" | "-o" => dir = Some(PathBuf::from(value())),
+            _ => {
+                range = app::parse_range(arg).ok_or_else(|| {
+                    anyhow::anyhow!("invalid range '{}', expected YYYY-MM-DD..YYYY-MM-DD", arg)
+                })?
+            }
+        }
+    }
+
+    let db_path = Config::db_path().unwrap_or_else(|| "time.db".into());
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let db = Database::open(&db_path)?;
+    db.sync_pomodoro_sessions()?;
+    let rounding = Rounding::from_config(&config.report);
+    let sheet = Timesheet::collect(&db, range.0, range.1, group_by, rounding)?;
+
+    match (format, dir) {
+        (ReportFormat::Csv, None) => print!("{}", sheet.to_csv()),
+        (ReportFormat::Markdown, None) => print!("{}", sheet.to_markdown()),
+        (format, dir) => {
+            let dir = dir.unwrap_or_else(|| config.export_dir());
+            let files = report::export_to(&db, &sheet, &dir, format, &config.invoice)?;
+            if files.is_empty() {
+                eprintln!("No billable time for clients in {}..{}", range.0, range.1);
+            }
+            for file in files {
+                println!("Wrote {}", file.display());
+            }
+        }
+    }
+    Ok(())
+}
@@ -123,9 +123,26 @@ impl TimeEntry {
 
     /// Stop a running entry.
     pub fn stop(&mut self) {
-        let now = Utc::now();
-        self.end_time = Some(now);
-        self.duration_secs = Some(now.signed_duration_since(self.start_time).num_seconds());
+        self.stop_at(Utc::now());
+    }
+
+    /// Stop a running entry at `end`.
+    pub fn stop_at(&mut self, end: DateTime<Utc>) {
+        let end = end.max(self.start_time);
+        self.end_time = Some(end);
+        self.duration_secs = Some(end.signed_duration_since(self.start_time).num_seconds());
+    }
+
+    /// Start a new running entry from `start` for the same project and task.
+    pub fn resume_at(&self, start: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            start_time: start,
+            end_time: None,
+            duration_secs: None,
+            created_at: Utc::now(),
+            ..self.clone()
+        }
     }
 
     /// Check if entry is running.
@@ -240,4 +257,25 @@ mod tests {
         assert!(!entry.is_running());
         assert!(entry.duration_secs.is_some());
     }
+
+    #[test]
+    fn test_split_around_idle() {
+        let mut entry = TimeEntry::start("Writing");
+        entry.tags.push("docs".to_string());
+        let idle_start = entry.start_time + Duration::minutes(40);
+        let idle_end = idle_start + Duration::minutes(15);
+
+        let resumed = entry.resume_at(idle_end);
+        entry.stop_at(idle_start);
+        assert_eq!(entry.duration_secs, Some(40 * 60));
+        assert!(resumed.is_running());
+        assert_ne!(resumed.id, entry.id);
+        assert_eq!(resumed.description, "Writing");
+        assert_eq!(resumed.tags, entry.tags);
+
+        // Never ends before it started
+        let mut late = TimeEntry::start("Late");
+        late.stop_at(late.start_time - Duration::minutes(5));
+        assert_eq!(late.duration_secs, Some(0));
+    }
 }
@@ -1,7 +1,9 @@
 //! Pomodoro timer functionality.
 
 use crate::config::PomodoroConfig;
+use crate::models::EntryId;
 use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
 
 /// Pomodoro session type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -23,6 +25,37 @@ impl SessionType {
     pub fn is_break(&self) -> bool {
         matches!(self, Self::ShortBreak | Self::LongBreak)
     }
+
+    /// Key used in the database.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Work => "work",
+            Self::ShortBreak => "short_break",
+            Self::LongBreak => "long_break",
+        }
+    }
+
+    pub fn from_key(s: &str) -> Option<Self> {
+        match s {
+            "work" => Some(Self::Work),
+            "short_break" => Some(Self::ShortBreak),
+            "long_break" => Some(Self::LongBreak),
+            _ => None,
+        }
+    }
+}
+
+/// A finished Pomodoro session.
+#[derive(Debug, Clone)]
+pub struct PomodoroSession {
+    pub id: Uuid,
+    /// Time entry the session was synced into.
+    pub entry_id: Option<EntryId>,
+    pub session_type: SessionType,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Ran to the end rather than being skipped.
+    pub completed: bool,
 }
 
 /// Pomodoro timer state.
@@ -62,7 +95,13 @@ impl PomodoroTimer {
     pub fn start(&mut self) {
         if !self.active {
             self.active = true;
-            self.started_at = Some(Utc::now());
+            // Resume where a pause left off
+            let elapsed = self
+                .paused_remaining
+                .take()
+                .map(|remaining| self.session_duration() - remaining)
+                .unwrap_or_else(Duration::zero);
+            self.started_at = Some(Utc::now() - elapsed);
         }
     }
 
@@ -175,13 +214,23 @@ impl PomodoroTimer {
         self.remaining() <= Duration::zero()
     }
 
-    /// Tick the timer (call each frame).
-    pub fn tick(&mut self) -> bool {
+    /// Tick the timer (call each frame). Returns the session that just
+    /// completed, if any.
+    pub fn tick(&mut self) -> Option<PomodoroSession> {
         if self.active && self.is_complete() {
+            let ended_at = Utc::now();
+            let session = PomodoroSession {
+                id: Uuid::new_v4(),
+                entry_id: None,
+                session_type: self.session_type,
+                started_at: ended_at - self.session_duration(),
+                ended_at,
+                completed: true,
+            };
             self.complete_session();
-            return true; // Session completed
+            return Some(session);
         }
-        false
+        None
     }
 
     /// Get current session type.
@@ -254,4 +303,30 @@ mod tests {
 
         assert_eq!(timer.session_duration(), Duration::minutes(25));
     }
+
+    #[test]
+    fn test_resume_after_pause() {
+        let mut timer = PomodoroTimer::new(PomodoroConfig::default());
+        timer.start();
+        timer.started_at = Some(Utc::now() - Duration::minutes(10));
+        timer.pause();
+        timer.start();
+
+        let remaining = timer.remaining();
+        assert!(remaining <= Duration::minutes(15) && remaining > Duration::minutes(14));
+        assert!(timer.paused_remaining.is_none());
+    }
+
+    #[test]
+    fn test_tick_reports_session() {
+        let mut timer = PomodoroTimer::new(PomodoroConfig::default());
+        timer.start();
+        assert!(timer.tick().is_none());
+
+        timer.started_at = Some(Utc::now() - Duration::minutes(26));
+        let session = timer.tick().unwrap();
+        assert_eq!(session.session_type, SessionType::Work);
+        assert_eq!(session.ended_at - session.started_at, Duration::minutes(25));
+        assert_eq!(timer.session_type(), SessionType::ShortBreak);
+    }
 }
@@ -0,0 +1,734 @@
+//! Timesheet reports and exports.
+//!
+//! A timesheet covers a date range and groups entries by client, project or
+//! tag. Rounding is applied to each entry before anything is summed, so the
+//! lines of an export always add up to its totals. Amounts are billable time
+//! at the client's hourly rate, kept per currency.
+
+use crate::config::{GroupBy, InvoiceConfig, ReportConfig, ReportFormat, RoundingMode};
+use crate::db::{Database, DbResult};
+use crate::models::{Client, ClientId, Project, TimeEntry};
+use chrono::{Local, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// How entry durations are rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rounding {
+    /// Increment in minutes; 0 leaves durations as they are.
+    pub mins: u32,
+    pub mode: RoundingMode,
+}
+
+impl Rounding {
+    pub fn from_config(config: &ReportConfig) -> Self {
+        Self {
+            mins: config.rounding_mins,
+            mode: config.rounding,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self {
+            mins: 0,
+            mode: RoundingMode::Nearest,
+        }
+    }
+
+    /// Round a duration in seconds to the increment.
+    pub fn apply(&self, secs: i64) -> i64 {
+        let step = self.mins as i64 * 60;
+        if step == 0 {
+            return secs;
+        }
+        let units = match self.mode {
+            RoundingMode::Up => (secs + step - 1).div_euclid(step),
+            RoundingMode::Down => secs.div_euclid(step),
+            RoundingMode::Nearest => (secs + step / 2).div_euclid(step),
+        };
+        units * step
+    }
+
+    pub fn describe(&self) -> String {
+        if self.mins == 0 {
+            return "none".to_string();
+        }
+        let mode = match self.mode {
+            RoundingMode::Up => "up",
+            RoundingMode::Down => "down",
+            RoundingMode::Nearest => "to the nearest",
+        };
+        format!("each entry {} {} min", mode, self.mins)
+    }
+}
+
+/// One entry on a timesheet, with names and rate resolved.
+#[derive(Debug, Clone)]
+pub struct TimesheetLine {
+    pub entry: TimeEntry,
+    pub project: Option<String>,
+    pub client_id: Option<ClientId>,
+    pub client: Option<String>,
+    /// Rounded duration.
+    pub secs: i64,
+    pub rate: Option<f64>,
+    pub currency: String,
+}
+
+impl TimesheetLine {
+    pub fn billable_secs(&self) -> i64 {
+        if self.entry.billable {
+            self.secs
+        } else {
+            0
+        }
+    }
+
+    /// Billable amount, if the client has a rate.
+    pub fn amount(&self) -> Option<f64> {
+        let rate = self.rate?;
+        Some(self.billable_secs() as f64 / 3600.0 * rate)
+    }
+}
+
+/// Totals for one client, project or tag.
+#[derive(Debug, Clone, Default)]
+pub struct TimesheetGroup {
+    pub name: String,
+    pub entries: usize,
+    pub secs: i64,
+    pub billable_secs: i64,
+    /// Amount per currency.
+    pub amounts: BTreeMap<String, f64>,
+}
+
+impl TimesheetGroup {
+    fn add(&mut self, line: &TimesheetLine) {
+        self.entries += 1;
+        self.secs += line.secs;
+        self.billable_secs += line.billable_secs();
+        if let Some(amount) = line.amount() {
+            *self.amounts.entry(line.currency.clone()).or_default() += amount;
+        }
+    }
+}
+
+/// Entries over a date range, grouped and totalled.
+#[derive(Debug, Clone)]
+pub struct Timesheet {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub group_by: GroupBy,
+    pub rounding: Rounding,
+    pub lines: Vec<TimesheetLine>,
+    /// Largest first. With tags, an entry counts toward each of its tags.
+    pub groups: Vec<TimesheetGroup>,
+    pub total: TimesheetGroup,
+}
+
+impl Timesheet {
+    /// Load entries between `from` and `to` (inclusive) from the database.
+    pub fn collect(
+        db: &Database,
+        from: NaiveDate,
+        to: NaiveDate,
+        group_by: GroupBy,
+        rounding: Rounding,
+    ) -> DbResult<Self> {
+        let entries = db.get_entries_range(from, to)?;
+        let projects = db.list_projects(true)?;
+        let clients = db.list_clients(true)?;
+        Ok(Self::build(
+            entries, &projects, &clients, from, to, group_by, rounding,
+        ))
+    }
+
+    pub fn build(
+        entries: Vec<TimeEntry>,
+        projects: &[Project],
+        clients: &[Client],
+        from: NaiveDate,
+        to: NaiveDate,
+        group_by: GroupBy,
+        rounding: Rounding,
+    ) -> Self {
+        let projects: HashMap<_, _> = projects.iter().map(|p| (p.id, p)).collect();
+        let clients: HashMap<_, _> = clients.iter().map(|c| (c.id, c)).collect();
+
+        let lines: Vec<TimesheetLine> = entries
+            .into_iter()
+            .map(|entry| {
+                let project = entry.project_id.and_then(|id| projects.get(&id));
+                let client = project
+                    .and_then(|p| p.client_id)
+                    .and_then(|id| clients.get(&id));
+                TimesheetLine {
+                    project: project.map(|p| p.name.clone()),
+                    client_id: client.map(|c| c.id),
+                    client: client.map(|c| c.name.clone()),
+                    secs: rounding.apply(entry.duration().num_seconds()),
+                    rate: client.and_then(|c| c.hourly_rate),
+                    currency: client.map(|c| c.currency.clone()).unwrap_or_default(),
+                    entry,
+                }
+            })
+            .collect();
+
+        let mut groups: BTreeMap<String, TimesheetGroup> = BTreeMap::new();
+        let mut total = TimesheetGroup {
+            name: "Total".to_string(),
+            ..Default::default()
+        };
+        for line in &lines {
+            total.add(line);
+            let names = match group_by {
+                GroupBy::Client => {
+                    vec![line.client.clone().unwrap_or_else(|| "(no client)".into())]
+                }
+                GroupBy::Project => {
+                    vec![line
+                        .project
+                        .clone()
+                        .unwrap_or_else(|| "(no project)".into())]
+                }
+                GroupBy::Tag if line.entry.tags.is_empty() => vec!["(untagged)".to_string()],
+                GroupBy::Tag => line.entry.tags.clone(),
+            };
+            for name in names {
+                groups
+                    .entry(name.clone())
+                    .or_insert_with(|| TimesheetGroup {
+                        name,
+                        ..Default::default()
+                    })
+                    .add(line);
+            }
+        }
+        let mut groups: Vec<_> = groups.into_values().collect();
+        groups.sort_by_key(|g| std::cmp::Reverse(g.secs));
+
+        Self {
+            from,
+            to,
+            group_by,
+            rounding,
+            lines,
+            groups,
+            total,
+        }
+    }
+
+    pub fn billable_percent(&self) -> f64 {
+        if self.total.secs > 0 {
+            self.total.billable_secs as f64 / self.total.secs as f64 * 100.0
+        } else {
+            0.0
+        }
+    }
+
+    pub fn title(&self) -> String {
+        if self.from == self.to {
+            format!("Timesheet {}", self.from)
+        } else {
+            format!("Timesheet {} to {}", self.from, self.to)
+        }
+    }
+
+    /// One row per entry.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "date,start,end,client,project,description,tags,hours,billable,rate,currency,amount\n",
+        );
+        for line in &self.lines {
+            let e = &line.entry;
+            let fields = [
+                e.start_time.date_naive().to_string(),
+                e.start_time.format("%H:%M").to_string(),
+                e.end_time
+                    .map(|t| t.format("%H:%M").to_string())
+                    .unwrap_or_default(),
+                line.client.clone().unwrap_or_default(),
+                line.project.clone().unwrap_or_default(),
+                e.description.clone(),
+                e.tags.join(";"),
+                format_hours(line.secs),
+                e.billable.to_string(),
+                line.rate.map(|r| format!("{:.2}", r)).unwrap_or_default(),
+                if line.rate.is_some() {
+                    line.currency.clone()
+                } else {
+                    String::new()
+                },
+                line.amount()
+                    .map(|a| format!("{:.2}", a))
+                    .unwrap_or_default(),
+            ];
+            let fields: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Grouped summary followed by the entries.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.title());
+        let _ = writeln!(
+            out,
+            "| {} | Entries | Hours | Billable | Amount |",
+            self.group_by.name()
+        );
+        let _ = writeln!(out, "|---|---:|---:|---:|---:|");
+        for group in &self.groups {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} |",
+                md_cell(&group.name),
+                group.entries,
+                format_hours(group.secs),
+                format_hours(group.billable_secs),
+                format_amounts(&group.amounts),
+            );
+        }
+        let _ = writeln!(
+            out,
+            "| **Total** | **{}** | **{}** | **{}** | **{}** |\n",
+            self.total.entries,
+            format_hours(self.total.secs),
+            format_hours(self.total.billable_secs),
+            format_amounts(&self.total.amounts),
+        );
+        let _ = writeln!(
+            out,
+            "Billable: {:.0}%. Rounding: {}.\n",
+            self.billable_percent(),
+            self.rounding.describe()
+        );
+
+        let _ = writeln!(out, "## Entries\n");
+        let _ = writeln!(
+            out,
+            "| Date | Time | Project | Description | Hours | Billable |"
+        );
+        let _ = writeln!(out, "|---|---|---|---|---:|:---:|");
+        for line in &self.lines {
+            let e = &line.entry;
+            let _ = writeln!(
+                out,
+                "| {} | {}-{} | {} | {} | {} | {} |",
+                e.start_time.date_naive(),
+                e.start_time.format("%H:%M"),
+                e.end_time
+                    .map(|t| t.format("%H:%M").to_string())
+                    .unwrap_or_else(|| "running".into()),
+                md_cell(line.project.as_deref().unwrap_or("")),
+                md_cell(&e.description),
+                format_hours(line.secs),
+                if e.billable { "yes" } else { "no" },
+            );
+        }
+        out
+    }
+
+    /// Clients with billable time on this timesheet.
+    pub fn billed_clients(&self) -> Vec<ClientId> {
+        let mut ids: Vec<ClientId> = Vec::new();
+        for line in &self.lines {
+            if let Some(id) = line.client_id {
+                if line.billable_secs() > 0 && !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+        ids
+    }
+}
+
+/// One line per project on an invoice.
+#[derive(Debug, Clone)]
+pub struct InvoiceItem {
+    pub description: String,
+    pub secs: i64,
+    pub rate: Option<f64>,
+    pub amount: f64,
+}
+
+/// An invoice for one client's billable time on a timesheet.
+#[derive(Debug, Clone)]
+pub struct Invoice {
+    pub number: String,
+    pub client: Client,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub issued: NaiveDate,
+    pub items: Vec<InvoiceItem>,
+    pub total_secs: i64,
+    pub total_amount: f64,
+    pub currency: String,
+}
+
+impl Invoice {
+    pub fn from_timesheet(sheet: &Timesheet, client: &Client, config: &InvoiceConfig) -> Self {
+        let mut items: Vec<InvoiceItem> = Vec::new();
+        for line in &sheet.lines {
+            if line.client_id != Some(client.id) || line.billable_secs() == 0 {
+                continue;
+            }
+            let description = line
+                .project
+                .clone()
+                .unwrap_or_else(|| "(no project)".into());
+            let amount = line.amount().unwrap_or(0.0);
+            match items.iter_mut().find(|i| i.description == description) {
+                Some(item) => {
+                    item.secs += line.billable_secs();
+                    item.amount += amount;
+                }
+                None => items.push(InvoiceItem {
+                    description,
+                    secs: line.billable_secs(),
+                    rate: line.rate,
+                    amount,
+                }),
+            }
+        }
+
+        let currency = if client.currency.is_empty() {
+            config.default_currency.clone()
+        } else {
+            client.currency.clone()
+        };
+        Self {
+            number: format!("{}-{}", sheet.to.format("%Y%m%d"), slug(&client.name)),
+            client: client.clone(),
+            from: sheet.from,
+            to: sheet.to,
+            issued: Local::now().date_naive(),
+            total_secs: items.iter().map(|i| i.secs).sum(),
+            total_amount: items.iter().map(|i| i.amount).sum(),
+            items,
+            currency,
+        }
+    }
+
+    /// A standalone HTML page, laid out for printing or saving as PDF.
+    pub fn to_html(&self, config: &InvoiceConfig) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">"
+        );
+        let _ = writeln!(out, "<title>Invoice {}</title>", html_escape(&self.number));
+        let _ = writeln!(out, "<style>{}</style>\n</head>\n<body>", INVOICE_CSS);
+
+        let _ = writeln!(out, "<header>\n<h1>Invoice</h1>\n<div class=\"from\">");
+        for line in &config.from {
+            let _ = writeln!(out, "{}<br>", html_escape(line));
+        }
+        let _ = writeln!(out, "</div>\n</header>");
+
+        let _ = writeln!(out, "<table class=\"meta\">");
+        let _ = writeln!(
+            out,
+            "<tr><th>Invoice no.</th><td>{}</td></tr>",
+            html_escape(&self.number)
+        );
+        let _ = writeln!(out, "<tr><th>Date</th><td>{}</td></tr>", self.issued);
+        let _ = writeln!(
+            out,
+            "<tr><th>Period</th><td>{} to {}</td></tr>",
+            self.from, self.to
+        );
+        let _ = writeln!(
+            out,
+            "<tr><th>Bill to</th><td>{}</td></tr>",
+            html_escape(&self.client.name)
+        );
+        let _ = writeln!(out, "</table>");
+
+        let _ = writeln!(out, "<table class=\"items\">");
+        let _ = writeln!(
+            out,
+            "<tr><th>Description</th><th>Hours</th><th>Rate</th><th>Amount</th></tr>"
+        );
+        for item in &self.items {
+            let rate = item
+                .rate
+                .map(|r| format!("{:.2}", r))
+                .unwrap_or_else(|| "-".to_string());
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.2}</td></tr>",
+                html_escape(&item.description),
+                format_hours(item.secs),
+                rate,
+                item.amount,
+            );
+        }
+        let _ = writeln!(
+            out,
+            "<tr class=\"total\"><td>Total</td><td>{}</td><td></td><td>{} {:.2}</td></tr>",
+            format_hours(self.total_secs),
+            html_escape(&self.currency),
+            self.total_amount,
+        );
+        let _ = writeln!(out, "</table>");
+
+        if let Some(notes) = &config.notes {
+            let _ = writeln!(out, "<p class=\"notes\">{}</p>", html_escape(notes));
+        }
+        let _ = writeln!(out, "</body>\n</html>");
+        out
+    }
+}
+
+const INVOICE_CSS: &str = "\
+body { font-family: sans-serif; max-width: 48em; margin: 2em auto; color: #222; }
+header { display: flex; justify-content: space-between; align-items: flex-start; }
+.from { text-align: right; }
+table { border-collapse: collapse; margin: 1.5em 0; }
+.meta th { text-align: left; padding-right: 2em; font-weight: normal; color: #666; }
+.items { width: 100%; }
+.items th, .items td { border-bottom: 1px solid #ccc; padding: .4em; text-align: right; }
+.items th:first-child, .items td:first-child { text-align: left; }
+.total td { font-weight: bold; border-bottom: none; }
+.notes { color: #666; }
+@media print { body { margin: 0; } }";
+
+/// Write `sheet` to `dir` in `format`. Invoices are written one file per
+/// billed client. Returns the files written.
+pub fn export_to(
+    db: &Database,
+    sheet: &Timesheet,
+    dir: &Path,
+    format: ReportFormat,
+    invoice: &InvoiceConfig,
+) -> anyhow::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let stem = format!("timesheet-{}-{}", sheet.from, sheet.to);
+    let mut written = Vec::new();
+
+    match format {
+        ReportFormat::Csv => {
+            let path = dir.join(format!("{}.csv", stem));
+            std::fs::write(&path, sheet.to_csv())?;
+            written.push(path);
+        }
+        ReportFormat::Markdown => {
+            let path = dir.join(format!("{}.md", stem));
+            std::fs::write(&path, sheet.to_markdown())?;
+            written.push(path);
+        }
+        ReportFormat::Invoice => {
+            for id in sheet.billed_clients() {
+                let Some(client) = db.get_client(id)? else {
+                    continue;
+                };
+                let inv = Invoice::from_timesheet(sheet, &client, invoice);
+                let path = dir.join(format!("invoice-{}.html", inv.number));
+                std::fs::write(&path, inv.to_html(invoice))?;
+                written.push(path);
+            }
+        }
+    }
+    Ok(written)
+}
+
+/// Hours with two decimals, e.g. `1.75`.
+pub fn format_hours(secs: i64) -> String {
+    format!("{:.2}", secs as f64 / 3600.0)
+}
+
+/// e.g. `EUR 40.00, USD 120.00`; `-` when empty.
+pub fn format_amounts(amounts: &BTreeMap<String, f64>) -> String {
+    if amounts.is_empty() {
+        return "-".to_string();
+    }
+    amounts
+        .iter()
+        .map(|(currency, amount)| format!("{} {:.2}", currency, amount))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn md_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn slug(s: &str) -> String {
+    let slug: String = s
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "client".to_string()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn entry(desc: &str, project: Option<&Project>, hour: u32, mins: i64) -> TimeEntry {
+        let start = Utc.with_ymd_and_hms(2024, 1, 15, hour, 0, 0).unwrap();
+        let mut entry = TimeEntry::create(desc, start, start + Duration::minutes(mins));
+        entry.project_id = project.map(|p| p.id);
+        entry
+    }
+
+    fn sheet(group_by: GroupBy, rounding: Rounding) -> Timesheet {
+        let acme = Client::new("Acme, Inc.").with_rate(100.0, "USD");
+        let site = Project::new("Website").with_client(acme.id);
+        let app = Project::new("App").with_client(acme.id);
+        let internal = Project::new("Internal");
+
+        let mut design = entry("Design", Some(&site), 9, 50);
+        design.tags = vec!["design".into(), "meeting".into()];
+        let build = entry("Build", Some(&app), 11, 70);
+        let mut admin = entry("Admin", Some(&internal), 14, 20);
+        admin.billable = false;
+        let stray = entry("Stray", None, 16, 10);
+
+        let day = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        Timesheet::build(
+            vec![design, build, admin, stray],
+            &[site, app, internal],
+            &[acme],
+            day,
+            day,
+            group_by,
+            rounding,
+        )
+    }
+
+    #[test]
+    fn test_rounding() {
+        let up = Rounding {
+            mins: 15,
+            mode: RoundingMode::Up,
+        };
+        let down = Rounding {
+            mins: 15,
+            mode: RoundingMode::Down,
+        };
+        let nearest = Rounding {
+            mins: 15,
+            mode: RoundingMode::Nearest,
+        };
+        let mins = |m: i64| m * 60;
+
+        assert_eq!(up.apply(mins(16)), mins(30));
+        assert_eq!(up.apply(mins(15)), mins(15));
+        assert_eq!(down.apply(mins(29)), mins(15));
+        assert_eq!(nearest.apply(mins(22)), mins(15));
+        assert_eq!(nearest.apply(mins(23)), mins(30));
+        assert_eq!(Rounding::none().apply(123), 123);
+    }
+
+    #[test]
+    fn test_group_by_project_and_client() {
+        let sheet = sheet(GroupBy::Project, Rounding::none());
+        let names: Vec<_> = sheet.groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, ["App", "Website", "Internal", "(no project)"]);
+        assert_eq!(sheet.total.secs, 150 * 60);
+        assert_eq!(sheet.total.billable_secs, 130 * 60);
+        // 120 billable minutes at the client's rate
+        assert!((sheet.total.amounts["USD"] - 200.0).abs() < 1e-9);
+
+        let sheet = self::sheet(GroupBy::Client, Rounding::none());
+        assert_eq!(sheet.groups[0].name, "Acme, Inc.");
+        assert_eq!(sheet.groups[0].secs, 120 * 60);
+        assert_eq!(sheet.groups[1].name, "(no client)");
+    }
+
+    #[test]
+    fn test_group_by_tag_counts_each_tag() {
+        let sheet = sheet(GroupBy::Tag, Rounding::none());
+        let secs = |name| sheet.groups.iter().find(|g| g.name == name).unwrap().secs;
+        assert_eq!(secs("design"), 50 * 60);
+        assert_eq!(secs("meeting"), 50 * 60);
+        assert_eq!(secs("(untagged)"), 100 * 60);
+        // Totals are per entry, not per group
+        assert_eq!(sheet.total.secs, 150 * 60);
+    }
+
+    #[test]
+    fn test_rounded_lines_add_up() {
+        let rounding = Rounding {
+            mins: 15,
+            mode: RoundingMode::Up,
+        };
+        let sheet = sheet(GroupBy::Project, rounding);
+        // 50 -> 60, 70 -> 75, 20 -> 30, 10 -> 15
+        assert_eq!(sheet.total.secs, 180 * 60);
+        let sum: i64 = sheet.lines.iter().map(|l| l.secs).sum();
+        assert_eq!(sum, sheet.total.secs);
+        assert!((sheet.total.amounts["USD"] - 225.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_exports() {
+        let sheet = sheet(GroupBy::Project, Rounding::none());
+
+        let csv = sheet.to_csv();
+        let mut rows = csv.lines();
+        assert!(rows.next().unwrap().starts_with("date,start,end,client"));
+        assert_eq!(
+            rows.next().unwrap(),
+            "2024-01-15,09:00,09:50,\"Acme, Inc.\",Website,Design,design;meeting,0.83,true,100.00,USD,83.33"
+        );
+        assert_eq!(csv.lines().count(), 5);
+
+        let md = sheet.to_markdown();
+        assert!(md.starts_with("# Timesheet 2024-01-15\n"));
+        assert!(md.contains("| App | 1 | 1.17 | 1.17 | USD 116.67 |"));
+        assert!(md.contains("| **Total** | **4** | **2.50** | **2.17** | **USD 200.00** |"));
+
+        let client = Client::new("Acme, Inc.").with_rate(100.0, "USD");
+        let mut sheet = sheet;
+        for line in &mut sheet.lines {
+            if line.client_id.is_some() {
+                line.client_id = Some(client.id);
+            }
+        }
+        assert_eq!(sheet.billed_clients(), vec![client.id]);
+        let invoice = Invoice::from_timesheet(&sheet, &client, &InvoiceConfig::default());
+        assert_eq!(invoice.number, "20240115-acme-inc");
+        assert_eq!(invoice.items.len(), 2);
+        assert!((invoice.total_amount - 200.0).abs() < 1e-9);
+        let html = invoice.to_html(&InvoiceConfig::default());
+        assert!(html.contains("<th>Bill to</th><td>Acme, Inc.</td>"));
+        assert!(html.contains("USD 200.00"));
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::app::{App, InputField, View};
 use crate::pomodoro::SessionType;
+use crate::report;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -28,6 +29,10 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         draw_input(f, app);
     }
 
+    if app.idle_prompt.is_some() {
+        draw_idle_prompt(f, app);
+    }
+
     if let Some(msg) = &app.message {
         draw_message(f, msg);
     }
@@ -205,50 +210,78 @@ fn draw_entries_view(f: &mut Frame, app: &App, area: Rect) {
 }
 
 fn draw_reports_view(f: &mut Frame, app: &App, area: Rect) {
+    let Some(sheet) = &app.timesheet else {
+        return;
+    };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .constraints([Constraint::Length(6), Constraint::Min(0)])
         .split(area);
 
-    // Today summary
-    let today_total = app.today_total();
-    let summary = format!(
-        "Today: {}\nEntries: {}",
-        App::format_duration(today_total),
-        app.entries.len()
-    );
-
-    let today = Paragraph::new(summary)
-        .block(Block::default().borders(Borders::ALL).title(" Today "))
-        .wrap(Wrap { trim: true });
-    f.render_widget(today, chunks[0]);
-
-    // Project breakdown
-    if app.projects.is_empty() {
-        let msg = Paragraph::new("No projects yet")
-            .block(Block::default().borders(Borders::ALL).title(" By Project "))
+    // Summary
+    let summary = vec![
+        Line::from(vec![
+            Span::styled("Total: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                format!("{}h", report::format_hours(sheet.total.secs)),
+                Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!("  ({} entries)", sheet.total.entries)),
+        ]),
+        Line::from(format!(
+            "Billable: {}h ({:.0}%)",
+            report::format_hours(sheet.total.billable_secs),
+            sheet.billable_percent()
+        )),
+        Line::from(format!("Amount: {}", report::format_amounts(&sheet.total.amounts))),
+        Line::from(Span::styled(
+            format!("Rounding: {}", sheet.rounding.describe()),
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let summary = Paragraph::new(summary)
+        .block(Block::default().borders(Borders::ALL).title(format!(" {} ", sheet.title())));
+    f.render_widget(summary, chunks[0]);
+
+    // Groups
+    let title = format!(" By {} (g to change) ", sheet.group_by.name());
+    if sheet.groups.is_empty() {
+        let msg = Paragraph::new("No entries in this range")
+            .block(Block::default().borders(Borders::ALL).title(title))
             .alignment(Alignment::Center);
         f.render_widget(msg, chunks[1]);
-    } else {
-        let rows: Vec<Row> = app.projects.iter().map(|p| {
-            let hours = app.project_hours.get(&p.id).copied().unwrap_or(0.0);
-            let budget = p.budget_hours.map(|b| format!("{:.1}h", b)).unwrap_or_else(|| "-".to_string());
-            Row::new(vec![
-                p.name.clone(),
-                format!("{:.1}h", hours),
-                budget,
-            ])
-        }).collect();
+        return;
+    }
 
-        let table = Table::new(
-            rows,
-            [Constraint::Percentage(50), Constraint::Percentage(25), Constraint::Percentage(25)],
-        )
-        .header(Row::new(vec!["Project", "Hours", "Budget"]).style(Style::default().add_modifier(Modifier::BOLD)))
-        .block(Block::default().borders(Borders::ALL).title(" By Project "));
+    let max = sheet.groups.iter().map(|g| g.secs).max().unwrap_or(1).max(1);
+    let rows: Vec<Row> = sheet.groups.iter().map(|g| {
+        let filled = (g.secs * 10 / max) as usize;
+        Row::new(vec![
+            g.name.clone(),
+            report::format_hours(g.secs),
+            report::format_hours(g.billable_secs),
+            report::format_amounts(&g.amounts),
+            format!("{}{}", "█".repeat(filled), "░".repeat(10 - filled)),
+        ])
+    }).collect();
 
-        f.render_widget(table, chunks[1]);
-    }
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(30),
+            Constraint::Length(8),
+            Constraint::Length(9),
+            Constraint::Percentage(30),
+            Constraint::Length(10),
+        ],
+    )
+    .header(
+        Row::new(vec![sheet.group_by.name(), "Hours", "Billable", "Amount", ""])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::default().borders(Borders::ALL).title(title));
+
+    f.render_widget(table, chunks[1]);
 }
 
 fn draw_projects_view(f: &mut Frame, app: &App, area: Rect) {
@@ -285,7 +318,9 @@ fn draw_footer(f: &mut Frame, app: &App, area: Rect) {
     let help = match app.view {
         View::Timer => "s:Start/Stop  p:Pomodoro  Enter:Description  h/l:Date  ?:Help  q:Quit",
         View::Entries => "j/k:Navigate  e:Edit  d:Delete  h/l:Date  ?:Help  q:Quit",
-        View::Reports => "h/l:Date  ?:Help  q:Quit",
+        View::Reports => {
+            "w/m:Week/Month  h/l:Prev/Next  R:Range  g:Group  x:Export  i:Invoice  ?:Help  q:Quit"
+        }
         View::Projects => "j/k:Navigate  a:Add project  ?:Help  q:Quit",
     };
 
@@ -318,11 +353,24 @@ Views:
   r               Reports view
   P               Projects view
 
+Reports:
+  w / m           This week / this month
+  h/l             Previous/next range
+  R               Enter a date range
+  g               Group by client, project or tag
+  x               Export CSV and Markdown
+  i               Write invoices for billed clients
+
 Actions:
   a               Add project (in Projects view)
   e               Edit entry (in Entries view)
   d               Delete entry (in Entries view)
 
+Idle prompt:
+  k               Keep idle time
+  d               Discard idle time
+  r               Reassign idle time to a project
+
 General:
   ?               Show this help
   q               Quit
@@ -343,6 +391,7 @@ fn draw_input(f: &mut Frame, app: &App) {
     let title = match app.input_field {
         InputField::Description => "Enter description",
         InputField::ProjectName => "Enter project name",
+        InputField::ReportRange => "Report range (YYYY-MM-DD..YYYY-MM-DD)",
         InputField::None => "",
     };
 
@@ -354,6 +403,48 @@ fn draw_input(f: &mut Frame, app: &App) {
     f.set_cursor_position((area.x + 1 + app.input_buffer.len() as u16, area.y + 1));
 }
 
+fn draw_idle_prompt(f: &mut Frame, app: &App) {
+    let Some(prompt) = &app.idle_prompt else {
+        return;
+    };
+    let area = centered_rect(50, 40, f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" You were idle ")
+        .border_style(Style::default().fg(Color::Yellow));
+
+    if prompt.reassigning {
+        let items: Vec<ListItem> = app.projects.iter().enumerate().map(|(i, p)| {
+            let style = if i == prompt.selected {
+                Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(p.name.clone()).style(style)
+        }).collect();
+        let list = List::new(items).block(block.title_bottom(" Enter:Move  Esc:Back "));
+        f.render_widget(list, area);
+        return;
+    }
+
+    let text = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            prompt.span.describe(),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from("k: Keep it on the running timer"),
+        Line::from("d: Discard it"),
+        Line::from("r: Reassign it to another project"),
+    ];
+    let popup = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Center);
+    f.render_widget(popup, area);
+}
+
 fn draw_message(f: &mut Frame, msg: &str) {
     let area = Rect::new(
         f.area().x + 2,
@@ -1,6 +1,12 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent, MouseEventKind};
+use std::collections::HashMap;
+use tui_shell::{
+    AppEvent, AppId, AppManager, AppSession, IpcMessage, JournalEntry, LayoutState, Session,
+    SessionManager, ShellResult,
+};
 
 use crate::config::Config;
+use crate::session;
 use crate::window::{Desktop, Rect, SnapPosition, Window, WindowId, WindowState};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -23,6 +29,12 @@ pub struct App {
     pub launcher_input: String,
     pub screen_width: u16,
     pub screen_height: u16,
+    /// Running subprocess apps.
+    pub apps: AppManager,
+    /// Window -> running app mapping.
+    pub running: HashMap<WindowId, AppId>,
+    /// Session snapshot and crash journal.
+    pub sessions: SessionManager,
 }
 
 impl App {
@@ -47,12 +59,27 @@ impl App {
             launcher_input: String::new(),
             screen_width: 80,
             screen_height: 24,
+            apps: AppManager::new(),
+            running: HashMap::new(),
+            sessions: SessionManager::new(session::session_path(), session::SAVE_INTERVAL_SECS),
         };
+        if let Ok((width, height)) = crossterm::terminal::size() {
+            app.set_screen_size(width, height);
+        }
+
+        match app.sessions.load() {
+            Ok(()) if !app.sessions.session().apps.is_empty() => app.restore_session(),
+            result => {
+                if let Err(e) = result {
+                    app.message = Some(format!("Failed to load session: {}", e));
+                }
 
-        // Add demo windows
-        app.spawn_window("task-manager", "Task Manager");
-        app.spawn_window("note-manager", "Note Manager");
-        app.spawn_window("file-manager", "File Manager");
+                // Start with a few suite apps
+                app.spawn_window("task-manager", "Task Manager");
+                app.spawn_window("note-manager-folder", "Note Manager");
+                app.spawn_window("file-manager", "File Manager");
+            }
+        }
 
         app
     }
@@ -76,6 +103,232 @@ impl App {
         &mut self.desktops[self.active_desktop]
     }
 
+    /// Pump app output and report apps that exited.
+    pub fn tick(&mut self) {
+        for event in self.apps.poll() {
+            let app_id = match event {
+                AppEvent::Exited { id, .. } | AppEvent::Restarted { id, .. } => id,
+            };
+            let name = self
+                .apps
+                .get(app_id)
+                .map(|h| h.manifest.name.clone())
+                .unwrap_or_default();
+            self.message = Some(match event {
+                AppEvent::Exited { exit, .. } if exit.success => format!("{} exited", name),
+                AppEvent::Exited {
+                    exit,
+                    restart_in: Some(delay),
+                    ..
+                } => format!(
+                    "{} exited with code {}, restarting in {:.1}s",
+                    name,
+                    exit.code,
+                    delay.as_secs_f32()
+                ),
+                AppEvent::Exited { exit, .. } => format!("{} exited with code {}", name, exit.code),
+                AppEvent::Restarted { attempt, .. } => format!("{} restarted (attempt {})", name, attempt),
+            });
+        }
+
+        // Bus traffic is routed by the manager; surface what's left
+        for (id, msg) in self.apps.poll_ipc() {
+            match msg {
+                IpcMessage::SessionState { state } => self.journal(JournalEntry::AppState { id, state }),
+                IpcMessage::Notification(notification) => self.message = Some(notification.message),
+                _ => {}
+            }
+        }
+
+        if self.sessions.needs_save() {
+            // Ask apps for fresh state; replies land in the journal
+            self.apps.request_session_save();
+        }
+        if let Err(e) = self.sessions.try_auto_save() {
+            self.message = Some(format!("Failed to save session: {}", e));
+        }
+    }
+
+    /// Journal a session change, reporting failures in the taskbar.
+    fn journal(&mut self, entry: JournalEntry) {
+        if let Err(e) = self.sessions.record(entry) {
+            self.message = Some(format!("Failed to journal session: {}", e));
+        }
+    }
+
+    /// Journal window geometry, desktop membership and focus.
+    fn commit_layout(&mut self) {
+        let layout = self.layout_state();
+        self.journal(JournalEntry::Layout { layout });
+        let workspaces = self.shell_workspaces();
+        self.journal(JournalEntry::Workspaces { workspaces });
+        let id = self.focused_app_id();
+        self.journal(JournalEntry::Focus { id });
+    }
+
+    fn layout_state(&self) -> LayoutState {
+        LayoutState {
+            floating: self
+                .desktops
+                .iter()
+                .flat_map(|desktop| desktop.windows.iter())
+                .filter_map(|w| self.running.get(&w.id).map(|&app_id| session::to_floating(w, app_id)))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn shell_workspaces(&self) -> Vec<tui_shell::Workspace> {
+        self.desktops
+            .iter()
+            .enumerate()
+            .map(|(i, desktop)| tui_shell::Workspace {
+                id: desktop.id as u64,
+                name: desktop.name.clone(),
+                apps: desktop
+                    .windows
+                    .iter()
+                    .filter_map(|w| self.running.get(&w.id).copied())
+                    .collect(),
+                active: i == self.active_desktop,
+            })
+            .collect()
+    }
+
+    /// Write a session snapshot, e.g. on quit.
+    pub fn save_session(&mut self) -> ShellResult<()> {
+        let session = Session {
+            layout: self.layout_state(),
+            apps: self.apps.save_sessions()?,
+            focused: self.focused_app_id(),
+            workspaces: self.shell_workspaces(),
+        };
+        self.sessions.update(session)?;
+        self.sessions.save()
+    }
+
+    /// Relaunch the apps of the loaded session and put their windows back.
+    fn restore_session(&mut self) {
+        let session = self.sessions.session().clone();
+
+        let areas = session
+            .layout
+            .floating
+            .iter()
+            .map(|w| (w.app_id, session::content_area(w)))
+            .collect();
+
+        let mut relaunched = HashMap::new();
+        let mut failed = Vec::new();
+        for (old, result) in self.apps.restore_session(&session, &areas) {
+            match result {
+                Ok(new) => {
+                    relaunched.insert(old, new);
+                }
+                Err(e) => failed.push(e.to_string()),
+            }
+        }
+        if !failed.is_empty() {
+            self.message = Some(format!("Failed to restore: {}", failed.join("; ")));
+        }
+
+        self.active_desktop = session
+            .workspaces
+            .iter()
+            .find(|ws| ws.active)
+            .and_then(|saved| self.desktops.iter().position(|d| d.id as u64 == saved.id))
+            .unwrap_or(0);
+
+        let layout = session.layout.remap(&relaunched);
+        for floating in &layout.floating {
+            let name = self
+                .apps
+                .get(floating.app_id)
+                .map(|h| h.manifest.name.clone())
+                .unwrap_or_default();
+            let desktop = session
+                .workspaces
+                .iter()
+                .find(|ws| ws.apps.iter().any(|id| relaunched.get(id) == Some(&floating.app_id)))
+                .and_then(|saved| self.desktops.iter().position(|d| d.id as u64 == saved.id))
+                .unwrap_or(self.active_desktop);
+
+            let id = self.next_id();
+            let window =
+                session::from_floating(id, &name, floating, self.screen_width, self.screen_height);
+            let desktop = &mut self.desktops[desktop];
+            desktop.windows.push(window);
+            desktop.focused = Some(id);
+            self.running.insert(id, floating.app_id);
+        }
+
+        // Apps without a window have nowhere to show
+        let placed: Vec<AppId> = self.running.values().copied().collect();
+        for id in relaunched.values().filter(|id| !placed.contains(id)) {
+            let _ = self.apps.kill(*id);
+        }
+
+        let focused = session.focused.and_then(|id| relaunched.get(&id));
+        let window = focused.and_then(|app_id| {
+            self.running.iter().find(|(_, id)| *id == app_id).map(|(&window, _)| window)
+        });
+        if let Some(window) = window {
+            self.current_desktop_mut().focused = Some(window);
+        }
+
+        // Replace the old IDs on disk so later journal entries apply
+        if let Err(e) = self.save_session() {
+            self.message = Some(format!("Failed to save session: {}", e));
+        }
+    }
+
+    /// Running app in the focused window, if any.
+    pub fn focused_app_id(&self) -> Option<AppId> {
+        let window_id = self.current_desktop().focused?;
+        self.running.get(&window_id).copied()
+    }
+
+    /// Forward pasted text to the focused app.
+    pub fn handle_paste(&mut self, text: String) {
+        if self.mode == Mode::Normal {
+            self.forward_to_focused(Event::Paste(text));
+        }
+    }
+
+    /// Click-to-raise, then forward the mouse event to the window's app.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.mode != Mode::Normal || self.show_help {
+            return;
+        }
+
+        let target = self
+            .current_desktop()
+            .windows_sorted_by_z()
+            .into_iter()
+            .rev()
+            .find(|w| w.rect.contains(mouse.column, mouse.row))
+            .map(|w| w.id);
+        let Some(window_id) = target else {
+            return;
+        };
+
+        if matches!(mouse.kind, MouseEventKind::Down(_)) {
+            self.current_desktop_mut().raise_window(window_id);
+            self.commit_layout();
+        }
+        if let Some(&app_id) = self.running.get(&window_id) {
+            let _ = self.apps.send_event(app_id, &Event::Mouse(mouse));
+        }
+    }
+
+    fn forward_to_focused(&mut self, event: Event) {
+        if let Some(app_id) = self.focused_app_id() {
+            if let Err(e) = self.apps.send_event(app_id, &event) {
+                self.message = Some(e.to_string());
+            }
+        }
+    }
+
     pub fn handle_key(&mut self, key: KeyEvent) -> bool {
         self.message = None;
 
@@ -90,17 +343,21 @@ impl App {
     fn handle_normal_key(&mut self, key: KeyEvent) -> bool {
         let is_mod = key.modifiers.contains(KeyModifiers::CONTROL);
         let is_shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let has_app = self.focused_app_id().is_some();
 
         match key.code {
             // Exit
             KeyCode::Char('q') if is_mod => return true,
-            KeyCode::Char('c') if is_mod => return true,
+            KeyCode::Char('c') if is_mod && !has_app => return true,
 
             // Help
-            KeyCode::Char('?') => {
+            KeyCode::Char('?') if !has_app => {
                 self.show_help = !self.show_help;
             }
-            KeyCode::Esc => {
+            KeyCode::F(1) => {
+                self.show_help = !self.show_help;
+            }
+            KeyCode::Esc if self.show_help || !has_app => {
                 self.show_help = false;
             }
 
@@ -113,9 +370,11 @@ impl App {
             // Window cycling
             KeyCode::Tab if is_mod && is_shift => {
                 self.current_desktop_mut().cycle_focus(true);
+                self.commit_layout();
             }
             KeyCode::Tab if is_mod => {
                 self.current_desktop_mut().cycle_focus(false);
+                self.commit_layout();
             }
 
             // Close window
@@ -155,6 +414,7 @@ impl App {
                         window.minimize();
                     }
                 }
+                self.commit_layout();
             }
 
             // Snap left/right
@@ -193,7 +453,8 @@ impl App {
                 self.cascade_windows();
             }
 
-            _ => {}
+            // Everything else goes to the focused app
+            _ => self.forward_to_focused(Event::Key(key)),
         }
 
         false
@@ -206,6 +467,7 @@ impl App {
         match key.code {
             KeyCode::Esc | KeyCode::Enter => {
                 self.mode = Mode::Normal;
+                self.commit_layout();
             }
             KeyCode::Left => {
                 if let Some(window) = self.current_desktop_mut().focused_window_mut() {
@@ -239,6 +501,7 @@ impl App {
         match key.code {
             KeyCode::Esc | KeyCode::Enter => {
                 self.mode = Mode::Normal;
+                self.commit_layout();
             }
             KeyCode::Left => {
                 if let Some(window) = self.current_desktop_mut().focused_window_mut() {
@@ -300,6 +563,19 @@ impl App {
 
         self.current_desktop_mut().add_window(window);
 
+        // The area is corrected on the next render
+        match self.apps.launch(name, &[]) {
+            Ok(app_id) => {
+                self.running.insert(id, app_id);
+                let desktop_id = self.current_desktop().id as u64;
+                self.apps.add_to_workspace(app_id, desktop_id);
+                let app = AppSession::new(name).with_id(app_id).in_workspace(desktop_id);
+                self.journal(JournalEntry::AppOpened { app });
+                self.commit_layout();
+            }
+            Err(e) => self.message = Some(e.to_string()),
+        }
+
         // Update cascade offset
         self.cascade_offset = (self.cascade_offset + 3) % 15;
     }
@@ -307,6 +583,11 @@ impl App {
     fn close_focused(&mut self) {
         if let Some(id) = self.current_desktop().focused {
             self.current_desktop_mut().remove_window(id);
+            if let Some(app_id) = self.running.remove(&id) {
+                let _ = self.apps.kill(app_id);
+                self.journal(JournalEntry::AppClosed { id: app_id });
+            }
+            self.commit_layout();
         }
     }
 
@@ -321,6 +602,7 @@ impl App {
                 window.maximize(screen_width, screen_height);
             }
         }
+        self.commit_layout();
     }
 
     fn minimize_focused(&mut self) {
@@ -329,6 +611,7 @@ impl App {
         }
         // Focus next window
         self.current_desktop_mut().cycle_focus(false);
+        self.commit_layout();
     }
 
     fn snap_focused(&mut self, position: SnapPosition) {
@@ -338,11 +621,13 @@ impl App {
         if let Some(window) = self.current_desktop_mut().focused_window_mut() {
             window.snap(position, screen_width, screen_height);
         }
+        self.commit_layout();
     }
 
     fn switch_desktop(&mut self, index: usize) {
         if index < self.desktops.len() {
             self.active_desktop = index;
+            self.commit_layout();
             self.message = Some(format!("Switched to {}", self.desktops[index].name));
         }
     }
@@ -365,6 +650,7 @@ impl App {
                     .map(|w| w.id);
 
                 self.desktops[index].add_window(window);
+                self.commit_layout();
                 self.message = Some(format!("Moved to {}", self.desktops[index].name));
             }
         }
@@ -382,13 +668,20 @@ impl App {
                 }
             }
         }
+        self.commit_layout();
         self.message = Some("Windows cascaded".to_string());
     }
 
     pub fn focused_title(&self) -> String {
         self.current_desktop()
             .focused_window()
-            .map(|w| w.title.clone())
+            .map(|w| {
+                self.running
+                    .get(&w.id)
+                    .and_then(|&app_id| self.apps.title(app_id))
+                    .unwrap_or(&w.title)
+                    .to_string()
+            })
             .unwrap_or_else(|| "No window".to_string())
     }
 }
@@ -0,0 +1,87 @@
+//! Session persistence through the shell's crash journal.
+//!
+//! Windows of every desktop are journaled as floating windows; desktop
+//! membership is journaled as workspaces.
+
+use std::path::PathBuf;
+use tui_shell::compositor::FloatingWindow;
+use tui_shell::AppId;
+
+use crate::window::{Rect, Window, WindowId, WindowState};
+
+/// Seconds between automatic session snapshots.
+pub const SAVE_INTERVAL_SECS: u64 = 60;
+
+pub fn session_path() -> PathBuf {
+    directories::ProjectDirs::from("", "", "tui-shell-floating")
+        .map(|d| d.data_dir().join("session.json"))
+        .unwrap_or_else(|| PathBuf::from("session.json"))
+}
+
+/// Convert a window showing `app_id` into its journaled form.
+///
+/// Maximized windows keep the rect they restore to.
+pub fn to_floating(window: &Window, app_id: AppId) -> FloatingWindow {
+    let rect = match window.state {
+        WindowState::Maximized => window.saved_rect,
+        WindowState::Normal | WindowState::Minimized => window.rect,
+    };
+    FloatingWindow {
+        z_order: window.z_order.min(u16::MAX as u32) as u16,
+        minimized: window.state == WindowState::Minimized,
+        maximized: window.state == WindowState::Maximized,
+        ..FloatingWindow::new(app_id, rect.x, rect.y, rect.width, rect.height)
+    }
+}
+
+/// Rebuild a window from its journaled form.
+pub fn from_floating(
+    id: WindowId,
+    name: &str,
+    floating: &FloatingWindow,
+    screen_width: u16,
+    screen_height: u16,
+) -> Window {
+    let rect = Rect::new(floating.x, floating.y, floating.width, floating.height);
+    let mut window = Window::new(id, name.to_string(), name.to_string(), rect);
+    window.z_order = floating.z_order as u32;
+    if floating.maximized {
+        window.maximize(screen_width, screen_height);
+    } else if floating.minimized {
+        window.minimize();
+    }
+    window
+}
+
+/// Area the app gets inside a window, below its border and title row.
+pub fn content_area(floating: &FloatingWindow) -> ratatui::layout::Rect {
+    ratatui::layout::Rect::new(
+        floating.x.saturating_add(1),
+        floating.y.saturating_add(2),
+        floating.width.saturating_sub(2),
+        floating.height.saturating_sub(3),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_floating_round_trip() {
+        let mut window = Window::new(1, "app".to_string(), "App".to_string(), Rect::new(5, 3, 40, 20));
+        window.z_order = 7;
+        window.maximize(100, 50);
+
+        let floating = to_floating(&window, 42);
+        assert_eq!(floating.app_id, 42);
+        assert_eq!((floating.x, floating.y, floating.width, floating.height), (5, 3, 40, 20));
+        assert!(floating.maximized);
+
+        let restored = from_floating(2, "app", &floating, 100, 50);
+        assert_eq!(restored.state, WindowState::Maximized);
+        assert_eq!(restored.z_order, 7);
+        assert_eq!(restored.saved_rect.x, 5);
+        assert_eq!(content_area(&floating), ratatui::layout::Rect::new(6, 5, 38, 17));
+    }
+}
@@ -3,8 +3,11 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph},
 };
 
+use std::collections::HashMap;
+use tui_shell::{AppId, AppManager};
+
 use crate::app::{App, Mode};
-use crate::window::WindowState;
+use crate::window::{WindowId, WindowState};
 
 pub fn render(frame: &mut Frame, app: &mut App) {
     let area = frame.area();
@@ -34,22 +37,28 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     }
 }
 
-fn render_desktop(frame: &mut Frame, app: &App, area: Rect) {
+fn render_desktop(frame: &mut Frame, app: &mut App, area: Rect) {
     // Desktop background
     let bg = Block::default().style(Style::default().bg(Color::DarkGray));
     frame.render_widget(bg, area);
 
-    let desktop = app.current_desktop();
+    let desktop = &app.desktops[app.active_desktop];
     let focused_id = desktop.focused;
 
     // Render windows in z-order
     for window in desktop.windows_sorted_by_z() {
         let is_focused = focused_id == Some(window.id);
-        render_window(frame, window, is_focused, area);
+        render_window(frame, window, is_focused, &mut app.apps, &app.running);
     }
 }
 
-fn render_window(frame: &mut Frame, window: &crate::window::Window, focused: bool, _desktop_area: Rect) {
+fn render_window(
+    frame: &mut Frame,
+    window: &crate::window::Window,
+    focused: bool,
+    apps: &mut AppManager,
+    running: &HashMap<WindowId, AppId>,
+) {
     if window.state == WindowState::Minimized {
         return;
     }
@@ -70,10 +79,15 @@ fn render_window(frame: &mut Frame, window: &crate::window::Window, focused: boo
     };
 
     // Title bar
+    let app_id = running.get(&window.id).copied();
+    let exited = app_id
+        .and_then(|app_id| apps.get(app_id))
+        .is_some_and(|h| h.exit.is_some());
     let title = format!(
-        " {} {}",
+        " {} {}{}",
         window.title,
-        if window.always_on_top { "[T]" } else { "" }
+        if window.always_on_top { "[T]" } else { "" },
+        if exited { "[exited]" } else { "" }
     );
     let title_len = title.len().min(rect.width.saturating_sub(8) as usize);
     let title_truncated = &title[..title_len];
@@ -111,6 +125,20 @@ fn render_window(frame: &mut Frame, window: &crate::window::Window, focused: boo
             inner.height.saturating_sub(1),
         );
 
+        if let Some(app_id) = app_id {
+            let _ = apps.resize_app(app_id, content_area);
+            if let Some(handle) = apps.get(app_id) {
+                frame.render_widget(&handle.buffer, content_area);
+            }
+            if focused {
+                if let Some((x, y)) = apps.cursor(app_id) {
+                    frame.set_cursor_position(Position::new(x, y));
+                }
+            }
+            return;
+        }
+
+        // No running process: show what would have been launched
         let content = vec![
             Line::from(""),
             Line::from(Span::styled(
@@ -118,8 +146,8 @@ fn render_window(frame: &mut Frame, window: &crate::window::Window, focused: boo
                 Style::default().add_modifier(Modifier::BOLD),
             )),
             Line::from(""),
-            Line::from("  This is a placeholder for the"),
-            Line::from("  application content."),
+            Line::from("  This app is not running."),
+            Line::from("  Make sure its binary is built."),
         ];
 
         let content_widget = Paragraph::new(content).style(Style::default().bg(Color::Black));
@@ -236,8 +264,9 @@ fn render_help(frame: &mut Frame) {
         Line::from("  Ctrl+p         Launch app"),
         Line::from("  Ctrl+t         Toggle always on top"),
         Line::from("  Ctrl+a         Cascade windows"),
-        Line::from("  ?              Toggle help"),
+        Line::from("  ? / F1         Toggle help"),
         Line::from("  Ctrl+q         Quit"),
+        Line::from("  Other keys     Sent to the focused app"),
     ];
 
     let help = Paragraph::new(help_text)
@@ -1,6 +1,13 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use ratatui::layout::{Position, Rect};
+use std::collections::HashMap;
+use tui_shell::{
+    AppEvent, AppId, AppManager, AppSession, IpcMessage, JournalEntry, LayoutState, Session,
+    SessionManager, ShellResult,
+};
 
 use crate::config::Config;
+use crate::session;
 use crate::tab::{Direction, Tab, TabId};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -18,6 +25,14 @@ pub struct App {
     pub show_help: bool,
     pub message: Option<String>,
     pub launcher_input: String,
+    /// Split requested before the launcher opened.
+    pub pending_split: Option<Direction>,
+    /// Running subprocess apps.
+    pub apps: AppManager,
+    /// Screen areas of visible apps, from the last render.
+    pub app_areas: HashMap<AppId, Rect>,
+    /// Session snapshot and crash journal.
+    pub sessions: SessionManager,
 }
 
 impl App {
@@ -31,16 +46,29 @@ impl App {
             show_help: false,
             message: None,
             launcher_input: String::new(),
+            pending_split: None,
+            apps: AppManager::new(),
+            app_areas: HashMap::new(),
+            sessions: SessionManager::new(session::session_path(), session::SAVE_INTERVAL_SECS),
         };
 
-        // Create initial tabs
-        app.new_tab("task-manager", "Task Manager");
-        app.new_tab("note-manager", "Note Manager");
-        app.new_tab("file-manager", "File Manager");
+        match app.sessions.load() {
+            Ok(()) if !app.sessions.session().apps.is_empty() => app.restore_session(),
+            result => {
+                if let Err(e) = result {
+                    app.message = Some(format!("Failed to load session: {}", e));
+                }
+
+                // Create initial tabs
+                app.new_tab("task-manager", "Task Manager");
+                app.new_tab("note-manager-folder", "Note Manager");
+                app.new_tab("file-manager", "File Manager");
 
-        // Pin first tab
-        if let Some(tab) = app.tabs.first_mut() {
-            tab.pinned = true;
+                // Pin first tab
+                if let Some(tab) = app.tabs.first_mut() {
+                    tab.pinned = true;
+                }
+            }
         }
 
         app
@@ -60,6 +88,233 @@ impl App {
         self.tabs.get_mut(self.active_tab)
     }
 
+    /// Pump app output and report apps that exited.
+    pub fn tick(&mut self) {
+        for event in self.apps.poll() {
+            let app_id = match event {
+                AppEvent::Exited { id, .. } | AppEvent::Restarted { id, .. } => id,
+            };
+            let name = self
+                .apps
+                .get(app_id)
+                .map(|h| h.manifest.name.clone())
+                .unwrap_or_default();
+            self.message = Some(match event {
+                AppEvent::Exited { exit, .. } if exit.success => format!("{} exited", name),
+                AppEvent::Exited {
+                    exit,
+                    restart_in: Some(delay),
+                    ..
+                } => format!(
+                    "{} exited with code {}, restarting in {:.1}s",
+                    name,
+                    exit.code,
+                    delay.as_secs_f32()
+                ),
+                AppEvent::Exited { exit, .. } => format!("{} exited with code {}", name, exit.code),
+                AppEvent::Restarted { attempt, .. } => format!("{} restarted (attempt {})", name, attempt),
+            });
+        }
+
+        // Bus traffic is routed by the manager; surface what's left
+        for (id, msg) in self.apps.poll_ipc() {
+            match msg {
+                IpcMessage::SessionState { state } => self.journal(JournalEntry::AppState { id, state }),
+                IpcMessage::Notification(notification) => self.message = Some(notification.message),
+                _ => {}
+            }
+        }
+
+        if self.sessions.needs_save() {
+            // Ask apps for fresh state; replies land in the journal
+            self.apps.request_session_save();
+        }
+        if let Err(e) = self.sessions.try_auto_save() {
+            self.message = Some(format!("Failed to save session: {}", e));
+        }
+    }
+
+    /// Journal a session change, reporting failures in the status bar.
+    fn journal(&mut self, entry: JournalEntry) {
+        if let Err(e) = self.sessions.record(entry) {
+            self.message = Some(format!("Failed to journal session: {}", e));
+        }
+    }
+
+    /// Journal the active tab's panes, the tabs and focus.
+    fn commit_layout(&mut self) {
+        let layout = self.layout_state();
+        self.journal(JournalEntry::Layout { layout });
+        let workspaces = self.shell_workspaces();
+        self.journal(JournalEntry::Workspaces { workspaces });
+        let id = self.focused_app_id();
+        self.journal(JournalEntry::Focus { id });
+    }
+
+    fn layout_state(&self) -> LayoutState {
+        LayoutState {
+            tiled: self.current_tab().and_then(|tab| session::to_layout(&tab.layout)),
+            ..Default::default()
+        }
+    }
+
+    fn shell_workspaces(&self) -> Vec<tui_shell::Workspace> {
+        self.tabs
+            .iter()
+            .enumerate()
+            .map(|(i, tab)| tui_shell::Workspace {
+                id: tab.id as u64,
+                name: tab.title().to_string(),
+                apps: tab.layout.app_ids(),
+                active: i == self.active_tab,
+            })
+            .collect()
+    }
+
+    /// Write a session snapshot, e.g. on quit.
+    pub fn save_session(&mut self) -> ShellResult<()> {
+        let session = Session {
+            layout: self.layout_state(),
+            apps: self.apps.save_sessions()?,
+            focused: self.focused_app_id(),
+            workspaces: self.shell_workspaces(),
+        };
+        self.sessions.update(session)?;
+        self.sessions.save()
+    }
+
+    /// Relaunch the apps of the loaded session and rebuild their tabs.
+    fn restore_session(&mut self) {
+        let session = self.sessions.session().clone();
+
+        // Content area between the tab bar and the status bar
+        let (cols, rows) = crossterm::terminal::size().unwrap_or((80, 24));
+        let screen = Rect::new(0, 1, cols, rows.saturating_sub(2));
+        let areas = session
+            .layout
+            .tiled
+            .as_ref()
+            .map(|node| node.layout(screen))
+            .unwrap_or_default();
+
+        let mut relaunched = HashMap::new();
+        let mut failed = Vec::new();
+        for (old, result) in self.apps.restore_session(&session, &areas) {
+            match result {
+                Ok(new) => {
+                    relaunched.insert(old, new);
+                }
+                Err(e) => failed.push(e.to_string()),
+            }
+        }
+        if !failed.is_empty() {
+            self.message = Some(format!("Failed to restore: {}", failed.join("; ")));
+        }
+
+        let names: HashMap<AppId, String> = relaunched
+            .values()
+            .filter_map(|&id| self.apps.get(id).map(|h| (id, h.manifest.name.clone())))
+            .collect();
+        let active = session.layout.tiled.as_ref().and_then(|node| node.remap(&relaunched));
+
+        for saved in &session.workspaces {
+            let node = if saved.active {
+                active.clone()
+            } else {
+                let apps: Vec<AppId> =
+                    saved.apps.iter().filter_map(|id| relaunched.get(id).copied()).collect();
+                session::even_split(&apps)
+            };
+            let Some(node) = node else {
+                continue;
+            };
+
+            let id = self.next_id();
+            let mut tab = Tab::new(id, "", "");
+            tab.layout = session::from_layout(&node, &names);
+            if saved.active {
+                self.active_tab = self.tabs.len();
+            }
+            self.tabs.push(tab);
+        }
+
+        // Apps without a tab have nowhere to show
+        let placed: Vec<AppId> = self.tabs.iter().flat_map(|tab| tab.layout.app_ids()).collect();
+        for id in relaunched.values().filter(|id| !placed.contains(id)) {
+            let _ = self.apps.kill(*id);
+        }
+
+        if let Some(&focused) = session.focused.and_then(|id| relaunched.get(&id)) {
+            if let Some(tab) = self.current_tab_mut() {
+                session::focus_app(&mut tab.layout, focused);
+            }
+        }
+
+        // Replace the old IDs on disk so later journal entries apply
+        if let Err(e) = self.save_session() {
+            self.message = Some(format!("Failed to save session: {}", e));
+        }
+    }
+
+    /// Running app in the focused pane, if any.
+    pub fn focused_app_id(&self) -> Option<AppId> {
+        self.current_tab().and_then(|tab| tab.layout.focused_app())
+    }
+
+    /// Forward pasted text to the focused app.
+    pub fn handle_paste(&mut self, text: String) {
+        if self.mode == Mode::Normal {
+            self.forward_to_focused(Event::Paste(text));
+        }
+    }
+
+    /// Forward a mouse event to the app under the pointer.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.mode != Mode::Normal || self.show_help {
+            return;
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        let target = self
+            .app_areas
+            .iter()
+            .find(|(_, area)| area.contains(position))
+            .map(|(id, _)| *id);
+        if let Some(app_id) = target {
+            let _ = self.apps.send_event(app_id, &Event::Mouse(mouse));
+        }
+    }
+
+    fn forward_to_focused(&mut self, event: Event) {
+        if let Some(app_id) = self.focused_app_id() {
+            if let Err(e) = self.apps.send_event(app_id, &event) {
+                self.message = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Launch an app, reporting failures in the status bar.
+    fn launch(&mut self, name: &str) -> Option<AppId> {
+        // The area is corrected on the next render
+        match self.apps.launch(name, &[]) {
+            Ok(app_id) => {
+                self.journal(JournalEntry::AppOpened { app: AppSession::new(name).with_id(app_id) });
+                Some(app_id)
+            }
+            Err(e) => {
+                self.message = Some(e.to_string());
+                None
+            }
+        }
+    }
+
+    fn kill_apps(&mut self, app_ids: Vec<AppId>) {
+        for app_id in app_ids {
+            let _ = self.apps.kill(app_id);
+            self.app_areas.remove(&app_id);
+            self.journal(JournalEntry::AppClosed { id: app_id });
+        }
+    }
+
     pub fn handle_key(&mut self, key: KeyEvent) -> bool {
         self.message = None;
 
@@ -72,17 +327,21 @@ impl App {
     fn handle_normal_key(&mut self, key: KeyEvent) -> bool {
         let is_mod = key.modifiers.contains(KeyModifiers::CONTROL);
         let is_shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let has_app = self.focused_app_id().is_some();
 
         match key.code {
             // Exit
             KeyCode::Char('q') if is_mod => return true,
-            KeyCode::Char('c') if is_mod => return true,
+            KeyCode::Char('c') if is_mod && !has_app => return true,
 
             // Help
-            KeyCode::Char('?') => {
+            KeyCode::Char('?') if !has_app => {
                 self.show_help = !self.show_help;
             }
-            KeyCode::Esc => {
+            KeyCode::F(1) => {
+                self.show_help = !self.show_help;
+            }
+            KeyCode::Esc if self.show_help || !has_app => {
                 self.show_help = false;
             }
 
@@ -119,6 +378,7 @@ impl App {
                 // Go to last tab
                 if !self.tabs.is_empty() {
                     self.active_tab = self.tabs.len() - 1;
+                    self.commit_layout();
                 }
             }
 
@@ -143,27 +403,37 @@ impl App {
                 if let Some(tab) = self.current_tab_mut() {
                     tab.focus_pane(Direction::Horizontal);
                 }
+                self.commit_layout();
             }
             KeyCode::Right if is_mod && !is_shift => {
                 if let Some(tab) = self.current_tab_mut() {
                     tab.focus_pane(Direction::Horizontal);
                 }
+                self.commit_layout();
             }
             KeyCode::Up if is_mod && !is_shift => {
                 if let Some(tab) = self.current_tab_mut() {
                     tab.focus_pane(Direction::Vertical);
                 }
+                self.commit_layout();
             }
             KeyCode::Down if is_mod && !is_shift => {
                 if let Some(tab) = self.current_tab_mut() {
                     tab.focus_pane(Direction::Vertical);
                 }
+                self.commit_layout();
             }
 
             // Close pane
             KeyCode::Char('x') if is_mod => {
                 if let Some(tab) = self.current_tab_mut() {
-                    if !tab.close_focused_pane() {
+                    let before = tab.layout.app_ids();
+                    if tab.close_focused_pane() {
+                        let after = tab.layout.app_ids();
+                        let closed = before.into_iter().filter(|id| !after.contains(id)).collect();
+                        self.kill_apps(closed);
+                        self.commit_layout();
+                    } else {
                         // No split, close tab instead
                         self.close_current_tab();
                     }
@@ -181,7 +451,8 @@ impl App {
                 }
             }
 
-            _ => {}
+            // Everything else goes to the focused app
+            _ => self.forward_to_focused(Event::Key(key)),
         }
 
         false
@@ -190,13 +461,18 @@ impl App {
     fn handle_launcher_key(&mut self, key: KeyEvent) -> bool {
         match key.code {
             KeyCode::Esc => {
+                self.pending_split = None;
                 self.mode = Mode::Normal;
             }
             KeyCode::Enter => {
                 if !self.launcher_input.is_empty() {
                     let name = self.launcher_input.clone();
-                    self.new_tab(&name, &name);
+                    match self.pending_split.take() {
+                        Some(direction) => self.split_with(direction, &name),
+                        None => self.new_tab(&name, &name),
+                    }
                 }
+                self.pending_split = None;
                 self.mode = Mode::Normal;
             }
             KeyCode::Backspace => {
@@ -212,9 +488,13 @@ impl App {
 
     fn new_tab(&mut self, name: &str, title: &str) {
         let id = self.next_id();
-        let tab = Tab::new(id, name, title);
+        let mut tab = Tab::new(id, name, title);
+        if let Some(app_id) = self.launch(name) {
+            tab.layout.set_focused_app(app_id);
+        }
         self.tabs.push(tab);
         self.active_tab = self.tabs.len() - 1;
+        self.commit_layout();
     }
 
     fn close_current_tab(&mut self) {
@@ -230,15 +510,18 @@ impl App {
             }
         }
 
-        self.tabs.remove(self.active_tab);
+        let closed = self.tabs.remove(self.active_tab).layout.app_ids();
+        self.kill_apps(closed);
         if self.active_tab >= self.tabs.len() && !self.tabs.is_empty() {
             self.active_tab = self.tabs.len() - 1;
         }
+        self.commit_layout();
     }
 
     fn next_tab(&mut self) {
         if !self.tabs.is_empty() {
             self.active_tab = (self.active_tab + 1) % self.tabs.len();
+            self.commit_layout();
         }
     }
 
@@ -249,12 +532,14 @@ impl App {
             } else {
                 self.active_tab - 1
             };
+            self.commit_layout();
         }
     }
 
     fn go_to_tab(&mut self, index: usize) {
         if index < self.tabs.len() {
             self.active_tab = index;
+            self.commit_layout();
         }
     }
 
@@ -262,6 +547,7 @@ impl App {
         if self.active_tab > 0 {
             self.tabs.swap(self.active_tab, self.active_tab - 1);
             self.active_tab -= 1;
+            self.commit_layout();
         }
     }
 
@@ -269,17 +555,29 @@ impl App {
         if self.active_tab < self.tabs.len() - 1 {
             self.tabs.swap(self.active_tab, self.active_tab + 1);
             self.active_tab += 1;
+            self.commit_layout();
         }
     }
 
     fn split_current(&mut self, direction: Direction) {
+        if self.current_tab().is_none() {
+            return;
+        }
+
+        // The split is created once the launcher picks an app
+        self.pending_split = Some(direction);
         self.mode = Mode::Launcher;
         self.launcher_input.clear();
+    }
 
-        // Store direction for when launcher completes
-        // For simplicity, we'll create the split when a new app is selected
+    fn split_with(&mut self, direction: Direction, name: &str) {
+        let app_id = self.launch(name);
         if let Some(tab) = self.current_tab_mut() {
-            tab.split(direction, "new-app", "New App");
+            tab.split(direction, name, name);
+            if let Some(app_id) = app_id {
+                tab.layout.set_focused_app(app_id);
+            }
         }
+        self.commit_layout();
     }
 }
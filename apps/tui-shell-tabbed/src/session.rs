@@ -0,0 +1,129 @@
+//! Session persistence through the shell's crash journal.
+//!
+//! Each tab is journaled as a workspace holding its pane apps. The active
+//! tab's panes are journaled as the tiled layout; other tabs come back as
+//! even splits.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tui_shell::compositor::{LayoutNode, SplitDirection};
+use tui_shell::AppId;
+
+use crate::tab::{Direction, TabLayout};
+
+/// Seconds between automatic session snapshots.
+pub const SAVE_INTERVAL_SECS: u64 = 60;
+
+pub fn session_path() -> PathBuf {
+    directories::ProjectDirs::from("", "", "tui-shell-tabbed")
+        .map(|d| d.data_dir().join("session.json"))
+        .unwrap_or_else(|| PathBuf::from("session.json"))
+}
+
+/// Convert a tab's panes into a layout tree over running app IDs.
+///
+/// Panes without a running app are skipped.
+pub fn to_layout(layout: &TabLayout) -> Option<LayoutNode> {
+    match layout {
+        TabLayout::Single { app, .. } => app.map(LayoutNode::leaf),
+        TabLayout::Split { direction, ratio, children, .. } => {
+            match (to_layout(&children.0), to_layout(&children.1)) {
+                (Some(first), Some(second)) => Some(match direction {
+                    // A horizontal pane split places panes side by side,
+                    // which the compositor calls a vertical split
+                    Direction::Horizontal => LayoutNode::vsplit(first, second, *ratio),
+                    Direction::Vertical => LayoutNode::hsplit(first, second, *ratio),
+                }),
+                (Some(node), None) | (None, Some(node)) => Some(node),
+                (None, None) => None,
+            }
+        }
+    }
+}
+
+/// Lay out apps as an even side-by-side split.
+pub fn even_split(apps: &[AppId]) -> Option<LayoutNode> {
+    let (&last, rest) = apps.split_last()?;
+    let mut node = LayoutNode::leaf(last);
+    for (count, &app_id) in rest.iter().rev().enumerate() {
+        // Each new pane takes an equal share of everything to its right
+        node = LayoutNode::vsplit(LayoutNode::leaf(app_id), node, 1.0 / (count + 2) as f32);
+    }
+    Some(node)
+}
+
+/// Rebuild a tab's panes from a layout tree over running app IDs.
+pub fn from_layout(node: &LayoutNode, names: &HashMap<AppId, String>) -> TabLayout {
+    match node {
+        LayoutNode::Leaf { app_id } => {
+            let name = names.get(app_id).cloned().unwrap_or_default();
+            TabLayout::Single {
+                app_title: name.clone(),
+                app_name: name,
+                app: Some(*app_id),
+            }
+        }
+        LayoutNode::Split { direction, ratio, first, second } => TabLayout::Split {
+            direction: match direction {
+                SplitDirection::Vertical => Direction::Horizontal,
+                SplitDirection::Horizontal => Direction::Vertical,
+            },
+            ratio: *ratio,
+            children: Box::new((from_layout(first, names), from_layout(second, names))),
+            focused: 0,
+        },
+    }
+}
+
+/// Point a tab's pane focus at an app. Returns whether it was found.
+pub fn focus_app(layout: &mut TabLayout, app_id: AppId) -> bool {
+    match layout {
+        TabLayout::Single { app, .. } => *app == Some(app_id),
+        TabLayout::Split { children, focused, .. } => {
+            if focus_app(&mut children.0, app_id) {
+                *focused = 0;
+                true
+            } else if focus_app(&mut children.1, app_id) {
+                *focused = 1;
+                true
+            } else {
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tab::Tab;
+
+    #[test]
+    fn test_layout_round_trip() {
+        let mut tab = Tab::new(1, "a", "A");
+        tab.layout.set_focused_app(10);
+        tab.split(Direction::Vertical, "b", "B");
+        tab.layout.set_focused_app(11);
+
+        let node = to_layout(&tab.layout).unwrap();
+        assert!(matches!(node, LayoutNode::Split { direction: SplitDirection::Horizontal, .. }));
+
+        let names = HashMap::from([(10, "a".to_string()), (11, "b".to_string())]);
+        let mut layout = from_layout(&node, &names);
+        assert_eq!(layout.app_ids(), vec![10, 11]);
+        assert!(focus_app(&mut layout, 11));
+        assert_eq!(layout.focused_app(), Some(11));
+        assert_eq!(layout.focused_name(), "b");
+    }
+
+    #[test]
+    fn test_even_split() {
+        let node = even_split(&[1, 2, 3]).unwrap();
+        assert_eq!(node.app_ids(), vec![1, 2, 3]);
+        let widths: Vec<u16> = [1, 2, 3]
+            .iter()
+            .map(|id| node.layout(ratatui::layout::Rect::new(0, 0, 90, 10))[id].width)
+            .collect();
+        assert_eq!(widths, vec![30, 30, 30]);
+    }
+}
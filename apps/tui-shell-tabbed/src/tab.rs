@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use tui_shell::AppId;
 
 pub type TabId = u32;
 
@@ -13,6 +14,9 @@ pub enum TabLayout {
     Single {
         app_name: String,
         app_title: String,
+        /// Running app shown in this pane.
+        #[serde(skip)]
+        app: Option<AppId>,
     },
     Split {
         direction: Direction,
@@ -27,6 +31,7 @@ impl TabLayout {
         TabLayout::Single {
             app_name: name.to_string(),
             app_title: title.to_string(),
+            app: None,
         }
     }
 
@@ -43,6 +48,43 @@ impl TabLayout {
         }
     }
 
+    pub fn focused_app(&self) -> Option<AppId> {
+        match self {
+            TabLayout::Single { app, .. } => *app,
+            TabLayout::Split { children, focused, .. } => {
+                if *focused == 0 {
+                    children.0.focused_app()
+                } else {
+                    children.1.focused_app()
+                }
+            }
+        }
+    }
+
+    pub fn set_focused_app(&mut self, app_id: AppId) {
+        match self {
+            TabLayout::Single { app, .. } => *app = Some(app_id),
+            TabLayout::Split { children, focused, .. } => {
+                if *focused == 0 {
+                    children.0.set_focused_app(app_id)
+                } else {
+                    children.1.set_focused_app(app_id)
+                }
+            }
+        }
+    }
+
+    pub fn app_ids(&self) -> Vec<AppId> {
+        match self {
+            TabLayout::Single { app, .. } => app.iter().copied().collect(),
+            TabLayout::Split { children, .. } => {
+                let mut ids = children.0.app_ids();
+                ids.extend(children.1.app_ids());
+                ids
+            }
+        }
+    }
+
     pub fn focused_name(&self) -> &str {
         match self {
             TabLayout::Single { app_name, .. } => app_name,
@@ -150,6 +192,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pane_apps() {
+        let mut tab = Tab::new(1, "app1", "App 1");
+        tab.layout.set_focused_app(10);
+        tab.split(Direction::Vertical, "app2", "App 2");
+        assert_eq!(tab.layout.focused_app(), None);
+
+        tab.layout.set_focused_app(11);
+        assert_eq!(tab.layout.focused_app(), Some(11));
+        assert_eq!(tab.layout.app_ids(), vec![10, 11]);
+    }
+
     #[test]
     fn test_close_pane() {
         let mut tab = Tab::new(1, "app1", "App 1");
@@ -3,10 +3,19 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph, Tabs as TabsWidget},
 };
 
+use std::collections::HashMap;
+use tui_shell::{AppId, AppManager};
+
 use crate::app::{App, Mode};
 use crate::tab::{Direction, TabLayout};
 
-pub fn render(frame: &mut Frame, app: &App) {
+/// Running apps threaded through pane rendering.
+struct Panes<'a> {
+    apps: &'a mut AppManager,
+    areas: &'a mut HashMap<AppId, Rect>,
+}
+
+pub fn render(frame: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(layout::Direction::Vertical)
         .constraints([
@@ -68,9 +77,14 @@ fn render_tab_bar(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(tabs, area);
 }
 
-fn render_content(frame: &mut Frame, app: &App, area: Rect) {
-    if let Some(tab) = app.current_tab() {
-        render_layout(frame, &tab.layout, area, true);
+fn render_content(frame: &mut Frame, app: &mut App, area: Rect) {
+    app.app_areas.clear();
+    if let Some(tab) = app.tabs.get(app.active_tab) {
+        let mut panes = Panes {
+            apps: &mut app.apps,
+            areas: &mut app.app_areas,
+        };
+        render_layout(frame, &tab.layout, area, true, &mut panes);
     } else {
         let empty = Paragraph::new("No tabs. Press Ctrl+T to create a new tab.")
             .alignment(Alignment::Center)
@@ -79,18 +93,36 @@ fn render_content(frame: &mut Frame, app: &App, area: Rect) {
     }
 }
 
-fn render_layout(frame: &mut Frame, layout: &TabLayout, area: Rect, focused: bool) {
+fn render_layout(frame: &mut Frame, layout: &TabLayout, area: Rect, focused: bool, panes: &mut Panes) {
     match layout {
-        TabLayout::Single { app_name, app_title } => {
+        TabLayout::Single { app_name, app_title, app } => {
             let border_color = if focused { Color::Cyan } else { Color::DarkGray };
+            let exited = app
+                .and_then(|app_id| panes.apps.get(app_id))
+                .is_some_and(|h| h.exit.is_some());
             let block = Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(border_color))
-                .title(format!(" {} ", app_title));
+                .title(format!(" {}{} ", app_title, if exited { " [exited]" } else { "" }));
 
             let inner = block.inner(area);
             frame.render_widget(block, area);
 
+            if let Some(app_id) = *app {
+                let _ = panes.apps.resize_app(app_id, inner);
+                if let Some(handle) = panes.apps.get(app_id) {
+                    frame.render_widget(&handle.buffer, inner);
+                }
+                if focused {
+                    if let Some((x, y)) = panes.apps.cursor(app_id) {
+                        frame.set_cursor_position(Position::new(x, y));
+                    }
+                }
+                panes.areas.insert(app_id, inner);
+                return;
+            }
+
+            // No running process: show what would have been launched
             let content = vec![
                 Line::from(""),
                 Line::from(Span::styled(
@@ -98,8 +130,8 @@ fn render_layout(frame: &mut Frame, layout: &TabLayout, area: Rect, focused: boo
                     Style::default().add_modifier(Modifier::BOLD),
                 )),
                 Line::from(""),
-                Line::from("  This is a placeholder for the"),
-                Line::from("  application content."),
+                Line::from("  This app is not running."),
+                Line::from("  Make sure its binary is built."),
                 Line::from(""),
                 Line::from("  Press Ctrl+V for vertical split"),
                 Line::from("  Press Ctrl+H for horizontal split"),
@@ -129,8 +161,8 @@ fn render_layout(frame: &mut Frame, layout: &TabLayout, area: Rect, focused: boo
                 .constraints([constraint1, constraint2])
                 .split(area);
 
-            render_layout(frame, &children.0, chunks[0], focused && *focused_idx == 0);
-            render_layout(frame, &children.1, chunks[1], focused && *focused_idx == 1);
+            render_layout(frame, &children.0, chunks[0], focused && *focused_idx == 0, panes);
+            render_layout(frame, &children.1, chunks[1], focused && *focused_idx == 1, panes);
         }
     }
 }
@@ -180,8 +212,9 @@ fn render_help(frame: &mut Frame) {
         Line::from("  Ctrl+x         Close pane"),
         Line::from(""),
         Line::from(Span::styled("Other", Style::default().add_modifier(Modifier::BOLD))),
-        Line::from("  ?              Toggle help"),
+        Line::from("  ? / F1         Toggle help"),
         Line::from("  Ctrl+q         Quit"),
+        Line::from("  Other keys     Sent to the focused app"),
     ];
 
     let help = Paragraph::new(help_text)
@@ -205,7 +238,7 @@ fn render_launcher(frame: &mut Frame, app: &App) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(Color::Green))
-                .title(" New Tab "),
+                .title(if app.pending_split.is_some() { " Split " } else { " New Tab " }),
         )
         .style(Style::default().bg(Color::Black));
     frame.render_widget(launcher, area);
@@ -1,7 +1,14 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use ratatui::layout::{Position, Rect};
+use std::collections::HashMap;
+use tui_shell::{
+    AppEvent, AppId, AppManager, AppSession, IpcMessage, JournalEntry, LayoutState, Session,
+    SessionManager, ShellResult,
+};
 
 use crate::config::Config;
 use crate::container::{Container, ContainerId, Direction, Workspace};
+use crate::session;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
@@ -29,6 +36,14 @@ pub struct App {
     pub show_help: bool,
     pub message: Option<String>,
     pub launcher_input: String,
+    /// Running subprocess apps.
+    pub apps: AppManager,
+    /// Container -> running app mapping.
+    pub running: HashMap<ContainerId, AppId>,
+    /// Screen areas of visible apps, from the last render.
+    pub app_areas: HashMap<AppId, Rect>,
+    /// Session snapshot and crash journal.
+    pub sessions: SessionManager,
 }
 
 impl App {
@@ -52,12 +67,25 @@ impl App {
             show_help: false,
             message: None,
             launcher_input: String::new(),
+            apps: AppManager::new(),
+            running: HashMap::new(),
+            app_areas: HashMap::new(),
+            sessions: SessionManager::new(session::session_path(), session::SAVE_INTERVAL_SECS),
         };
 
-        // Add some demo apps
-        app.spawn_app("task-manager", "Task Manager");
-        app.split_current(Direction::Horizontal);
-        app.spawn_app("note-manager", "Note Manager");
+        match app.sessions.load() {
+            Ok(()) if !app.sessions.session().apps.is_empty() => app.restore_session(),
+            result => {
+                if let Err(e) = result {
+                    app.message = Some(format!("Failed to load session: {}", e));
+                }
+
+                // Start with a couple of suite apps
+                app.spawn_app("task-manager", "Task Manager");
+                app.split_current(Direction::Horizontal);
+                app.spawn_app("note-manager-folder", "Note Manager");
+            }
+        }
 
         app
     }
@@ -76,6 +104,222 @@ impl App {
         &mut self.workspaces[self.active_workspace]
     }
 
+    /// Pump app output and report apps that exited.
+    pub fn tick(&mut self) {
+        for event in self.apps.poll() {
+            let app_id = match event {
+                AppEvent::Exited { id, .. } | AppEvent::Restarted { id, .. } => id,
+            };
+            let name = self
+                .apps
+                .get(app_id)
+                .map(|h| h.manifest.name.clone())
+                .unwrap_or_default();
+            self.message = Some(match event {
+                AppEvent::Exited { exit, .. } if exit.success => format!("{} exited", name),
+                AppEvent::Exited {
+                    exit,
+                    restart_in: Some(delay),
+                    ..
+                } => format!(
+                    "{} exited with code {}, restarting in {:.1}s",
+                    name,
+                    exit.code,
+                    delay.as_secs_f32()
+                ),
+                AppEvent::Exited { exit, .. } => format!("{} exited with code {}", name, exit.code),
+                AppEvent::Restarted { attempt, .. } => format!("{} restarted (attempt {})", name, attempt),
+            });
+        }
+
+        // Bus traffic is routed by the manager; surface what's left
+        for (id, msg) in self.apps.poll_ipc() {
+            match msg {
+                IpcMessage::SessionState { state } => self.journal(JournalEntry::AppState { id, state }),
+                IpcMessage::Notification(notification) => self.message = Some(notification.message),
+                _ => {}
+            }
+        }
+
+        if self.sessions.needs_save() {
+            // Ask apps for fresh state; replies land in the journal
+            self.apps.request_session_save();
+        }
+        if let Err(e) = self.sessions.try_auto_save() {
+            self.message = Some(format!("Failed to save session: {}", e));
+        }
+    }
+
+    /// Journal a session change, reporting failures in the status bar.
+    fn journal(&mut self, entry: JournalEntry) {
+        if let Err(e) = self.sessions.record(entry) {
+            self.message = Some(format!("Failed to journal session: {}", e));
+        }
+    }
+
+    /// Journal the active layout, workspace membership and focus.
+    fn commit_layout(&mut self) {
+        let layout = self.layout_state();
+        self.journal(JournalEntry::Layout { layout });
+        let workspaces = self.shell_workspaces();
+        self.journal(JournalEntry::Workspaces { workspaces });
+        let id = self.focused_app_id();
+        self.journal(JournalEntry::Focus { id });
+    }
+
+    fn layout_state(&self) -> LayoutState {
+        LayoutState {
+            tiled: session::to_layout(&self.current_workspace().root, &self.running),
+            ..Default::default()
+        }
+    }
+
+    fn shell_workspaces(&self) -> Vec<tui_shell::Workspace> {
+        self.workspaces
+            .iter()
+            .enumerate()
+            .map(|(i, ws)| tui_shell::Workspace {
+                id: ws.id as u64,
+                name: ws.name.clone(),
+                apps: ws
+                    .root
+                    .app_container_ids()
+                    .iter()
+                    .filter_map(|id| self.running.get(id).copied())
+                    .collect(),
+                active: i == self.active_workspace,
+            })
+            .collect()
+    }
+
+    /// Write a session snapshot, e.g. on quit.
+    pub fn save_session(&mut self) -> ShellResult<()> {
+        let session = Session {
+            layout: self.layout_state(),
+            apps: self.apps.save_sessions()?,
+            focused: self.focused_app_id(),
+            workspaces: self.shell_workspaces(),
+        };
+        self.sessions.update(session)?;
+        self.sessions.save()
+    }
+
+    /// Relaunch the apps of the loaded session and rebuild the workspaces around them.
+    fn restore_session(&mut self) {
+        let session = self.sessions.session().clone();
+
+        let (cols, rows) = crossterm::terminal::size().unwrap_or((80, 24));
+        let screen = Rect::new(0, 0, cols, rows.saturating_sub(1));
+        let areas = session
+            .layout
+            .tiled
+            .as_ref()
+            .map(|node| node.layout(screen))
+            .unwrap_or_default();
+
+        let mut relaunched = HashMap::new();
+        let mut failed = Vec::new();
+        for (old, result) in self.apps.restore_session(&session, &areas) {
+            match result {
+                Ok(new) => {
+                    relaunched.insert(old, new);
+                }
+                Err(e) => failed.push(e.to_string()),
+            }
+        }
+        if !failed.is_empty() {
+            self.message = Some(format!("Failed to restore: {}", failed.join("; ")));
+        }
+
+        let names: HashMap<AppId, String> = relaunched
+            .values()
+            .filter_map(|&id| self.apps.get(id).map(|h| (id, h.manifest.name.clone())))
+            .collect();
+        let tiled = session.layout.tiled.as_ref().and_then(|node| node.remap(&relaunched));
+        let mut placed = tiled.as_ref().map(|node| node.app_ids()).unwrap_or_default();
+
+        self.active_workspace = session
+            .workspaces
+            .iter()
+            .find(|ws| ws.active)
+            .and_then(|saved| self.workspaces.iter().position(|ws| ws.id as u64 == saved.id))
+            .unwrap_or(0);
+        for (index, ws) in self.workspaces.iter_mut().enumerate() {
+            let node = if index == self.active_workspace {
+                tiled.clone()
+            } else {
+                let apps: Vec<AppId> = session
+                    .workspaces
+                    .iter()
+                    .filter(|saved| saved.id == ws.id as u64)
+                    .flat_map(|saved| saved.apps.iter())
+                    .filter_map(|id| relaunched.get(id).copied())
+                    .filter(|id| !placed.contains(id))
+                    .collect();
+                placed.extend(&apps);
+                session::even_split(&apps)
+            };
+            if let Some(node) = node {
+                ws.root = session::from_layout(&node, &names, &mut self.next_container_id, &mut self.running);
+            }
+        }
+
+        // Apps whose workspace no longer exists have nowhere to show
+        for id in relaunched.values().filter(|id| !placed.contains(id)) {
+            let _ = self.apps.kill(*id);
+        }
+
+        let focused = session.focused.and_then(|id| relaunched.get(&id));
+        let container = focused.and_then(|app_id| {
+            self.running.iter().find(|(_, id)| *id == app_id).map(|(&container, _)| container)
+        });
+        if let Some(container) = container {
+            session::focus_container(&mut self.workspaces[self.active_workspace].root, container);
+        }
+
+        // Replace the old IDs on disk so later journal entries apply
+        if let Err(e) = self.save_session() {
+            self.message = Some(format!("Failed to save session: {}", e));
+        }
+    }
+
+    /// Running app in the focused container, if any.
+    pub fn focused_app_id(&self) -> Option<AppId> {
+        let container = self.current_workspace().root.find_focused_app()?;
+        self.running.get(&container.id()).copied()
+    }
+
+    /// Forward pasted text to the focused app.
+    pub fn handle_paste(&mut self, text: String) {
+        if self.mode == Mode::Normal {
+            self.forward_to_focused(Event::Paste(text));
+        }
+    }
+
+    /// Forward a mouse event to the app under the pointer.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        if self.mode != Mode::Normal || self.show_help {
+            return;
+        }
+        let position = Position::new(mouse.column, mouse.row);
+        let target = self
+            .app_areas
+            .iter()
+            .find(|(_, area)| area.contains(position))
+            .map(|(id, _)| *id);
+        if let Some(app_id) = target {
+            let _ = self.apps.send_event(app_id, &Event::Mouse(mouse));
+        }
+    }
+
+    fn forward_to_focused(&mut self, event: Event) {
+        if let Some(app_id) = self.focused_app_id() {
+            if let Err(e) = self.apps.send_event(app_id, &event) {
+                self.message = Some(e.to_string());
+            }
+        }
+    }
+
     pub fn handle_key(&mut self, key: KeyEvent) -> bool {
         self.message = None;
 
@@ -89,17 +333,21 @@ impl App {
 
     fn handle_normal_key(&mut self, key: KeyEvent) -> bool {
         let is_mod = key.modifiers.contains(KeyModifiers::CONTROL);
+        let has_app = self.focused_app_id().is_some();
 
         match key.code {
             // Exit
             KeyCode::Char('q') if is_mod => return true,
-            KeyCode::Char('c') if is_mod => return true,
+            KeyCode::Char('c') if is_mod && !has_app => return true,
 
             // Help
-            KeyCode::Char('?') => {
+            KeyCode::Char('?') if !has_app => {
                 self.show_help = !self.show_help;
             }
-            KeyCode::Esc => {
+            KeyCode::F(1) => {
+                self.show_help = !self.show_help;
+            }
+            KeyCode::Esc if self.show_help || !has_app => {
                 self.show_help = false;
             }
 
@@ -156,7 +404,8 @@ impl App {
                 self.message = Some("Fullscreen toggle (not implemented in demo)".to_string());
             }
 
-            _ => {}
+            // Everything else goes to the focused app
+            _ => self.forward_to_focused(Event::Key(key)),
         }
 
         false
@@ -250,6 +499,9 @@ impl App {
                 }
             }
         }
+
+        let id = self.focused_app_id();
+        self.journal(JournalEntry::Focus { id });
     }
 
     fn split_current(&mut self, direction: Direction) {
@@ -302,19 +554,43 @@ impl App {
         let ws_idx = self.active_workspace;
 
         let ws = &mut self.workspaces[ws_idx];
-        match &mut ws.root {
+        let placed = match &mut ws.root {
             Container::Empty { .. } => {
                 ws.root = Container::new_app(id, name.to_string(), title.to_string());
+                true
             }
-            Container::Split { children, focused, .. } => {
-                if let Some(child) = children.get_mut(*focused) {
-                    if child.is_empty() {
-                        *child = Container::new_app(id, name.to_string(), title.to_string());
-                    }
+            Container::Split { children, focused, .. } => match children.get_mut(*focused) {
+                Some(child) if child.is_empty() => {
+                    *child = Container::new_app(id, name.to_string(), title.to_string());
+                    true
+                }
+                _ => false,
+            },
+            Container::App { .. } => false,
+            Container::Tabbed { .. } => false,
+        };
+
+        if placed {
+            // The area is corrected on the next render
+            match self.apps.launch(name, &[]) {
+                Ok(app_id) => {
+                    self.running.insert(id, app_id);
+                    let ws_id = self.workspaces[ws_idx].id as u64;
+                    self.apps.add_to_workspace(app_id, ws_id);
+                    let app = AppSession::new(name).with_id(app_id).in_workspace(ws_id);
+                    self.journal(JournalEntry::AppOpened { app });
+                    self.commit_layout();
                 }
+                Err(e) => self.message = Some(e.to_string()),
             }
-            Container::App { .. } => {}
-            Container::Tabbed { .. } => {}
+        }
+    }
+
+    fn kill_container(&mut self, container_id: ContainerId) {
+        if let Some(app_id) = self.running.remove(&container_id) {
+            let _ = self.apps.kill(app_id);
+            self.app_areas.remove(&app_id);
+            self.journal(JournalEntry::AppClosed { id: app_id });
         }
     }
 
@@ -322,14 +598,16 @@ impl App {
         let id = self.next_id();
         let ws_idx = self.active_workspace;
 
+        let mut closed = Vec::new();
         let ws = &mut self.workspaces[ws_idx];
         match &mut ws.root {
             Container::App { .. } => {
+                closed = ws.root.app_container_ids();
                 ws.root = Container::Empty { id };
             }
             Container::Split { children, focused, ratios, .. } => {
                 if children.len() > 1 {
-                    children.remove(*focused);
+                    closed = children.remove(*focused).app_container_ids();
                     ratios.pop();
                     let ratio = 1.0 / children.len() as f32;
                     for r in ratios.iter_mut() {
@@ -340,6 +618,7 @@ impl App {
                     }
                 } else if children.len() == 1 {
                     if matches!(children.first(), Some(Container::App { .. })) {
+                        closed = children[0].app_container_ids();
                         children[0] = Container::Empty { id };
                     }
                 }
@@ -347,6 +626,13 @@ impl App {
             Container::Empty { .. } => {}
             Container::Tabbed { .. } => {}
         }
+
+        if !closed.is_empty() {
+            for container_id in closed {
+                self.kill_container(container_id);
+            }
+            self.commit_layout();
+        }
     }
 
     fn resize_current(&mut self, _dw: f32, _dh: f32) {
@@ -362,6 +648,7 @@ impl App {
     fn switch_workspace(&mut self, index: usize) {
         if index < self.workspaces.len() {
             self.active_workspace = index;
+            self.commit_layout();
             self.message = Some(format!(
                 "Switched to workspace {}",
                 self.workspaces[index].name
@@ -371,8 +658,12 @@ impl App {
 
     pub fn focused_title(&self) -> String {
         if let Some(app) = self.current_workspace().root.find_focused_app() {
+            let app_title = self
+                .running
+                .get(&app.id())
+                .and_then(|&app_id| self.apps.title(app_id));
             match app {
-                Container::App { title, .. } => title.clone(),
+                Container::App { title, .. } => app_title.unwrap_or(title).to_string(),
                 _ => "Empty".to_string(),
             }
         } else {
@@ -84,6 +84,16 @@ impl Container {
         }
     }
 
+    pub fn app_container_ids(&self) -> Vec<ContainerId> {
+        match self {
+            Container::App { id, .. } => vec![*id],
+            Container::Empty { .. } => Vec::new(),
+            Container::Split { children, .. } | Container::Tabbed { children, .. } => {
+                children.iter().flat_map(|c| c.app_container_ids()).collect()
+            }
+        }
+    }
+
     pub fn find_focused_app(&self) -> Option<&Container> {
         match self {
             Container::App { .. } => Some(self),
@@ -151,6 +161,20 @@ mod tests {
         assert_eq!(app.id(), 1);
     }
 
+    #[test]
+    fn test_app_container_ids() {
+        let split = Container::new_split(
+            1,
+            Direction::Horizontal,
+            vec![
+                Container::new_app(2, "a".to_string(), "A".to_string()),
+                Container::Empty { id: 3 },
+                Container::new_app(4, "b".to_string(), "B".to_string()),
+            ],
+        );
+        assert_eq!(split.app_container_ids(), vec![2, 4]);
+    }
+
     #[test]
     fn test_workspace_empty() {
         let ws = Workspace::new(1, "main".to_string());
@@ -0,0 +1,170 @@
+//! Session persistence through the shell's crash journal.
+//!
+//! The active workspace's container tree is journaled as the tiled layout.
+//! Other workspaces only record which apps they hold and come back as even
+//! splits.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tui_shell::compositor::{LayoutNode, SplitDirection};
+use tui_shell::AppId;
+
+use crate::container::{Container, ContainerId, Direction};
+
+/// Seconds between automatic session snapshots.
+pub const SAVE_INTERVAL_SECS: u64 = 60;
+
+pub fn session_path() -> PathBuf {
+    directories::ProjectDirs::from("", "", "tui-shell-tiled")
+        .map(|d| d.data_dir().join("session.json"))
+        .unwrap_or_else(|| PathBuf::from("session.json"))
+}
+
+/// Convert a container tree into a layout tree over running app IDs.
+///
+/// Empty containers are skipped and tabbed containers keep only their
+/// active child.
+pub fn to_layout(container: &Container, running: &HashMap<ContainerId, AppId>) -> Option<LayoutNode> {
+    match container {
+        Container::App { id, .. } => running.get(id).map(|&app_id| LayoutNode::leaf(app_id)),
+        Container::Empty { .. } => None,
+        Container::Tabbed { children, active, .. } => {
+            children.get(*active).and_then(|child| to_layout(child, running))
+        }
+        Container::Split { direction, children, ratios, .. } => {
+            let parts = children
+                .iter()
+                .zip(ratios)
+                .filter_map(|(child, ratio)| to_layout(child, running).map(|node| (node, *ratio)))
+                .collect();
+            chain(*direction, parts)
+        }
+    }
+}
+
+/// Lay out apps as an even side-by-side split.
+pub fn even_split(apps: &[AppId]) -> Option<LayoutNode> {
+    let parts = apps.iter().map(|&id| (LayoutNode::leaf(id), 1.0)).collect();
+    chain(Direction::Horizontal, parts)
+}
+
+/// Fold n-ary children into nested binary splits that keep their shares.
+fn chain(direction: Direction, mut parts: Vec<(LayoutNode, f32)>) -> Option<LayoutNode> {
+    let (mut node, mut share) = parts.pop()?;
+    while let Some((first, first_share)) = parts.pop() {
+        let total = first_share + share;
+        let ratio = if total > 0.0 { first_share / total } else { 0.5 };
+        // A horizontal container places children side by side, which the
+        // compositor calls a vertical split
+        node = match direction {
+            Direction::Horizontal => LayoutNode::vsplit(first, node, ratio),
+            Direction::Vertical => LayoutNode::hsplit(first, node, ratio),
+        };
+        share = total;
+    }
+    Some(node)
+}
+
+/// Rebuild a container tree from a layout tree over running app IDs.
+///
+/// New containers take IDs from `next_id` and are registered in `running`.
+pub fn from_layout(
+    node: &LayoutNode,
+    names: &HashMap<AppId, String>,
+    next_id: &mut ContainerId,
+    running: &mut HashMap<ContainerId, AppId>,
+) -> Container {
+    let id = *next_id;
+    *next_id += 1;
+
+    match node {
+        LayoutNode::Leaf { app_id } => {
+            let name = names.get(app_id).cloned().unwrap_or_default();
+            running.insert(id, *app_id);
+            Container::new_app(id, name.clone(), name)
+        }
+        LayoutNode::Split { direction, ratio, first, second } => {
+            let first = from_layout(first, names, next_id, running);
+            let second = from_layout(second, names, next_id, running);
+            Container::Split {
+                id,
+                direction: match direction {
+                    SplitDirection::Vertical => Direction::Horizontal,
+                    SplitDirection::Horizontal => Direction::Vertical,
+                },
+                children: vec![first, second],
+                ratios: vec![*ratio, 1.0 - ratio],
+                focused: 0,
+            }
+        }
+    }
+}
+
+/// Point the focus path of a tree at a container. Returns whether it was found.
+pub fn focus_container(container: &mut Container, target: ContainerId) -> bool {
+    if container.id() == target {
+        return true;
+    }
+    match container {
+        Container::Split { children, focused, .. } => {
+            match children.iter_mut().position(|child| focus_container(child, target)) {
+                Some(index) => {
+                    *focused = index;
+                    true
+                }
+                None => false,
+            }
+        }
+        Container::Tabbed { children, active, .. } => {
+            match children.iter_mut().position(|child| focus_container(child, target)) {
+                Some(index) => {
+                    *active = index;
+                    true
+                }
+                None => false,
+            }
+        }
+        Container::App { .. } | Container::Empty { .. } => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_round_trip() {
+        let root = Container::new_split(
+            1,
+            Direction::Horizontal,
+            vec![
+                Container::new_app(2, "a".to_string(), "A".to_string()),
+                Container::Empty { id: 3 },
+                Container::new_split(
+                    4,
+                    Direction::Vertical,
+                    vec![
+                        Container::new_app(5, "b".to_string(), "B".to_string()),
+                        Container::new_app(6, "c".to_string(), "C".to_string()),
+                    ],
+                ),
+            ],
+        );
+        let running = HashMap::from([(2, 10), (5, 11), (6, 12)]);
+
+        let layout = to_layout(&root, &running).unwrap();
+        assert_eq!(layout.app_ids(), vec![10, 11, 12]);
+        assert!(matches!(layout, LayoutNode::Split { direction: SplitDirection::Vertical, .. }));
+
+        let names = HashMap::from([(10, "a".to_string()), (11, "b".to_string()), (12, "c".to_string())]);
+        let mut next_id = 100;
+        let mut rebuilt = HashMap::new();
+        let mut container = from_layout(&layout, &names, &mut next_id, &mut rebuilt);
+        assert_eq!(container.app_container_ids().len(), 3);
+        assert_eq!(rebuilt.len(), 3);
+
+        let target = rebuilt.iter().find(|(_, &app)| app == 12).map(|(&id, _)| id).unwrap();
+        assert!(focus_container(&mut container, target));
+        assert_eq!(container.find_focused_app().map(|c| c.id()), Some(target));
+    }
+}
@@ -3,10 +3,20 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph},
 };
 
+use std::collections::HashMap;
+use tui_shell::{AppId, AppManager};
+
 use crate::app::{App, Mode};
-use crate::container::{Container, Direction};
+use crate::container::{Container, ContainerId, Direction};
+
+/// Running apps threaded through container rendering.
+struct Panes<'a> {
+    apps: &'a mut AppManager,
+    running: &'a HashMap<ContainerId, AppId>,
+    areas: &'a mut HashMap<AppId, Rect>,
+}
 
-pub fn render(frame: &mut Frame, app: &App) {
+pub fn render(frame: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical.into())
         .constraints([
@@ -16,8 +26,14 @@ pub fn render(frame: &mut Frame, app: &App) {
         .split(frame.area());
 
     // Main content area
-    let workspace = app.current_workspace();
-    render_container(frame, &workspace.root, chunks[0], true, &app.config);
+    app.app_areas.clear();
+    let mut panes = Panes {
+        apps: &mut app.apps,
+        running: &app.running,
+        areas: &mut app.app_areas,
+    };
+    let workspace = &app.workspaces[app.active_workspace];
+    render_container(frame, &workspace.root, chunks[0], true, &app.config, &mut panes);
 
     // Status bar
     render_status_bar(frame, app, chunks[1]);
@@ -32,7 +48,14 @@ pub fn render(frame: &mut Frame, app: &App) {
     }
 }
 
-fn render_container(frame: &mut Frame, container: &Container, area: Rect, focused: bool, config: &crate::config::Config) {
+fn render_container(
+    frame: &mut Frame,
+    container: &Container,
+    area: Rect,
+    focused: bool,
+    config: &crate::config::Config,
+    panes: &mut Panes,
+) {
     match container {
         Container::Empty { .. } => {
             let border_color = if focused { Color::Cyan } else { Color::DarkGray };
@@ -47,25 +70,43 @@ fn render_container(frame: &mut Frame, container: &Container, area: Rect, focuse
                 .alignment(Alignment::Center);
             frame.render_widget(hint, inner);
         }
-        Container::App { name, title, .. } => {
+        Container::App { id, name, title } => {
             let border_color = if focused { Color::Cyan } else { Color::DarkGray };
+            let app_id = panes.running.get(id).copied();
+            let exited = app_id
+                .and_then(|app_id| panes.apps.get(app_id))
+                .is_some_and(|h| h.exit.is_some());
             let block = Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(border_color))
-                .title(format!(" {} ", title));
+                .title(format!(" {}{} ", title, if exited { " [exited]" } else { "" }));
             let inner = block.inner(area);
             frame.render_widget(block, area);
 
-            // Simulate app content
+            if let Some(app_id) = app_id {
+                let _ = panes.apps.resize_app(app_id, inner);
+                if let Some(handle) = panes.apps.get(app_id) {
+                    frame.render_widget(&handle.buffer, inner);
+                }
+                if focused {
+                    if let Some((x, y)) = panes.apps.cursor(app_id) {
+                        frame.set_cursor_position(Position::new(x, y));
+                    }
+                }
+                panes.areas.insert(app_id, inner);
+                return;
+            }
+
+            // No running process: show what would have been launched
             let content = Paragraph::new(vec![
                 Line::from(Span::styled(
                     format!("App: {}", name),
                     Style::default().add_modifier(Modifier::BOLD),
                 )),
                 Line::from(""),
-                Line::from("This is a placeholder for the app content."),
-                Line::from("In a real implementation, this would render"),
-                Line::from("the actual application interface."),
+                Line::from("This app is not running."),
+                Line::from("Make sure its binary is built and on PATH"),
+                Line::from("or next to the shell executable."),
             ])
             .alignment(Alignment::Center)
             .block(Block::default());
@@ -101,12 +142,12 @@ fn render_container(frame: &mut Frame, container: &Container, area: Rect, focuse
 
             for (i, (child, child_area)) in children.iter().zip(child_areas.iter()).enumerate() {
                 let is_focused = focused && i == *focused_idx;
-                render_container(frame, child, *child_area, is_focused, config);
+                render_container(frame, child, *child_area, is_focused, config, panes);
             }
         }
         Container::Tabbed { children, active, .. } => {
             if let Some(child) = children.get(*active) {
-                render_container(frame, child, area, focused, config);
+                render_container(frame, child, area, focused, config, panes);
             }
         }
     }
@@ -193,7 +234,8 @@ fn render_help(frame: &mut Frame) {
         Line::from("  Ctrl+p         Launch app"),
         Line::from(""),
         Line::from(Span::styled("Other", Style::default().add_modifier(Modifier::BOLD))),
-        Line::from("  ?              Toggle help"),
+        Line::from("  ? / F1         Toggle help (? goes to the app if one is focused)"),
+        Line::from("  Other keys     Sent to the focused app"),
         Line::from("  Ctrl+q         Quit"),
     ];
 
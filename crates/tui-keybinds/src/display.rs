@@ -1,5 +1,7 @@
 //! Key display configuration.
 
+use crate::binding::KeyBinding;
+use crossterm::event::{KeyCode, KeyModifiers};
 use serde::{Deserialize, Serialize};
 
 /// Format for displaying key bindings.
@@ -104,6 +106,49 @@ impl KeyDisplayConfig {
     }
 }
 
+impl KeyDisplayConfig {
+    /// Format a key binding with its modifiers.
+    pub fn format_binding(&self, key: &KeyBinding) -> String {
+        let mut parts = Vec::new();
+
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            parts.push(self.format_modifier("ctrl"));
+        }
+        if key.modifiers.contains(KeyModifiers::ALT) {
+            parts.push(self.format_modifier("alt"));
+        }
+        if key.modifiers.contains(KeyModifiers::SHIFT) {
+            parts.push(self.format_modifier("shift"));
+        }
+
+        let key_str = match key.key {
+            KeyCode::Char(' ') => self.format_key("space"),
+            KeyCode::Char(c) => c.to_uppercase().to_string(),
+            KeyCode::Enter => self.format_key("enter"),
+            KeyCode::Esc => self.format_key("escape"),
+            KeyCode::Tab => self.format_key("tab"),
+            KeyCode::Backspace => self.format_key("backspace"),
+            KeyCode::Delete => self.format_key("delete"),
+            KeyCode::Up => self.format_key("up"),
+            KeyCode::Down => self.format_key("down"),
+            KeyCode::Left => self.format_key("left"),
+            KeyCode::Right => self.format_key("right"),
+            KeyCode::Home => self.format_key("home"),
+            KeyCode::End => self.format_key("end"),
+            KeyCode::PageUp => self.format_key("pageup"),
+            KeyCode::PageDown => self.format_key("pagedown"),
+            KeyCode::F(n) => format!("F{}", n),
+            _ => format!("{:?}", key.key),
+        };
+
+        parts.push(key_str);
+        match self.format {
+            KeyDisplayFormat::Symbolic => parts.concat(),
+            KeyDisplayFormat::Text => parts.join("+"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -125,4 +170,14 @@ mod tests {
         assert_eq!(config.format_key("enter"), "\u{23ce}");
         assert_eq!(config.format_key("up"), "\u{2191}");
     }
+
+    #[test]
+    fn test_format_binding() {
+        let binding = KeyBinding::ctrl('s');
+        assert_eq!(KeyDisplayConfig::text().format_binding(&binding), "Ctrl+S");
+        assert_eq!(KeyDisplayConfig::symbolic().format_binding(&binding), "\u{2303}S");
+
+        let space = KeyBinding::key(KeyCode::Char(' '));
+        assert_eq!(KeyDisplayConfig::text().format_binding(&space), "Space");
+    }
 }
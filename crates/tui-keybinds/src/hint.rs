@@ -0,0 +1,49 @@
+//! Continuation hints for pending key sequences.
+
+use crate::binding::KeyBinding;
+use crate::keymap::ActionId;
+
+/// A possible next key after a pending prefix, for which-key style overlays.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyHint {
+    /// The next key to press
+    pub key: KeyBinding,
+    /// Action triggered by this key, if it completes a sequence
+    pub action: Option<ActionId>,
+    /// Description shown next to the key
+    pub description: String,
+    /// Action group name, used to section the overlay
+    pub group: Option<String>,
+    /// Whether more keys are needed after this one
+    pub is_prefix: bool,
+}
+
+impl KeyHint {
+    /// Create a hint for a key that triggers an action.
+    pub fn action(key: KeyBinding, action: impl Into<ActionId>, description: impl Into<String>) -> Self {
+        Self {
+            key,
+            action: Some(action.into()),
+            description: description.into(),
+            group: None,
+            is_prefix: false,
+        }
+    }
+
+    /// Create a hint for a key that leads to further bindings.
+    pub fn prefix(key: KeyBinding, continuations: usize) -> Self {
+        Self {
+            key,
+            action: None,
+            description: format!("+{} more", continuations),
+            group: None,
+            is_prefix: true,
+        }
+    }
+
+    /// Set the group.
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+}
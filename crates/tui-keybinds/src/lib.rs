@@ -8,12 +8,14 @@
 //! - Context-aware bindings with expression syntax
 //! - Macro recording and playback
 //! - Conflict detection
+//! - Continuation hints for which-key style overlays
 //! - Built-in vim, emacs, and default presets
 
 mod binding;
 mod conflict;
 mod context;
 mod display;
+mod hint;
 mod keymap;
 mod macros;
 mod manager;
@@ -25,7 +27,8 @@ pub use binding::{KeyBinding, KeySequence};
 pub use conflict::{Conflict, ConflictReport, ConflictSeverity};
 pub use context::{Context, ContextCondition};
 pub use display::{KeyDisplayConfig, KeyDisplayFormat};
-pub use keymap::{Action, ActionGroup, Keymap};
+pub use hint::KeyHint;
+pub use keymap::{Action, ActionGroup, ActionId, Keymap};
 pub use macros::{Macro, MacroManager};
 pub use manager::{ConflictError, KeybindManager};
 pub use parser::parse_key;
@@ -4,7 +4,8 @@ use crate::binding::{KeyBinding, KeySequence};
 use crate::conflict::{Conflict, ConflictReport, ConflictSeverity};
 use crate::context::Context;
 use crate::display::KeyDisplayConfig;
-use crate::keymap::{ActionId, Keymap};
+use crate::hint::KeyHint;
+use crate::keymap::{Action, ActionGroup, ActionId, Keymap};
 use crate::macros::MacroManager;
 use crate::preset::KeymapPreset;
 use crate::scheme::KeyScheme;
@@ -45,6 +46,10 @@ pub struct KeybindManager {
     last_key_time: Option<Instant>,
     /// Keys that were explicitly unbound
     unbound_keys: HashSet<KeySequence>,
+    /// Action groups, in display order
+    groups: Vec<ActionGroup>,
+    /// Action descriptions for hints and help
+    descriptions: HashMap<String, String>,
 }
 
 impl KeybindManager {
@@ -62,6 +67,8 @@ impl KeybindManager {
             pending_keys: Vec::new(),
             last_key_time: None,
             unbound_keys: HashSet::new(),
+            groups: Vec::new(),
+            descriptions: HashMap::new(),
         }
     }
 
@@ -76,6 +83,49 @@ impl KeybindManager {
         self.display = config;
     }
 
+    /// Get the display configuration.
+    pub fn display(&self) -> &KeyDisplayConfig {
+        &self.display
+    }
+
+    /// Register an action group.
+    ///
+    /// Groups section the hint overlay in registration order.
+    pub fn register_group(&mut self, group: ActionGroup) {
+        match self.groups.iter_mut().find(|g| g.name == group.name) {
+            Some(existing) => existing.actions.extend(group.actions),
+            None => self.groups.push(group),
+        }
+    }
+
+    /// Set the description shown for an action.
+    pub fn describe(&mut self, action_id: impl Into<String>, description: impl Into<String>) {
+        self.descriptions.insert(action_id.into(), description.into());
+    }
+
+    /// Register an action's description and group.
+    pub fn register_action(&mut self, action: &dyn Action) {
+        self.describe(action.id(), action.description());
+        if let Some(group) = action.group() {
+            self.register_group(ActionGroup::new(group).action(action.id()));
+        }
+    }
+
+    /// Get the description for an action, falling back to its id.
+    pub fn description(&self, action_id: &str) -> String {
+        self.descriptions
+            .get(action_id)
+            .cloned()
+            .unwrap_or_else(|| action_id.replace('_', " "))
+    }
+
+    /// Get the group an action belongs to.
+    pub fn group_of(&self, action_id: &str) -> Option<&ActionGroup> {
+        self.groups
+            .iter()
+            .find(|g| g.actions.iter().any(|a| a == action_id))
+    }
+
     /// Set the current context.
     pub fn set_context(&mut self, context: Context) {
         self.context = context;
@@ -192,6 +242,81 @@ impl KeybindManager {
         false
     }
 
+    /// Keys pressed so far in an unfinished sequence.
+    pub fn pending_keys(&self) -> &[KeyBinding] {
+        &self.pending_keys
+    }
+
+    /// Check if a multi-key sequence is in progress and has not timed out.
+    pub fn is_pending(&self) -> bool {
+        if self.pending_keys.is_empty() {
+            return false;
+        }
+
+        let timeout_ms = self.scheme.timeout_ms();
+        match self.last_key_time {
+            Some(last) if timeout_ms > 0 => last.elapsed().as_millis() <= timeout_ms as u128,
+            _ => true,
+        }
+    }
+
+    /// Hints for the keys that can follow the pending sequence.
+    ///
+    /// Empty when no sequence is pending.
+    pub fn hints(&self) -> Vec<KeyHint> {
+        if !self.is_pending() {
+            return Vec::new();
+        }
+        self.hints_for(&KeySequence::from_keys(self.pending_keys.clone()))
+    }
+
+    /// Hints for the keys that can follow `prefix` in the current context.
+    ///
+    /// Sorted by group registration order, ungrouped hints last.
+    pub fn hints_for(&self, prefix: &KeySequence) -> Vec<KeyHint> {
+        // Resolve the effective keymap: preset, then user, context bindings last
+        let mut effective: HashMap<&KeySequence, &ActionId> = HashMap::new();
+        for keymap in [&self.preset_keymap, &self.user_keymap] {
+            for (seq, action) in keymap.bindings_for_context(&self.context) {
+                effective.insert(seq, action);
+            }
+        }
+
+        let mut hints: Vec<KeyHint> = Vec::new();
+        let mut continuations: HashMap<KeyBinding, usize> = HashMap::new();
+
+        for (seq, action) in effective {
+            if self.unbound_keys.contains(seq) || seq.len() <= prefix.len() || !seq.starts_with(prefix) {
+                continue;
+            }
+
+            let next = seq.keys[prefix.len()].clone();
+            if seq.len() == prefix.len() + 1 {
+                let mut hint = KeyHint::action(next, action.clone(), self.description(&action.0));
+                hint.group = self.group_of(&action.0).map(|g| g.name.clone());
+                hints.push(hint);
+            } else {
+                *continuations.entry(next).or_default() += 1;
+            }
+        }
+
+        // A complete binding wins over longer sequences sharing its key
+        for (key, count) in continuations {
+            if !hints.iter().any(|h| h.key == key) {
+                hints.push(KeyHint::prefix(key, count));
+            }
+        }
+
+        let group_index = |hint: &KeyHint| {
+            hint.group
+                .as_ref()
+                .and_then(|name| self.groups.iter().position(|g| &g.name == name))
+                .unwrap_or(usize::MAX)
+        };
+        hints.sort_by_cached_key(|h| (group_index(h), self.format_key(&h.key)));
+        hints
+    }
+
     /// Clear pending key sequence.
     fn clear_pending(&mut self) {
         self.pending_keys.clear();
@@ -229,39 +354,7 @@ impl KeybindManager {
 
     /// Format a single key binding for display.
     pub fn format_key(&self, key: &KeyBinding) -> String {
-        let mut parts = Vec::new();
-
-        if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) {
-            parts.push(self.display.format_modifier("ctrl").to_string());
-        }
-        if key.modifiers.contains(crossterm::event::KeyModifiers::ALT) {
-            parts.push(self.display.format_modifier("alt").to_string());
-        }
-        if key.modifiers.contains(crossterm::event::KeyModifiers::SHIFT) {
-            parts.push(self.display.format_modifier("shift").to_string());
-        }
-
-        let key_str = match key.key {
-            crossterm::event::KeyCode::Char(c) => c.to_uppercase().to_string(),
-            crossterm::event::KeyCode::Enter => self.display.format_key("enter"),
-            crossterm::event::KeyCode::Esc => self.display.format_key("escape"),
-            crossterm::event::KeyCode::Tab => self.display.format_key("tab"),
-            crossterm::event::KeyCode::Backspace => self.display.format_key("backspace"),
-            crossterm::event::KeyCode::Delete => self.display.format_key("delete"),
-            crossterm::event::KeyCode::Up => self.display.format_key("up"),
-            crossterm::event::KeyCode::Down => self.display.format_key("down"),
-            crossterm::event::KeyCode::Left => self.display.format_key("left"),
-            crossterm::event::KeyCode::Right => self.display.format_key("right"),
-            crossterm::event::KeyCode::Home => self.display.format_key("home"),
-            crossterm::event::KeyCode::End => self.display.format_key("end"),
-            crossterm::event::KeyCode::PageUp => self.display.format_key("pageup"),
-            crossterm::event::KeyCode::PageDown => self.display.format_key("pagedown"),
-            crossterm::event::KeyCode::F(n) => format!("F{}", n),
-            _ => format!("{:?}", key.key),
-        };
-
-        parts.push(key_str);
-        parts.join("+")
+        self.display.format_binding(key)
     }
 
     /// Check for conflicts between keymaps.
@@ -383,6 +476,43 @@ mod tests {
         assert!(action.is_none());
     }
 
+    #[test]
+    fn test_hints_after_prefix() {
+        let mut manager = KeybindManager::new(KeyScheme::chords(), None);
+        let leader = KeyBinding::key(KeyCode::Char(' '));
+        let seq = |keys: &[char]| {
+            let mut seq = KeySequence::single(leader.clone());
+            for c in keys {
+                seq.push(KeyBinding::key(KeyCode::Char(*c)));
+            }
+            seq
+        };
+
+        manager.bind(seq(&['f', 'f']), "find_file");
+        manager.bind(seq(&['f', 'r']), "recent_files");
+        manager.bind(seq(&['w']), "save");
+        manager.bind(seq(&['q']), "quit");
+        manager.register_group(ActionGroup::new("Files").action("save"));
+        manager.describe("save", "Save buffer");
+
+        assert!(manager.hints().is_empty());
+        assert!(manager.handle_key(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE)).is_none());
+        assert!(manager.is_pending());
+
+        let hints = manager.hints();
+        assert_eq!(hints.len(), 3);
+        assert_eq!(hints[0].description, "Save buffer");
+        assert_eq!(hints[0].group.as_deref(), Some("Files"));
+        assert!(hints.iter().any(|h| h.is_prefix && h.description == "+2 more"));
+        assert!(hints.iter().any(|h| h.description == "quit"));
+
+        // Continuations narrow as the sequence grows
+        manager.handle_key(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE));
+        let hints = manager.hints();
+        assert_eq!(hints.len(), 2);
+        assert!(hints.iter().all(|h| !h.is_prefix));
+    }
+
     #[test]
     fn test_unbind() {
         let mut manager = KeybindManager::default();
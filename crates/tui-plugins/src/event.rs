@@ -94,6 +94,67 @@ impl KeyEventData {
     }
 }
 
+#[cfg(feature = "crossterm")]
+impl From<&crossterm::event::KeyEvent> for KeyEventData {
+    fn from(key: &crossterm::event::KeyEvent) -> Self {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        let code = match key.code {
+            KeyCode::Char(' ') => "space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::F(n) => format!("f{}", n),
+            KeyCode::Enter => "enter".to_string(),
+            KeyCode::Esc => "esc".to_string(),
+            KeyCode::Tab => "tab".to_string(),
+            KeyCode::BackTab => "backtab".to_string(),
+            KeyCode::Backspace => "backspace".to_string(),
+            KeyCode::Delete => "delete".to_string(),
+            KeyCode::Insert => "insert".to_string(),
+            KeyCode::Up => "up".to_string(),
+            KeyCode::Down => "down".to_string(),
+            KeyCode::Left => "left".to_string(),
+            KeyCode::Right => "right".to_string(),
+            KeyCode::Home => "home".to_string(),
+            KeyCode::End => "end".to_string(),
+            KeyCode::PageUp => "pageup".to_string(),
+            KeyCode::PageDown => "pagedown".to_string(),
+            other => format!("{:?}", other).to_lowercase(),
+        };
+
+        let mut modifiers = Vec::new();
+        for (flag, name) in [
+            (KeyModifiers::CONTROL, "ctrl"),
+            (KeyModifiers::ALT, "alt"),
+            (KeyModifiers::SHIFT, "shift"),
+        ] {
+            if key.modifiers.contains(flag) {
+                modifiers.push(name.to_string());
+            }
+        }
+
+        // Combined form such as "ctrl+s", handy for matching in scripts
+        let raw = modifiers
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(code.as_str()))
+            .collect::<Vec<_>>()
+            .join("+");
+
+        Self {
+            code,
+            modifiers,
+            raw: Some(raw),
+        }
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl From<crossterm::event::KeyEvent> for KeyEventData {
+    fn from(key: crossterm::event::KeyEvent) -> Self {
+        Self::from(&key)
+    }
+}
+
 /// Command event data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommandEvent {
@@ -276,6 +337,20 @@ mod tests {
         assert!(!event.has_alt());
     }
 
+    #[cfg(feature = "crossterm")]
+    #[test]
+    fn test_from_crossterm() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+        let event = KeyEventData::from(&KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+        assert_eq!(event.code, "s");
+        assert!(event.has_ctrl());
+        assert_eq!(event.raw.as_deref(), Some("ctrl+s"));
+
+        let event = KeyEventData::from(KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE));
+        assert_eq!(event.raw.as_deref(), Some("pagedown"));
+    }
+
     #[test]
     fn test_command_event() {
         let mut args = HashMap::new();
@@ -0,0 +1,801 @@
+//! Host-side integration layer for apps.
+//!
+//! [`PluginHost`] bundles discovery, event delivery and response handling so
+//! an app can adopt plugins with a few lines:
+//!
+//! ```ignore
+//! let mut plugins = PluginHost::new("file-manager", env!("CARGO_PKG_VERSION"));
+//! plugins.register_command(AppCommand::new("refresh", "Refresh both panes"));
+//! plugins.load();
+//!
+//! // In the event loop
+//! if plugins.key(&key) {
+//!     return; // a plugin consumed the key
+//! }
+//! plugins.tick();
+//! for action in plugins.take_actions() {
+//!     match action {
+//!         HostAction::Notify { message, .. } => self.message = Some(message),
+//!         HostAction::RunCommand { name, .. } => self.run_command(&name),
+//!         _ => {}
+//!     }
+//! }
+//! ```
+
+use crate::context::PluginContextBuilder;
+use crate::error::PluginResult;
+use crate::event::{
+    CommandEvent, FileEvent, KeyEventData, PluginEvent, SelectionEvent, SelectionType, TimerEvent,
+};
+use crate::manager::{PluginManager, PluginManagerConfig};
+use crate::plugin::{PluginCommand, PluginState};
+use crate::response::{LogLevel, NotifyLevel, PluginResponse, PromptInputType, ResponseAction};
+use crate::Backend;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Shared-state key under which app commands are published to plugins.
+pub const COMMANDS_STATE_KEY: &str = "commands";
+
+/// Number of plugin log lines kept.
+const LOG_CAPACITY: usize = 200;
+
+/// A command the app exposes to plugins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppCommand {
+    /// Command name, as passed to `tui.run_command`.
+    pub name: String,
+    /// Display label.
+    pub label: String,
+    /// Description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl AppCommand {
+    /// Create a new command.
+    pub fn new(name: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: label.into(),
+            description: None,
+        }
+    }
+
+    /// Set the description.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// Something a plugin asked the app to do.
+#[derive(Debug, Clone)]
+pub enum HostAction {
+    /// Show a notification.
+    Notify {
+        /// Message to display.
+        message: String,
+        /// Notification level.
+        level: NotifyLevel,
+    },
+    /// Ask the user for input; answer with [`PluginHost::respond`].
+    Prompt(PluginPrompt),
+    /// Run an app command.
+    RunCommand {
+        /// Command name.
+        name: String,
+        /// Command arguments.
+        args: HashMap<String, serde_json::Value>,
+    },
+    /// Insert text at the cursor (or into the active input).
+    InsertText(String),
+    /// Replace the current selection.
+    ReplaceSelection(String),
+    /// Copy text to the clipboard.
+    SetClipboard(String),
+    /// Open a file.
+    OpenFile {
+        /// File path.
+        path: String,
+        /// Position to jump to.
+        position: Option<(usize, usize)>,
+    },
+    /// Send app data back with [`PluginHost::reply`].
+    RequestData {
+        /// Requesting plugin.
+        plugin: String,
+        /// Data type requested.
+        data_type: String,
+        /// Callback command.
+        callback: String,
+    },
+    /// Data returned by a plugin.
+    Data {
+        /// Returning plugin.
+        plugin: String,
+        /// Returned data.
+        data: serde_json::Value,
+    },
+    /// App-specific action.
+    Custom {
+        /// Requesting plugin.
+        plugin: String,
+        /// Action name.
+        name: String,
+        /// Action data.
+        data: serde_json::Value,
+    },
+}
+
+/// Outcome of feeding a key to a [`PluginPrompt`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptStatus {
+    /// Still collecting input.
+    Pending,
+    /// User confirmed; pass the prompt to [`PluginHost::respond`].
+    Submitted,
+    /// User cancelled.
+    Cancelled,
+}
+
+/// An open prompt requested by a plugin.
+#[derive(Debug, Clone)]
+pub struct PluginPrompt {
+    /// Plugin that asked.
+    pub plugin: String,
+    /// Prompt title.
+    pub title: String,
+    /// Prompt message.
+    pub message: Option<String>,
+    /// Input type.
+    pub input_type: PromptInputType,
+    /// Current text input.
+    pub input: String,
+    /// Selected option for `Select` prompts.
+    pub selected: usize,
+    /// Callback command run with the answer.
+    pub callback: String,
+}
+
+impl PluginPrompt {
+    /// Value to send back to the plugin.
+    ///
+    /// Confirm prompts answer with a boolean, select prompts with the chosen
+    /// option and everything else with the entered text.
+    pub fn value(&self) -> serde_json::Value {
+        match &self.input_type {
+            PromptInputType::Confirm => {
+                serde_json::Value::Bool(matches!(self.input.as_str(), "y" | "Y" | "yes"))
+            }
+            PromptInputType::Select { options } => options
+                .get(self.selected)
+                .cloned()
+                .map(serde_json::Value::String)
+                .unwrap_or(serde_json::Value::Null),
+            _ => serde_json::Value::String(self.input.clone()),
+        }
+    }
+
+    /// Input as it should be displayed (masked for passwords).
+    pub fn display_input(&self) -> String {
+        match &self.input_type {
+            PromptInputType::Password => "*".repeat(self.input.chars().count()),
+            PromptInputType::Confirm => "[y/n]".to_string(),
+            PromptInputType::Select { options } => options
+                .get(self.selected)
+                .map(|o| format!("< {} >", o))
+                .unwrap_or_default(),
+            _ => self.input.clone(),
+        }
+    }
+
+    /// Feed a key press to the prompt.
+    #[cfg(feature = "crossterm")]
+    pub fn handle_key(&mut self, key: &crossterm::event::KeyEvent) -> PromptStatus {
+        use crossterm::event::KeyCode;
+
+        match (&self.input_type, key.code) {
+            (_, KeyCode::Esc) => PromptStatus::Cancelled,
+            (PromptInputType::Confirm, KeyCode::Char(c @ ('y' | 'Y' | 'n' | 'N'))) => {
+                self.input = c.to_string();
+                PromptStatus::Submitted
+            }
+            (PromptInputType::Confirm, _) => PromptStatus::Pending,
+            (PromptInputType::Select { options }, code) => {
+                let len = options.len().max(1);
+                match code {
+                    KeyCode::Enter => return PromptStatus::Submitted,
+                    KeyCode::Down | KeyCode::Right | KeyCode::Tab | KeyCode::Char('j') => {
+                        self.selected = (self.selected + 1) % len;
+                    }
+                    KeyCode::Up | KeyCode::Left | KeyCode::BackTab | KeyCode::Char('k') => {
+                        self.selected = (self.selected + len - 1) % len;
+                    }
+                    _ => {}
+                }
+                PromptStatus::Pending
+            }
+            (PromptInputType::TextArea, KeyCode::Enter)
+                if key.modifiers.contains(crossterm::event::KeyModifiers::SHIFT) =>
+            {
+                self.input.push('\n');
+                PromptStatus::Pending
+            }
+            (_, KeyCode::Enter) => PromptStatus::Submitted,
+            (_, KeyCode::Backspace) => {
+                self.input.pop();
+                PromptStatus::Pending
+            }
+            (_, KeyCode::Char(c)) => {
+                self.input.push(c);
+                PromptStatus::Pending
+            }
+            _ => PromptStatus::Pending,
+        }
+    }
+}
+
+/// A timer set by a plugin.
+struct Timer {
+    plugin: String,
+    id: String,
+    interval: Duration,
+    repeat: bool,
+    started: Instant,
+    next: Instant,
+}
+
+/// State shared with the callbacks handed to plugins.
+#[derive(Default)]
+struct Shared {
+    actions: Mutex<Vec<HostAction>>,
+    selection: Mutex<Option<String>>,
+    logs: Mutex<VecDeque<String>>,
+}
+
+impl Shared {
+    fn push(&self, action: HostAction) {
+        if let Ok(mut actions) = self.actions.lock() {
+            actions.push(action);
+        }
+    }
+
+    fn log(&self, source: &str, level: LogLevel, message: &str) {
+        if let Ok(mut logs) = self.logs.lock() {
+            if logs.len() == LOG_CAPACITY {
+                logs.pop_front();
+            }
+            logs.push_back(format!("[{}] {:?}: {}", source, level, message));
+        }
+    }
+}
+
+/// Plugin integration for an app.
+///
+/// Owns a [`PluginManager`], forwards app events to ready plugins and turns
+/// their responses into [`HostAction`]s for the app to apply. Timers and
+/// logging are handled internally.
+pub struct PluginHost {
+    manager: PluginManager,
+    config: PluginManagerConfig,
+    commands: Vec<AppCommand>,
+    shared: Arc<Shared>,
+    timers: Vec<Timer>,
+    last_selection: Option<String>,
+}
+
+impl PluginHost {
+    /// Create a host for an app, searching the app's standard plugin directories.
+    pub fn new(app_name: impl Into<String>, app_version: impl Into<String>) -> Self {
+        let mut config = PluginManagerConfig::for_app(app_name, app_version);
+        for backend in [Backend::Lua, Backend::Wasm, Backend::Native] {
+            if backend.is_available() {
+                config = config.enable_backend(backend);
+            }
+        }
+        Self::with_config(config)
+    }
+
+    /// Create a host with an explicit manager configuration.
+    pub fn with_config(config: PluginManagerConfig) -> Self {
+        let shared = Arc::new(Shared::default());
+        let mut host = Self {
+            manager: PluginManager::new(config.clone()),
+            config,
+            commands: Vec::new(),
+            shared,
+            timers: Vec::new(),
+            last_selection: None,
+        };
+        host.install_context();
+        host
+    }
+
+    /// Build the plugin context whose callbacks feed this host.
+    fn install_context(&mut self) {
+        let log = self.shared.clone();
+        let notify = self.shared.clone();
+        let selection = self.shared.clone();
+        let clipboard = self.shared.clone();
+        let command = self.shared.clone();
+
+        let mut builder = PluginContextBuilder::new(&self.config.app_name, &self.config.app_version)
+            .on_log(move |level, msg| log.log("plugin", level, msg))
+            .on_notify(move |msg| {
+                notify.push(HostAction::Notify {
+                    message: msg.to_string(),
+                    level: NotifyLevel::Info,
+                })
+            })
+            .on_get_selection(move || selection.selection.lock().ok()?.clone())
+            .on_set_clipboard(move |text| {
+                clipboard.push(HostAction::SetClipboard(text.to_string()));
+                Ok(())
+            })
+            .on_run_command(move |name, args| {
+                command.push(HostAction::RunCommand {
+                    name: name.to_string(),
+                    args: args.clone(),
+                });
+                Ok(())
+            });
+        if let Some(dir) = self.config.plugin_dirs.first() {
+            builder = builder.data_dir(dir.clone()).config_dir(dir.clone());
+        }
+
+        let context = builder.build();
+        context.set_state(
+            COMMANDS_STATE_KEY,
+            serde_json::to_value(&self.commands).unwrap_or_default(),
+        );
+        self.manager.set_context(context);
+    }
+
+    /// Expose an app command to plugins.
+    ///
+    /// Register commands before [`Self::load`] so plugins see them in `init`.
+    pub fn register_command(&mut self, command: AppCommand) {
+        self.commands.retain(|c| c.name != command.name);
+        self.commands.push(command);
+        self.install_context();
+    }
+
+    /// Expose several app commands to plugins.
+    pub fn register_commands(&mut self, commands: impl IntoIterator<Item = AppCommand>) {
+        for command in commands {
+            self.commands.retain(|c| c.name != command.name);
+            self.commands.push(command);
+        }
+        self.install_context();
+    }
+
+    /// Commands the app exposed to plugins.
+    pub fn app_commands(&self) -> &[AppCommand] {
+        &self.commands
+    }
+
+    /// Discover and initialize plugins from the configured directories.
+    ///
+    /// Returns the IDs of plugins that are ready. Failures are logged.
+    pub fn load(&mut self) -> Vec<String> {
+        if let Err(e) = self.manager.discover_and_load() {
+            self.shared.log("host", LogLevel::Error, &e.to_string());
+        }
+        for (id, result) in self.manager.init_all() {
+            if let Err(e) = result {
+                self.shared
+                    .log("host", LogLevel::Error, &format!("{} failed to start: {}", id, e));
+            }
+        }
+        self.ready_plugins()
+    }
+
+    /// Load and initialize a single plugin file.
+    pub fn load_file(&mut self, path: &Path) -> PluginResult<String> {
+        let id = self.manager.load(path)?;
+        self.manager.init(&id)?;
+        Ok(id)
+    }
+
+    /// IDs of ready plugins, sorted for stable dispatch order.
+    pub fn ready_plugins(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .manager
+            .plugin_ids()
+            .into_iter()
+            .filter(|id| self.manager.state(id) == Some(PluginState::Ready))
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Number of loaded plugins.
+    pub fn count(&self) -> usize {
+        self.manager.count()
+    }
+
+    /// Underlying plugin manager.
+    pub fn manager(&self) -> &PluginManager {
+        &self.manager
+    }
+
+    /// Mutable plugin manager.
+    pub fn manager_mut(&mut self) -> &mut PluginManager {
+        &mut self.manager
+    }
+
+    /// Commands provided by plugins (IDs are `plugin:command`).
+    pub fn plugin_commands(&self) -> Vec<PluginCommand> {
+        self.ready_plugins()
+            .iter()
+            .filter_map(|id| self.manager.get(id))
+            .flat_map(|plugin| plugin.get_commands())
+            .collect()
+    }
+
+    /// Run a plugin-provided command by its `plugin:command` ID.
+    pub fn run_plugin_command(&mut self, id: &str) -> PluginResult<()> {
+        let (plugin, command) = id
+            .split_once(':')
+            .ok_or_else(|| crate::PluginError::NotFound(id.to_string()))?;
+        let event = PluginEvent::Command(CommandEvent::new(command));
+        let response = self.manager.send(plugin, &event)?;
+        if let Some(response) = response {
+            self.apply(plugin, response);
+        }
+        Ok(())
+    }
+
+    /// Deliver a key press. Returns `true` if a plugin consumed it.
+    pub fn key(&mut self, key: impl Into<KeyEventData>) -> bool {
+        self.dispatch(&PluginEvent::Key(key.into()))
+    }
+
+    /// Notify plugins that an app command ran. Returns `true` if a plugin handled it.
+    pub fn command(&mut self, name: &str, args: HashMap<String, serde_json::Value>) -> bool {
+        self.dispatch(&PluginEvent::Command(CommandEvent::with_args(name, args)))
+    }
+
+    /// Report the current selection; repeated selections are not re-sent.
+    pub fn selection_changed(&mut self, selection: impl Into<String>, selection_type: SelectionType) {
+        let selection = selection.into();
+        if self.last_selection.as_ref() == Some(&selection) {
+            return;
+        }
+        if let Ok(mut current) = self.shared.selection.lock() {
+            *current = Some(selection.clone());
+        }
+        self.last_selection = Some(selection.clone());
+        self.dispatch(&PluginEvent::SelectionChanged(SelectionEvent {
+            selection,
+            selection_type,
+            start: None,
+            end: None,
+        }));
+    }
+
+    /// Report that a file was opened.
+    pub fn file_opened(&mut self, path: impl AsRef<Path>) {
+        let event = FileEvent::new(path.as_ref().display().to_string());
+        self.dispatch(&PluginEvent::FileOpened(event));
+    }
+
+    /// Report that a file was saved.
+    pub fn file_saved(&mut self, path: impl AsRef<Path>) {
+        let event = FileEvent::new(path.as_ref().display().to_string());
+        self.dispatch(&PluginEvent::FileSaved(event));
+    }
+
+    /// Fire due plugin timers. Call once per event-loop iteration.
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        let mut due = Vec::new();
+        self.timers.retain_mut(|timer| {
+            if timer.next > now {
+                return true;
+            }
+            due.push((
+                timer.plugin.clone(),
+                TimerEvent {
+                    id: timer.id.clone(),
+                    elapsed_ms: now.duration_since(timer.started).as_millis() as u64,
+                },
+            ));
+            timer.next = now + timer.interval;
+            timer.repeat
+        });
+
+        for (plugin, event) in due {
+            self.send(&plugin, &PluginEvent::Timer(event));
+        }
+    }
+
+    /// Send the answer to a prompt back to the plugin that asked.
+    pub fn respond(&mut self, prompt: &PluginPrompt) {
+        let mut args = HashMap::new();
+        args.insert("value".to_string(), prompt.value());
+        let event = PluginEvent::Command(CommandEvent::with_args(&prompt.callback, args));
+        self.send(&prompt.plugin, &event);
+    }
+
+    /// Answer a [`HostAction::RequestData`].
+    pub fn reply(&mut self, plugin: &str, callback: &str, data: serde_json::Value) {
+        let mut args = HashMap::new();
+        args.insert("data".to_string(), data);
+        let event = PluginEvent::Command(CommandEvent::with_args(callback, args));
+        self.send(plugin, &event);
+    }
+
+    /// Drain actions requested by plugins since the last call.
+    pub fn take_actions(&mut self) -> Vec<HostAction> {
+        self.shared
+            .actions
+            .lock()
+            .map(|mut actions| std::mem::take(&mut *actions))
+            .unwrap_or_default()
+    }
+
+    /// Recent plugin log lines, oldest first.
+    pub fn logs(&self) -> Vec<String> {
+        self.shared
+            .logs
+            .lock()
+            .map(|logs| logs.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Shut down all plugins.
+    pub fn shutdown(&mut self) {
+        self.timers.clear();
+        for (id, result) in self.manager.shutdown_all() {
+            if let Err(e) = result {
+                self.shared
+                    .log("host", LogLevel::Error, &format!("{} failed to shut down: {}", id, e));
+            }
+        }
+    }
+
+    /// Deliver an event to ready plugins until one marks it handled.
+    fn dispatch(&mut self, event: &PluginEvent) -> bool {
+        for id in self.ready_plugins() {
+            match self.manager.send(&id, event) {
+                Ok(Some(response)) => {
+                    let handled = response.handled;
+                    self.apply(&id, response);
+                    if handled {
+                        return true;
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => self.shared.log(&id, LogLevel::Error, &e.to_string()),
+            }
+        }
+        false
+    }
+
+    /// Deliver an event to one plugin.
+    fn send(&mut self, plugin: &str, event: &PluginEvent) {
+        match self.manager.send(plugin, event) {
+            Ok(Some(response)) => self.apply(plugin, response),
+            Ok(None) => {}
+            Err(e) => self.shared.log(plugin, LogLevel::Error, &e.to_string()),
+        }
+    }
+
+    /// Turn a plugin response into host state changes or queued actions.
+    fn apply(&mut self, plugin: &str, response: PluginResponse) {
+        let action = match response.action {
+            ResponseAction::None => return,
+            ResponseAction::Notify { message, level, .. } => HostAction::Notify { message, level },
+            ResponseAction::Prompt {
+                title,
+                message,
+                input_type,
+                default_value,
+                callback,
+            } => HostAction::Prompt(PluginPrompt {
+                plugin: plugin.to_string(),
+                title,
+                message,
+                input_type,
+                input: default_value.unwrap_or_default(),
+                selected: 0,
+                callback,
+            }),
+            ResponseAction::RunCommand { name, args } => HostAction::RunCommand { name, args },
+            ResponseAction::SetClipboard { text } => HostAction::SetClipboard(text),
+            ResponseAction::OpenFile { path, position } => HostAction::OpenFile { path, position },
+            ResponseAction::InsertText { text } => HostAction::InsertText(text),
+            ResponseAction::ReplaceSelection { text } => HostAction::ReplaceSelection(text),
+            ResponseAction::Log { level, message } => {
+                self.shared.log(plugin, level, &message);
+                return;
+            }
+            ResponseAction::SetTimer {
+                id,
+                interval_ms,
+                repeat,
+            } => {
+                let now = Instant::now();
+                let interval = Duration::from_millis(interval_ms.max(1));
+                self.timers.retain(|t| !(t.plugin == plugin && t.id == id));
+                self.timers.push(Timer {
+                    plugin: plugin.to_string(),
+                    id,
+                    interval,
+                    repeat,
+                    started: now,
+                    next: now + interval,
+                });
+                return;
+            }
+            ResponseAction::CancelTimer { id } => {
+                self.timers.retain(|t| !(t.plugin == plugin && t.id == id));
+                return;
+            }
+            ResponseAction::RequestData {
+                data_type,
+                callback,
+            } => HostAction::RequestData {
+                plugin: plugin.to_string(),
+                data_type,
+                callback,
+            },
+            ResponseAction::ReturnData { data } => HostAction::Data {
+                plugin: plugin.to_string(),
+                data,
+            },
+            ResponseAction::Custom { name, data } => HostAction::Custom {
+                plugin: plugin.to_string(),
+                name,
+                data,
+            },
+        };
+        self.shared.push(action);
+    }
+}
+
+impl Drop for PluginHost {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host() -> PluginHost {
+        let config = PluginManagerConfig {
+            plugin_dirs: Vec::new(),
+            ..PluginManagerConfig::for_app("test-app", "1.0.0")
+        };
+        PluginHost::with_config(config)
+    }
+
+    #[test]
+    fn test_prompt_value() {
+        let mut prompt = PluginPrompt {
+            plugin: "p".to_string(),
+            title: "Pick".to_string(),
+            message: None,
+            input_type: PromptInputType::Select {
+                options: vec!["a".to_string(), "b".to_string()],
+            },
+            input: String::new(),
+            selected: 1,
+            callback: "picked".to_string(),
+        };
+        assert_eq!(prompt.value(), serde_json::json!("b"));
+        assert_eq!(prompt.display_input(), "< b >");
+
+        prompt.input_type = PromptInputType::Password;
+        prompt.input = "secret".to_string();
+        assert_eq!(prompt.display_input(), "******");
+        assert_eq!(prompt.value(), serde_json::json!("secret"));
+    }
+
+    #[test]
+    fn test_timer_and_log_responses() {
+        let mut host = host();
+        host.apply(
+            "p",
+            PluginResponse {
+                action: ResponseAction::SetTimer {
+                    id: "poll".to_string(),
+                    interval_ms: 1,
+                    repeat: true,
+                },
+                handled: false,
+                payload: None,
+            },
+        );
+        host.apply("p", PluginResponse::log(LogLevel::Warn, "careful"));
+        assert_eq!(host.timers.len(), 1);
+        assert!(host.logs()[0].contains("careful"));
+        assert!(host.take_actions().is_empty());
+
+        host.apply(
+            "p",
+            PluginResponse {
+                action: ResponseAction::CancelTimer {
+                    id: "poll".to_string(),
+                },
+                handled: false,
+                payload: None,
+            },
+        );
+        assert!(host.timers.is_empty());
+    }
+
+    #[cfg(feature = "lua")]
+    #[test]
+    fn test_lua_plugin_round_trip() {
+        use std::io::Write;
+
+        let mut file = tempfile::NamedTempFile::with_suffix(".lua").unwrap();
+        write!(
+            file,
+            r#"
+local plugin = {{ id = "todo" }}
+
+function plugin.init(ctx)
+    plugin.count = #tui.commands()
+end
+
+function plugin.on_event(event)
+    if event.type == "key" and event.code == "t" and event.modifiers[1] == "ctrl" then
+        tui.run_command("refresh", {{ force = true }})
+        return {{ type = "insert_text", text = "TODO ", handled = true }}
+    elseif event.type == "selection_changed" then
+        tui.notify(event.selection .. " of " .. plugin.count)
+    elseif event.type == "command" and event.name == "answer" then
+        return {{ type = "set_timer", id = "t", interval_ms = 1 }}
+    elseif event.type == "timer" then
+        return {{ action = "notify", message = "timer " .. event.id }}
+    end
+end
+
+return plugin
+"#
+        )
+        .unwrap();
+
+        let mut host = host();
+        host.register_command(AppCommand::new("refresh", "Refresh"));
+        host.load_file(file.path()).unwrap();
+
+        assert!(host.key(KeyEventData::new("t", vec!["ctrl".to_string()])));
+        assert!(!host.key(KeyEventData::new("x", Vec::new())));
+        let actions = host.take_actions();
+        assert!(matches!(
+            &actions[0],
+            HostAction::RunCommand { name, args } if name == "refresh" && args["force"] == true
+        ));
+        assert!(matches!(&actions[1], HostAction::InsertText(text) if text == "TODO "));
+
+        host.selection_changed("task 3", SelectionType::Item);
+        host.selection_changed("task 3", SelectionType::Item);
+        let actions = host.take_actions();
+        assert_eq!(actions.len(), 1);
+        assert!(matches!(&actions[0], HostAction::Notify { message, .. } if message == "task 3 of 1"));
+
+        host.respond(&PluginPrompt {
+            plugin: "todo".to_string(),
+            title: String::new(),
+            message: None,
+            input_type: PromptInputType::Text,
+            input: "ok".to_string(),
+            selected: 0,
+            callback: "answer".to_string(),
+        });
+        std::thread::sleep(Duration::from_millis(5));
+        host.tick();
+        let actions = host.take_actions();
+        assert!(matches!(&actions[0], HostAction::Notify { message, .. } if message == "timer t"));
+    }
+}
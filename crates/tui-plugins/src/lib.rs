@@ -22,6 +22,7 @@ pub mod capability;
 pub mod context;
 pub mod error;
 pub mod event;
+pub mod host;
 pub mod manifest;
 pub mod manager;
 pub mod plugin;
@@ -41,11 +42,12 @@ pub mod native;
 pub use capability::Capability;
 pub use context::PluginContext;
 pub use error::PluginError;
-pub use event::PluginEvent;
+pub use event::{KeyEventData, PluginEvent, SelectionType};
+pub use host::{AppCommand, HostAction, PluginHost, PluginPrompt, PromptStatus};
 pub use manifest::{Manifest, ManifestBackend, ManifestCapabilities, ManifestPermissions};
 pub use manager::{PluginManager, PluginManagerConfig};
 pub use plugin::Plugin;
-pub use response::PluginResponse;
+pub use response::{NotifyLevel, PluginResponse, PromptInputType, ResponseAction};
 pub use sandbox::SandboxConfig;
 
 /// Plugin backend type.
@@ -9,6 +9,7 @@ use crate::response::PluginResponse;
 use crate::sandbox::SandboxConfig;
 use crate::Backend;
 use mlua::{Function, Lua, Table, Value};
+use std::collections::HashMap;
 use std::path::Path;
 
 /// A Lua-based plugin.
@@ -210,12 +211,69 @@ impl Plugin for LuaPlugin {
         ctx_table.set("app_name", ctx.app_name())?;
         ctx_table.set("app_version", ctx.app_version())?;
 
-        // Add log function to context
-        let log_fn = self.lua.create_function(|_, msg: String| {
-            println!("[plugin] {}", msg);
+        // Route the tui API through the host's callbacks
+        let tui = self.lua.create_table()?;
+
+        let log_ctx = ctx.clone();
+        let log_fn = self.lua.create_function(move |_, msg: String| {
+            log_ctx.log_info(&msg);
             Ok(())
         })?;
-        ctx_table.set("log", log_fn)?;
+        ctx_table.set("log", log_fn.clone())?;
+        tui.set("log", log_fn)?;
+
+        let notify_ctx = ctx.clone();
+        tui.set(
+            "notify",
+            self.lua.create_function(move |_, msg: String| {
+                notify_ctx.notify(&msg);
+                Ok(())
+            })?,
+        )?;
+
+        let command_ctx = ctx.clone();
+        tui.set(
+            "run_command",
+            self.lua.create_function(move |_, (name, args): (String, Option<Table>)| {
+                let args = match args.map(|t| lua_to_json(&Value::Table(t))) {
+                    Some(serde_json::Value::Object(map)) => map.into_iter().collect(),
+                    _ => HashMap::new(),
+                };
+                command_ctx
+                    .run_command(&name, &args)
+                    .map_err(mlua::Error::external)
+            })?,
+        )?;
+
+        let selection_ctx = ctx.clone();
+        tui.set(
+            "get_selection",
+            self.lua
+                .create_function(move |_, ()| Ok(selection_ctx.get_selection()))?,
+        )?;
+
+        let clipboard_ctx = ctx.clone();
+        tui.set(
+            "set_clipboard",
+            self.lua.create_function(move |_, text: String| {
+                clipboard_ctx
+                    .set_clipboard(&text)
+                    .map_err(mlua::Error::external)
+            })?,
+        )?;
+
+        let commands_ctx = ctx.clone();
+        tui.set(
+            "commands",
+            self.lua.create_function(move |lua, ()| {
+                let commands = commands_ctx
+                    .get_state(crate::host::COMMANDS_STATE_KEY)
+                    .unwrap_or(serde_json::Value::Array(Vec::new()));
+                json_to_lua(lua, &commands)
+            })?,
+        )?;
+
+        self.lua.globals().set("tui", tui)?;
 
         // Call init function if it exists
         let _: Option<()> = self.call_plugin_fn("init", ctx_table)?;
@@ -237,36 +295,23 @@ impl Plugin for LuaPlugin {
             return Err(PluginError::InvalidState("Plugin not initialized".to_string()));
         }
 
-        // Convert event to Lua table
-        let event_json = serde_json::to_string(event)?;
-        let _event_table: Value = self.lua.load(&format!("return {}",
-            event_json.replace("\"", "'")
-                .replace("null", "nil")
-        )).eval().unwrap_or(Value::Nil);
-
-        // Actually, let's use a simpler approach - create a table directly
-        let event_table = self.lua.create_table()?;
+        // Expose the event's fields directly (event.type, event.name, ...),
+        // keeping the raw JSON under `data` for plugins that prefer it
+        let event_json = event.to_json();
+        let event_table = match json_to_lua(&self.lua, &event_json)? {
+            Value::Table(table) => table,
+            _ => self.lua.create_table()?,
+        };
         event_table.set("type", event.event_type())?;
-        event_table.set("data", event_json)?;
+        event_table.set("data", event_json.to_string())?;
 
         // Call on_event
-        let result: Option<Table> = self.call_plugin_fn("on_event", event_table)?;
-
-        if let Some(response_table) = result {
-            // Parse response from Lua table
-            let action: String = response_table.get::<_, String>("action").unwrap_or_default();
-            let message: Option<String> = response_table.get::<_, String>("message").ok();
-
-            if action == "notify" {
-                if let Some(msg) = message {
-                    return Ok(Some(PluginResponse::notify(msg)));
-                }
-            }
+        let result: Option<Value> = self.call_plugin_fn("on_event", event_table)?;
 
-            // Handle other actions...
+        match result {
+            Some(Value::Table(table)) => parse_response(&table).map(Some),
+            _ => Ok(None),
         }
-
-        Ok(None)
     }
 
     fn get_commands(&self) -> Vec<PluginCommand> {
@@ -300,6 +345,94 @@ impl Plugin for LuaPlugin {
     }
 }
 
+/// Parse a response table returned from `on_event`.
+///
+/// Accepts the full [`PluginResponse`] shape (`{ type = "insert_text", text = "..." }`)
+/// as well as the shorthand `{ action = "notify", message = "..." }`.
+fn parse_response(table: &Table) -> PluginResult<PluginResponse> {
+    let mut fields = match lua_to_json(&Value::Table(table.clone())) {
+        serde_json::Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+
+    let handled = fields
+        .remove("handled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let payload = fields.remove("payload");
+    if let Some(action) = fields.remove("action") {
+        fields.entry("type").or_insert(action);
+    }
+    if !fields.contains_key("type") {
+        return Ok(PluginResponse::none());
+    }
+
+    let action = serde_json::from_value(serde_json::Value::Object(fields))
+        .map_err(|e| PluginError::PluginReturned(format!("invalid response: {}", e)))?;
+    Ok(PluginResponse {
+        action,
+        handled,
+        payload,
+    })
+}
+
+/// Convert a JSON value into a Lua value.
+fn json_to_lua<'lua>(lua: &'lua Lua, value: &serde_json::Value) -> mlua::Result<Value<'lua>> {
+    Ok(match value {
+        serde_json::Value::Null => Value::Nil,
+        serde_json::Value::Bool(b) => Value::Boolean(*b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Number(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => Value::String(lua.create_string(s)?),
+        serde_json::Value::Array(items) => {
+            let table = lua.create_table()?;
+            for (i, item) in items.iter().enumerate() {
+                table.raw_set(i + 1, json_to_lua(lua, item)?)?;
+            }
+            Value::Table(table)
+        }
+        serde_json::Value::Object(map) => {
+            let table = lua.create_table()?;
+            for (key, item) in map {
+                table.raw_set(key.as_str(), json_to_lua(lua, item)?)?;
+            }
+            Value::Table(table)
+        }
+    })
+}
+
+/// Convert a Lua value into JSON.
+///
+/// Tables with a non-empty array part become arrays; other tables become
+/// objects keyed by their string keys. Functions and userdata are dropped.
+fn lua_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::Boolean(b) => serde_json::Value::Bool(*b),
+        Value::Integer(i) => serde_json::Value::from(*i),
+        Value::Number(n) => serde_json::Value::from(*n),
+        Value::String(s) => serde_json::Value::String(s.to_string_lossy().into_owned()),
+        Value::Table(table) => {
+            let len = table.raw_len();
+            if len > 0 {
+                (1..=len)
+                    .map(|i| lua_to_json(&table.raw_get(i).unwrap_or(Value::Nil)))
+                    .collect()
+            } else {
+                table
+                    .clone()
+                    .pairs::<String, Value>()
+                    .flatten()
+                    .map(|(key, item)| (key, lua_to_json(&item)))
+                    .collect::<serde_json::Map<_, _>>()
+                    .into()
+            }
+        }
+        _ => serde_json::Value::Null,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -350,6 +483,65 @@ return plugin
         assert_eq!(plugin.backend(), Backend::Lua);
     }
 
+    fn load_source(source: &str) -> Box<dyn Plugin> {
+        let mut file = NamedTempFile::with_suffix(".lua").unwrap();
+        write!(file, "{}", source).unwrap();
+        let mut plugin = LuaPlugin::load(file.path(), &SandboxConfig::default()).unwrap();
+        plugin
+            .init(&PluginContext::new(
+                "test-app",
+                "1.0.0",
+                std::path::PathBuf::from("/tmp"),
+                std::path::PathBuf::from("/tmp"),
+            ))
+            .unwrap();
+        plugin
+    }
+
+    #[test]
+    fn test_event_fields_and_responses() {
+        let mut plugin = load_source(
+            r#"
+return {
+    id = "echo",
+    on_event = function(event)
+        if event.type == "key" and event.code == "t" then
+            return { type = "insert_text", text = "TODO: ", handled = true }
+        elseif event.type == "command" and event.name == "ask" then
+            return { type = "prompt", title = "Name?", callback = "greet" }
+        elseif event.type == "command" then
+            return { action = "notify", message = "hi " .. event.args.value }
+        end
+    end,
+}
+"#,
+        );
+
+        let key = PluginEvent::Key(crate::event::KeyEventData::new("t", Vec::new()));
+        let response = plugin.on_event(&key).unwrap().unwrap();
+        assert!(response.handled);
+        assert!(matches!(
+            response.action,
+            crate::response::ResponseAction::InsertText { ref text } if text == "TODO: "
+        ));
+
+        let ask = PluginEvent::Command(crate::event::CommandEvent::new("ask"));
+        let response = plugin.on_event(&ask).unwrap().unwrap();
+        assert!(matches!(
+            response.action,
+            crate::response::ResponseAction::Prompt { ref callback, .. } if callback == "greet"
+        ));
+
+        let mut args = HashMap::new();
+        args.insert("value".to_string(), serde_json::json!("bob"));
+        let greet = PluginEvent::Command(crate::event::CommandEvent::with_args("greet", args));
+        let response = plugin.on_event(&greet).unwrap().unwrap();
+        assert!(matches!(
+            response.action,
+            crate::response::ResponseAction::Notify { ref message, .. } if message == "hi bob"
+        ));
+    }
+
     #[test]
     fn test_plugin_init() {
         let file = create_test_plugin();
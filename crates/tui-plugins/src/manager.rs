@@ -274,7 +274,7 @@ impl PluginManager {
             }
             #[cfg(feature = "wasm")]
             Backend::Wasm => {
-                crate::wasm::WasmPlugin::load(path, &self.config.sandbox)
+                crate::wasm::WasmPlugin::load_with_manifest(path, &self.config.sandbox, _manifest)
             }
             #[cfg(feature = "native")]
             Backend::Native => {
@@ -1,16 +1,122 @@
 //! WASM plugin backend.
 //!
 //! This module provides WebAssembly-based plugin support using wasmtime.
+//!
+//! # Host ABI
+//!
+//! Data crosses the boundary as UTF-8 JSON in the module's linear memory.
+//! Buffers returned by the guest are packed into an `i64` as
+//! `(ptr << 32) | len`; a return value of `0` means "nothing".
+//!
+//! Required exports:
+//! - `memory` - the module's linear memory.
+//! - `tui_alloc(len: i32) -> i32` - allocate `len` bytes for the host to write into.
+//! - `tui_on_event(ptr: i32, len: i32) -> i64` - handle a [`PluginEvent`] and
+//!   optionally return a [`PluginResponse`].
+//!
+//! Optional exports:
+//! - `tui_info() -> i64` - plugin metadata (`id`, `name`, `version`,
+//!   `description`, `capabilities`, `commands`).
+//! - `tui_init(ptr: i32, len: i32) -> i32` - receives `{"app_name", "app_version"}`;
+//!   non-zero means failure.
+//! - `tui_shutdown() -> i32` - non-zero means failure.
+//! - `tui_free(ptr: i32, len: i32)` - release a buffer returned by the guest.
+//! - `tui_capability_<name>` - any export with this prefix declares a capability.
+//!
+//! Host imports (module `tui`):
+//! - `log(level: i32, ptr: i32, len: i32)` - level 0-3 is debug, info, warn, error.
+//! - `notify(ptr: i32, len: i32)` - show a notification.
+//!
+//! Every call runs with the sandbox's instruction limit as fuel and its
+//! timeout as an epoch deadline; memory growth is capped at the memory limit.
 
 use crate::capability::Capability;
 use crate::context::PluginContext;
 use crate::error::{PluginError, PluginResult};
 use crate::event::PluginEvent;
-use crate::plugin::Plugin;
-use crate::response::PluginResponse;
+use crate::manifest::Manifest;
+use crate::plugin::{Plugin, PluginCommand};
+use crate::response::{LogLevel, PluginResponse};
 use crate::sandbox::SandboxConfig;
 use crate::Backend;
+use serde::Deserialize;
 use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+use wasmtime::{
+    Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder, Trap, TypedFunc,
+};
+
+/// Interval between epoch ticks used for timeouts.
+const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// Prefix of exports that declare a capability.
+const CAPABILITY_EXPORT_PREFIX: &str = "tui_capability_";
+
+/// Largest buffer accepted from a plugin.
+const MAX_BUFFER_LEN: usize = 16 * 1024 * 1024;
+
+/// Shared engine, with a background thread driving epoch deadlines.
+static ENGINE: OnceLock<Result<Engine, String>> = OnceLock::new();
+
+fn engine() -> PluginResult<&'static Engine> {
+    ENGINE
+        .get_or_init(|| {
+            let mut config = Config::new();
+            config.consume_fuel(true);
+            config.epoch_interruption(true);
+            let engine = Engine::new(&config).map_err(|e| e.to_string())?;
+
+            let ticker = engine.clone();
+            std::thread::Builder::new()
+                .name("wasm-epoch".to_string())
+                .spawn(move || loop {
+                    std::thread::sleep(EPOCH_TICK);
+                    ticker.increment_epoch();
+                })
+                .map_err(|e| e.to_string())?;
+
+            Ok(engine)
+        })
+        .as_ref()
+        .map_err(|e| PluginError::Wasm(e.clone()))
+}
+
+/// Store data available to host functions.
+struct HostState {
+    /// Memory limits for the instance.
+    limits: StoreLimits,
+    /// Host context, set once the plugin is initialized.
+    context: Option<PluginContext>,
+}
+
+/// Metadata returned by `tui_info`.
+#[derive(Debug, Default, Deserialize)]
+struct WasmInfo {
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    capabilities: Vec<String>,
+    #[serde(default)]
+    commands: Vec<WasmCommand>,
+}
+
+/// A command declared in `tui_info`.
+#[derive(Debug, Deserialize)]
+struct WasmCommand {
+    id: String,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
 
 /// A WASM-based plugin.
 pub struct WasmPlugin {
@@ -20,36 +126,343 @@ pub struct WasmPlugin {
     name: String,
     /// Plugin version.
     version: String,
+    /// Plugin description.
+    description: Option<String>,
     /// Capabilities.
     capabilities: Vec<Capability>,
+    /// Commands declared by the module.
+    commands: Vec<WasmCommand>,
     /// Whether initialized.
     initialized: bool,
-    // wasmtime engine and instance would go here
+    /// Sandbox configuration.
+    sandbox: SandboxConfig,
+    /// Store owning the instance.
+    store: Store<HostState>,
+    /// Exported linear memory.
+    memory: Memory,
+    /// `tui_alloc` export.
+    alloc: TypedFunc<i32, i32>,
+    /// `tui_on_event` export.
+    on_event: TypedFunc<(i32, i32), i64>,
+    /// `tui_init` export.
+    init: Option<TypedFunc<(i32, i32), i32>>,
+    /// `tui_shutdown` export.
+    shutdown: Option<TypedFunc<(), i32>>,
+    /// `tui_free` export.
+    free: Option<TypedFunc<(i32, i32), ()>>,
 }
 
 impl WasmPlugin {
     /// Load a WASM plugin from a file.
-    pub fn load(path: &Path, _sandbox: &SandboxConfig) -> PluginResult<Box<dyn Plugin>> {
-        // Read the WASM file
-        let _wasm_bytes = std::fs::read(path)?;
-
-        // TODO: Initialize wasmtime engine and load module
-        // For now, return an error indicating this is not yet implemented
+    pub fn load(path: &Path, sandbox: &SandboxConfig) -> PluginResult<Box<dyn Plugin>> {
+        Self::load_with_manifest(path, sandbox, None)
+    }
 
-        let id = path
+    /// Load a WASM plugin, taking metadata from a manifest if given.
+    pub fn load_with_manifest(
+        path: &Path,
+        sandbox: &SandboxConfig,
+        manifest: Option<&Manifest>,
+    ) -> PluginResult<Box<dyn Plugin>> {
+        let bytes = std::fs::read(path)?;
+        let fallback_id = path
             .file_stem()
             .and_then(|s| s.to_str())
-            .unwrap_or("unknown")
-            .to_string();
+            .unwrap_or("unknown");
+
+        Ok(Box::new(Self::from_bytes(&bytes, fallback_id, sandbox, manifest)?))
+    }
+
+    /// Instantiate a plugin from WASM (or WAT) bytes.
+    fn from_bytes(
+        bytes: &[u8],
+        fallback_id: &str,
+        sandbox: &SandboxConfig,
+        manifest: Option<&Manifest>,
+    ) -> PluginResult<Self> {
+        let engine = engine()?;
+        let module = Module::new(engine, bytes).map_err(|e| PluginError::Wasm(e.to_string()))?;
+
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(sandbox.memory_limit)
+            .instances(1)
+            .build();
+        let mut store = Store::new(
+            engine,
+            HostState {
+                limits,
+                context: None,
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+
+        let linker = Self::linker(engine)?;
+        Self::arm(&mut store, sandbox)?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .map_err(|e| Self::call_error(e, sandbox))?;
 
-        Ok(Box::new(Self {
-            id: id.clone(),
-            name: id,
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| PluginError::Wasm("Missing export: memory".to_string()))?;
+        let alloc = Self::required_func(&instance, &mut store, "tui_alloc")?;
+        let on_event = Self::required_func(&instance, &mut store, "tui_on_event")?;
+        let init = Self::optional_func(&instance, &mut store, "tui_init")?;
+        let shutdown = Self::optional_func(&instance, &mut store, "tui_shutdown")?;
+        let free = Self::optional_func(&instance, &mut store, "tui_free")?;
+        let info_fn: Option<TypedFunc<(), i64>> =
+            Self::optional_func(&instance, &mut store, "tui_info")?;
+
+        let export_caps: Vec<Capability> = module
+            .exports()
+            .filter_map(|export| export.name().strip_prefix(CAPABILITY_EXPORT_PREFIX))
+            .map(Capability::from_str_simple)
+            .collect();
+
+        let mut plugin = Self {
+            id: fallback_id.to_string(),
+            name: fallback_id.to_string(),
             version: "0.0.0".to_string(),
+            description: None,
             capabilities: Vec::new(),
+            commands: Vec::new(),
             initialized: false,
-        }))
+            sandbox: sandbox.clone(),
+            store,
+            memory,
+            alloc,
+            on_event,
+            init,
+            shutdown,
+            free,
+        };
+
+        let info = match info_fn {
+            Some(func) => {
+                Self::arm(&mut plugin.store, sandbox)?;
+                let packed = func
+                    .call(&mut plugin.store, ())
+                    .map_err(|e| Self::call_error(e, sandbox))?;
+                match plugin.take_buffer(packed)? {
+                    Some(json) => serde_json::from_slice(&json)?,
+                    None => WasmInfo::default(),
+                }
+            }
+            None => WasmInfo::default(),
+        };
+
+        if let Some(id) = info.id {
+            plugin.name = id.clone();
+            plugin.id = id;
+        }
+        if let Some(name) = info.name {
+            plugin.name = name;
+        }
+        if let Some(version) = info.version {
+            plugin.version = version;
+        }
+        plugin.description = info.description;
+        plugin.commands = info.commands;
+
+        let mut capabilities: Vec<Capability> = info
+            .capabilities
+            .iter()
+            .map(|name| Capability::from_str_simple(name))
+            .collect();
+        if let Some(manifest) = manifest {
+            plugin.id = manifest.plugin.id.clone();
+            plugin.name = manifest.plugin.name.clone();
+            plugin.version = manifest.plugin.version.clone();
+            if manifest.plugin.description.is_some() {
+                plugin.description = manifest.plugin.description.clone();
+            }
+            if manifest.capabilities.has_any() {
+                capabilities = Self::manifest_capabilities(manifest);
+            }
+        }
+        for capability in export_caps {
+            if !capabilities.contains(&capability) {
+                capabilities.push(capability);
+            }
+        }
+        plugin.capabilities = capabilities;
+
+        Ok(plugin)
+    }
+
+    /// Build the linker providing the `tui` host module.
+    fn linker(engine: &Engine) -> PluginResult<Linker<HostState>> {
+        let mut linker = Linker::new(engine);
+
+        linker
+            .func_wrap(
+                "tui",
+                "log",
+                |mut caller: Caller<'_, HostState>, level: i32, ptr: i32, len: i32| {
+                    let message = read_guest_string(&mut caller, ptr, len)?;
+                    let level = match level {
+                        0 => LogLevel::Debug,
+                        1 => LogLevel::Info,
+                        2 => LogLevel::Warn,
+                        _ => LogLevel::Error,
+                    };
+                    if let Some(ctx) = &caller.data().context {
+                        ctx.log(level, &message);
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|e| PluginError::Wasm(e.to_string()))?;
+
+        linker
+            .func_wrap(
+                "tui",
+                "notify",
+                |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+                    let message = read_guest_string(&mut caller, ptr, len)?;
+                    if let Some(ctx) = &caller.data().context {
+                        ctx.notify(&message);
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|e| PluginError::Wasm(e.to_string()))?;
+
+        Ok(linker)
+    }
+
+    /// Capabilities declared in a plugin manifest.
+    fn manifest_capabilities(manifest: &Manifest) -> Vec<Capability> {
+        let caps = &manifest.capabilities;
+        let mut capabilities: Vec<Capability> = caps
+            .names()
+            .iter()
+            .filter(|name| name.as_str() != "file_handler")
+            .map(|name| Capability::from_str_simple(name))
+            .collect();
+        if !caps.file_extensions.is_empty() {
+            capabilities.push(Capability::FileHandler {
+                extensions: caps.file_extensions.clone(),
+            });
+        }
+        capabilities
+    }
+
+    /// Look up a required typed export.
+    fn required_func<P, R>(
+        instance: &Instance,
+        store: &mut Store<HostState>,
+        name: &str,
+    ) -> PluginResult<TypedFunc<P, R>>
+    where
+        P: wasmtime::WasmParams,
+        R: wasmtime::WasmResults,
+    {
+        Self::optional_func(instance, store, name)?
+            .ok_or_else(|| PluginError::Wasm(format!("Missing export: {}", name)))
     }
+
+    /// Look up an optional typed export, failing if its signature is wrong.
+    fn optional_func<P, R>(
+        instance: &Instance,
+        store: &mut Store<HostState>,
+        name: &str,
+    ) -> PluginResult<Option<TypedFunc<P, R>>>
+    where
+        P: wasmtime::WasmParams,
+        R: wasmtime::WasmResults,
+    {
+        match instance.get_func(&mut *store, name) {
+            Some(func) => func
+                .typed(&*store)
+                .map(Some)
+                .map_err(|e| PluginError::Wasm(format!("{}: {}", name, e))),
+            None => Ok(None),
+        }
+    }
+
+    /// Refill fuel and reset the deadline before a guest call.
+    fn arm(store: &mut Store<HostState>, sandbox: &SandboxConfig) -> PluginResult<()> {
+        store
+            .set_fuel(sandbox.instruction_limit)
+            .map_err(|e| PluginError::Wasm(e.to_string()))?;
+        let ticks = sandbox.timeout_ms.div_ceil(EPOCH_TICK.as_millis() as u64);
+        store.set_epoch_deadline(ticks.max(1));
+        Ok(())
+    }
+
+    /// Map a wasmtime error to a plugin error.
+    fn call_error(error: wasmtime::Error, sandbox: &SandboxConfig) -> PluginError {
+        match error.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => PluginError::ResourceLimit(format!(
+                "instruction limit of {} exceeded",
+                sandbox.instruction_limit
+            )),
+            Some(Trap::Interrupt) => PluginError::Timeout,
+            _ => PluginError::Wasm(error.to_string()),
+        }
+    }
+
+    /// Copy bytes into a freshly allocated guest buffer.
+    fn write_buffer(&mut self, bytes: &[u8]) -> PluginResult<(i32, i32)> {
+        let len = i32::try_from(bytes.len())
+            .map_err(|_| PluginError::ResourceLimit("buffer too large".to_string()))?;
+        Self::arm(&mut self.store, &self.sandbox)?;
+        let ptr = self
+            .alloc
+            .call(&mut self.store, len)
+            .map_err(|e| Self::call_error(e, &self.sandbox))?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, bytes)
+            .map_err(|e| PluginError::Wasm(format!("tui_alloc returned bad pointer: {}", e)))?;
+        Ok((ptr, len))
+    }
+
+    /// Read (and free) a packed buffer returned by the guest.
+    fn take_buffer(&mut self, packed: i64) -> PluginResult<Option<Vec<u8>>> {
+        if packed == 0 {
+            return Ok(None);
+        }
+        let ptr = (packed as u64 >> 32) as u32;
+        let len = packed as u32;
+        if len as usize > MAX_BUFFER_LEN {
+            return Err(PluginError::ResourceLimit(format!(
+                "returned buffer of {} bytes",
+                len
+            )));
+        }
+
+        let mut buf = vec![0u8; len as usize];
+        self.memory
+            .read(&self.store, ptr as usize, &mut buf)
+            .map_err(|e| PluginError::Wasm(format!("returned buffer out of bounds: {}", e)))?;
+
+        if let Some(free) = self.free.clone() {
+            Self::arm(&mut self.store, &self.sandbox)?;
+            free.call(&mut self.store, (ptr as i32, len as i32))
+                .map_err(|e| Self::call_error(e, &self.sandbox))?;
+        }
+
+        Ok(Some(buf))
+    }
+}
+
+/// Read a UTF-8 string from the calling instance's memory.
+fn read_guest_string(
+    caller: &mut Caller<'_, HostState>,
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<String> {
+    let Some(Extern::Memory(memory)) = caller.get_export("memory") else {
+        return Err(wasmtime::Error::msg("missing export: memory"));
+    };
+    let start = ptr as u32 as usize;
+    let end = start + len as u32 as usize;
+    let bytes = memory
+        .data(&caller)
+        .get(start..end)
+        .ok_or_else(|| wasmtime::Error::msg("string out of bounds"))?;
+    Ok(String::from_utf8_lossy(bytes).into_owned())
 }
 
 impl Plugin for WasmPlugin {
@@ -65,6 +478,10 @@ impl Plugin for WasmPlugin {
         &self.version
     }
 
+    fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
     fn backend(&self) -> Backend {
         Backend::Wasm
     }
@@ -73,25 +490,83 @@ impl Plugin for WasmPlugin {
         &self.capabilities
     }
 
-    fn init(&mut self, _ctx: &PluginContext) -> PluginResult<()> {
-        // TODO: Call WASM init export
+    fn init(&mut self, ctx: &PluginContext) -> PluginResult<()> {
+        self.store.data_mut().context = Some(ctx.clone());
+
+        if let Some(init) = self.init.clone() {
+            let ctx_json = serde_json::to_vec(&serde_json::json!({
+                "app_name": ctx.app_name(),
+                "app_version": ctx.app_version(),
+            }))?;
+            let (ptr, len) = self.write_buffer(&ctx_json)?;
+            Self::arm(&mut self.store, &self.sandbox)?;
+            let status = init
+                .call(&mut self.store, (ptr, len))
+                .map_err(|e| Self::call_error(e, &self.sandbox))?;
+            if status != 0 {
+                return Err(PluginError::InitError(format!(
+                    "tui_init returned {}",
+                    status
+                )));
+            }
+        }
+
         self.initialized = true;
         Ok(())
     }
 
     fn shutdown(&mut self) -> PluginResult<()> {
-        // TODO: Call WASM shutdown export
+        if !self.initialized {
+            return Ok(());
+        }
         self.initialized = false;
+
+        if let Some(shutdown) = self.shutdown.clone() {
+            Self::arm(&mut self.store, &self.sandbox)?;
+            let status = shutdown
+                .call(&mut self.store, ())
+                .map_err(|e| Self::call_error(e, &self.sandbox))?;
+            if status != 0 {
+                return Err(PluginError::PluginReturned(format!(
+                    "tui_shutdown returned {}",
+                    status
+                )));
+            }
+        }
         Ok(())
     }
 
-    fn on_event(&mut self, _event: &PluginEvent) -> PluginResult<Option<PluginResponse>> {
+    fn on_event(&mut self, event: &PluginEvent) -> PluginResult<Option<PluginResponse>> {
         if !self.initialized {
             return Err(PluginError::InvalidState("Plugin not initialized".to_string()));
         }
 
-        // TODO: Call WASM on_event export
-        Ok(None)
+        let event_json = serde_json::to_vec(event)?;
+        let (ptr, len) = self.write_buffer(&event_json)?;
+        Self::arm(&mut self.store, &self.sandbox)?;
+        let packed = self
+            .on_event
+            .call(&mut self.store, (ptr, len))
+            .map_err(|e| Self::call_error(e, &self.sandbox))?;
+
+        match self.take_buffer(packed)? {
+            Some(json) => Ok(Some(serde_json::from_slice(&json)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_commands(&self) -> Vec<PluginCommand> {
+        self.commands
+            .iter()
+            .map(|cmd| PluginCommand {
+                id: format!("{}:{}", self.id, cmd.id),
+                label: cmd.label.clone().unwrap_or_else(|| cmd.id.clone()),
+                description: cmd.description.clone(),
+                keywords: Vec::new(),
+                category: Some(self.name.clone()),
+                params: Vec::new(),
+            })
+            .collect()
     }
 
     fn is_initialized(&self) -> bool {
@@ -102,11 +577,137 @@ impl Plugin for WasmPlugin {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::event::CommandEvent;
+    use crate::response::ResponseAction;
+    use std::sync::{Arc, Mutex};
+
+    /// Bump allocator plus data segments holding the info and response JSON.
+    fn plugin_wat(on_event_body: &str) -> String {
+        let info = r#"{"id":"wasm-test","name":"Wasm Test","version":"1.2.3","capabilities":["commands"],"commands":[{"id":"greet","label":"Greet"}]}"#;
+        let response = r#"{"action":{"type":"notify","message":"Hello!"},"handled":true}"#;
+        format!(
+            r#"(module
+                (import "tui" "notify" (func $notify (param i32 i32)))
+                (memory (export "memory") 1)
+                (global $heap (mut i32) (i32.const 4096))
+                (data (i32.const 0) "{info}")
+                (data (i32.const 1024) "{response}")
+                (data (i32.const 2048) "ready")
+                (func (export "tui_alloc") (param $len i32) (result i32)
+                    (local $ptr i32)
+                    (local.set $ptr (global.get $heap))
+                    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+                    (local.get $ptr))
+                (func (export "tui_info") (result i64)
+                    (i64.const {info_len}))
+                (func (export "tui_init") (param i32 i32) (result i32)
+                    (call $notify (i32.const 2048) (i32.const 5))
+                    (i32.const 0))
+                (func (export "tui_on_event") (param $ptr i32) (param $len i32) (result i64)
+                    {on_event_body})
+                (func (export "tui_capability_theming"))
+            )"#,
+            info = info.replace('"', "\\\""),
+            response = response.replace('"', "\\\""),
+            info_len = info.len(),
+            on_event_body = on_event_body
+                .replace("RESPONSE", &format!("{}", (1024i64 << 32) | response.len() as i64)),
+        )
+    }
+
+    fn test_context(notes: Arc<Mutex<Vec<String>>>) -> PluginContext {
+        let callbacks = crate::context::HostCallbacks {
+            notify: Box::new(move |msg| notes.lock().unwrap().push(msg.to_string())),
+            ..Default::default()
+        };
+        PluginContext::new(
+            "test-app",
+            "1.0.0",
+            std::path::PathBuf::from("/tmp"),
+            std::path::PathBuf::from("/tmp"),
+        )
+        .with_callbacks(callbacks)
+    }
+
+    fn load(wat: &str, sandbox: &SandboxConfig) -> PluginResult<WasmPlugin> {
+        WasmPlugin::from_bytes(wat.as_bytes(), "fallback", sandbox, None)
+    }
+
+    #[test]
+    fn test_load_and_metadata() {
+        let plugin = load(&plugin_wat("(i64.const 0)"), &SandboxConfig::default()).unwrap();
+
+        assert_eq!(plugin.id(), "wasm-test");
+        assert_eq!(plugin.name(), "Wasm Test");
+        assert_eq!(plugin.version(), "1.2.3");
+        assert_eq!(plugin.backend(), Backend::Wasm);
+        assert_eq!(
+            plugin.capabilities(),
+            &[Capability::Commands, Capability::Theming]
+        );
+        assert_eq!(plugin.get_commands()[0].id, "wasm-test:greet");
+    }
+
+    #[test]
+    fn test_event_round_trip() {
+        let mut plugin = load(&plugin_wat("(i64.const RESPONSE)"), &SandboxConfig::default()).unwrap();
+        let notes = Arc::new(Mutex::new(Vec::new()));
+
+        let event = PluginEvent::Command(CommandEvent::new("greet"));
+        assert!(plugin.on_event(&event).is_err());
+
+        plugin.init(&test_context(notes.clone())).unwrap();
+        assert_eq!(notes.lock().unwrap().as_slice(), ["ready"]);
+
+        let response = plugin.on_event(&event).unwrap().unwrap();
+        assert!(response.handled);
+        assert!(matches!(response.action, ResponseAction::Notify { ref message, .. } if message == "Hello!"));
+    }
+
+    #[test]
+    fn test_fuel_limit() {
+        let sandbox = SandboxConfig::default().with_instruction_limit(10_000);
+        let mut plugin = load(&plugin_wat("(loop $l (br $l)) (i64.const 0)"), &sandbox).unwrap();
+        plugin.init(&test_context(Arc::default())).unwrap();
+
+        let err = plugin
+            .on_event(&PluginEvent::Command(CommandEvent::new("spin")))
+            .unwrap_err();
+        assert!(matches!(err, PluginError::ResourceLimit(_)));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let sandbox = SandboxConfig::default()
+            .with_instruction_limit(u64::MAX)
+            .with_timeout(50);
+        let mut plugin = load(&plugin_wat("(loop $l (br $l)) (i64.const 0)"), &sandbox).unwrap();
+        plugin.init(&test_context(Arc::default())).unwrap();
+
+        let err = plugin
+            .on_event(&PluginEvent::Command(CommandEvent::new("spin")))
+            .unwrap_err();
+        assert!(matches!(err, PluginError::Timeout));
+    }
+
+    #[test]
+    fn test_memory_limit() {
+        let sandbox = SandboxConfig::default().with_memory_limit(128 * 1024);
+        // Growing past the limit fails instead of allocating
+        let body = "(i64.extend_i32_s (memory.grow (i32.const 16)))";
+        let mut plugin = load(&plugin_wat(body), &sandbox).unwrap();
+        plugin.init(&test_context(Arc::default())).unwrap();
+
+        let packed = plugin.on_event.call(&mut plugin.store, (0, 0)).unwrap();
+        assert_eq!(packed, -1);
+        assert!(plugin.memory.data_size(&plugin.store) <= 128 * 1024);
+    }
 
     #[test]
-    fn test_wasm_plugin_placeholder() {
-        // WASM plugin loading requires actual WASM files
-        // This is a placeholder test
-        assert_eq!(Backend::Wasm.extension(), "wasm");
+    fn test_missing_exports() {
+        let err = load("(module (memory (export \"memory\") 1))", &SandboxConfig::default())
+            .err()
+            .unwrap();
+        assert!(matches!(err, PluginError::Wasm(ref msg) if msg.contains("tui_alloc")));
     }
 }
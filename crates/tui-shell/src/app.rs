@@ -1,14 +1,21 @@
 //! App lifecycle management.
 
+use crate::bus::DataBus;
+use crate::config::CrashConfig;
 use crate::error::{ShellError, ShellResult};
-use crate::ipc::IpcChannel;
-use crate::session::AppSession;
+use crate::input;
+use crate::ipc::{self, IpcChannel, IpcListener, IpcMessage};
+use crate::pty::{self, ExitInfo, PtyProcess};
+use crate::session::{AppSession, Session};
+use crate::terminal::VirtualTerminal;
 use crate::workspace::WorkspaceId;
+use crossterm::event::Event;
 use ratatui::buffer::Buffer;
-use ratatui::layout::Rect;
+use ratatui::layout::{Position, Rect};
+use ratatui::widgets::Widget;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::process::Child;
+use std::time::{Duration, Instant};
 
 /// Unique identifier for an app instance.
 pub type AppId = u64;
@@ -66,10 +73,12 @@ pub enum LaunchMode {
         /// Plugin trait object.
         plugin: Box<dyn AppPlugin>,
     },
-    /// Subprocess.
+    /// Subprocess running on a pseudo-terminal.
     Subprocess {
-        /// Child process.
-        process: Child,
+        /// Child process and its PTY.
+        process: PtyProcess,
+        /// Terminal emulator parsing the child's output.
+        terminal: Box<VirtualTerminal>,
     },
 }
 
@@ -112,10 +121,16 @@ pub struct AppHandle {
     pub manifest: AppManifest,
     /// Launch mode.
     pub launch_mode: LaunchMode,
+    /// Launch arguments.
+    pub args: Vec<String>,
+    /// Exit status, once a subprocess app has exited.
+    pub exit: Option<ExitInfo>,
     /// Render buffer.
     pub buffer: AppBuffer,
     /// IPC channel (for subprocess apps).
     pub ipc: Option<IpcChannel>,
+    /// Socket the app connects to for IPC.
+    pub ipc_listener: Option<IpcListener>,
     /// Workspace memberships.
     pub workspaces: HashSet<WorkspaceId>,
     /// Whether this app is "sticky" (visible in all workspaces).
@@ -124,6 +139,14 @@ pub struct AppHandle {
     pub restart_count: u32,
     /// Last crash time.
     pub last_crash: Option<std::time::Instant>,
+    /// When the current process was started.
+    pub started_at: Instant,
+    /// Scheduled auto-restart after a crash.
+    pub restart_at: Option<Instant>,
+    /// Last state reported by the app (or restored from a session).
+    pub session_state: Option<serde_json::Value>,
+    /// State to hand to the app once it connects over IPC.
+    pub pending_restore: Option<serde_json::Value>,
 }
 
 /// Sandboxed render buffer for an app.
@@ -170,6 +193,45 @@ impl AppBuffer {
     }
 }
 
+impl Widget for &AppBuffer {
+    /// Blit the app's buffer into `area`, clipping to whichever is smaller.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let width = area.width.min(self.area.width);
+        let height = area.height.min(self.area.height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let src = Position::new(self.area.x + x, self.area.y + y);
+                let dst = Position::new(area.x + x, area.y + y);
+                if let (Some(cell), Some(target)) = (self.buffer.cell(src), buf.cell_mut(dst)) {
+                    *target = cell.clone();
+                }
+            }
+        }
+    }
+}
+
+/// Lifecycle events reported by [`AppManager::poll`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppEvent {
+    /// A subprocess app exited.
+    Exited {
+        /// App ID.
+        id: AppId,
+        /// Exit status.
+        exit: ExitInfo,
+        /// Delay before an automatic restart, if one is scheduled.
+        restart_in: Option<Duration>,
+    },
+    /// A crashed app was restarted.
+    Restarted {
+        /// App ID (unchanged across restarts).
+        id: AppId,
+        /// Restart attempt number, starting at 1.
+        attempt: u32,
+    },
+}
+
 /// Manages app lifecycle.
 pub struct AppManager {
     /// Running apps.
@@ -182,6 +244,10 @@ pub struct AppManager {
     next_id: AppId,
     /// App registry.
     registry: HashMap<String, AppManifest>,
+    /// Cross-app data bus.
+    bus: DataBus,
+    /// Crash handling policy.
+    crash: CrashConfig,
 }
 
 impl AppManager {
@@ -193,16 +259,32 @@ impl AppManager {
             history: Vec::new(),
             next_id: 1,
             registry: HashMap::new(),
+            bus: DataBus::new(),
+            crash: CrashConfig::default(),
         }
     }
 
+    /// Set the crash handling policy.
+    pub fn with_crash_config(mut self, crash: CrashConfig) -> Self {
+        self.crash = crash;
+        self
+    }
+
     /// Register an app manifest.
     pub fn register(&mut self, manifest: AppManifest) {
         self.registry.insert(manifest.name.clone(), manifest);
     }
 
-    /// Launch an app.
-    pub fn launch(&mut self, app_name: &str, _args: &[&str]) -> ShellResult<AppId> {
+    /// Launch an app with a default 80x24 area.
+    pub fn launch(&mut self, app_name: &str, args: &[&str]) -> ShellResult<AppId> {
+        self.launch_in(app_name, args, Rect::new(0, 0, 80, 24))
+    }
+
+    /// Launch an app sized to the given screen area.
+    ///
+    /// The app's binary is resolved next to the shell executable or on
+    /// `PATH` and started on its own pseudo-terminal.
+    pub fn launch_in(&mut self, app_name: &str, args: &[&str], area: Rect) -> ShellResult<AppId> {
         let manifest = self.registry.get(app_name).cloned().unwrap_or_else(|| {
             AppManifest {
                 name: app_name.to_string(),
@@ -212,24 +294,34 @@ impl AppManager {
         });
 
         let id = self.next_id;
+        let cols = area.width.max(1);
+        let rows = area.height.max(1);
+
+        // IPC is optional; apps still run if the socket can't be created
+        let ipc_listener = IpcListener::bind(&ipc::socket_path(id)).ok();
+        let process = spawn_app(id, &manifest, args, cols, rows, ipc_listener.as_ref())?;
         self.next_id += 1;
 
-        // For now, create a placeholder handle
-        // In a real implementation, this would spawn a subprocess or load a plugin
         let handle = AppHandle {
             id,
             manifest,
             launch_mode: LaunchMode::Subprocess {
-                process: std::process::Command::new("true")
-                    .spawn()
-                    .map_err(|e| ShellError::LaunchFailed(e.to_string()))?,
+                process,
+                terminal: Box::new(VirtualTerminal::new(cols, rows)),
             },
-            buffer: AppBuffer::new(Rect::default()),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            exit: None,
+            buffer: AppBuffer::new(area),
             ipc: None,
+            ipc_listener,
             workspaces: HashSet::new(),
             sticky: false,
             restart_count: 0,
             last_crash: None,
+            started_at: Instant::now(),
+            restart_at: None,
+            session_state: None,
+            pending_restore: None,
         };
 
         self.apps.insert(id, handle);
@@ -243,6 +335,331 @@ impl AppManager {
         Ok(id)
     }
 
+    /// Pump output from all subprocess apps into their buffers.
+    ///
+    /// Reports apps that exited since the last call and restarts crashed
+    /// apps whose backoff has elapsed. Exited apps stay registered (showing
+    /// their final screen) until killed or restarted.
+    pub fn poll(&mut self) -> Vec<AppEvent> {
+        let mut events = Vec::new();
+        let now = Instant::now();
+
+        for handle in self.apps.values_mut() {
+            let LaunchMode::Subprocess { process, terminal } = &mut handle.launch_mode else {
+                continue;
+            };
+
+            terminal.process(&process.read_available());
+            if terminal.is_dirty() {
+                terminal.render(handle.buffer.buffer_mut());
+            }
+
+            if handle.exit.is_none() {
+                if let Ok(Some(info)) = process.try_wait() {
+                    // Pick up anything written right before exit
+                    terminal.process(&process.read_available());
+                    terminal.render(handle.buffer.buffer_mut());
+                    handle.exit = Some(info);
+                    handle.ipc = None;
+
+                    let restart_in = if !info.success
+                        && (handle.manifest.auto_restart || self.crash.auto_restart_default)
+                    {
+                        let delay = restart_backoff(handle, &self.crash);
+                        handle.last_crash = Some(now);
+                        handle.restart_at = Some(now + delay);
+                        Some(delay)
+                    } else {
+                        None
+                    };
+
+                    events.push(AppEvent::Exited {
+                        id: handle.id,
+                        exit: info,
+                        restart_in,
+                    });
+                }
+            }
+        }
+
+        let due: Vec<AppId> = self
+            .apps
+            .values()
+            .filter(|h| h.restart_at.is_some_and(|at| at <= now))
+            .map(|h| h.id)
+            .collect();
+        for id in due {
+            match self.restart(id) {
+                Ok(attempt) => events.push(AppEvent::Restarted { id, attempt }),
+                Err(_) => {
+                    // Try again after the next backoff step
+                    if let Some(handle) = self.apps.get_mut(&id) {
+                        handle.restart_count += 1;
+                        handle.restart_at = Some(now + restart_backoff(handle, &self.crash));
+                    }
+                }
+            }
+        }
+
+        events
+    }
+
+    /// Respawn an exited subprocess app in place, keeping its ID.
+    fn restart(&mut self, id: AppId) -> ShellResult<u32> {
+        let handle = self
+            .apps
+            .get_mut(&id)
+            .ok_or_else(|| ShellError::AppNotFound(id.to_string()))?;
+
+        let area = handle.buffer.area();
+        let (cols, rows) = (area.width.max(1), area.height.max(1));
+        let args: Vec<&str> = handle.args.iter().map(String::as_str).collect();
+        let process = spawn_app(
+            id,
+            &handle.manifest,
+            &args,
+            cols,
+            rows,
+            handle.ipc_listener.as_ref(),
+        )?;
+
+        handle.launch_mode = LaunchMode::Subprocess {
+            process,
+            terminal: Box::new(VirtualTerminal::new(cols, rows)),
+        };
+        handle.buffer.clear();
+        handle.exit = None;
+        handle.ipc = None;
+        handle.restart_at = None;
+        handle.restart_count += 1;
+        handle.started_at = Instant::now();
+        // Hand the last known state back once the new process connects
+        handle.pending_restore = handle.session_state.clone();
+
+        Ok(handle.restart_count)
+    }
+
+    /// Accept IPC connections and route messages from apps.
+    ///
+    /// Bus traffic (`Data`, `Subscribe`, `Unsubscribe`) and pings are handled
+    /// here, and `SessionState` replies are stored on the app's handle (and
+    /// also returned, so the shell can journal them). Everything else is
+    /// returned for the shell to act on.
+    pub fn poll_ipc(&mut self) -> Vec<(AppId, IpcMessage)> {
+        let mut incoming = Vec::new();
+
+        for handle in self.apps.values_mut() {
+            if handle.ipc.is_none() && handle.exit.is_none() {
+                if let Some(listener) = &handle.ipc_listener {
+                    handle.ipc = listener.accept().ok().flatten();
+                }
+            }
+
+            let Some(channel) = handle.ipc.as_mut() else {
+                continue;
+            };
+            if let Some(state) = handle.pending_restore.take() {
+                if channel.send(IpcMessage::SessionRestore { state }).is_err() {
+                    handle.ipc = None;
+                    continue;
+                }
+            }
+            loop {
+                match channel.recv() {
+                    Ok(Some(msg)) => incoming.push((handle.id, msg)),
+                    Ok(None) => break,
+                    Err(_) => {
+                        // Disconnected; the app may reconnect later
+                        handle.ipc = None;
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut unhandled = Vec::new();
+        for (id, msg) in incoming {
+            match msg {
+                IpcMessage::Data { key, value } => {
+                    match self.bus.publish(Some(id), &key, value.clone()) {
+                        Ok(targets) => {
+                            for target in targets {
+                                self.send_ipc(
+                                    target,
+                                    IpcMessage::Data {
+                                        key: key.clone(),
+                                        value: value.clone(),
+                                    },
+                                );
+                            }
+                        }
+                        Err(e) => self.send_ipc(
+                            id,
+                            IpcMessage::Error {
+                                message: e.to_string(),
+                            },
+                        ),
+                    }
+                }
+                IpcMessage::Subscribe { topic } => {
+                    for retained in self.bus.subscribe(id, &topic) {
+                        self.send_ipc(id, retained);
+                    }
+                }
+                IpcMessage::Unsubscribe { topic } => self.bus.unsubscribe(id, &topic),
+                IpcMessage::Ping => self.send_ipc(id, IpcMessage::Pong),
+                IpcMessage::SessionState { state } => {
+                    if let Some(handle) = self.apps.get_mut(&id) {
+                        handle.session_state = Some(state.clone());
+                    }
+                    unhandled.push((id, IpcMessage::SessionState { state }));
+                }
+                other => unhandled.push((id, other)),
+            }
+        }
+
+        unhandled
+    }
+
+    /// Send a message to an app's IPC channel, dropping it if not connected.
+    fn send_ipc(&mut self, id: AppId, msg: IpcMessage) {
+        if let Some(handle) = self.apps.get_mut(&id) {
+            if let Some(channel) = handle.ipc.as_mut() {
+                if channel.send(msg).is_err() {
+                    handle.ipc = None;
+                }
+            }
+        }
+    }
+
+    /// Ask every connected subprocess app to report its session state.
+    ///
+    /// Replies arrive as `SessionState` messages through [`Self::poll_ipc`].
+    pub fn request_session_save(&mut self) {
+        let ids: Vec<AppId> = self.apps.keys().copied().collect();
+        for id in ids {
+            self.send_ipc(id, IpcMessage::SessionSave);
+        }
+    }
+
+    /// Publish a payload from the shell itself to subscribed apps.
+    pub fn publish(&mut self, topic: &str, value: serde_json::Value) -> ShellResult<()> {
+        for target in self.bus.publish(None, topic, value.clone())? {
+            self.send_ipc(
+                target,
+                IpcMessage::Data {
+                    key: topic.to_string(),
+                    value: value.clone(),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Get the data bus.
+    pub fn bus(&self) -> &DataBus {
+        &self.bus
+    }
+
+    /// Get the data bus mutably (e.g. to register schemas).
+    pub fn bus_mut(&mut self) -> &mut DataBus {
+        &mut self.bus
+    }
+
+    /// Move or resize an app's screen area.
+    pub fn resize_app(&mut self, id: AppId, area: Rect) -> ShellResult<()> {
+        let handle = self
+            .apps
+            .get_mut(&id)
+            .ok_or_else(|| ShellError::AppNotFound(id.to_string()))?;
+
+        if handle.buffer.area() == area {
+            return Ok(());
+        }
+        handle.buffer.resize(area);
+
+        if let LaunchMode::Subprocess { process, terminal } = &mut handle.launch_mode {
+            terminal.resize(area.width, area.height);
+            terminal.render(handle.buffer.buffer_mut());
+            if handle.exit.is_none() {
+                process.resize(area.width, area.height)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Forward a terminal event to an app.
+    ///
+    /// Mouse coordinates are absolute and are translated into the app's
+    /// area; mouse events outside it are dropped. Returns whether the
+    /// event was delivered.
+    pub fn send_event(&mut self, id: AppId, event: &Event) -> ShellResult<bool> {
+        let handle = self
+            .apps
+            .get_mut(&id)
+            .ok_or_else(|| ShellError::AppNotFound(id.to_string()))?;
+        let area = handle.buffer.area();
+
+        match &mut handle.launch_mode {
+            LaunchMode::InProcess { plugin } => Ok(plugin.handle_input(event.clone())),
+            LaunchMode::Subprocess { process, terminal } => {
+                if handle.exit.is_some() {
+                    return Ok(false);
+                }
+
+                let modes = terminal.input_modes();
+                let bytes = match event {
+                    Event::Key(key) => input::encode_key(key, &modes),
+                    Event::Mouse(mouse) => {
+                        if !area.contains(Position::new(mouse.column, mouse.row)) {
+                            return Ok(false);
+                        }
+                        let mut local = *mouse;
+                        local.column -= area.x;
+                        local.row -= area.y;
+                        input::encode_mouse(&local, &modes)
+                    }
+                    Event::Paste(text) => Some(input::encode_paste(text, &modes)),
+                    _ => None,
+                };
+
+                match bytes {
+                    Some(bytes) => {
+                        process.write(&bytes)?;
+                        Ok(true)
+                    }
+                    None => Ok(false),
+                }
+            }
+        }
+    }
+
+    /// Absolute cursor position for an app, if it shows one.
+    pub fn cursor(&self, id: AppId) -> Option<(u16, u16)> {
+        let handle = self.apps.get(&id)?;
+        match &handle.launch_mode {
+            LaunchMode::Subprocess { terminal, .. } if handle.exit.is_none() => {
+                let area = handle.buffer.area();
+                terminal
+                    .cursor()
+                    .filter(|&(x, y)| x < area.width && y < area.height)
+                    .map(|(x, y)| (area.x + x, area.y + y))
+            }
+            _ => None,
+        }
+    }
+
+    /// Window title set by an app, if any.
+    pub fn title(&self, id: AppId) -> Option<&str> {
+        match &self.apps.get(&id)?.launch_mode {
+            LaunchMode::Subprocess { terminal, .. } => {
+                Some(terminal.title()).filter(|t| !t.is_empty())
+            }
+            LaunchMode::InProcess { .. } => None,
+        }
+    }
+
     /// Suspend an app.
     pub fn suspend(&mut self, id: AppId) -> ShellResult<()> {
         let _handle = self
@@ -277,11 +694,15 @@ impl AppManager {
             LaunchMode::InProcess { plugin } => {
                 plugin.shutdown()?;
             }
-            LaunchMode::Subprocess { process } => {
-                let _ = process.kill();
+            LaunchMode::Subprocess { process, .. } => {
+                if handle.exit.is_none() {
+                    let _ = process.kill();
+                }
             }
         }
 
+        self.bus.remove_app(id);
+
         // Update focus
         if self.focused == Some(id) {
             self.focused = self.history.iter().rev().find(|&&h| h != id).copied();
@@ -371,12 +792,13 @@ impl AppManager {
         for handle in self.apps.values() {
             let state = match &handle.launch_mode {
                 LaunchMode::InProcess { plugin } => plugin.save_state(),
-                LaunchMode::Subprocess { .. } => None,
+                LaunchMode::Subprocess { .. } => handle.session_state.clone(),
             };
 
             sessions.push(AppSession {
+                id: handle.id,
                 app_name: handle.manifest.name.clone(),
-                args: Vec::new(),
+                args: handle.args.clone(),
                 state,
                 workspace_memberships: handle.workspaces.iter().copied().collect(),
             });
@@ -385,9 +807,10 @@ impl AppManager {
         Ok(sessions)
     }
 
-    /// Restore an app from session.
-    pub fn restore_app(&mut self, session: &AppSession) -> ShellResult<AppId> {
-        let id = self.launch(&session.app_name, &[])?;
+    /// Restore an app from session into the given pane area.
+    pub fn restore_app(&mut self, session: &AppSession, area: Rect) -> ShellResult<AppId> {
+        let args: Vec<&str> = session.args.iter().map(String::as_str).collect();
+        let id = self.launch_in(&session.app_name, &args, area)?;
 
         // Restore workspace memberships
         if let Some(handle) = self.apps.get_mut(&id) {
@@ -395,10 +818,12 @@ impl AppManager {
                 handle.workspaces.insert(*ws);
             }
 
-            // Restore state if supported
+            // Restore state if supported; subprocess apps get it over IPC
             if let Some(state) = &session.state {
-                if let LaunchMode::InProcess { plugin } = &mut handle.launch_mode {
-                    plugin.restore_state(state.clone())?;
+                handle.session_state = Some(state.clone());
+                match &mut handle.launch_mode {
+                    LaunchMode::InProcess { plugin } => plugin.restore_state(state.clone())?,
+                    LaunchMode::Subprocess { .. } => handle.pending_restore = Some(state.clone()),
                 }
             }
         }
@@ -406,6 +831,36 @@ impl AppManager {
         Ok(id)
     }
 
+    /// Relaunch every app in a session.
+    ///
+    /// `areas` holds each app's saved pane area by recorded ID; apps without
+    /// one start at 80x24. Returns each app's recorded ID alongside the
+    /// result of relaunching it, so callers can remap layouts onto the new IDs.
+    pub fn restore_session(
+        &mut self,
+        session: &Session,
+        areas: &HashMap<AppId, Rect>,
+    ) -> Vec<(AppId, ShellResult<AppId>)> {
+        let restored: Vec<_> = session
+            .apps
+            .iter()
+            .map(|app| {
+                let area = areas.get(&app.id).copied().unwrap_or(Rect::new(0, 0, 80, 24));
+                (app.id, self.restore_app(app, area))
+            })
+            .collect();
+
+        let focused = session
+            .focused
+            .and_then(|old| restored.iter().find(|(id, _)| *id == old))
+            .and_then(|(_, result)| result.as_ref().ok().copied());
+        if let Some(id) = focused {
+            let _ = self.focus(id);
+        }
+
+        restored
+    }
+
     /// Shutdown all apps.
     pub fn shutdown_all(&mut self) -> Vec<ShellResult<()>> {
         let ids: Vec<_> = self.apps.keys().copied().collect();
@@ -423,6 +878,43 @@ impl AppManager {
     }
 }
 
+/// Spawn an app's binary on a PTY, wiring up its IPC socket.
+fn spawn_app(
+    id: AppId,
+    manifest: &AppManifest,
+    args: &[&str],
+    cols: u16,
+    rows: u16,
+    listener: Option<&IpcListener>,
+) -> ShellResult<PtyProcess> {
+    let program = pty::resolve_binary(&manifest.name).ok_or_else(|| {
+        ShellError::LaunchFailed(format!("{}: executable not found", manifest.name))
+    })?;
+
+    let mut env = vec![(pty::APP_ID_ENV, id.to_string())];
+    if let Some(listener) = listener {
+        env.push((ipc::SOCKET_ENV, listener.path().display().to_string()));
+    }
+
+    PtyProcess::spawn(&program, args, cols, rows, &env)
+}
+
+/// Exponential backoff for the next restart of a crashed app.
+///
+/// Starts at the manifest's `restart_backoff_ms`, doubles per restart and is
+/// capped at `backoff_max_ms`. An app that stayed up for longer than the cap
+/// starts over from the initial delay.
+fn restart_backoff(handle: &mut AppHandle, crash: &CrashConfig) -> Duration {
+    let max = Duration::from_millis(crash.backoff_max_ms);
+    if handle.started_at.elapsed() >= max {
+        handle.restart_count = 0;
+    }
+
+    let initial = handle.manifest.restart_backoff_ms;
+    let factor = 1u64.checked_shl(handle.restart_count).unwrap_or(u64::MAX);
+    Duration::from_millis(initial.saturating_mul(factor)).min(max)
+}
+
 impl Default for AppManager {
     fn default() -> Self {
         Self::new()
@@ -447,6 +939,203 @@ mod tests {
         assert_eq!(buffer.area(), area);
     }
 
+    #[test]
+    fn test_launch_subprocess() {
+        let mut manager = AppManager::new();
+        let area = Rect::new(2, 1, 20, 4);
+        let id = manager.launch_in("sh", &["-c", "printf 'hello shell'"], area).unwrap();
+        assert_eq!(manager.focused(), Some(id));
+
+        let row_text = |manager: &AppManager| -> String {
+            let buffer = manager.get(id).unwrap().buffer.buffer();
+            (0..11).map(|x| buffer[(area.x + x, area.y)].symbol()).collect()
+        };
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let mut exited = Vec::new();
+        while std::time::Instant::now() < deadline
+            && (exited.is_empty() || row_text(&manager) != "hello shell")
+        {
+            exited.extend(manager.poll());
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(exited.len(), 1);
+        assert!(matches!(exited[0], AppEvent::Exited { exit, restart_in: None, .. } if exit.success));
+        assert_eq!(row_text(&manager), "hello shell");
+    }
+
+    #[test]
+    fn test_data_bus_routing() {
+        let mut manager = AppManager::new();
+        let publisher = manager.launch("sh", &["-c", "sleep 5"]).unwrap();
+        let subscriber = manager.launch("sh", &["-c", "sleep 5"]).unwrap();
+
+        let connect = |manager: &AppManager, id: AppId| {
+            let path = manager.get(id).unwrap().ipc_listener.as_ref().unwrap().path().to_path_buf();
+            IpcChannel::connect(&path).unwrap()
+        };
+        let mut pub_client = connect(&manager, publisher);
+        let mut sub_client = connect(&manager, subscriber);
+
+        let pump = |manager: &mut AppManager, client: &mut IpcChannel| -> Option<IpcMessage> {
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+            while std::time::Instant::now() < deadline {
+                manager.poll_ipc();
+                if let Some(msg) = client.recv().unwrap() {
+                    return Some(msg);
+                }
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+            None
+        };
+
+        sub_client.send(IpcMessage::Subscribe { topic: "file.*".to_string() }).unwrap();
+        sub_client.send(IpcMessage::Ping).unwrap();
+        assert!(matches!(pump(&mut manager, &mut sub_client), Some(IpcMessage::Pong)));
+
+        let selected = crate::bus::FileSelected { path: "/tmp/notes.md".into() };
+        pub_client.send(crate::bus::Payload::to_message(&selected).unwrap()).unwrap();
+        let delivered = pump(&mut manager, &mut sub_client).unwrap();
+        assert_eq!(
+            <crate::bus::FileSelected as crate::bus::Payload>::from_message(&delivered),
+            Some(selected)
+        );
+
+        // Invalid payloads bounce back to the publisher
+        pub_client
+            .send(IpcMessage::Data { key: "file.selected".to_string(), value: serde_json::json!({}) })
+            .unwrap();
+        assert!(matches!(pump(&mut manager, &mut pub_client), Some(IpcMessage::Error { .. })));
+
+        manager.shutdown_all();
+    }
+
+    #[test]
+    fn test_crash_restart_with_backoff() {
+        let mut manager = AppManager::new().with_crash_config(CrashConfig {
+            auto_restart_default: true,
+            backoff_max_ms: 200,
+            ..Default::default()
+        });
+        manager.register(AppManifest {
+            name: "sh".to_string(),
+            restart_backoff_ms: 20,
+            ..Default::default()
+        });
+        let id = manager.launch("sh", &["-c", "exit 3"]).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut events = Vec::new();
+        while Instant::now() < deadline
+            && events.iter().filter(|e| matches!(e, AppEvent::Restarted { .. })).count() < 2
+        {
+            events.extend(manager.poll());
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        let delays: Vec<Duration> = events
+            .iter()
+            .filter_map(|e| match e {
+                AppEvent::Exited { restart_in, .. } => *restart_in,
+                _ => None,
+            })
+            .collect();
+        assert_eq!(delays[..2], [Duration::from_millis(20), Duration::from_millis(40)]);
+        assert!(events.contains(&AppEvent::Restarted { id, attempt: 2 }));
+        assert_eq!(manager.count(), 1);
+
+        manager.shutdown_all();
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let mut manager = AppManager::new();
+        manager.register(AppManifest {
+            name: "sh".to_string(),
+            restart_backoff_ms: 1000,
+            ..Default::default()
+        });
+        let id = manager.launch("sh", &["-c", "sleep 5"]).unwrap();
+        let crash = CrashConfig::default();
+
+        let handle = manager.get_mut(id).unwrap();
+        handle.restart_count = 3;
+        assert_eq!(restart_backoff(handle, &crash), Duration::from_millis(8000));
+        handle.restart_count = 40;
+        assert_eq!(restart_backoff(handle, &crash), Duration::from_millis(30000));
+
+        manager.shutdown_all();
+    }
+
+    #[test]
+    fn test_session_state_round_trip() {
+        let mut manager = AppManager::new();
+        let session = AppSession::new("sh")
+            .with_id(7)
+            .with_args(vec!["-c".to_string(), "sleep 5".to_string()])
+            .with_state(serde_json::json!({ "cursor": 12 }));
+        let area = Rect::new(0, 0, 120, 40);
+        let id = manager.restore_app(&session, area).unwrap();
+        assert_eq!(manager.get(id).unwrap().buffer.area(), area);
+
+        let path = manager.get(id).unwrap().ipc_listener.as_ref().unwrap().path().to_path_buf();
+        let mut client = IpcChannel::connect(&path).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut restored = None;
+        while restored.is_none() && Instant::now() < deadline {
+            manager.poll_ipc();
+            restored = client.recv().unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(matches!(
+            restored,
+            Some(IpcMessage::SessionRestore { ref state }) if state["cursor"] == 12
+        ));
+
+        manager.request_session_save();
+        let mut asked = None;
+        while asked.is_none() && Instant::now() < deadline {
+            asked = client.recv().unwrap();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(matches!(asked, Some(IpcMessage::SessionSave)));
+
+        client
+            .send(IpcMessage::SessionState { state: serde_json::json!({ "cursor": 40 }) })
+            .unwrap();
+        let mut reported = Vec::new();
+        while reported.is_empty() && Instant::now() < deadline {
+            reported = manager.poll_ipc();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(matches!(reported[0], (app, IpcMessage::SessionState { .. }) if app == id));
+
+        let saved = manager.save_sessions().unwrap();
+        assert_eq!(saved[0].args, session.args);
+        assert_eq!(saved[0].state, Some(serde_json::json!({ "cursor": 40 })));
+
+        manager.shutdown_all();
+    }
+
+    #[test]
+    fn test_launch_missing_binary() {
+        let mut manager = AppManager::new();
+        let result = manager.launch("definitely-not-a-real-app", &[]);
+        assert!(matches!(result, Err(ShellError::LaunchFailed(_))));
+        assert_eq!(manager.count(), 0);
+    }
+
+    #[test]
+    fn test_app_buffer_widget() {
+        let mut app_buffer = AppBuffer::new(Rect::new(5, 5, 3, 1));
+        app_buffer.buffer_mut()[(5, 5)].set_symbol("x");
+
+        let mut target = Buffer::empty(Rect::new(0, 0, 10, 10));
+        (&app_buffer).render(Rect::new(1, 1, 3, 1), &mut target);
+        assert_eq!(target[(1, 1)].symbol(), "x");
+    }
+
     #[test]
     fn test_manifest_default() {
         let manifest = AppManifest::default();
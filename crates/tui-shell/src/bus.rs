@@ -0,0 +1,470 @@
+//! Cross-app data bus brokered by the shell.
+//!
+//! Apps publish payloads on a topic with [`IpcMessage::Data`] and receive
+//! other apps' payloads after sending [`IpcMessage::Subscribe`]. Payloads for
+//! well-known topics are checked against the [`SchemaRegistry`] before they
+//! are delivered.
+
+use crate::app::AppId;
+use crate::error::{ShellError, ShellResult};
+use crate::ipc::IpcMessage;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+
+/// A typed payload for a well-known topic.
+pub trait Payload: Serialize + DeserializeOwned {
+    /// Topic the payload is published on.
+    const TOPIC: &'static str;
+
+    /// Wrap the payload in a data message.
+    fn to_message(&self) -> ShellResult<IpcMessage> {
+        Ok(IpcMessage::Data {
+            key: Self::TOPIC.to_string(),
+            value: serde_json::to_value(self)?,
+        })
+    }
+
+    /// Decode the payload from a data message, if it is on this topic.
+    fn from_message(msg: &IpcMessage) -> Option<Self> {
+        match msg {
+            IpcMessage::Data { key, value } if key == Self::TOPIC => {
+                serde_json::from_value(value.clone()).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A file was selected (file-manager, diff-tool, hex-editor).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileSelected {
+    /// Path of the selected file.
+    pub path: PathBuf,
+}
+
+impl Payload for FileSelected {
+    const TOPIC: &'static str = "file.selected";
+}
+
+/// A git commit was selected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitCommit {
+    /// Repository root.
+    pub repo: PathBuf,
+    /// Full commit hash.
+    pub hash: String,
+    /// First line of the commit message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+impl Payload for GitCommit {
+    const TOPIC: &'static str = "git.commit";
+}
+
+/// A container was selected (docker-manager).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerSelected {
+    /// Container ID.
+    pub id: String,
+    /// Container name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Image the container runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+impl Payload for ContainerSelected {
+    const TOPIC: &'static str = "container.selected";
+}
+
+/// A log line was emitted or selected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogLine {
+    /// Where the line came from (file path, container, unit).
+    pub source: String,
+    /// The line itself.
+    pub line: String,
+    /// Severity, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+}
+
+impl Payload for LogLine {
+    const TOPIC: &'static str = "log.line";
+}
+
+/// JSON type of a payload field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    /// JSON string.
+    String,
+    /// JSON integer.
+    Integer,
+    /// Any JSON number.
+    Number,
+    /// JSON boolean.
+    Bool,
+    /// Any JSON value.
+    Any,
+}
+
+impl FieldType {
+    /// Check whether a value has this type.
+    pub fn matches(&self, value: &serde_json::Value) -> bool {
+        match self {
+            Self::String => value.is_string(),
+            Self::Integer => value.is_i64() || value.is_u64(),
+            Self::Number => value.is_number(),
+            Self::Bool => value.is_boolean(),
+            Self::Any => true,
+        }
+    }
+}
+
+/// A field in a payload schema.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldSchema {
+    /// Field name.
+    pub name: String,
+    /// Expected type.
+    pub field_type: FieldType,
+    /// Whether the field must be present.
+    pub required: bool,
+}
+
+impl FieldSchema {
+    /// A required field.
+    pub fn required(name: impl Into<String>, field_type: FieldType) -> Self {
+        Self {
+            name: name.into(),
+            field_type,
+            required: true,
+        }
+    }
+
+    /// An optional field.
+    pub fn optional(name: impl Into<String>, field_type: FieldType) -> Self {
+        Self {
+            name: name.into(),
+            field_type,
+            required: false,
+        }
+    }
+}
+
+/// Schema for the payload of one topic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayloadSchema {
+    /// Topic name.
+    pub topic: String,
+    /// What the payload means.
+    pub description: String,
+    /// Known fields (extra fields are allowed).
+    pub fields: Vec<FieldSchema>,
+}
+
+impl PayloadSchema {
+    /// Create a schema.
+    pub fn new(topic: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            topic: topic.into(),
+            description: description.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Add a field.
+    pub fn field(mut self, field: FieldSchema) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Check a payload against this schema.
+    pub fn validate(&self, value: &serde_json::Value) -> Result<(), String> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| "payload must be an object".to_string())?;
+
+        for field in &self.fields {
+            match object.get(&field.name) {
+                Some(serde_json::Value::Null) | None if field.required => {
+                    return Err(format!("missing field `{}`", field.name));
+                }
+                Some(serde_json::Value::Null) | None => {}
+                Some(v) if !field.field_type.matches(v) => {
+                    return Err(format!(
+                        "field `{}` should be {:?}",
+                        field.name, field.field_type
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Registry of well-known payload schemas.
+#[derive(Debug, Clone)]
+pub struct SchemaRegistry {
+    /// Schemas by topic.
+    schemas: BTreeMap<String, PayloadSchema>,
+}
+
+impl SchemaRegistry {
+    /// Create an empty registry.
+    pub fn empty() -> Self {
+        Self {
+            schemas: BTreeMap::new(),
+        }
+    }
+
+    /// Create a registry with the suite's well-known topics.
+    pub fn new() -> Self {
+        let mut registry = Self::empty();
+        registry.register(
+            PayloadSchema::new(FileSelected::TOPIC, "A file was selected")
+                .field(FieldSchema::required("path", FieldType::String)),
+        );
+        registry.register(
+            PayloadSchema::new(GitCommit::TOPIC, "A git commit was selected")
+                .field(FieldSchema::required("repo", FieldType::String))
+                .field(FieldSchema::required("hash", FieldType::String))
+                .field(FieldSchema::optional("summary", FieldType::String)),
+        );
+        registry.register(
+            PayloadSchema::new(ContainerSelected::TOPIC, "A container was selected")
+                .field(FieldSchema::required("id", FieldType::String))
+                .field(FieldSchema::optional("name", FieldType::String))
+                .field(FieldSchema::optional("image", FieldType::String)),
+        );
+        registry.register(
+            PayloadSchema::new(LogLine::TOPIC, "A log line was emitted or selected")
+                .field(FieldSchema::required("source", FieldType::String))
+                .field(FieldSchema::required("line", FieldType::String))
+                .field(FieldSchema::optional("level", FieldType::String)),
+        );
+        registry
+    }
+
+    /// Register (or replace) a schema.
+    pub fn register(&mut self, schema: PayloadSchema) {
+        self.schemas.insert(schema.topic.clone(), schema);
+    }
+
+    /// Get the schema for a topic.
+    pub fn get(&self, topic: &str) -> Option<&PayloadSchema> {
+        self.schemas.get(topic)
+    }
+
+    /// All registered schemas, sorted by topic.
+    pub fn schemas(&self) -> impl Iterator<Item = &PayloadSchema> {
+        self.schemas.values()
+    }
+
+    /// Validate a payload. Topics without a schema are accepted as-is.
+    pub fn validate(&self, topic: &str, value: &serde_json::Value) -> ShellResult<()> {
+        match self.schemas.get(topic) {
+            Some(schema) => schema
+                .validate(value)
+                .map_err(|e| ShellError::Ipc(format!("invalid `{}` payload: {}", topic, e))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for SchemaRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Check whether a subscription pattern matches a topic.
+///
+/// Patterns are exact topics, `*` for everything, or `prefix.*` for a
+/// topic family.
+pub fn topic_matches(pattern: &str, topic: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    match pattern.strip_suffix(".*") {
+        Some(prefix) => topic
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('.')),
+        None => pattern == topic,
+    }
+}
+
+/// Topic-based publish/subscribe broker.
+#[derive(Debug, Default)]
+pub struct DataBus {
+    /// Payload schemas.
+    registry: SchemaRegistry,
+    /// Subscription patterns per app.
+    subscriptions: HashMap<AppId, HashSet<String>>,
+    /// Last payload published on each topic.
+    retained: BTreeMap<String, serde_json::Value>,
+}
+
+impl DataBus {
+    /// Create a bus with the well-known schemas.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a bus with a custom registry.
+    pub fn with_registry(registry: SchemaRegistry) -> Self {
+        Self {
+            registry,
+            ..Default::default()
+        }
+    }
+
+    /// Get the schema registry.
+    pub fn registry(&self) -> &SchemaRegistry {
+        &self.registry
+    }
+
+    /// Get the schema registry mutably.
+    pub fn registry_mut(&mut self) -> &mut SchemaRegistry {
+        &mut self.registry
+    }
+
+    /// Subscribe an app to a topic pattern.
+    ///
+    /// Returns the retained payloads the pattern matches, so a late
+    /// subscriber starts from the current selection.
+    pub fn subscribe(&mut self, app: AppId, pattern: &str) -> Vec<IpcMessage> {
+        self.subscriptions
+            .entry(app)
+            .or_default()
+            .insert(pattern.to_string());
+
+        self.retained
+            .iter()
+            .filter(|(topic, _)| topic_matches(pattern, topic))
+            .map(|(topic, value)| IpcMessage::Data {
+                key: topic.clone(),
+                value: value.clone(),
+            })
+            .collect()
+    }
+
+    /// Remove a subscription.
+    pub fn unsubscribe(&mut self, app: AppId, pattern: &str) {
+        if let Some(patterns) = self.subscriptions.get_mut(&app) {
+            patterns.remove(pattern);
+            if patterns.is_empty() {
+                self.subscriptions.remove(&app);
+            }
+        }
+    }
+
+    /// Drop all subscriptions of an app.
+    pub fn remove_app(&mut self, app: AppId) {
+        self.subscriptions.remove(&app);
+    }
+
+    /// Check whether an app is subscribed to a topic.
+    pub fn is_subscribed(&self, app: AppId, topic: &str) -> bool {
+        self.subscriptions
+            .get(&app)
+            .is_some_and(|patterns| patterns.iter().any(|p| topic_matches(p, topic)))
+    }
+
+    /// Publish a payload and return the subscribers to deliver it to.
+    ///
+    /// The publisher itself is never included. Fails without delivering if
+    /// the payload does not match the topic's schema.
+    pub fn publish(
+        &mut self,
+        from: Option<AppId>,
+        topic: &str,
+        value: serde_json::Value,
+    ) -> ShellResult<Vec<AppId>> {
+        self.registry.validate(topic, &value)?;
+
+        let mut targets: Vec<AppId> = self
+            .subscriptions
+            .keys()
+            .copied()
+            .filter(|&app| Some(app) != from && self.is_subscribed(app, topic))
+            .collect();
+        targets.sort_unstable();
+
+        self.retained.insert(topic.to_string(), value);
+        Ok(targets)
+    }
+
+    /// Last payload published on a topic.
+    pub fn last(&self, topic: &str) -> Option<&serde_json::Value> {
+        self.retained.get(topic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_matches() {
+        assert!(topic_matches("*", "file.selected"));
+        assert!(topic_matches("file.selected", "file.selected"));
+        assert!(topic_matches("file.*", "file.selected"));
+        assert!(!topic_matches("file.*", "filesystem.changed"));
+        assert!(!topic_matches("git.commit", "git.branch"));
+    }
+
+    #[test]
+    fn test_schema_validation() {
+        let registry = SchemaRegistry::new();
+        assert!(registry
+            .validate("file.selected", &serde_json::json!({ "path": "/tmp/a" }))
+            .is_ok());
+        assert!(registry.validate("file.selected", &serde_json::json!({})).is_err());
+        assert!(registry
+            .validate("container.selected", &serde_json::json!({ "id": 42 }))
+            .is_err());
+        // Unknown topics are passed through
+        assert!(registry.validate("custom.thing", &serde_json::json!([1, 2])).is_ok());
+    }
+
+    #[test]
+    fn test_publish_subscribe() {
+        let mut bus = DataBus::new();
+        assert!(bus.subscribe(2, "container.*").is_empty());
+        bus.subscribe(3, "*");
+
+        let payload = ContainerSelected {
+            id: "abc123".to_string(),
+            name: Some("web".to_string()),
+            image: None,
+        };
+        let value = serde_json::to_value(&payload).unwrap();
+        assert_eq!(bus.publish(Some(1), ContainerSelected::TOPIC, value.clone()).unwrap(), vec![2, 3]);
+        assert_eq!(bus.publish(Some(3), ContainerSelected::TOPIC, value).unwrap(), vec![2]);
+
+        // Late subscribers get the retained payload
+        let retained = bus.subscribe(4, ContainerSelected::TOPIC);
+        assert_eq!(retained.len(), 1);
+        assert_eq!(ContainerSelected::from_message(&retained[0]), Some(payload));
+
+        bus.remove_app(2);
+        assert!(!bus.is_subscribed(2, ContainerSelected::TOPIC));
+        assert!(bus.publish(Some(1), FileSelected::TOPIC, serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn test_payload_message() {
+        let file = FileSelected { path: PathBuf::from("/etc/hosts") };
+        let msg = file.to_message().unwrap();
+        assert!(matches!(&msg, IpcMessage::Data { key, .. } if key == "file.selected"));
+        assert_eq!(FileSelected::from_message(&msg), Some(file));
+        assert_eq!(LogLine::from_message(&msg), None);
+    }
+}
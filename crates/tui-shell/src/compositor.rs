@@ -101,6 +101,25 @@ impl LayoutNode {
         }
     }
 
+    /// Rewrite app IDs through `ids`, dropping leaves with no mapping.
+    pub fn remap(&self, ids: &HashMap<AppId, AppId>) -> Option<LayoutNode> {
+        match self {
+            Self::Leaf { app_id } => ids.get(app_id).map(|&id| Self::leaf(id)),
+            Self::Split { direction, ratio, first, second } => {
+                match (first.remap(ids), second.remap(ids)) {
+                    (None, None) => None,
+                    (Some(node), None) | (None, Some(node)) => Some(node),
+                    (Some(f), Some(s)) => Some(Self::Split {
+                        direction: *direction,
+                        ratio: *ratio,
+                        first: Box::new(f),
+                        second: Box::new(s),
+                    }),
+                }
+            }
+        }
+    }
+
     /// Calculate rectangles for all apps.
     pub fn layout(&self, area: Rect) -> HashMap<AppId, Rect> {
         let mut result = HashMap::new();
@@ -230,6 +249,40 @@ impl LayoutState {
 
         apps
     }
+
+    /// Rewrite app IDs through `ids`, e.g. after apps were relaunched.
+    ///
+    /// Windows, tabs and leaves of apps with no mapping are dropped.
+    pub fn remap(&self, ids: &HashMap<AppId, AppId>) -> LayoutState {
+        let floating = self
+            .floating
+            .iter()
+            .filter_map(|win| {
+                ids.get(&win.app_id).map(|&app_id| FloatingWindow { app_id, ..win.clone() })
+            })
+            .collect();
+
+        let active = self.tabs.get(self.active_tab).map(|t| t.app_id);
+        let tabs: Vec<Tab> = self
+            .tabs
+            .iter()
+            .filter_map(|tab| {
+                ids.get(&tab.app_id).map(|&app_id| Tab { app_id, title: tab.title.clone() })
+            })
+            .collect();
+        let active_tab = active
+            .and_then(|old| ids.get(&old))
+            .and_then(|new| tabs.iter().position(|t| t.app_id == *new))
+            .unwrap_or_else(|| self.active_tab.min(tabs.len().saturating_sub(1)));
+
+        LayoutState {
+            tiled: self.tiled.as_ref().and_then(|node| node.remap(ids)),
+            floating,
+            tabs,
+            active_tab,
+            fullscreen: self.fullscreen.and_then(|id| ids.get(&id).copied()),
+        }
+    }
 }
 
 /// Compositor for managing app layout and rendering.
@@ -630,6 +683,28 @@ mod tests {
         assert_eq!(r1.x + r1.width, r2.x);
     }
 
+    #[test]
+    fn test_layout_state_remap() {
+        let state = LayoutState {
+            tiled: Some(LayoutNode::vsplit(LayoutNode::leaf(1), LayoutNode::leaf(2), 0.5)),
+            floating: vec![FloatingWindow::new(3, 1, 1, 20, 10)],
+            tabs: vec![
+                Tab { app_id: 4, title: "a".into() },
+                Tab { app_id: 5, title: "b".into() },
+            ],
+            active_tab: 1,
+            fullscreen: Some(2),
+        };
+        let ids = HashMap::from([(1, 11), (3, 13), (5, 15)]);
+
+        let remapped = state.remap(&ids);
+        assert_eq!(remapped.tiled.map(|n| n.app_ids()), Some(vec![11]));
+        assert_eq!(remapped.floating[0].app_id, 13);
+        assert_eq!(remapped.tabs.len(), 1);
+        assert_eq!(remapped.tabs[remapped.active_tab].app_id, 15);
+        assert_eq!(remapped.fullscreen, None);
+    }
+
     #[test]
     fn test_compositor_tiled() {
         let mut comp = Compositor::new(100, 50);
@@ -109,6 +109,9 @@ pub struct SessionConfig {
     /// Whether to restore session on start.
     #[serde(default = "default_true")]
     pub restore_on_start: bool,
+    /// Session file path (defaults to the shell's data directory).
+    #[serde(default)]
+    pub path: Option<PathBuf>,
 }
 
 fn default_true() -> bool {
@@ -126,6 +129,7 @@ impl Default for SessionConfig {
             auto_save: true,
             save_interval_secs: 300,
             restore_on_start: true,
+            path: None,
         }
     }
 }
@@ -0,0 +1,287 @@
+//! Translation of terminal input events into bytes for subprocess apps.
+
+use crate::terminal::{InputModes, MouseEncoding, MouseMode};
+use crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+
+/// Encode a key event as the bytes a terminal would send.
+///
+/// Returns `None` for events with no terminal representation (releases,
+/// media keys, bare modifiers).
+pub fn encode_key(key: &KeyEvent, modes: &InputModes) -> Option<Vec<u8>> {
+    if key.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    let mods = key.modifiers;
+    let param = modifier_param(mods);
+
+    let bytes = match key.code {
+        KeyCode::Char(c) => return Some(encode_char(c, mods)),
+        KeyCode::Enter => alt_prefixed(b"\r", mods),
+        KeyCode::Tab => alt_prefixed(b"\t", mods),
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Backspace => alt_prefixed(b"\x7f", mods),
+        KeyCode::Esc => b"\x1b".to_vec(),
+        KeyCode::Up => cursor_key(b'A', param, modes.application_cursor),
+        KeyCode::Down => cursor_key(b'B', param, modes.application_cursor),
+        KeyCode::Right => cursor_key(b'C', param, modes.application_cursor),
+        KeyCode::Left => cursor_key(b'D', param, modes.application_cursor),
+        KeyCode::Home => cursor_key(b'H', param, modes.application_cursor),
+        KeyCode::End => cursor_key(b'F', param, modes.application_cursor),
+        KeyCode::Insert => tilde_key(2, param),
+        KeyCode::Delete => tilde_key(3, param),
+        KeyCode::PageUp => tilde_key(5, param),
+        KeyCode::PageDown => tilde_key(6, param),
+        KeyCode::F(n @ 1..=4) => {
+            let final_byte = b'P' + (n - 1);
+            match param {
+                Some(p) => format!("\x1b[1;{}{}", p, final_byte as char).into_bytes(),
+                None => vec![0x1b, b'O', final_byte],
+            }
+        }
+        KeyCode::F(n) => {
+            let code = match n {
+                5 => 15,
+                6 => 17,
+                7 => 18,
+                8 => 19,
+                9 => 20,
+                10 => 21,
+                11 => 23,
+                12 => 24,
+                _ => return None,
+            };
+            tilde_key(code, param)
+        }
+        _ => return None,
+    };
+
+    Some(bytes)
+}
+
+/// Encode a mouse event for an app whose screen origin is at `(0, 0)`.
+///
+/// The event's coordinates must already be relative to the app's area.
+/// Returns `None` if the app has not requested this kind of mouse event.
+pub fn encode_mouse(event: &MouseEvent, modes: &InputModes) -> Option<Vec<u8>> {
+    let (mut code, release) = match event.kind {
+        MouseEventKind::Down(button) => (button_code(button), false),
+        MouseEventKind::Up(button) => {
+            if modes.mouse_mode == MouseMode::Press {
+                return None;
+            }
+            (button_code(button), true)
+        }
+        MouseEventKind::Drag(button) => {
+            if !matches!(modes.mouse_mode, MouseMode::ButtonMotion | MouseMode::AnyMotion) {
+                return None;
+            }
+            (button_code(button) + 32, false)
+        }
+        MouseEventKind::Moved => {
+            if modes.mouse_mode != MouseMode::AnyMotion {
+                return None;
+            }
+            (3 + 32, false)
+        }
+        MouseEventKind::ScrollUp => (64, false),
+        MouseEventKind::ScrollDown => (65, false),
+        MouseEventKind::ScrollLeft => (66, false),
+        MouseEventKind::ScrollRight => (67, false),
+    };
+
+    if modes.mouse_mode == MouseMode::None {
+        return None;
+    }
+
+    if event.modifiers.contains(KeyModifiers::SHIFT) {
+        code += 4;
+    }
+    if event.modifiers.contains(KeyModifiers::ALT) {
+        code += 8;
+    }
+    if event.modifiers.contains(KeyModifiers::CONTROL) {
+        code += 16;
+    }
+
+    let col = u32::from(event.column) + 1;
+    let row = u32::from(event.row) + 1;
+
+    let bytes = match modes.mouse_encoding {
+        MouseEncoding::Sgr => format!(
+            "\x1b[<{};{};{}{}",
+            code,
+            col,
+            row,
+            if release { 'm' } else { 'M' }
+        )
+        .into_bytes(),
+        MouseEncoding::Default | MouseEncoding::Utf8 => {
+            // Legacy encodings report releases as button 3
+            let code = if release { (code & !3) | 3 } else { code };
+            let mut bytes = b"\x1b[M".to_vec();
+            for value in [code + 32, col + 32, row + 32] {
+                if modes.mouse_encoding == MouseEncoding::Utf8 {
+                    let c = char::from_u32(value)?;
+                    let mut tmp = [0u8; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut tmp).as_bytes());
+                } else {
+                    bytes.push(u8::try_from(value).ok()?);
+                }
+            }
+            bytes
+        }
+    };
+
+    Some(bytes)
+}
+
+/// Encode pasted text, honouring bracketed paste mode.
+pub fn encode_paste(text: &str, modes: &InputModes) -> Vec<u8> {
+    if modes.bracketed_paste {
+        format!("\x1b[200~{}\x1b[201~", text).into_bytes()
+    } else {
+        text.as_bytes().to_vec()
+    }
+}
+
+fn encode_char(c: char, mods: KeyModifiers) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    if mods.contains(KeyModifiers::ALT) {
+        bytes.push(0x1b);
+    }
+
+    if mods.contains(KeyModifiers::CONTROL) {
+        let control = match c {
+            'a'..='z' => Some(c as u8 - b'a' + 1),
+            'A'..='Z' => Some(c as u8 - b'A' + 1),
+            ' ' | '@' | '2' => Some(0),
+            '[' | '3' => Some(0x1b),
+            '\\' | '4' => Some(0x1c),
+            ']' | '5' => Some(0x1d),
+            '^' | '6' => Some(0x1e),
+            '_' | '-' | '7' => Some(0x1f),
+            '?' | '8' => Some(0x7f),
+            _ => None,
+        };
+        if let Some(byte) = control {
+            bytes.push(byte);
+            return bytes;
+        }
+    }
+
+    let mut tmp = [0u8; 4];
+    bytes.extend_from_slice(c.encode_utf8(&mut tmp).as_bytes());
+    bytes
+}
+
+fn alt_prefixed(seq: &[u8], mods: KeyModifiers) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(seq.len() + 1);
+    if mods.contains(KeyModifiers::ALT) {
+        bytes.push(0x1b);
+    }
+    bytes.extend_from_slice(seq);
+    bytes
+}
+
+/// xterm modifier parameter (`1 + shift + 2*alt + 4*ctrl`), if any modifier is held.
+fn modifier_param(mods: KeyModifiers) -> Option<u8> {
+    let mut value = 0;
+    if mods.contains(KeyModifiers::SHIFT) {
+        value |= 1;
+    }
+    if mods.contains(KeyModifiers::ALT) {
+        value |= 2;
+    }
+    if mods.contains(KeyModifiers::CONTROL) {
+        value |= 4;
+    }
+    (value != 0).then_some(value + 1)
+}
+
+fn cursor_key(final_byte: u8, param: Option<u8>, application: bool) -> Vec<u8> {
+    match param {
+        Some(p) => format!("\x1b[1;{}{}", p, final_byte as char).into_bytes(),
+        None if application => vec![0x1b, b'O', final_byte],
+        None => vec![0x1b, b'[', final_byte],
+    }
+}
+
+fn tilde_key(code: u8, param: Option<u8>) -> Vec<u8> {
+    match param {
+        Some(p) => format!("\x1b[{};{}~", code, p).into_bytes(),
+        None => format!("\x1b[{}~", code).into_bytes(),
+    }
+}
+
+fn button_code(button: MouseButton) -> u32 {
+    match button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_encode_chars() {
+        let modes = InputModes::default();
+        assert_eq!(encode_key(&key(KeyCode::Char('a'), KeyModifiers::NONE), &modes), Some(b"a".to_vec()));
+        assert_eq!(encode_key(&key(KeyCode::Char('c'), KeyModifiers::CONTROL), &modes), Some(vec![3]));
+        assert_eq!(encode_key(&key(KeyCode::Char('x'), KeyModifiers::ALT), &modes), Some(b"\x1bx".to_vec()));
+        assert_eq!(encode_key(&key(KeyCode::Enter, KeyModifiers::NONE), &modes), Some(b"\r".to_vec()));
+    }
+
+    #[test]
+    fn test_encode_cursor_keys() {
+        let mut modes = InputModes::default();
+        assert_eq!(encode_key(&key(KeyCode::Up, KeyModifiers::NONE), &modes), Some(b"\x1b[A".to_vec()));
+        assert_eq!(encode_key(&key(KeyCode::Right, KeyModifiers::CONTROL), &modes), Some(b"\x1b[1;5C".to_vec()));
+
+        modes.application_cursor = true;
+        assert_eq!(encode_key(&key(KeyCode::Up, KeyModifiers::NONE), &modes), Some(b"\x1bOA".to_vec()));
+        assert_eq!(encode_key(&key(KeyCode::F(5), KeyModifiers::NONE), &modes), Some(b"\x1b[15~".to_vec()));
+    }
+
+    #[test]
+    fn test_encode_mouse() {
+        let event = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 4,
+            row: 2,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        assert_eq!(encode_mouse(&event, &InputModes::default()), None);
+
+        let modes = InputModes {
+            mouse_mode: MouseMode::PressRelease,
+            mouse_encoding: MouseEncoding::Sgr,
+            ..Default::default()
+        };
+        assert_eq!(encode_mouse(&event, &modes), Some(b"\x1b[<0;5;3M".to_vec()));
+
+        let legacy = InputModes {
+            mouse_mode: MouseMode::PressRelease,
+            ..Default::default()
+        };
+        assert_eq!(encode_mouse(&event, &legacy), Some(vec![0x1b, b'[', b'M', 32, 37, 35]));
+    }
+
+    #[test]
+    fn test_encode_paste() {
+        let mut modes = InputModes::default();
+        assert_eq!(encode_paste("hi", &modes), b"hi".to_vec());
+        modes.bracketed_paste = true;
+        assert_eq!(encode_paste("hi", &modes), b"\x1b[200~hi\x1b[201~".to_vec());
+    }
+}
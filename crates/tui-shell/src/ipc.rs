@@ -5,8 +5,11 @@ use crate::notification::Notification;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::io::{Read, Write};
-use std::os::unix::net::UnixStream;
-use std::path::Path;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+
+/// Environment variable carrying the app's IPC socket path into the child.
+pub const SOCKET_ENV: &str = "TUI_SHELL_SOCKET";
 
 /// IPC message types.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,12 +28,18 @@ pub enum IpcMessage {
     SessionRestore { state: serde_json::Value },
 
     // App -> Shell
+    /// Reply to `SessionSave` with the app's state.
+    SessionState { state: serde_json::Value },
     /// Send notification.
     Notification(Notification),
     /// Request focus.
     RequestFocus,
-    /// Share data with other apps.
+    /// Publish data on a bus topic (also delivered Shell -> App to subscribers).
     Data { key: String, value: serde_json::Value },
+    /// Subscribe to a bus topic (`*` and `prefix.*` patterns allowed).
+    Subscribe { topic: String },
+    /// Unsubscribe from a bus topic.
+    Unsubscribe { topic: String },
     /// Run a shell command.
     Command { name: String, args: Vec<String> },
 
@@ -75,6 +84,14 @@ impl IpcChannel {
         })
     }
 
+    /// Connect to the shell socket named in [`SOCKET_ENV`], if running under a shell.
+    pub fn from_env() -> ShellResult<Option<Self>> {
+        match std::env::var_os(SOCKET_ENV) {
+            Some(path) => Self::connect(Path::new(&path)).map(Some),
+            None => Ok(None),
+        }
+    }
+
     /// Create from an existing stream.
     pub fn from_stream(stream: UnixStream) -> ShellResult<Self> {
         stream
@@ -121,6 +138,11 @@ impl IpcChannel {
             return Ok(Some(msg));
         }
 
+        // A previous read may have left whole messages buffered
+        if let Some(msg) = self.parse_messages()? {
+            return Ok(Some(msg));
+        }
+
         // Try to read from socket
         let mut buf = [0u8; 4096];
         match self.stream.read(&mut buf) {
@@ -193,13 +215,64 @@ impl IpcChannel {
     }
 }
 
+/// Listening socket the shell opens for one app.
+pub struct IpcListener {
+    /// Bound listener.
+    listener: UnixListener,
+    /// Socket file path.
+    path: PathBuf,
+}
+
+impl IpcListener {
+    /// Bind a socket at the given path, replacing any stale socket file.
+    pub fn bind(path: &Path) -> ShellResult<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let _ = std::fs::remove_file(path);
+
+        let listener = UnixListener::bind(path).map_err(|e| ShellError::Ipc(e.to_string()))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| ShellError::Ipc(e.to_string()))?;
+
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Accept a pending connection (non-blocking).
+    pub fn accept(&self) -> ShellResult<Option<IpcChannel>> {
+        match self.listener.accept() {
+            Ok((stream, _)) => IpcChannel::from_stream(stream).map(Some),
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(ShellError::Ipc(e.to_string())),
+        }
+    }
+
+    /// Socket file path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for IpcListener {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
 /// Create a socket path for an app.
+///
+/// Paths are scoped by the shell's process ID so several shells can run at once.
 pub fn socket_path(app_id: u64) -> std::path::PathBuf {
     let runtime_dir = std::env::var("XDG_RUNTIME_DIR")
         .unwrap_or_else(|_| "/tmp".to_string());
 
     std::path::PathBuf::from(runtime_dir)
         .join("tui-shell")
+        .join(std::process::id().to_string())
         .join(format!("app-{}.sock", app_id))
 }
 
@@ -217,6 +290,37 @@ mod tests {
         assert!(matches!(restored, IpcMessage::Notification(_)));
     }
 
+    #[test]
+    fn test_listener_round_trip() {
+        let dir = std::env::temp_dir().join(format!("tui-shell-ipc-{}", std::process::id()));
+        let listener = IpcListener::bind(&dir.join("test.sock")).unwrap();
+        assert!(listener.accept().unwrap().is_none());
+
+        let mut client = IpcChannel::connect(listener.path()).unwrap();
+        let mut server = listener.accept().unwrap().unwrap();
+
+        // Several messages in one write are all delivered
+        client.queue(IpcMessage::Subscribe { topic: "file.*".to_string() });
+        client.queue(IpcMessage::Ping);
+        client.flush().unwrap();
+
+        let mut received = Vec::new();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while received.len() < 2 && std::time::Instant::now() < deadline {
+            match server.recv().unwrap() {
+                Some(msg) => received.push(msg),
+                None => std::thread::sleep(std::time::Duration::from_millis(5)),
+            }
+        }
+        assert!(matches!(&received[0], IpcMessage::Subscribe { topic } if topic == "file.*"));
+        assert!(matches!(received[1], IpcMessage::Ping));
+
+        let path = listener.path().to_path_buf();
+        drop(listener);
+        assert!(!path.exists());
+        let _ = std::fs::remove_dir(&dir);
+    }
+
     #[test]
     fn test_socket_path() {
         let path = socket_path(123);
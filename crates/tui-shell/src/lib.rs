@@ -10,19 +10,25 @@
 //! - **Unified Notifications**: Priority queue with expandable panel
 //! - **Context Switching**: Multiple methods (recent, numbered, fuzzy search, workspace)
 //! - **IPC**: Unix domain sockets with JSON messages
+//! - **Data Bus**: Topic-based publish/subscribe between apps
+//! - **Subprocess Apps**: Real binaries on a PTY, rendered through a VT emulator
 //! - **Session Management**: Full state persistence and restore
 //! - **Multi-Workspace**: Apps can be visible in multiple workspaces
 
 pub mod app;
+pub mod bus;
 pub mod compositor;
 pub mod config;
 pub mod error;
+pub mod input;
 pub mod ipc;
 pub mod launcher;
 pub mod notification;
 pub mod prefix;
+pub mod pty;
 pub mod session;
 pub mod task;
+pub mod terminal;
 pub mod workspace;
 
 // Re-exports from shared crates
@@ -31,19 +37,23 @@ pub use tui_theme;
 pub use tui_widgets;
 
 // Re-exports
-pub use app::{AppHandle, AppId, AppManager, LaunchMode};
+pub use app::{AppBuffer, AppEvent, AppHandle, AppId, AppManager, LaunchMode};
+pub use bus::{DataBus, Payload, PayloadSchema, SchemaRegistry};
 pub use compositor::{Compositor, LayoutState};
 pub use config::ShellConfig;
 pub use error::{ShellError, ShellResult};
-pub use ipc::{IpcChannel, IpcMessage};
+pub use ipc::{IpcChannel, IpcListener, IpcMessage};
 pub use launcher::{AppLauncher, AppMeta};
 pub use notification::{Notification, NotificationLevel, NotificationQueue};
 pub use prefix::PrefixKeyHandler;
-pub use session::{AppSession, Session, SessionState};
+pub use pty::{ExitInfo, PtyProcess};
+pub use session::{AppSession, JournalEntry, Session, SessionManager, SessionState};
 pub use task::{TaskCoordinator, TaskInfo, TaskStatus};
+pub use terminal::VirtualTerminal;
 pub use workspace::{Workspace, WorkspaceId, WorkspaceManager};
 
 use ratatui::layout::Rect;
+use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 
 /// Shell variant types.
@@ -104,6 +114,8 @@ pub struct Shell {
     prefix: PrefixKeyHandler,
     /// Compositor for rendering.
     compositor: Compositor,
+    /// Session snapshot and journal.
+    sessions: SessionManager,
     /// Current terminal size.
     size: Rect,
     /// Whether shell is running.
@@ -118,13 +130,17 @@ impl Shell {
 
         Self {
             config: config.clone(),
-            apps: AppManager::new(),
+            apps: AppManager::new().with_crash_config(config.crash.clone()),
             notifications: NotificationQueue::new(config.notifications.clone()),
             workspaces: WorkspaceManager::new(),
             tasks: TaskCoordinator::default(),
             launcher: AppLauncher::default(),
             prefix,
             compositor: Compositor::new(80, 24),
+            sessions: SessionManager::new(
+                config.session.path.clone().unwrap_or_else(session_path),
+                config.session.save_interval_secs,
+            ),
             size: Rect::default(),
             running: false,
         }
@@ -228,8 +244,11 @@ impl Shell {
         // Restore session if configured
         if self.config.session.restore_on_start {
             if let Err(e) = self.restore_session() {
-                // Log but don't fail
-                eprintln!("Failed to restore session: {}", e);
+                // Report but don't fail
+                self.notify(Notification::error(
+                    "shell",
+                    format!("Failed to restore session: {}", e),
+                ));
             }
         }
 
@@ -252,55 +271,210 @@ impl Shell {
         Ok(())
     }
 
+    /// Pump app output, route IPC and journal session changes.
+    ///
+    /// Call once per frame. Returns lifecycle events for the caller to surface.
+    pub fn tick(&mut self) -> ShellResult<Vec<AppEvent>> {
+        let events = self.apps.poll();
+        for (id, msg) in self.apps.poll_ipc() {
+            match msg {
+                IpcMessage::SessionState { state } if self.config.session.enabled => {
+                    self.sessions.record(JournalEntry::AppState { id, state })?;
+                }
+                IpcMessage::Notification(notification) => self.notify(notification),
+                _ => {}
+            }
+        }
+
+        if self.config.session.enabled && self.config.session.auto_save {
+            if self.sessions.needs_save() {
+                // Ask apps for fresh state; replies land in the journal
+                self.apps.request_session_save();
+            }
+            self.sessions.try_auto_save()?;
+        }
+        Ok(events)
+    }
+
+    /// Launch an app into the current layout and active workspace.
+    ///
+    /// Where the app lands depends on the shell variant. The launch, the new
+    /// layout and the focus change are journaled.
+    pub fn launch(&mut self, app_name: &str, args: &[&str]) -> ShellResult<AppId> {
+        let id = self.apps.launch_in(app_name, args, self.compositor.usable_area())?;
+
+        match self.config.variant {
+            ShellVariant::Tiled => self.compositor.add_tiled(id, None),
+            ShellVariant::Floating => self.compositor.add_floating(id),
+            ShellVariant::Tabbed => self.compositor.add_tab(id, app_name),
+            ShellVariant::Fullscreen => {
+                self.compositor.add_tiled(id, None);
+                self.compositor.set_fullscreen(Some(id));
+            }
+        }
+        if let Some(workspace) = self.workspaces.active_id() {
+            self.workspaces.add_app(workspace, id);
+            self.apps.add_to_workspace(id, workspace);
+        }
+        let _ = self.apps.focus(id);
+        self.resize_apps();
+
+        let app = AppSession::new(app_name)
+            .with_id(id)
+            .with_args(args.iter().map(|a| a.to_string()).collect());
+        let app = self
+            .workspaces
+            .workspaces_for_app(id)
+            .into_iter()
+            .fold(app, AppSession::in_workspace);
+        self.record(JournalEntry::AppOpened { app })?;
+        self.commit_layout()?;
+        self.record(JournalEntry::Focus { id: Some(id) })?;
+
+        Ok(id)
+    }
+
+    /// Kill an app and remove it from the layout and workspaces.
+    pub fn kill(&mut self, id: AppId) -> ShellResult<()> {
+        self.apps.kill(id)?;
+        self.compositor.remove(id);
+        self.workspaces.remove_app_everywhere(id);
+
+        // Hand focus to the most recent app still running
+        let focused = self.apps.focused();
+        if let Some(next) = focused {
+            self.compositor.focus(next);
+        }
+        self.resize_apps();
+
+        self.record(JournalEntry::AppClosed { id })?;
+        self.commit_layout()?;
+        self.record(JournalEntry::Focus { id: focused })
+    }
+
+    /// Focus an app.
+    pub fn focus(&mut self, id: AppId) -> ShellResult<()> {
+        self.apps.focus(id)?;
+        self.compositor.focus(id);
+        self.resize_apps();
+
+        // Focus can raise a window or switch tabs, so the layout changes too
+        self.commit_layout()?;
+        self.record(JournalEntry::Focus { id: Some(id) })
+    }
+
+    /// Switch the active workspace.
+    pub fn switch_workspace(&mut self, id: WorkspaceId) -> ShellResult<bool> {
+        if !self.workspaces.switch_to(id) {
+            return Ok(false);
+        }
+        self.record(JournalEntry::Workspaces { workspaces: self.workspaces.to_vec() })?;
+        Ok(true)
+    }
+
+    /// Journal the current layout and workspaces.
+    ///
+    /// Call after changing them through [`Shell::compositor_mut`] or
+    /// [`Shell::workspaces_mut`] so the change survives a crash.
+    pub fn commit_layout(&mut self) -> ShellResult<()> {
+        self.record(JournalEntry::Layout { layout: self.compositor.state().clone() })?;
+        self.record(JournalEntry::Workspaces { workspaces: self.workspaces.to_vec() })
+    }
+
+    /// Journal a change so it survives a crash.
+    pub fn record(&mut self, entry: JournalEntry) -> ShellResult<()> {
+        if self.config.session.enabled {
+            self.sessions.record(entry)?;
+        }
+        Ok(())
+    }
+
     /// Save the current session.
-    pub fn save_session(&self) -> ShellResult<Session> {
+    pub fn save_session(&mut self) -> ShellResult<Session> {
         let session = Session {
             layout: self.compositor.state().clone(),
-            apps: Vec::new(), // Apps save their own state
+            apps: self.apps.save_sessions()?,
             focused: self.compositor.focused(),
             workspaces: self.workspaces.to_vec(),
         };
 
-        // Write to file
-        let path = self.session_path();
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        let json = serde_json::to_string_pretty(&session)?;
-        std::fs::write(&path, json)?;
+        self.sessions.update(session.clone())?;
+        self.sessions.save()?;
 
         Ok(session)
     }
 
-    /// Restore a saved session.
+    /// Restore a saved session, replaying any journal left by a crash.
+    ///
+    /// Each app is relaunched with its args into its saved pane area;
+    /// subprocess apps receive their saved state over IPC once they connect.
+    /// Apps get new IDs, so the layout and workspaces are remapped onto
+    /// them, and apps that fail to start are dropped and reported.
     pub fn restore_session(&mut self) -> ShellResult<()> {
-        let path = self.session_path();
-        if !path.exists() {
-            return Ok(());
+        self.sessions.load()?;
+        let session = self.sessions.session().clone();
+
+        // Work out pane areas from the saved layout before relaunching
+        self.compositor.restore_state(session.layout.clone());
+        let usable = self.compositor.usable_area();
+        let areas: HashMap<AppId, Rect> = session
+            .apps
+            .iter()
+            .map(|app| (app.id, self.compositor.get_rect(app.id).unwrap_or(usable)))
+            .collect();
+
+        let mut relaunched = HashMap::new();
+        for (old, result) in self.apps.restore_session(&session, &areas) {
+            match result {
+                Ok(new) => {
+                    relaunched.insert(old, new);
+                }
+                Err(e) => {
+                    let name = session
+                        .apps
+                        .iter()
+                        .find(|a| a.id == old)
+                        .map(|a| a.app_name.as_str())
+                        .unwrap_or_default();
+                    self.notify(Notification::error(
+                        "shell",
+                        format!("Failed to restore {}: {}", name, e),
+                    ));
+                }
+            }
         }
 
-        let content = std::fs::read_to_string(&path)?;
-        let session: Session = serde_json::from_str(&content)?;
-
-        // Restore workspaces
-        self.workspaces = WorkspaceManager::from_workspaces(session.workspaces);
-
-        // Restore layout
-        self.compositor.restore_state(session.layout);
+        // Point the layout and workspaces at the new IDs
+        self.compositor.restore_state(session.layout.remap(&relaunched));
+        let workspaces = session
+            .workspaces
+            .iter()
+            .map(|ws| Workspace {
+                apps: ws.apps.iter().filter_map(|id| relaunched.get(id).copied()).collect(),
+                ..ws.clone()
+            })
+            .collect();
+        self.workspaces = WorkspaceManager::from_workspaces(workspaces);
 
         // Restore focus
-        if let Some(focused) = session.focused {
-            self.compositor.focus(focused);
+        if let Some(focused) = session.focused.and_then(|id| relaunched.get(&id)) {
+            self.compositor.focus(*focused);
+        }
+        self.resize_apps();
+
+        // Replace the old IDs on disk so later journal entries apply
+        if self.config.session.enabled {
+            self.save_session()?;
         }
 
         Ok(())
     }
 
-    /// Get the session file path.
-    fn session_path(&self) -> std::path::PathBuf {
-        directories::ProjectDirs::from("", "", "tui-shell")
-            .map(|d| d.data_dir().join("session.json"))
-            .unwrap_or_else(|| std::path::PathBuf::from("session.json"))
+    /// Resize every visible app to its current layout area.
+    fn resize_apps(&mut self) {
+        for (id, area) in self.compositor.get_all_rects() {
+            let _ = self.apps.resize_app(id, area);
+        }
     }
 
     /// Push a notification.
@@ -314,6 +488,13 @@ impl Shell {
     }
 }
 
+/// Get the session file path.
+fn session_path() -> std::path::PathBuf {
+    directories::ProjectDirs::from("", "", "tui-shell")
+        .map(|d| d.data_dir().join("session.json"))
+        .unwrap_or_else(|| std::path::PathBuf::from("session.json"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -329,4 +510,42 @@ mod tests {
         let shell = Shell::with_defaults();
         assert!(!shell.is_running());
     }
+
+    #[test]
+    fn test_session_survives_crash() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = ShellConfig::default();
+        config.session.path = Some(dir.path().join("session.json"));
+
+        let mut shell = Shell::new(config.clone());
+        shell.set_size(100, 40);
+        let first = shell.launch("sh", &["-c", "sleep 5"]).unwrap();
+        shell.launch("sh", &["-c", "sleep 5"]).unwrap();
+        shell.focus(first).unwrap();
+
+        // Crash: nothing is saved, only the journal is left behind
+        shell.apps_mut().shutdown_all();
+        drop(shell);
+
+        let mut restored = Shell::new(config);
+        restored.set_size(100, 40);
+        restored.restore_session().unwrap();
+
+        let ids = restored.apps().list_running();
+        assert_eq!(ids.len(), 2);
+
+        let layout = restored.compositor().state().all_apps();
+        assert_eq!(layout.len(), 2);
+        assert!(layout.iter().all(|id| ids.contains(id)));
+        let workspace = restored.workspaces().active().unwrap();
+        assert!(ids.iter().all(|&id| workspace.contains(id)));
+
+        // The first app was focused and sits in the left pane
+        let focused = restored.compositor().focused().unwrap();
+        assert_eq!(restored.compositor().get_rect(focused).map(|r| r.x), Some(0));
+        let area = restored.apps().get(focused).unwrap().buffer.area();
+        assert_eq!(area, restored.compositor().get_rect(focused).unwrap());
+
+        restored.apps_mut().shutdown_all();
+    }
 }
@@ -3,6 +3,7 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
+use tui_keybinds::{KeyBinding, KeyHint};
 
 /// Prefix key state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -68,6 +69,62 @@ pub enum ShellCommand {
     Custom(String),
 }
 
+impl ShellCommand {
+    /// Short description for help and key hints.
+    pub fn description(&self) -> String {
+        match self {
+            Self::CommandPalette => "Command palette".to_string(),
+            Self::Launcher => "Launch app".to_string(),
+            Self::NextApp => "Next app".to_string(),
+            Self::PrevApp => "Previous app".to_string(),
+            Self::FocusApp(n) => format!("Focus app {}", n),
+            Self::NextWorkspace => "Next workspace".to_string(),
+            Self::PrevWorkspace => "Previous workspace".to_string(),
+            Self::SwitchWorkspace(n) => format!("Workspace {}", n),
+            Self::NewWorkspace => "New workspace".to_string(),
+            Self::CloseApp => "Close app".to_string(),
+            Self::KillApp => "Kill app".to_string(),
+            Self::ToggleMaximize => "Toggle maximize".to_string(),
+            Self::ToggleFullscreen => "Toggle fullscreen".to_string(),
+            Self::NotificationPanel => "Notifications".to_string(),
+            Self::DismissNotifications => "Dismiss notifications".to_string(),
+            Self::Help => "Help".to_string(),
+            Self::Settings => "Settings".to_string(),
+            Self::Quit => "Quit shell".to_string(),
+            Self::SplitHorizontal => "Split horizontally".to_string(),
+            Self::SplitVertical => "Split vertically".to_string(),
+            Self::FocusDirection(dir) => format!("Focus {}", dir.name()),
+            Self::SwapDirection(dir) => format!("Swap {}", dir.name()),
+            Self::Resize(dir, amount) => format!("Resize {} by {}", dir.name(), amount),
+            Self::Custom(name) => name.clone(),
+        }
+    }
+
+    /// Group name used to section key hints.
+    pub fn group(&self) -> &'static str {
+        match self {
+            Self::CommandPalette | Self::Launcher => "Launch",
+            Self::NextApp | Self::PrevApp | Self::FocusApp(_) | Self::FocusDirection(_) => "Focus",
+            Self::NextWorkspace | Self::PrevWorkspace | Self::SwitchWorkspace(_) | Self::NewWorkspace => {
+                "Workspaces"
+            }
+            Self::CloseApp
+            | Self::KillApp
+            | Self::ToggleMaximize
+            | Self::ToggleFullscreen
+            | Self::SplitHorizontal
+            | Self::SplitVertical
+            | Self::SwapDirection(_)
+            | Self::Resize(..) => "Windows",
+            Self::NotificationPanel | Self::DismissNotifications => "Notifications",
+            Self::Help | Self::Settings | Self::Quit | Self::Custom(_) => "Shell",
+        }
+    }
+}
+
+/// Order of hint groups in the which-key overlay.
+const HINT_GROUPS: &[&str] = &["Launch", "Focus", "Workspaces", "Windows", "Notifications", "Shell"];
+
 /// Direction for navigation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Direction {
@@ -77,6 +134,17 @@ pub enum Direction {
     Right,
 }
 
+impl Direction {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Left => "left",
+            Self::Right => "right",
+        }
+    }
+}
+
 /// Prefix key handler.
 pub struct PrefixKeyHandler {
     /// Prefix key.
@@ -301,6 +369,33 @@ impl PrefixKeyHandler {
         self.bindings.iter().find(|(_, c)| *c == command).map(|(k, _)| k)
     }
 
+    /// Hints for every key bound after the prefix, for a which-key overlay.
+    ///
+    /// Sorted by group, then key.
+    pub fn hints(&self) -> Vec<KeyHint> {
+        let mut hints: Vec<KeyHint> = self
+            .bindings
+            .iter()
+            .map(|(key, command)| {
+                KeyHint {
+                    key: KeyBinding::new(key.code, key.modifiers),
+                    action: None,
+                    description: command.description(),
+                    group: None,
+                    is_prefix: false,
+                }
+                .with_group(command.group())
+            })
+            .collect();
+
+        hints.sort_by_cached_key(|h| {
+            let group = h.group.as_deref().unwrap_or_default();
+            let index = HINT_GROUPS.iter().position(|g| *g == group).unwrap_or(usize::MAX);
+            (index, h.key.to_string())
+        });
+        hints
+    }
+
     /// Get prefix key display string.
     pub fn prefix_display(&self) -> String {
         format_key_event(&self.prefix_key)
@@ -499,6 +594,19 @@ mod tests {
         assert_eq!(result, PrefixResult::PassPrefix);
     }
 
+    #[test]
+    fn test_hints() {
+        let handler = PrefixKeyHandler::new();
+        let hints = handler.hints();
+
+        assert_eq!(hints.len(), handler.bindings().len());
+        assert_eq!(hints[0].group.as_deref(), Some("Launch"));
+        assert!(hints
+            .iter()
+            .any(|h| h.key == KeyBinding::key(KeyCode::Char('q')) && h.description == "Quit shell"));
+        assert_eq!(hints.last().unwrap().group.as_deref(), Some("Shell"));
+    }
+
     #[test]
     fn test_parse_key() {
         let key = parse_key_event("ctrl+space").unwrap();
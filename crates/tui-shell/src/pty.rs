@@ -0,0 +1,238 @@
+//! Pseudo-terminal management for subprocess apps.
+
+use crate::error::{ShellError, ShellResult};
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+/// Environment variable carrying the shell's app ID into the child.
+pub const APP_ID_ENV: &str = "TUI_SHELL_APP_ID";
+
+/// Exit status of a subprocess app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitInfo {
+    /// Exit code (non-zero when killed by a signal).
+    pub code: u32,
+    /// Whether the process exited successfully.
+    pub success: bool,
+}
+
+/// A child process attached to a pseudo-terminal.
+pub struct PtyProcess {
+    /// Master side of the PTY.
+    master: Box<dyn MasterPty + Send>,
+    /// Writer for the child's stdin.
+    writer: Box<dyn Write + Send>,
+    /// The child process.
+    child: Box<dyn Child + Send + Sync>,
+    /// Output chunks read by the background reader thread.
+    output: Receiver<Vec<u8>>,
+    /// Whether the reader hit EOF.
+    eof: bool,
+    /// Current size (cols, rows).
+    size: (u16, u16),
+}
+
+impl PtyProcess {
+    /// Spawn a program on a new PTY with the given size.
+    pub fn spawn(
+        program: &Path,
+        args: &[&str],
+        cols: u16,
+        rows: u16,
+        env: &[(&str, String)],
+    ) -> ShellResult<Self> {
+        let size = pty_size(cols, rows);
+        let pair = native_pty_system()
+            .openpty(size)
+            .map_err(|e| ShellError::LaunchFailed(e.to_string()))?;
+
+        let mut cmd = CommandBuilder::new(program);
+        cmd.args(args);
+        if let Ok(cwd) = std::env::current_dir() {
+            cmd.cwd(cwd);
+        }
+        cmd.env("TERM", "xterm-256color");
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+
+        let child = pair
+            .slave
+            .spawn_command(cmd)
+            .map_err(|e| ShellError::LaunchFailed(e.to_string()))?;
+        // The slave end belongs to the child now
+        drop(pair.slave);
+
+        let mut reader = pair
+            .master
+            .try_clone_reader()
+            .map_err(|e| ShellError::LaunchFailed(e.to_string()))?;
+        let writer = pair
+            .master
+            .take_writer()
+            .map_err(|e| ShellError::LaunchFailed(e.to_string()))?;
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name(format!("pty-{}", program.display()))
+            .spawn(move || {
+                let mut buf = [0u8; 8192];
+                loop {
+                    match reader.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => {
+                            if tx.send(buf[..n].to_vec()).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            master: pair.master,
+            writer,
+            child,
+            output: rx,
+            eof: false,
+            size: (size.cols, size.rows),
+        })
+    }
+
+    /// Write input bytes to the child.
+    pub fn write(&mut self, bytes: &[u8]) -> ShellResult<()> {
+        self.writer.write_all(bytes)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Drain all output produced since the last call (non-blocking).
+    pub fn read_available(&mut self) -> Vec<u8> {
+        let mut out = Vec::new();
+        loop {
+            match self.output.try_recv() {
+                Ok(chunk) => out.extend_from_slice(&chunk),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.eof = true;
+                    break;
+                }
+            }
+        }
+        out
+    }
+
+    /// Whether the output stream has been closed.
+    pub fn is_eof(&self) -> bool {
+        self.eof
+    }
+
+    /// Resize the PTY, notifying the child with SIGWINCH.
+    pub fn resize(&mut self, cols: u16, rows: u16) -> ShellResult<()> {
+        let size = pty_size(cols, rows);
+        if (size.cols, size.rows) == self.size {
+            return Ok(());
+        }
+        self.master
+            .resize(size)
+            .map_err(|e| ShellError::App(e.to_string()))?;
+        self.size = (size.cols, size.rows);
+        Ok(())
+    }
+
+    /// Current size as (cols, rows).
+    pub fn size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    /// Process ID of the child, if still known.
+    pub fn pid(&self) -> Option<u32> {
+        self.child.process_id()
+    }
+
+    /// Check whether the child has exited (non-blocking).
+    pub fn try_wait(&mut self) -> ShellResult<Option<ExitInfo>> {
+        Ok(self.child.try_wait()?.map(|status| ExitInfo {
+            code: status.exit_code(),
+            success: status.success(),
+        }))
+    }
+
+    /// Kill the child process.
+    pub fn kill(&mut self) -> ShellResult<()> {
+        self.child.kill()?;
+        // Reap to avoid leaving a zombie behind
+        let _ = self.child.wait();
+        Ok(())
+    }
+}
+
+impl Drop for PtyProcess {
+    fn drop(&mut self) {
+        if matches!(self.child.try_wait(), Ok(None)) {
+            let _ = self.kill();
+        }
+    }
+}
+
+fn pty_size(cols: u16, rows: u16) -> PtySize {
+    PtySize {
+        rows: rows.max(1),
+        cols: cols.max(1),
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+/// Resolve an app name to an executable.
+///
+/// Looks next to the running shell binary first (so a cargo build of the
+/// whole suite works out of the box), then falls back to `PATH`.
+pub fn resolve_binary(name: &str) -> Option<PathBuf> {
+    let candidate = Path::new(name);
+    if candidate.components().count() > 1 {
+        return candidate.is_file().then(|| candidate.to_path_buf());
+    }
+
+    let sibling = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(name)));
+    if let Some(path) = sibling.filter(|p| p.is_file()) {
+        return Some(path);
+    }
+
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(name))
+            .find(|p| p.is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_resolve_binary() {
+        assert!(resolve_binary("sh").is_some());
+        assert!(resolve_binary("definitely-not-a-real-app").is_none());
+    }
+
+    #[test]
+    fn test_spawn_and_read() {
+        let sh = resolve_binary("sh").unwrap();
+        let mut pty = PtyProcess::spawn(&sh, &["-c", "printf hello"], 20, 5, &[]).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut output = Vec::new();
+        while Instant::now() < deadline && !String::from_utf8_lossy(&output).contains("hello") {
+            output.extend(pty.read_available());
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(String::from_utf8_lossy(&output).contains("hello"));
+        assert_eq!(pty.size(), (20, 5));
+    }
+}
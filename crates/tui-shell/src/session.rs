@@ -4,6 +4,8 @@ use crate::compositor::LayoutState;
 use crate::workspace::{Workspace, WorkspaceId};
 use crate::AppId;
 use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
 
 /// Trait for apps that support session state.
 pub trait SessionState {
@@ -27,9 +29,59 @@ pub struct Session {
     pub workspaces: Vec<Workspace>,
 }
 
+impl Session {
+    /// Apply a journal entry.
+    pub fn apply(&mut self, entry: &JournalEntry) {
+        match entry {
+            JournalEntry::Snapshot { session } => *self = session.clone(),
+            JournalEntry::AppOpened { app } => {
+                self.apps.retain(|a| a.id != app.id);
+                self.apps.push(app.clone());
+            }
+            JournalEntry::AppClosed { id } => {
+                self.apps.retain(|a| a.id != *id);
+                if self.focused == Some(*id) {
+                    self.focused = None;
+                }
+            }
+            JournalEntry::AppState { id, state } => {
+                if let Some(app) = self.apps.iter_mut().find(|a| a.id == *id) {
+                    app.state = Some(state.clone());
+                }
+            }
+            JournalEntry::Focus { id } => self.focused = *id,
+            JournalEntry::Layout { layout } => self.layout = layout.clone(),
+            JournalEntry::Workspaces { workspaces } => self.workspaces = workspaces.clone(),
+        }
+    }
+}
+
+/// A single change recorded in the session journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum JournalEntry {
+    /// Replace the whole session.
+    Snapshot { session: Session },
+    /// An app was launched.
+    AppOpened { app: AppSession },
+    /// An app was closed.
+    AppClosed { id: AppId },
+    /// An app reported its state.
+    AppState { id: AppId, state: serde_json::Value },
+    /// Focus changed.
+    Focus { id: Option<AppId> },
+    /// Layout changed.
+    Layout { layout: LayoutState },
+    /// Workspaces changed.
+    Workspaces { workspaces: Vec<Workspace> },
+}
+
 /// Session data for a single app.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSession {
+    /// App ID at the time the session was recorded.
+    #[serde(default)]
+    pub id: AppId,
     /// App name.
     pub app_name: String,
     /// Launch arguments.
@@ -44,6 +96,7 @@ impl AppSession {
     /// Create a new app session.
     pub fn new(app_name: impl Into<String>) -> Self {
         Self {
+            id: 0,
             app_name: app_name.into(),
             args: Vec::new(),
             state: None,
@@ -51,6 +104,12 @@ impl AppSession {
         }
     }
 
+    /// Set the app ID.
+    pub fn with_id(mut self, id: AppId) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Set arguments.
     pub fn with_args(mut self, args: Vec<String>) -> Self {
         self.args = args;
@@ -71,11 +130,18 @@ impl AppSession {
 }
 
 /// Session manager for auto-save and restore.
+///
+/// Every change is appended to a write-ahead journal next to the session
+/// file as soon as it is recorded, so a shell crash (or SIGKILL) loses
+/// nothing that reached the journal. Saving writes a fresh snapshot and
+/// truncates the journal.
 pub struct SessionManager {
     /// Current session.
     session: Session,
     /// Session file path.
-    path: std::path::PathBuf,
+    path: PathBuf,
+    /// Open journal file.
+    journal: Option<std::fs::File>,
     /// Last save time.
     last_save: Option<std::time::Instant>,
     /// Auto-save interval.
@@ -86,41 +152,99 @@ pub struct SessionManager {
 
 impl SessionManager {
     /// Create a new session manager.
-    pub fn new(path: std::path::PathBuf, save_interval_secs: u64) -> Self {
+    pub fn new(path: PathBuf, save_interval_secs: u64) -> Self {
         Self {
             session: Session::default(),
             path,
+            journal: None,
             last_save: None,
             save_interval: std::time::Duration::from_secs(save_interval_secs),
             dirty: false,
         }
     }
 
-    /// Load session from disk.
+    /// Path of the write-ahead journal.
+    pub fn journal_path(&self) -> PathBuf {
+        self.path.with_extension("journal")
+    }
+
+    /// Load the last snapshot and replay the journal on top of it.
+    ///
+    /// A torn final journal line (from a crash mid-write) is ignored.
     pub fn load(&mut self) -> Result<(), crate::ShellError> {
-        if self.path.exists() {
+        self.session = if self.path.exists() {
             let content = std::fs::read_to_string(&self.path)?;
-            self.session = serde_json::from_str(&content)?;
+            serde_json::from_str(&content)?
+        } else {
+            Session::default()
+        };
+
+        let journal_path = self.journal_path();
+        if journal_path.exists() {
+            let reader = std::io::BufReader::new(std::fs::File::open(&journal_path)?);
+            for line in reader.lines() {
+                let line = line?;
+                match serde_json::from_str::<JournalEntry>(&line) {
+                    Ok(entry) => {
+                        self.session.apply(&entry);
+                        self.dirty = true;
+                    }
+                    Err(_) => break,
+                }
+            }
         }
         Ok(())
     }
 
-    /// Save session to disk.
+    /// Write a snapshot to disk and truncate the journal.
     pub fn save(&mut self) -> Result<(), crate::ShellError> {
         if let Some(parent) = self.path.parent() {
             std::fs::create_dir_all(parent)?;
         }
         let content = serde_json::to_string_pretty(&self.session)?;
-        std::fs::write(&self.path, content)?;
+        write_atomic(&self.path, content.as_bytes())?;
+
+        self.journal = None;
+        let journal_path = self.journal_path();
+        if journal_path.exists() {
+            std::fs::remove_file(journal_path)?;
+        }
+
         self.last_save = Some(std::time::Instant::now());
         self.dirty = false;
         Ok(())
     }
 
-    /// Update session data.
-    pub fn update(&mut self, session: Session) {
-        self.session = session;
+    /// Apply a change and append it to the journal.
+    pub fn record(&mut self, entry: JournalEntry) -> Result<(), crate::ShellError> {
+        self.session.apply(&entry);
         self.dirty = true;
+
+        if self.journal.is_none() {
+            let journal_path = self.journal_path();
+            if let Some(parent) = journal_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            self.journal = Some(
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(journal_path)?,
+            );
+        }
+
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        if let Some(journal) = self.journal.as_mut() {
+            journal.write_all(&line)?;
+            journal.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Replace the session data.
+    pub fn update(&mut self, session: Session) -> Result<(), crate::ShellError> {
+        self.record(JournalEntry::Snapshot { session })
     }
 
     /// Get current session.
@@ -161,6 +285,17 @@ impl SessionManager {
     }
 }
 
+/// Write a file via a temporary sibling and rename, so readers never see a partial file.
+fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+    }
+    std::fs::rename(tmp, path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,4 +327,48 @@ mod tests {
         manager.mark_dirty();
         assert!(manager.is_dirty());
     }
+
+    #[test]
+    fn test_journal_replay() {
+        let dir = std::env::temp_dir().join(format!("tui-shell-journal-{}", std::process::id()));
+        let path = dir.join("session.json");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut manager = SessionManager::new(path.clone(), 300);
+        manager
+            .record(JournalEntry::AppOpened {
+                app: AppSession::new("file-manager").with_id(1).with_args(vec!["/tmp".into()]),
+            })
+            .unwrap();
+        manager.save().unwrap();
+        assert!(!manager.journal_path().exists());
+
+        manager
+            .record(JournalEntry::AppOpened { app: AppSession::new("log-viewer").with_id(2) })
+            .unwrap();
+        manager
+            .record(JournalEntry::AppState { id: 2, state: serde_json::json!({ "filter": "web" }) })
+            .unwrap();
+        manager.record(JournalEntry::Focus { id: Some(2) }).unwrap();
+        manager.record(JournalEntry::AppClosed { id: 1 }).unwrap();
+
+        // Simulate a crash mid-write
+        let mut journal = std::fs::OpenOptions::new()
+            .append(true)
+            .open(manager.journal_path())
+            .unwrap();
+        journal.write_all(b"{\"op\":\"focus\",\"id").unwrap();
+        drop(manager);
+
+        let mut restored = SessionManager::new(path, 300);
+        restored.load().unwrap();
+        let session = restored.session();
+        assert_eq!(session.apps.len(), 1);
+        assert_eq!(session.apps[0].app_name, "log-viewer");
+        assert_eq!(session.apps[0].state, Some(serde_json::json!({ "filter": "web" })));
+        assert_eq!(session.focused, Some(2));
+        assert!(restored.is_dirty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,245 @@
+//! Virtual terminal emulation for subprocess apps.
+//!
+//! Output from an app's PTY is fed through a VT100/xterm parser and the
+//! resulting screen is painted into the app's sandboxed render buffer.
+
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier, Style};
+
+/// Lines of scrollback kept per app.
+const SCROLLBACK_LINES: usize = 1000;
+
+/// Mouse reporting requested by the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MouseMode {
+    /// App does not want mouse events.
+    #[default]
+    None,
+    /// Button presses only.
+    Press,
+    /// Presses and releases.
+    PressRelease,
+    /// Presses, releases and drags.
+    ButtonMotion,
+    /// All motion.
+    AnyMotion,
+}
+
+/// Wire format for mouse reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MouseEncoding {
+    /// Legacy X10 encoding.
+    #[default]
+    Default,
+    /// UTF-8 extended coordinates.
+    Utf8,
+    /// SGR (`CSI < ... M/m`) encoding.
+    Sgr,
+}
+
+/// Input modes the app has switched on, used to encode forwarded input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InputModes {
+    /// DECCKM application cursor keys.
+    pub application_cursor: bool,
+    /// Bracketed paste mode.
+    pub bracketed_paste: bool,
+    /// Mouse reporting mode.
+    pub mouse_mode: MouseMode,
+    /// Mouse report encoding.
+    pub mouse_encoding: MouseEncoding,
+}
+
+/// A terminal emulator backing a subprocess app.
+pub struct VirtualTerminal {
+    /// Escape-sequence parser and screen state.
+    parser: vt100::Parser,
+    /// Whether the screen changed since the last render.
+    dirty: bool,
+}
+
+impl VirtualTerminal {
+    /// Create a terminal with the given size.
+    pub fn new(cols: u16, rows: u16) -> Self {
+        Self {
+            parser: vt100::Parser::new(rows.max(1), cols.max(1), SCROLLBACK_LINES),
+            dirty: true,
+        }
+    }
+
+    /// Feed raw output bytes from the app.
+    pub fn process(&mut self, bytes: &[u8]) {
+        if !bytes.is_empty() {
+            self.parser.process(bytes);
+            self.dirty = true;
+        }
+    }
+
+    /// Resize the screen.
+    pub fn resize(&mut self, cols: u16, rows: u16) {
+        let (cur_rows, cur_cols) = self.parser.screen().size();
+        if (cur_cols, cur_rows) != (cols.max(1), rows.max(1)) {
+            self.parser.set_size(rows.max(1), cols.max(1));
+            self.dirty = true;
+        }
+    }
+
+    /// Current size as (cols, rows).
+    pub fn size(&self) -> (u16, u16) {
+        let (rows, cols) = self.parser.screen().size();
+        (cols, rows)
+    }
+
+    /// Whether the screen changed since the last render.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Window title set by the app (OSC 0/2).
+    pub fn title(&self) -> &str {
+        self.parser.screen().title()
+    }
+
+    /// Cursor position (col, row) relative to the screen, if visible.
+    pub fn cursor(&self) -> Option<(u16, u16)> {
+        let screen = self.parser.screen();
+        if screen.hide_cursor() {
+            return None;
+        }
+        let (row, col) = screen.cursor_position();
+        Some((col, row))
+    }
+
+    /// Input modes requested by the app.
+    pub fn input_modes(&self) -> InputModes {
+        let screen = self.parser.screen();
+        InputModes {
+            application_cursor: screen.application_cursor(),
+            bracketed_paste: screen.bracketed_paste(),
+            mouse_mode: match screen.mouse_protocol_mode() {
+                vt100::MouseProtocolMode::None => MouseMode::None,
+                vt100::MouseProtocolMode::Press => MouseMode::Press,
+                vt100::MouseProtocolMode::PressRelease => MouseMode::PressRelease,
+                vt100::MouseProtocolMode::ButtonMotion => MouseMode::ButtonMotion,
+                vt100::MouseProtocolMode::AnyMotion => MouseMode::AnyMotion,
+            },
+            mouse_encoding: match screen.mouse_protocol_encoding() {
+                vt100::MouseProtocolEncoding::Default => MouseEncoding::Default,
+                vt100::MouseProtocolEncoding::Utf8 => MouseEncoding::Utf8,
+                vt100::MouseProtocolEncoding::Sgr => MouseEncoding::Sgr,
+            },
+        }
+    }
+
+    /// Plain-text contents of the visible screen.
+    pub fn contents(&self) -> String {
+        self.parser.screen().contents()
+    }
+
+    /// Paint the screen into a buffer, starting at the buffer's origin.
+    pub fn render(&mut self, buf: &mut Buffer) {
+        let screen = self.parser.screen();
+        let area = buf.area;
+        let (rows, cols) = screen.size();
+
+        for row in 0..area.height.min(rows) {
+            for col in 0..area.width.min(cols) {
+                let Some(vt_cell) = screen.cell(row, col) else {
+                    continue;
+                };
+                let cell = &mut buf[(area.x + col, area.y + row)];
+                cell.reset();
+
+                if vt_cell.is_wide_continuation() {
+                    // Covered by the wide glyph to the left
+                    cell.set_symbol("");
+                    continue;
+                }
+
+                let contents = vt_cell.contents();
+                if contents.is_empty() {
+                    cell.set_symbol(" ");
+                } else {
+                    cell.set_symbol(&contents);
+                }
+                cell.set_style(cell_style(vt_cell));
+            }
+        }
+
+        self.dirty = false;
+    }
+}
+
+fn cell_style(cell: &vt100::Cell) -> Style {
+    let mut style = Style::default()
+        .fg(convert_color(cell.fgcolor()))
+        .bg(convert_color(cell.bgcolor()));
+
+    let mut modifiers = Modifier::empty();
+    if cell.bold() {
+        modifiers |= Modifier::BOLD;
+    }
+    if cell.italic() {
+        modifiers |= Modifier::ITALIC;
+    }
+    if cell.underline() {
+        modifiers |= Modifier::UNDERLINED;
+    }
+    if cell.inverse() {
+        modifiers |= Modifier::REVERSED;
+    }
+    style = style.add_modifier(modifiers);
+    style
+}
+
+fn convert_color(color: vt100::Color) -> Color {
+    match color {
+        vt100::Color::Default => Color::Reset,
+        vt100::Color::Idx(idx) => Color::Indexed(idx),
+        vt100::Color::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn test_render_text_and_colors() {
+        let mut term = VirtualTerminal::new(10, 3);
+        term.process(b"hi \x1b[1;31mred\x1b[0m");
+        assert!(term.is_dirty());
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 3));
+        term.render(&mut buf);
+        assert!(!term.is_dirty());
+
+        assert_eq!(buf[(0, 0)].symbol(), "h");
+        assert_eq!(buf[(3, 0)].symbol(), "r");
+        assert_eq!(buf[(3, 0)].fg, Color::Indexed(1));
+        assert!(buf[(3, 0)].modifier.contains(Modifier::BOLD));
+        assert_eq!(buf[(0, 0)].fg, Color::Reset);
+    }
+
+    #[test]
+    fn test_cursor_and_modes() {
+        let mut term = VirtualTerminal::new(10, 3);
+        term.process(b"\x1b[2;5H");
+        assert_eq!(term.cursor(), Some((4, 1)));
+
+        term.process(b"\x1b[?25l\x1b[?1h\x1b[?1000h\x1b[?1006h");
+        assert_eq!(term.cursor(), None);
+        let modes = term.input_modes();
+        assert!(modes.application_cursor);
+        assert_eq!(modes.mouse_mode, MouseMode::PressRelease);
+        assert_eq!(modes.mouse_encoding, MouseEncoding::Sgr);
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut term = VirtualTerminal::new(10, 3);
+        term.resize(40, 12);
+        assert_eq!(term.size(), (40, 12));
+    }
+}
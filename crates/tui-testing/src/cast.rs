@@ -0,0 +1,967 @@
+//! Asciicast v2 recording and replay.
+//!
+//! Exports a [`TestTerminal`]'s frame history and recorded inputs as an
+//! asciinema `.cast` file, and turns the input events of a recording back into
+//! an [`InputSequence`] so user reproductions can become regression tests.
+
+use crate::input::{InputSequence, SequenceItem};
+use crate::snapshot::{BufferSnapshot, SerializableColor, SerializableModifier};
+use crate::terminal::TestTerminal;
+use crate::{TestError, TestResult};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+use unicode_width::UnicodeWidthStr;
+
+/// Asciicast format version written and accepted.
+pub const CAST_VERSION: u32 = 2;
+
+/// Minimum gap between exported events so playback is watchable.
+const DEFAULT_MIN_STEP: Duration = Duration::from_millis(50);
+
+/// Header line of an asciicast file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CastHeader {
+    /// Terminal width in columns.
+    pub width: u16,
+    /// Terminal height in rows.
+    pub height: u16,
+    /// Unix timestamp of the recording start.
+    pub timestamp: Option<u64>,
+    /// Recording title.
+    pub title: Option<String>,
+    /// Maximum idle time between events during playback, in seconds.
+    pub idle_time_limit: Option<f64>,
+    /// Captured environment variables.
+    pub env: BTreeMap<String, String>,
+}
+
+impl CastHeader {
+    /// Create a header for a terminal of the given size.
+    pub fn new(width: u16, height: u16) -> Self {
+        let mut env = BTreeMap::new();
+        env.insert("TERM".to_string(), "xterm-256color".to_string());
+        Self {
+            width,
+            height,
+            timestamp: None,
+            title: None,
+            idle_time_limit: None,
+            env,
+        }
+    }
+}
+
+/// Kind of an asciicast event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CastEventKind {
+    /// Data written to the terminal (`"o"`).
+    Output,
+    /// Data read from the keyboard (`"i"`).
+    Input,
+    /// A named marker (`"m"`).
+    Marker,
+    /// Terminal resize, data is `"COLSxROWS"` (`"r"`).
+    Resize,
+}
+
+impl CastEventKind {
+    /// The event code used in the file.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Output => "o",
+            Self::Input => "i",
+            Self::Marker => "m",
+            Self::Resize => "r",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "o" => Some(Self::Output),
+            "i" => Some(Self::Input),
+            "m" => Some(Self::Marker),
+            "r" => Some(Self::Resize),
+            _ => None,
+        }
+    }
+}
+
+/// A single timed event in a recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CastEvent {
+    /// Seconds since the start of the recording.
+    pub time: f64,
+    /// Event kind.
+    pub kind: CastEventKind,
+    /// Event payload.
+    pub data: String,
+}
+
+impl CastEvent {
+    /// Create a new event.
+    pub fn new(time: f64, kind: CastEventKind, data: impl Into<String>) -> Self {
+        Self {
+            time,
+            kind,
+            data: data.into(),
+        }
+    }
+}
+
+/// An asciicast v2 recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CastRecording {
+    /// File header.
+    pub header: CastHeader,
+    /// Events in time order.
+    pub events: Vec<CastEvent>,
+}
+
+impl CastRecording {
+    /// Create an empty recording.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            header: CastHeader::new(width, height),
+            events: Vec::new(),
+        }
+    }
+
+    /// Build a recording from a terminal's frame history and recorded inputs.
+    ///
+    /// Each frame is written as a full redraw. Inputs are interleaved by time,
+    /// and consecutive events are spaced at least 50ms apart.
+    pub fn from_terminal(terminal: &TestTerminal) -> Self {
+        Self::from_history(
+            terminal.frame_history(),
+            terminal.frame_times(),
+            terminal
+                .inputs()
+                .iter()
+                .map(|input| (input.time, &input.event)),
+            (terminal.width(), terminal.height()),
+            DEFAULT_MIN_STEP,
+        )
+    }
+
+    /// Build a recording from frames and inputs with explicit timings.
+    ///
+    /// `size` is used for the header when there are no frames.
+    pub fn from_history<'a>(
+        frames: &[BufferSnapshot],
+        frame_times: &[Duration],
+        inputs: impl IntoIterator<Item = (Duration, &'a Event)>,
+        size: (u16, u16),
+        min_step: Duration,
+    ) -> Self {
+        let (width, height) = frames
+            .first()
+            .map(|f| (f.width, f.height))
+            .unwrap_or(size);
+        let mut recording = Self::new(width, height);
+
+        // Merge frames and inputs by time; inputs first on ties since they
+        // produce the frame captured at the same instant
+        let mut timeline: Vec<(Duration, u8, CastEventKind, String)> = Vec::new();
+        let mut size = (width, height);
+        for (index, frame) in frames.iter().enumerate() {
+            let time = frame_times.get(index).copied().unwrap_or_default();
+            if (frame.width, frame.height) != size {
+                size = (frame.width, frame.height);
+                timeline.push((time, 1, CastEventKind::Resize, format!("{}x{}", size.0, size.1)));
+            }
+            timeline.push((time, 1, CastEventKind::Output, render_frame(frame)));
+        }
+        for (time, event) in inputs {
+            if let Some(data) = encode_event(event) {
+                timeline.push((time, 0, CastEventKind::Input, data));
+            }
+        }
+        timeline.sort_by_key(|(time, order, _, _)| (*time, *order));
+
+        let mut last: Option<f64> = None;
+        for (time, _, kind, data) in timeline {
+            let mut seconds = time.as_secs_f64();
+            if let Some(last) = last {
+                seconds = seconds.max(last + min_step.as_secs_f64());
+            }
+            last = Some(seconds);
+            recording.events.push(CastEvent::new(round_time(seconds), kind, data));
+        }
+
+        recording
+    }
+
+    /// Set the title.
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.header.title = Some(title.into());
+        self
+    }
+
+    /// Add a marker at the given time.
+    pub fn add_marker(&mut self, time: f64, label: impl Into<String>) {
+        let index = self.events.partition_point(|e| e.time <= time);
+        self.events
+            .insert(index, CastEvent::new(time, CastEventKind::Marker, label));
+    }
+
+    /// Total duration in seconds.
+    pub fn duration(&self) -> f64 {
+        self.events.last().map(|e| e.time).unwrap_or(0.0)
+    }
+
+    /// Events of one kind.
+    pub fn events_of(&self, kind: CastEventKind) -> impl Iterator<Item = &CastEvent> {
+        self.events.iter().filter(move |e| e.kind == kind)
+    }
+
+    /// Decode the input events into an input sequence.
+    ///
+    /// Gaps between inputs become delays, so the sequence can be replayed
+    /// with real timing.
+    pub fn to_input_sequence(&self) -> InputSequence {
+        let mut seq = InputSequence::new();
+        let mut last: Option<f64> = None;
+
+        for event in self.events_of(CastEventKind::Input) {
+            if let Some(last) = last {
+                let ms = ((event.time - last) * 1000.0).round();
+                if ms > 0.0 {
+                    seq.delay(ms as u64);
+                }
+            }
+            last = Some(event.time);
+
+            for decoded in decode_input(&event.data) {
+                seq.event(decoded);
+            }
+        }
+
+        seq
+    }
+
+    /// Serialize to asciicast v2 (newline-delimited JSON).
+    pub fn to_cast_string(&self) -> String {
+        let mut header = json!({
+            "version": CAST_VERSION,
+            "width": self.header.width,
+            "height": self.header.height,
+        });
+        if let Some(timestamp) = self.header.timestamp {
+            header["timestamp"] = json!(timestamp);
+        }
+        if let Some(title) = &self.header.title {
+            header["title"] = json!(title);
+        }
+        if let Some(limit) = self.header.idle_time_limit {
+            header["idle_time_limit"] = json!(limit);
+        }
+        if !self.header.env.is_empty() {
+            header["env"] = json!(self.header.env);
+        }
+
+        let mut out = header.to_string();
+        out.push('\n');
+        for event in &self.events {
+            out.push_str(&json!([event.time, event.kind.code(), event.data]).to_string());
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Parse an asciicast v2 file.
+    ///
+    /// Unknown event codes are skipped, as asciinema players do.
+    pub fn parse(content: &str) -> TestResult<Self> {
+        let mut lines = content.lines().filter(|l| !l.trim().is_empty());
+        let header_line = lines
+            .next()
+            .ok_or_else(|| TestError::InvalidRecording("empty file".to_string()))?;
+        let header: Value = serde_json::from_str(header_line)
+            .map_err(|e| TestError::InvalidRecording(format!("header: {}", e)))?;
+
+        let version = header["version"].as_u64().unwrap_or_default();
+        if version != u64::from(CAST_VERSION) {
+            return Err(TestError::InvalidRecording(format!(
+                "unsupported asciicast version {}",
+                version
+            )));
+        }
+
+        let dimension = |key: &str| {
+            header[key]
+                .as_u64()
+                .and_then(|v| u16::try_from(v).ok())
+                .ok_or_else(|| TestError::InvalidRecording(format!("header: missing {}", key)))
+        };
+
+        let mut recording = Self::new(dimension("width")?, dimension("height")?);
+        recording.header.timestamp = header["timestamp"].as_u64();
+        recording.header.title = header["title"].as_str().map(String::from);
+        recording.header.idle_time_limit = header["idle_time_limit"].as_f64();
+        if let Some(env) = header["env"].as_object() {
+            recording.header.env = env
+                .iter()
+                .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                .collect();
+        }
+
+        for (number, line) in lines.enumerate() {
+            let invalid = || TestError::InvalidRecording(format!("event {}: {}", number + 1, line));
+            let value: Value = serde_json::from_str(line).map_err(|_| invalid())?;
+            let time = value[0].as_f64().ok_or_else(invalid)?;
+            let code = value[1].as_str().ok_or_else(invalid)?;
+            let data = value[2].as_str().ok_or_else(invalid)?;
+
+            if let Some(kind) = CastEventKind::from_code(code) {
+                recording.events.push(CastEvent::new(time, kind, data));
+            }
+        }
+
+        Ok(recording)
+    }
+
+    /// Write to a `.cast` file.
+    pub fn save(&self, path: impl AsRef<Path>) -> TestResult<()> {
+        std::fs::write(path, self.to_cast_string())?;
+        Ok(())
+    }
+
+    /// Read a `.cast` file.
+    pub fn load(path: impl AsRef<Path>) -> TestResult<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+}
+
+impl InputSequence {
+    /// Load the input events of an asciicast recording.
+    pub fn from_cast(content: &str) -> TestResult<Self> {
+        Ok(CastRecording::parse(content)?.to_input_sequence())
+    }
+
+    /// Encode the sequence as asciicast input events.
+    ///
+    /// Delays advance the event clock; events without delays share a time.
+    pub fn to_cast_events(&self) -> Vec<CastEvent> {
+        let mut time = Duration::ZERO;
+        let mut events = Vec::new();
+        for item in self.iter() {
+            match item {
+                SequenceItem::Delay(delay) => time += delay,
+                SequenceItem::Event(event) => {
+                    if let Some(data) = encode_event(event) {
+                        events.push(CastEvent::new(
+                            round_time(time.as_secs_f64()),
+                            CastEventKind::Input,
+                            data,
+                        ));
+                    }
+                }
+            }
+        }
+        events
+    }
+}
+
+/// Round to microseconds, like asciinema does.
+fn round_time(seconds: f64) -> f64 {
+    (seconds * 1_000_000.0).round() / 1_000_000.0
+}
+
+/// Render a frame as a full-screen redraw with SGR styling.
+fn render_frame(frame: &BufferSnapshot) -> String {
+    let mut out = String::from("\x1b[H\x1b[2J");
+    let mut current: Option<String> = None;
+
+    for y in 0..frame.height {
+        out.push_str(&format!("\x1b[{};1H", y + 1));
+
+        let mut skip = 0;
+        for x in 0..frame.width {
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let Some(cell) = frame.cell_at(x, y) else {
+                out.push(' ');
+                continue;
+            };
+
+            let sgr = sgr_for(&cell.fg, &cell.bg, &cell.modifiers);
+            if current.as_ref() != Some(&sgr) {
+                out.push_str(&sgr);
+                current = Some(sgr);
+            }
+
+            if cell.symbol.is_empty() {
+                out.push(' ');
+            } else {
+                out.push_str(&cell.symbol);
+                // Wide symbols cover the following cells
+                skip = cell.symbol.width().saturating_sub(1);
+            }
+        }
+    }
+
+    out.push_str("\x1b[0m");
+    out
+}
+
+fn sgr_for(fg: &SerializableColor, bg: &SerializableColor, modifiers: &SerializableModifier) -> String {
+    let mut codes = vec!["0".to_string()];
+    let flags = [
+        (modifiers.bold, "1"),
+        (modifiers.dim, "2"),
+        (modifiers.italic, "3"),
+        (modifiers.underlined, "4"),
+        (modifiers.slow_blink, "5"),
+        (modifiers.rapid_blink, "6"),
+        (modifiers.reversed, "7"),
+        (modifiers.hidden, "8"),
+        (modifiers.crossed_out, "9"),
+    ];
+    codes.extend(flags.iter().filter(|(on, _)| *on).map(|(_, code)| code.to_string()));
+    if let Some(code) = color_code(fg, false) {
+        codes.push(code);
+    }
+    if let Some(code) = color_code(bg, true) {
+        codes.push(code);
+    }
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+fn color_code(color: &SerializableColor, background: bool) -> Option<String> {
+    let base = if background { 40 } else { 30 };
+    let bright = if background { 100 } else { 90 };
+    let extended = if background { 48 } else { 38 };
+
+    let code = match color {
+        SerializableColor::Reset => return None,
+        SerializableColor::Black => base,
+        SerializableColor::Red => base + 1,
+        SerializableColor::Green => base + 2,
+        SerializableColor::Yellow => base + 3,
+        SerializableColor::Blue => base + 4,
+        SerializableColor::Magenta => base + 5,
+        SerializableColor::Cyan => base + 6,
+        SerializableColor::Gray => base + 7,
+        SerializableColor::DarkGray => bright,
+        SerializableColor::LightRed => bright + 1,
+        SerializableColor::LightGreen => bright + 2,
+        SerializableColor::LightYellow => bright + 3,
+        SerializableColor::LightBlue => bright + 4,
+        SerializableColor::LightMagenta => bright + 5,
+        SerializableColor::LightCyan => bright + 6,
+        SerializableColor::White => bright + 7,
+        SerializableColor::Indexed(i) => return Some(format!("{};5;{}", extended, i)),
+        SerializableColor::Rgb { r, g, b } => {
+            return Some(format!("{};2;{};{};{}", extended, r, g, b))
+        }
+    };
+    Some(code.to_string())
+}
+
+/// Encode an event as the bytes a terminal would send.
+///
+/// Returns `None` for events with no terminal representation.
+pub fn encode_event(event: &Event) -> Option<String> {
+    match event {
+        Event::Key(key) => encode_key(key),
+        Event::Mouse(mouse) => encode_mouse(mouse),
+        Event::Paste(text) => Some(format!("\x1b[200~{}\x1b[201~", text)),
+        Event::FocusGained => Some("\x1b[I".to_string()),
+        Event::FocusLost => Some("\x1b[O".to_string()),
+        Event::Resize(..) => None,
+    }
+}
+
+fn encode_key(key: &KeyEvent) -> Option<String> {
+    let mods = key.modifiers;
+    let alt = if mods.contains(KeyModifiers::ALT) { "\x1b" } else { "" };
+    let param = modifier_param(mods);
+
+    let seq = match key.code {
+        KeyCode::Char(c) => {
+            if mods.contains(KeyModifiers::CONTROL) {
+                if let Some(byte) = control_byte(c) {
+                    return Some(format!("{}{}", alt, byte as char));
+                }
+            }
+            let c = if mods.contains(KeyModifiers::SHIFT) {
+                c.to_uppercase().next().unwrap_or(c)
+            } else {
+                c
+            };
+            format!("{}{}", alt, c)
+        }
+        KeyCode::Enter => format!("{}\r", alt),
+        KeyCode::Tab if mods.contains(KeyModifiers::SHIFT) => "\x1b[Z".to_string(),
+        KeyCode::Tab => format!("{}\t", alt),
+        KeyCode::BackTab => "\x1b[Z".to_string(),
+        KeyCode::Backspace => format!("{}\x7f", alt),
+        KeyCode::Esc => "\x1b".to_string(),
+        KeyCode::Up => cursor_key('A', param),
+        KeyCode::Down => cursor_key('B', param),
+        KeyCode::Right => cursor_key('C', param),
+        KeyCode::Left => cursor_key('D', param),
+        KeyCode::Home => cursor_key('H', param),
+        KeyCode::End => cursor_key('F', param),
+        KeyCode::Insert => tilde_key(2, param),
+        KeyCode::Delete => tilde_key(3, param),
+        KeyCode::PageUp => tilde_key(5, param),
+        KeyCode::PageDown => tilde_key(6, param),
+        KeyCode::F(n @ 1..=4) => {
+            let final_char = (b'P' + n - 1) as char;
+            match param {
+                Some(p) => format!("\x1b[1;{}{}", p, final_char),
+                None => format!("\x1bO{}", final_char),
+            }
+        }
+        KeyCode::F(n) => {
+            let code = match n {
+                5 => 15,
+                6 => 17,
+                7 => 18,
+                8 => 19,
+                9 => 20,
+                10 => 21,
+                11 => 23,
+                12 => 24,
+                _ => return None,
+            };
+            tilde_key(code, param)
+        }
+        _ => return None,
+    };
+
+    Some(seq)
+}
+
+fn control_byte(c: char) -> Option<u8> {
+    match c {
+        'a'..='z' => Some(c as u8 - b'a' + 1),
+        'A'..='Z' => Some(c as u8 - b'A' + 1),
+        ' ' | '@' => Some(0),
+        '\\' => Some(0x1c),
+        ']' => Some(0x1d),
+        '^' => Some(0x1e),
+        '_' => Some(0x1f),
+        _ => None,
+    }
+}
+
+/// xterm modifier parameter (`1 + shift + 2*alt + 4*ctrl`), if any modifier is held.
+fn modifier_param(mods: KeyModifiers) -> Option<u8> {
+    let mut value = 0;
+    if mods.contains(KeyModifiers::SHIFT) {
+        value |= 1;
+    }
+    if mods.contains(KeyModifiers::ALT) {
+        value |= 2;
+    }
+    if mods.contains(KeyModifiers::CONTROL) {
+        value |= 4;
+    }
+    (value != 0).then_some(value + 1)
+}
+
+fn modifiers_from_param(param: u8) -> KeyModifiers {
+    let value = param.saturating_sub(1);
+    let mut mods = KeyModifiers::NONE;
+    if value & 1 != 0 {
+        mods |= KeyModifiers::SHIFT;
+    }
+    if value & 2 != 0 {
+        mods |= KeyModifiers::ALT;
+    }
+    if value & 4 != 0 {
+        mods |= KeyModifiers::CONTROL;
+    }
+    mods
+}
+
+fn cursor_key(final_char: char, param: Option<u8>) -> String {
+    match param {
+        Some(p) => format!("\x1b[1;{}{}", p, final_char),
+        None => format!("\x1b[{}", final_char),
+    }
+}
+
+fn tilde_key(code: u8, param: Option<u8>) -> String {
+    match param {
+        Some(p) => format!("\x1b[{};{}~", code, p),
+        None => format!("\x1b[{}~", code),
+    }
+}
+
+/// Encode a mouse event using SGR (1006) mouse reporting.
+fn encode_mouse(mouse: &MouseEvent) -> Option<String> {
+    let (mut code, release) = match mouse.kind {
+        MouseEventKind::Down(button) => (button_code(button), false),
+        MouseEventKind::Up(button) => (button_code(button), true),
+        MouseEventKind::Drag(button) => (button_code(button) + 32, false),
+        MouseEventKind::Moved => (35, false),
+        MouseEventKind::ScrollUp => (64, false),
+        MouseEventKind::ScrollDown => (65, false),
+        MouseEventKind::ScrollLeft => (66, false),
+        MouseEventKind::ScrollRight => (67, false),
+    };
+    if mouse.modifiers.contains(KeyModifiers::SHIFT) {
+        code += 4;
+    }
+    if mouse.modifiers.contains(KeyModifiers::ALT) {
+        code += 8;
+    }
+    if mouse.modifiers.contains(KeyModifiers::CONTROL) {
+        code += 16;
+    }
+
+    Some(format!(
+        "\x1b[<{};{};{}{}",
+        code,
+        u32::from(mouse.column) + 1,
+        u32::from(mouse.row) + 1,
+        if release { 'm' } else { 'M' }
+    ))
+}
+
+fn button_code(button: MouseButton) -> u32 {
+    match button {
+        MouseButton::Left => 0,
+        MouseButton::Middle => 1,
+        MouseButton::Right => 2,
+    }
+}
+
+/// Decode terminal input bytes into events.
+///
+/// Understands the sequences produced by common terminals: control
+/// characters, Alt as an Escape prefix, xterm cursor and function keys with
+/// modifier parameters, SGR mouse reports and bracketed paste.
+pub fn decode_input(data: &str) -> Vec<Event> {
+    let chars: Vec<char> = data.chars().collect();
+    let mut events = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (event, consumed) = decode_one(&chars[i..]);
+        if let Some(event) = event {
+            events.push(event);
+        }
+        i += consumed.max(1);
+    }
+
+    events
+}
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> Option<Event> {
+    Some(Event::Key(KeyEvent::new(code, modifiers)))
+}
+
+/// Decode one event from the start of `chars`, returning it and the chars consumed.
+fn decode_one(chars: &[char]) -> (Option<Event>, usize) {
+    match chars[0] {
+        '\x1b' => match chars.get(1) {
+            None => (key(KeyCode::Esc, KeyModifiers::NONE), 1),
+            Some('[') => decode_csi(chars),
+            Some('O') if chars.len() > 2 => (decode_ss3(chars[2]), 3),
+            Some('\x1b') => (key(KeyCode::Esc, KeyModifiers::ALT), 2),
+            Some(_) => {
+                // Alt is sent as an Escape prefix
+                let (event, consumed) = decode_one(&chars[1..]);
+                let event = event.map(|event| match event {
+                    Event::Key(mut k) => {
+                        k.modifiers |= KeyModifiers::ALT;
+                        Event::Key(k)
+                    }
+                    other => other,
+                });
+                (event, consumed + 1)
+            }
+        },
+        '\r' | '\n' => (key(KeyCode::Enter, KeyModifiers::NONE), 1),
+        '\t' => (key(KeyCode::Tab, KeyModifiers::NONE), 1),
+        '\x7f' | '\x08' => (key(KeyCode::Backspace, KeyModifiers::NONE), 1),
+        '\0' => (key(KeyCode::Char(' '), KeyModifiers::CONTROL), 1),
+        c @ '\x01'..='\x1a' => (
+            key(KeyCode::Char((c as u8 - 1 + b'a') as char), KeyModifiers::CONTROL),
+            1,
+        ),
+        c @ '\x1c'..='\x1f' => (
+            key(KeyCode::Char((c as u8 - 0x1c + b'\\') as char), KeyModifiers::CONTROL),
+            1,
+        ),
+        c if c.is_uppercase() => (key(KeyCode::Char(c), KeyModifiers::SHIFT), 1),
+        c => (key(KeyCode::Char(c), KeyModifiers::NONE), 1),
+    }
+}
+
+fn decode_ss3(final_char: char) -> Option<Event> {
+    let code = match final_char {
+        'P' => KeyCode::F(1),
+        'Q' => KeyCode::F(2),
+        'R' => KeyCode::F(3),
+        'S' => KeyCode::F(4),
+        'A' => KeyCode::Up,
+        'B' => KeyCode::Down,
+        'C' => KeyCode::Right,
+        'D' => KeyCode::Left,
+        'H' => KeyCode::Home,
+        'F' => KeyCode::End,
+        _ => return None,
+    };
+    key(code, KeyModifiers::NONE)
+}
+
+fn decode_csi(chars: &[char]) -> (Option<Event>, usize) {
+    // Find the final byte
+    let Some(end) = chars[2..]
+        .iter()
+        .position(|c| ('\x40'..='\x7e').contains(c))
+        .map(|p| p + 2)
+    else {
+        return (None, chars.len());
+    };
+
+    let params: String = chars[2..end].iter().collect();
+    let final_char = chars[end];
+    let consumed = end + 1;
+
+    if params == "200" && final_char == '~' {
+        return decode_paste(chars, consumed);
+    }
+
+    if let Some(mouse) = params.strip_prefix('<') {
+        return (decode_sgr_mouse(mouse, final_char), consumed);
+    }
+
+    let values: Vec<u8> = params.split(';').filter_map(|p| p.parse().ok()).collect();
+    let mods = values
+        .get(1)
+        .copied()
+        .map(modifiers_from_param)
+        .unwrap_or(KeyModifiers::NONE);
+
+    let code = match final_char {
+        'A' => KeyCode::Up,
+        'B' => KeyCode::Down,
+        'C' => KeyCode::Right,
+        'D' => KeyCode::Left,
+        'H' => KeyCode::Home,
+        'F' => KeyCode::End,
+        'P' => KeyCode::F(1),
+        'Q' => KeyCode::F(2),
+        'R' => KeyCode::F(3),
+        'S' => KeyCode::F(4),
+        'Z' => return (key(KeyCode::BackTab, KeyModifiers::SHIFT), consumed),
+        'I' => return (Some(Event::FocusGained), consumed),
+        'O' => return (Some(Event::FocusLost), consumed),
+        '~' => match values.first().copied().unwrap_or_default() {
+            1 | 7 => KeyCode::Home,
+            2 => KeyCode::Insert,
+            3 => KeyCode::Delete,
+            4 | 8 => KeyCode::End,
+            5 => KeyCode::PageUp,
+            6 => KeyCode::PageDown,
+            15 => KeyCode::F(5),
+            17 => KeyCode::F(6),
+            18 => KeyCode::F(7),
+            19 => KeyCode::F(8),
+            20 => KeyCode::F(9),
+            21 => KeyCode::F(10),
+            23 => KeyCode::F(11),
+            24 => KeyCode::F(12),
+            _ => return (None, consumed),
+        },
+        _ => return (None, consumed),
+    };
+
+    (key(code, mods), consumed)
+}
+
+fn decode_paste(chars: &[char], start: usize) -> (Option<Event>, usize) {
+    const END: [char; 6] = ['\x1b', '[', '2', '0', '1', '~'];
+    let rest = &chars[start..];
+    let end = rest.windows(END.len()).position(|w| w == END).unwrap_or(rest.len());
+    let text: String = rest[..end].iter().collect();
+    let consumed = (start + end + END.len()).min(chars.len());
+    (Some(Event::Paste(text)), consumed)
+}
+
+fn decode_sgr_mouse(params: &str, final_char: char) -> Option<Event> {
+    let values: Vec<u32> = params.split(';').filter_map(|p| p.parse().ok()).collect();
+    let [code, column, row] = values[..] else {
+        return None;
+    };
+
+    let mut modifiers = KeyModifiers::NONE;
+    if code & 4 != 0 {
+        modifiers |= KeyModifiers::SHIFT;
+    }
+    if code & 8 != 0 {
+        modifiers |= KeyModifiers::ALT;
+    }
+    if code & 16 != 0 {
+        modifiers |= KeyModifiers::CONTROL;
+    }
+
+    let button = match code & 3 {
+        0 => MouseButton::Left,
+        1 => MouseButton::Middle,
+        _ => MouseButton::Right,
+    };
+    let base = code & !(4 | 8 | 16);
+    let kind = match base {
+        64 => MouseEventKind::ScrollUp,
+        65 => MouseEventKind::ScrollDown,
+        66 => MouseEventKind::ScrollLeft,
+        67 => MouseEventKind::ScrollRight,
+        35 => MouseEventKind::Moved,
+        32..=34 => MouseEventKind::Drag(button),
+        _ if final_char == 'm' => MouseEventKind::Up(button),
+        _ => MouseEventKind::Down(button),
+    };
+
+    Some(Event::Mouse(MouseEvent {
+        kind,
+        column: column.saturating_sub(1) as u16,
+        row: row.saturating_sub(1) as u16,
+        modifiers,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::{Color, Style};
+    use ratatui::widgets::Paragraph;
+
+    #[test]
+    fn test_key_encoding_round_trip() {
+        let keys = [
+            KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('S'), KeyModifiers::SHIFT),
+            KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Char('x'), KeyModifiers::ALT),
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Up, KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::PageDown, KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::F(2), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::F(5), KeyModifiers::SHIFT),
+        ];
+
+        for k in keys {
+            let encoded = encode_event(&Event::Key(k)).unwrap();
+            assert_eq!(decode_input(&encoded), vec![Event::Key(k)], "{:?}", encoded);
+        }
+
+        let back_tab = encode_event(&Event::Key(KeyEvent::new(KeyCode::Tab, KeyModifiers::SHIFT)));
+        assert_eq!(back_tab.as_deref(), Some("\x1b[Z"));
+    }
+
+    #[test]
+    fn test_decode_mouse_and_paste() {
+        let click = Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 4,
+            row: 2,
+            modifiers: KeyModifiers::NONE,
+        });
+        let encoded = encode_event(&click).unwrap();
+        assert_eq!(encoded, "\x1b[<0;5;3M");
+        assert_eq!(decode_input(&encoded), vec![click]);
+
+        let events = decode_input("\x1b[200~hello\x1b[201~q");
+        assert_eq!(events[0], Event::Paste("hello".to_string()));
+        assert_eq!(events[1], Event::Key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)));
+    }
+
+    #[test]
+    fn test_terminal_export() {
+        let mut terminal = TestTerminal::new(12, 2);
+        terminal.draw(|frame| {
+            frame.render_widget(Paragraph::new("start"), frame.area());
+        });
+        terminal.capture_frame();
+
+        let mut input = InputSequence::new();
+        input.delay(200).ctrl('s');
+        terminal.record_sequence(&input);
+        terminal.draw(|frame| {
+            let para = Paragraph::new("saved").style(Style::default().fg(Color::Green));
+            frame.render_widget(para, frame.area());
+        });
+        terminal.capture_frame();
+
+        let cast = terminal.to_cast();
+        assert_eq!((cast.header.width, cast.header.height), (12, 2));
+
+        let kinds: Vec<_> = cast.events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![CastEventKind::Output, CastEventKind::Input, CastEventKind::Output]
+        );
+        assert!(cast.events[1].time >= 0.2);
+        assert!(cast.events[2].time >= cast.events[1].time + 0.05);
+        assert_eq!(cast.events[1].data, "\x13");
+        assert!(cast.events[0].data.contains("start"));
+        assert!(cast.events[2].data.contains("\x1b[0;32msaved"));
+    }
+
+    #[test]
+    fn test_cast_file_round_trip() {
+        let mut cast = CastRecording::new(80, 24).with_title("repro");
+        cast.events.push(CastEvent::new(0.5, CastEventKind::Input, "j"));
+        cast.events.push(CastEvent::new(0.6, CastEventKind::Output, "\x1b[H"));
+        cast.events.push(CastEvent::new(1.25, CastEventKind::Input, "\x1b[A"));
+        cast.add_marker(1.0, "before up");
+
+        let text = cast.to_cast_string();
+        let header = text.lines().next().unwrap();
+        assert!(header.contains("\"version\":2") && header.contains("\"title\":\"repro\""));
+        assert_eq!(text.lines().count(), 5);
+
+        let parsed = CastRecording::parse(&text).unwrap();
+        assert_eq!(parsed, cast);
+        assert_eq!(parsed.events_of(CastEventKind::Marker).count(), 1);
+
+        let seq = parsed.to_input_sequence();
+        let items: Vec<_> = seq.iter().collect();
+        assert_eq!(items.len(), 3);
+        assert!(matches!(items[1], SequenceItem::Delay(d) if d == Duration::from_millis(750)));
+        assert_eq!(seq.key_events()[1], KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn test_input_sequence_cast_events() {
+        let mut seq = InputSequence::new();
+        seq.text("ab").delay(100).enter();
+
+        let events = seq.to_cast_events();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2].time, 0.1);
+        assert_eq!(events[2].data, "\r");
+
+        let mut cast = CastRecording::new(80, 24);
+        cast.events = events;
+        let replayed = InputSequence::from_cast(&cast.to_cast_string()).unwrap();
+        assert_eq!(replayed.key_events(), seq.key_events());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(CastRecording::parse("").is_err());
+        assert!(CastRecording::parse("{\"version\":1,\"width\":80,\"height\":24}").is_err());
+        assert!(CastRecording::parse("{\"version\":2,\"width\":80,\"height\":24}\n[0.1,\"o\"]").is_err());
+
+        // Unknown event codes are ignored
+        let cast = CastRecording::parse("{\"version\":2,\"width\":80,\"height\":24}\n[0.1,\"x\",\"?\"]").unwrap();
+        assert!(cast.events.is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 //! Input simulation for testing.
 
+use crate::{TestError, TestResult};
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use std::time::Duration;
 
@@ -50,6 +51,89 @@ impl InputSequence {
         self
     }
 
+    /// Add an arbitrary event.
+    pub fn event(&mut self, event: Event) -> &mut Self {
+        self.events.push(InputItem::Event(event));
+        self
+    }
+
+    /// Add a key event parsed from notation like `ctrl+s`, `enter` or `f5`.
+    pub fn key_str(&mut self, notation: &str) -> TestResult<&mut Self> {
+        let key = parse_key(notation)
+            .ok_or_else(|| TestError::InvalidRecording(format!("unknown key: {}", notation)))?;
+        Ok(self.key_mod(key.code, key.modifiers))
+    }
+
+    /// Parse a key log: one key per line in `key_str` notation.
+    ///
+    /// Lines may start with a timestamp in seconds (`1.250 ctrl+s`), which
+    /// becomes a delay from the previous line. `delay <ms>` adds a delay
+    /// explicitly. Blank lines and `#` comments are skipped.
+    pub fn from_key_log(log: &str) -> TestResult<Self> {
+        let mut seq = Self::new();
+        let mut last_time: Option<f64> = None;
+
+        for (number, line) in log.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let mut first = parts.next().unwrap_or_default();
+
+            if let Ok(time) = first.parse::<f64>() {
+                if let Some(last) = last_time {
+                    let ms = ((time - last) * 1000.0).round();
+                    if ms > 0.0 {
+                        seq.delay(ms as u64);
+                    }
+                }
+                last_time = Some(time);
+                first = match parts.next() {
+                    Some(key) => key,
+                    None => continue,
+                };
+            }
+
+            if first == "delay" {
+                let ms = parts
+                    .next()
+                    .and_then(|ms| ms.parse().ok())
+                    .ok_or_else(|| TestError::InvalidRecording(format!("line {}: bad delay", number + 1)))?;
+                seq.delay(ms);
+                continue;
+            }
+
+            let key = parse_key(first).ok_or_else(|| {
+                TestError::InvalidRecording(format!("line {}: unknown key: {}", number + 1, first))
+            })?;
+            seq.key_mod(key.code, key.modifiers);
+        }
+
+        Ok(seq)
+    }
+
+    /// Write the key events and delays as a key log.
+    ///
+    /// Mouse and other non-key events are skipped.
+    pub fn to_key_log(&self) -> String {
+        let mut log = String::new();
+        for item in &self.events {
+            match item {
+                InputItem::Event(Event::Key(key)) => {
+                    log.push_str(&format_key(key));
+                    log.push('\n');
+                }
+                InputItem::Delay(delay) => {
+                    log.push_str(&format!("delay {}\n", delay.as_millis()));
+                }
+                InputItem::Event(_) => {}
+            }
+        }
+        log
+    }
+
     /// Add a character key event.
     pub fn char(&mut self, c: char) -> &mut Self {
         self.key(KeyCode::Char(c))
@@ -288,6 +372,97 @@ impl InputSequence {
     }
 }
 
+/// Parse key notation like `ctrl+shift+tab`, `enter`, `f5` or `x`.
+pub fn parse_key(notation: &str) -> Option<KeyEvent> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut parts: Vec<&str> = notation.split('+').collect();
+
+    // A trailing '+' means the plus key itself, e.g. "ctrl++"
+    let key = if notation.ends_with("++") || notation == "+" {
+        parts.truncate(parts.len().saturating_sub(2));
+        "+"
+    } else {
+        parts.pop()?
+    };
+
+    for part in parts {
+        match part.to_lowercase().as_str() {
+            "ctrl" | "control" | "c" => modifiers |= KeyModifiers::CONTROL,
+            "alt" | "meta" | "m" | "a" => modifiers |= KeyModifiers::ALT,
+            "shift" | "s" => modifiers |= KeyModifiers::SHIFT,
+            _ => return None,
+        }
+    }
+
+    let code = match key.to_lowercase().as_str() {
+        "enter" | "return" => KeyCode::Enter,
+        "esc" | "escape" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" | "bs" => KeyCode::Backspace,
+        "delete" | "del" => KeyCode::Delete,
+        "insert" | "ins" => KeyCode::Insert,
+        "space" => KeyCode::Char(' '),
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" | "pgup" => KeyCode::PageUp,
+        "pagedown" | "pgdn" => KeyCode::PageDown,
+        name if name.len() > 1 && name.starts_with('f') => KeyCode::F(name[1..].parse().ok()?),
+        _ => {
+            let mut chars = key.chars();
+            let c = chars.next()?;
+            if chars.next().is_some() {
+                return None;
+            }
+            KeyCode::Char(c)
+        }
+    };
+
+    Some(KeyEvent::new(code, modifiers))
+}
+
+/// Format a key event in the notation accepted by [`parse_key`].
+pub fn format_key(key: &KeyEvent) -> String {
+    let mut parts = Vec::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        parts.push("ctrl".to_string());
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        parts.push("alt".to_string());
+    }
+    if key.modifiers.contains(KeyModifiers::SHIFT) {
+        parts.push("shift".to_string());
+    }
+
+    parts.push(match key.code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::BackTab => "backtab".to_string(),
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::Delete => "delete".to_string(),
+        KeyCode::Insert => "insert".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        KeyCode::Home => "home".to_string(),
+        KeyCode::End => "end".to_string(),
+        KeyCode::PageUp => "pageup".to_string(),
+        KeyCode::PageDown => "pagedown".to_string(),
+        KeyCode::F(n) => format!("f{}", n),
+        other => format!("{:?}", other).to_lowercase(),
+    });
+
+    parts.join("+")
+}
+
 /// An item yielded by the sequence iterator.
 #[derive(Debug, Clone)]
 pub enum SequenceItem<'a> {
@@ -429,6 +604,32 @@ mod tests {
         assert_eq!(seq1.len(), 4);
     }
 
+    #[test]
+    fn test_key_log_round_trip() {
+        let mut seq = InputSequence::new();
+        seq.ctrl('s').delay(250).text("a ").key_mod(KeyCode::Tab, KeyModifiers::SHIFT).f(5);
+
+        let log = seq.to_key_log();
+        assert_eq!(log, "ctrl+s\ndelay 250\na\nspace\nshift+tab\nf5\n");
+
+        let parsed = InputSequence::from_key_log(&log).unwrap();
+        assert_eq!(parsed.key_events(), seq.key_events());
+        assert!(matches!(parsed.iter().nth(1), Some(SequenceItem::Delay(d)) if d == Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn test_key_log_timestamps() {
+        let log = "# reproduction from issue\n0.000 j\n0.500 j\n\n1.000 ctrl++\n";
+        let seq = InputSequence::from_key_log(log).unwrap();
+
+        let items: Vec<_> = seq.iter().collect();
+        assert_eq!(items.len(), 5);
+        assert!(matches!(items[1], SequenceItem::Delay(d) if d == Duration::from_millis(500)));
+        assert_eq!(seq.key_events()[2], KeyEvent::new(KeyCode::Char('+'), KeyModifiers::CONTROL));
+
+        assert!(InputSequence::from_key_log("hyper+x").is_err());
+    }
+
     #[test]
     fn test_repeat() {
         let mut seq = InputSequence::new();
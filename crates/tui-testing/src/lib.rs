@@ -6,7 +6,9 @@
 //! - Async test harnesses for multiple runtimes
 //! - Property-based testing generators
 //! - Deterministic fixtures and random data generators
+//! - Asciicast v2 export of frame history and import of recorded input
 
+pub mod cast;
 pub mod ci;
 pub mod diff;
 pub mod fixtures;
@@ -29,13 +31,14 @@ pub mod smol_harness;
 pub mod generators;
 
 // Re-exports
+pub use cast::{CastEvent, CastEventKind, CastHeader, CastRecording};
 pub use ci::{is_ci, should_update_snapshots, CiConfig};
 pub use diff::{CellChanges, CellDiff, SnapshotDiff};
 pub use fixtures::Fixtures;
 pub use golden::{CleanupReport, CoverageReport, GoldenFiles};
 pub use input::InputSequence;
 pub use snapshot::{BufferSnapshot, CapturedFrame, CellSnapshot, SnapshotTest};
-pub use terminal::TestTerminal;
+pub use terminal::{RecordedInput, TestTerminal};
 
 #[cfg(feature = "tokio-harness")]
 pub use tokio_harness::TokioTestHarness;
@@ -72,6 +75,9 @@ pub enum TestError {
 
     #[error("Terminal error: {0}")]
     Terminal(String),
+
+    #[error("Invalid recording: {0}")]
+    InvalidRecording(String),
 }
 
 /// Result type for testing operations.
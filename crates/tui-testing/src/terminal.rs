@@ -1,15 +1,37 @@
 //! Virtual terminal for headless testing.
 
+use crate::cast::CastRecording;
+use crate::input::{InputSequence, SequenceItem};
 use crate::snapshot::BufferSnapshot;
+use crate::TestResult;
+use crossterm::event::Event;
 use ratatui::backend::TestBackend;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::Terminal;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// An input event recorded alongside the frame history.
+#[derive(Debug, Clone)]
+pub struct RecordedInput {
+    /// Time since the terminal was created.
+    pub time: Duration,
+    /// The event.
+    pub event: Event,
+}
 
 /// A virtual terminal for testing TUI applications.
 pub struct TestTerminal {
     terminal: Terminal<TestBackend>,
     frame_history: Vec<BufferSnapshot>,
+    /// Capture time of each frame in `frame_history`.
+    frame_times: Vec<Duration>,
+    /// Inputs recorded with `record_input`.
+    inputs: Vec<RecordedInput>,
+    started: Instant,
+    /// Simulated time added by `advance`.
+    offset: Duration,
 }
 
 impl TestTerminal {
@@ -20,6 +42,10 @@ impl TestTerminal {
         Self {
             terminal,
             frame_history: Vec::new(),
+            frame_times: Vec::new(),
+            inputs: Vec::new(),
+            started: Instant::now(),
+            offset: Duration::ZERO,
         }
     }
 
@@ -139,9 +165,56 @@ impl TestTerminal {
     pub fn capture_frame(&mut self) -> usize {
         let snapshot = self.snapshot();
         self.frame_history.push(snapshot);
+        self.frame_times.push(self.elapsed());
         self.frame_history.len() - 1
     }
 
+    /// Time since the terminal was created, including simulated time.
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed() + self.offset
+    }
+
+    /// Advance the recording clock without sleeping.
+    pub fn advance(&mut self, duration: Duration) {
+        self.offset += duration;
+    }
+
+    /// Record an input event for export with the frame history.
+    pub fn record_input(&mut self, event: Event) {
+        let time = self.elapsed();
+        self.inputs.push(RecordedInput { time, event });
+    }
+
+    /// Record every event in a sequence, advancing the clock for its delays.
+    pub fn record_sequence(&mut self, sequence: &InputSequence) {
+        for item in sequence.iter() {
+            match item {
+                SequenceItem::Event(event) => self.record_input(event.clone()),
+                SequenceItem::Delay(delay) => self.advance(delay),
+            }
+        }
+    }
+
+    /// Get the recorded inputs.
+    pub fn inputs(&self) -> &[RecordedInput] {
+        &self.inputs
+    }
+
+    /// Get the capture time of each frame in history.
+    pub fn frame_times(&self) -> &[Duration] {
+        &self.frame_times
+    }
+
+    /// Export the frame history and recorded inputs as an asciicast recording.
+    pub fn to_cast(&self) -> CastRecording {
+        CastRecording::from_terminal(self)
+    }
+
+    /// Write the frame history and recorded inputs to an asciicast v2 file.
+    pub fn save_cast(&self, path: impl AsRef<Path>) -> TestResult<()> {
+        self.to_cast().save(path)
+    }
+
     /// Get a frame from history.
     pub fn frame(&self, index: usize) -> Option<&BufferSnapshot> {
         self.frame_history.get(index)
@@ -157,9 +230,11 @@ impl TestTerminal {
         &self.frame_history
     }
 
-    /// Clear the frame history.
+    /// Clear the frame history and recorded inputs.
     pub fn clear_history(&mut self) {
         self.frame_history.clear();
+        self.frame_times.clear();
+        self.inputs.clear();
     }
 
     /// Resize the terminal.
@@ -170,6 +170,38 @@ impl Color {
             _ => None,
         }
     }
+
+    /// Replace the RGB value, keeping the hex or RGB representation.
+    ///
+    /// Indexed, named and empty colors are returned unchanged.
+    pub fn with_rgb(&self, (r, g, b): (u8, u8, u8)) -> Self {
+        match self {
+            Self::Hex(_) => Self::Hex(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+            Self::Rgb { .. } => Self::Rgb { r, g, b },
+            other => other.clone(),
+        }
+    }
+
+    /// WCAG relative luminance (0.0-1.0), for RGB colors.
+    pub fn relative_luminance(&self) -> Option<f32> {
+        self.to_rgb().map(luminance)
+    }
+
+    /// WCAG contrast ratio (1.0-21.0) against another color, if both are RGB.
+    pub fn contrast_ratio(&self, other: &Color) -> Option<f32> {
+        Some(contrast_from_luminance(
+            self.relative_luminance()?,
+            other.relative_luminance()?,
+        ))
+    }
+
+    /// Daltonize this color to stay distinguishable under a color vision deficiency.
+    pub fn daltonize(&self, mode: crate::ColorblindMode) -> Self {
+        match self.to_rgb() {
+            Some(rgb) => self.with_rgb(daltonize_rgb(rgb, mode)),
+            None => self.clone(),
+        }
+    }
 }
 
 impl Default for Color {
@@ -297,22 +329,305 @@ pub struct ColorSet {
     pub flash_error: ColorToken,
 }
 
+/// Names of all tokens in a [`ColorSet`], in declaration order.
+pub const TOKEN_NAMES: &[&str] = &[
+    "bg_primary",
+    "bg_secondary",
+    "bg_tertiary",
+    "bg_hover",
+    "bg_focused",
+    "bg_pressed",
+    "bg_disabled",
+    "fg_primary",
+    "fg_secondary",
+    "fg_muted",
+    "fg_disabled",
+    "accent",
+    "accent_secondary",
+    "accent_hover",
+    "accent_focused",
+    "success",
+    "warning",
+    "error",
+    "info",
+    "border",
+    "border_focused",
+    "border_error",
+    "flash_success",
+    "flash_error",
+];
+
+/// Foreground/background pairs that must stay readable, in priority order.
+pub const CONTRAST_PAIRS: &[(&str, &str)] = &[
+    ("fg_primary", "bg_primary"),
+    ("fg_primary", "bg_secondary"),
+    ("fg_secondary", "bg_primary"),
+    ("fg_secondary", "bg_secondary"),
+    ("fg_muted", "bg_primary"),
+    ("accent", "bg_primary"),
+    ("success", "bg_primary"),
+    ("warning", "bg_primary"),
+    ("error", "bg_primary"),
+    ("info", "bg_primary"),
+    ("fg_primary", "bg_hover"),
+    ("fg_primary", "bg_focused"),
+];
+
+/// A foreground/background pair below the required contrast ratio.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContrastFailure {
+    /// Foreground token name
+    pub foreground: &'static str,
+    /// Background token name
+    pub background: &'static str,
+    /// Measured contrast ratio
+    pub ratio: f32,
+}
+
 impl ColorSet {
+    /// Get a token by name.
+    pub fn token(&self, name: &str) -> Option<&ColorToken> {
+        let token = match name {
+            "bg_primary" => &self.bg_primary,
+            "bg_secondary" => &self.bg_secondary,
+            "bg_tertiary" => &self.bg_tertiary,
+            "bg_hover" => &self.bg_hover,
+            "bg_focused" => &self.bg_focused,
+            "bg_pressed" => &self.bg_pressed,
+            "bg_disabled" => &self.bg_disabled,
+            "fg_primary" => &self.fg_primary,
+            "fg_secondary" => &self.fg_secondary,
+            "fg_muted" => &self.fg_muted,
+            "fg_disabled" => &self.fg_disabled,
+            "accent" => &self.accent,
+            "accent_secondary" => &self.accent_secondary,
+            "accent_hover" => &self.accent_hover,
+            "accent_focused" => &self.accent_focused,
+            "success" => &self.success,
+            "warning" => &self.warning,
+            "error" => &self.error,
+            "info" => &self.info,
+            "border" => &self.border,
+            "border_focused" => &self.border_focused,
+            "border_error" => &self.border_error,
+            "flash_success" => &self.flash_success,
+            "flash_error" => &self.flash_error,
+            _ => return None,
+        };
+        Some(token)
+    }
+
+    /// Get a mutable token by name.
+    pub fn token_mut(&mut self, name: &str) -> Option<&mut ColorToken> {
+        let token = match name {
+            "bg_primary" => &mut self.bg_primary,
+            "bg_secondary" => &mut self.bg_secondary,
+            "bg_tertiary" => &mut self.bg_tertiary,
+            "bg_hover" => &mut self.bg_hover,
+            "bg_focused" => &mut self.bg_focused,
+            "bg_pressed" => &mut self.bg_pressed,
+            "bg_disabled" => &mut self.bg_disabled,
+            "fg_primary" => &mut self.fg_primary,
+            "fg_secondary" => &mut self.fg_secondary,
+            "fg_muted" => &mut self.fg_muted,
+            "fg_disabled" => &mut self.fg_disabled,
+            "accent" => &mut self.accent,
+            "accent_secondary" => &mut self.accent_secondary,
+            "accent_hover" => &mut self.accent_hover,
+            "accent_focused" => &mut self.accent_focused,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "info" => &mut self.info,
+            "border" => &mut self.border,
+            "border_focused" => &mut self.border_focused,
+            "border_error" => &mut self.border_error,
+            "flash_success" => &mut self.flash_success,
+            "flash_error" => &mut self.flash_error,
+            _ => return None,
+        };
+        Some(token)
+    }
+
+    /// Contrast ratio between two tokens, if both are RGB colors.
+    pub fn contrast(&self, foreground: &str, background: &str) -> Option<f32> {
+        let fg = self.token(foreground)?;
+        let bg = self.token(background)?;
+        fg.color.contrast_ratio(&bg.color)
+    }
+
+    /// Find the [`CONTRAST_PAIRS`] that fall below `min_ratio`.
+    ///
+    /// Pairs involving indexed or named colors are skipped, since their
+    /// actual values depend on the terminal.
+    pub fn contrast_failures(&self, min_ratio: f32) -> Vec<ContrastFailure> {
+        CONTRAST_PAIRS
+            .iter()
+            .filter_map(|&(foreground, background)| {
+                let ratio = self.contrast(foreground, background)?;
+                (ratio < min_ratio).then_some(ContrastFailure {
+                    foreground,
+                    background,
+                    ratio,
+                })
+            })
+            .collect()
+    }
+
     /// Apply high contrast to this color set.
-    pub fn with_increased_contrast(self, _config: crate::HighContrastConfig) -> Self {
-        // In a full implementation, this would analyze contrast ratios
-        // and boost colors that don't meet the minimum
+    ///
+    /// Each failing pair is pushed apart in steps of `boost_factor * 5%`
+    /// until it meets the minimum ratio. The foreground moves first; once a
+    /// token has been settled by an earlier pair, the other side moves instead.
+    pub fn with_increased_contrast(mut self, config: crate::HighContrastConfig) -> Self {
+        let step = (config.boost_factor * 0.05).clamp(0.01, 1.0);
+        let mut settled: Vec<&str> = Vec::new();
+
+        for &(foreground, background) in CONTRAST_PAIRS {
+            let (adjust, fixed) = if settled.contains(&foreground) {
+                (background, foreground)
+            } else {
+                (foreground, background)
+            };
+
+            if !self.boost_pair(adjust, fixed, config.min_contrast_ratio, step) {
+                // Ran out of room; move the other side as well
+                self.boost_pair(fixed, adjust, config.min_contrast_ratio, step);
+            }
+
+            for name in [foreground, background] {
+                if !settled.contains(&name) {
+                    settled.push(name);
+                }
+            }
+        }
+
         self
     }
 
+    /// Move `adjust` away from `fixed` until their contrast reaches `min_ratio`.
+    ///
+    /// Returns `false` if the target could not be reached.
+    fn boost_pair(&mut self, adjust: &str, fixed: &str, min_ratio: f32, step: f32) -> bool {
+        let (Some(original), Some(fixed_color)) = (
+            self.token(adjust).map(|t| t.color.clone()),
+            self.token(fixed).map(|t| t.color.clone()),
+        ) else {
+            return true;
+        };
+        let (Some(rgb), Some(fixed_lum)) = (original.to_rgb(), fixed_color.relative_luminance())
+        else {
+            return true;
+        };
+
+        let mut ratio = contrast_from_luminance(luminance(rgb), fixed_lum);
+        if ratio >= min_ratio {
+            return true;
+        }
+
+        // Head for whichever extreme gives more room
+        let target = if contrast_from_luminance(1.0, fixed_lum)
+            >= contrast_from_luminance(0.0, fixed_lum)
+        {
+            (255, 255, 255)
+        } else {
+            (0, 0, 0)
+        };
+
+        let mut amount = 0.0;
+        let mut boosted = rgb;
+        while ratio < min_ratio && amount < 1.0 {
+            amount = (amount + step).min(1.0);
+            boosted = mix(rgb, target, amount);
+            ratio = contrast_from_luminance(luminance(boosted), fixed_lum);
+        }
+
+        if let Some(token) = self.token_mut(adjust) {
+            token.color = original.with_rgb(boosted);
+        }
+        ratio >= min_ratio
+    }
+
     /// Apply colorblind filter.
-    pub fn with_colorblind_filter(self, _mode: crate::ColorblindMode) -> Self {
-        // In a full implementation, this would apply daltonization
-        // to transform colors for better colorblind visibility
+    pub fn with_colorblind_filter(mut self, mode: crate::ColorblindMode) -> Self {
+        for name in TOKEN_NAMES {
+            if let Some(token) = self.token_mut(name) {
+                token.color = token.color.daltonize(mode);
+            }
+        }
         self
     }
 }
 
+/// Convert an 8-bit sRGB channel to linear light.
+fn linearize(channel: u8) -> f32 {
+    let c = channel as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// WCAG relative luminance of an sRGB color.
+fn luminance((r, g, b): (u8, u8, u8)) -> f32 {
+    0.2126 * linearize(r) + 0.7152 * linearize(g) + 0.0722 * linearize(b)
+}
+
+/// WCAG contrast ratio between two relative luminances.
+fn contrast_from_luminance(a: f32, b: f32) -> f32 {
+    let (lighter, darker) = if a >= b { (a, b) } else { (b, a) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+/// Linear blend from `from` towards `to` by `amount` (0.0-1.0).
+fn mix(from: (u8, u8, u8), to: (u8, u8, u8), amount: f32) -> (u8, u8, u8) {
+    let blend = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+    (blend(from.0, to.0), blend(from.1, to.1), blend(from.2, to.2))
+}
+
+/// sRGB to LMS cone space.
+const RGB_TO_LMS: [[f32; 3]; 3] = [
+    [17.8824, 43.5161, 4.11935],
+    [3.45565, 27.1554, 3.86714],
+    [0.0299566, 0.184309, 1.46709],
+];
+
+/// LMS cone space back to sRGB.
+const LMS_TO_RGB: [[f32; 3]; 3] = [
+    [0.080_944_45, -0.130_504_41, 0.116_721_07],
+    [-0.010_248_534, 0.054_019_33, -0.113_614_71],
+    [-0.000_365_296_95, -0.004_121_614_7, 0.693_511_4],
+];
+
+/// Shifts the information lost to a deficiency into channels that remain visible.
+const ERROR_SHIFT: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+
+fn mat_mul(m: &[[f32; 3]; 3], v: [f32; 3]) -> [f32; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+/// Daltonize an sRGB color for the given deficiency.
+fn daltonize_rgb((r, g, b): (u8, u8, u8), mode: crate::ColorblindMode) -> (u8, u8, u8) {
+    let simulate: [[f32; 3]; 3] = match mode {
+        crate::ColorblindMode::Protanopia => [[0.0, 2.02344, -2.52581], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        crate::ColorblindMode::Deuteranopia => [[1.0, 0.0, 0.0], [0.494207, 0.0, 1.24827], [0.0, 0.0, 1.0]],
+        crate::ColorblindMode::Tritanopia => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [-0.395913, 0.801109, 0.0]],
+    };
+
+    let rgb = [r as f32, g as f32, b as f32];
+    let simulated = mat_mul(&LMS_TO_RGB, mat_mul(&simulate, mat_mul(&RGB_TO_LMS, rgb)));
+    let error = [rgb[0] - simulated[0], rgb[1] - simulated[1], rgb[2] - simulated[2]];
+    let shift = mat_mul(&ERROR_SHIFT, error);
+
+    let channel = |i: usize| (rgb[i] + shift[i]).round().clamp(0.0, 255.0) as u8;
+    (channel(0), channel(1), channel(2))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -348,4 +663,61 @@ mod tests {
         assert!(token.modifiers.contains(Modifier::BOLD));
         assert!(token.modifiers.contains(Modifier::ITALIC));
     }
+
+    #[test]
+    fn test_contrast_ratio() {
+        let black = Color::hex("#000000");
+        let white = Color::rgb(255, 255, 255);
+        assert!((black.contrast_ratio(&white).unwrap() - 21.0).abs() < 0.01);
+        assert!((white.contrast_ratio(&white).unwrap() - 1.0).abs() < 0.01);
+        assert_eq!(Color::named("red").contrast_ratio(&white), None);
+    }
+
+    #[test]
+    fn test_daltonize() {
+        use crate::ColorblindMode;
+
+        // Greys carry no hue information and are left alone
+        let grey = Color::hex("#808080");
+        for mode in [ColorblindMode::Protanopia, ColorblindMode::Deuteranopia, ColorblindMode::Tritanopia] {
+            let (r, g, b) = grey.daltonize(mode).to_rgb().unwrap();
+            assert!(r.abs_diff(128) <= 1 && g.abs_diff(128) <= 1 && b.abs_diff(128) <= 1);
+        }
+
+        let red = Color::hex("#ff0000");
+        let shifted = red.daltonize(ColorblindMode::Protanopia);
+        assert_ne!(shifted, red);
+        assert!(matches!(shifted, Color::Hex(_)));
+
+        let green = Color::rgb(0, 200, 0);
+        assert!(matches!(green.daltonize(ColorblindMode::Deuteranopia), Color::Rgb { .. }));
+
+        assert_eq!(Color::index(9).daltonize(ColorblindMode::Tritanopia), Color::index(9));
+    }
+
+    #[test]
+    fn test_increased_contrast() {
+        let set = ColorSet {
+            bg_primary: ColorToken::new(Color::hex("#1a1b26")),
+            fg_primary: ColorToken::new(Color::hex("#c0caf5")),
+            fg_muted: ColorToken::new(Color::hex("#3b4261")),
+            ..Default::default()
+        };
+        assert_eq!(set.contrast_failures(4.5).len(), 1);
+
+        let boosted = set.with_increased_contrast(crate::HighContrastConfig::default());
+        assert!(boosted.contrast("fg_muted", "bg_primary").unwrap() >= 4.5);
+        assert!(boosted.contrast_failures(4.5).is_empty());
+        // Colors already passing are untouched
+        assert_eq!(boosted.fg_primary.color, Color::hex("#c0caf5"));
+    }
+
+    #[test]
+    fn test_token_names() {
+        let set = ColorSet::default();
+        for name in TOKEN_NAMES {
+            assert!(set.token(name).is_some(), "{}", name);
+        }
+        assert!(set.token("nonexistent").is_none());
+    }
 }
@@ -21,7 +21,10 @@ mod styles;
 mod variant;
 
 pub use animation::{AnimationConfig, EasingFunction};
-pub use colors::{Color, ColorDepth, ColorPalette, ColorSet, ColorToken};
+pub use colors::{
+    Color, ColorDepth, ColorPalette, ColorSet, ColorToken, ContrastFailure, CONTRAST_PAIRS,
+    TOKEN_NAMES,
+};
 pub use manager::{ThemeManager, ValidationResult, ThemeWarning, ThemeError};
 pub use presets::builtin_themes;
 pub use spacing::{Spacing, SPACING_XS, SPACING_SM, SPACING_MD, SPACING_LG, SPACING_XL};
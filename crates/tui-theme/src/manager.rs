@@ -1,6 +1,6 @@
 //! Theme manager for loading and switching themes.
 
-use crate::{Theme, ThemeOverrides, ThemeMeta};
+use crate::{HighContrastConfig, Theme, ThemeOverrides, ThemeMeta};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
@@ -31,6 +31,15 @@ pub enum ThemeWarning {
         path: String,
         replacement: String,
     },
+    /// A foreground/background pair is below the minimum contrast ratio
+    LowContrast {
+        theme: String,
+        palette: String,
+        foreground: String,
+        background: String,
+        ratio: f32,
+        required: f32,
+    },
 }
 
 /// Result of theme validation.
@@ -116,6 +125,9 @@ impl ThemeManager {
                 if path.extension().map_or(false, |ext| ext == "toml") {
                     match self.load_theme_file(&path) {
                         Ok((name, theme)) => {
+                            result.warnings.extend(
+                                Self::validate_theme(&theme, HighContrastConfig::default()).warnings,
+                            );
                             self.user.insert(name, theme);
                         }
                         Err(e) => {
@@ -156,6 +168,45 @@ impl ThemeManager {
         Ok(child)
     }
 
+    /// Check a theme's foreground/background pairs against a contrast minimum.
+    pub fn validate_theme(theme: &Theme, config: HighContrastConfig) -> ValidationResult {
+        let mut result = ValidationResult::default();
+        let palettes = [
+            ("true_color", &theme.colors.true_color),
+            ("color_256", &theme.colors.color_256),
+            ("color_16", &theme.colors.color_16),
+        ];
+
+        for (palette, set) in palettes {
+            for failure in set.contrast_failures(config.min_contrast_ratio) {
+                result.warnings.push(ThemeWarning::LowContrast {
+                    theme: theme.name.clone(),
+                    palette: palette.to_string(),
+                    foreground: failure.foreground.to_string(),
+                    background: failure.background.to_string(),
+                    ratio: failure.ratio,
+                    required: config.min_contrast_ratio,
+                });
+            }
+        }
+
+        result
+    }
+
+    /// Validate contrast for every built-in preset, in name order.
+    pub fn validate_builtin(&self, config: HighContrastConfig) -> ValidationResult {
+        let mut names: Vec<&String> = self.builtin.keys().collect();
+        names.sort();
+
+        let mut result = ValidationResult::default();
+        for name in names {
+            result
+                .warnings
+                .extend(Self::validate_theme(&self.builtin[name], config).warnings);
+        }
+        result
+    }
+
     /// Get a theme by name.
     pub fn get(&self, name: &str) -> Option<&Theme> {
         self.user.get(name).or_else(|| self.builtin.get(name))
@@ -302,6 +353,27 @@ mod tests {
         assert!(manager.set_current("nonexistent").is_err());
     }
 
+    #[test]
+    fn test_validate_builtin() {
+        let manager = ThemeManager::new();
+        let result = manager.validate_builtin(HighContrastConfig::wcag_aaa());
+        assert!(result.is_ok());
+        assert!(result.has_warnings());
+        assert!(result.warnings.iter().all(|w| matches!(
+            w,
+            ThemeWarning::LowContrast { ratio, required, .. } if ratio < required
+        )));
+
+        // Boosting every preset clears its warnings
+        for theme in manager.builtin.values() {
+            for config in [HighContrastConfig::default(), HighContrastConfig::wcag_aaa()] {
+                let boosted = theme.to_high_contrast(config);
+                let result = ThemeManager::validate_theme(&boosted, config);
+                assert!(!result.has_warnings(), "{}: {:?}", theme.name, result.warnings);
+            }
+        }
+    }
+
     #[test]
     fn test_list_themes() {
         let manager = ThemeManager::new();
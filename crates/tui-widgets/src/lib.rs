@@ -11,6 +11,7 @@
 //! - [`TreeView`] - Expandable hierarchical view with lazy loading
 //! - [`FormBuilder`] - Declarative form construction with validation
 //! - [`CommandPalette`] - Fuzzy-search command launcher
+//! - [`WhichKey`] - Continuation hints for pending key sequences
 //!
 //! ## Architecture
 //!
@@ -27,12 +28,14 @@ mod table;
 mod tree;
 mod form;
 mod palette;
+mod which_key;
 
 pub use accessibility::{Accessible, AccessibilityConfig, SoundCue};
 pub use animation::EasingFunction;
 pub use command::{Command, CommandError};
 pub use table::{
-    AggregateFunc, CellContent, Column, ColumnWidth, DataProvider, DataSource, DataTable,
+    AggregateFunc, AsyncDataProvider, CellContent, Column, ColumnWidth, DataProvider, DataSource,
+    DataTable, LoadProgress, LoadTask, Page, PageFuture, PageRequest, PagedProvider, ProviderError,
     Selection, SortDirection, TableState,
 };
 pub use tree::{LoadErrorAction, TreeChildren, TreeNode, TreeState, TreeView};
@@ -40,7 +43,11 @@ pub use form::{
     Field, Form, FormBuilder, FormData, FormState, InputType, RowBuilder, Section, Validator,
     Value,
 };
-pub use palette::{CommandPalette, PaletteState, Parameter};
+pub use palette::{
+    CommandHistory, CommandPalette, CommandUsage, HistoryError, HistoryResult, PaletteState,
+    Parameter,
+};
+pub use which_key::WhichKey;
 
 /// Compact mode setting for widgets
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -0,0 +1,262 @@
+//! Persistent command usage history for CommandPalette.
+//!
+//! Usage counts and last-use times feed a frecency score (frequency times
+//! recency) that is mixed into fuzzy ranking. The last parameter values
+//! entered in the wizard are remembered per command.
+
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+/// Error type for command history.
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    /// Database error
+    #[error("history database error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
+    /// Could not create the data directory
+    #[error("failed to create history directory: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// No data directory for the app
+    #[error("no data directory for {0}")]
+    NoDataDir(String),
+}
+
+/// Result type for command history.
+pub type HistoryResult<T> = Result<T, HistoryError>;
+
+/// Usage of one command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandUsage {
+    /// Number of executions
+    pub count: u32,
+    /// Last execution (Unix seconds)
+    pub last_used: i64,
+}
+
+impl CommandUsage {
+    /// Frecency score at `now` (Unix seconds).
+    ///
+    /// The use count is weighted by how recently the command was last used.
+    pub fn frecency(&self, now: i64) -> f64 {
+        let age = (now - self.last_used).max(0);
+        let weight = match age {
+            a if a < 60 * 60 => 4.0,
+            a if a < 24 * 60 * 60 => 2.0,
+            a if a < 7 * 24 * 60 * 60 => 1.0,
+            a if a < 30 * 24 * 60 * 60 => 0.5,
+            _ => 0.25,
+        };
+        self.count as f64 * weight
+    }
+}
+
+/// Command usage and parameter history backed by SQLite.
+pub struct CommandHistory {
+    conn: Connection,
+    /// Usage by command id, mirrored from the database
+    usage: HashMap<String, CommandUsage>,
+}
+
+impl CommandHistory {
+    /// Open or create the history database at a path.
+    pub fn open(path: &Path) -> HistoryResult<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Open the history for an app under its XDG data directory.
+    pub fn for_app(app_name: &str) -> HistoryResult<Self> {
+        Self::open(&Self::default_path(app_name)?)
+    }
+
+    /// Create an in-memory history (for testing).
+    pub fn in_memory() -> HistoryResult<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    /// Default history path for an app.
+    pub fn default_path(app_name: &str) -> HistoryResult<PathBuf> {
+        directories::ProjectDirs::from("", "", app_name)
+            .map(|d| d.data_dir().join("palette.db"))
+            .ok_or_else(|| HistoryError::NoDataDir(app_name.to_string()))
+    }
+
+    fn init(conn: Connection) -> HistoryResult<Self> {
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS command_usage (
+                command_id TEXT PRIMARY KEY,
+                count INTEGER NOT NULL,
+                last_used INTEGER NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS command_params (
+                command_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                value TEXT NOT NULL,
+                PRIMARY KEY (command_id, name)
+            );
+            "#,
+        )?;
+
+        let usage = {
+            let mut stmt = conn.prepare("SELECT command_id, count, last_used FROM command_usage")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    CommandUsage {
+                        count: row.get(1)?,
+                        last_used: row.get(2)?,
+                    },
+                ))
+            })?;
+            rows.collect::<Result<HashMap<_, _>, _>>()?
+        };
+
+        Ok(Self { conn, usage })
+    }
+
+    /// Record an execution of a command now.
+    pub fn record(&mut self, command_id: &str) -> HistoryResult<()> {
+        self.record_at(command_id, now())
+    }
+
+    /// Record an execution of a command at a given time (Unix seconds).
+    pub fn record_at(&mut self, command_id: &str, at: i64) -> HistoryResult<()> {
+        self.conn.execute(
+            "INSERT INTO command_usage (command_id, count, last_used) VALUES (?1, 1, ?2)
+             ON CONFLICT(command_id) DO UPDATE SET count = count + 1, last_used = ?2",
+            params![command_id, at],
+        )?;
+        let usage = self.usage.entry(command_id.to_string()).or_insert(CommandUsage {
+            count: 0,
+            last_used: at,
+        });
+        usage.count += 1;
+        usage.last_used = at;
+        Ok(())
+    }
+
+    /// Get the usage of a command.
+    pub fn usage(&self, command_id: &str) -> Option<CommandUsage> {
+        self.usage.get(command_id).copied()
+    }
+
+    /// Frecency of a command now (0 if never used).
+    pub fn frecency(&self, command_id: &str) -> f64 {
+        self.frecency_at(command_id, now())
+    }
+
+    /// Frecency of a command at a given time.
+    pub fn frecency_at(&self, command_id: &str, now: i64) -> f64 {
+        self.usage
+            .get(command_id)
+            .map(|u| u.frecency(now))
+            .unwrap_or(0.0)
+    }
+
+    /// Most recently used command ids, newest first.
+    pub fn recent(&self, limit: usize) -> Vec<&str> {
+        let mut recent: Vec<(&String, &CommandUsage)> = self.usage.iter().collect();
+        recent.sort_by(|a, b| b.1.last_used.cmp(&a.1.last_used).then_with(|| a.0.cmp(b.0)));
+        recent.into_iter().take(limit).map(|(id, _)| id.as_str()).collect()
+    }
+
+    /// Last parameter values entered for a command.
+    pub fn last_params(&self, command_id: &str) -> HistoryResult<HashMap<String, String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name, value FROM command_params WHERE command_id = ?1")?;
+        let rows = stmt.query_map([command_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Remember the parameter values entered for a command.
+    pub fn save_params(
+        &mut self,
+        command_id: &str,
+        values: &HashMap<String, String>,
+    ) -> HistoryResult<()> {
+        let tx = self.conn.transaction()?;
+        for (name, value) in values {
+            tx.execute(
+                "INSERT OR REPLACE INTO command_params (command_id, name, value)
+                 VALUES (?1, ?2, ?3)",
+                params![command_id, name, value],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Forget all usage and parameters.
+    pub fn clear(&mut self) -> HistoryResult<()> {
+        self.conn
+            .execute_batch("DELETE FROM command_usage; DELETE FROM command_params;")?;
+        self.usage.clear();
+        Ok(())
+    }
+}
+
+/// Current time in Unix seconds.
+pub(crate) fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 60 * 60;
+    const DAY: i64 = 24 * HOUR;
+
+    #[test]
+    fn test_frecency_weights() {
+        let usage = CommandUsage { count: 4, last_used: 1000 };
+        assert_eq!(usage.frecency(1000), 16.0);
+        assert_eq!(usage.frecency(1000 + 2 * HOUR), 8.0);
+        assert_eq!(usage.frecency(1000 + 3 * DAY), 4.0);
+        assert_eq!(usage.frecency(1000 + 60 * DAY), 1.0);
+    }
+
+    #[test]
+    fn test_record_and_recent() {
+        let mut history = CommandHistory::in_memory().unwrap();
+        history.record_at("file.open", 100).unwrap();
+        history.record_at("file.save", 200).unwrap();
+        history.record_at("file.open", 300).unwrap();
+
+        assert_eq!(history.usage("file.open"), Some(CommandUsage { count: 2, last_used: 300 }));
+        assert_eq!(history.recent(5), vec!["file.open", "file.save"]);
+        assert_eq!(history.recent(1), vec!["file.open"]);
+        assert!(history.frecency_at("file.open", 300) > history.frecency_at("file.save", 300));
+        assert_eq!(history.frecency_at("edit.copy", 300), 0.0);
+    }
+
+    #[test]
+    fn test_persisted_across_opens() {
+        let dir = std::env::temp_dir().join(format!("palette-history-{}", std::process::id()));
+        let path = dir.join("palette.db");
+        {
+            let mut history = CommandHistory::open(&path).unwrap();
+            history.record_at("goto.line", 100).unwrap();
+            let values = HashMap::from([("line".to_string(), "42".to_string())]);
+            history.save_params("goto.line", &values).unwrap();
+        }
+
+        let history = CommandHistory::open(&path).unwrap();
+        assert_eq!(history.usage("goto.line").map(|u| u.count), Some(1));
+        assert_eq!(history.last_params("goto.line").unwrap()["line"], "42");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
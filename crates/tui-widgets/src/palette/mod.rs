@@ -21,8 +21,10 @@
 //! palette.register(OpenFileCommand);
 //! ```
 
+mod history;
 mod state;
 
+pub use history::{CommandHistory, CommandUsage, HistoryError, HistoryResult};
 pub use state::PaletteState;
 
 use crate::accessibility::{Accessible, SoundCue};
@@ -142,6 +144,12 @@ pub struct CommandPalette {
     matcher: Matcher,
     /// Callback for command execution
     on_execute: Option<Box<dyn Fn(&dyn Command, HashMap<String, String>)>>,
+    /// Usage history for frecency ranking and remembered parameters
+    history: Option<CommandHistory>,
+    /// How strongly frecency boosts fuzzy scores
+    frecency_weight: f64,
+    /// Maximum recent commands shown for an empty query
+    recent_limit: usize,
 }
 
 impl CommandPalette {
@@ -154,6 +162,9 @@ impl CommandPalette {
             max_results: 10,
             matcher: Matcher::new(Config::DEFAULT),
             on_execute: None,
+            history: None,
+            frecency_weight: 32.0,
+            recent_limit: 5,
         }
     }
 
@@ -178,6 +189,31 @@ impl CommandPalette {
         self
     }
 
+    /// Track usage in a history database.
+    ///
+    /// Use [`CommandHistory::for_app`] to persist it under the app's data directory.
+    pub fn history(mut self, history: CommandHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Set how strongly frecency boosts fuzzy scores (0 disables it).
+    pub fn frecency_weight(mut self, weight: f64) -> Self {
+        self.frecency_weight = weight;
+        self
+    }
+
+    /// Set the maximum recent commands shown for an empty query.
+    pub fn recent_limit(mut self, limit: usize) -> Self {
+        self.recent_limit = limit;
+        self
+    }
+
+    /// Get the usage history.
+    pub fn command_history(&self) -> Option<&CommandHistory> {
+        self.history.as_ref()
+    }
+
     /// Register a command.
     pub fn register(&mut self, command: impl Command + 'static) {
         self.commands.push(PaletteCommand::new(command));
@@ -188,17 +224,49 @@ impl CommandPalette {
         self.commands.push(command);
     }
 
+    /// Indices of recently used commands, newest first.
+    fn recent_indices(&self) -> Vec<usize> {
+        let Some(history) = &self.history else {
+            return Vec::new();
+        };
+        history
+            .recent(self.recent_limit)
+            .into_iter()
+            .filter_map(|id| {
+                self.commands.iter().position(|c| {
+                    c.command.id() == id && !c.command.is_hidden() && c.command.is_enabled()
+                })
+            })
+            .collect()
+    }
+
+    /// Score boost for a command's frecency.
+    fn frecency_boost(&self, index: usize, now: i64) -> u32 {
+        match &self.history {
+            Some(history) => {
+                let frecency = history.frecency_at(self.commands[index].command.id(), now);
+                (frecency.ln_1p() * self.frecency_weight) as u32
+            }
+            None => 0,
+        }
+    }
+
     /// Get filtered and ranked results.
+    ///
+    /// An empty query lists recent commands first, then the rest in
+    /// registration order. Otherwise fuzzy scores are boosted by frecency.
     pub fn get_results(&mut self, query: &str) -> Vec<(usize, u32)> {
         if query.is_empty() {
-            // Return all commands when query is empty
-            return self
+            let recent = self.recent_indices();
+            let rest = self
                 .commands
                 .iter()
                 .enumerate()
-                .filter(|(_, c)| !c.command.is_hidden() && c.command.is_enabled())
-                .map(|(i, _)| (i, 0))
-                .collect();
+                .filter(|(i, c)| {
+                    !c.command.is_hidden() && c.command.is_enabled() && !recent.contains(i)
+                })
+                .map(|(i, _)| i);
+            return recent.iter().copied().chain(rest).map(|i| (i, 0)).collect();
         }
 
         let pattern = Pattern::new(
@@ -245,6 +313,11 @@ impl CommandPalette {
             }
         }
 
+        let now = history::now();
+        for (idx, score) in &mut results {
+            *score += self.frecency_boost(*idx, now);
+        }
+
         // Sort by score descending
         results.sort_by(|a, b| b.1.cmp(&a.1));
 
@@ -320,9 +393,15 @@ impl CommandPalette {
                         if let Some(ref callback) = self.on_execute {
                             callback(cmd.command.as_ref(), params.clone());
                         }
-                        let _ = cmd.command.execute(params);
+                        self.remember(cmd_idx, &params);
+                        let _ = self.commands[cmd_idx].command.execute(params);
                     }
                     state.close();
+                } else {
+                    // Prefill the next parameter with its last value
+                    let next = &wizard.params[wizard.current_param].name;
+                    wizard.current_input =
+                        wizard.last_values.get(next).cloned().unwrap_or_default();
                 }
                 true
             }
@@ -343,26 +422,50 @@ impl CommandPalette {
         if let Some(&(cmd_idx, _)) = results.get(state.selected_index) {
             if let Some(cmd) = self.commands.get(cmd_idx) {
                 if !cmd.parameters.is_empty() {
-                    // Enter wizard mode
+                    // Enter wizard mode, prefilled with the last values used
+                    let last_values = self
+                        .history
+                        .as_ref()
+                        .and_then(|h| h.last_params(cmd.command.id()).ok())
+                        .unwrap_or_default();
+                    let current_input = last_values
+                        .get(&cmd.parameters[0].name)
+                        .cloned()
+                        .unwrap_or_default();
                     state.wizard_step = Some(WizardState {
                         command_index: cmd_idx,
                         params: cmd.parameters.clone(),
                         current_param: 0,
-                        current_input: String::new(),
+                        current_input,
                         values: HashMap::new(),
+                        last_values,
                     });
                 } else {
                     // Execute directly
                     if let Some(ref callback) = self.on_execute {
                         callback(cmd.command.as_ref(), HashMap::new());
                     }
-                    let _ = cmd.command.execute(HashMap::new());
+                    self.remember(cmd_idx, &HashMap::new());
+                    let _ = self.commands[cmd_idx].command.execute(HashMap::new());
                     state.close();
                 }
             }
         }
     }
 
+    /// Record an execution and the parameters it was given.
+    fn remember(&mut self, cmd_idx: usize, params: &HashMap<String, String>) {
+        let Some(history) = self.history.as_mut() else {
+            return;
+        };
+        let id = self.commands[cmd_idx].command.id();
+        // History is best-effort; a failed write must not block the command
+        let _ = history.record(id);
+        if !params.is_empty() {
+            let _ = history.save_params(id, params);
+        }
+    }
+
     /// Get command at index.
     pub fn get_command(&self, index: usize) -> Option<&dyn Command> {
         self.commands.get(index).map(|c| c.command.as_ref())
@@ -388,6 +491,8 @@ pub struct WizardState {
     pub current_input: String,
     /// Collected values
     pub values: HashMap<String, String>,
+    /// Values entered the last time the command ran
+    pub last_values: HashMap<String, String>,
 }
 
 impl StatefulWidget for CommandPalette {
@@ -398,9 +503,17 @@ impl StatefulWidget for CommandPalette {
             return;
         }
 
+        // Recent commands get their own section when the query is empty
+        let recent_count = if state.query.is_empty() && state.wizard_step.is_none() {
+            self.recent_indices().len().min(self.max_results)
+        } else {
+            0
+        };
+        let section_lines = if recent_count > 0 { 2 } else { 0 };
+
         // Calculate palette dimensions
         let width = (area.width * 3 / 4).min(60).max(30);
-        let height = (self.max_results + 3) as u16; // +3 for border and input
+        let height = (self.max_results + 3 + section_lines) as u16; // +3 for border and input
 
         let x = area.x + (area.width - width) / 2;
         let y = area.y + 2; // Near top
@@ -450,8 +563,22 @@ impl StatefulWidget for CommandPalette {
         let results = self.get_results(&state.query);
         let results_y = input_y + 1;
 
+        if recent_count > 0 {
+            let header_style = Style::default().fg(Color::DarkGray).add_modifier(Modifier::BOLD);
+            buf.set_string(inner.x, results_y, "Recent", header_style);
+            let all_y = results_y + 1 + recent_count as u16;
+            if results.len() > recent_count && all_y < inner.y + inner.height {
+                buf.set_string(inner.x, all_y, "All Commands", header_style);
+            }
+        }
+
         for (i, &(cmd_idx, _score)) in results.iter().take(self.max_results).enumerate() {
-            let y = results_y + i as u16;
+            let header_offset = match recent_count {
+                0 => 0,
+                n if i < n => 1,
+                _ => 2,
+            };
+            let y = results_y + i as u16 + header_offset;
             if y >= inner.y + inner.height {
                 break;
             }
@@ -645,6 +772,67 @@ mod tests {
         assert!(results.len() >= 2);
     }
 
+    fn history_palette() -> CommandPalette {
+        let mut palette = CommandPalette::new().history(CommandHistory::in_memory().unwrap());
+        let commands = [
+            ("file.open", "Open File"),
+            ("folder.open", "Open Folder"),
+            ("edit.copy", "Copy"),
+        ];
+        for (id, label) in commands {
+            palette.register(TestCommand { id: id.into(), label: label.into() });
+        }
+        palette
+    }
+
+    #[test]
+    fn test_frecency_ranking() {
+        let mut palette = history_palette();
+        assert_eq!(palette.get_results("open")[0].0, 0);
+
+        let mut state = PaletteState::new();
+        for _ in 0..3 {
+            state.open();
+            state.query = "folder".into();
+            palette.execute_selected(&mut state);
+        }
+        assert_eq!(palette.command_history().unwrap().usage("folder.open").unwrap().count, 3);
+        assert_eq!(palette.get_results("open")[0].0, 1);
+
+        // Recent commands come first for an empty query
+        let results: Vec<usize> = palette.get_results("").iter().map(|r| r.0).collect();
+        assert_eq!(results, vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn test_wizard_remembers_params() {
+        let mut palette = CommandPalette::new().history(CommandHistory::in_memory().unwrap());
+        palette.register_with_params(
+            PaletteCommand::new(TestCommand { id: "goto.line".into(), label: "Go to Line".into() })
+                .param(Parameter::new("line", "Line"))
+                .param(Parameter::new("column", "Column")),
+        );
+        let mut state = PaletteState::new();
+        let enter = KeyEvent::from(KeyCode::Enter);
+
+        state.open();
+        palette.handle_key(enter, &mut state);
+        assert_eq!(state.wizard_step().unwrap().current_input, "");
+        for c in "42".chars() {
+            palette.handle_key(KeyEvent::from(KeyCode::Char(c)), &mut state);
+        }
+        palette.handle_key(enter, &mut state);
+        palette.handle_key(KeyEvent::from(KeyCode::Char('7')), &mut state);
+        palette.handle_key(enter, &mut state);
+        assert!(!state.visible);
+
+        state.open();
+        palette.handle_key(enter, &mut state);
+        assert_eq!(state.wizard_step().unwrap().current_input, "42");
+        palette.handle_key(enter, &mut state);
+        assert_eq!(state.wizard_step().unwrap().current_input, "7");
+    }
+
     #[test]
     fn test_palette_state() {
         let mut state = PaletteState::new();
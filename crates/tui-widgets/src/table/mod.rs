@@ -30,11 +30,16 @@
 
 mod cell;
 mod column;
+mod provider;
 mod selection;
 mod state;
 
 pub use cell::CellContent;
 pub use column::{AggregateFunc, Column, ColumnWidth};
+pub use provider::{
+    AsyncDataProvider, LoadProgress, LoadTask, Page, PageFuture, PageRequest, PagedProvider,
+    ProviderError,
+};
 pub use selection::Selection;
 pub use state::TableState;
 
@@ -97,6 +102,22 @@ impl<T: Clone> DataSource<T> {
             Self::Provider(p) => p.get_row(index),
         }
     }
+
+    /// Check if a row is still loading.
+    pub fn is_loading(&self, index: usize) -> bool {
+        match self {
+            Self::Vec(_) => false,
+            Self::Provider(p) => p.is_loading(index),
+        }
+    }
+
+    /// Get the loading progress of the provider.
+    pub fn load_progress(&self) -> Option<LoadProgress> {
+        match self {
+            Self::Vec(_) => None,
+            Self::Provider(p) => p.load_progress(),
+        }
+    }
 }
 
 /// Trait for paginated/virtualized data loading.
@@ -112,6 +133,29 @@ pub trait DataProvider<T>: Send + Sync {
 
     /// Invalidate cache (called when data changes).
     fn invalidate(&mut self);
+
+    /// Start loading rows ahead of display (called with the viewport on render).
+    fn prefetch(&self, _start: usize, _count: usize) {}
+
+    /// Check if a row is still loading (rendered as a placeholder).
+    fn is_loading(&self, _index: usize) -> bool {
+        false
+    }
+
+    /// Get loading progress, for providers that load asynchronously.
+    fn load_progress(&self) -> Option<LoadProgress> {
+        None
+    }
+
+    /// Sort rows in the provider. Return false to let the table sort locally.
+    fn sort(&mut self, _column: usize, _direction: SortDirection) -> bool {
+        false
+    }
+
+    /// Filter rows in the provider. Return false to let the table filter locally.
+    fn filter(&mut self, _query: &str) -> bool {
+        false
+    }
 }
 
 /// Sortable, filterable table with virtual scrolling.
@@ -282,6 +326,19 @@ impl<T: Clone> DataTable<T> {
             return;
         }
 
+        if let DataSource::Provider(provider) = &mut self.data {
+            if provider.sort(column_index, direction) {
+                self.sorted_indices = None;
+                state.sort_column = Some(column_index);
+                state.sort_direction = direction;
+                state.scroll_offset = 0;
+                if let Some(ref callback) = self.on_sort {
+                    callback(column_index, direction);
+                }
+                return;
+            }
+        }
+
         // Build sorted indices
         let mut indices: Vec<usize> = (0..self.data.len()).collect();
 
@@ -317,6 +374,15 @@ impl<T: Clone> DataTable<T> {
 
     /// Filter rows by predicate.
     pub fn filter(&mut self, query: &str, state: &mut TableState) {
+        if let DataSource::Provider(provider) = &mut self.data {
+            if provider.filter(query) {
+                self.filtered_indices = None;
+                state.filter = (!query.is_empty()).then(|| query.to_string());
+                state.scroll_offset = 0;
+                return;
+            }
+        }
+
         if query.is_empty() {
             self.filtered_indices = None;
             state.filter = None;
@@ -586,10 +652,23 @@ impl<T: Clone> StatefulWidget for DataTable<T> {
             return;
         }
 
+        // Load the viewport (and whatever the provider prefetches around it)
+        let visible_height = (inner.height - 1) as usize; // -1 for header
+        if let DataSource::Provider(provider) = &self.data {
+            if self.sorted_indices.is_none() && self.filtered_indices.is_none() {
+                provider.prefetch(state.scroll_offset, visible_height);
+            }
+        }
+        state.loading = self.data.load_progress();
+
         let row_count = self.row_count();
         if row_count == 0 {
             // Empty state
-            let msg = "No data";
+            let msg = match &state.loading {
+                Some(progress) if progress.is_loading() => "Loading...",
+                Some(LoadProgress { error: Some(_), .. }) => "Failed to load data",
+                _ => "No data",
+            };
             let x = inner.x + (inner.width.saturating_sub(msg.len() as u16)) / 2;
             let y = inner.y + inner.height / 2;
             buf.set_string(x, y, msg, Style::default().fg(Color::DarkGray));
@@ -623,8 +702,29 @@ impl<T: Clone> StatefulWidget for DataTable<T> {
             x += width + 1; // +1 for separator
         }
 
+        // Loading indicator at the right of the header
+        if let Some(progress) = &state.loading {
+            let (text, color) = if let Some(error) = &progress.error {
+                (format!(" \u{26a0} {} ", error), Color::Red)
+            } else if progress.is_loading() {
+                let text = match progress.total_rows {
+                    Some(total) => format!(" Loading {}/{} ", progress.loaded_rows, total),
+                    None => " Loading... ".to_string(),
+                };
+                (text, Color::Yellow)
+            } else {
+                (String::new(), Color::Reset)
+            };
+            let max = inner.width.saturating_sub(1) as usize;
+            let display = truncate_with_ellipsis(&text, max);
+            let width = display.chars().count() as u16;
+            if width > 0 {
+                let x = inner.x + inner.width - 1 - width;
+                buf.set_string(x, inner.y, &display, Style::default().fg(color));
+            }
+        }
+
         // Data rows
-        let visible_height = (inner.height - 1) as usize; // -1 for header
         state.visible_rows = visible_height;
 
         // Adjust scroll if needed
@@ -645,7 +745,15 @@ impl<T: Clone> StatefulWidget for DataTable<T> {
             let y = inner.y + 1 + row_offset as u16;
             let row = match self.get_row(display_idx) {
                 Some(r) => r,
-                None => continue,
+                None => {
+                    let loading = self
+                        .data_index(display_idx)
+                        .is_some_and(|i| self.data.is_loading(i));
+                    if loading {
+                        self.render_placeholder(inner, y, &column_widths, buf);
+                    }
+                    continue;
+                }
             };
 
             // Determine row style
@@ -700,6 +808,20 @@ impl<T: Clone> StatefulWidget for DataTable<T> {
 }
 
 impl<T: Clone> DataTable<T> {
+    /// Render a row whose data is still loading.
+    fn render_placeholder(&self, inner: Rect, y: u16, column_widths: &[u16], buf: &mut Buffer) {
+        let style = Style::default().fg(Color::DarkGray);
+        let mut x = inner.x;
+        for &width in column_widths {
+            if width == 0 {
+                continue;
+            }
+            let bar = "\u{2591}".repeat((width as usize).min(3));
+            buf.set_string(x, y, &bar, style);
+            x += width + 1;
+        }
+    }
+
     fn calculate_column_widths(&self, total_width: usize) -> Vec<u16> {
         let col_count = self.columns.len();
         if col_count == 0 {
@@ -836,6 +958,96 @@ mod tests {
         assert_eq!(clipboard, "Alice\t10");
     }
 
+    /// Serves `TestRow`s and queues loads until the test runs them.
+    struct QueuedRows {
+        sortable: bool,
+    }
+
+    impl AsyncDataProvider<TestRow> for QueuedRows {
+        fn fetch(&self, request: PageRequest) -> PageFuture<TestRow> {
+            Box::pin(async move {
+                let rows = (request.start..(request.start + request.count).min(10))
+                    .map(|i| TestRow { name: format!("row{}", i), value: i as i32 })
+                    .collect();
+                Ok(Page { rows, total: 10 })
+            })
+        }
+
+        fn supports_sort(&self) -> bool {
+            self.sortable
+        }
+    }
+
+    type Queue = std::sync::Arc<std::sync::Mutex<Vec<provider::LoadTask>>>;
+
+    fn queued_provider(sortable: bool) -> (PagedProvider<TestRow>, Queue) {
+        let queue = Queue::default();
+        let sink = queue.clone();
+        let provider = PagedProvider::new(QueuedRows { sortable })
+            .page_size(2)
+            .prefetch_pages(0)
+            .with_spawner(move |future| sink.lock().unwrap().push(future));
+        (provider, queue)
+    }
+
+    fn run_queued(queue: &Queue) {
+        let futures: Vec<_> = queue.lock().unwrap().drain(..).collect();
+        for future in futures {
+            provider::block_on(future);
+        }
+    }
+
+    fn row_text(buf: &Buffer, y: u16) -> String {
+        (0..buf.area.width).map(|x| buf[(x, y)].symbol()).collect()
+    }
+
+    #[test]
+    fn test_async_provider_placeholders() {
+        let (provider, queue) = queued_provider(false);
+        let area = Rect::new(0, 0, 30, 6);
+        let mut state = TableState::default();
+
+        let mut buf = Buffer::empty(area);
+        DataTable::with_provider(test_columns(), provider.clone()).render(area, &mut buf, &mut state);
+        assert!(row_text(&buf, 3).contains("Loading..."));
+        assert!(state.loading.as_ref().unwrap().is_loading());
+
+        // Scrolling past the loaded pages shows placeholders while they load
+        run_queued(&queue);
+        state.scroll_offset = 4;
+        let mut buf = Buffer::empty(area);
+        DataTable::with_provider(test_columns(), provider.clone()).render(area, &mut buf, &mut state);
+        assert!(row_text(&buf, 0).contains("Loading 6/10"));
+        assert!(row_text(&buf, 1).contains("row4"));
+        assert!(row_text(&buf, 3).contains('\u{2591}'));
+
+        run_queued(&queue);
+        let mut buf = Buffer::empty(area);
+        DataTable::with_provider(test_columns(), provider).render(area, &mut buf, &mut state);
+        assert!(row_text(&buf, 3).contains("row6"));
+        assert!(!row_text(&buf, 0).contains("Loading"));
+    }
+
+    #[test]
+    fn test_sort_delegated_to_provider() {
+        let columns = || {
+            vec![Column::new("Name", |r: &TestRow| r.name.clone().into()).sortable(true)]
+        };
+        let mut state = TableState::default();
+
+        let (provider, _) = queued_provider(true);
+        let mut table = DataTable::with_provider(columns(), provider);
+        table.sort_by(0, SortDirection::Descending, &mut state);
+        assert!(table.sorted_indices.is_none());
+        assert_eq!(state.sort_column, Some(0));
+
+        // Providers that cannot sort fall back to sorting loaded rows locally
+        let (provider, _) = queued_provider(false);
+        let mut table = DataTable::with_provider(columns(), provider);
+        table.sort_by(0, SortDirection::Descending, &mut state);
+        assert!(table.sorted_indices.is_some());
+    }
+
     #[test]
     fn test_truncate_with_ellipsis() {
         assert_eq!(truncate_with_ellipsis("hello", 10), "hello");
@@ -0,0 +1,631 @@
+//! Async, paged data loading for DataTable.
+//!
+//! [`PagedProvider`] adapts an [`AsyncDataProvider`] to the synchronous
+//! [`DataProvider`] interface. Pages are fetched in the background, rows
+//! around the viewport are prefetched, and rows whose page is still in flight
+//! render as placeholders.
+
+use super::{DataProvider, SortDirection};
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Fetch attempts per page before a failed page is left alone.
+const MAX_ATTEMPTS: u32 = 4;
+
+/// Error returned by an async provider.
+#[derive(Debug, Clone, Error)]
+#[error("{message}")]
+pub struct ProviderError {
+    pub message: String,
+}
+
+impl ProviderError {
+    /// Create a new error.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+/// A request for a page of rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRequest {
+    /// First row index
+    pub start: usize,
+    /// Number of rows
+    pub count: usize,
+    /// Column and direction to sort by, if sorting is pushed to the provider
+    pub sort: Option<(usize, SortDirection)>,
+    /// Filter query, if filtering is pushed to the provider
+    pub filter: Option<String>,
+}
+
+/// A page of rows returned by an async provider.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    /// Rows starting at the requested index
+    pub rows: Vec<T>,
+    /// Total rows matching the request's sort and filter
+    pub total: usize,
+}
+
+/// Future returned by [`AsyncDataProvider::fetch`].
+pub type PageFuture<T> = Pin<Box<dyn Future<Output = Result<Page<T>, ProviderError>> + Send>>;
+
+/// A background load handed to the spawner.
+pub type LoadTask = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Function that drives a background load to completion.
+type Spawner = Arc<dyn Fn(LoadTask) + Send + Sync>;
+
+/// Data source that loads pages asynchronously.
+pub trait AsyncDataProvider<T>: Send + Sync {
+    /// Fetch a page of rows.
+    fn fetch(&self, request: PageRequest) -> PageFuture<T>;
+
+    /// Whether the provider can sort rows itself (e.g. `ORDER BY`).
+    fn supports_sort(&self) -> bool {
+        false
+    }
+
+    /// Whether the provider can filter rows itself (e.g. `WHERE`).
+    fn supports_filter(&self) -> bool {
+        false
+    }
+}
+
+/// Loading progress of an async provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadProgress {
+    /// Rows loaded so far
+    pub loaded_rows: usize,
+    /// Total rows, once the first page has arrived
+    pub total_rows: Option<usize>,
+    /// Pages currently in flight
+    pub pending_pages: usize,
+    /// Last load error
+    pub error: Option<String>,
+}
+
+impl LoadProgress {
+    /// Whether any page is in flight.
+    pub fn is_loading(&self) -> bool {
+        self.pending_pages > 0
+    }
+
+    /// Fraction of rows loaded, if the total is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total_rows {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.loaded_rows as f64 / total as f64).min(1.0)),
+            None => None,
+        }
+    }
+}
+
+/// State of one cached page.
+enum PageSlot<T> {
+    Loading,
+    Loaded(Vec<T>),
+    Failed { attempts: u32, retry_at: Instant },
+}
+
+impl<T> PageSlot<T> {
+    /// Whether a failed page may be fetched again at `now`.
+    fn retry_due(&self, now: Instant) -> bool {
+        matches!(self, PageSlot::Failed { attempts, retry_at }
+            if *attempts < MAX_ATTEMPTS && now >= *retry_at)
+    }
+}
+
+struct Cache<T> {
+    pages: HashMap<usize, PageSlot<T>>,
+    total: Option<usize>,
+    sort: Option<(usize, SortDirection)>,
+    filter: Option<String>,
+    /// Bumped when the query changes; results for older generations are dropped
+    generation: u64,
+    error: Option<String>,
+    updated: bool,
+}
+
+/// [`DataProvider`] that loads pages from an [`AsyncDataProvider`] in the background.
+///
+/// Cloning is cheap and shares the cache, so an app can keep a handle while
+/// passing a clone to each frame's `DataTable`.
+pub struct PagedProvider<T> {
+    source: Arc<dyn AsyncDataProvider<T>>,
+    cache: Arc<Mutex<Cache<T>>>,
+    spawner: Spawner,
+    page_size: usize,
+    /// Pages to prefetch on each side of the viewport
+    prefetch_pages: usize,
+    /// Maximum pages in flight
+    max_in_flight: usize,
+    /// Wait before refetching a failed page, doubled on each failure
+    retry_delay: Duration,
+}
+
+impl<T> Clone for PagedProvider<T> {
+    fn clone(&self) -> Self {
+        Self {
+            source: Arc::clone(&self.source),
+            cache: Arc::clone(&self.cache),
+            spawner: Arc::clone(&self.spawner),
+            page_size: self.page_size,
+            prefetch_pages: self.prefetch_pages,
+            max_in_flight: self.max_in_flight,
+            retry_delay: self.retry_delay,
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> PagedProvider<T> {
+    /// Create a provider that loads each page on a background thread.
+    pub fn new(source: impl AsyncDataProvider<T> + 'static) -> Self {
+        Self {
+            source: Arc::new(source),
+            cache: Arc::new(Mutex::new(Cache {
+                pages: HashMap::new(),
+                total: None,
+                sort: None,
+                filter: None,
+                generation: 0,
+                error: None,
+                updated: false,
+            })),
+            spawner: Arc::new(|future| {
+                thread::spawn(move || block_on(future));
+            }),
+            page_size: 200,
+            prefetch_pages: 1,
+            max_in_flight: 4,
+            retry_delay: Duration::from_millis(500),
+        }
+    }
+
+    /// Run loads with a custom spawner, e.g. `tokio::spawn`.
+    pub fn with_spawner(mut self, spawner: impl Fn(LoadTask) + Send + Sync + 'static) -> Self {
+        self.spawner = Arc::new(spawner);
+        self
+    }
+
+    /// Set the number of rows per page.
+    pub fn page_size(mut self, size: usize) -> Self {
+        self.page_size = size.max(1);
+        self
+    }
+
+    /// Set how many pages to prefetch on each side of the viewport.
+    pub fn prefetch_pages(mut self, pages: usize) -> Self {
+        self.prefetch_pages = pages;
+        self
+    }
+
+    /// Set the maximum number of pages loading at once.
+    pub fn max_in_flight(mut self, pages: usize) -> Self {
+        self.max_in_flight = pages.max(1);
+        self
+    }
+
+    /// Set how long to wait before refetching a failed page.
+    ///
+    /// The wait doubles after each failure; a page is given up on after
+    /// a few attempts, until the query changes.
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Check and clear whether a page arrived since the last call.
+    ///
+    /// Apps poll this to know when to redraw.
+    pub fn take_updates(&self) -> bool {
+        std::mem::take(&mut self.lock().updated)
+    }
+
+    /// Request every page overlapping `start..start + count` that is not
+    /// cached, or whose failed load is due for a retry.
+    fn request(&self, start: usize, count: usize) {
+        let first = start / self.page_size;
+        let last = (start + count.max(1) - 1) / self.page_size;
+
+        for page in first..=last {
+            let mut cache = self.lock();
+            if let Some(total) = cache.total {
+                if page * self.page_size >= total {
+                    break;
+                }
+            }
+            let attempts = match cache.pages.get(&page) {
+                None => 0,
+                Some(slot @ PageSlot::Failed { attempts, .. }) if slot.retry_due(Instant::now()) => {
+                    *attempts
+                }
+                Some(_) => continue,
+            };
+            let in_flight = cache
+                .pages
+                .values()
+                .filter(|slot| matches!(slot, PageSlot::Loading))
+                .count();
+            if in_flight >= self.max_in_flight {
+                break;
+            }
+
+            cache.pages.insert(page, PageSlot::Loading);
+            let request = PageRequest {
+                start: page * self.page_size,
+                count: self.page_size,
+                sort: cache.sort,
+                filter: cache.filter.clone(),
+            };
+            let generation = cache.generation;
+            let retry_delay = self.retry_delay;
+            drop(cache);
+
+            let future = self.source.fetch(request);
+            let cache = Arc::clone(&self.cache);
+            (self.spawner)(Box::pin(async move {
+                let result = future.await;
+                let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+                if cache.generation != generation {
+                    return;
+                }
+                match result {
+                    Ok(loaded) => {
+                        cache.total = Some(loaded.total);
+                        cache.pages.insert(page, PageSlot::Loaded(loaded.rows));
+                    }
+                    Err(e) => {
+                        cache.error = Some(e.message);
+                        let backoff = retry_delay.saturating_mul(2u32.saturating_pow(attempts));
+                        cache.pages.insert(
+                            page,
+                            PageSlot::Failed {
+                                attempts: attempts + 1,
+                                retry_at: Instant::now() + backoff,
+                            },
+                        );
+                    }
+                }
+                cache.updated = true;
+            }));
+        }
+    }
+
+    /// Drop cached pages and start a new query generation.
+    fn reset(cache: &mut Cache<T>) {
+        cache.pages.clear();
+        cache.total = None;
+        cache.error = None;
+        cache.generation += 1;
+        cache.updated = true;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cache<T>> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T: Clone + Send + 'static> DataProvider<T> for PagedProvider<T> {
+    fn total_count(&self) -> usize {
+        self.lock().total.unwrap_or(0)
+    }
+
+    fn get_row(&self, index: usize) -> Option<T> {
+        let page = index / self.page_size;
+        let cached = match self.lock().pages.get(&page) {
+            Some(PageSlot::Loaded(rows)) => return rows.get(index % self.page_size).cloned(),
+            Some(slot) => !slot.retry_due(Instant::now()),
+            None => false,
+        };
+        if !cached {
+            self.request(index, 1);
+        }
+        None
+    }
+
+    fn get_range(&self, start: usize, count: usize) -> Vec<T> {
+        self.request(start, count);
+        (start..start + count).map_while(|i| self.get_row(i)).collect()
+    }
+
+    fn invalidate(&mut self) {
+        Self::reset(&mut self.lock());
+    }
+
+    fn prefetch(&self, start: usize, count: usize) {
+        let margin = self.prefetch_pages * self.page_size;
+        let from = start.saturating_sub(margin);
+        self.request(from, start - from + count + margin);
+    }
+
+    fn is_loading(&self, index: usize) -> bool {
+        let cache = self.lock();
+        match cache.pages.get(&(index / self.page_size)) {
+            Some(PageSlot::Loading) => true,
+            Some(_) => false,
+            // Rows are unknown until the first page arrives
+            None => cache.total.is_none(),
+        }
+    }
+
+    fn load_progress(&self) -> Option<LoadProgress> {
+        let cache = self.lock();
+        let mut progress = LoadProgress {
+            total_rows: cache.total,
+            error: cache.error.clone(),
+            ..Default::default()
+        };
+        for slot in cache.pages.values() {
+            match slot {
+                PageSlot::Loading => progress.pending_pages += 1,
+                PageSlot::Loaded(rows) => progress.loaded_rows += rows.len(),
+                PageSlot::Failed { .. } => {}
+            }
+        }
+        Some(progress)
+    }
+
+    fn sort(&mut self, column: usize, direction: SortDirection) -> bool {
+        if !self.source.supports_sort() {
+            return false;
+        }
+        let mut cache = self.lock();
+        cache.sort = Some((column, direction));
+        Self::reset(&mut cache);
+        true
+    }
+
+    fn filter(&mut self, query: &str) -> bool {
+        if !self.source.supports_filter() {
+            return false;
+        }
+        let mut cache = self.lock();
+        cache.filter = (!query.is_empty()).then(|| query.to_string());
+        Self::reset(&mut cache);
+        true
+    }
+}
+
+/// Wakes a thread parked in [`block_on`].
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Run a future to completion on the current thread.
+///
+/// Used by the default spawner so the widget does not depend on a runtime.
+pub(super) fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Numbers 0..total, sortable and filterable on the "server".
+    struct Numbers {
+        total: usize,
+        fetches: Arc<AtomicUsize>,
+    }
+
+    impl AsyncDataProvider<usize> for Numbers {
+        fn fetch(&self, request: PageRequest) -> PageFuture<usize> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            let total = self.total;
+            Box::pin(async move {
+                let mut rows: Vec<usize> = (0..total).collect();
+                if let Some(filter) = &request.filter {
+                    rows.retain(|n| n.to_string().contains(filter.as_str()));
+                }
+                if let Some((_, SortDirection::Descending)) = request.sort {
+                    rows.reverse();
+                }
+                let matching = rows.len();
+                Ok(Page {
+                    rows: rows.into_iter().skip(request.start).take(request.count).collect(),
+                    total: matching,
+                })
+            })
+        }
+
+        fn supports_sort(&self) -> bool {
+            true
+        }
+
+        fn supports_filter(&self) -> bool {
+            true
+        }
+    }
+
+    fn inline(total: usize) -> (PagedProvider<usize>, Arc<AtomicUsize>) {
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let provider = PagedProvider::new(Numbers {
+            total,
+            fetches: Arc::clone(&fetches),
+        })
+        .page_size(10)
+        .with_spawner(block_on);
+        (provider, fetches)
+    }
+
+    #[test]
+    fn test_prefetch_around_viewport() {
+        let (provider, fetches) = inline(1000);
+        assert!(provider.is_loading(0));
+
+        provider.prefetch(50, 10);
+        // Page 5 plus one page on each side
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+        assert_eq!(provider.total_count(), 1000);
+        assert_eq!(provider.get_row(42), Some(42));
+        assert_eq!(provider.get_row(65), Some(65));
+        assert!(provider.take_updates());
+        assert!(!provider.take_updates());
+
+        // Cached pages are not fetched again
+        provider.prefetch(50, 10);
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+
+        let progress = provider.load_progress().unwrap();
+        assert_eq!(progress.loaded_rows, 30);
+        assert_eq!(progress.fraction(), Some(0.03));
+    }
+
+    #[test]
+    fn test_sort_and_filter_on_provider() {
+        let (mut provider, _) = inline(100);
+        provider.prefetch(0, 5);
+        assert_eq!(provider.get_row(0), Some(0));
+
+        assert!(provider.sort(0, SortDirection::Descending));
+        assert_eq!(provider.total_count(), 0);
+        provider.prefetch(0, 5);
+        assert_eq!(provider.get_row(0), Some(99));
+
+        assert!(provider.filter("7"));
+        provider.prefetch(0, 5);
+        assert_eq!(provider.total_count(), 19);
+        assert_eq!(provider.get_row(0), Some(97));
+    }
+
+    #[test]
+    fn test_background_loading() {
+        let provider = PagedProvider::new(Numbers {
+            total: 50,
+            fetches: Arc::new(AtomicUsize::new(0)),
+        })
+        .page_size(20);
+
+        provider.prefetch(0, 10);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while provider.get_row(5).is_none() {
+            assert!(Instant::now() < deadline, "page never arrived");
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        assert_eq!(provider.total_count(), 50);
+        assert!(!provider.is_loading(5));
+    }
+
+    #[test]
+    fn test_stale_results_dropped() {
+        struct Failing;
+        impl AsyncDataProvider<usize> for Failing {
+            fn fetch(&self, _request: PageRequest) -> PageFuture<usize> {
+                Box::pin(async { Err(ProviderError::new("connection lost")) })
+            }
+        }
+
+        // Queue loads without running them, then invalidate before they finish
+        let queued: Arc<Mutex<Vec<LoadTask>>> = Arc::default();
+        let sink = Arc::clone(&queued);
+        let mut provider = PagedProvider::new(Failing)
+            .with_spawner(move |future| sink.lock().unwrap().push(future));
+
+        provider.prefetch(0, 10);
+        provider.invalidate();
+        for future in queued.lock().unwrap().drain(..) {
+            block_on(future);
+        }
+        assert_eq!(provider.load_progress().unwrap().error, None);
+
+        provider.prefetch(0, 10);
+        for future in queued.lock().unwrap().drain(..) {
+            block_on(future);
+        }
+        let progress = provider.load_progress().unwrap();
+        assert_eq!(progress.error.as_deref(), Some("connection lost"));
+        assert!(!progress.is_loading());
+    }
+
+    #[test]
+    fn test_failed_page_retried() {
+        /// Fails the first fetch, then serves rows.
+        struct Flaky {
+            fetches: Arc<AtomicUsize>,
+        }
+        impl AsyncDataProvider<usize> for Flaky {
+            fn fetch(&self, request: PageRequest) -> PageFuture<usize> {
+                let first = self.fetches.fetch_add(1, Ordering::SeqCst) == 0;
+                Box::pin(async move {
+                    if first {
+                        return Err(ProviderError::new("timed out"));
+                    }
+                    Ok(Page {
+                        rows: (request.start..request.start + request.count).collect(),
+                        total: 100,
+                    })
+                })
+            }
+        }
+
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let provider = PagedProvider::new(Flaky {
+            fetches: Arc::clone(&fetches),
+        })
+        .page_size(10)
+        .prefetch_pages(0)
+        .retry_delay(Duration::ZERO)
+        .with_spawner(block_on);
+
+        provider.prefetch(0, 5);
+        assert_eq!(provider.get_row(0), None);
+        assert_eq!(provider.load_progress().unwrap().error.as_deref(), Some("timed out"));
+
+        provider.prefetch(0, 5);
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+        assert_eq!(provider.get_row(3), Some(3));
+        assert!(!provider.is_loading(3));
+    }
+
+    #[test]
+    fn test_failed_page_backoff_and_cap() {
+        struct Failing(Arc<AtomicUsize>);
+        impl AsyncDataProvider<usize> for Failing {
+            fn fetch(&self, _request: PageRequest) -> PageFuture<usize> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async { Err(ProviderError::new("down")) })
+            }
+        }
+
+        let failing = |delay| {
+            let fetches = Arc::new(AtomicUsize::new(0));
+            let provider = PagedProvider::new(Failing(Arc::clone(&fetches)))
+                .prefetch_pages(0)
+                .retry_delay(delay)
+                .with_spawner(block_on);
+            for _ in 0..10 {
+                provider.prefetch(0, 5);
+            }
+            fetches.load(Ordering::SeqCst)
+        };
+        // Not retried before the delay has passed, and only a few times after
+        assert_eq!(failing(Duration::from_secs(60)), 1);
+        assert_eq!(failing(Duration::ZERO), MAX_ATTEMPTS as usize);
+    }
+}
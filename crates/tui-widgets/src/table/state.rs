@@ -1,6 +1,6 @@
 //! State management for DataTable.
 
-use super::{Selection, SortDirection};
+use super::{LoadProgress, Selection, SortDirection};
 
 /// State for DataTable widget.
 #[derive(Debug, Clone, Default)]
@@ -17,6 +17,8 @@ pub struct TableState {
     pub scroll_offset: usize,
     /// Cell being edited (row, col)
     pub edit_cell: Option<(usize, usize)>,
+    /// Loading progress of an async provider (set during render)
+    pub loading: Option<LoadProgress>,
     /// Number of visible rows (set during render)
     pub(crate) visible_rows: usize,
     /// Anchor for range selection
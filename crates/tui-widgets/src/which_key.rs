@@ -0,0 +1,296 @@
+//! WhichKey widget - shows the keys that can follow a pending prefix.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use tui_widgets::WhichKey;
+//!
+//! // After a prefix key was passed to the manager
+//! let hints = keybinds.hints();
+//! if !hints.is_empty() {
+//!     let title = keybinds.format_sequence(&KeySequence::from_keys(keybinds.pending_keys().to_vec()));
+//!     frame.render_widget(WhichKey::new(&hints).title(title).display(keybinds.display().clone()), area);
+//! }
+//! ```
+
+use crate::accessibility::{Accessible, SoundCue};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Block, Borders, Clear, Widget};
+use tui_keybinds::{KeyDisplayConfig, KeyHint};
+use unicode_width::UnicodeWidthStr;
+
+/// Separator between a key and its description.
+const SEPARATOR: &str = " → ";
+
+/// Gap between columns.
+const COLUMN_GAP: u16 = 3;
+
+/// Popup listing the continuations of a pending key sequence.
+///
+/// Hints are drawn in columns along the bottom of the render area, sectioned
+/// by action group in the order they are given.
+pub struct WhichKey<'a> {
+    /// Hints to show, already sorted
+    hints: &'a [KeyHint],
+    /// Title, usually the pending sequence
+    title: Option<String>,
+    /// Key display format
+    display: KeyDisplayConfig,
+    /// Maximum popup height including borders
+    max_height: u16,
+}
+
+impl<'a> WhichKey<'a> {
+    /// Create a new overlay for the given hints.
+    pub fn new(hints: &'a [KeyHint]) -> Self {
+        Self {
+            hints,
+            title: None,
+            display: KeyDisplayConfig::default(),
+            max_height: 12,
+        }
+    }
+
+    /// Set the title.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Set the key display format.
+    pub fn display(mut self, display: KeyDisplayConfig) -> Self {
+        self.display = display;
+        self
+    }
+
+    /// Set the maximum height including borders.
+    pub fn max_height(mut self, height: u16) -> Self {
+        self.max_height = height;
+        self
+    }
+
+    /// Split hints into sections by group, keeping order.
+    ///
+    /// Section titles are omitted when no hint has a group.
+    fn sections(&self) -> Vec<(Option<&'a str>, Vec<&'a KeyHint>)> {
+        let grouped = self.hints.iter().any(|h| h.group.is_some());
+        let mut sections: Vec<(Option<&'a str>, Vec<&'a KeyHint>)> = Vec::new();
+
+        for hint in self.hints {
+            let name = match &hint.group {
+                Some(group) => Some(group.as_str()),
+                None if grouped => Some("Other"),
+                None => None,
+            };
+
+            match sections.last_mut() {
+                Some((last, items)) if *last == name => items.push(hint),
+                _ => sections.push((name, vec![hint])),
+            }
+        }
+
+        sections
+    }
+
+    /// Column layout for `width`: (column width, column count).
+    fn columns(&self, keys: &[String], width: u16) -> (u16, u16) {
+        let widest = self
+            .hints
+            .iter()
+            .zip(keys)
+            .map(|(hint, key)| key.width() + SEPARATOR.width() + hint.description.width())
+            .max()
+            .unwrap_or(0) as u16;
+
+        let column_width = widest.min(width).max(1);
+        let count = ((width + COLUMN_GAP) / (column_width + COLUMN_GAP)).max(1);
+        (column_width, count)
+    }
+
+    /// Height needed to show every hint with `columns` columns, excluding borders.
+    fn content_height(&self, columns: u16) -> u16 {
+        self.sections()
+            .iter()
+            .map(|(name, items)| {
+                let rows = items.len().div_ceil(columns as usize) as u16;
+                rows + u16::from(name.is_some())
+            })
+            .sum()
+    }
+}
+
+impl Widget for WhichKey<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if self.hints.is_empty() || area.width < 3 || area.height < 3 {
+            return;
+        }
+
+        let keys: Vec<String> = self
+            .hints
+            .iter()
+            .map(|h| self.display.format_binding(&h.key))
+            .collect();
+        let (column_width, column_count) = self.columns(&keys, area.width.saturating_sub(2));
+
+        let height = (self.content_height(column_count) + 2)
+            .min(self.max_height)
+            .min(area.height);
+        let popup = Rect::new(area.x, area.bottom() - height, area.width, height);
+
+        Clear.render(popup, buf);
+
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan));
+        if let Some(title) = &self.title {
+            block = block.title(format!(" {} ", title));
+        }
+        let inner = block.inner(popup);
+        block.render(popup, buf);
+
+        let key_width = keys.iter().map(|k| k.width()).max().unwrap_or(0) as u16;
+        let key_style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+        let prefix_style = Style::default().fg(Color::Magenta);
+        let header_style = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+
+        let mut y = inner.y;
+        let mut index = 0;
+        for (name, items) in self.sections() {
+            if y >= inner.bottom() {
+                break;
+            }
+
+            if let Some(name) = name {
+                buf.set_stringn(inner.x, y, name, inner.width as usize, header_style);
+                y += 1;
+            }
+
+            for row in items.chunks(column_count as usize) {
+                if y >= inner.bottom() {
+                    break;
+                }
+
+                for (col, hint) in row.iter().enumerate() {
+                    let x = inner.x + col as u16 * (column_width + COLUMN_GAP);
+                    let available = inner.right().saturating_sub(x) as usize;
+                    let key = &keys[index + col];
+
+                    // Right-align keys so the arrows line up
+                    let pad = key_width.saturating_sub(key.width() as u16).min(column_width);
+                    let (after_key, _) = buf.set_stringn(x + pad, y, key, available, key_style);
+                    let (after_sep, _) = buf.set_stringn(
+                        after_key,
+                        y,
+                        SEPARATOR,
+                        inner.right().saturating_sub(after_key) as usize,
+                        Style::default().fg(Color::DarkGray),
+                    );
+                    buf.set_stringn(
+                        after_sep,
+                        y,
+                        &hint.description,
+                        inner.right().saturating_sub(after_sep) as usize,
+                        if hint.is_prefix { prefix_style } else { Style::default() },
+                    );
+                }
+
+                index += row.len();
+                y += 1;
+            }
+        }
+    }
+}
+
+impl Accessible for WhichKey<'_> {
+    fn aria_role(&self) -> &str {
+        "menu"
+    }
+
+    fn aria_label(&self) -> String {
+        match &self.title {
+            Some(title) => format!("{} keys available after {}", self.hints.len(), title),
+            None => format!("{} keys available", self.hints.len()),
+        }
+    }
+
+    fn announce(&self, _message: &str) {
+        // Would integrate with announcement buffer
+    }
+
+    fn play_sound(&self, _sound: SoundCue) {
+        // Would integrate with sound system
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyCode;
+    use tui_keybinds::KeyBinding;
+
+    fn hint(c: char, description: &str, group: Option<&str>) -> KeyHint {
+        let hint = KeyHint::action(KeyBinding::key(KeyCode::Char(c)), description, description);
+        match group {
+            Some(group) => hint.with_group(group),
+            None => hint,
+        }
+    }
+
+    fn row(buf: &Buffer, y: u16) -> String {
+        (0..buf.area.width).map(|x| buf[(x, y)].symbol()).collect()
+    }
+
+    #[test]
+    fn test_sections() {
+        let hints = vec![
+            hint('w', "save", Some("Files")),
+            hint('o', "open", Some("Files")),
+            hint('q', "quit", None),
+        ];
+        let widget = WhichKey::new(&hints);
+        let sections = widget.sections();
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].0, Some("Files"));
+        assert_eq!(sections[0].1.len(), 2);
+        assert_eq!(sections[1].0, Some("Other"));
+
+        let ungrouped = vec![hint('q', "quit", None)];
+        assert_eq!(WhichKey::new(&ungrouped).sections()[0].0, None);
+    }
+
+    #[test]
+    fn test_render_at_bottom() {
+        let hints = vec![
+            hint('w', "save", Some("Files")),
+            KeyHint::prefix(KeyBinding::key(KeyCode::Char('g')), 2),
+        ];
+        let area = Rect::new(0, 0, 40, 10);
+        let mut buf = Buffer::empty(area);
+        WhichKey::new(&hints).title("Space").render(area, &mut buf);
+
+        // Border + Files header + entry + Other header + entry + border
+        assert!(row(&buf, 4).contains("Space"));
+        assert!(row(&buf, 5).contains("Files"));
+        assert!(row(&buf, 6).contains("W → save"));
+        assert!(row(&buf, 8).contains("G → +2 more"));
+        assert!(row(&buf, 3).trim().is_empty());
+    }
+
+    #[test]
+    fn test_columns_fill_width() {
+        let hints: Vec<KeyHint> = "abcdef".chars().map(|c| hint(c, "act", None)).collect();
+        let area = Rect::new(0, 0, 40, 10);
+        let mut buf = Buffer::empty(area);
+        WhichKey::new(&hints).render(area, &mut buf);
+
+        // Six 7-wide entries fit in four columns on a 38-wide row
+        assert!(row(&buf, 7).contains("A → act"));
+        assert!(row(&buf, 7).contains("D → act"));
+        assert!(row(&buf, 8).contains("E → act"));
+        assert!(row(&buf, 5).trim().is_empty());
+    }
+}